@@ -0,0 +1,52 @@
+//! Compares generating chunks with reading them from a [`ChunkCache`]
+//!
+//! Run with `cargo bench --bench chunk_cache`.
+
+use obsidium::game::world::generators::{ChunkProvider, VoidWorldChunkProvider};
+use obsidium::game::world::{ChunkCache, ChunkPosition};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Radius of the square of chunks requested, like a view distance
+const RADIUS: i32 = 10;
+
+/// Number of times every chunk in the square is requested
+const ROUNDS: u32 = 20;
+
+/// Request every chunk around the origin `ROUNDS` times
+fn request_chunks(mut provide: impl FnMut(i32, i32)) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        for x in -RADIUS..=RADIUS {
+            for z in -RADIUS..=RADIUS {
+                provide(x, z);
+            }
+        }
+    }
+    start.elapsed()
+}
+
+fn main() {
+    let requests = ROUNDS * (2 * RADIUS as u32 + 1).pow(2);
+
+    let provider = VoidWorldChunkProvider::new();
+    let uncached = request_chunks(|x, z| {
+        black_box(provider.provide_chunk(ChunkPosition::new(x, z)));
+    });
+
+    let cache = ChunkCache::new(Box::new(VoidWorldChunkProvider::new()), 1024);
+    let cached = request_chunks(|x, z| {
+        black_box(cache.get_chunk(x, z));
+    });
+
+    println!(
+        "uncached: {:?} per chunk ({} requests)",
+        uncached / requests,
+        requests
+    );
+    println!(
+        "cached:   {:?} per chunk ({} requests)",
+        cached / requests,
+        requests
+    );
+}
@@ -0,0 +1,56 @@
+//! Compares encoding the registry entries for every player with sharing
+//! the data [`GameData`] encodes once
+//!
+//! Run with `cargo bench --bench registry_packets`.
+
+use obsidium::data::GameData;
+use obsidium::protocol::packets::Packet;
+use obsidium::protocol::packets::configuration::{RegistryDataPacket, RegistryEntry};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Number of players the registries are sent to
+const PLAYERS: u32 = 200;
+
+/// Build and write the registry packets once for every player
+fn send_to_players(mut packets: impl FnMut() -> Vec<RegistryDataPacket>) -> Duration {
+    let start = Instant::now();
+    for _ in 0..PLAYERS {
+        let mut buffer = Vec::new();
+        for packet in packets() {
+            packet.write(&mut buffer).unwrap();
+        }
+        black_box(buffer);
+    }
+    start.elapsed()
+}
+
+fn main() {
+    let data = GameData::load().unwrap();
+
+    // Every player's packets encode each entry's NBT again
+    let reencoded = send_to_players(|| {
+        data.registry_ids()
+            .map(|registry_id| {
+                let entries = data
+                    .get_registry_entries(&registry_id.to_string())
+                    .unwrap()
+                    .iter()
+                    .map(|entry| {
+                        RegistryEntry::from_nbt(entry.identifier.clone(), Some(&entry.data))
+                            .unwrap()
+                    })
+                    .collect();
+                RegistryDataPacket {
+                    registry_id: registry_id.clone(),
+                    entries,
+                }
+            })
+            .collect()
+    });
+
+    let shared = send_to_players(|| data.registry_packets().unwrap());
+
+    println!("re-encoded: {:?} per player", reencoded / PLAYERS);
+    println!("shared:     {:?} per player", shared / PLAYERS);
+}
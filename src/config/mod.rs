@@ -7,4 +7,4 @@ pub mod properties;
 pub mod server;
 
 pub use properties::ServerProperties;
-pub use server::ServerConfig;
+pub use server::{LevelType, ResourcePack, ServerConfig, TransferBackend};
@@ -328,6 +328,16 @@ impl ServerProperties {
         self.set("view-distance", distance);
     }
 
+    /// Get the permission level given to new operators
+    pub fn op_permission_level(&self) -> u8 {
+        self.get("op-permission-level").unwrap_or(4)
+    }
+
+    /// Set the permission level given to new operators
+    pub fn set_op_permission_level(&mut self, level: u8) {
+        self.set("op-permission-level", level);
+    }
+
     /// Get the simulation distance
     pub fn simulation_distance(&self) -> u8 {
         self.get("simulation-distance").unwrap_or(10)
@@ -350,6 +360,13 @@ impl ServerProperties {
         self.set("level-name", name);
     }
 
+    /// Get the level type (e.g. "minecraft:flat")
+    pub fn level_type(&self) -> &str {
+        self.get_string("level-type")
+            .map(|s| s.as_str())
+            .unwrap_or("minecraft:normal")
+    }
+
     /// Get the level seed
     pub fn level_seed(&self) -> Option<&String> {
         let seed = self.get_string("level-seed")?;
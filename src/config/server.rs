@@ -3,15 +3,98 @@
 //! This module defines the main server configuration structure and
 //! provides sensible defaults for all server settings.
 
+use std::fmt;
 use std::net::SocketAddr;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
+use serde::{Deserialize, Deserializer};
+
 use crate::config::properties::ServerProperties;
 use crate::error::ServerError;
+use crate::protocol::PROTOCOL_VERSION;
+use crate::protocol::types::{McIdentifier, McUuid, Position};
+use crate::server::bans::BAN_LIST_FILE;
+use crate::server::ops::{MAX_PERMISSION_LEVEL, OP_LIST_FILE};
+use crate::server::status::FAVICON_FILE;
+use crate::server::whitelist::WHITELIST_FILE;
+use crate::{query, rcon};
+
+/// Default file the packet log is written to
+pub const PACKET_LOG_FILE: &str = "packets.jsonl";
 
 /// Main server configuration
-#[derive(Debug, Clone)]
+///
+/// The configuration can be loaded from a TOML file with
+/// [`ServerConfig::load`]. Every key is optional and falls back to its
+/// default:
+///
+/// ```toml
+/// bind_address = "0.0.0.0:25565"
+/// max_players = 20
+/// motd = "A Minecraft Server"
+/// online_mode = true
+/// # Feature flags sent to clients, including experimental content
+/// enabled_features = ["minecraft:vanilla"]
+/// # Protocol versions clients may log in with
+/// accepted_protocol_versions = [771]
+/// # Cookies requested from every client logging in
+/// login_cookies = ["lobby:session"]
+/// # Packets of at least this many bytes are compressed; negative disables it
+/// compression_threshold = 256
+/// # Timeouts in seconds
+/// connection_timeout = 30
+/// teleport_confirm_timeout = 30
+/// keep_alive_interval = 15
+/// keep_alive_timeout = 30
+/// view_distance = 10
+/// simulation_distance = 10
+/// # Maximum number of generated chunks kept in memory
+/// chunk_cache_size = 1024
+/// # "void" or "flat"
+/// level_type = "flat"
+/// favicon = "server-icon.png"
+/// spawn_position = { x = 0, y = 64, z = 0 }
+/// # The world and its players are only saved when this is set
+/// world_directory = "world"
+/// # Bans are only kept across restarts when this is set
+/// ban_list_file = "banned-players.json"
+/// # Only players in the whitelist file can join when this is set
+/// white_list = false
+/// whitelist_file = "whitelist.json"
+/// # Operators get this permission level, from 1 to 4, when /op is used
+/// op_permission_level = 4
+/// op_list_file = "ops.json"
+/// # Prometheus metrics are served on /metrics when this is set
+/// metrics_address = "127.0.0.1:9225"
+/// # The remote console listens on this port, on the same address as the
+/// # game, when it is set; clients log in with the password
+/// rcon_port = 25575
+/// rcon_password = "change me"
+/// # Monitoring tools can query the server over UDP on this port when it
+/// # is set, on the same address as the game
+/// query_port = 25565
+/// # Every packet is recorded as a JSON line when this is set; the log is
+/// # moved to packets.jsonl.1 once it reaches log_max_mb megabytes
+/// log_packets = false
+/// packet_log_file = "packets.jsonl"
+/// log_max_mb = 64
+///
+/// # Sent during configuration; players must accept forced packs to join
+/// [[resource_packs]]
+/// id = "8f1a2b3c-4d5e-4f60-8172-839405a6b7c8"
+/// url = "https://example.com/pack.zip"
+/// hash = "2fd4e1c67a2d28fced849ee1bb76e7391b93eb12"
+/// forced = true
+/// prompt = "This server requires its resource pack"
+///
+/// # Players transferred here are sent on to these servers in turn
+/// [[transfer_backends]]
+/// host = "lobby.example.com"
+/// port = 25565
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
 pub struct ServerConfig {
     /// Server bind address
     pub bind_address: SocketAddr,
@@ -25,19 +108,169 @@ pub struct ServerConfig {
     /// Online mode (authentication with Mojang)
     pub online_mode: bool,
 
+    /// Protocol versions clients may log in with
+    pub accepted_protocol_versions: Vec<i32>,
+
+    /// Feature flags enabled on the server
+    pub enabled_features: Vec<McIdentifier>,
+
+    /// Cookies requested from every client logging in
+    pub login_cookies: Vec<McIdentifier>,
+
     /// Compression threshold in bytes
+    #[serde(deserialize_with = "deserialize_compression_threshold")]
     pub compression_threshold: Option<u32>,
 
     /// Connection timeout
+    #[serde(deserialize_with = "deserialize_seconds")]
     pub connection_timeout: Duration,
 
+    /// How long to wait for a client to confirm a teleport
+    #[serde(deserialize_with = "deserialize_seconds")]
+    pub teleport_confirm_timeout: Duration,
+
+    /// Interval between keep alives sent to players
+    #[serde(deserialize_with = "deserialize_seconds")]
+    pub keep_alive_interval: Duration,
+
+    /// How long a player may take to answer a keep alive
+    #[serde(deserialize_with = "deserialize_seconds")]
+    pub keep_alive_timeout: Duration,
+
     /// View distance in chunks
     pub view_distance: u8,
     /// Simulation distance in chunks  
     pub simulation_distance: u8,
+    /// Maximum number of generated chunks kept in memory
+    pub chunk_cache_size: usize,
+    /// How chunks are generated
+    pub level_type: LevelType,
 
     /// Server favicon (path to 64x64 PNG file or base64 data URL)
+    ///
+    /// Servers configured from `server.properties` use `server-icon.png`,
+    /// like vanilla, and show no icon when that file is missing.
     pub favicon: Option<String>,
+
+    /// Position new players spawn at
+    pub spawn_position: Position,
+
+    /// Directory the world is saved to, holding `level.dat` and the data
+    /// of players that have left
+    pub world_directory: Option<PathBuf>,
+
+    /// File the ban list is saved to, in the format of vanilla's
+    /// `banned-players.json`
+    pub ban_list_file: Option<PathBuf>,
+
+    /// Whether only whitelisted players can join
+    pub white_list: bool,
+
+    /// File the whitelist is saved to, in the format of vanilla's
+    /// `whitelist.json`
+    pub whitelist_file: Option<PathBuf>,
+
+    /// Permission level given to players made operators with `/op`
+    pub op_permission_level: u8,
+
+    /// File the operators are saved to, in the format of vanilla's
+    /// `ops.json`
+    pub op_list_file: Option<PathBuf>,
+
+    /// Address serving metrics in the Prometheus text format
+    pub metrics_address: Option<SocketAddr>,
+
+    /// Port the remote console listens on, or `None` to not start it
+    pub rcon_port: Option<u16>,
+
+    /// Password RCON clients log in with
+    pub rcon_password: String,
+
+    /// UDP port answering queries, or `None` to not answer them
+    pub query_port: Option<u16>,
+
+    /// Whether every packet is recorded in the packet log
+    pub log_packets: bool,
+
+    /// File the packet log is written to
+    pub packet_log_file: PathBuf,
+
+    /// Size in megabytes the packet log reaches before it is rotated, or 0
+    /// for no limit
+    pub log_max_mb: u64,
+
+    /// Resource packs offered to players while they join
+    pub resource_packs: Vec<ResourcePack>,
+
+    /// Servers players transferred here are sent on to
+    pub transfer_backends: Vec<TransferBackend>,
+}
+
+/// How the world's chunks are generated
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LevelType {
+    /// Nothing but air
+    #[default]
+    Void,
+    /// Layers of bedrock, dirt and grass
+    Flat,
+}
+
+impl LevelType {
+    /// Parse the vanilla `level-type` property, with or without the
+    /// `minecraft:` namespace
+    ///
+    /// World types that cannot be generated yet fall back to the void.
+    pub fn from_property(value: &str) -> Self {
+        match value.strip_prefix("minecraft:").unwrap_or(value) {
+            "flat" => LevelType::Flat,
+            _ => LevelType::Void,
+        }
+    }
+}
+
+/// A resource pack offered to players
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ResourcePack {
+    /// Pack ID, which lets clients cache the pack
+    pub id: McUuid,
+    /// Download URL
+    pub url: String,
+    /// SHA-1 hash of the pack as hex, or empty to skip the check
+    #[serde(default)]
+    pub hash: String,
+    /// Whether players must accept the pack to join
+    #[serde(default)]
+    pub forced: bool,
+    /// Message shown in the prompt
+    #[serde(default)]
+    pub prompt: Option<String>,
+}
+
+/// A server players can be transferred to
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct TransferBackend {
+    /// Host name or IP address
+    pub host: String,
+    /// Port
+    pub port: u16,
+}
+
+impl TransferBackend {
+    /// Create a backend from its host and port
+    pub fn new(host: impl Into<String>, port: u16) -> Self {
+        Self {
+            host: host.into(),
+            port,
+        }
+    }
+}
+
+impl fmt::Display for TransferBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.host, self.port)
+    }
 }
 
 impl Default for ServerConfig {
@@ -50,9 +283,33 @@ impl Default for ServerConfig {
             online_mode: true,
             compression_threshold: Some(256),
             connection_timeout: Duration::from_secs(30),
+            teleport_confirm_timeout: Duration::from_secs(30),
+            keep_alive_interval: Duration::from_secs(15),
+            keep_alive_timeout: Duration::from_secs(30),
             view_distance: 12,
             simulation_distance: 12,
+            chunk_cache_size: 1024,
+            level_type: LevelType::Void,
             favicon: None,
+            spawn_position: Position::new(0, 64, 0),
+            world_directory: None,
+            ban_list_file: None,
+            white_list: false,
+            whitelist_file: None,
+            op_permission_level: MAX_PERMISSION_LEVEL,
+            op_list_file: None,
+            metrics_address: None,
+            rcon_port: None,
+            rcon_password: String::new(),
+            query_port: None,
+            log_packets: false,
+            packet_log_file: PathBuf::from(PACKET_LOG_FILE),
+            log_max_mb: 64,
+            accepted_protocol_versions: vec![PROTOCOL_VERSION],
+            login_cookies: Vec::new(),
+            enabled_features: vec![McIdentifier::minecraft("vanilla")],
+            resource_packs: Vec::new(),
+            transfer_backends: Vec::new(),
         }
     }
 }
@@ -63,6 +320,18 @@ impl ServerConfig {
         Self::default()
     }
 
+    /// Load configuration from a TOML file
+    pub fn load(path: &Path) -> Result<Self, ServerError> {
+        let contents = std::fs::read_to_string(path)?;
+        Self::from_toml(&contents)
+    }
+
+    /// Parse configuration from a TOML string
+    pub fn from_toml(contents: &str) -> Result<Self, ServerError> {
+        toml::from_str(contents)
+            .map_err(|e| ServerError::Protocol(format!("Invalid configuration file: {}", e)))
+    }
+
     /// Load configuration from server.properties file
     pub fn from_properties_file<P: AsRef<Path>>(path: P) -> Result<Self, ServerError> {
         let props = ServerProperties::load_from_file(path)?;
@@ -103,9 +372,42 @@ impl ServerConfig {
             online_mode: props.online_mode(),
             compression_threshold,
             connection_timeout: Duration::from_secs(30),
+            teleport_confirm_timeout: Duration::from_secs(30),
+            keep_alive_interval: Duration::from_secs(15),
+            keep_alive_timeout: Duration::from_secs(30),
             view_distance: props.view_distance(),
             simulation_distance: props.simulation_distance(),
-            favicon: None,
+            chunk_cache_size: 1024,
+            level_type: LevelType::from_property(props.level_type()),
+            favicon: Some(FAVICON_FILE.to_string()),
+            spawn_position: Position::new(0, 64, 0),
+            world_directory: Some(PathBuf::from(props.level_name())),
+            ban_list_file: Some(PathBuf::from(BAN_LIST_FILE)),
+            white_list: props.whitelist(),
+            whitelist_file: Some(PathBuf::from(WHITELIST_FILE)),
+            op_permission_level: props.op_permission_level().clamp(1, MAX_PERMISSION_LEVEL),
+            op_list_file: Some(PathBuf::from(OP_LIST_FILE)),
+            metrics_address: None,
+            rcon_port: props
+                .get_bool("enable-rcon")
+                .unwrap_or(false)
+                .then(|| props.get("rcon.port").unwrap_or(rcon::DEFAULT_PORT)),
+            rcon_password: props
+                .get_string("rcon.password")
+                .cloned()
+                .unwrap_or_default(),
+            query_port: props
+                .get_bool("enable-query")
+                .unwrap_or(false)
+                .then(|| props.get("query.port").unwrap_or(query::DEFAULT_PORT)),
+            log_packets: false,
+            packet_log_file: PathBuf::from(PACKET_LOG_FILE),
+            log_max_mb: 64,
+            accepted_protocol_versions: vec![PROTOCOL_VERSION],
+            login_cookies: Vec::new(),
+            enabled_features: vec![McIdentifier::minecraft("vanilla")],
+            resource_packs: resource_pack_from_properties(&props).into_iter().collect(),
+            transfer_backends: Vec::new(),
         })
     }
 
@@ -121,6 +423,8 @@ impl ServerConfig {
         props.set_online_mode(self.online_mode);
         props.set_view_distance(self.view_distance);
         props.set_simulation_distance(self.simulation_distance);
+        props.set_whitelist(self.white_list);
+        props.set_op_permission_level(self.op_permission_level);
 
         if let Some(threshold) = self.compression_threshold {
             props.set_network_compression_threshold(threshold as i32);
@@ -166,6 +470,37 @@ impl ServerConfig {
         self
     }
 
+    /// Set the teleport confirm timeout
+    pub fn with_teleport_confirm_timeout(mut self, timeout: Duration) -> Self {
+        self.teleport_confirm_timeout = timeout;
+        self
+    }
+
+    /// Set the keep alive interval and timeout
+    pub fn with_keep_alive(mut self, interval: Duration, timeout: Duration) -> Self {
+        self.keep_alive_interval = interval;
+        self.keep_alive_timeout = timeout;
+        self
+    }
+
+    /// Set the spawn position
+    pub fn with_spawn_position(mut self, position: Position) -> Self {
+        self.spawn_position = position;
+        self
+    }
+
+    /// Offer a resource pack to joining players
+    pub fn with_resource_pack(mut self, pack: ResourcePack) -> Self {
+        self.resource_packs.push(pack);
+        self
+    }
+
+    /// Add a server players transferred here are sent on to
+    pub fn with_transfer_backend(mut self, backend: TransferBackend) -> Self {
+        self.transfer_backends.push(backend);
+        self
+    }
+
     /// Set server favicon (path to PNG file or base64 data URL)
     pub fn with_favicon(mut self, favicon: Option<String>) -> Self {
         self.favicon = favicon;
@@ -183,4 +518,309 @@ impl ServerConfig {
         self.simulation_distance = distance;
         self
     }
+
+    /// Set how chunks are generated
+    pub fn with_level_type(mut self, level_type: LevelType) -> Self {
+        self.level_type = level_type;
+        self
+    }
+
+    /// Set the directory the world is saved to, or `None` to not save it
+    pub fn with_world_directory(mut self, directory: Option<PathBuf>) -> Self {
+        self.world_directory = directory;
+        self
+    }
+
+    /// Set the file the ban list is saved to, or `None` to not save it
+    pub fn with_ban_list_file(mut self, path: Option<PathBuf>) -> Self {
+        self.ban_list_file = path;
+        self
+    }
+
+    /// Set whether only whitelisted players can join
+    pub fn with_white_list(mut self, enabled: bool) -> Self {
+        self.white_list = enabled;
+        self
+    }
+
+    /// Set the file the whitelist is saved to, or `None` to not save it
+    pub fn with_whitelist_file(mut self, path: Option<PathBuf>) -> Self {
+        self.whitelist_file = path;
+        self
+    }
+
+    /// Set the permission level given to players made operators
+    pub fn with_op_permission_level(mut self, level: u8) -> Self {
+        self.op_permission_level = level;
+        self
+    }
+
+    /// Set the file the operators are saved to, or `None` to not save them
+    pub fn with_op_list_file(mut self, path: Option<PathBuf>) -> Self {
+        self.op_list_file = path;
+        self
+    }
+
+    /// Set the address metrics are served on, or `None` to not serve them
+    pub fn with_metrics_address(mut self, address: Option<SocketAddr>) -> Self {
+        self.metrics_address = address;
+        self
+    }
+
+    /// Set the port the remote console listens on, or `None` to not start it
+    pub fn with_rcon_port(mut self, port: Option<u16>) -> Self {
+        self.rcon_port = port;
+        self
+    }
+
+    /// Set the password RCON clients log in with
+    pub fn with_rcon_password(mut self, password: impl Into<String>) -> Self {
+        self.rcon_password = password.into();
+        self
+    }
+
+    /// Set the UDP port answering queries, or `None` to not answer them
+    pub fn with_query_port(mut self, port: Option<u16>) -> Self {
+        self.query_port = port;
+        self
+    }
+
+    /// Set the protocol versions clients may log in with
+    pub fn with_accepted_protocol_versions(mut self, versions: Vec<i32>) -> Self {
+        self.accepted_protocol_versions = versions;
+        self
+    }
+
+    /// Set the feature flags enabled on the server
+    pub fn with_enabled_features(mut self, features: Vec<McIdentifier>) -> Self {
+        self.enabled_features = features;
+        self
+    }
+
+    /// Add a cookie requested from every client logging in
+    pub fn with_login_cookie(mut self, key: McIdentifier) -> Self {
+        self.login_cookies.push(key);
+        self
+    }
+
+    /// Set whether every packet is recorded in the packet log
+    pub fn with_log_packets(mut self, log_packets: bool) -> Self {
+        self.log_packets = log_packets;
+        self
+    }
+
+    /// Set the file the packet log is written to
+    pub fn with_packet_log_file(mut self, path: PathBuf) -> Self {
+        self.packet_log_file = path;
+        self
+    }
+
+    /// Set the size in megabytes the packet log is rotated at, or 0 for no
+    /// limit
+    pub fn with_log_max_mb(mut self, max_mb: u64) -> Self {
+        self.log_max_mb = max_mb;
+        self
+    }
+
+    /// Set the maximum number of cached chunks
+    pub fn with_chunk_cache_size(mut self, size: usize) -> Self {
+        self.chunk_cache_size = size;
+        self
+    }
+}
+
+/// Read the vanilla `resource-pack` properties
+fn resource_pack_from_properties(props: &ServerProperties) -> Option<ResourcePack> {
+    let url = props
+        .get_string("resource-pack")
+        .filter(|url| !url.is_empty())?;
+    let id = props
+        .get::<McUuid>("resource-pack-id")
+        .unwrap_or_else(McUuid::new_v4);
+    Some(ResourcePack {
+        id,
+        url: url.clone(),
+        hash: props
+            .get_string("resource-pack-sha1")
+            .cloned()
+            .unwrap_or_default(),
+        forced: props.get_bool("require-resource-pack").unwrap_or(false),
+        prompt: props
+            .get_string("resource-pack-prompt")
+            .filter(|prompt| !prompt.is_empty())
+            .cloned(),
+    })
+}
+
+/// Deserialize a duration given in whole seconds
+fn deserialize_seconds<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+    u64::deserialize(deserializer).map(Duration::from_secs)
+}
+
+/// Deserialize a compression threshold, where a negative value disables compression
+fn deserialize_compression_threshold<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<u32>, D::Error> {
+    let threshold = i64::deserialize(deserializer)?;
+    if threshold < 0 {
+        return Ok(None);
+    }
+    u32::try_from(threshold)
+        .map(Some)
+        .map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_toml() {
+        let config = ServerConfig::from_toml(
+            r#"
+            bind_address = "127.0.0.1:25566"
+            motd = "Test server"
+            compression_threshold = -1
+            teleport_confirm_timeout = 5
+            spawn_position = { x = 8, y = 100, z = -8 }
+            level_type = "flat"
+            metrics_address = "127.0.0.1:9225"
+            rcon_port = 25576
+            rcon_password = "hunter2"
+            query_port = 25567
+            log_packets = true
+            log_max_mb = 8
+            accepted_protocol_versions = [770, 771]
+            login_cookies = ["lobby:session"]
+            enabled_features = ["minecraft:vanilla", "minecraft:trade_rebalance"]
+
+            [[transfer_backends]]
+            host = "lobby.example.com"
+            port = 25566
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.bind_address, "127.0.0.1:25566".parse().unwrap());
+        assert_eq!(config.motd, "Test server");
+        assert_eq!(config.compression_threshold, None);
+        assert_eq!(config.teleport_confirm_timeout, Duration::from_secs(5));
+        assert_eq!(config.spawn_position, Position::new(8, 100, -8));
+        assert_eq!(config.level_type, LevelType::Flat);
+        assert_eq!(
+            config.metrics_address,
+            Some("127.0.0.1:9225".parse().unwrap())
+        );
+        assert_eq!(config.rcon_port, Some(25576));
+        assert_eq!(config.rcon_password, "hunter2");
+        assert_eq!(config.query_port, Some(25567));
+
+        assert!(config.log_packets);
+        assert_eq!(config.log_max_mb, 8);
+        assert_eq!(config.accepted_protocol_versions, [770, 771]);
+        assert_eq!(
+            config.enabled_features,
+            [
+                McIdentifier::minecraft("vanilla"),
+                McIdentifier::minecraft("trade_rebalance")
+            ]
+        );
+        assert_eq!(
+            config.login_cookies,
+            [McIdentifier::new("lobby", "session").unwrap()]
+        );
+        assert_eq!(
+            config.transfer_backends,
+            [TransferBackend::new("lobby.example.com", 25566)]
+        );
+
+        // Missing keys keep their defaults
+        let defaults = ServerConfig::default();
+        assert_eq!(config.packet_log_file, defaults.packet_log_file);
+        assert_eq!(config.max_players, defaults.max_players);
+        assert_eq!(config.connection_timeout, defaults.connection_timeout);
+    }
+
+    #[test]
+    fn test_resource_packs_from_toml() {
+        let config = ServerConfig::from_toml(
+            r#"
+            [[resource_packs]]
+            id = "8f1a2b3c-4d5e-4f60-8172-839405a6b7c8"
+            url = "https://example.com/pack.zip"
+            forced = true
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.resource_packs.len(), 1);
+        let pack = &config.resource_packs[0];
+        assert_eq!(pack.url, "https://example.com/pack.zip");
+        assert!(pack.forced);
+        assert_eq!(pack.hash, "");
+        assert_eq!(pack.prompt, None);
+    }
+
+    #[test]
+    fn test_resource_pack_from_properties() {
+        let mut props = ServerProperties::new();
+        assert!(resource_pack_from_properties(&props).is_none());
+
+        props.set("resource-pack", "https://example.com/pack.zip");
+        props.set("require-resource-pack", "true");
+        let pack = resource_pack_from_properties(&props).unwrap();
+        assert!(pack.forced);
+        assert_eq!(pack.prompt, None);
+    }
+
+    #[test]
+    fn test_rcon_from_properties() {
+        let mut props = ServerProperties::new();
+        props.set("rcon.password", "hunter2");
+        let config = ServerConfig::from_properties(props.clone()).unwrap();
+        assert_eq!(config.rcon_port, None);
+        assert_eq!(config.rcon_password, "hunter2");
+
+        props.set("enable-rcon", "true");
+        let config = ServerConfig::from_properties(props).unwrap();
+        assert_eq!(config.rcon_port, Some(rcon::DEFAULT_PORT));
+    }
+
+    #[test]
+    fn test_query_from_properties() {
+        let mut props = ServerProperties::new();
+        let config = ServerConfig::from_properties(props.clone()).unwrap();
+        assert_eq!(config.query_port, None);
+
+        props.set("enable-query", "true");
+        props.set("query.port", 25570);
+        let config = ServerConfig::from_properties(props).unwrap();
+        assert_eq!(config.query_port, Some(25570));
+    }
+
+    #[test]
+    fn test_level_type_from_property() {
+        assert_eq!(LevelType::from_property("minecraft:flat"), LevelType::Flat);
+        assert_eq!(LevelType::from_property("flat"), LevelType::Flat);
+        assert_eq!(
+            LevelType::from_property("minecraft:normal"),
+            LevelType::Void
+        );
+    }
+
+    #[test]
+    fn test_from_toml_invalid() {
+        assert!(ServerConfig::from_toml("max_players = \"many\"").is_err());
+        assert!(ServerConfig::from_toml("enabled_features = [\"Vanilla!\"]").is_err());
+        assert!(ServerConfig::from_toml("bind_address = \"not an address\"").is_err());
+    }
+
+    #[test]
+    fn test_empty_toml_is_default() {
+        let config = ServerConfig::from_toml("").unwrap();
+        let defaults = ServerConfig::default();
+        assert_eq!(config.bind_address, defaults.bind_address);
+        assert_eq!(config.compression_threshold, defaults.compression_threshold);
+        assert_eq!(config.spawn_position, defaults.spawn_position);
+    }
 }
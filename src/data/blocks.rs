@@ -0,0 +1,136 @@
+//! Block registry
+//!
+//! Maps block identifiers to their numeric IDs in the `minecraft:block`
+//! registry. These are block IDs, not the block state IDs used in chunks.
+
+use super::ProtocolIds;
+use crate::error::{Result, ServerError};
+use std::collections::{BTreeMap, HashMap};
+
+/// Block IDs bundled with the server, in the same format as `items.json`
+const BLOCKS_JSON: &str = include_str!("blocks.json");
+
+/// Default block state ID of each bundled block, in the same format
+const BLOCK_STATES_JSON: &str = include_str!("block_states.json");
+
+/// Properties of the default state of each bundled block that has any
+const BLOCK_PROPERTIES_JSON: &str = include_str!("block_properties.json");
+
+/// Properties of a block state, by name
+pub type BlockProperties = BTreeMap<String, String>;
+
+/// Numeric protocol IDs of blocks
+#[derive(Debug, Clone, Default)]
+pub struct BlockRegistry {
+    /// Block identifiers and IDs
+    ids: ProtocolIds,
+    /// Block identifiers and the IDs of their default states
+    default_states: ProtocolIds,
+    /// Properties of the default states of blocks that have any
+    default_properties: HashMap<String, BlockProperties>,
+}
+
+impl BlockRegistry {
+    /// Load the block IDs bundled with the server
+    pub fn load() -> Result<Self> {
+        Self::from_json(BLOCKS_JSON)?
+            .with_default_states(BLOCK_STATES_JSON)?
+            .with_default_properties(BLOCK_PROPERTIES_JSON)
+    }
+
+    /// Load block IDs from a JSON object mapping identifiers to IDs
+    pub fn from_json(json_str: &str) -> Result<Self> {
+        Ok(Self {
+            ids: ProtocolIds::from_json(json_str, "block")?,
+            default_states: ProtocolIds::default(),
+            default_properties: HashMap::new(),
+        })
+    }
+
+    /// Add default block states from a JSON object mapping block
+    /// identifiers to state IDs
+    pub fn with_default_states(mut self, json_str: &str) -> Result<Self> {
+        self.default_states = ProtocolIds::from_json(json_str, "block state")?;
+        Ok(self)
+    }
+
+    /// Add the properties of default block states from a JSON object
+    /// mapping block identifiers to objects of property values
+    pub fn with_default_properties(mut self, json_str: &str) -> Result<Self> {
+        self.default_properties = serde_json::from_str(json_str)
+            .map_err(|e| ServerError::Protocol(format!("Invalid block property JSON: {}", e)))?;
+        Ok(self)
+    }
+
+    /// Get the protocol ID of a block (e.g. "minecraft:stone")
+    pub fn id_of(&self, name: &str) -> Option<i32> {
+        self.ids.id_of(name)
+    }
+
+    /// Get the identifier of a block from its protocol ID
+    pub fn name_of(&self, id: i32) -> Option<&str> {
+        self.ids.name_of(id)
+    }
+
+    /// Get the ID of the state a block is placed in by default
+    pub fn default_state_of(&self, name: &str) -> Option<i32> {
+        self.default_states.id_of(name)
+    }
+
+    /// Get the block a state ID is the default state of
+    pub fn block_of_default_state(&self, state: i32) -> Option<&str> {
+        self.default_states.name_of(state)
+    }
+
+    /// Get the properties of a block's default state, which are empty for
+    /// blocks without any
+    pub fn default_properties_of(&self, name: &str) -> Option<&BlockProperties> {
+        self.default_properties.get(name)
+    }
+
+    /// Number of known blocks
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Whether no blocks are known
+    pub fn is_empty(&self) -> bool {
+        self.ids.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_blocks() {
+        let blocks = BlockRegistry::load().unwrap();
+        assert_eq!(blocks.id_of("minecraft:grass_block"), Some(8));
+        assert_eq!(blocks.name_of(9), Some("minecraft:dirt"));
+        assert!(!blocks.is_empty());
+
+        // Grass has a snowy state before its default one
+        assert_eq!(blocks.default_state_of("minecraft:grass_block"), Some(9));
+        assert_eq!(blocks.default_state_of("minecraft:stone"), Some(1));
+        assert_eq!(blocks.default_state_of("minecraft:not_a_block"), None);
+        assert_eq!(blocks.block_of_default_state(85), Some("minecraft:bedrock"));
+        assert_eq!(blocks.block_of_default_state(8), None);
+    }
+
+    #[test]
+    fn test_default_properties() {
+        let blocks = BlockRegistry::load().unwrap();
+        let grass = blocks
+            .default_properties_of("minecraft:grass_block")
+            .unwrap();
+        assert_eq!(grass.get("snowy").map(String::as_str), Some("false"));
+        assert_eq!(blocks.default_properties_of("minecraft:stone"), None);
+        assert!(
+            BlockRegistry::load()
+                .unwrap()
+                .with_default_properties("[]")
+                .is_err()
+        );
+    }
+}
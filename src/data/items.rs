@@ -0,0 +1,75 @@
+//! Item registry
+//!
+//! Maps item identifiers to the numeric IDs used in inventory packets.
+
+use super::ProtocolIds;
+use crate::error::Result;
+
+/// Item IDs bundled with the server
+///
+/// Maps each item identifier to its protocol ID, in the format of the
+/// `minecraft:item` entries of the vanilla data generator's registries report.
+const ITEMS_JSON: &str = include_str!("items.json");
+
+/// Numeric protocol IDs of items
+#[derive(Debug, Clone, Default)]
+pub struct ItemRegistry {
+    /// Item identifiers and IDs
+    ids: ProtocolIds,
+}
+
+impl ItemRegistry {
+    /// Load the item IDs bundled with the server
+    pub fn load() -> Result<Self> {
+        Self::from_json(ITEMS_JSON)
+    }
+
+    /// Load item IDs from a JSON object mapping identifiers to IDs
+    pub fn from_json(json_str: &str) -> Result<Self> {
+        Ok(Self {
+            ids: ProtocolIds::from_json(json_str, "item")?,
+        })
+    }
+
+    /// Get the protocol ID of an item (e.g. "minecraft:stone")
+    pub fn id_of(&self, name: &str) -> Option<i32> {
+        self.ids.id_of(name)
+    }
+
+    /// Get the identifier of an item from its protocol ID
+    pub fn name_of(&self, id: i32) -> Option<&str> {
+        self.ids.name_of(id)
+    }
+
+    /// Number of known items
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Whether no items are known
+    pub fn is_empty(&self) -> bool {
+        self.ids.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_items() {
+        let items = ItemRegistry::load().unwrap();
+        assert_eq!(items.id_of("minecraft:air"), Some(0));
+        assert_eq!(items.id_of("minecraft:stone"), Some(1));
+        assert_eq!(items.name_of(1), Some("minecraft:stone"));
+        assert_eq!(items.id_of("minecraft:not_an_item"), None);
+    }
+
+    #[test]
+    fn test_invalid_items() {
+        assert!(ItemRegistry::from_json("[]").is_err());
+        assert!(ItemRegistry::from_json(r#"{ "minecraft:stone": -1 }"#).is_err());
+        assert!(ItemRegistry::from_json(r#"{ "Stone": 1 }"#).is_err());
+        assert!(ItemRegistry::from_json(r#"{ "minecraft:a": 1, "minecraft:b": 1 }"#).is_err());
+    }
+}
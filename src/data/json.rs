@@ -0,0 +1,171 @@
+//! JSON to NBT conversion
+//!
+//! Registry data is stored as JSON (`registry_data.json`), but sent to the
+//! client as NBT. JSON has a single number type, so by default integers
+//! become `Int` (or `Long` if they do not fit in 32 bits), other numbers
+//! become `Double` and booleans become `Byte`.
+//!
+//! When a field must use a specific tag type, the JSON generator should
+//! write a type hint object instead of a bare value:
+//!
+//! ```json
+//! { "__type": "long", "__value": 42 }
+//! ```
+//!
+//! Supported types are `byte`, `short`, `int`, `long`, `float` and
+//! `double`. Hint objects must contain exactly these two fields.
+
+use crate::error::{Result, ServerError};
+use crate::protocol::nbt::{NbtCompound, NbtTag};
+use serde_json::{Map, Value as JsonValue};
+
+/// Field holding the tag type of a type hint object
+const TYPE_FIELD: &str = "__type";
+
+/// Field holding the value of a type hint object
+const VALUE_FIELD: &str = "__value";
+
+/// Convert a JSON value into an NBT tag
+pub fn json_to_nbt(value: &JsonValue) -> Result<NbtTag> {
+    match value {
+        JsonValue::Null => Err(ServerError::Nbt("NBT has no null value".to_string())),
+        JsonValue::Bool(value) => Ok(NbtTag::from(*value)),
+        JsonValue::Number(number) => {
+            if let Some(value) = number.as_i64() {
+                Ok(i32::try_from(value).map_or(NbtTag::Long(value), NbtTag::Int))
+            } else if let Some(value) = number.as_f64() {
+                Ok(NbtTag::Double(value))
+            } else {
+                Err(ServerError::Nbt(format!("Number out of range: {}", number)))
+            }
+        }
+        JsonValue::String(value) => Ok(NbtTag::String(value.clone())),
+        JsonValue::Array(values) => values
+            .iter()
+            .map(json_to_nbt)
+            .collect::<Result<Vec<_>>>()
+            .map(NbtTag::List),
+        JsonValue::Object(fields) => {
+            if let Some(tag) = typed_value(fields)? {
+                return Ok(tag);
+            }
+            fields
+                .iter()
+                .map(|(name, value)| Ok((name.clone(), json_to_nbt(value)?)))
+                .collect::<Result<NbtCompound>>()
+                .map(NbtTag::Compound)
+        }
+    }
+}
+
+/// Convert a type hint object, or return `None` if the object is not one
+fn typed_value(fields: &Map<String, JsonValue>) -> Result<Option<NbtTag>> {
+    let Some(tag_type) = fields.get(TYPE_FIELD) else {
+        return Ok(None);
+    };
+
+    let invalid =
+        |reason: &str| ServerError::Nbt(format!("Invalid type hint {:?}: {}", fields, reason));
+
+    if fields.len() != 2 {
+        return Err(invalid("expected exactly __type and __value"));
+    }
+    let tag_type = tag_type
+        .as_str()
+        .ok_or_else(|| invalid("__type must be a string"))?;
+    let value = fields
+        .get(VALUE_FIELD)
+        .ok_or_else(|| invalid("missing __value"))?;
+
+    let integer = || {
+        value
+            .as_i64()
+            .ok_or_else(|| invalid("__value must be an integer"))
+    };
+    let float = || {
+        value
+            .as_f64()
+            .ok_or_else(|| invalid("__value must be a number"))
+    };
+    let out_of_range = |_| invalid("__value out of range");
+
+    let tag = match tag_type {
+        "byte" => NbtTag::Byte(i8::try_from(integer()?).map_err(out_of_range)?),
+        "short" => NbtTag::Short(i16::try_from(integer()?).map_err(out_of_range)?),
+        "int" => NbtTag::Int(i32::try_from(integer()?).map_err(out_of_range)?),
+        "long" => NbtTag::Long(integer()?),
+        "float" => NbtTag::Float(float()? as f32),
+        "double" => NbtTag::Double(float()?),
+        _ => return Err(invalid("unknown type")),
+    };
+    Ok(Some(tag))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_untyped_values() {
+        assert_eq!(json_to_nbt(&json!(true)).unwrap(), NbtTag::Byte(1));
+        assert_eq!(json_to_nbt(&json!(7)).unwrap(), NbtTag::Int(7));
+        assert_eq!(
+            json_to_nbt(&json!(5_000_000_000i64)).unwrap(),
+            NbtTag::Long(5_000_000_000)
+        );
+        assert_eq!(json_to_nbt(&json!(0.5)).unwrap(), NbtTag::Double(0.5));
+        assert_eq!(json_to_nbt(&json!("a")).unwrap(), NbtTag::from("a"));
+        assert!(json_to_nbt(&json!(null)).is_err());
+    }
+
+    #[test]
+    fn test_compound_preserves_order() {
+        let tag = json_to_nbt(&json!({ "b": 1, "a": [1, 2] })).unwrap();
+        let compound = tag.as_compound().unwrap();
+        let keys: Vec<&str> = compound.iter().map(|(key, _)| key).collect();
+        assert_eq!(keys, ["b", "a"]);
+        assert_eq!(
+            compound.get("a"),
+            Some(&NbtTag::List(vec![NbtTag::Int(1), NbtTag::Int(2)]))
+        );
+    }
+
+    #[test]
+    fn test_type_hints() {
+        let hint = |tag_type: &str, value: JsonValue| {
+            json_to_nbt(&json!({ "__type": tag_type, "__value": value }))
+        };
+
+        assert_eq!(hint("byte", json!(-3)).unwrap(), NbtTag::Byte(-3));
+        assert_eq!(hint("short", json!(300)).unwrap(), NbtTag::Short(300));
+        assert_eq!(hint("int", json!(1)).unwrap(), NbtTag::Int(1));
+        assert_eq!(hint("long", json!(42)).unwrap(), NbtTag::Long(42));
+        assert_eq!(hint("float", json!(0.25)).unwrap(), NbtTag::Float(0.25));
+        assert_eq!(hint("double", json!(1)).unwrap(), NbtTag::Double(1.0));
+    }
+
+    #[test]
+    fn test_type_hints_nested() {
+        let tag = json_to_nbt(&json!({
+            "fixed_time": { "__type": "long", "__value": 6000 },
+            "names": [{ "__type": "short", "__value": 1 }]
+        }))
+        .unwrap();
+        let compound = tag.as_compound().unwrap();
+        assert_eq!(compound.get("fixed_time"), Some(&NbtTag::Long(6000)));
+        assert_eq!(
+            compound.get("names"),
+            Some(&NbtTag::List(vec![NbtTag::Short(1)]))
+        );
+    }
+
+    #[test]
+    fn test_invalid_type_hints() {
+        assert!(json_to_nbt(&json!({ "__type": "byte", "__value": 128 })).is_err());
+        assert!(json_to_nbt(&json!({ "__type": "long", "__value": 1.5 })).is_err());
+        assert!(json_to_nbt(&json!({ "__type": "uuid", "__value": 1 })).is_err());
+        assert!(json_to_nbt(&json!({ "__type": "int" })).is_err());
+        assert!(json_to_nbt(&json!({ "__type": "int", "__value": 1, "x": 2 })).is_err());
+    }
+}
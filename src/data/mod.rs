@@ -0,0 +1,513 @@
+//! Game data
+//!
+//! This module loads the vanilla data the server sends to clients, such as
+//! the synchronized registries, and converts it into protocol types.
+
+pub mod blocks;
+pub mod items;
+pub mod json;
+pub mod nbt_io;
+pub mod packs;
+pub mod sounds;
+
+pub use blocks::{BlockProperties, BlockRegistry};
+pub use items::ItemRegistry;
+pub use json::json_to_nbt;
+pub use nbt_io::{decode_nbt, encode_nbt, read_nbt_file, write_nbt_file};
+pub use packs::DataPackRegistry;
+pub use sounds::SoundRegistry;
+
+use crate::data::packs::EntryKey;
+use crate::error::{Result, ServerError};
+use crate::protocol::MINECRAFT_VERSION;
+use crate::protocol::nbt::NbtTag;
+use crate::protocol::packets::configuration::{KnownPack, RegistryDataPacket};
+use crate::protocol::types::McIdentifier;
+use serde_json::Value as JsonValue;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, OnceLock};
+
+/// Registry data bundled with the server
+///
+/// Maps each registry ID to the entries the client needs, in protocol ID
+/// order. Values use the type hint convention described in [`json`].
+const REGISTRY_DATA_JSON: &str = include_str!("registry_data.json");
+
+/// A single registry entry with its data converted to NBT
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegistryEntry {
+    /// Entry identifier (e.g. "minecraft:overworld")
+    pub identifier: McIdentifier,
+    /// Entry data
+    pub data: NbtTag,
+}
+
+/// Parse registry data JSON into typed entries
+///
+/// The JSON must be an object mapping registry IDs to objects that map entry
+/// IDs to entry data. Entry order is preserved, since the position of an
+/// entry is its numeric ID in the protocol. Registry and entry IDs must be
+/// valid identifiers.
+pub fn parse_registry_data_json(
+    json_str: &str,
+) -> Result<HashMap<McIdentifier, Vec<RegistryEntry>>> {
+    let root: JsonValue = serde_json::from_str(json_str)
+        .map_err(|e| ServerError::Protocol(format!("Invalid registry data JSON: {}", e)))?;
+
+    let registries = root
+        .as_object()
+        .ok_or_else(|| ServerError::Protocol("Registry data must be a JSON object".to_string()))?;
+
+    let mut result = HashMap::with_capacity(registries.len());
+    for (registry_id, entries) in registries {
+        let registry_id: McIdentifier = registry_id.parse()?;
+        let entries = entries.as_object().ok_or_else(|| {
+            ServerError::Protocol(format!("Registry {} must be a JSON object", registry_id))
+        })?;
+
+        let entries = entries
+            .iter()
+            .map(|(identifier, data)| {
+                let data = json_to_nbt(data).map_err(|e| {
+                    ServerError::Protocol(format!(
+                        "Invalid data for {} in {}: {}",
+                        identifier, registry_id, e
+                    ))
+                })?;
+                Ok(RegistryEntry {
+                    identifier: identifier.parse()?,
+                    data,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        result.insert(registry_id, entries);
+    }
+
+    Ok(result)
+}
+
+/// Two-way mapping between identifiers and numeric protocol IDs
+#[derive(Debug, Clone, Default)]
+struct ProtocolIds {
+    /// Protocol IDs by identifier
+    ids: HashMap<String, i32>,
+    /// Identifiers by protocol ID
+    names: HashMap<i32, String>,
+}
+
+impl ProtocolIds {
+    /// Parse a JSON object mapping identifiers to unique, non-negative IDs
+    fn from_json(json_str: &str, kind: &str) -> Result<Self> {
+        let root: HashMap<String, JsonValue> = serde_json::from_str(json_str)
+            .map_err(|e| ServerError::Protocol(format!("Invalid {} ID JSON: {}", kind, e)))?;
+
+        let mut ids = HashMap::with_capacity(root.len());
+        let mut names = HashMap::with_capacity(root.len());
+        for (name, id) in root {
+            name.parse::<McIdentifier>()?;
+            let id = id
+                .as_i64()
+                .and_then(|id| i32::try_from(id).ok())
+                .filter(|id| *id >= 0)
+                .ok_or_else(|| {
+                    ServerError::Protocol(format!("Invalid ID for {} {}: {}", kind, name, id))
+                })?;
+
+            if let Some(existing) = names.insert(id, name.clone()) {
+                return Err(ServerError::Protocol(format!(
+                    "Duplicate {} ID {} for {} and {}",
+                    kind, id, existing, name
+                )));
+            }
+            ids.insert(name, id);
+        }
+
+        Ok(Self { ids, names })
+    }
+
+    /// Get the protocol ID of an identifier
+    fn id_of(&self, name: &str) -> Option<i32> {
+        self.ids.get(name).copied()
+    }
+
+    /// Get the identifier of a protocol ID
+    fn name_of(&self, id: i32) -> Option<&str> {
+        self.names.get(&id).map(String::as_str)
+    }
+
+    /// Number of mapped identifiers
+    fn len(&self) -> usize {
+        self.ids.len()
+    }
+}
+
+/// Vanilla game data loaded at startup
+#[derive(Debug, Clone)]
+pub struct GameData {
+    /// Synchronized registries, keyed by registry ID
+    registries: HashMap<McIdentifier, Vec<RegistryEntry>>,
+    /// Item protocol IDs
+    items: ItemRegistry,
+    /// Block protocol IDs
+    blocks: BlockRegistry,
+    /// Sound event protocol IDs
+    sounds: SoundRegistry,
+    /// Data packs the registries come from
+    packs: DataPackRegistry,
+    /// Network NBT of each registry's entries, in entry order, encoded the
+    /// first time registry packets are built
+    encoded: OnceLock<HashMap<McIdentifier, Vec<Arc<[u8]>>>>,
+}
+
+impl GameData {
+    /// Load the game data bundled with the server
+    ///
+    /// The bundled registries are vanilla's, so they all belong to the
+    /// `minecraft:core` pack of the current version.
+    pub fn load() -> Result<Self> {
+        let data = Self::from_registry_json(REGISTRY_DATA_JSON)?;
+        let mut packs = DataPackRegistry::new();
+        packs.add_pack(
+            KnownPack::new("minecraft", "core", MINECRAFT_VERSION),
+            data.entry_keys(),
+        );
+        Ok(Self {
+            items: ItemRegistry::load()?,
+            blocks: BlockRegistry::load()?,
+            sounds: SoundRegistry::load()?,
+            packs,
+            ..data
+        })
+    }
+
+    /// Load game data from registry data JSON, without any items, blocks or
+    /// sounds
+    pub fn from_registry_json(json_str: &str) -> Result<Self> {
+        Ok(Self {
+            registries: parse_registry_data_json(json_str)?,
+            items: ItemRegistry::default(),
+            blocks: BlockRegistry::default(),
+            sounds: SoundRegistry::default(),
+            packs: DataPackRegistry::default(),
+            encoded: OnceLock::new(),
+        })
+    }
+
+    /// Get the item registry
+    pub fn items(&self) -> &ItemRegistry {
+        &self.items
+    }
+
+    /// Get the block registry
+    pub fn blocks(&self) -> &BlockRegistry {
+        &self.blocks
+    }
+
+    /// Get the sound event registry
+    pub fn sounds(&self) -> &SoundRegistry {
+        &self.sounds
+    }
+
+    /// Get the data packs the registries come from
+    pub fn packs(&self) -> &DataPackRegistry {
+        &self.packs
+    }
+
+    /// Every entry of every registry
+    fn entry_keys(&self) -> Vec<EntryKey> {
+        self.registries
+            .iter()
+            .flat_map(|(registry_id, entries)| {
+                entries
+                    .iter()
+                    .map(|entry| (registry_id.clone(), entry.identifier.clone()))
+            })
+            .collect()
+    }
+
+    /// Get the default block state placed by an item
+    ///
+    /// Items place the block with the same identifier, so items without a
+    /// matching block, and blocks without a known state, place nothing.
+    pub fn placed_block_state(&self, item_id: i32) -> Option<i32> {
+        let name = self.items.name_of(item_id)?;
+        self.blocks.default_state_of(name)
+    }
+
+    /// Get the entries of a registry
+    pub fn get_registry_entries(&self, registry_id: &str) -> Option<&[RegistryEntry]> {
+        let registry_id: McIdentifier = registry_id.parse().ok()?;
+        self.registries.get(&registry_id).map(Vec::as_slice)
+    }
+
+    /// Get the entries of a registry with their encoded data, sorted by
+    /// identifier
+    ///
+    /// Meant for comparing registries; packets list entries in protocol ID
+    /// order instead, since the position of an entry is its ID.
+    pub fn get_registry_entries_sorted(
+        &self,
+        registry_id: &str,
+    ) -> Result<Vec<(McIdentifier, Arc<[u8]>)>> {
+        let registry_id: McIdentifier = registry_id.parse()?;
+        let entries = self
+            .registries
+            .get(&registry_id)
+            .ok_or_else(|| ServerError::Protocol(format!("Unknown registry: {}", registry_id)))?;
+        let mut sorted: Vec<_> = entries
+            .iter()
+            .zip(&self.encoded_entries()?[&registry_id])
+            .map(|(entry, data)| (entry.identifier.clone(), Arc::clone(data)))
+            .collect();
+        sorted.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        Ok(sorted)
+    }
+
+    /// Registries sorted by ID, so they are always sent in the same order
+    fn sorted_registries(&self) -> Vec<(&McIdentifier, &Vec<RegistryEntry>)> {
+        let mut registries: Vec<_> = self.registries.iter().collect();
+        registries.sort_unstable_by_key(|(registry_id, _)| *registry_id);
+        registries
+    }
+
+    /// Get the IDs of all loaded registries
+    pub fn registry_ids(&self) -> impl Iterator<Item = &McIdentifier> {
+        self.registries.keys()
+    }
+
+    /// Get the numeric protocol ID of a registry entry
+    pub fn registry_entry_id(&self, registry_id: &str, identifier: &str) -> Option<i32> {
+        let identifier: McIdentifier = identifier.parse().ok()?;
+        self.get_registry_entries(registry_id)?
+            .iter()
+            .position(|entry| entry.identifier == identifier)
+            .map(|index| index as i32)
+    }
+
+    /// Network NBT of every registry entry, encoded on the first call
+    ///
+    /// Registries never change once loaded, so the encoded data is shared by
+    /// the packets sent to every player.
+    fn encoded_entries(&self) -> Result<&HashMap<McIdentifier, Vec<Arc<[u8]>>>> {
+        if let Some(encoded) = self.encoded.get() {
+            return Ok(encoded);
+        }
+        let encoded = self
+            .registries
+            .iter()
+            .map(|(registry_id, entries)| {
+                let entries = entries
+                    .iter()
+                    .map(|entry| entry.data.to_network_bytes().map(Arc::from))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok((registry_id.clone(), entries))
+            })
+            .collect::<Result<HashMap<_, _>>>()?;
+        Ok(self.encoded.get_or_init(|| encoded))
+    }
+
+    /// Build a Registry Data packet for every loaded registry, in order of
+    /// registry ID
+    pub fn registry_packets(&self) -> Result<Vec<RegistryDataPacket>> {
+        self.registry_packets_with_known(&HashSet::new())
+    }
+
+    /// Build a Registry Data packet for every loaded registry, leaving out
+    /// the data of entries the client already has
+    ///
+    /// Every entry is still listed, since its position is its protocol ID.
+    /// Registries are sorted by ID, so packet captures are reproducible.
+    pub fn registry_packets_with_known(
+        &self,
+        known: &HashSet<EntryKey>,
+    ) -> Result<Vec<RegistryDataPacket>> {
+        let encoded = self.encoded_entries()?;
+        Ok(self
+            .sorted_registries()
+            .into_iter()
+            .map(|(registry_id, entries)| {
+                RegistryDataPacket::new(
+                    registry_id.clone(),
+                    entries
+                        .iter()
+                        .zip(&encoded[registry_id])
+                        .map(|(entry, data)| {
+                            let key = (registry_id.clone(), entry.identifier.clone());
+                            let data = (!known.contains(&key)).then(|| Arc::clone(data));
+                            (entry.identifier.clone(), data)
+                        }),
+                )
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::packets::Packet;
+
+    #[test]
+    fn test_parse_registry_data_json() {
+        let registries = parse_registry_data_json(
+            r#"{
+                "minecraft:test": {
+                    "minecraft:b": { "value": 1 },
+                    "minecraft:a": { "value": { "__type": "long", "__value": 2 } }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let entries = &registries[&McIdentifier::minecraft("test")];
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].identifier, McIdentifier::minecraft("b"));
+        assert_eq!(
+            entries[1].data.as_compound().unwrap().get("value"),
+            Some(&NbtTag::Long(2))
+        );
+    }
+
+    #[test]
+    fn test_parse_registry_data_json_invalid() {
+        assert!(parse_registry_data_json("[]").is_err());
+        assert!(parse_registry_data_json(r#"{ "minecraft:test": [] }"#).is_err());
+        assert!(parse_registry_data_json(r#"{ "minecraft:test": { "a": null } }"#).is_err());
+        assert!(parse_registry_data_json(r#"{ "minecraft:Test": {} }"#).is_err());
+        assert!(parse_registry_data_json(r#"{ "minecraft:test": { "a b": {} } }"#).is_err());
+    }
+
+    #[test]
+    fn test_bundled_game_data() {
+        let data = GameData::load().unwrap();
+
+        // The login packet refers to the overworld as dimension type 0
+        assert_eq!(
+            data.registry_entry_id("minecraft:dimension_type", "minecraft:overworld"),
+            Some(0)
+        );
+        assert!(
+            data.get_registry_entries("minecraft:damage_type")
+                .is_some_and(|entries| entries.len() > 40)
+        );
+        assert_eq!(
+            data.registry_packets().unwrap().len(),
+            data.registry_ids().count()
+        );
+        assert_eq!(data.items().id_of("minecraft:stone"), Some(1));
+        assert_eq!(data.blocks().id_of("minecraft:stone"), Some(1));
+        assert_eq!(data.placed_block_state(1), Some(1));
+        assert!(!data.sounds().is_empty());
+    }
+
+    #[test]
+    fn test_overworld_keeps_json_field_order() {
+        let data = GameData::load().unwrap();
+        let packets = data.registry_packets().unwrap();
+        let dimension_types = packets
+            .iter()
+            .find(|packet| packet.registry_id == McIdentifier::minecraft("dimension_type"))
+            .unwrap();
+        let overworld = &dimension_types.entries[0];
+        assert_eq!(overworld.entry_id, McIdentifier::minecraft("overworld"));
+
+        // The encoded compound lists its fields in the order of the JSON
+        let root: JsonValue = serde_json::from_str(REGISTRY_DATA_JSON).unwrap();
+        let json_fields: Vec<&str> = root["minecraft:dimension_type"]["minecraft:overworld"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        let nbt = overworld.nbt().unwrap().unwrap();
+        let nbt_fields: Vec<&str> = nbt.as_compound().unwrap().iter().map(|(k, _)| k).collect();
+        assert_eq!(nbt_fields, json_fields);
+
+        let bytes = overworld.data.as_deref().unwrap();
+        let position = |name: &[u8]| bytes.windows(name.len()).position(|w| w == name);
+        assert!(position(b"min_y").unwrap() < position(b"height").unwrap());
+    }
+
+    #[test]
+    fn test_registry_order_is_deterministic() {
+        let json = r#"{
+            "minecraft:b": { "minecraft:z": {}, "minecraft:y": {} },
+            "minecraft:c": {},
+            "minecraft:a": { "minecraft:x": { "value": 1 } }
+        }"#;
+        let data = GameData::from_registry_json(json).unwrap();
+        let packets = data.registry_packets().unwrap();
+        let ids: Vec<String> = packets.iter().map(|p| p.registry_id.to_string()).collect();
+        assert_eq!(ids, ["minecraft:a", "minecraft:b", "minecraft:c"]);
+
+        // Entries keep their protocol order, but can be listed sorted
+        let entries: Vec<_> = packets[1].entries.iter().map(|e| &e.entry_id).collect();
+        assert_eq!(
+            entries,
+            [&McIdentifier::minecraft("z"), &McIdentifier::minecraft("y")]
+        );
+        let sorted = data.get_registry_entries_sorted("minecraft:b").unwrap();
+        assert_eq!(sorted[0].0, McIdentifier::minecraft("y"));
+        assert_eq!(sorted[1].0, McIdentifier::minecraft("z"));
+        assert!(data.get_registry_entries_sorted("minecraft:d").is_err());
+
+        // Loading the same data again gives the same bytes
+        let again = GameData::from_registry_json(json).unwrap();
+        let encode = |packets: Vec<RegistryDataPacket>| {
+            let mut buffer = Vec::new();
+            for packet in packets {
+                packet.write(&mut buffer).unwrap();
+            }
+            buffer
+        };
+        assert_eq!(encode(packets), encode(again.registry_packets().unwrap()));
+    }
+
+    #[test]
+    fn test_registry_data_is_encoded_once() {
+        let data = GameData::from_registry_json(
+            r#"{ "minecraft:test": { "minecraft:a": { "value": 1 } } }"#,
+        )
+        .unwrap();
+        let first = data.registry_packets().unwrap();
+        let second = data.registry_packets().unwrap();
+
+        let entry = &first[0].entries[0];
+        assert_eq!(
+            entry.nbt().unwrap().as_ref(),
+            Some(&data.get_registry_entries("minecraft:test").unwrap()[0].data)
+        );
+        // Every packet shares the same encoded data
+        let (first, second) = (&entry.data, &second[0].entries[0].data);
+        assert!(Arc::ptr_eq(
+            first.as_ref().unwrap(),
+            second.as_ref().unwrap()
+        ));
+    }
+
+    #[test]
+    fn test_known_pack_entries_have_no_data() {
+        let data = GameData::load().unwrap();
+        let packs = data.packs().packs();
+        assert_eq!(
+            packs,
+            [KnownPack::new("minecraft", "core", MINECRAFT_VERSION)]
+        );
+
+        let known = data.packs().known_entries(&packs);
+        let registries = data.registry_packets_with_known(&known).unwrap();
+        assert_eq!(registries.len(), data.registry_ids().count());
+        assert!(registries.iter().all(|registry| {
+            !registry.entries.is_empty()
+                && registry.entries.iter().all(|entry| entry.data.is_none())
+        }));
+        assert!(
+            data.registry_packets()
+                .unwrap()
+                .iter()
+                .flat_map(|registry| &registry.entries)
+                .all(|entry| entry.data.is_some())
+        );
+    }
+}
@@ -0,0 +1,146 @@
+//! Reading and writing NBT files
+//!
+//! Player data, world data and structures are stored as NBT files with a
+//! named root tag, usually gzip-compressed. Files written from network
+//! packets may instead use the network format, without a root name.
+
+use crate::error::{Result, ServerError};
+use crate::protocol::nbt::NbtTag;
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use std::fs;
+use std::io::{Cursor, Read, Write};
+use std::path::Path;
+
+/// First two bytes of every gzip stream
+const GZIP_MAGIC: [u8; 2] = [0x1F, 0x8B];
+
+/// Read the root tag of an NBT file
+///
+/// See [`decode_nbt`] for the formats that are understood.
+pub fn read_nbt_file(path: &Path) -> Result<NbtTag> {
+    decode_nbt(&fs::read(path)?)
+        .map_err(|e| ServerError::Nbt(format!("Invalid NBT file {}: {}", path.display(), e)))
+}
+
+/// Write a tag to an NBT file with an empty root name, optionally
+/// gzip-compressed
+pub fn write_nbt_file(path: &Path, value: &NbtTag, compressed: bool) -> Result<()> {
+    Ok(fs::write(path, encode_nbt(value, compressed)?)?)
+}
+
+/// Decode the contents of an NBT file
+///
+/// Gzip compression is detected from the header, and a root tag without a
+/// name is read as network NBT. The root name is discarded.
+pub fn decode_nbt(bytes: &[u8]) -> Result<NbtTag> {
+    let mut decompressed = Vec::new();
+    let bytes = if bytes.starts_with(&GZIP_MAGIC) {
+        GzDecoder::new(bytes).read_to_end(&mut decompressed)?;
+        &decompressed
+    } else {
+        bytes
+    };
+    read_root(bytes, true).or_else(|_| read_root(bytes, false))
+}
+
+/// Encode a tag as the contents of an NBT file with an empty root name,
+/// optionally gzip-compressed
+pub fn encode_nbt(value: &NbtTag, compressed: bool) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    value.write_named("", &mut bytes)?;
+    if compressed {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&bytes)?;
+        bytes = encoder.finish()?;
+    }
+    Ok(bytes)
+}
+
+/// Read a root tag, named or in network format, that must span the whole
+/// buffer
+fn read_root(bytes: &[u8], named: bool) -> Result<NbtTag> {
+    let mut cursor = Cursor::new(bytes);
+    let tag = if named {
+        NbtTag::read_named(&mut cursor)?.1
+    } else {
+        NbtTag::read_network(&mut cursor)?
+    };
+    if cursor.position() as usize != bytes.len() {
+        return Err(ServerError::Nbt(
+            "Trailing bytes after the root tag".to_string(),
+        ));
+    }
+    Ok(tag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::nbt::NbtCompound;
+    use std::path::PathBuf;
+
+    fn player_data() -> NbtTag {
+        NbtCompound::new()
+            .with("DataVersion", 4435)
+            .with("Health", 20.0f32)
+            .with("playerGameType", 0)
+            .with("Dimension", "minecraft:overworld")
+            .with(
+                "Pos",
+                NbtTag::List(vec![0.5.into(), 64.0.into(), 0.5.into()]),
+            )
+            .into()
+    }
+
+    /// A path in the temporary directory unique to one test
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("obsidium-{}-{}.nbt", std::process::id(), name))
+    }
+
+    #[test]
+    fn test_compressed_roundtrip() {
+        let path = temp_path("compressed");
+        let tag = player_data();
+        write_nbt_file(&path, &tag, true).unwrap();
+        let written = fs::read(&path).unwrap();
+        assert!(written.starts_with(&GZIP_MAGIC));
+        assert_eq!(read_nbt_file(&path).unwrap(), tag);
+
+        // Writing the tag again produces the same file
+        write_nbt_file(&path, &read_nbt_file(&path).unwrap(), true).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), written);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_uncompressed_roundtrip() {
+        let path = temp_path("uncompressed");
+        let tag = player_data();
+        write_nbt_file(&path, &tag, false).unwrap();
+        let mut expected = Vec::new();
+        tag.write_named("", &mut expected).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), expected);
+        assert_eq!(read_nbt_file(&path).unwrap(), tag);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_network_format_file() {
+        let path = temp_path("network");
+        let tag = player_data();
+        fs::write(&path, tag.to_network_bytes().unwrap()).unwrap();
+        assert_eq!(read_nbt_file(&path).unwrap(), tag);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_file() {
+        let path = temp_path("invalid");
+        fs::write(&path, [0x0A, 0x00, 0x05]).unwrap();
+        assert!(matches!(read_nbt_file(&path), Err(ServerError::Nbt(_))));
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(read_nbt_file(&path), Err(ServerError::Io(_))));
+    }
+}
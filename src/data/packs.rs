@@ -0,0 +1,70 @@
+//! Data packs
+//!
+//! Registry entries come from data packs. Clients ship with vanilla's
+//! `minecraft:core` pack, so when a client confirms it knows a pack, the
+//! entries from that pack are sent without their data and the client
+//! loads them from its own copy.
+
+use crate::protocol::packets::configuration::KnownPack;
+use crate::protocol::types::McIdentifier;
+use std::collections::HashSet;
+
+/// A registry entry, by registry ID and entry ID
+pub type EntryKey = (McIdentifier, McIdentifier);
+
+/// Maps data packs to the registry entries they provide
+#[derive(Debug, Clone, Default)]
+pub struct DataPackRegistry {
+    /// Packs in the order they are offered, with their entries
+    packs: Vec<(KnownPack, HashSet<EntryKey>)>,
+}
+
+impl DataPackRegistry {
+    /// Create a registry without any packs
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a pack and the entries it provides
+    pub fn add_pack(&mut self, pack: KnownPack, entries: impl IntoIterator<Item = EntryKey>) {
+        self.packs.push((pack, entries.into_iter().collect()));
+    }
+
+    /// Packs to offer clients
+    pub fn packs(&self) -> Vec<KnownPack> {
+        self.packs.iter().map(|(pack, _)| pack.clone()).collect()
+    }
+
+    /// Entries provided by the packs a client confirmed knowing
+    ///
+    /// Packs the server never offered are ignored.
+    pub fn known_entries(&self, confirmed: &[KnownPack]) -> HashSet<EntryKey> {
+        self.packs
+            .iter()
+            .filter(|(pack, _)| confirmed.contains(pack))
+            .flat_map(|(_, entries)| entries.iter().cloned())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_entries() {
+        let mut packs = DataPackRegistry::new();
+        let core = KnownPack::new("minecraft", "core", "1.21.6");
+        let biome = McIdentifier::minecraft("worldgen/biome");
+        packs.add_pack(core, [(biome.clone(), McIdentifier::minecraft("plains"))]);
+        assert_eq!(packs.packs().len(), 1);
+
+        let known = packs.known_entries(&packs.packs());
+        assert!(known.contains(&(biome.clone(), McIdentifier::minecraft("plains"))));
+        assert_eq!(known.len(), 1);
+
+        // Other versions of a pack may differ, so they are not trusted
+        let old = KnownPack::new("minecraft", "core", "1.21.5");
+        assert!(packs.known_entries(&[old]).is_empty());
+    }
+}
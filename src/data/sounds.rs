@@ -0,0 +1,69 @@
+//! Sound event registry
+//!
+//! Maps sound event identifiers to their numeric IDs in the
+//! `minecraft:sound_event` registry. Sounds missing from it can still be
+//! played by name.
+
+use super::ProtocolIds;
+use crate::error::Result;
+
+/// Sound event IDs bundled with the server, in the same format as
+/// `items.json`
+const SOUNDS_JSON: &str = include_str!("sounds.json");
+
+/// Numeric protocol IDs of sound events
+#[derive(Debug, Clone, Default)]
+pub struct SoundRegistry {
+    /// Sound event identifiers and IDs
+    ids: ProtocolIds,
+}
+
+impl SoundRegistry {
+    /// Load the sound event IDs bundled with the server
+    pub fn load() -> Result<Self> {
+        Self::from_json(SOUNDS_JSON)
+    }
+
+    /// Load sound event IDs from a JSON object mapping identifiers to IDs
+    pub fn from_json(json_str: &str) -> Result<Self> {
+        Ok(Self {
+            ids: ProtocolIds::from_json(json_str, "sound event")?,
+        })
+    }
+
+    /// Get the protocol ID of a sound event (e.g. "minecraft:entity.allay.hurt")
+    pub fn id_of(&self, name: &str) -> Option<i32> {
+        self.ids.id_of(name)
+    }
+
+    /// Get the identifier of a sound event from its protocol ID
+    pub fn name_of(&self, id: i32) -> Option<&str> {
+        self.ids.name_of(id)
+    }
+
+    /// Number of known sound events
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Whether no sound events are known
+    pub fn is_empty(&self) -> bool {
+        self.ids.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_sounds() {
+        let sounds = SoundRegistry::load().unwrap();
+        assert_eq!(sounds.id_of("minecraft:entity.allay.hurt"), Some(3));
+        assert_eq!(
+            sounds.name_of(0),
+            Some("minecraft:entity.allay.ambient_with_item")
+        );
+        assert_eq!(sounds.id_of("minecraft:ui.button.click"), None);
+    }
+}
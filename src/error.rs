@@ -20,6 +20,27 @@ pub enum ServerError {
     /// Decompression error
     #[error("Decompression error: {0}")]
     Decompression(#[from] flate2::DecompressError),
+
+    /// A JSON file could not be read or written
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// NBT encoding or decoding error
+    #[error("NBT error: {0}")]
+    Nbt(String),
+
+    /// Encryption error
+    #[error("Encryption error: {0}")]
+    Encryption(String),
+
+    /// Authentication error
+    #[error("Authentication error: {0}")]
+    Authentication(String),
+
+    /// The server ended the connection after telling the client why, such
+    /// as when a player is kicked or banned
+    #[error("Disconnected: {0}")]
+    Disconnected(String),
 }
 
 /// Convenience type alias
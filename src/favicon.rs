@@ -49,6 +49,22 @@ pub fn load_favicon_from_file<P: AsRef<Path>>(path: P) -> Result<String> {
         return Err(ServerError::Protocol("Invalid PNG file format".to_string()));
     }
 
+    // Clients only draw 64x64 icons
+    match png_dimensions(&image_data) {
+        Some((FAVICON_SIZE, FAVICON_SIZE)) => {}
+        Some((width, height)) => {
+            return Err(ServerError::Protocol(format!(
+                "Favicon must be {}x{} pixels, not {}x{}",
+                FAVICON_SIZE, FAVICON_SIZE, width, height
+            )));
+        }
+        None => {
+            return Err(ServerError::Protocol(
+                "Favicon has no PNG image header".to_string(),
+            ));
+        }
+    }
+
     // Encode as base64 data URL
     let encoded = base64::engine::general_purpose::STANDARD.encode(&image_data);
     let data_url = format!("data:image/png;base64,{}", encoded);
@@ -81,6 +97,23 @@ fn is_valid_png(data: &[u8]) -> bool {
     data.len() >= PNG_SIGNATURE.len() && &data[..PNG_SIGNATURE.len()] == PNG_SIGNATURE
 }
 
+/// Width and height favicons must have, in pixels
+pub const FAVICON_SIZE: u32 = 64;
+
+/// Read the width and height from a PNG's image header
+///
+/// The header is the `IHDR` chunk, which must come right after the
+/// signature. Returns `None` if it is missing.
+pub fn png_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    let header = data.get(8..24)?;
+    if header[..8] != [0, 0, 0, 13, b'I', b'H', b'D', b'R'] {
+        return None;
+    }
+    let width = u32::from_be_bytes(header[8..12].try_into().ok()?);
+    let height = u32::from_be_bytes(header[12..16].try_into().ok()?);
+    Some((width, height))
+}
+
 /// Generate a simple default favicon (placeholder)
 /// Returns a base64-encoded 64x64 PNG with a simple design
 pub fn generate_default_favicon() -> String {
@@ -128,6 +161,18 @@ mod tests {
         assert!(!is_valid_png(&invalid_data));
     }
 
+    #[test]
+    fn test_png_dimensions() {
+        let data = fs::read("server-icon.png").unwrap();
+        assert_eq!(png_dimensions(&data), Some((64, 64)));
+        assert_eq!(png_dimensions(&data[..20]), None);
+
+        let mut wide = data.clone();
+        wide[16..20].copy_from_slice(&128u32.to_be_bytes());
+        assert_eq!(png_dimensions(&wide), Some((128, 64)));
+        assert_eq!(png_dimensions(&[0x89, 0x50, 0x4E, 0x47]), None);
+    }
+
     #[test]
     fn test_create_favicon_from_data() {
         let valid_png_data = [
@@ -0,0 +1,285 @@
+//! Entity attributes
+//!
+//! Every attribute has a base value that modifiers are applied on top of.
+//! The server only tracks which attributes changed so their new values can
+//! be sent to the client, which works out the final values itself.
+
+use crate::protocol::packets::play::{
+    AttributeModifier, AttributeProperty, ModifierOperation, UpdateAttributesPacket,
+};
+use crate::protocol::types::{McIdentifier, VarInt};
+use std::collections::BTreeSet;
+
+/// Paths of the attributes in the `minecraft:attribute` registry, in
+/// protocol ID order, with their default base values
+pub const STANDARD_ATTRIBUTES: &[(&str, f64)] = &[
+    ("armor", 0.0),
+    ("armor_toughness", 0.0),
+    ("attack_damage", 2.0),
+    ("attack_knockback", 0.0),
+    ("attack_speed", 4.0),
+    ("block_break_speed", 1.0),
+    ("block_interaction_range", 4.5),
+    ("burning_time", 1.0),
+    ("camera_distance", 4.0),
+    ("explosion_knockback_resistance", 0.0),
+    ("entity_interaction_range", 3.0),
+    ("fall_damage_multiplier", 1.0),
+    ("flying_speed", 0.4),
+    ("follow_range", 32.0),
+    ("gravity", 0.08),
+    ("jump_strength", 0.42),
+    ("knockback_resistance", 0.0),
+    ("luck", 0.0),
+    ("max_absorption", 0.0),
+    ("max_health", 20.0),
+    ("mining_efficiency", 0.0),
+    ("movement_efficiency", 0.0),
+    ("movement_speed", 0.7),
+    ("oxygen_bonus", 0.0),
+    ("safe_fall_distance", 3.0),
+    ("scale", 1.0),
+    ("sneaking_speed", 0.3),
+    ("spawn_reinforcements", 0.0),
+    ("step_height", 0.6),
+    ("submerged_mining_speed", 0.2),
+    ("sweeping_damage_ratio", 0.0),
+    ("tempt_range", 10.0),
+    ("water_movement_efficiency", 0.0),
+    ("waypoint_transmit_range", 0.0),
+    ("waypoint_receive_range", 0.0),
+];
+
+/// Base values players have instead of the registry defaults
+const PLAYER_BASE_VALUES: &[(&str, f64)] = &[("attack_damage", 1.0), ("movement_speed", 0.1)];
+
+/// An attribute of an entity
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attribute {
+    /// Identifier of the attribute (e.g. "minecraft:max_health")
+    pub key: McIdentifier,
+    /// Base value, before modifiers
+    pub value: f64,
+    /// Modifiers applied to the base value
+    pub modifiers: Vec<AttributeModifier>,
+}
+
+impl Attribute {
+    /// Create an attribute without modifiers
+    pub fn new(key: McIdentifier, value: f64) -> Self {
+        Self {
+            key,
+            value,
+            modifiers: Vec::new(),
+        }
+    }
+
+    /// Value after applying every modifier
+    ///
+    /// Added values come first, then the multiples of the base value, and
+    /// finally the multipliers of the total so far.
+    pub fn final_value(&self) -> f64 {
+        let sum = |operation: ModifierOperation| -> f64 {
+            self.modifiers
+                .iter()
+                .filter(|modifier| modifier.operation == operation)
+                .map(|modifier| modifier.amount)
+                .sum()
+        };
+        let base = self.value + sum(ModifierOperation::AddValue);
+        let mut total = base * (1.0 + sum(ModifierOperation::AddMultipliedBase));
+        for modifier in &self.modifiers {
+            if modifier.operation == ModifierOperation::AddMultipliedTotal {
+                total *= 1.0 + modifier.amount;
+            }
+        }
+        total
+    }
+}
+
+/// The attributes of a player and the ones changed since they were last sent
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerAttributes {
+    /// Every standard attribute, indexed by protocol ID
+    attributes: Vec<Attribute>,
+    /// Protocol IDs of the attributes changed since the last update
+    changed: BTreeSet<usize>,
+}
+
+impl PlayerAttributes {
+    /// Create the attributes of a new player, with vanilla base values
+    pub fn new() -> Self {
+        let attributes = STANDARD_ATTRIBUTES
+            .iter()
+            .map(|&(path, default)| {
+                let value = PLAYER_BASE_VALUES
+                    .iter()
+                    .find(|(player_path, _)| *player_path == path)
+                    .map_or(default, |&(_, value)| value);
+                Attribute::new(McIdentifier::minecraft(path), value)
+            })
+            .collect();
+        Self {
+            attributes,
+            changed: BTreeSet::new(),
+        }
+    }
+
+    /// Get an attribute
+    pub fn get(&self, key: &McIdentifier) -> Option<&Attribute> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.key == *key)
+    }
+
+    /// Get an attribute to change it, marking it as changed
+    fn get_mut(&mut self, key: &McIdentifier) -> Option<&mut Attribute> {
+        let index = self
+            .attributes
+            .iter()
+            .position(|attribute| attribute.key == *key)?;
+        self.changed.insert(index);
+        Some(&mut self.attributes[index])
+    }
+
+    /// Set the base value of an attribute, returning whether it exists
+    pub fn set_base(&mut self, key: &McIdentifier, value: f64) -> bool {
+        let Some(attribute) = self.get_mut(key) else {
+            return false;
+        };
+        attribute.value = value;
+        true
+    }
+
+    /// Add a modifier to an attribute, replacing any with the same ID, and
+    /// return whether the attribute exists
+    pub fn add_modifier(&mut self, key: &McIdentifier, modifier: AttributeModifier) -> bool {
+        let Some(attribute) = self.get_mut(key) else {
+            return false;
+        };
+        attribute
+            .modifiers
+            .retain(|existing| existing.id != modifier.id);
+        attribute.modifiers.push(modifier);
+        true
+    }
+
+    /// Remove a modifier from an attribute, returning whether it had it
+    pub fn remove_modifier(&mut self, key: &McIdentifier, id: &McIdentifier) -> bool {
+        let Some(index) = self
+            .attributes
+            .iter()
+            .position(|attribute| attribute.key == *key)
+        else {
+            return false;
+        };
+        let modifiers = &mut self.attributes[index].modifiers;
+        let count = modifiers.len();
+        modifiers.retain(|modifier| modifier.id != *id);
+        if modifiers.len() == count {
+            return false;
+        }
+        self.changed.insert(index);
+        true
+    }
+
+    /// Build the packet with every attribute, as sent when the player spawns
+    ///
+    /// Clears the changes, since the packet includes them.
+    pub fn spawn_packet(&mut self, entity_id: i32) -> UpdateAttributesPacket {
+        self.changed.clear();
+        self.packet(entity_id, 0..self.attributes.len())
+    }
+
+    /// Build the packet with the attributes changed since the last packet,
+    /// if any changed
+    pub fn take_update(&mut self, entity_id: i32) -> Option<UpdateAttributesPacket> {
+        if self.changed.is_empty() {
+            return None;
+        }
+        let changed = std::mem::take(&mut self.changed);
+        Some(self.packet(entity_id, changed))
+    }
+
+    /// Build the packet with some of the attributes, by protocol ID
+    fn packet(
+        &self,
+        entity_id: i32,
+        indices: impl IntoIterator<Item = usize>,
+    ) -> UpdateAttributesPacket {
+        UpdateAttributesPacket {
+            entity_id: VarInt(entity_id),
+            attributes: indices
+                .into_iter()
+                .map(|index| {
+                    let attribute = &self.attributes[index];
+                    AttributeProperty {
+                        attribute_id: VarInt(index as i32),
+                        base: attribute.value,
+                        modifiers: attribute.modifiers.clone(),
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Default for PlayerAttributes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn modifier(path: &str, amount: f64, operation: ModifierOperation) -> AttributeModifier {
+        AttributeModifier {
+            id: McIdentifier::minecraft(path),
+            amount,
+            operation,
+        }
+    }
+
+    #[test]
+    fn test_final_value() {
+        let mut attribute = Attribute::new(McIdentifier::minecraft("max_health"), 20.0);
+        attribute.modifiers = vec![
+            modifier("a", 4.0, ModifierOperation::AddValue),
+            modifier("b", 0.5, ModifierOperation::AddMultipliedBase),
+            modifier("c", 1.0, ModifierOperation::AddMultipliedTotal),
+        ];
+        assert_eq!(attribute.final_value(), 72.0);
+    }
+
+    #[test]
+    fn test_player_attributes() {
+        let mut attributes = PlayerAttributes::new();
+        let speed = McIdentifier::minecraft("movement_speed");
+        assert_eq!(attributes.get(&speed).unwrap().value, 0.1);
+
+        let spawn = attributes.spawn_packet(1);
+        assert_eq!(spawn.attributes.len(), STANDARD_ATTRIBUTES.len());
+        assert_eq!(spawn.attributes[19].base, 20.0);
+        assert_eq!(attributes.take_update(1), None);
+
+        let sprinting = modifier("sprinting", 0.3, ModifierOperation::AddMultipliedTotal);
+        assert!(attributes.add_modifier(&speed, sprinting.clone()));
+        assert!(attributes.add_modifier(&speed, sprinting));
+        assert!(!attributes.set_base(&McIdentifier::minecraft("unknown"), 1.0));
+        let update = attributes.take_update(1).unwrap();
+        assert_eq!(update.attributes.len(), 1);
+        assert_eq!(update.attributes[0].attribute_id.0, 22);
+        assert_eq!(update.attributes[0].modifiers.len(), 1);
+
+        let sprinting = McIdentifier::minecraft("sprinting");
+        assert!(attributes.remove_modifier(&speed, &sprinting));
+        assert!(!attributes.remove_modifier(&speed, &sprinting));
+        assert!(
+            attributes.take_update(1).unwrap().attributes[0]
+                .modifiers
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,275 @@
+//! Game rules
+//!
+//! Game rules are named settings of a world that are either on/off or a
+//! number. They are saved as strings in `level.dat`, so the type of each
+//! rule comes from its default value.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Name of the rule that stops the time of day
+pub const DO_DAYLIGHT_CYCLE: &str = "doDaylightCycle";
+
+/// Name of the rule that skips the death screen
+pub const DO_IMMEDIATE_RESPAWN: &str = "doImmediateRespawn";
+
+/// Name of the rule that limits crafting to unlocked recipes
+pub const DO_LIMITED_CRAFTING: &str = "doLimitedCrafting";
+
+/// Name of the rule that stops the weather from changing by itself
+pub const DO_WEATHER_CYCLE: &str = "doWeatherCycle";
+
+/// Name of the rule that keeps items on death
+pub const KEEP_INVENTORY: &str = "keepInventory";
+
+/// Name of the rule that heals players with a full hunger bar
+pub const NATURAL_REGENERATION: &str = "naturalRegeneration";
+
+/// Name of the rule that hides coordinates from the debug screen
+pub const REDUCED_DEBUG_INFO: &str = "reducedDebugInfo";
+
+/// Value of a game rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameRuleValue {
+    /// On/off rule
+    Bool(bool),
+    /// Numeric rule
+    Int(i32),
+}
+
+impl GameRuleValue {
+    /// Parse a string as a value of the same type as this one
+    pub fn parse_same(&self, value: &str) -> Option<Self> {
+        match self {
+            GameRuleValue::Bool(_) => match value {
+                "true" => Some(GameRuleValue::Bool(true)),
+                "false" => Some(GameRuleValue::Bool(false)),
+                _ => None,
+            },
+            GameRuleValue::Int(_) => value.parse().ok().map(GameRuleValue::Int),
+        }
+    }
+}
+
+impl fmt::Display for GameRuleValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameRuleValue::Bool(value) => write!(f, "{}", value),
+            GameRuleValue::Int(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// Vanilla game rules and their default values
+pub const DEFAULT_GAME_RULES: &[(&str, GameRuleValue)] = &[
+    ("allowFireTicksAwayFromPlayer", GameRuleValue::Bool(false)),
+    ("announceAdvancements", GameRuleValue::Bool(true)),
+    ("blockExplosionDropDecay", GameRuleValue::Bool(true)),
+    ("commandBlockOutput", GameRuleValue::Bool(true)),
+    ("commandModificationBlockLimit", GameRuleValue::Int(32_768)),
+    ("disableElytraMovementCheck", GameRuleValue::Bool(false)),
+    ("disablePlayerMovementCheck", GameRuleValue::Bool(false)),
+    ("disableRaids", GameRuleValue::Bool(false)),
+    ("doDaylightCycle", GameRuleValue::Bool(true)),
+    ("doEntityDrops", GameRuleValue::Bool(true)),
+    ("doFireTick", GameRuleValue::Bool(true)),
+    ("doImmediateRespawn", GameRuleValue::Bool(false)),
+    ("doInsomnia", GameRuleValue::Bool(true)),
+    ("doLimitedCrafting", GameRuleValue::Bool(false)),
+    ("doMobLoot", GameRuleValue::Bool(true)),
+    ("doMobSpawning", GameRuleValue::Bool(true)),
+    ("doPatrolSpawning", GameRuleValue::Bool(true)),
+    ("doTileDrops", GameRuleValue::Bool(true)),
+    ("doTraderSpawning", GameRuleValue::Bool(true)),
+    ("doVinesSpread", GameRuleValue::Bool(true)),
+    ("doWardenSpawning", GameRuleValue::Bool(true)),
+    ("doWeatherCycle", GameRuleValue::Bool(true)),
+    ("drowningDamage", GameRuleValue::Bool(true)),
+    ("enderPearlsVanishOnDeath", GameRuleValue::Bool(true)),
+    ("fallDamage", GameRuleValue::Bool(true)),
+    ("fireDamage", GameRuleValue::Bool(true)),
+    ("forgiveDeadPlayers", GameRuleValue::Bool(true)),
+    ("freezeDamage", GameRuleValue::Bool(true)),
+    ("globalSoundEvents", GameRuleValue::Bool(true)),
+    ("keepInventory", GameRuleValue::Bool(false)),
+    ("lavaSourceConversion", GameRuleValue::Bool(false)),
+    ("locatorBar", GameRuleValue::Bool(true)),
+    ("logAdminCommands", GameRuleValue::Bool(true)),
+    ("maxCommandChainLength", GameRuleValue::Int(65_536)),
+    ("maxCommandForkCount", GameRuleValue::Int(65_536)),
+    ("maxEntityCramming", GameRuleValue::Int(24)),
+    ("mobExplosionDropDecay", GameRuleValue::Bool(true)),
+    ("mobGriefing", GameRuleValue::Bool(true)),
+    ("naturalRegeneration", GameRuleValue::Bool(true)),
+    ("playersNetherPortalCreativeDelay", GameRuleValue::Int(0)),
+    ("playersNetherPortalDefaultDelay", GameRuleValue::Int(80)),
+    ("playersSleepingPercentage", GameRuleValue::Int(100)),
+    ("projectilesCanBreakBlocks", GameRuleValue::Bool(true)),
+    ("randomTickSpeed", GameRuleValue::Int(3)),
+    ("reducedDebugInfo", GameRuleValue::Bool(false)),
+    ("sendCommandFeedback", GameRuleValue::Bool(true)),
+    ("showDeathMessages", GameRuleValue::Bool(true)),
+    ("snowAccumulationHeight", GameRuleValue::Int(1)),
+    ("spawnChunkRadius", GameRuleValue::Int(2)),
+    ("spawnRadius", GameRuleValue::Int(10)),
+    ("spectatorsGenerateChunks", GameRuleValue::Bool(true)),
+    ("tntExplodes", GameRuleValue::Bool(true)),
+    ("tntExplosionDropDecay", GameRuleValue::Bool(false)),
+    ("universalAnger", GameRuleValue::Bool(false)),
+    ("waterSourceConversion", GameRuleValue::Bool(true)),
+];
+
+/// Game rules of a world
+///
+/// Only vanilla rules are known; setting any other name fails, and a rule
+/// can only be set to a value of its own type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameRuleRegistry {
+    /// Value of every rule, by name
+    rules: BTreeMap<String, GameRuleValue>,
+}
+
+impl Default for GameRuleRegistry {
+    fn default() -> Self {
+        Self {
+            rules: DEFAULT_GAME_RULES
+                .iter()
+                .map(|(name, value)| (name.to_string(), *value))
+                .collect(),
+        }
+    }
+}
+
+impl GameRuleRegistry {
+    /// Create a registry with every rule at its default value
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read rules saved as strings, as in `level.dat`
+    ///
+    /// Missing rules keep their default value, as do rules whose saved value
+    /// does not parse. Unknown rules are dropped.
+    pub fn from_strings<'a>(saved: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        let mut registry = Self::default();
+        for (name, value) in saved {
+            match registry.parse(name, value) {
+                Some(value) => {
+                    registry.set(name, value);
+                }
+                None if registry.get(name).is_some() => {
+                    tracing::warn!("Invalid value '{}' for game rule {}", value, name);
+                }
+                None => tracing::debug!("Dropping unknown game rule {}", name),
+            }
+        }
+        registry
+    }
+
+    /// Number of rules
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Check whether there are no rules
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Every rule and its value, sorted by name
+    pub fn iter(&self) -> impl Iterator<Item = (&str, GameRuleValue)> {
+        self.rules
+            .iter()
+            .map(|(name, value)| (name.as_str(), *value))
+    }
+
+    /// Value of a rule
+    pub fn get(&self, name: &str) -> Option<GameRuleValue> {
+        self.rules.get(name).copied()
+    }
+
+    /// Value of an on/off rule
+    pub fn get_bool(&self, name: &str) -> Option<bool> {
+        match self.get(name)? {
+            GameRuleValue::Bool(value) => Some(value),
+            GameRuleValue::Int(_) => None,
+        }
+    }
+
+    /// Value of a numeric rule
+    pub fn get_int(&self, name: &str) -> Option<i32> {
+        match self.get(name)? {
+            GameRuleValue::Int(value) => Some(value),
+            GameRuleValue::Bool(_) => None,
+        }
+    }
+
+    /// Parse a string as a value of a rule, if the rule exists
+    pub fn parse(&self, name: &str, value: &str) -> Option<GameRuleValue> {
+        self.get(name)?.parse_same(value)
+    }
+
+    /// Set a rule, returning whether it exists and has the value's type
+    pub fn set(&mut self, name: &str, value: GameRuleValue) -> bool {
+        match self.rules.get_mut(name) {
+            Some(current) if std::mem::discriminant(current) == std::mem::discriminant(&value) => {
+                *current = value;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Set an on/off rule, returning whether it exists
+    pub fn set_bool(&mut self, name: &str, value: bool) -> bool {
+        self.set(name, GameRuleValue::Bool(value))
+    }
+
+    /// Set a numeric rule, returning whether it exists
+    pub fn set_int(&mut self, name: &str, value: i32) -> bool {
+        self.set(name, GameRuleValue::Int(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_strings() {
+        let rules = GameRuleRegistry::from_strings([
+            ("keepInventory", "true"),
+            ("randomTickSpeed", "10"),
+            ("doFireTick", "yes"),
+            ("maxEntityCramming", "1.5"),
+            ("notARule", "true"),
+        ]);
+        assert_eq!(rules.len(), DEFAULT_GAME_RULES.len());
+        assert_eq!(rules.get_bool(KEEP_INVENTORY), Some(true));
+        assert_eq!(rules.get_int("randomTickSpeed"), Some(10));
+        // Values that do not parse keep the default
+        assert_eq!(rules.get_bool("doFireTick"), Some(true));
+        assert_eq!(rules.get_int("maxEntityCramming"), Some(24));
+        assert_eq!(rules.get("notARule"), None);
+    }
+
+    #[test]
+    fn test_typed_values() {
+        let mut rules = GameRuleRegistry::new();
+        assert_eq!(rules.get_bool(DO_DAYLIGHT_CYCLE), Some(true));
+        assert_eq!(rules.get_int(DO_DAYLIGHT_CYCLE), None);
+        assert!(rules.set_bool(DO_DAYLIGHT_CYCLE, false));
+        assert!(!rules.set_int(DO_DAYLIGHT_CYCLE, 1));
+        assert!(!rules.set_bool("notARule", true));
+        assert_eq!(rules.get_bool(DO_DAYLIGHT_CYCLE), Some(false));
+
+        assert_eq!(
+            rules.parse("spawnRadius", "-3"),
+            Some(GameRuleValue::Int(-3))
+        );
+        assert_eq!(rules.parse("spawnRadius", "true"), None);
+        assert_eq!(rules.parse(KEEP_INVENTORY, "TRUE"), None);
+        assert_eq!(GameRuleValue::Int(-3).to_string(), "-3");
+        assert_eq!(GameRuleValue::Bool(false).to_string(), "false");
+    }
+}
@@ -3,9 +3,13 @@
 //! This module contains all the game-related logic including players,
 //! worlds, entities, and game mechanics.
 
+pub mod attributes;
 pub mod entity;
+pub mod game_rules;
 pub mod player;
 pub mod world;
 
+pub use attributes::PlayerAttributes;
+pub use game_rules::{GameRuleRegistry, GameRuleValue};
 pub use player::Player;
 pub use world::World;
@@ -2,6 +2,8 @@
 //!
 //! This module handles player state, authentication, and player-specific logic.
 
+use crate::error::{Result, ServerError};
+use crate::protocol::packets::play::abilities;
 use crate::protocol::types::McUuid;
 use std::collections::HashMap;
 use std::net::SocketAddr;
@@ -29,6 +31,8 @@ pub struct Player {
     pub experience: PlayerExperience,
     /// Whether the player is on ground
     pub on_ground: bool,
+    /// Whether the player is flying
+    pub flying: bool,
 }
 
 /// Player position in the world
@@ -64,6 +68,58 @@ pub enum GameMode {
     Spectator = 3,
 }
 
+impl GameMode {
+    /// Every game mode, in ID order
+    pub const ALL: [GameMode; 4] = [
+        GameMode::Survival,
+        GameMode::Creative,
+        GameMode::Adventure,
+        GameMode::Spectator,
+    ];
+
+    /// Name of the mode, as used in commands and `server.properties`
+    pub fn name(self) -> &'static str {
+        match self {
+            GameMode::Survival => "survival",
+            GameMode::Creative => "creative",
+            GameMode::Adventure => "adventure",
+            GameMode::Spectator => "spectator",
+        }
+    }
+
+    /// Look up a mode by its name
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.name() == name)
+    }
+
+    /// Whether players in this mode may fly
+    pub fn allows_flight(self) -> bool {
+        matches!(self, GameMode::Creative | GameMode::Spectator)
+    }
+
+    /// Whether players in this mode take no damage
+    pub fn is_invulnerable(self) -> bool {
+        matches!(self, GameMode::Creative | GameMode::Spectator)
+    }
+}
+
+impl TryFrom<u8> for GameMode {
+    type Error = ServerError;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(GameMode::Survival),
+            1 => Ok(GameMode::Creative),
+            2 => Ok(GameMode::Adventure),
+            3 => Ok(GameMode::Spectator),
+            _ => Err(ServerError::Protocol(format!(
+                "Invalid game mode: {}",
+                value
+            ))),
+        }
+    }
+}
+
 /// Player experience information
 #[derive(Debug, Clone, Copy)]
 pub struct PlayerExperience {
@@ -99,6 +155,7 @@ impl Player {
                 progress: 0.0,
             },
             on_ground: true,
+            flying: false,
         }
     }
 
@@ -115,9 +172,28 @@ impl Player {
         self.rotation.pitch = pitch;
     }
 
-    /// Set game mode
+    /// Set game mode, landing the player if the new mode cannot fly
     pub fn set_game_mode(&mut self, mode: GameMode) {
         self.game_mode = mode;
+        self.flying &= mode.allows_flight();
+    }
+
+    /// Ability flags sent in the player abilities packet
+    pub fn ability_flags(&self) -> u8 {
+        let mut flags = 0;
+        if self.game_mode.is_invulnerable() {
+            flags |= abilities::INVULNERABLE;
+        }
+        if self.flying {
+            flags |= abilities::FLYING;
+        }
+        if self.game_mode.allows_flight() {
+            flags |= abilities::ALLOW_FLYING;
+        }
+        if self.game_mode == GameMode::Creative {
+            flags |= abilities::INSTANT_BREAK;
+        }
+        flags
     }
 
     /// Set health
@@ -245,3 +321,32 @@ impl Default for PlayerManager {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ability_flags() {
+        let mut player = Player::new(McUuid::new_v4(), "Steve".to_string());
+        assert_eq!(player.ability_flags(), 0);
+
+        player.set_game_mode(GameMode::Creative);
+        player.flying = true;
+        assert_eq!(player.ability_flags(), 0x0F);
+
+        // Switching to survival lands the player
+        player.set_game_mode(GameMode::Survival);
+        assert!(!player.flying);
+        assert_eq!(player.ability_flags(), 0);
+    }
+
+    #[test]
+    fn test_game_mode_names() {
+        for mode in GameMode::ALL {
+            assert_eq!(GameMode::from_name(mode.name()), Some(mode));
+            assert_eq!(GameMode::try_from(mode as u8).unwrap(), mode);
+        }
+        assert_eq!(GameMode::from_name("Creative"), None);
+    }
+}
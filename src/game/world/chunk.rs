@@ -3,6 +3,14 @@
 //! This module handles individual chunks and their block data.
 
 use super::ChunkPosition;
+use super::heightmap::HeightMap;
+use super::palette::{LightArray, PalettedContainer};
+use crate::error::{Result, ServerError};
+use crate::protocol::packets::play::chunk_data::{
+    ChunkDataPacket, ChunkSection as WireSection, LightData, SECTION_BIOME_COUNT,
+    SECTION_BLOCK_COUNT,
+};
+use crate::protocol::types::BitSet;
 
 /// Chunk size constants
 pub const CHUNK_SIZE: usize = 16;
@@ -43,35 +51,6 @@ impl Chunk {
         }
     }
 
-    /// Generate a simple flat chunk for testing
-    pub fn generate_flat(position: ChunkPosition) -> Self {
-        let mut chunk = Self::new(position);
-
-        // Generate flat terrain
-        for x in 0..CHUNK_SIZE {
-            for z in 0..CHUNK_SIZE {
-                // Bedrock at bottom
-                chunk.set_block(x, 0, z, 7); // Bedrock
-
-                // Stone layers
-                for y in 1..60 {
-                    chunk.set_block(x, y, z, 1); // Stone
-                }
-
-                // Dirt layers
-                for y in 60..63 {
-                    chunk.set_block(x, y, z, 3); // Dirt
-                }
-
-                // Grass on top
-                chunk.set_block(x, 63, z, 2); // Grass
-            }
-        }
-
-        chunk.modified = false; // Reset modified flag after generation
-        chunk
-    }
-
     /// Get the chunk position
     pub fn position(&self) -> ChunkPosition {
         self.position
@@ -146,3 +125,333 @@ impl Chunk {
         self.count_blocks() == 0
     }
 }
+
+/// Number of sections in a chunk
+pub const SECTION_COUNT: usize = CHUNK_HEIGHT / CHUNK_SIZE;
+
+/// A block state ID
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct BlockState(pub u16);
+
+impl BlockState {
+    /// `minecraft:air`
+    pub const AIR: Self = Self(0);
+
+    /// Block state ID
+    pub fn id(self) -> u16 {
+        self.0
+    }
+
+    /// Whether the block is air
+    pub fn is_air(self) -> bool {
+        self == Self::AIR
+    }
+}
+
+/// A 16x16x16 section of a chunk in memory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkSection {
+    /// Block states, indexed by `(y * 16 + z) * 16 + x`
+    pub block_states: PalettedContainer<u16>,
+    /// Biome IDs in 4x4x4 cells, indexed like block states
+    pub biomes: PalettedContainer<u8>,
+    /// Light emitted by blocks
+    pub block_light: LightArray,
+    /// Light from the sky
+    pub sky_light: LightArray,
+    /// Number of non-air blocks
+    block_count: u16,
+}
+
+impl ChunkSection {
+    /// Create a section of air in the given biome, lit by the sky
+    pub fn empty(biome: u8) -> Self {
+        Self {
+            block_states: PalettedContainer::filled(SECTION_BLOCK_COUNT, BlockState::AIR.id()),
+            biomes: PalettedContainer::filled(SECTION_BIOME_COUNT, biome),
+            block_light: LightArray::dark(),
+            sky_light: LightArray::full(),
+            block_count: 0,
+        }
+    }
+
+    /// Create a section from stored block states, biomes and light,
+    /// counting its non-air blocks
+    pub fn from_parts(
+        block_states: PalettedContainer<u16>,
+        biomes: PalettedContainer<u8>,
+        block_light: LightArray,
+        sky_light: LightArray,
+    ) -> Self {
+        let block_count = block_states
+            .values()
+            .into_iter()
+            .filter(|&id| !BlockState(id).is_air())
+            .count() as u16;
+        Self {
+            block_states,
+            biomes,
+            block_light,
+            sky_light,
+            block_count,
+        }
+    }
+
+    /// Get the block at local coordinates
+    pub fn get_block(&self, x: usize, y: usize, z: usize) -> Option<BlockState> {
+        section_index(x, y, z).map(|index| BlockState(self.block_states.get(index)))
+    }
+
+    /// Set the block at local coordinates, returning whether they are valid
+    pub fn set_block(&mut self, x: usize, y: usize, z: usize, block: BlockState) -> bool {
+        let Some(index) = section_index(x, y, z) else {
+            return false;
+        };
+        let previous = BlockState(self.block_states.get(index));
+        match (previous.is_air(), block.is_air()) {
+            (true, false) => self.block_count += 1,
+            (false, true) => self.block_count -= 1,
+            _ => {}
+        }
+        self.block_states.set(index, block.id());
+        true
+    }
+
+    /// Number of non-air blocks
+    pub fn block_count(&self) -> u16 {
+        self.block_count
+    }
+
+    /// Encode the section for a chunk data packet
+    pub fn to_wire(&self) -> WireSection {
+        WireSection {
+            block_count: self.block_count as i16,
+            block_states: self.block_states.to_wire(),
+            biomes: self.biomes.to_wire(),
+        }
+    }
+}
+
+/// A chunk column in memory, stored as paletted sections
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkData {
+    /// Chunk X coordinate
+    pub x: i32,
+    /// Chunk Z coordinate
+    pub z: i32,
+    /// Sections from the bottom of the world up
+    pub sections: Vec<ChunkSection>,
+}
+
+impl ChunkData {
+    /// Create a chunk of air in the given biome
+    pub fn new(x: i32, z: i32, biome: u8) -> Self {
+        Self {
+            x,
+            z,
+            sections: (0..SECTION_COUNT)
+                .map(|_| ChunkSection::empty(biome))
+                .collect(),
+        }
+    }
+
+    /// Read a chunk from the packet sending it, such as one made by a
+    /// generator
+    ///
+    /// Sections the packet has no light for are dark.
+    pub fn from_packet(packet: &ChunkDataPacket) -> Result<Self> {
+        let count = packet.sections.len();
+        let sky_light =
+            section_light(&packet.light.sky_light_mask, &packet.light.sky_light, count)?;
+        let block_light = section_light(
+            &packet.light.block_light_mask,
+            &packet.light.block_light,
+            count,
+        )?;
+        let sections = packet
+            .sections
+            .iter()
+            .zip(sky_light.into_iter().zip(block_light))
+            .map(|(section, (sky_light, block_light))| {
+                Ok(ChunkSection::from_parts(
+                    PalettedContainer::from_wire(&section.block_states)?,
+                    PalettedContainer::from_wire(&section.biomes)?,
+                    block_light,
+                    sky_light,
+                ))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            x: packet.chunk_x,
+            z: packet.chunk_z,
+            sections,
+        })
+    }
+
+    /// Get the block at local coordinates, with Y counted from the bottom of
+    /// the world
+    pub fn get_block(&self, x: usize, y: usize, z: usize) -> Option<BlockState> {
+        self.sections
+            .get(y / CHUNK_SIZE)?
+            .get_block(x, y % CHUNK_SIZE, z)
+    }
+
+    /// Set the block at local coordinates, with Y counted from the bottom of
+    /// the world, returning whether they are valid
+    pub fn set_block(&mut self, x: usize, y: usize, z: usize, block: BlockState) -> bool {
+        match self.sections.get_mut(y / CHUNK_SIZE) {
+            Some(section) => section.set_block(x, y % CHUNK_SIZE, z, block),
+            None => false,
+        }
+    }
+
+    /// Build the packet sending this chunk to a client
+    ///
+    /// The sections just below and above the world are sent dark and fully
+    /// sky lit respectively.
+    pub fn to_packet(&self) -> ChunkDataPacket {
+        let heightmap = HeightMap::from_blocks(
+            |x, y, z| self.get_block(x, y, z).unwrap_or(BlockState::AIR),
+            |block| !block.is_air(),
+        );
+
+        let mut light = LightData::default();
+        light.empty_sky_light_mask.set(0, true);
+        light.empty_block_light_mask.set(0, true);
+        for (index, section) in self.sections.iter().enumerate() {
+            let light_section = index + 1;
+            if section.sky_light.is_dark() {
+                light.empty_sky_light_mask.set(light_section, true);
+            } else {
+                light.sky_light_mask.set(light_section, true);
+                light.sky_light.push(section.sky_light.as_bytes().to_vec());
+            }
+            if section.block_light.is_dark() {
+                light.empty_block_light_mask.set(light_section, true);
+            } else {
+                light.block_light_mask.set(light_section, true);
+                light
+                    .block_light
+                    .push(section.block_light.as_bytes().to_vec());
+            }
+        }
+        let above = self.sections.len() + 1;
+        light.sky_light_mask.set(above, true);
+        light.sky_light.push(LightArray::full().as_bytes().to_vec());
+        light.empty_block_light_mask.set(above, true);
+
+        ChunkDataPacket {
+            chunk_x: self.x,
+            chunk_z: self.z,
+            heightmaps: heightmap.to_heightmaps(),
+            sections: self.sections.iter().map(ChunkSection::to_wire).collect(),
+            block_entities: Vec::new(),
+            light,
+        }
+    }
+}
+
+/// Light of each section of a chunk, from the arrays sent for the light
+/// sections set in a mask
+///
+/// Light sections start one section below the world, and have an array
+/// each only when their bit in the mask is set.
+fn section_light(mask: &BitSet, arrays: &[Vec<u8>], sections: usize) -> Result<Vec<LightArray>> {
+    let invalid = || ServerError::Protocol("Invalid chunk light data".to_string());
+    let mut arrays = arrays.iter();
+    let mut light = Vec::with_capacity(sections);
+    for light_section in 0..sections + 2 {
+        let array = match mask.get(light_section) {
+            true => Some(arrays.next().ok_or_else(invalid)?),
+            false => None,
+        };
+        if (1..=sections).contains(&light_section) {
+            light.push(match array {
+                Some(bytes) => LightArray::from_bytes(bytes.clone()).ok_or_else(invalid)?,
+                None => LightArray::dark(),
+            });
+        }
+    }
+    Ok(light)
+}
+
+/// Index of a block within a section
+fn section_index(x: usize, y: usize, z: usize) -> Option<usize> {
+    (x < CHUNK_SIZE && y < CHUNK_SIZE && z < CHUNK_SIZE)
+        .then(|| (y * CHUNK_SIZE + z) * CHUNK_SIZE + x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::world::generators::ChunkProvider;
+    use crate::game::world::generators::flat::{BEDROCK, FlatWorldGenerator, GRASS_BLOCK};
+    use crate::protocol::packets::Packet;
+    use crate::protocol::packets::play::chunk_data::Palette;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_chunk_data_blocks() {
+        let mut chunk = ChunkData::new(1, -2, 0);
+        assert_eq!(chunk.sections.len(), 24);
+        assert_eq!(chunk.get_block(3, 100, 4), Some(BlockState::AIR));
+
+        assert!(chunk.set_block(3, 100, 4, BlockState(1)));
+        assert!(chunk.set_block(3, 101, 4, BlockState(1)));
+        assert!(chunk.set_block(3, 101, 4, BlockState::AIR));
+        assert!(!chunk.set_block(16, 0, 0, BlockState(1)));
+        assert!(!chunk.set_block(0, CHUNK_HEIGHT, 0, BlockState(1)));
+        assert_eq!(chunk.get_block(3, 100, 4), Some(BlockState(1)));
+        assert_eq!(chunk.sections[6].block_count(), 1);
+        assert_eq!(chunk.get_block(0, CHUNK_HEIGHT, 0), None);
+    }
+
+    #[test]
+    fn test_chunk_data_packet() {
+        let mut chunk = ChunkData::new(1, -2, 0);
+        chunk.set_block(0, 0, 0, BlockState(85));
+        chunk.sections[0].sky_light = LightArray::dark();
+
+        let packet = chunk.to_packet();
+        assert_eq!((packet.chunk_x, packet.chunk_z), (1, -2));
+        assert_eq!(packet.sections[0].block_count, 1);
+        assert_eq!(
+            packet.sections[0].block_states.palette,
+            Palette::Indirect(vec![0, 85])
+        );
+        assert_eq!(packet.sections[1].block_count, 0);
+        assert!(packet.light.empty_sky_light_mask.get(1));
+        assert!(packet.light.sky_light_mask.get(2));
+        assert!(packet.light.sky_light_mask.get(25));
+        assert_eq!(packet.light.sky_light.len(), 24);
+        assert!(packet.light.block_light.is_empty());
+
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        let decoded = ChunkDataPacket::read(&mut Cursor::new(buffer)).unwrap();
+        assert_eq!(decoded, packet);
+        let section = &decoded.sections[0];
+        assert_eq!(
+            PalettedContainer::<u16>::from_wire(&section.block_states).unwrap(),
+            chunk.sections[0].block_states
+        );
+        assert_eq!(ChunkData::from_packet(&decoded).unwrap(), chunk);
+    }
+
+    #[test]
+    fn test_chunk_data_from_generated_packet() {
+        let packet = FlatWorldGenerator::default().provide_chunk(ChunkPosition::new(2, 3));
+        let chunk = ChunkData::from_packet(&packet).unwrap();
+        assert_eq!((chunk.x, chunk.z), (2, 3));
+        assert_eq!(chunk.get_block(0, 0, 0), Some(BlockState(BEDROCK)));
+        assert_eq!(chunk.get_block(15, 3, 15), Some(BlockState(GRASS_BLOCK)));
+        assert_eq!(chunk.get_block(0, 4, 0), Some(BlockState::AIR));
+        assert_eq!(chunk.sections[0].block_count(), 4 * 256);
+        assert_eq!(chunk.sections[1].sky_light, LightArray::full());
+
+        // Light that is missing is an error
+        let mut missing_light = packet.clone();
+        missing_light.light.sky_light.pop();
+        assert!(ChunkData::from_packet(&missing_light).is_err());
+    }
+}
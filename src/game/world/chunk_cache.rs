@@ -0,0 +1,169 @@
+//! Chunk caching
+//!
+//! Generating a chunk is far more expensive than copying one, and players
+//! standing near each other request the same chunks over and over. The
+//! [`ChunkCache`] keeps the most recently used chunks of another provider.
+
+use super::ChunkPosition;
+use super::generators::ChunkProvider;
+use crate::protocol::packets::play::ChunkDataPacket;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, RwLock};
+
+/// Caches the chunks of a provider, evicting the least recently used ones
+pub struct ChunkCache {
+    /// Provider generating missing chunks
+    provider: Box<dyn ChunkProvider>,
+    /// Maximum number of cached chunks
+    max_chunks: usize,
+    /// Cached chunks
+    chunks: RwLock<HashMap<ChunkPosition, Arc<ChunkDataPacket>>>,
+    /// Cached positions, least recently used first
+    access_order: Mutex<VecDeque<ChunkPosition>>,
+}
+
+impl ChunkCache {
+    /// Cache up to `max_chunks` chunks of a provider
+    pub fn new(provider: Box<dyn ChunkProvider>, max_chunks: usize) -> Self {
+        Self {
+            provider,
+            max_chunks,
+            chunks: RwLock::new(HashMap::new()),
+            access_order: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Get a chunk, generating it if it is not cached
+    pub fn get_chunk(&self, x: i32, z: i32) -> Arc<ChunkDataPacket> {
+        let position = ChunkPosition::new(x, z);
+        let cached = self
+            .chunks
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&position)
+            .cloned();
+        if let Some(chunk) = cached {
+            self.touch(position);
+            return chunk;
+        }
+
+        // Generate without holding the lock, so other chunks can be read
+        let generated = Arc::new(self.provider.provide_chunk(position));
+
+        let mut chunks = self.chunks.write().unwrap_or_else(|e| e.into_inner());
+        let mut access_order = self.access_order.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(chunk) = chunks.get(&position) {
+            // Another caller generated it in the meantime
+            return Arc::clone(chunk);
+        }
+        if self.max_chunks == 0 {
+            return generated;
+        }
+        while chunks.len() >= self.max_chunks {
+            let Some(evicted) = access_order.pop_front() else {
+                break;
+            };
+            chunks.remove(&evicted);
+        }
+        chunks.insert(position, Arc::clone(&generated));
+        access_order.push_back(position);
+        generated
+    }
+
+    /// Number of cached chunks
+    pub fn len(&self) -> usize {
+        self.chunks.read().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Whether no chunks are cached
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether a chunk is cached
+    pub fn contains(&self, position: ChunkPosition) -> bool {
+        self.chunks
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .contains_key(&position)
+    }
+
+    /// Drop a chunk from the cache, such as after it changed
+    pub fn invalidate(&self, position: ChunkPosition) {
+        let mut chunks = self.chunks.write().unwrap_or_else(|e| e.into_inner());
+        let mut access_order = self.access_order.lock().unwrap_or_else(|e| e.into_inner());
+        if chunks.remove(&position).is_some() {
+            access_order.retain(|cached| *cached != position);
+        }
+    }
+
+    /// Mark a cached chunk as the most recently used
+    fn touch(&self, position: ChunkPosition) {
+        let mut access_order = self.access_order.lock().unwrap_or_else(|e| e.into_inner());
+        // The chunk may have been evicted since it was read
+        if let Some(index) = access_order.iter().position(|cached| *cached == position) {
+            access_order.remove(index);
+            access_order.push_back(position);
+        }
+    }
+}
+
+impl ChunkProvider for ChunkCache {
+    fn provide_chunk(&self, position: ChunkPosition) -> ChunkDataPacket {
+        ChunkDataPacket::clone(&self.get_chunk(position.x, position.z))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::world::generators::VoidWorldChunkProvider;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Provider counting how many chunks it generated
+    struct CountingProvider(Arc<AtomicUsize>);
+
+    impl ChunkProvider for CountingProvider {
+        fn provide_chunk(&self, position: ChunkPosition) -> ChunkDataPacket {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            VoidWorldChunkProvider::new().provide_chunk(position)
+        }
+    }
+
+    fn counting_cache(max_chunks: usize) -> (ChunkCache, Arc<AtomicUsize>) {
+        let generated = Arc::new(AtomicUsize::new(0));
+        let provider = CountingProvider(Arc::clone(&generated));
+        (ChunkCache::new(Box::new(provider), max_chunks), generated)
+    }
+
+    #[test]
+    fn test_chunks_are_generated_once() {
+        let (cache, generated) = counting_cache(16);
+        let first = cache.get_chunk(1, -2);
+        let second = cache.get_chunk(1, -2);
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!((first.chunk_x, first.chunk_z), (1, -2));
+        assert_eq!(generated.load(Ordering::Relaxed), 1);
+
+        cache.invalidate(ChunkPosition::new(1, -2));
+        assert!(cache.is_empty());
+        cache.get_chunk(1, -2);
+        assert_eq!(generated.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_least_recently_used_chunk_is_evicted() {
+        let (cache, generated) = counting_cache(2);
+        cache.get_chunk(0, 0);
+        cache.get_chunk(1, 0);
+        // Using the first chunk again makes the second the oldest
+        cache.get_chunk(0, 0);
+        cache.get_chunk(2, 0);
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.contains(ChunkPosition::new(0, 0)));
+        assert!(!cache.contains(ChunkPosition::new(1, 0)));
+        assert!(cache.contains(ChunkPosition::new(2, 0)));
+        assert_eq!(generated.load(Ordering::Relaxed), 3);
+    }
+}
@@ -0,0 +1,262 @@
+//! Chunks in memory
+//!
+//! The [`ChunkStore`] holds the chunks whose blocks the server has looked at
+//! or changed. It serves them to clients in place of freshly generated ones,
+//! so players see the blocks placed and broken in them, and the blocks the
+//! server checks are the ones players see. Other chunks come straight from
+//! the provider, usually a [`ChunkCache`] of the world generator.
+//!
+//! A store with a [`ChunkStorage`] first looks for chunks in the world's
+//! region files, and [`ChunkStore::save`] writes the loaded chunks back.
+//!
+//! [`ChunkCache`]: super::ChunkCache
+
+use super::chunk::{CHUNK_HEIGHT, CHUNK_MIN_Y, CHUNK_SIZE};
+use super::generators::ChunkProvider;
+use super::{BlockState, ChunkData, ChunkPosition};
+use crate::error::Result;
+use crate::protocol::packets::play::ChunkDataPacket;
+use crate::protocol::types::Position;
+use crate::storage::ChunkStorage;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// Loaded chunks of a world, in front of the provider of the others
+pub struct ChunkStore {
+    /// Provider of chunks that are not loaded
+    provider: Box<dyn ChunkProvider>,
+    /// Loaded chunks
+    chunks: RwLock<HashMap<ChunkPosition, ChunkData>>,
+    /// Where chunks are saved, if they are
+    storage: Option<ChunkStorage>,
+}
+
+impl ChunkStore {
+    /// Create a store loading chunks from a provider
+    pub fn new(provider: Box<dyn ChunkProvider>) -> Self {
+        Self {
+            provider,
+            chunks: RwLock::new(HashMap::new()),
+            storage: None,
+        }
+    }
+
+    /// Load chunks from and save them to region files, ahead of the
+    /// provider
+    pub fn with_storage(mut self, storage: ChunkStorage) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Get the block at a position, loading its chunk
+    ///
+    /// Returns `None` for positions above or below the world.
+    pub fn get_block(&self, position: Position) -> Result<Option<BlockState>> {
+        let Some((chunk, x, y, z)) = local_position(position) else {
+            return Ok(None);
+        };
+        if let Some(loaded) = self.read().get(&chunk) {
+            return Ok(loaded.get_block(x, y, z));
+        }
+        self.with_chunk(chunk, |loaded| loaded.get_block(x, y, z))
+    }
+
+    /// Set the block at a position, loading its chunk, and return the block
+    /// that was there
+    ///
+    /// Returns `None` without changing anything for positions above or below
+    /// the world.
+    pub fn set_block(&self, position: Position, block: BlockState) -> Result<Option<BlockState>> {
+        let Some((chunk, x, y, z)) = local_position(position) else {
+            return Ok(None);
+        };
+        self.with_chunk(chunk, |loaded| {
+            let previous = loaded.get_block(x, y, z)?;
+            loaded.set_block(x, y, z, block).then_some(previous)
+        })
+    }
+
+    /// Whether a chunk is loaded
+    pub fn is_loaded(&self, position: ChunkPosition) -> bool {
+        self.read().contains_key(&position)
+    }
+
+    /// Number of loaded chunks
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    /// Whether no chunks are loaded
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Save every loaded chunk, if the store has storage
+    pub fn save(&self) -> Result<()> {
+        let Some(storage) = &self.storage else {
+            return Ok(());
+        };
+        // Copy the chunks, so players can change blocks while they are written
+        let chunks: Vec<ChunkData> = self.read().values().cloned().collect();
+        storage.save(&chunks)
+    }
+
+    /// Read a chunk from storage, if it was saved
+    fn load_saved(&self, position: ChunkPosition) -> Result<Option<ChunkData>> {
+        match &self.storage {
+            Some(storage) => storage.load(position),
+            None => Ok(None),
+        }
+    }
+
+    /// Run a function on a chunk, loading it from storage or the provider
+    /// first if needed
+    fn with_chunk<T>(
+        &self,
+        position: ChunkPosition,
+        f: impl FnOnce(&mut ChunkData) -> T,
+    ) -> Result<T> {
+        if let Some(chunk) = self.write().get_mut(&position) {
+            return Ok(f(chunk));
+        }
+        // Load without holding the lock, so other chunks can be read
+        let chunk = match self.load_saved(position)? {
+            Some(chunk) => chunk,
+            None => ChunkData::from_packet(&self.provider.provide_chunk(position))?,
+        };
+        // Another caller may have loaded it in the meantime
+        Ok(f(self.write().entry(position).or_insert(chunk)))
+    }
+
+    /// Lock the loaded chunks for reading
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, HashMap<ChunkPosition, ChunkData>> {
+        self.chunks.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Lock the loaded chunks for writing
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, HashMap<ChunkPosition, ChunkData>> {
+        self.chunks.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl ChunkProvider for ChunkStore {
+    fn provide_chunk(&self, position: ChunkPosition) -> ChunkDataPacket {
+        if let Some(chunk) = self.read().get(&position) {
+            return chunk.to_packet();
+        }
+        // Saved chunks are kept loaded once read, generated ones are not
+        match self.load_saved(position) {
+            Ok(Some(chunk)) => self.write().entry(position).or_insert(chunk).to_packet(),
+            Ok(None) => self.provider.provide_chunk(position),
+            Err(e) => {
+                tracing::warn!("Failed to load chunk {}, {}: {}", position.x, position.z, e);
+                self.provider.provide_chunk(position)
+            }
+        }
+    }
+}
+
+/// Chunk of a block and its coordinates within it, with Y counted from the
+/// bottom of the world, or `None` above or below the world
+fn local_position(position: Position) -> Option<(ChunkPosition, usize, usize, usize)> {
+    let y = usize::try_from(position.y - CHUNK_MIN_Y).ok()?;
+    if y >= CHUNK_HEIGHT {
+        return None;
+    }
+    let mask = CHUNK_SIZE as i32 - 1;
+    Some((
+        ChunkPosition::new(position.x >> 4, position.z >> 4),
+        (position.x & mask) as usize,
+        y,
+        (position.z & mask) as usize,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::GameData;
+    use crate::game::world::generators::FlatWorldGenerator;
+    use crate::game::world::generators::flat::{BEDROCK, GRASS_BLOCK};
+    use crate::storage::PaletteNames;
+
+    fn flat_store() -> ChunkStore {
+        ChunkStore::new(Box::new(FlatWorldGenerator::default()))
+    }
+
+    #[test]
+    fn test_blocks_of_generated_chunks() {
+        let store = flat_store();
+        assert_eq!(
+            store.get_block(Position::new(-1, -64, -1)).unwrap(),
+            Some(BlockState(BEDROCK))
+        );
+        assert_eq!(
+            store.get_block(Position::new(5, -61, 20)).unwrap(),
+            Some(BlockState(GRASS_BLOCK))
+        );
+        assert_eq!(
+            store.get_block(Position::new(5, -60, 20)).unwrap(),
+            Some(BlockState::AIR)
+        );
+        assert_eq!(store.get_block(Position::new(0, -65, 0)).unwrap(), None);
+        assert_eq!(store.get_block(Position::new(0, 320, 0)).unwrap(), None);
+        assert!(store.is_loaded(ChunkPosition::new(-1, -1)));
+        assert!(store.is_loaded(ChunkPosition::new(0, 1)));
+        assert_eq!(store.len(), 2);
+    }
+
+    #[test]
+    fn test_changed_blocks_are_served() {
+        let store = flat_store();
+        let position = Position::new(17, -61, -3);
+        assert_eq!(
+            store.set_block(position, BlockState::AIR).unwrap(),
+            Some(BlockState(GRASS_BLOCK))
+        );
+        assert_eq!(store.get_block(position).unwrap(), Some(BlockState::AIR));
+        assert_eq!(
+            store
+                .set_block(Position::new(0, 320, 0), BlockState(1))
+                .unwrap(),
+            None
+        );
+
+        let packet = store.provide_chunk(ChunkPosition::new(1, -1));
+        let served = ChunkData::from_packet(&packet).unwrap();
+        assert_eq!(served.get_block(1, 3, 13), Some(BlockState::AIR));
+        assert_eq!(served.get_block(2, 3, 13), Some(BlockState(GRASS_BLOCK)));
+        assert_eq!(served.sections[0].block_count(), 4 * 256 - 1);
+
+        // Chunks that were never loaded come from the provider
+        let generated = store.provide_chunk(ChunkPosition::new(5, 5));
+        assert_eq!(
+            generated,
+            FlatWorldGenerator::default().provide_chunk(ChunkPosition::new(5, 5))
+        );
+        assert!(!store.is_loaded(ChunkPosition::new(5, 5)));
+    }
+
+    #[test]
+    fn test_chunks_are_saved() {
+        let directory =
+            std::env::temp_dir().join(format!("obsidium-{}-chunk-store", std::process::id()));
+        let storage =
+            || ChunkStorage::new(&directory, PaletteNames::new(&GameData::load().unwrap()));
+        let position = Position::new(17, -61, -3);
+        let store = flat_store().with_storage(storage());
+        store.set_block(position, BlockState::AIR).unwrap();
+        store.save().unwrap();
+
+        // A new store finds the saved chunk, both for blocks and for clients
+        let store = flat_store().with_storage(storage());
+        let packet = store.provide_chunk(ChunkPosition::new(1, -1));
+        assert!(store.is_loaded(ChunkPosition::new(1, -1)));
+        let served = ChunkData::from_packet(&packet).unwrap();
+        assert_eq!(served.get_block(1, 3, 13), Some(BlockState::AIR));
+        assert_eq!(store.get_block(position).unwrap(), Some(BlockState::AIR));
+        store.provide_chunk(ChunkPosition::new(5, 5));
+        assert!(!store.is_loaded(ChunkPosition::new(5, 5)));
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
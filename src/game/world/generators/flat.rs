@@ -0,0 +1,244 @@
+//! Flat world generator
+//!
+//! Produces chunks made of horizontal layers of blocks stacked from the
+//! bottom of the world, like the vanilla superflat world type.
+
+use super::ChunkProvider;
+use crate::game::world::chunk::{CHUNK_HEIGHT, CHUNK_SIZE};
+use crate::game::world::heightmap::{COLUMN_COUNT, HeightMap};
+use crate::game::world::{BlockState, ChunkPosition};
+use crate::protocol::packets::play::chunk_data::{
+    ChunkDataPacket, ChunkSection, LIGHT_ARRAY_LENGTH, LightData, PalettedContainer,
+    PalettedContainerKind,
+};
+
+/// Block state ID of `minecraft:bedrock`
+pub const BEDROCK: u16 = 85;
+
+/// Block state ID of `minecraft:dirt`
+pub const DIRT: u16 = 10;
+
+/// Block state ID of `minecraft:grass_block[snowy=false]`
+pub const GRASS_BLOCK: u16 = 9;
+
+/// Biome ID used for every section (the first entry of the biome registry)
+const BIOME: i32 = 0;
+
+/// Sky light level in the open
+const FULL_SKY_LIGHT: u8 = 15;
+
+/// Generates layered flat chunks
+///
+/// Every chunk of a flat world is the same, so the chunk is built once and
+/// only its position changes.
+#[derive(Debug, Clone)]
+pub struct FlatWorldGenerator {
+    /// Block state ID and thickness of each layer, from the bottom up
+    layers: Vec<(u16, u8)>,
+    /// Chunk at the origin
+    template: ChunkDataPacket,
+}
+
+impl FlatWorldGenerator {
+    /// Create a generator stacking the given layers of block state ID and
+    /// thickness from the bottom of the world
+    ///
+    /// Layers reaching past the top of the world are cut off.
+    pub fn new(layers: Vec<(u16, u8)>) -> Self {
+        let template = Self::build(&layers);
+        Self { layers, template }
+    }
+
+    /// Layers of the world, from the bottom up
+    pub fn layers(&self) -> &[(u16, u8)] {
+        &self.layers
+    }
+
+    /// Block state at each Y index of a column
+    fn column(layers: &[(u16, u8)]) -> Vec<u16> {
+        let mut column: Vec<u16> = layers
+            .iter()
+            .flat_map(|&(block, height)| std::iter::repeat_n(block, height as usize))
+            .take(CHUNK_HEIGHT)
+            .collect();
+        column.resize(CHUNK_HEIGHT, BlockState::AIR.id());
+        column
+    }
+
+    /// Build the chunk at the origin
+    fn build(layers: &[(u16, u8)]) -> ChunkDataPacket {
+        let column = Self::column(layers);
+        let surface = column
+            .iter()
+            .rposition(|&block| !BlockState(block).is_air())
+            .map_or(0, |y| y + 1);
+
+        let sections = column
+            .chunks(CHUNK_SIZE)
+            .map(|section_column| {
+                // Entries are ordered by Y, then Z, then X
+                let entries: Vec<i32> = section_column
+                    .iter()
+                    .flat_map(|&block| std::iter::repeat_n(i32::from(block), COLUMN_COUNT))
+                    .collect();
+                let block_count = section_column
+                    .iter()
+                    .filter(|&&block| !BlockState(block).is_air())
+                    .count()
+                    * COLUMN_COUNT;
+                ChunkSection {
+                    block_count: block_count as i16,
+                    block_states: PalettedContainer::from_entries(
+                        &entries,
+                        PalettedContainerKind::BlockStates,
+                    ),
+                    biomes: PalettedContainer::single_value(BIOME),
+                }
+            })
+            .collect();
+
+        let height = surface as i32;
+        let heightmap = HeightMap {
+            motion_blocking: [height; COLUMN_COUNT],
+            world_surface: [height; COLUMN_COUNT],
+        };
+
+        ChunkDataPacket {
+            chunk_x: 0,
+            chunk_z: 0,
+            heightmaps: heightmap.to_heightmaps(),
+            sections,
+            block_entities: Vec::new(),
+            light: Self::light(surface),
+        }
+    }
+
+    /// Light data for a column whose highest block is below `surface`: full
+    /// sky light above it, darkness below it and no block light
+    fn light(surface: usize) -> LightData {
+        let section_count = CHUNK_HEIGHT / CHUNK_SIZE;
+        // Light sections include one below and one above the world
+        let light_sections = section_count + 2;
+
+        let mut light = LightData::default();
+        for light_section in 0..light_sections {
+            light.empty_block_light_mask.set(light_section, true);
+
+            // Y index of the bottom of the section, which is negative for
+            // the section below the world
+            let bottom = light_section as i64 * CHUNK_SIZE as i64 - CHUNK_SIZE as i64;
+            if bottom + (CHUNK_SIZE as i64) <= surface as i64 {
+                light.empty_sky_light_mask.set(light_section, true);
+                continue;
+            }
+
+            let mut array = vec![0u8; LIGHT_ARRAY_LENGTH];
+            for (index, byte) in array.iter_mut().enumerate() {
+                // Two blocks per byte, the first in the low nibble
+                let y = bottom + (index * 2 / COLUMN_COUNT) as i64;
+                if y >= surface as i64 {
+                    *byte = FULL_SKY_LIGHT | (FULL_SKY_LIGHT << 4);
+                }
+            }
+            light.sky_light_mask.set(light_section, true);
+            light.sky_light.push(array);
+        }
+        light
+    }
+}
+
+impl Default for FlatWorldGenerator {
+    /// The classic superflat layers: bedrock, two layers of dirt and grass
+    fn default() -> Self {
+        Self::new(vec![(BEDROCK, 1), (DIRT, 2), (GRASS_BLOCK, 1)])
+    }
+}
+
+impl ChunkProvider for FlatWorldGenerator {
+    fn provide_chunk(&self, position: ChunkPosition) -> ChunkDataPacket {
+        ChunkDataPacket {
+            chunk_x: position.x,
+            chunk_z: position.z,
+            ..self.template.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::packets::Packet;
+    use crate::protocol::packets::play::chunk_data::{HeightmapKind, Palette, SECTION_BLOCK_COUNT};
+    use std::io::Cursor;
+
+    #[test]
+    fn test_default_layers() {
+        let packet = FlatWorldGenerator::default().provide_chunk(ChunkPosition::new(3, -7));
+        assert_eq!((packet.chunk_x, packet.chunk_z), (3, -7));
+        assert_eq!(packet.sections.len(), 24);
+
+        // Four layers in the bottom section, air above
+        let bottom = &packet.sections[0];
+        assert_eq!(bottom.block_count, 4 * 256);
+        assert_eq!(
+            bottom.block_states.palette,
+            Palette::Indirect(vec![85, 10, 9, 0])
+        );
+        assert!(
+            packet.sections[1..]
+                .iter()
+                .all(|section| section == &ChunkSection::filled(0, 0, 0))
+        );
+
+        let world_surface = &packet.heightmaps[0];
+        assert_eq!(world_surface.kind, HeightmapKind::WorldSurface);
+        assert_eq!(
+            world_surface.data[0],
+            (0..7).map(|i| 4i64 << (9 * i)).sum::<i64>()
+        );
+
+        let decoded = {
+            let mut buffer = Vec::new();
+            packet.write(&mut buffer).unwrap();
+            ChunkDataPacket::read(&mut Cursor::new(buffer)).unwrap()
+        };
+        assert_eq!(decoded, packet);
+    }
+
+    #[test]
+    fn test_sky_light_above_surface() {
+        // Surface at Y index 20, in the second section
+        let packet = FlatWorldGenerator::new(vec![(1, 20)]).provide_chunk(ChunkPosition::new(0, 0));
+        let light = &packet.light;
+
+        // Below the world and the fully buried first section are dark
+        assert!(light.empty_sky_light_mask.get(0));
+        assert!(light.empty_sky_light_mask.get(1));
+        assert!(light.sky_light_mask.get(2));
+        assert_eq!(light.sky_light.len(), 24);
+
+        // Y index 19 is stone, 20 is open sky
+        let second = &light.sky_light[0];
+        assert_eq!(second[3 * 128], 0);
+        assert_eq!(second[4 * 128], 0xFF);
+        assert!(light.sky_light[23].iter().all(|&byte| byte == 0xFF));
+        assert_eq!(
+            packet.sections[1].block_count as usize,
+            SECTION_BLOCK_COUNT / 4
+        );
+    }
+
+    #[test]
+    fn test_layers_cut_off_at_top() {
+        let generator = FlatWorldGenerator::new(vec![(1, 255), (2, 255)]);
+        let packet = generator.provide_chunk(ChunkPosition::new(0, 0));
+        assert!(
+            packet
+                .sections
+                .iter()
+                .all(|section| section.block_count == 4096)
+        );
+        assert!(packet.light.sky_light_mask.get(25));
+        assert_eq!(packet.light.sky_light.len(), 1);
+    }
+}
@@ -0,0 +1,19 @@
+//! World generators
+//!
+//! Generators produce the chunks sent to clients for positions that have not
+//! been loaded from storage.
+
+pub mod flat;
+pub mod void;
+
+pub use flat::FlatWorldGenerator;
+pub use void::VoidWorldChunkProvider;
+
+use super::ChunkPosition;
+use crate::protocol::packets::play::ChunkDataPacket;
+
+/// Produces chunk data for chunk positions
+pub trait ChunkProvider: Send + Sync {
+    /// Produce the chunk at the given position
+    fn provide_chunk(&self, position: ChunkPosition) -> ChunkDataPacket;
+}
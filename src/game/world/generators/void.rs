@@ -0,0 +1,112 @@
+//! Void world generator
+//!
+//! Produces chunks that contain nothing but air, under full sky light.
+
+use super::ChunkProvider;
+use crate::game::world::chunk::{CHUNK_HEIGHT, CHUNK_SIZE};
+use crate::game::world::{BlockState, ChunkPosition};
+use crate::protocol::packets::play::chunk_data::{
+    ChunkDataPacket, ChunkSection, Heightmap, HeightmapKind, LIGHT_ARRAY_LENGTH, LightData,
+};
+use crate::protocol::types::BitSet;
+
+/// Biome ID used for every section (the first entry of the biome registry)
+const BIOME: i32 = 0;
+
+/// Generates all-air chunks
+///
+/// The client expects every section of the dimension to be present, so each
+/// chunk carries one empty section per 16 blocks of world height.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VoidWorldChunkProvider;
+
+impl VoidWorldChunkProvider {
+    /// Create a new void world chunk provider
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Number of sections in a chunk column
+    pub fn section_count() -> usize {
+        CHUNK_HEIGHT / CHUNK_SIZE
+    }
+
+    /// Light data for an empty column: full sky light, no block light
+    fn light() -> LightData {
+        // Light masks include one section below and one above the world
+        let light_sections = Self::section_count() + 2;
+
+        let mut sky_light_mask = BitSet::new();
+        let mut empty_block_light_mask = BitSet::new();
+        for section in 0..light_sections {
+            sky_light_mask.set(section, true);
+            empty_block_light_mask.set(section, true);
+        }
+
+        LightData {
+            sky_light_mask,
+            empty_block_light_mask,
+            sky_light: vec![vec![0xFF; LIGHT_ARRAY_LENGTH]; light_sections],
+            ..LightData::default()
+        }
+    }
+}
+
+impl ChunkProvider for VoidWorldChunkProvider {
+    fn provide_chunk(&self, position: ChunkPosition) -> ChunkDataPacket {
+        ChunkDataPacket {
+            chunk_x: position.x,
+            chunk_z: position.z,
+            heightmaps: vec![
+                Heightmap::empty(HeightmapKind::WorldSurface, CHUNK_HEIGHT as u32),
+                Heightmap::empty(HeightmapKind::MotionBlocking, CHUNK_HEIGHT as u32),
+            ],
+            sections: vec![
+                ChunkSection::filled(i32::from(BlockState::AIR.id()), BIOME, 0);
+                Self::section_count()
+            ],
+            block_entities: Vec::new(),
+            light: Self::light(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::packets::Packet;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_void_chunk() {
+        let packet = VoidWorldChunkProvider::new().provide_chunk(ChunkPosition::new(2, -1));
+        assert_eq!((packet.chunk_x, packet.chunk_z), (2, -1));
+        assert_eq!(packet.sections.len(), 24);
+        assert!(
+            packet
+                .sections
+                .iter()
+                .all(|section| section.block_count == 0)
+        );
+        assert_eq!(packet.light.sky_light.len(), 26);
+        assert!(packet.light.sky_light_mask.get(25));
+        assert!(!packet.light.sky_light_mask.get(26));
+
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+
+        // Position, two heightmaps of 37 longs each, then the section data:
+        // 24 sections of block count, block palette and biome palette
+        let heightmaps_length = 1 + 2 * (1 + 1 + 37 * 8);
+        let sections_start = 8 + heightmaps_length;
+        // The data length (24 * 6 = 144) takes two VarInt bytes
+        assert_eq!(&buffer[sections_start..sections_start + 2], &[0x90, 0x01]);
+        assert_eq!(
+            &buffer[sections_start + 2..sections_start + 8],
+            &[0, 0, 0, 0, 0, 0]
+        );
+
+        let decoded = ChunkDataPacket::read(&mut Cursor::new(buffer)).unwrap();
+        assert_eq!(decoded, packet);
+    }
+}
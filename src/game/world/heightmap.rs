@@ -0,0 +1,181 @@
+//! Heightmaps
+//!
+//! Clients use heightmaps to decide where rain falls and how to render
+//! distant terrain, so every chunk sent carries the height of each of its
+//! 16x16 columns.
+
+use super::chunk::{BlockState, CHUNK_HEIGHT, CHUNK_SIZE, Chunk};
+use crate::protocol::packets::play::chunk_data::{Heightmap, HeightmapKind};
+
+/// Number of columns in a chunk
+pub const COLUMN_COUNT: usize = CHUNK_SIZE * CHUNK_SIZE;
+
+/// The heights of every column of a chunk
+///
+/// Each height is one more than the Y index of the highest matching block,
+/// counted from the bottom of the world, and 0 when the column has none.
+/// Columns are indexed by `z * 16 + x`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeightMap {
+    /// Highest block that blocks motion or holds a fluid
+    pub motion_blocking: [i32; COLUMN_COUNT],
+    /// Highest block that is not air
+    pub world_surface: [i32; COLUMN_COUNT],
+}
+
+impl HeightMap {
+    /// Compute the heightmaps of a chunk
+    ///
+    /// Block states carry no collision data yet, so every block other than
+    /// air counts as motion blocking.
+    pub fn compute(chunk: &Chunk) -> Self {
+        Self::compute_with(chunk, |block| !block.is_air())
+    }
+
+    /// Compute the heightmaps of a chunk, deciding which block states block
+    /// motion with the given function
+    pub fn compute_with(chunk: &Chunk, blocks_motion: impl Fn(BlockState) -> bool) -> Self {
+        Self::from_blocks(
+            |x, y, z| {
+                chunk
+                    .get_block(x, y, z)
+                    .and_then(|id| u16::try_from(id).ok())
+                    .map_or(BlockState::AIR, BlockState)
+            },
+            blocks_motion,
+        )
+    }
+
+    /// Compute heightmaps from the block state at each local `(x, y, z)` of
+    /// a chunk, with Y counted from the bottom of the world
+    pub fn from_blocks(
+        block_at: impl Fn(usize, usize, usize) -> BlockState,
+        blocks_motion: impl Fn(BlockState) -> bool,
+    ) -> Self {
+        let mut heightmap = Self {
+            motion_blocking: [0; COLUMN_COUNT],
+            world_surface: [0; COLUMN_COUNT],
+        };
+
+        for z in 0..CHUNK_SIZE {
+            for x in 0..CHUNK_SIZE {
+                let column = z * CHUNK_SIZE + x;
+                // Scan down from the top until both heights are found
+                for y in (0..CHUNK_HEIGHT).rev() {
+                    let block = block_at(x, y, z);
+                    if block.is_air() {
+                        continue;
+                    }
+                    if heightmap.world_surface[column] == 0 {
+                        heightmap.world_surface[column] = y as i32 + 1;
+                    }
+                    if blocks_motion(block) {
+                        heightmap.motion_blocking[column] = y as i32 + 1;
+                        break;
+                    }
+                }
+            }
+        }
+        heightmap
+    }
+
+    /// Pack the heights of one heightmap into longs, using as many bits per
+    /// height as a world of the given height needs
+    ///
+    /// Heights never span two longs, so a 384 block world packs seven 9-bit
+    /// heights per long into 37 longs.
+    pub fn pack(heights: &[i32; COLUMN_COUNT], world_height: u32) -> Vec<i64> {
+        let bits = Heightmap::bits_per_entry(world_height) as usize;
+        let per_long = 64 / bits;
+        let mask = (1u64 << bits) - 1;
+
+        let mut data = vec![0i64; Heightmap::data_length(world_height)];
+        for (index, &height) in heights.iter().enumerate() {
+            let shift = (index % per_long) * bits;
+            data[index / per_long] |= ((height as u64 & mask) << shift) as i64;
+        }
+        data
+    }
+
+    /// Build the heightmaps sent in a chunk data packet
+    pub fn to_heightmaps(&self) -> Vec<Heightmap> {
+        let world_height = CHUNK_HEIGHT as u32;
+        vec![
+            Heightmap {
+                kind: HeightmapKind::WorldSurface,
+                data: Self::pack(&self.world_surface, world_height),
+            },
+            Heightmap {
+                kind: HeightmapKind::MotionBlocking,
+                data: Self::pack(&self.motion_blocking, world_height),
+            },
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::world::ChunkPosition;
+
+    #[test]
+    fn test_empty_chunk() {
+        let heightmap = HeightMap::compute(&Chunk::new(ChunkPosition::new(0, 0)));
+        assert!(heightmap.world_surface.iter().all(|&height| height == 0));
+        assert_eq!(
+            heightmap.to_heightmaps(),
+            [
+                Heightmap::empty(HeightmapKind::WorldSurface, CHUNK_HEIGHT as u32),
+                Heightmap::empty(HeightmapKind::MotionBlocking, CHUNK_HEIGHT as u32),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_flat_chunk() {
+        // Terrain topped at Y index 63
+        let mut chunk = Chunk::new(ChunkPosition::new(0, 0));
+        for x in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                for y in 0..64 {
+                    chunk.set_block(x, y, z, 1);
+                }
+            }
+        }
+        let heightmap = HeightMap::compute(&chunk);
+        assert!(heightmap.world_surface.iter().all(|&height| height == 64));
+        assert_eq!(heightmap.motion_blocking, heightmap.world_surface);
+    }
+
+    #[test]
+    fn test_non_blocking_blocks() {
+        let mut chunk = Chunk::new(ChunkPosition::new(0, 0));
+        chunk.set_block(3, 10, 2, 1);
+        // A sapling on top only counts for the world surface
+        chunk.set_block(3, 11, 2, 6);
+        chunk.set_block(0, 383, 0, 1);
+
+        let heightmap = HeightMap::compute_with(&chunk, |block| block == BlockState(1));
+        let column = 2 * 16 + 3;
+        assert_eq!(heightmap.world_surface[column], 12);
+        assert_eq!(heightmap.motion_blocking[column], 11);
+        assert_eq!(heightmap.world_surface[0], 384);
+        assert_eq!(heightmap.world_surface[1], 0);
+    }
+
+    #[test]
+    fn test_pack() {
+        let mut heights = [0; COLUMN_COUNT];
+        heights[0] = 384;
+        heights[1] = 1;
+        heights[7] = 5;
+        heights[255] = 2;
+
+        let data = HeightMap::pack(&heights, 384);
+        assert_eq!(data.len(), 37);
+        assert_eq!(data[0], 384 | (1 << 9));
+        assert_eq!(data[1], 5);
+        // The last long holds the 256th height alone
+        assert_eq!(data[36], 2 << 27);
+    }
+}
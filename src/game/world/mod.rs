@@ -3,11 +3,23 @@
 //! This module handles world state, chunks, blocks, and world generation.
 
 pub mod chunk;
+pub mod chunk_cache;
+pub mod chunk_store;
+pub mod generators;
+pub mod heightmap;
+pub mod palette;
 pub mod registry;
 
+pub use chunk::{BlockState, ChunkData, ChunkSection};
+pub use chunk_cache::ChunkCache;
+pub use chunk_store::ChunkStore;
+pub use heightmap::HeightMap;
+pub use palette::{LightArray, PalettedContainer};
+
+use crate::error::Result;
 use crate::game::entity::EntityManager;
 use crate::protocol::types::Position;
-use std::collections::HashMap;
+use std::sync::Arc;
 
 /// Represents a Minecraft world
 pub struct World {
@@ -15,12 +27,23 @@ pub struct World {
     pub name: String,
     /// World seed
     pub seed: i64,
-    /// Loaded chunks
-    chunks: HashMap<ChunkPosition, chunk::Chunk>,
+    /// Loaded chunks, shared with the connections sending them
+    chunks: Arc<ChunkStore>,
     /// Entity manager for this world
     entities: EntityManager,
     /// World spawn position
     spawn_position: Position,
+    /// Block changes made since the last [`World::take_mutations`]
+    mutations: Vec<WorldMutation>,
+}
+
+/// A block changed in the world
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorldMutation {
+    /// Position of the changed block
+    pub position: Position,
+    /// New block state ID
+    pub block_id: u32,
 }
 
 /// Chunk position (x, z coordinates)
@@ -58,14 +81,15 @@ impl ChunkPosition {
 }
 
 impl World {
-    /// Create a new world
-    pub fn new(name: String, seed: i64) -> Self {
+    /// Create a new world made of the chunks of a store
+    pub fn new(name: String, seed: i64, chunks: Arc<ChunkStore>) -> Self {
         Self {
             name,
             seed,
-            chunks: HashMap::new(),
+            chunks,
             entities: EntityManager::new(),
             spawn_position: Position::new(0, 64, 0),
+            mutations: Vec::new(),
         }
     }
 
@@ -89,44 +113,10 @@ impl World {
         self.spawn_position = position;
     }
 
-    /// Load a chunk
-    pub fn load_chunk(&mut self, position: ChunkPosition) -> &chunk::Chunk {
-        self.chunks.entry(position).or_insert_with(|| {
-            // For now, generate a simple flat chunk
-            // In a real implementation, this would use world generation
-            chunk::Chunk::generate_flat(position)
-        })
-    }
-
-    /// Unload a chunk
-    pub fn unload_chunk(&mut self, position: ChunkPosition) {
-        self.chunks.remove(&position);
-        tracing::debug!("Unloaded chunk at {:?}", position);
-    }
-
-    /// Get a chunk if it's loaded
-    pub fn get_chunk(&self, position: ChunkPosition) -> Option<&chunk::Chunk> {
-        self.chunks.get(&position)
-    }
-
-    /// Get a mutable reference to a chunk if it's loaded
-    pub fn get_chunk_mut(&mut self, position: ChunkPosition) -> Option<&mut chunk::Chunk> {
-        self.chunks.get_mut(&position)
-    }
-
-    /// Check if a chunk is loaded
-    pub fn is_chunk_loaded(&self, position: ChunkPosition) -> bool {
-        self.chunks.contains_key(&position)
-    }
-
-    /// Get all loaded chunks
-    pub fn loaded_chunks(&self) -> impl Iterator<Item = (ChunkPosition, &chunk::Chunk)> {
-        self.chunks.iter().map(|(pos, chunk)| (*pos, chunk))
-    }
-
-    /// Get loaded chunk count
-    pub fn loaded_chunk_count(&self) -> usize {
-        self.chunks.len()
+    /// Loaded chunks of the world, which are also the chunks sent to
+    /// clients
+    pub fn chunks(&self) -> &ChunkStore {
+        &self.chunks
     }
 
     /// Get the entity manager
@@ -139,35 +129,32 @@ impl World {
         &mut self.entities
     }
 
-    /// Get block at position
-    pub fn get_block(&self, position: Position) -> Option<u32> {
-        let chunk_pos = ChunkPosition::from_world_coords(position.x as f64, position.z as f64);
-        let chunk = self.get_chunk(chunk_pos)?;
-
-        // Convert world coordinates to chunk-local coordinates
-        let local_x = (position.x - chunk_pos.world_x()) as usize;
-        let local_z = (position.z - chunk_pos.world_z()) as usize;
-        let y = position.y as usize;
-
-        chunk.get_block(local_x, y, local_z)
+    /// Get the block at a position, or `None` above or below the world
+    pub fn get_block(&self, position: Position) -> Result<Option<BlockState>> {
+        self.chunks.get_block(position)
+    }
+
+    /// Set the block at a position, returning whether it is inside the world
+    ///
+    /// Changing a block records a mutation for the tick loop to send.
+    pub fn set_block(&mut self, position: Position, block: BlockState) -> Result<bool> {
+        match self.chunks.set_block(position, block)? {
+            Some(previous) => {
+                if previous != block {
+                    self.mutations.push(WorldMutation {
+                        position,
+                        block_id: u32::from(block.id()),
+                    });
+                }
+                Ok(true)
+            }
+            None => Ok(false),
+        }
     }
 
-    /// Set block at position
-    pub fn set_block(&mut self, position: Position, block_id: u32) -> bool {
-        let chunk_pos = ChunkPosition::from_world_coords(position.x as f64, position.z as f64);
-
-        // Load chunk if not loaded
-        self.load_chunk(chunk_pos);
-
-        if let Some(chunk) = self.get_chunk_mut(chunk_pos) {
-            let local_x = (position.x - chunk_pos.world_x()) as usize;
-            let local_z = (position.z - chunk_pos.world_z()) as usize;
-            let y = position.y as usize;
-
-            chunk.set_block(local_x, y, local_z, block_id)
-        } else {
-            false
-        }
+    /// Take the block changes made since the last call, oldest first
+    pub fn take_mutations(&mut self) -> Vec<WorldMutation> {
+        std::mem::take(&mut self.mutations)
     }
 
     /// Update the world
@@ -182,3 +169,45 @@ impl World {
         // - Chunk generation/unloading based on player positions
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::game::world::generators::VoidWorldChunkProvider;
+
+    #[test]
+    fn test_set_block_records_mutations() {
+        let chunks = ChunkStore::new(Box::new(VoidWorldChunkProvider::new()));
+        let mut world = World::new("world".to_string(), 0, Arc::new(chunks));
+        assert!(
+            world
+                .set_block(Position::new(1, 70, -3), BlockState(1))
+                .unwrap()
+        );
+        // Setting the same block again changes nothing
+        assert!(
+            world
+                .set_block(Position::new(1, 70, -3), BlockState(1))
+                .unwrap()
+        );
+        assert!(
+            !world
+                .set_block(Position::new(0, 1000, 0), BlockState(1))
+                .unwrap()
+        );
+        assert_eq!(
+            world.get_block(Position::new(1, 70, -3)).unwrap(),
+            Some(BlockState(1))
+        );
+
+        assert_eq!(
+            world.take_mutations(),
+            [WorldMutation {
+                position: Position::new(1, 70, -3),
+                block_id: 1,
+            }]
+        );
+        assert!(world.take_mutations().is_empty());
+    }
+}
@@ -0,0 +1,479 @@
+//! Paletted block and biome storage
+//!
+//! A chunk section holds 4096 block states and 64 biomes, but usually only a
+//! handful of distinct values. A [`PalettedContainer`] stores each distinct
+//! value once and packs small indices into it, switching to storing values
+//! directly once there are too many of them.
+
+use crate::error::{Result, ServerError};
+use crate::protocol::packets::play::chunk_data::{
+    LIGHT_ARRAY_LENGTH, Palette, PalettedContainer as WireContainer, PalettedContainerKind,
+};
+
+/// A value stored in a paletted container
+pub trait PaletteEntry: Copy + Eq + Default {
+    /// What the container stores on the wire
+    const KIND: PalettedContainerKind;
+    /// Smallest bits per entry of an indirect palette
+    const MIN_INDIRECT_BITS: u8;
+    /// Largest bits per entry of an indirect palette
+    const MAX_INDIRECT_BITS: u8;
+    /// Bits per entry of values sent directly
+    const DIRECT_BITS: u8;
+
+    /// Registry ID of the value
+    fn to_id(self) -> i32;
+
+    /// Value of a registry ID, if it fits
+    fn from_id(id: i32) -> Option<Self>;
+}
+
+impl PaletteEntry for u16 {
+    const KIND: PalettedContainerKind = PalettedContainerKind::BlockStates;
+    const MIN_INDIRECT_BITS: u8 = 4;
+    const MAX_INDIRECT_BITS: u8 = 8;
+    const DIRECT_BITS: u8 = 15;
+
+    fn to_id(self) -> i32 {
+        i32::from(self)
+    }
+
+    fn from_id(id: i32) -> Option<Self> {
+        u16::try_from(id).ok()
+    }
+}
+
+impl PaletteEntry for u8 {
+    const KIND: PalettedContainerKind = PalettedContainerKind::Biomes;
+    const MIN_INDIRECT_BITS: u8 = 1;
+    const MAX_INDIRECT_BITS: u8 = 3;
+    const DIRECT_BITS: u8 = 7;
+
+    fn to_id(self) -> i32 {
+        i32::from(self)
+    }
+
+    fn from_id(id: i32) -> Option<Self> {
+        u8::try_from(id).ok()
+    }
+}
+
+/// Values of a chunk section, stored as indices into a palette or directly
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PalettedContainer<T> {
+    /// Packed indices into a list of distinct values
+    Indirect {
+        /// Number of values
+        len: usize,
+        /// Distinct values, in the order they were first stored
+        palette: Vec<T>,
+        /// Bits used by each index, 0 while the palette has one value
+        bits_per_entry: u8,
+        /// Packed indices; indices never span two longs
+        data: Vec<u64>,
+    },
+    /// One value per entry
+    Direct(Vec<T>),
+}
+
+impl<T: PaletteEntry> PalettedContainer<T> {
+    /// Create a container of `len` copies of a value
+    pub fn filled(len: usize, value: T) -> Self {
+        PalettedContainer::Indirect {
+            len,
+            palette: vec![value],
+            bits_per_entry: 0,
+            data: Vec::new(),
+        }
+    }
+
+    /// Number of values
+    pub fn len(&self) -> usize {
+        match self {
+            PalettedContainer::Indirect { len, .. } => *len,
+            PalettedContainer::Direct(values) => values.len(),
+        }
+    }
+
+    /// Whether the container has no values
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get a value
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn get(&self, index: usize) -> T {
+        match self {
+            PalettedContainer::Indirect {
+                len,
+                palette,
+                bits_per_entry,
+                data,
+            } => {
+                assert!(index < *len, "Index {} out of bounds", index);
+                palette[unpack(data, *bits_per_entry, index)]
+            }
+            PalettedContainer::Direct(values) => values[index],
+        }
+    }
+
+    /// Replace a value
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set(&mut self, index: usize, value: T) {
+        match self {
+            PalettedContainer::Indirect {
+                len,
+                palette,
+                bits_per_entry,
+                data,
+            } => {
+                assert!(index < *len, "Index {} out of bounds", index);
+                let palette_index = match palette.iter().position(|&entry| entry == value) {
+                    Some(palette_index) => palette_index,
+                    None => {
+                        palette.push(value);
+                        palette.len() - 1
+                    }
+                };
+
+                let needed = bits_for(palette.len()).max(T::MIN_INDIRECT_BITS);
+                if palette.len() > 1 && needed > *bits_per_entry {
+                    if needed > T::MAX_INDIRECT_BITS {
+                        let mut values: Vec<T> = self.values();
+                        values[index] = value;
+                        *self = PalettedContainer::Direct(values);
+                        return;
+                    }
+                    *data = repack(data, *bits_per_entry, needed, *len);
+                    *bits_per_entry = needed;
+                }
+                if *bits_per_entry > 0 {
+                    pack(data, *bits_per_entry, index, palette_index);
+                }
+            }
+            PalettedContainer::Direct(values) => values[index] = value,
+        }
+    }
+
+    /// Every value, in index order
+    pub fn values(&self) -> Vec<T> {
+        (0..self.len()).map(|index| self.get(index)).collect()
+    }
+
+    /// Encode the container as saved to disk: its distinct values, and
+    /// unless there is only one, the index of each value packed with as few
+    /// bits as the palette allows
+    ///
+    /// Unlike on the wire, saved containers always have a palette.
+    pub fn to_saved(&self) -> (Vec<T>, Vec<i64>) {
+        let values = self.values();
+        let mut palette = Vec::new();
+        for &value in &values {
+            if !palette.contains(&value) {
+                palette.push(value);
+            }
+        }
+        if palette.len() <= 1 {
+            return (palette, Vec::new());
+        }
+
+        let bits = bits_for(palette.len()).max(T::MIN_INDIRECT_BITS);
+        let mut data = vec![0u64; WireContainer::data_length(bits, values.len())];
+        for (index, value) in values.iter().enumerate() {
+            let palette_index = palette.iter().position(|entry| entry == value);
+            pack(&mut data, bits, index, palette_index.unwrap_or_default());
+        }
+        (palette, data.into_iter().map(|long| long as i64).collect())
+    }
+
+    /// Decode a container of `len` values saved by
+    /// [`PalettedContainer::to_saved`]
+    pub fn from_saved(len: usize, palette: &[T], data: &[i64]) -> Result<Self> {
+        let Some(&first) = palette.first() else {
+            return Err(ServerError::Nbt("Empty palette".to_string()));
+        };
+        let mut container = Self::filled(len, first);
+        if palette.len() == 1 {
+            return Ok(container);
+        }
+
+        let bits = bits_for(palette.len()).max(T::MIN_INDIRECT_BITS);
+        if data.len() < WireContainer::data_length(bits, len) {
+            return Err(ServerError::Nbt(
+                "Paletted container data is too short".to_string(),
+            ));
+        }
+        let data: Vec<u64> = data.iter().map(|&long| long as u64).collect();
+        for index in 0..len {
+            let value = palette
+                .get(unpack(&data, bits, index))
+                .ok_or_else(|| ServerError::Nbt("Palette index out of bounds".to_string()))?;
+            container.set(index, *value);
+        }
+        Ok(container)
+    }
+
+    /// Encode the container for a chunk data packet
+    ///
+    /// Palette entries that are no longer used are still sent, which is
+    /// harmless.
+    pub fn to_wire(&self) -> WireContainer {
+        match self {
+            PalettedContainer::Indirect {
+                palette,
+                bits_per_entry: 0,
+                ..
+            } => WireContainer::single_value(palette[0].to_id()),
+            PalettedContainer::Indirect {
+                palette,
+                bits_per_entry,
+                data,
+                ..
+            } => WireContainer {
+                bits_per_entry: *bits_per_entry,
+                palette: Palette::Indirect(palette.iter().map(|entry| entry.to_id()).collect()),
+                data: data.iter().map(|&long| long as i64).collect(),
+            },
+            PalettedContainer::Direct(values) => {
+                let ids: Vec<i32> = values.iter().map(|value| value.to_id()).collect();
+                let mut data = vec![0u64; WireContainer::data_length(T::DIRECT_BITS, ids.len())];
+                for (index, &id) in ids.iter().enumerate() {
+                    pack(&mut data, T::DIRECT_BITS, index, id as usize);
+                }
+                WireContainer {
+                    bits_per_entry: T::DIRECT_BITS,
+                    palette: Palette::Direct,
+                    data: data.into_iter().map(|long| long as i64).collect(),
+                }
+            }
+        }
+    }
+
+    /// Decode a container received in a chunk data packet
+    pub fn from_wire(wire: &WireContainer) -> Result<Self> {
+        let len = T::KIND.entry_count();
+        let entry = |id: i32| {
+            T::from_id(id)
+                .ok_or_else(|| ServerError::Protocol(format!("Invalid palette entry: {}", id)))
+        };
+        let bits = wire.bits_per_entry;
+        if wire.data.len() < WireContainer::data_length(bits, len) {
+            return Err(ServerError::Protocol(
+                "Paletted container data is too short".to_string(),
+            ));
+        }
+        let data: Vec<u64> = wire.data.iter().map(|&long| long as u64).collect();
+
+        match &wire.palette {
+            Palette::SingleValue(id) => Ok(Self::filled(len, entry(*id)?)),
+            Palette::Indirect(ids) => {
+                let palette = ids
+                    .iter()
+                    .map(|&id| entry(id))
+                    .collect::<Result<Vec<_>>>()?;
+                if (0..len).any(|index| unpack(&data, bits, index) >= palette.len()) {
+                    return Err(ServerError::Protocol(
+                        "Palette index out of bounds".to_string(),
+                    ));
+                }
+                Ok(PalettedContainer::Indirect {
+                    len,
+                    palette,
+                    bits_per_entry: bits,
+                    data,
+                })
+            }
+            Palette::Direct => (0..len)
+                .map(|index| entry(unpack(&data, bits, index) as i32))
+                .collect::<Result<Vec<_>>>()
+                .map(PalettedContainer::Direct),
+        }
+    }
+}
+
+/// Bits needed to index a palette of the given length
+fn bits_for(palette_len: usize) -> u8 {
+    (usize::BITS - (palette_len.max(1) - 1).leading_zeros()) as u8
+}
+
+/// Read the entry at `index` from packed longs
+fn unpack(data: &[u64], bits: u8, index: usize) -> usize {
+    if bits == 0 {
+        return 0;
+    }
+    let per_long = 64 / bits as usize;
+    let mask = (1u64 << bits) - 1;
+    ((data[index / per_long] >> ((index % per_long) * bits as usize)) & mask) as usize
+}
+
+/// Write the entry at `index` into packed longs
+fn pack(data: &mut [u64], bits: u8, index: usize, value: usize) {
+    let per_long = 64 / bits as usize;
+    let shift = (index % per_long) * bits as usize;
+    let mask = ((1u64 << bits) - 1) << shift;
+    let long = &mut data[index / per_long];
+    *long = (*long & !mask) | (((value as u64) << shift) & mask);
+}
+
+/// Pack `len` entries again with more bits each
+fn repack(data: &[u64], old_bits: u8, new_bits: u8, len: usize) -> Vec<u64> {
+    let mut repacked = vec![0u64; WireContainer::data_length(new_bits, len)];
+    for index in 0..len {
+        pack(
+            &mut repacked,
+            new_bits,
+            index,
+            unpack(data, old_bits, index),
+        );
+    }
+    repacked
+}
+
+/// Light levels of a chunk section, 4 bits per block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LightArray(Vec<u8>);
+
+impl LightArray {
+    /// Create a section without light
+    pub fn dark() -> Self {
+        Self(vec![0; LIGHT_ARRAY_LENGTH])
+    }
+
+    /// Create a section with every block at light level 15
+    pub fn full() -> Self {
+        Self(vec![0xFF; LIGHT_ARRAY_LENGTH])
+    }
+
+    /// Get the light level of a block, indexed by `(y * 16 + z) * 16 + x`
+    pub fn get(&self, index: usize) -> u8 {
+        let byte = self.0[index / 2];
+        if index.is_multiple_of(2) {
+            byte & 0xF
+        } else {
+            byte >> 4
+        }
+    }
+
+    /// Set the light level of a block, indexed by `(y * 16 + z) * 16 + x`
+    pub fn set(&mut self, index: usize, level: u8) {
+        let byte = &mut self.0[index / 2];
+        if index.is_multiple_of(2) {
+            *byte = (*byte & 0xF0) | (level & 0xF);
+        } else {
+            *byte = (*byte & 0x0F) | ((level & 0xF) << 4);
+        }
+    }
+
+    /// Whether every block has light level 0
+    pub fn is_dark(&self) -> bool {
+        self.0.iter().all(|&byte| byte == 0)
+    }
+
+    /// The packed light levels, as sent on the wire
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Read packed light levels, which must cover a whole section
+    pub fn from_bytes(bytes: Vec<u8>) -> Option<Self> {
+        (bytes.len() == LIGHT_ARRAY_LENGTH).then_some(Self(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::packets::play::chunk_data::SECTION_BLOCK_COUNT;
+
+    #[test]
+    fn test_palette_grows() {
+        let mut container = PalettedContainer::filled(SECTION_BLOCK_COUNT, 0u16);
+        assert_eq!(container.get(4095), 0);
+        assert_eq!(container.to_wire(), WireContainer::single_value(0));
+
+        container.set(1, 9);
+        container.set(4095, 10);
+        assert_eq!(container.get(0), 0);
+        assert_eq!(container.get(1), 9);
+        assert_eq!(container.get(4095), 10);
+
+        let wire = container.to_wire();
+        assert_eq!(wire.bits_per_entry, 4);
+        assert_eq!(wire.palette, Palette::Indirect(vec![0, 9, 10]));
+        assert_eq!(wire.data[0], 0x10);
+        assert_eq!(
+            PalettedContainer::<u16>::from_wire(&wire).unwrap(),
+            container
+        );
+    }
+
+    #[test]
+    fn test_many_values_switch_to_direct() {
+        let mut container = PalettedContainer::filled(SECTION_BLOCK_COUNT, 0u16);
+        for index in 0..300 {
+            container.set(index, index as u16);
+        }
+        assert!(matches!(container, PalettedContainer::Direct(_)));
+        assert_eq!(container.get(299), 299);
+        assert_eq!(container.get(300), 0);
+
+        let wire = container.to_wire();
+        assert_eq!(wire.bits_per_entry, 15);
+        assert_eq!(
+            PalettedContainer::<u16>::from_wire(&wire).unwrap().values(),
+            container.values()
+        );
+    }
+
+    #[test]
+    fn test_biomes() {
+        let mut biomes = PalettedContainer::filled(64, 0u8);
+        biomes.set(63, 2);
+        let wire = biomes.to_wire();
+        assert_eq!(wire.bits_per_entry, 1);
+        assert_eq!(wire.data, [1 << 63]);
+
+        // Registry IDs that do not fit are rejected
+        let invalid = WireContainer::single_value(300);
+        assert!(PalettedContainer::<u8>::from_wire(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_saved_container() {
+        let mut container = PalettedContainer::filled(SECTION_BLOCK_COUNT, 0u16);
+        let (palette, data) = container.to_saved();
+        assert_eq!((palette, data.len()), (vec![0], 0));
+
+        for index in 0..40 {
+            container.set(index * 3, index as u16 + 1);
+        }
+        let (palette, data) = container.to_saved();
+        // 41 values need 6 bits, so 10 fit in each long
+        assert_eq!((palette.len(), data.len()), (41, 410));
+        let saved = PalettedContainer::from_saved(SECTION_BLOCK_COUNT, &palette, &data).unwrap();
+        assert_eq!(saved.values(), container.values());
+
+        assert!(PalettedContainer::<u16>::from_saved(SECTION_BLOCK_COUNT, &[], &[]).is_err());
+        assert!(PalettedContainer::from_saved(SECTION_BLOCK_COUNT, &palette, &data[..10]).is_err());
+        assert!(
+            PalettedContainer::from_saved(SECTION_BLOCK_COUNT, &[1u16, 2], &[-1; 256]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_light_array() {
+        let mut light = LightArray::dark();
+        assert!(light.is_dark());
+        light.set(0, 15);
+        light.set(1, 7);
+        assert_eq!(light.as_bytes()[0], 0x7F);
+        assert_eq!((light.get(0), light.get(1)), (15, 7));
+        assert_eq!(LightArray::full().get(4095), 15);
+    }
+}
@@ -16,6 +16,11 @@
 //! - [`game`] - Game logic including players, worlds, and entities
 //! - [`server`] - Core server implementation and orchestration
 //! - [`config`] - Configuration management
+//! - [`metrics`] - Traffic and player counters, served for Prometheus
+//! - [`rcon`] - Remote console running commands over the RCON protocol
+//! - [`query`] - Server statistics for monitoring tools over UDP
+//! - [`data`] - Vanilla game data such as the synchronized registries
+//! - [`storage`] - Persistent storage such as player data
 //!
 //! # Example
 //!
@@ -35,12 +40,17 @@
 #![deny(clippy::too_many_lines, missing_docs, clippy::panic)]
 
 pub mod config;
+pub mod data;
 pub mod error;
 pub mod favicon;
 pub mod game;
 pub mod logger;
+pub mod metrics;
 pub mod network;
 pub mod protocol;
+pub mod query;
+pub mod rcon;
 pub mod server;
+pub mod storage;
 
 pub use error::{Result, ServerError};
@@ -9,12 +9,37 @@ use obsidium::config::ServerConfig;
 use obsidium::error::ServerError;
 use obsidium::logger;
 use obsidium::server::MinecraftServer;
+use obsidium::server::bans::BAN_LIST_FILE;
+use obsidium::server::ops::OP_LIST_FILE;
+use obsidium::server::whitelist::WHITELIST_FILE;
+use std::path::Path;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     // Initialize logger
     logger::init();
 
+    let config = load_config()?;
+
+    // Create and run server
+    let server = MinecraftServer::new(config).await?;
+    server.run().await?;
+
+    Ok(())
+}
+
+/// Load the server configuration
+///
+/// `server.toml` is used when present; otherwise the configuration comes
+/// from `server.properties`, which is created with defaults if missing.
+fn load_config() -> Result<ServerConfig> {
+    let toml_path = Path::new("server.toml");
+    if toml_path.exists() {
+        let config = ServerConfig::load(toml_path)?;
+        tracing::info!("Loaded configuration from server.toml");
+        return Ok(config);
+    }
+
     // Try to load configuration from server.properties file
     let config = match ServerConfig::from_properties_file("server.properties") {
         Ok(config) => {
@@ -30,7 +55,11 @@ async fn main() -> Result<()> {
                 )
                 .with_max_players(999_999_999)
                 .with_compression_threshold(Some(256))
-                .with_favicon(Some("server-icon.png".to_string()));
+                .with_favicon(Some("server-icon.png".to_string()))
+                .with_world_directory(Some("world".into()))
+                .with_ban_list_file(Some(BAN_LIST_FILE.into()))
+                .with_whitelist_file(Some(WHITELIST_FILE.into()))
+                .with_op_list_file(Some(OP_LIST_FILE.into()));
 
             // Save the default configuration to server.properties
             if let Err(e) = config.save_properties_file("server.properties") {
@@ -52,12 +81,12 @@ async fn main() -> Result<()> {
                 .with_max_players(999_999_999)
                 .with_compression_threshold(Some(256))
                 .with_favicon(Some("server-icon.png".to_string()))
+                .with_world_directory(Some("world".into()))
+                .with_ban_list_file(Some(BAN_LIST_FILE.into()))
+                .with_whitelist_file(Some(WHITELIST_FILE.into()))
+                .with_op_list_file(Some(OP_LIST_FILE.into()))
         }
     };
 
-    // Create and run server
-    let server = MinecraftServer::new(config).await?;
-    server.run().await?;
-
-    Ok(())
+    Ok(config)
 }
@@ -0,0 +1,251 @@
+//! Server metrics
+//!
+//! Connections count the packets and bytes they move in a process-wide
+//! [`Metrics`], and a [`MetricsServer`] serves the counters over HTTP in the
+//! Prometheus text format, for monitoring systems to scrape from `/metrics`.
+
+use crate::error::{Result, ServerError};
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicI32, AtomicU64, Ordering};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Largest request head the metrics server reads before giving up
+const MAX_REQUEST_SIZE: usize = 8192;
+
+/// Counters describing the server's traffic and players
+#[derive(Debug, Default)]
+pub struct Metrics {
+    /// Packets read from clients
+    pub packets_received: AtomicU64,
+    /// Packets written to clients
+    pub packets_sent: AtomicU64,
+    /// Bytes read from clients, as sent over the network
+    pub bytes_received: AtomicU64,
+    /// Bytes written to clients, as sent over the network
+    pub bytes_sent: AtomicU64,
+    /// Players in the play state
+    pub player_count: AtomicI32,
+}
+
+/// Metrics of the whole process
+static GLOBAL: Metrics = Metrics::new();
+
+impl Metrics {
+    /// Create metrics with every counter at zero
+    pub const fn new() -> Self {
+        Self {
+            packets_received: AtomicU64::new(0),
+            packets_sent: AtomicU64::new(0),
+            bytes_received: AtomicU64::new(0),
+            bytes_sent: AtomicU64::new(0),
+            player_count: AtomicI32::new(0),
+        }
+    }
+
+    /// Metrics shared by every connection of the process
+    pub fn global() -> &'static Self {
+        &GLOBAL
+    }
+
+    /// Count a packet read from a client
+    pub fn record_packet_received(&self) {
+        self.packets_received.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count a packet written to a client
+    pub fn record_packet_sent(&self) {
+        self.packets_sent.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count bytes read from a client
+    pub fn record_bytes_received(&self, count: usize) {
+        self.bytes_received
+            .fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    /// Count bytes written to a client
+    pub fn record_bytes_sent(&self, count: usize) {
+        self.bytes_sent.fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    /// Count a player entering the play state
+    pub fn player_joined(&self) {
+        self.player_count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count a player in the play state leaving
+    pub fn player_left(&self) {
+        self.player_count.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// Format the metrics in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let counters = [
+            (
+                "obsidium_packets_received_total",
+                "Packets read from clients",
+                &self.packets_received,
+            ),
+            (
+                "obsidium_packets_sent_total",
+                "Packets written to clients",
+                &self.packets_sent,
+            ),
+            (
+                "obsidium_bytes_received_total",
+                "Bytes read from clients",
+                &self.bytes_received,
+            ),
+            (
+                "obsidium_bytes_sent_total",
+                "Bytes written to clients",
+                &self.bytes_sent,
+            ),
+        ];
+        let mut text = String::new();
+        for (name, help, counter) in counters {
+            let value = counter.load(Ordering::Relaxed);
+            let _ = write!(
+                text,
+                "# HELP {name} {help}\n# TYPE {name} counter\n{name} {value}\n"
+            );
+        }
+        let players = self.player_count.load(Ordering::Relaxed);
+        let _ = write!(
+            text,
+            "# HELP obsidium_players Players in the play state\n\
+             # TYPE obsidium_players gauge\nobsidium_players {players}\n"
+        );
+        text
+    }
+}
+
+/// Minimal HTTP server answering `GET /metrics`
+pub struct MetricsServer {
+    /// Socket accepting scrapes
+    listener: TcpListener,
+    /// Metrics served
+    metrics: &'static Metrics,
+}
+
+impl MetricsServer {
+    /// Listen for scrapes on an address
+    pub async fn bind(address: SocketAddr, metrics: &'static Metrics) -> Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(address).await?,
+            metrics,
+        })
+    }
+
+    /// Get the address the server is listening on
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        self.listener.local_addr().map_err(ServerError::from)
+    }
+
+    /// Answer scrapes until the task is aborted
+    pub async fn run(self) -> Result<()> {
+        loop {
+            let (stream, address) = self.listener.accept().await?;
+            let metrics = self.metrics;
+            tokio::spawn(async move {
+                if let Err(e) = respond(stream, metrics).await {
+                    tracing::debug!("Metrics request from {} failed: {}", address, e);
+                }
+            });
+        }
+    }
+}
+
+/// Read one request and answer it, then close the connection
+async fn respond(mut stream: TcpStream, metrics: &Metrics) -> Result<()> {
+    let mut request = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+        let count = stream.read(&mut chunk).await?;
+        if count == 0 || request.len() + count > MAX_REQUEST_SIZE {
+            return Err(ServerError::Protocol(
+                "Incomplete metrics request".to_string(),
+            ));
+        }
+        request.extend_from_slice(&chunk[..count]);
+    }
+
+    let request_line = String::from_utf8_lossy(&request);
+    let mut parts = request_line.split_whitespace();
+    let (status, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => ("200 OK", metrics.render()),
+        (Some("GET"), _) => ("404 Not Found", "Not found\n".to_string()),
+        _ => ("405 Method Not Allowed", "Method not allowed\n".to_string()),
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let metrics = Metrics::new();
+        metrics.record_packet_received();
+        metrics.record_bytes_received(10);
+        metrics.record_bytes_sent(25);
+        metrics.player_joined();
+        metrics.player_joined();
+        metrics.player_left();
+
+        let text = metrics.render();
+        assert!(text.contains(
+            "# HELP obsidium_packets_received_total Packets read from clients\n\
+             # TYPE obsidium_packets_received_total counter\n\
+             obsidium_packets_received_total 1\n"
+        ));
+        assert!(text.contains("\nobsidium_packets_sent_total 0\n"));
+        assert!(text.contains("\nobsidium_bytes_received_total 10\n"));
+        assert!(text.contains("\nobsidium_bytes_sent_total 25\n"));
+        assert!(text.ends_with("# TYPE obsidium_players gauge\nobsidium_players 1\n"));
+    }
+
+    /// Send a request to a metrics server and read the whole response
+    async fn request(address: SocketAddr, request: &str) -> String {
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_metrics_server() {
+        static METRICS: Metrics = Metrics::new();
+        METRICS.record_packet_sent();
+        let server = MetricsServer::bind(SocketAddr::from(([127, 0, 0, 1], 0)), &METRICS)
+            .await
+            .unwrap();
+        let address = server.local_addr().unwrap();
+        let task = tokio::spawn(server.run());
+
+        let response = request(address, "GET /metrics HTTP/1.1\r\nHost: x\r\n\r\n").await;
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        assert!(head.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(head.contains(&format!("Content-Length: {}", body.len())));
+        assert_eq!(body, METRICS.render());
+
+        let response = request(address, "GET / HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+        let response = request(address, "POST /metrics HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 405 Method Not Allowed\r\n"));
+        task.abort();
+    }
+}
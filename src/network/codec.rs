@@ -1,82 +1,450 @@
 //! Packet codec
 //!
-//! This module provides utilities for encoding and decoding Minecraft packets.
+//! This module provides [`PacketCodec`], which owns a client stream and
+//! applies framing, compression and encryption in the right order, along with
+//! stateless helpers for encoding and decoding individual packets.
 
 use crate::error::{Result, ServerError};
+use crate::metrics::Metrics;
+use crate::network::packet_logger::{Direction, PacketLogger, PacketRecord};
+use crate::protocol::encryption::PacketCipher;
+use crate::protocol::packets::Packet;
 use crate::protocol::types::VarInt;
+use crate::protocol::{Compression, ConnectionState, ProtocolState};
 use std::io::Cursor;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::mpsc;
 
-/// Packet codec for reading and writing Minecraft packets
-pub struct PacketCodec;
+/// Size of each read from the underlying stream
+const READ_CHUNK_SIZE: usize = 4096;
 
-impl PacketCodec {
-    /// Encode a packet with length prefix
-    pub fn encode<P>(packet: &P) -> Result<Vec<u8>>
-    where
-        P: crate::protocol::packets::Packet,
-    {
-        let mut packet_data = Vec::new();
+/// Sender for packets queued to a connection by other tasks
+pub type PacketSender = mpsc::UnboundedSender<RawPacket>;
 
-        // Write packet ID
-        P::id().write(&mut packet_data)?;
+/// Receiver a connection drains to write packets queued by other tasks
+pub type PacketReceiver = mpsc::UnboundedReceiver<RawPacket>;
 
-        // Write packet data
-        packet.write(&mut packet_data)?;
-
-        // Create final buffer with length prefix
-        let mut result = Vec::new();
-        VarInt(packet_data.len() as i32).write(&mut result)?;
-        result.extend_from_slice(&packet_data);
+/// A decoded packet whose body has not been parsed yet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawPacket {
+    /// Packet ID
+    pub id: VarInt,
+    /// Packet data (without the packet ID)
+    pub data: Vec<u8>,
+}
 
-        Ok(result)
+impl RawPacket {
+    /// Create a raw packet from an ID and body
+    pub fn new(id: i32, data: Vec<u8>) -> Self {
+        Self {
+            id: VarInt(id),
+            data,
+        }
     }
 
-    /// Decode a packet from raw bytes
-    pub fn decode<P>(data: &[u8]) -> Result<P>
-    where
-        P: crate::protocol::packets::Packet,
-    {
-        let mut cursor = Cursor::new(data);
+    /// Serialize a packet into a raw packet
+    pub fn from_packet<P: Packet>(packet: &P) -> Result<Self> {
+        let mut data = Vec::new();
+        packet.write(&mut data)?;
+        Ok(Self { id: P::id(), data })
+    }
 
-        // Read and verify packet ID
-        let packet_id = VarInt::read(&mut cursor)?;
-        if packet_id.0 != P::ID {
+    /// Parse the body as the given packet type
+    pub fn parse<P: Packet>(&self) -> Result<P> {
+        if self.id.0 != P::ID {
             return Err(ServerError::Protocol(format!(
                 "Packet ID mismatch: expected {}, got {}",
                 P::ID,
-                packet_id.0
+                self.id.0
+            )));
+        }
+        P::read(&mut Cursor::new(&self.data))
+    }
+}
+
+/// Framed packet stream
+///
+/// Incoming bytes are decrypted as soon as they arrive and buffered until a
+/// whole frame is available, so [`PacketCodec::read_packet`] is cancel safe
+/// and can be used in `tokio::select!`. Outgoing packets are framed,
+/// compressed and then encrypted.
+pub struct PacketCodec<S> {
+    /// Underlying stream
+    stream: S,
+    /// Protocol state
+    protocol_state: ProtocolState,
+    /// Compression handler
+    compression: Option<Compression>,
+    /// Encryption cipher
+    encryption: Option<PacketCipher>,
+    /// Decrypted bytes that have not been consumed yet
+    read_buffer: Vec<u8>,
+    /// Records every packet read or written, if packet logging is enabled
+    packet_logger: Option<PacketLogger>,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> PacketCodec<S> {
+    /// Create a new codec in the handshaking state
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            protocol_state: ProtocolState::new(),
+            compression: None,
+            encryption: None,
+            read_buffer: Vec::new(),
+            packet_logger: None,
+        }
+    }
+
+    /// Record every packet read or written from now on
+    pub fn set_packet_logger(&mut self, logger: PacketLogger) {
+        self.packet_logger = Some(logger);
+    }
+
+    /// Get the current protocol state
+    pub fn protocol_state(&self) -> &ProtocolState {
+        &self.protocol_state
+    }
+
+    /// Get the current connection state
+    pub fn state(&self) -> ConnectionState {
+        self.protocol_state.state
+    }
+
+    /// Transition to a new connection state
+    pub fn set_state(&mut self, new_state: ConnectionState) {
+        self.protocol_state.transition_to(new_state);
+    }
+
+    /// Set the protocol version negotiated during the handshake
+    pub fn set_protocol_version(&mut self, version: i32) {
+        self.protocol_state.set_protocol_version(version);
+    }
+
+    /// Enable compression with the given threshold
+    ///
+    /// Applies to every packet read or written after this call.
+    pub fn enable_compression(&mut self, threshold: u32) {
+        self.protocol_state.enable_compression(threshold);
+        self.compression = Some(Compression::new(threshold));
+    }
+
+    /// Check if compression is enabled
+    pub fn is_compressed(&self) -> bool {
+        self.compression.is_some()
+    }
+
+    /// Enable AES-128-CFB8 encryption using the given shared secret
+    ///
+    /// Applies to every byte read or written after this call, including any
+    /// bytes that have already been received but not yet consumed.
+    pub fn enable_encryption(&mut self, shared_secret: &[u8]) -> Result<()> {
+        let mut cipher = PacketCipher::new(shared_secret)?;
+        cipher.decrypt(&mut self.read_buffer);
+        self.encryption = Some(cipher);
+        Ok(())
+    }
+
+    /// Check if encryption is enabled
+    pub fn is_encrypted(&self) -> bool {
+        self.encryption.is_some()
+    }
+
+    /// Read the next packet
+    pub async fn read_packet(&mut self) -> Result<RawPacket> {
+        loop {
+            if let Some(frame) = self.take_frame()? {
+                Metrics::global().record_packet_received();
+                let packet = self.decode_frame(&frame)?;
+                self.log_packet(Direction::Serverbound, packet.id.0, packet.data.len());
+                return Ok(packet);
+            }
+            self.fill_buffer().await?;
+        }
+    }
+
+    /// Write an already serialized packet body with the given ID
+    pub async fn write_packet(&mut self, id: i32, body: &[u8]) -> Result<()> {
+        let payload = if let Some(ref mut compression) = self.compression {
+            // Data Length + (possibly compressed) Packet ID and Data
+            compression.compress_packet(VarInt(id), body)?
+        } else {
+            let mut payload = Vec::with_capacity(body.len() + 5);
+            VarInt(id).write(&mut payload)?;
+            payload.extend_from_slice(body);
+            payload
+        };
+
+        let mut frame = Vec::with_capacity(payload.len() + 3);
+        VarInt(payload.len() as i32).write(&mut frame)?;
+        frame.extend_from_slice(&payload);
+
+        Metrics::global().record_packet_sent();
+        self.log_packet(Direction::Clientbound, id, body.len());
+        self.write_raw(frame).await
+    }
+
+    /// Queue a record of a packet for the packet log, if it is enabled
+    fn log_packet(&self, direction: Direction, id: i32, length: usize) {
+        if let Some(logger) = &self.packet_logger {
+            logger.log(PacketRecord::new(direction, self.state(), id, length));
+        }
+    }
+
+    /// Write a packet
+    pub async fn write<P: Packet>(&mut self, packet: &P) -> Result<()> {
+        let raw = RawPacket::from_packet(packet)?;
+        self.write_packet(raw.id.0, &raw.data).await
+    }
+
+    /// Read raw (decrypted) bytes, bypassing framing
+    pub async fn read_raw(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if self.read_buffer.is_empty() {
+            self.fill_buffer().await?;
+        }
+        let count = buf.len().min(self.read_buffer.len());
+        buf[..count].copy_from_slice(&self.read_buffer[..count]);
+        self.read_buffer.drain(..count);
+        Ok(count)
+    }
+
+    /// Write raw bytes, bypassing framing but not encryption
+    pub async fn write_raw(&mut self, mut data: Vec<u8>) -> Result<()> {
+        if let Some(ref mut cipher) = self.encryption {
+            cipher.encrypt(&mut data);
+        }
+        self.stream.write_all(&data).await?;
+        self.stream.flush().await?;
+        Metrics::global().record_bytes_sent(data.len());
+        Ok(())
+    }
+
+    /// Get a reference to the underlying stream
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    /// Get a mutable reference to the underlying stream
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    /// Read more bytes from the stream into the buffer
+    async fn fill_buffer(&mut self) -> Result<()> {
+        let mut chunk = [0u8; READ_CHUNK_SIZE];
+        let bytes_read = self.stream.read(&mut chunk).await?;
+        if bytes_read == 0 {
+            return Err(ServerError::Io(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "Connection closed by peer",
             )));
         }
 
-        // Read packet data
-        P::read(&mut cursor)
+        Metrics::global().record_bytes_received(bytes_read);
+        let chunk = &mut chunk[..bytes_read];
+        if let Some(ref mut cipher) = self.encryption {
+            cipher.decrypt(chunk);
+        }
+        self.read_buffer.extend_from_slice(chunk);
+        Ok(())
+    }
+
+    /// Remove a complete frame from the buffer, if one is available
+    fn take_frame(&mut self) -> Result<Option<Vec<u8>>> {
+        let Some((length, header_len)) = peek_varint(&self.read_buffer)? else {
+            return Ok(None);
+        };
+
+        if length <= 0 {
+            return Err(ServerError::Protocol(format!(
+                "Invalid packet length: {}",
+                length
+            )));
+        }
+
+        let length = length as usize;
+        if length > crate::protocol::MAX_PACKET_SIZE {
+            return Err(ServerError::Protocol("Packet too large".to_string()));
+        }
+
+        if self.read_buffer.len() < header_len + length {
+            return Ok(None);
+        }
+
+        let frame = self.read_buffer[header_len..header_len + length].to_vec();
+        self.read_buffer.drain(..header_len + length);
+        Ok(Some(frame))
+    }
+
+    /// Decompress a frame and split off the packet ID
+    fn decode_frame(&mut self, frame: &[u8]) -> Result<RawPacket> {
+        let (id, data) = if let Some(ref mut compression) = self.compression {
+            compression.decompress_packet(frame)?
+        } else {
+            let mut cursor = Cursor::new(frame);
+            let id = VarInt::read(&mut cursor)?;
+            (id, frame[cursor.position() as usize..].to_vec())
+        };
+        Ok(RawPacket { id, data })
+    }
+}
+
+/// Parse a VarInt from the start of a buffer without consuming it
+///
+/// Returns the value and the number of bytes it occupies, or `None` if the
+/// buffer does not contain the whole VarInt yet.
+fn peek_varint(buf: &[u8]) -> Result<Option<(i32, usize)>> {
+    let mut value = 0i32;
+    for (i, byte) in buf.iter().enumerate() {
+        if i >= 5 {
+            return Err(ServerError::Protocol("VarInt too long".to_string()));
+        }
+        value |= ((byte & 0x7F) as i32) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok(Some((value, i + 1)));
+        }
+    }
+    if buf.len() >= 5 {
+        return Err(ServerError::Protocol("VarInt too long".to_string()));
+    }
+    Ok(None)
+}
+
+/// Encode a packet with length prefix
+pub fn encode<P>(packet: &P) -> Result<Vec<u8>>
+where
+    P: Packet,
+{
+    let mut packet_data = Vec::new();
+
+    // Write packet ID
+    P::id().write(&mut packet_data)?;
+
+    // Write packet data
+    packet.write(&mut packet_data)?;
+
+    // Create final buffer with length prefix
+    let mut result = Vec::new();
+    VarInt(packet_data.len() as i32).write(&mut result)?;
+    result.extend_from_slice(&packet_data);
+
+    Ok(result)
+}
+
+/// Decode a packet from raw bytes
+pub fn decode<P>(data: &[u8]) -> Result<P>
+where
+    P: Packet,
+{
+    let mut cursor = Cursor::new(data);
+
+    // Read and verify packet ID
+    let packet_id = VarInt::read(&mut cursor)?;
+    if packet_id.0 != P::ID {
+        return Err(ServerError::Protocol(format!(
+            "Packet ID mismatch: expected {}, got {}",
+            P::ID,
+            packet_id.0
+        )));
     }
 
-    /// Get the packet ID from raw packet data
-    pub fn get_packet_id(data: &[u8]) -> Result<VarInt> {
-        let mut cursor = Cursor::new(data);
-        VarInt::read(&mut cursor)
+    // Read packet data
+    P::read(&mut cursor)
+}
+
+/// Get the packet ID from raw packet data
+pub fn get_packet_id(data: &[u8]) -> Result<VarInt> {
+    let mut cursor = Cursor::new(data);
+    VarInt::read(&mut cursor)
+}
+
+/// Calculate the total size of an encoded packet
+pub fn calculate_packet_size<P>(packet: &P) -> Result<usize>
+where
+    P: Packet,
+{
+    let mut size = 0;
+
+    // Packet ID size
+    size += P::id().len();
+
+    // Packet data size (we need to serialize to get accurate size)
+    let mut temp_buffer = Vec::new();
+    packet.write(&mut temp_buffer)?;
+    size += temp_buffer.len();
+
+    // Length prefix size
+    let length_prefix = VarInt(size as i32);
+    size += length_prefix.len();
+
+    Ok(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::packets::status::PingRequestPacket;
+
+    #[tokio::test]
+    async fn test_codec_roundtrip_with_compression_and_encryption() {
+        let (server, client) = tokio::io::duplex(64 * 1024);
+        let mut server = PacketCodec::new(server);
+        let mut client = PacketCodec::new(client);
+
+        server
+            .write(&PingRequestPacket { payload: 1 })
+            .await
+            .unwrap();
+        assert_eq!(client.read_packet().await.unwrap().data, 1i64.to_be_bytes());
+
+        let secret = [3u8; 16];
+        server.enable_encryption(&secret).unwrap();
+        client.enable_encryption(&secret).unwrap();
+        server.set_state(ConnectionState::Login);
+        client.set_state(ConnectionState::Login);
+        server.enable_compression(64);
+        client.enable_compression(64);
+
+        let large = vec![0x5Au8; 1000];
+        server.write_packet(0x10, &large).await.unwrap();
+        server.write_packet(0x11, &[1, 2, 3]).await.unwrap();
+
+        assert_eq!(
+            client.read_packet().await.unwrap(),
+            RawPacket::new(0x10, large)
+        );
+        assert_eq!(
+            client.read_packet().await.unwrap(),
+            RawPacket::new(0x11, vec![1, 2, 3])
+        );
     }
 
-    /// Calculate the total size of an encoded packet
-    pub fn calculate_packet_size<P>(packet: &P) -> Result<usize>
-    where
-        P: crate::protocol::packets::Packet,
-    {
-        let mut size = 0;
+    #[tokio::test]
+    async fn test_read_packet_is_cancel_safe() {
+        let (mut raw_client, server) = tokio::io::duplex(1024);
+        let mut server = PacketCodec::new(server);
 
-        // Packet ID size
-        size += P::id().len();
+        // Deliver a frame in two halves, cancelling the read in between
+        let frame = encode(&PingRequestPacket { payload: 42 }).unwrap();
+        raw_client.write_all(&frame[..4]).await.unwrap();
 
-        // Packet data size (we need to serialize to get accurate size)
-        let mut temp_buffer = Vec::new();
-        packet.write(&mut temp_buffer)?;
-        size += temp_buffer.len();
+        let timed_out =
+            tokio::time::timeout(std::time::Duration::from_millis(20), server.read_packet()).await;
+        assert!(timed_out.is_err());
 
-        // Length prefix size
-        let length_prefix = VarInt(size as i32);
-        size += length_prefix.len();
+        raw_client.write_all(&frame[4..]).await.unwrap();
+        let ping: PingRequestPacket = server.read_packet().await.unwrap().parse().unwrap();
+        assert_eq!(ping.payload, 42);
+    }
 
-        Ok(size)
+    #[test]
+    fn test_peek_varint() {
+        assert_eq!(peek_varint(&[]).unwrap(), None);
+        assert_eq!(peek_varint(&[0x80]).unwrap(), None);
+        assert_eq!(
+            peek_varint(&[0xDD, 0xC7, 0x01, 0xFF]).unwrap(),
+            Some((25565, 3))
+        );
+        assert!(peek_varint(&[0xFF; 6]).is_err());
     }
 }
@@ -2,24 +2,22 @@
 //!
 //! This module handles individual client connections and their lifecycle.
 
-use crate::error::{Result, ServerError};
+use crate::error::Result;
+use crate::network::codec::{PacketCodec, RawPacket};
+use crate::network::packet_logger::PacketLogger;
+use crate::protocol::ConnectionState;
 use crate::protocol::types::VarInt;
-use crate::protocol::{Compression, ConnectionState, ProtocolState};
 use std::net::SocketAddr;
 use std::time::{Duration, Instant};
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::io::AsyncWriteExt;
 use tokio::net::TcpStream;
 
 /// Represents a single client connection
 pub struct Connection {
-    /// TCP stream
-    stream: TcpStream,
+    /// Packet codec owning the TCP stream
+    codec: PacketCodec<TcpStream>,
     /// Client address
     peer_addr: SocketAddr,
-    /// Protocol state
-    protocol_state: ProtocolState,
-    /// Compression handler
-    compression: Option<Compression>,
     /// Connection start time
     connected_at: Instant,
     /// Last activity time
@@ -31,15 +29,18 @@ impl Connection {
     pub fn new(stream: TcpStream, peer_addr: SocketAddr) -> Self {
         let now = Instant::now();
         Self {
-            stream,
+            codec: PacketCodec::new(stream),
             peer_addr,
-            protocol_state: ProtocolState::new(),
-            compression: None,
             connected_at: now,
             last_activity: now,
         }
     }
 
+    /// Record every packet of this connection in the packet log
+    pub fn set_packet_logger(&mut self, logger: PacketLogger) {
+        self.codec.set_packet_logger(logger);
+    }
+
     /// Get the peer address
     pub fn peer_addr(&self) -> SocketAddr {
         self.peer_addr
@@ -47,64 +48,52 @@ impl Connection {
 
     /// Get the current connection state
     pub fn state(&self) -> ConnectionState {
-        self.protocol_state.state
+        self.codec.state()
     }
 
     /// Transition to a new connection state
     pub fn set_state(&mut self, new_state: ConnectionState) {
-        self.protocol_state.transition_to(new_state);
+        self.codec.set_state(new_state);
     }
 
     /// Enable compression with the given threshold
     pub fn enable_compression(&mut self, threshold: u32) -> Result<()> {
-        self.protocol_state.enable_compression(threshold);
-        self.compression = Some(Compression::new(threshold));
+        self.codec.enable_compression(threshold);
         tracing::debug!("Compression enabled for connection {}", self.peer_addr);
         Ok(())
     }
 
+    /// Enable AES-128-CFB8 encryption using the given shared secret
+    ///
+    /// Every byte read or written after this call passes through the cipher.
+    pub fn enable_encryption(&mut self, shared_secret: &[u8]) -> Result<()> {
+        self.codec.enable_encryption(shared_secret)?;
+        tracing::debug!("Encryption enabled for connection {}", self.peer_addr);
+        Ok(())
+    }
+
+    /// Check if encryption is enabled
+    pub fn is_encrypted(&self) -> bool {
+        self.codec.is_encrypted()
+    }
+
     /// Read a packet from the connection
+    ///
+    /// This is cancel safe: a partially received packet stays buffered.
     pub async fn read_packet(&mut self) -> Result<(VarInt, Vec<u8>)> {
+        let packet = self.codec.read_packet().await?;
         self.last_activity = Instant::now();
 
-        // Read packet length
-        let packet_length = self.read_varint().await?;
-
-        if packet_length.0 < 0 {
-            return Err(ServerError::Protocol("Negative packet length".to_string()));
-        }
-
-        let length = packet_length.0 as usize;
-        if length == 0 {
-            return Err(ServerError::Protocol("Zero packet length".to_string()));
-        }
-
-        if length > crate::protocol::MAX_PACKET_SIZE {
-            return Err(ServerError::Protocol("Packet too large".to_string()));
-        }
-
-        // Read packet data
-        let mut data = vec![0u8; length];
-        self.stream.read_exact(&mut data).await?;
-
         // Debug: log the raw packet data
-        if data.len() <= 32 {
-            tracing::debug!("Raw packet data: {:02X?}", data);
+        if packet.data.len() <= 32 {
+            tracing::debug!("Raw packet data: {:02X?}", packet.data);
         } else {
-            tracing::debug!("Raw packet data (first 32): {:02X?}", &data[..32]);
+            tracing::debug!("Raw packet data (first 32): {:02X?}", &packet.data[..32]);
         }
 
-        // Handle compression if enabled
-        if let Some(ref mut compression) = self.compression {
-            compression.decompress_packet(&data)
-        } else {
-            // Uncompressed packet - first VarInt is packet ID
-            let mut cursor = std::io::Cursor::new(&data);
-            let packet_id = VarInt::read(&mut cursor)?;
-            let remaining_data = data[cursor.position() as usize..].to_vec();
-            Ok((packet_id, remaining_data))
-        }
+        Ok((packet.id, packet.data))
     }
+
     /// Write a packet to the connection
     pub async fn write_packet<P>(&mut self, packet: &P) -> Result<()>
     where
@@ -119,57 +108,28 @@ impl Connection {
             "Writing packet ID: 0x{:02X}, data length: {}, compression: {}",
             P::ID,
             packet_data.len(),
-            self.compression.is_some()
+            self.codec.is_compressed()
         );
 
-        let final_packet = if let Some(ref mut compression) = self.compression {
-            // Get the payload (Data Length + Data)
-            let payload = compression.compress_packet(P::id(), &packet_data)?;
-
-            // Prepend the Packet Length
-            let mut buffer = Vec::new();
-            VarInt(payload.len() as i32).write(&mut buffer)?;
-            buffer.extend_from_slice(&payload);
-            buffer
-        } else {
-            // Prepend the Packet Length to the uncompressed payload (PacketID + Data)
-            let mut uncompressed_payload = Vec::new();
-            P::id().write(&mut uncompressed_payload)?;
-            uncompressed_payload.extend_from_slice(&packet_data);
-
-            let mut buffer = Vec::new();
-            VarInt(uncompressed_payload.len() as i32).write(&mut buffer)?;
-            buffer.extend_from_slice(&uncompressed_payload);
-            buffer
-        };
-
-        tracing::debug!("Final packet size: {} bytes", final_packet.len());
-
-        if final_packet.len() <= 32 {
-            tracing::debug!("Packet bytes: {:02X?}", final_packet);
-        } else {
-            tracing::debug!("Packet bytes (first 32): {:02X?}", &final_packet[..32]);
-        }
-
-        self.stream.write_all(&final_packet).await?;
-        self.stream.flush().await?;
+        self.codec.write_packet(P::ID, &packet_data).await
+    }
 
-        Ok(())
+    /// Write a packet that has already been serialized
+    pub async fn write_raw_packet(&mut self, packet: &RawPacket) -> Result<()> {
+        self.last_activity = Instant::now();
+        self.codec.write_packet(packet.id.0, &packet.data).await
     }
 
     /// Read raw bytes from the connection
     pub async fn read_bytes(&mut self, buf: &mut [u8]) -> Result<usize> {
         self.last_activity = Instant::now();
-        let bytes_read = self.stream.read(buf).await?;
-        Ok(bytes_read)
+        self.codec.read_raw(buf).await
     }
 
     /// Write raw bytes to the connection
     pub async fn write_bytes(&mut self, data: &[u8]) -> Result<()> {
         self.last_activity = Instant::now();
-        self.stream.write_all(data).await?;
-        self.stream.flush().await?;
-        Ok(())
+        self.codec.write_raw(data.to_vec()).await
     }
 
     /// Check if the connection has timed out
@@ -189,43 +149,18 @@ impl Connection {
 
     /// Set protocol version
     pub fn set_protocol_version(&mut self, version: i32) {
-        self.protocol_state.set_protocol_version(version);
+        self.codec.set_protocol_version(version);
     }
 
     /// Get protocol version
     pub fn protocol_version(&self) -> Option<i32> {
-        self.protocol_state.protocol_version
+        self.codec.protocol_state().protocol_version
     }
 
     /// Close the connection
     pub async fn close(&mut self) -> Result<()> {
-        self.stream.shutdown().await?;
+        self.codec.get_mut().shutdown().await?;
         tracing::debug!("Connection {} closed", self.peer_addr);
         Ok(())
     }
-
-    /// Read a VarInt from the connection
-    async fn read_varint(&mut self) -> Result<VarInt> {
-        let mut value = 0i32;
-        let mut position = 0;
-
-        loop {
-            let mut byte = [0u8; 1];
-            self.stream.read_exact(&mut byte).await?;
-            let byte = byte[0];
-
-            value |= ((byte & 0x7F) as i32) << position;
-
-            if (byte & 0x80) == 0 {
-                break;
-            }
-
-            position += 7;
-            if position >= 32 {
-                return Err(ServerError::Protocol("VarInt too long".to_string()));
-            }
-        }
-
-        Ok(VarInt(value))
-    }
 }
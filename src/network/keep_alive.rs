@@ -0,0 +1,195 @@
+//! Keep alive handling
+//!
+//! Every player in the play state has a keep alive task. It periodically
+//! queues a keep alive on the player's connection and fails once the client
+//! stops answering, which ends the connection. The round trip of each
+//! answered keep alive is published as the client's latency.
+
+use crate::error::{Result, ServerError};
+use crate::network::{PacketSender, RawPacket};
+use crate::protocol::packets::play::KeepAlivePacket;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+/// Handle the connection uses to forward keep alive responses
+#[derive(Debug, Clone)]
+pub struct KeepAliveHandle {
+    /// Channel to the keep alive task
+    responses: mpsc::UnboundedSender<i64>,
+    /// Round trip of the latest answered keep alive
+    latency: watch::Receiver<Duration>,
+}
+
+impl KeepAliveHandle {
+    /// Forward the ID of a keep alive response from the client
+    pub fn respond(&self, keep_alive_id: i64) {
+        // The task only stops when the connection is closing
+        let _ = self.responses.send(keep_alive_id);
+    }
+
+    /// Subscribe to the client's latency, which is zero until the first
+    /// keep alive is answered
+    pub fn latency(&self) -> watch::Receiver<Duration> {
+        self.latency.clone()
+    }
+}
+
+/// Sends keep alives to a connection and checks that they are answered
+pub struct KeepAliveManager {
+    /// Time between keep alives
+    interval: Duration,
+    /// Time the client has to answer a keep alive
+    timeout: Duration,
+    /// Queue of packets written to the connection
+    packets: PacketSender,
+    /// Keep alive IDs answered by the client
+    responses: mpsc::UnboundedReceiver<i64>,
+    /// Publishes the round trip of each answered keep alive
+    latency: watch::Sender<Duration>,
+}
+
+impl KeepAliveManager {
+    /// Create a keep alive manager sending through the given packet queue
+    pub fn new(
+        interval: Duration,
+        timeout: Duration,
+        packets: PacketSender,
+    ) -> (Self, KeepAliveHandle) {
+        let (responses_tx, responses) = mpsc::unbounded_channel();
+        let (latency, latency_rx) = watch::channel(Duration::ZERO);
+        let manager = Self {
+            interval,
+            timeout,
+            packets,
+            responses,
+            latency,
+        };
+        (
+            manager,
+            KeepAliveHandle {
+                responses: responses_tx,
+                latency: latency_rx,
+            },
+        )
+    }
+
+    /// Run the manager in its own task
+    pub fn spawn(self) -> JoinHandle<Result<()>> {
+        tokio::spawn(self.run())
+    }
+
+    /// Send keep alives until the connection closes
+    ///
+    /// Returns `Ok` once the connection's packet queue or the response handle
+    /// is dropped, and an error if a keep alive is not answered in time.
+    pub async fn run(mut self) -> Result<()> {
+        let mut ticker = tokio::time::interval_at(Instant::now() + self.interval, self.interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        // ID and send time of the keep alive awaiting an answer
+        let mut pending: Option<(i64, Instant)> = None;
+
+        loop {
+            let deadline = pending.map(|(_, sent_at)| sent_at + self.timeout);
+
+            tokio::select! {
+                _ = ticker.tick(), if pending.is_none() => {
+                    let keep_alive_id = current_millis();
+                    let packet = RawPacket::from_packet(&KeepAlivePacket { keep_alive_id })?;
+                    if self.packets.send(packet).is_err() {
+                        return Ok(());
+                    }
+                    pending = Some((keep_alive_id, Instant::now()));
+                }
+                _ = tokio::time::sleep_until(deadline.unwrap_or_else(Instant::now)),
+                    if deadline.is_some() =>
+                {
+                    return Err(ServerError::Protocol("Keep alive timeout".to_string()));
+                }
+                response = self.responses.recv() => {
+                    let Some(keep_alive_id) = response else {
+                        return Ok(());
+                    };
+                    match pending {
+                        Some((expected, sent_at)) if expected == keep_alive_id => {
+                            let latency = sent_at.elapsed();
+                            tracing::trace!("Keep alive answered after {:?}", latency);
+                            self.latency.send_replace(latency);
+                            pending = None;
+                        }
+                        _ => tracing::debug!("Ignoring unexpected keep alive {}", keep_alive_id),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Milliseconds since the Unix epoch, used as keep alive ID like vanilla
+fn current_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERVAL: Duration = Duration::from_millis(10);
+    const TIMEOUT: Duration = Duration::from_millis(40);
+
+    #[tokio::test]
+    async fn test_unanswered_keep_alive_times_out() {
+        let (packets, mut connection) = mpsc::unbounded_channel();
+        let (manager, _handle) = KeepAliveManager::new(INTERVAL, TIMEOUT, packets);
+        let task = manager.spawn();
+
+        // The connection receives the keep alive but never answers it
+        let packet = connection.recv().await.unwrap();
+        assert!(packet.parse::<KeepAlivePacket>().is_ok());
+
+        let result = task.await.unwrap();
+        assert!(
+            matches!(result, Err(ServerError::Protocol(message)) if message == "Keep alive timeout")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_answered_keep_alives() {
+        let (packets, mut connection) = mpsc::unbounded_channel();
+        let (manager, handle) = KeepAliveManager::new(INTERVAL, TIMEOUT, packets);
+        let task = manager.spawn();
+
+        let mut latency = handle.latency();
+        assert_eq!(*latency.borrow(), Duration::ZERO);
+
+        // Answering keeps the connection alive well past the timeout
+        for _ in 0..8 {
+            let packet = connection.recv().await.unwrap();
+            let keep_alive = packet.parse::<KeepAlivePacket>().unwrap();
+            handle.respond(keep_alive.keep_alive_id);
+        }
+        assert!(!task.is_finished());
+
+        // Each answer is measured
+        latency.changed().await.unwrap();
+        assert!(*latency.borrow() < TIMEOUT);
+
+        // Dropping the handle means the connection is closing
+        drop(handle);
+        assert!(task.await.unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_closed_connection_stops_manager() {
+        let (packets, connection) = mpsc::unbounded_channel();
+        let (manager, _handle) = KeepAliveManager::new(INTERVAL, TIMEOUT, packets);
+        drop(connection);
+
+        assert!(manager.run().await.is_ok());
+    }
+}
@@ -4,7 +4,7 @@
 
 use crate::config::ServerConfig;
 use crate::error::{Result, ServerError};
-use crate::network::Connection;
+use crate::network::{Connection, PacketLogger};
 use std::net::SocketAddr;
 use tokio::net::TcpListener;
 use tokio::sync::mpsc;
@@ -17,6 +17,8 @@ pub struct ServerListener {
     config: ServerConfig,
     /// Channel for sending new connections
     connection_sender: mpsc::UnboundedSender<Connection>,
+    /// Packet log every connection records to, if enabled
+    packet_logger: Option<PacketLogger>,
 }
 
 impl ServerListener {
@@ -26,11 +28,20 @@ impl ServerListener {
         connection_sender: mpsc::UnboundedSender<Connection>,
     ) -> Result<Self> {
         let listener = TcpListener::bind(config.bind_address).await?;
+        let packet_logger = if config.log_packets {
+            let max_bytes = config.log_max_mb.saturating_mul(1024 * 1024);
+            let (logger, _) = PacketLogger::start(&config.packet_log_file, max_bytes)?;
+            tracing::info!("Logging packets to {}", config.packet_log_file.display());
+            Some(logger)
+        } else {
+            None
+        };
 
         Ok(Self {
             listener,
             config,
             connection_sender,
+            packet_logger,
         })
     }
 
@@ -41,7 +52,10 @@ impl ServerListener {
                 Ok((stream, addr)) => {
                     tracing::debug!("New connection from {}", addr);
 
-                    let connection = Connection::new(stream, addr);
+                    let mut connection = Connection::new(stream, addr);
+                    if let Some(logger) = &self.packet_logger {
+                        connection.set_packet_logger(logger.clone());
+                    }
 
                     if let Err(e) = self.connection_sender.send(connection) {
                         tracing::error!("Failed to send connection to handler: {}", e);
@@ -2,10 +2,28 @@
 //!
 //! This module handles low-level networking including connection management,
 //! packet framing, and the server listener.
+//!
+//! # I/O model
+//!
+//! All socket I/O is asynchronous: the [`ServerListener`] accepts connections
+//! on a Tokio listener and every connection runs in its own task. A
+//! [`Connection`] reads and writes whole packet frames asynchronously, and
+//! packets are only decoded and encoded with the synchronous
+//! [`Packet`](crate::protocol::packets::Packet) methods once a frame is in
+//! memory, so no thread ever blocks on a slow client.
 
 pub mod codec;
 pub mod connection;
+pub mod keep_alive;
 pub mod listener;
+pub mod packet_logger;
+pub mod status;
+pub mod view_distance;
 
+pub use codec::{PacketCodec, PacketReceiver, PacketSender, RawPacket};
 pub use connection::Connection;
+pub use keep_alive::{KeepAliveHandle, KeepAliveManager};
 pub use listener::ServerListener;
+pub use packet_logger::PacketLogger;
+pub use status::StatusHandler;
+pub use view_distance::ViewDistanceTracker;
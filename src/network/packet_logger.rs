@@ -0,0 +1,237 @@
+//! Packet logging
+//!
+//! When enabled, every packet read or written by a [`PacketCodec`] is
+//! recorded as one JSON line with its time, direction, state, ID and length.
+//! Codecs only queue records; a dedicated task writes them, so a slow disk
+//! never holds up a connection. Once the log reaches its size limit it is
+//! moved aside to `<path>.1` and a new file is started.
+//!
+//! [`PacketCodec`]: crate::network::PacketCodec
+
+use crate::error::Result;
+use crate::protocol::ConnectionState;
+use serde_json::json;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// Which way a packet travelled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Sent by the client
+    Serverbound,
+    /// Sent by the server
+    Clientbound,
+}
+
+impl Direction {
+    /// Short form used in the log
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Direction::Serverbound => "C→S",
+            Direction::Clientbound => "S→C",
+        }
+    }
+}
+
+/// A logged packet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PacketRecord {
+    /// Time the packet was read or written, in milliseconds since the Unix
+    /// epoch
+    pub timestamp: u64,
+    /// Which way the packet travelled
+    pub direction: Direction,
+    /// Connection state the packet belongs to
+    pub state: ConnectionState,
+    /// Packet ID
+    pub id: i32,
+    /// Length of the packet data after the ID, before compression
+    pub length: usize,
+}
+
+impl PacketRecord {
+    /// Create a record of a packet read or written now
+    pub fn new(direction: Direction, state: ConnectionState, id: i32, length: usize) -> Self {
+        Self {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_millis() as u64)
+                .unwrap_or_default(),
+            direction,
+            state,
+            id,
+            length,
+        }
+    }
+
+    /// Format the record as a JSON line, without the newline
+    pub fn to_json(&self) -> String {
+        json!({
+            "ts": self.timestamp,
+            "dir": self.direction.as_str(),
+            "state": format!("{:?}", self.state),
+            "id": format!("0x{:02X}", self.id),
+            "len": self.length,
+        })
+        .to_string()
+    }
+}
+
+/// Handle codecs queue packet records on
+#[derive(Debug, Clone)]
+pub struct PacketLogger {
+    /// Channel to the writer task
+    records: mpsc::UnboundedSender<PacketRecord>,
+}
+
+impl PacketLogger {
+    /// Start writing records to a file, appending to it if it exists
+    ///
+    /// The file is rotated once it would grow past `max_bytes`; a limit of
+    /// zero lets it grow forever. The writer task stops once every handle
+    /// is dropped.
+    pub fn start(path: impl Into<PathBuf>, max_bytes: u64) -> Result<(Self, JoinHandle<()>)> {
+        let writer = LogWriter::open(path.into(), max_bytes)?;
+        let (records, receiver) = mpsc::unbounded_channel();
+        let task = tokio::task::spawn_blocking(move || writer.run(receiver));
+        Ok((Self { records }, task))
+    }
+
+    /// Queue a record, without waiting for it to be written
+    pub fn log(&self, record: PacketRecord) {
+        // The writer only stops if its file failed, which it already logged
+        let _ = self.records.send(record);
+    }
+}
+
+/// Writes records to the log file, rotating it when it is full
+struct LogWriter {
+    /// Path of the current log file
+    path: PathBuf,
+    /// Size the file may reach before it is rotated, or zero for no limit
+    max_bytes: u64,
+    /// Current log file
+    file: BufWriter<File>,
+    /// Bytes in the current file
+    written: u64,
+}
+
+impl LogWriter {
+    /// Open the log file for appending
+    fn open(path: PathBuf, max_bytes: u64) -> Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let written = file.metadata()?.len();
+        Ok(Self {
+            path,
+            max_bytes,
+            file: BufWriter::new(file),
+            written,
+        })
+    }
+
+    /// Path the full log is moved to
+    fn rotated_path(path: &Path) -> PathBuf {
+        let mut rotated = path.as_os_str().to_owned();
+        rotated.push(".1");
+        PathBuf::from(rotated)
+    }
+
+    /// Move the current file aside and start a new one
+    fn rotate(&mut self) -> Result<()> {
+        self.file.flush()?;
+        fs::rename(&self.path, Self::rotated_path(&self.path))?;
+        self.file = BufWriter::new(File::create(&self.path)?);
+        self.written = 0;
+        Ok(())
+    }
+
+    /// Write one record
+    fn write(&mut self, record: &PacketRecord) -> Result<()> {
+        let mut line = record.to_json();
+        line.push('\n');
+        let length = line.len() as u64;
+        if self.max_bytes > 0 && self.written > 0 && self.written + length > self.max_bytes {
+            self.rotate()?;
+        }
+        self.file.write_all(line.as_bytes())?;
+        self.written += length;
+        Ok(())
+    }
+
+    /// Write records until every logger is dropped, flushing whenever the
+    /// queue is empty
+    fn run(mut self, mut records: mpsc::UnboundedReceiver<PacketRecord>) {
+        while let Some(record) = records.blocking_recv() {
+            let mut result = self.write(&record);
+            if result.is_ok() && records.is_empty() {
+                result = self.file.flush().map_err(Into::into);
+            }
+            if let Err(e) = result {
+                tracing::error!("Failed to write packet log {}: {}", self.path.display(), e);
+                return;
+            }
+        }
+        if let Err(e) = self.file.flush() {
+            tracing::error!("Failed to write packet log {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_json() {
+        let record = PacketRecord {
+            timestamp: 1_700_000_000_000,
+            direction: Direction::Serverbound,
+            state: ConnectionState::Play,
+            id: 0x0F,
+            length: 42,
+        };
+        assert_eq!(
+            record.to_json(),
+            r#"{"ts":1700000000000,"dir":"C→S","state":"Play","id":"0x0F","len":42}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_log_rotates_when_full() {
+        let path =
+            std::env::temp_dir().join(format!("obsidium-{}-packet-log.jsonl", std::process::id()));
+        let rotated = LogWriter::rotated_path(&path);
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_file(&rotated);
+
+        // Each record takes about 70 bytes, so three fit in the limit
+        let (logger, task) = PacketLogger::start(&path, 256).unwrap();
+        for id in 0..5 {
+            logger.log(PacketRecord::new(
+                Direction::Clientbound,
+                ConnectionState::Login,
+                id,
+                10,
+            ));
+        }
+        drop(logger);
+        task.await.unwrap();
+
+        let old = fs::read_to_string(&rotated).unwrap();
+        let new = fs::read_to_string(&path).unwrap();
+        assert_eq!(old.lines().count(), 3);
+        assert_eq!(new.lines().count(), 2);
+        assert!(old.len() <= 256);
+        let last: serde_json::Value = serde_json::from_str(new.lines().last().unwrap()).unwrap();
+        assert_eq!(last["id"], "0x04");
+        assert_eq!(last["dir"], "S→C");
+        assert_eq!(last["state"], "Login");
+
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(&rotated).unwrap();
+    }
+}
@@ -0,0 +1,125 @@
+//! Server list ping handling
+//!
+//! This module answers the status requests and pings a client sends when it
+//! lists the server in its multiplayer menu.
+
+use crate::error::Result;
+use crate::network::Connection;
+use crate::protocol::VarInt;
+use crate::protocol::packets::Packet;
+use crate::protocol::packets::status::{
+    PingRequestPacket, PingResponsePacket, ServerStatus, StatusRequestPacket, StatusResponsePacket,
+};
+
+/// Handles packets in the status state
+pub struct StatusHandler {
+    /// Status reported to the client
+    status: ServerStatus,
+}
+
+impl StatusHandler {
+    /// Create a new status handler reporting the given status
+    pub fn new(status: ServerStatus) -> Self {
+        Self { status }
+    }
+
+    /// Get the status reported to the client
+    pub fn status(&self) -> &ServerStatus {
+        &self.status
+    }
+
+    /// Handle a status state packet
+    ///
+    /// Returns `true` once the ping has been answered and the connection
+    /// should be closed.
+    pub async fn handle_packet(
+        &self,
+        connection: &mut Connection,
+        packet_id: VarInt,
+        data: &[u8],
+    ) -> Result<bool> {
+        if packet_id.0 == StatusRequestPacket::ID {
+            // Send status response
+            let response = StatusResponsePacket {
+                json_response: self.status.to_json()?.into(),
+            };
+            connection.write_packet(&response).await?;
+        } else if packet_id.0 == PingRequestPacket::ID {
+            let ping = PingRequestPacket::read(&mut std::io::Cursor::new(data))?;
+            let pong = PingResponsePacket {
+                payload: ping.payload,
+            };
+            connection.write_packet(&pong).await?;
+            return Ok(true); // Close connection after ping
+        }
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::packets::status::{Description, PlayersInfo, VersionInfo};
+    use tokio::net::{TcpListener, TcpStream};
+
+    #[tokio::test]
+    async fn test_status_and_ping() {
+        let status = ServerStatus {
+            version: VersionInfo {
+                name: crate::protocol::MINECRAFT_VERSION.to_string(),
+                protocol: crate::protocol::PROTOCOL_VERSION,
+            },
+            players: PlayersInfo {
+                max: 20,
+                online: 3,
+                sample: None,
+            },
+            description: Description::Text("A Minecraft Server".to_string()),
+            favicon: Some("data:image/png;base64,AAAA".to_string()),
+            enforces_secure_chat: false,
+        };
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let client_stream = TcpStream::connect(addr).await.unwrap();
+        let (server_stream, peer_addr) = listener.accept().await.unwrap();
+
+        let mut server = Connection::new(server_stream, peer_addr);
+        let mut client = Connection::new(client_stream, addr);
+        let handler = StatusHandler::new(status);
+
+        client.write_packet(&StatusRequestPacket).await.unwrap();
+        let (packet_id, data) = server.read_packet().await.unwrap();
+        assert!(
+            !handler
+                .handle_packet(&mut server, packet_id, &data)
+                .await
+                .unwrap()
+        );
+
+        let (packet_id, data) = client.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, StatusResponsePacket::ID);
+        let response = StatusResponsePacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        let reported = ServerStatus::from_json(&response.json_response.0).unwrap();
+        assert_eq!(reported.players.online, 3);
+        assert_eq!(reported.players.max, 20);
+        assert_eq!(reported.favicon, handler.status().favicon);
+
+        client
+            .write_packet(&PingRequestPacket { payload: 0x1234 })
+            .await
+            .unwrap();
+        let (packet_id, data) = server.read_packet().await.unwrap();
+        assert!(
+            handler
+                .handle_packet(&mut server, packet_id, &data)
+                .await
+                .unwrap()
+        );
+
+        let (packet_id, data) = client.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, PingResponsePacket::ID);
+        let pong = PingResponsePacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        assert_eq!(pong.payload, 0x1234);
+    }
+}
@@ -0,0 +1,179 @@
+//! Chunk loading around players
+//!
+//! Each player has a square of chunks around them loaded on their client.
+//! The [`ViewDistanceTracker`] remembers which chunks were sent, so that as
+//! the player walks only the chunks entering the square are sent and only
+//! the ones leaving it are unloaded.
+
+use crate::error::Result;
+use crate::game::world::ChunkPosition;
+use crate::game::world::generators::ChunkProvider;
+use crate::network::Connection;
+use crate::protocol::packets::play::{SetCenterChunkPacket, UnloadChunkPacket};
+use crate::protocol::types::VarInt;
+use std::collections::HashSet;
+
+/// Smallest view distance chunks are sent for, whatever the client asks for
+pub const MIN_VIEW_DISTANCE: u8 = 2;
+
+/// Chunks to send and unload after the player moved or the view distance
+/// changed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChunkChanges {
+    /// Chunks entering the view distance, nearest first
+    pub load: Vec<(i32, i32)>,
+    /// Chunks leaving the view distance
+    pub unload: Vec<(i32, i32)>,
+}
+
+/// Tracks the chunks loaded on a client
+#[derive(Debug, Clone, Default)]
+pub struct ViewDistanceTracker {
+    /// Chunk the loaded square is centered on
+    center: (i32, i32),
+    /// Radius of the loaded square in chunks
+    radius: u8,
+    /// Chunks loaded on the client
+    loaded: HashSet<(i32, i32)>,
+}
+
+impl ViewDistanceTracker {
+    /// Create a tracker with no chunks loaded
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// View distance to use: the server's, limited by the client's if it
+    /// has sent its settings
+    pub fn effective_radius(server: u8, client: Option<i8>) -> u8 {
+        match client {
+            Some(client) => {
+                let client = u8::try_from(client).unwrap_or(0).max(MIN_VIEW_DISTANCE);
+                server.min(client)
+            }
+            None => server,
+        }
+    }
+
+    /// Chunk the loaded square is centered on
+    pub fn center(&self) -> (i32, i32) {
+        self.center
+    }
+
+    /// Radius of the loaded square in chunks
+    pub fn radius(&self) -> u8 {
+        self.radius
+    }
+
+    /// Whether a chunk is loaded on the client
+    pub fn is_loaded(&self, chunk: (i32, i32)) -> bool {
+        self.loaded.contains(&chunk)
+    }
+
+    /// Number of chunks loaded on the client
+    pub fn loaded_count(&self) -> usize {
+        self.loaded.len()
+    }
+
+    /// Move the loaded square, returning the chunks that entered and left it
+    pub fn update(&mut self, center: (i32, i32), radius: u8) -> ChunkChanges {
+        let radius_i32 = i32::from(radius);
+        let required: HashSet<(i32, i32)> = (center.0 - radius_i32..=center.0 + radius_i32)
+            .flat_map(|x| (center.1 - radius_i32..=center.1 + radius_i32).map(move |z| (x, z)))
+            .collect();
+
+        let mut load: Vec<_> = required.difference(&self.loaded).copied().collect();
+        load.sort_by_key(|&(x, z)| ((x - center.0).pow(2) + (z - center.1).pow(2), x, z));
+        let mut unload: Vec<_> = self.loaded.difference(&required).copied().collect();
+        unload.sort_unstable();
+
+        self.center = center;
+        self.radius = radius;
+        self.loaded = required;
+        ChunkChanges { load, unload }
+    }
+
+    /// Move the loaded square and bring the client up to date
+    ///
+    /// The client is told the new center first, since it drops chunks
+    /// received outside the area around its current center.
+    pub async fn sync(
+        &mut self,
+        connection: &mut Connection,
+        provider: &dyn ChunkProvider,
+        center: (i32, i32),
+        radius: u8,
+    ) -> Result<()> {
+        let recenter = center != self.center || self.loaded.is_empty();
+        let changes = self.update(center, radius);
+
+        if recenter {
+            connection
+                .write_packet(&SetCenterChunkPacket {
+                    chunk_x: VarInt(center.0),
+                    chunk_z: VarInt(center.1),
+                })
+                .await?;
+        }
+        for &(chunk_x, chunk_z) in &changes.unload {
+            connection
+                .write_packet(&UnloadChunkPacket { chunk_x, chunk_z })
+                .await?;
+        }
+        for &(x, z) in &changes.load {
+            let chunk = provider.provide_chunk(ChunkPosition::new(x, z));
+            connection.write_packet(&chunk).await?;
+        }
+
+        if !changes.load.is_empty() || !changes.unload.is_empty() {
+            tracing::debug!(
+                "Sent {} chunks and unloaded {} around {:?}",
+                changes.load.len(),
+                changes.unload.len(),
+                center
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_effective_radius() {
+        assert_eq!(ViewDistanceTracker::effective_radius(10, None), 10);
+        assert_eq!(ViewDistanceTracker::effective_radius(10, Some(6)), 6);
+        assert_eq!(ViewDistanceTracker::effective_radius(10, Some(32)), 10);
+        assert_eq!(ViewDistanceTracker::effective_radius(10, Some(-1)), 2);
+        assert_eq!(ViewDistanceTracker::effective_radius(0, Some(6)), 0);
+    }
+
+    #[test]
+    fn test_moving_loads_and_unloads_edges() {
+        let mut tracker = ViewDistanceTracker::new();
+        let changes = tracker.update((0, 0), 1);
+        assert_eq!(changes.load.len(), 9);
+        assert_eq!(changes.load[0], (0, 0));
+        assert!(changes.unload.is_empty());
+
+        let changes = tracker.update((1, 0), 1);
+        assert_eq!(changes.load, [(2, 0), (2, -1), (2, 1)]);
+        assert_eq!(changes.unload, [(-1, -1), (-1, 0), (-1, 1)]);
+        assert!(tracker.is_loaded((2, 1)));
+        assert!(!tracker.is_loaded((-1, 0)));
+
+        assert_eq!(tracker.update((1, 0), 1), ChunkChanges::default());
+    }
+
+    #[test]
+    fn test_shrinking_view_distance() {
+        let mut tracker = ViewDistanceTracker::new();
+        tracker.update((0, 0), 2);
+        let changes = tracker.update((0, 0), 1);
+        assert!(changes.load.is_empty());
+        assert_eq!(changes.unload.len(), 16);
+        assert_eq!(tracker.loaded_count(), 9);
+    }
+}
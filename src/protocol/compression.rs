@@ -4,10 +4,12 @@
 //! according to the protocol specification.
 
 use crate::error::{Result, ServerError};
+use crate::protocol::packets::Packet;
 use crate::protocol::types::VarInt;
 use flate2::{
     Compress, Compression as FlateCompression, Decompress, FlushCompress, FlushDecompress, Status,
 };
+use std::io::{Read, Write};
 
 /// Compression utilities for Minecraft packets
 pub struct Compression {
@@ -23,8 +25,9 @@ impl Compression {
     /// Create a new compression instance
     pub fn new(threshold: u32) -> Self {
         Self {
-            compressor: Compress::new(FlateCompression::default(), false),
-            decompressor: Decompress::new(false),
+            // Minecraft uses zlib-wrapped deflate streams
+            compressor: Compress::new(FlateCompression::default(), true),
+            decompressor: Decompress::new(true),
             threshold,
         }
     }
@@ -61,54 +64,29 @@ impl Compression {
         }
 
         // Compress the data
-        let mut compressed_data = Vec::with_capacity(uncompressed_length);
+        let mut compressed_data = Vec::with_capacity(uncompressed_length / 2 + 64);
 
         self.compressor.reset();
 
-        let mut input_pos = 0;
-        let mut output_pos = 0;
-
         loop {
-            let old_input_pos = self.compressor.total_in() as usize;
-            let old_output_pos = self.compressor.total_out() as usize;
-
-            // Ensure we have space in the output buffer
-            if compressed_data.len() < output_pos + 1024 {
-                compressed_data.resize(output_pos + 1024, 0);
-            }
+            let input_pos = self.compressor.total_in() as usize;
 
-            let status = self.compressor.compress(
+            // `compress_vec` only writes into spare capacity, so keep growing
+            // the buffer until the zlib stream has been fully finished
+            let status = self.compressor.compress_vec(
                 &uncompressed_data[input_pos..],
-                &mut compressed_data[output_pos..],
-                if input_pos == uncompressed_length {
-                    FlushCompress::Finish
-                } else {
-                    FlushCompress::None
-                },
+                &mut compressed_data,
+                FlushCompress::Finish,
             )?;
 
-            let new_input_pos = self.compressor.total_in() as usize;
-            let new_output_pos = self.compressor.total_out() as usize;
-
-            input_pos += new_input_pos - old_input_pos;
-            output_pos += new_output_pos - old_output_pos;
-
             match status {
-                Status::Ok => {
-                    if input_pos >= uncompressed_length {
-                        break;
-                    }
-                }
                 Status::StreamEnd => break,
-                Status::BufError => {
-                    // Need more output space
-                    compressed_data.resize(compressed_data.len() + 1024, 0);
+                Status::Ok | Status::BufError => {
+                    compressed_data.reserve(compressed_data.capacity().max(1024));
                 }
             }
         }
 
-        compressed_data.truncate(output_pos);
-
         // Build final packet
         let mut result = Vec::new();
 
@@ -183,7 +161,7 @@ impl Compression {
 
         let mut uncompressed_data = vec![0u8; uncompressed_length];
 
-        self.decompressor.reset(false);
+        self.decompressor.reset(true);
 
         let mut input_pos = 0;
         let mut output_pos = 0;
@@ -209,6 +187,11 @@ impl Compression {
                     if output_pos >= uncompressed_length {
                         break;
                     }
+                    if new_input_pos == old_input_pos && new_output_pos == old_output_pos {
+                        return Err(ServerError::Protocol(
+                            "Truncated compressed packet".to_string(),
+                        ));
+                    }
                 }
                 Status::StreamEnd => break,
                 Status::BufError => {
@@ -245,6 +228,91 @@ impl Compression {
     }
 }
 
+/// A stream wrapper that frames packets using the compressed packet format
+///
+/// Once the server has sent `SetCompressionPacket`, every packet in either
+/// direction carries a Data Length VarInt after the Packet Length: 0 if the
+/// rest of the packet is uncompressed, otherwise the uncompressed length of
+/// the zlib-compressed Packet ID and Data that follow.
+pub struct CompressionStream<S> {
+    /// Underlying stream
+    inner: S,
+    /// Compression state
+    compression: Compression,
+}
+
+impl<S: Read + Write> CompressionStream<S> {
+    /// Wrap a stream using the given compression threshold
+    pub fn new(inner: S, threshold: u32) -> Self {
+        Self {
+            inner,
+            compression: Compression::new(threshold),
+        }
+    }
+
+    /// Get the compression threshold
+    pub fn threshold(&self) -> u32 {
+        self.compression.threshold()
+    }
+
+    /// Get a reference to the underlying stream
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the underlying stream
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Unwrap the underlying stream
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    /// Read a packet, returning its ID and data
+    pub fn read_packet(&mut self) -> Result<(VarInt, Vec<u8>)> {
+        let packet_length = VarInt::read(&mut self.inner)?;
+
+        if packet_length.0 <= 0 {
+            return Err(ServerError::Protocol(format!(
+                "Invalid packet length: {}",
+                packet_length.0
+            )));
+        }
+
+        let length = packet_length.0 as usize;
+        if length > crate::protocol::MAX_PACKET_SIZE {
+            return Err(ServerError::Protocol("Packet too large".to_string()));
+        }
+
+        let mut data = vec![0u8; length];
+        self.inner.read_exact(&mut data)?;
+
+        self.compression.decompress_packet(&data)
+    }
+
+    /// Write a packet
+    pub fn write_packet<P: Packet>(&mut self, packet: &P) -> Result<()> {
+        let mut data = Vec::new();
+        packet.write(&mut data)?;
+        self.write_raw_packet(P::id(), &data)
+    }
+
+    /// Write an already serialized packet with the given ID
+    pub fn write_raw_packet(&mut self, packet_id: VarInt, data: &[u8]) -> Result<()> {
+        let payload = self.compression.compress_packet(packet_id, data)?;
+
+        let mut buffer = Vec::with_capacity(payload.len() + 3);
+        VarInt(payload.len() as i32).write(&mut buffer)?;
+        buffer.extend_from_slice(&payload);
+
+        self.inner.write_all(&buffer)?;
+        self.inner.flush()?;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -262,4 +330,68 @@ mod tests {
         assert_eq!(packet_id, decoded_id);
         assert_eq!(data, decoded_data);
     }
+
+    #[test]
+    fn test_compression_zlib_roundtrip() {
+        let mut compression = Compression::new(64);
+        let packet_id = VarInt(0x27);
+        let data: Vec<u8> = (0..4096).map(|i| (i % 7) as u8).collect();
+
+        let compressed = compression.compress_packet(packet_id, &data).unwrap();
+        assert!(compressed.len() < data.len());
+
+        // The payload after the Data Length must be a standard zlib stream
+        let mut cursor = std::io::Cursor::new(&compressed);
+        let data_length = VarInt::read(&mut cursor).unwrap();
+        assert_eq!(data_length.0 as usize, data.len() + 1);
+
+        let mut inflated = Vec::new();
+        flate2::read::ZlibDecoder::new(&compressed[cursor.position() as usize..])
+            .read_to_end(&mut inflated)
+            .unwrap();
+        assert_eq!(inflated[0], 0x27);
+        assert_eq!(&inflated[1..], &data[..]);
+
+        let (decoded_id, decoded_data) = compression.decompress_packet(&compressed).unwrap();
+        assert_eq!(packet_id, decoded_id);
+        assert_eq!(data, decoded_data);
+    }
+
+    #[test]
+    fn test_compression_stream_below_threshold() {
+        let mut stream = CompressionStream::new(std::io::Cursor::new(Vec::new()), 256);
+        stream
+            .write_raw_packet(VarInt(0x01), &[0xAA, 0xBB])
+            .unwrap();
+
+        // Packet Length, Data Length 0, then the raw Packet ID and Data
+        let bytes = stream.into_inner().into_inner();
+        assert_eq!(bytes, vec![0x04, 0x00, 0x01, 0xAA, 0xBB]);
+
+        let mut stream = CompressionStream::new(std::io::Cursor::new(bytes), 256);
+        let (packet_id, data) = stream.read_packet().unwrap();
+        assert_eq!(packet_id, VarInt(0x01));
+        assert_eq!(data, vec![0xAA, 0xBB]);
+    }
+
+    #[test]
+    fn test_compression_stream_above_threshold() {
+        let data = vec![0x42u8; 1024];
+
+        let mut stream = CompressionStream::new(std::io::Cursor::new(Vec::new()), 256);
+        stream.write_raw_packet(VarInt(0x10), &data).unwrap();
+        stream.write_raw_packet(VarInt(0x11), &[0x01]).unwrap();
+
+        let bytes = stream.into_inner().into_inner();
+        assert!(bytes.len() < data.len());
+
+        let mut stream = CompressionStream::new(std::io::Cursor::new(bytes), 256);
+        let (packet_id, decoded) = stream.read_packet().unwrap();
+        assert_eq!(packet_id, VarInt(0x10));
+        assert_eq!(decoded, data);
+
+        let (packet_id, decoded) = stream.read_packet().unwrap();
+        assert_eq!(packet_id, VarInt(0x11));
+        assert_eq!(decoded, vec![0x01]);
+    }
 }
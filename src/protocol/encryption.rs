@@ -0,0 +1,284 @@
+//! Protocol encryption implementation
+//!
+//! This module handles the RSA key exchange performed during login and the
+//! AES-128-CFB8 stream encryption that is applied to every byte sent in either
+//! direction once the exchange has completed.
+
+use crate::error::{Result, ServerError};
+use aes::Aes128;
+use cfb8::cipher::generic_array::GenericArray;
+use cfb8::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use rsa::pkcs8::EncodePublicKey;
+use rsa::{Pkcs1v15Encrypt, RsaPrivateKey, RsaPublicKey};
+use sha1::{Digest, Sha1};
+use std::io::{Read, Write};
+
+type Aes128Cfb8Encryptor = cfb8::Encryptor<Aes128>;
+type Aes128Cfb8Decryptor = cfb8::Decryptor<Aes128>;
+
+/// Length of the verify token sent in the encryption request
+pub const VERIFY_TOKEN_LENGTH: usize = 4;
+
+/// Length of the shared secret chosen by the client
+pub const SHARED_SECRET_LENGTH: usize = 16;
+
+/// RSA key pair used by the server during the login encryption handshake
+pub struct ServerKeys {
+    /// Private key used to decrypt the client's shared secret and verify token
+    private_key: RsaPrivateKey,
+    /// Public key encoded in ASN.1 DER format, as sent to the client
+    public_key_der: Vec<u8>,
+}
+
+impl ServerKeys {
+    /// Key size used by the Notchian server
+    pub const KEY_BITS: usize = 1024;
+
+    /// Generate a new random key pair
+    pub fn generate() -> Result<Self> {
+        let mut rng = rand::thread_rng();
+        let private_key = RsaPrivateKey::new(&mut rng, Self::KEY_BITS)
+            .map_err(|e| ServerError::Encryption(format!("Failed to generate RSA key: {}", e)))?;
+
+        let public_key_der = RsaPublicKey::from(&private_key)
+            .to_public_key_der()
+            .map_err(|e| ServerError::Encryption(format!("Failed to encode public key: {}", e)))?
+            .as_bytes()
+            .to_vec();
+
+        Ok(Self {
+            private_key,
+            public_key_der,
+        })
+    }
+
+    /// Get the DER-encoded public key
+    pub fn public_key_der(&self) -> &[u8] {
+        &self.public_key_der
+    }
+
+    /// Decrypt data that the client encrypted with our public key
+    pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
+        self.private_key
+            .decrypt(Pkcs1v15Encrypt, data)
+            .map_err(|e| ServerError::Encryption(format!("RSA decryption failed: {}", e)))
+    }
+}
+
+/// AES-128-CFB8 cipher pair for an encrypted connection
+///
+/// The shared secret is used as both the key and the initial vector. The
+/// encryptor and decryptor keep their own state, so every byte must pass
+/// through them exactly once and in order.
+pub struct PacketCipher {
+    /// Cipher for outgoing data
+    encryptor: Aes128Cfb8Encryptor,
+    /// Cipher for incoming data
+    decryptor: Aes128Cfb8Decryptor,
+}
+
+impl PacketCipher {
+    /// Create a new cipher pair from the shared secret
+    pub fn new(shared_secret: &[u8]) -> Result<Self> {
+        if shared_secret.len() != SHARED_SECRET_LENGTH {
+            return Err(ServerError::Encryption(format!(
+                "Invalid shared secret length: {} (expected {})",
+                shared_secret.len(),
+                SHARED_SECRET_LENGTH
+            )));
+        }
+
+        let invalid_length = |_| ServerError::Encryption("Invalid shared secret".to_string());
+        let encryptor = Aes128Cfb8Encryptor::new_from_slices(shared_secret, shared_secret)
+            .map_err(invalid_length)?;
+        let decryptor = Aes128Cfb8Decryptor::new_from_slices(shared_secret, shared_secret)
+            .map_err(invalid_length)?;
+
+        Ok(Self {
+            encryptor,
+            decryptor,
+        })
+    }
+
+    /// Encrypt outgoing data in place
+    pub fn encrypt(&mut self, data: &mut [u8]) {
+        // CFB8 operates on single-byte blocks
+        for byte in data.chunks_mut(1) {
+            self.encryptor
+                .encrypt_block_mut(GenericArray::from_mut_slice(byte));
+        }
+    }
+
+    /// Decrypt incoming data in place
+    pub fn decrypt(&mut self, data: &mut [u8]) {
+        for byte in data.chunks_mut(1) {
+            self.decryptor
+                .decrypt_block_mut(GenericArray::from_mut_slice(byte));
+        }
+    }
+}
+
+/// A stream wrapper that transparently encrypts writes and decrypts reads
+pub struct EncryptedStream<S> {
+    /// Underlying stream
+    inner: S,
+    /// Cipher state
+    cipher: PacketCipher,
+}
+
+impl<S: Read + Write> EncryptedStream<S> {
+    /// Wrap a stream using the given shared secret
+    pub fn new(inner: S, shared_secret: &[u8]) -> Result<Self> {
+        Ok(Self {
+            inner,
+            cipher: PacketCipher::new(shared_secret)?,
+        })
+    }
+
+    /// Get a reference to the underlying stream
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the underlying stream
+    ///
+    /// Reading or writing through this reference bypasses the cipher and
+    /// will desynchronize the connection.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Unwrap the underlying stream
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: Read> Read for EncryptedStream<S> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let bytes_read = self.inner.read(buf)?;
+        self.cipher.decrypt(&mut buf[..bytes_read]);
+        Ok(bytes_read)
+    }
+}
+
+impl<S: Write> Write for EncryptedStream<S> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        // The cipher state has already advanced once the data is encrypted,
+        // so the whole buffer must be written to keep both sides in sync.
+        let mut encrypted = buf.to_vec();
+        self.cipher.encrypt(&mut encrypted);
+        self.inner.write_all(&encrypted)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Compute the server hash sent to the Mojang session server
+///
+/// This is a SHA-1 digest of the server ID, shared secret and public key,
+/// formatted the way Java's `BigInteger.toString(16)` formats it: the digest
+/// is treated as a signed two's complement number, so negative values are
+/// prefixed with `-` and leading zeros are stripped.
+pub fn minecraft_hex_digest(
+    server_id: &str,
+    shared_secret: &[u8],
+    public_key_der: &[u8],
+) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(server_id.as_bytes());
+    hasher.update(shared_secret);
+    hasher.update(public_key_der);
+    let mut digest: [u8; 20] = hasher.finalize().into();
+
+    let negative = digest[0] & 0x80 != 0;
+    if negative {
+        // Two's complement negation
+        let mut carry = true;
+        for byte in digest.iter_mut().rev() {
+            *byte = !*byte;
+            if carry {
+                let (value, overflow) = byte.overflowing_add(1);
+                *byte = value;
+                carry = overflow;
+            }
+        }
+    }
+
+    let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+    let trimmed = match hex.trim_start_matches('0') {
+        "" => "0",
+        trimmed => trimmed,
+    };
+
+    if negative {
+        format!("-{}", trimmed)
+    } else {
+        trimmed.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rsa::pkcs8::DecodePublicKey;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_minecraft_hex_digest() {
+        // Reference values from the protocol documentation
+        assert_eq!(
+            minecraft_hex_digest("Notch", &[], &[]),
+            "4ed1f46bbe04bc756bcb17c0c7ce3e4632f06a48"
+        );
+        assert_eq!(
+            minecraft_hex_digest("jeb_", &[], &[]),
+            "-7c9d5b0044c130109a5d7b5fb5c317c02b4e28c1"
+        );
+        assert_eq!(
+            minecraft_hex_digest("simon", &[], &[]),
+            "88e16a1019277b15d58faf0541e11910eb756f6"
+        );
+    }
+
+    #[test]
+    fn test_encrypted_stream_roundtrip() {
+        let secret = [7u8; SHARED_SECRET_LENGTH];
+        let message = b"Hello, encrypted world!";
+
+        let mut writer = EncryptedStream::new(Cursor::new(Vec::new()), &secret).unwrap();
+        writer.write_all(&message[..5]).unwrap();
+        writer.write_all(&message[5..]).unwrap();
+        let encrypted = writer.into_inner().into_inner();
+
+        assert_eq!(encrypted.len(), message.len());
+        assert_ne!(&encrypted[..], &message[..]);
+
+        let mut reader = EncryptedStream::new(Cursor::new(encrypted), &secret).unwrap();
+        let mut decrypted = Vec::new();
+        reader.read_to_end(&mut decrypted).unwrap();
+
+        assert_eq!(&decrypted[..], &message[..]);
+    }
+
+    #[test]
+    fn test_invalid_shared_secret_length() {
+        assert!(PacketCipher::new(&[0u8; 8]).is_err());
+    }
+
+    #[test]
+    fn test_server_keys_decrypt() {
+        let keys = ServerKeys::generate().unwrap();
+        let public_key = RsaPublicKey::from_public_key_der(keys.public_key_der()).unwrap();
+
+        let secret = [42u8; SHARED_SECRET_LENGTH];
+        let encrypted = public_key
+            .encrypt(&mut rand::thread_rng(), Pkcs1v15Encrypt, &secret)
+            .unwrap();
+
+        assert_eq!(keys.decrypt(&encrypted).unwrap(), secret);
+    }
+}
@@ -20,13 +20,20 @@
 //! - Data - Packet-specific data
 
 pub mod compression;
+pub mod encryption;
+pub mod nbt;
 pub mod packets;
 pub mod state;
+pub mod text;
 pub mod types;
+pub mod version;
 
-pub use compression::Compression;
+pub use compression::{Compression, CompressionStream};
+pub use nbt::{NbtCompound, NbtTag};
 pub use state::{ConnectionState, ProtocolState};
-pub use types::{McString, McUuid, Position, VarInt, VarLong};
+pub use text::{ClickEvent, NamedColor, TextComponent, TextComponentBuilder};
+pub use types::{Angle, McIdentifier, McString, McUuid, Position, Slot, VarInt, VarLong};
+pub use version::ProtocolVersion;
 
 /// Minecraft version string
 pub const MINECRAFT_VERSION: &str = "1.21.6";
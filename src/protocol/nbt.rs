@@ -0,0 +1,577 @@
+//! Named Binary Tag (NBT) implementation
+//!
+//! NBT is the binary format Minecraft uses for registry data, item
+//! components, block entities and world storage. Since 1.20.2 the network
+//! format omits the root tag's name ("network NBT"), while files still use
+//! a named root tag.
+//!
+//! Strings are encoded as Java's modified UTF-8: NUL is written as two bytes
+//! and supplementary characters are written as surrogate pairs.
+
+use crate::error::{Result, ServerError};
+use std::io::{Read, Write};
+
+/// Maximum nesting depth accepted when reading NBT
+const MAX_DEPTH: usize = 512;
+
+/// NBT tag type IDs
+mod tag_id {
+    pub const END: u8 = 0;
+    pub const BYTE: u8 = 1;
+    pub const SHORT: u8 = 2;
+    pub const INT: u8 = 3;
+    pub const LONG: u8 = 4;
+    pub const FLOAT: u8 = 5;
+    pub const DOUBLE: u8 = 6;
+    pub const BYTE_ARRAY: u8 = 7;
+    pub const STRING: u8 = 8;
+    pub const LIST: u8 = 9;
+    pub const COMPOUND: u8 = 10;
+    pub const INT_ARRAY: u8 = 11;
+    pub const LONG_ARRAY: u8 = 12;
+}
+
+/// A single NBT value
+#[derive(Debug, Clone, PartialEq)]
+pub enum NbtTag {
+    /// Signed 8-bit integer (also used for booleans)
+    Byte(i8),
+    /// Signed 16-bit integer
+    Short(i16),
+    /// Signed 32-bit integer
+    Int(i32),
+    /// Signed 64-bit integer
+    Long(i64),
+    /// 32-bit floating point number
+    Float(f32),
+    /// 64-bit floating point number
+    Double(f64),
+    /// Array of signed bytes
+    ByteArray(Vec<i8>),
+    /// UTF-8 string
+    String(String),
+    /// List of values that all share the same type
+    List(Vec<NbtTag>),
+    /// Ordered map of named values
+    Compound(NbtCompound),
+    /// Array of signed 32-bit integers
+    IntArray(Vec<i32>),
+    /// Array of signed 64-bit integers
+    LongArray(Vec<i64>),
+}
+
+/// An NBT compound that preserves insertion order
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NbtCompound {
+    /// Named entries in insertion order
+    entries: Vec<(String, NbtTag)>,
+}
+
+impl NbtCompound {
+    /// Create an empty compound
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert a value, replacing any existing value with the same name
+    pub fn insert(&mut self, name: impl Into<String>, value: impl Into<NbtTag>) {
+        let name = name.into();
+        let value = value.into();
+        match self.entries.iter_mut().find(|(key, _)| *key == name) {
+            Some((_, existing)) => *existing = value,
+            None => self.entries.push((name, value)),
+        }
+    }
+
+    /// Builder-style variant of [`NbtCompound::insert`]
+    pub fn with(mut self, name: impl Into<String>, value: impl Into<NbtTag>) -> Self {
+        self.insert(name, value);
+        self
+    }
+
+    /// Get a value by name
+    pub fn get(&self, name: &str) -> Option<&NbtTag> {
+        self.entries
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value)
+    }
+
+    /// Get a mutable value by name
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut NbtTag> {
+        self.entries
+            .iter_mut()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value)
+    }
+
+    /// Remove a value by name
+    pub fn remove(&mut self, name: &str) -> Option<NbtTag> {
+        let index = self.entries.iter().position(|(key, _)| key == name)?;
+        Some(self.entries.remove(index).1)
+    }
+
+    /// Check if the compound contains a value with the given name
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Get the number of entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the compound is empty
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterate over the entries in insertion order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &NbtTag)> {
+        self.entries
+            .iter()
+            .map(|(key, value)| (key.as_str(), value))
+    }
+
+    /// Write the compound payload (entries followed by an end tag)
+    fn write_payload<W: Write>(&self, writer: &mut W) -> Result<()> {
+        for (name, value) in &self.entries {
+            writer.write_all(&[value.id()])?;
+            write_string(name, writer)?;
+            value.write_payload(writer)?;
+        }
+        writer.write_all(&[tag_id::END])?;
+        Ok(())
+    }
+
+    /// Read a compound payload
+    fn read_payload<R: Read>(reader: &mut R, depth: usize) -> Result<Self> {
+        let mut compound = Self::new();
+        loop {
+            let id = read_u8(reader)?;
+            if id == tag_id::END {
+                return Ok(compound);
+            }
+            let name = read_string(reader)?;
+            let value = NbtTag::read_payload(id, reader, depth + 1)?;
+            compound.insert(name, value);
+        }
+    }
+}
+
+impl FromIterator<(String, NbtTag)> for NbtCompound {
+    fn from_iter<I: IntoIterator<Item = (String, NbtTag)>>(iter: I) -> Self {
+        let mut compound = Self::new();
+        for (name, value) in iter {
+            compound.insert(name, value);
+        }
+        compound
+    }
+}
+
+impl NbtTag {
+    /// Get the tag type ID
+    pub fn id(&self) -> u8 {
+        match self {
+            NbtTag::Byte(_) => tag_id::BYTE,
+            NbtTag::Short(_) => tag_id::SHORT,
+            NbtTag::Int(_) => tag_id::INT,
+            NbtTag::Long(_) => tag_id::LONG,
+            NbtTag::Float(_) => tag_id::FLOAT,
+            NbtTag::Double(_) => tag_id::DOUBLE,
+            NbtTag::ByteArray(_) => tag_id::BYTE_ARRAY,
+            NbtTag::String(_) => tag_id::STRING,
+            NbtTag::List(_) => tag_id::LIST,
+            NbtTag::Compound(_) => tag_id::COMPOUND,
+            NbtTag::IntArray(_) => tag_id::INT_ARRAY,
+            NbtTag::LongArray(_) => tag_id::LONG_ARRAY,
+        }
+    }
+
+    /// Write the tag in network format (type ID followed by the payload)
+    pub fn write_network<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(&[self.id()])?;
+        self.write_payload(writer)
+    }
+
+    /// Read a tag in network format
+    pub fn read_network<R: Read>(reader: &mut R) -> Result<Self> {
+        let id = read_u8(reader)?;
+        if id == tag_id::END {
+            return Err(ServerError::Nbt("Unexpected end tag at root".to_string()));
+        }
+        Self::read_payload(id, reader, 0)
+    }
+
+    /// Write an optional tag in network format, using an end tag for `None`
+    pub fn write_optional_network<W: Write>(tag: Option<&Self>, writer: &mut W) -> Result<()> {
+        match tag {
+            Some(tag) => tag.write_network(writer),
+            None => Ok(writer.write_all(&[tag_id::END])?),
+        }
+    }
+
+    /// Read an optional tag in network format, where an end tag means `None`
+    pub fn read_optional_network<R: Read>(reader: &mut R) -> Result<Option<Self>> {
+        let id = read_u8(reader)?;
+        if id == tag_id::END {
+            return Ok(None);
+        }
+        Self::read_payload(id, reader, 0).map(Some)
+    }
+
+    /// Write the tag with a named root, as used by NBT files
+    pub fn write_named<W: Write>(&self, name: &str, writer: &mut W) -> Result<()> {
+        writer.write_all(&[self.id()])?;
+        write_string(name, writer)?;
+        self.write_payload(writer)
+    }
+
+    /// Read a tag with a named root, as used by NBT files
+    pub fn read_named<R: Read>(reader: &mut R) -> Result<(String, Self)> {
+        let id = read_u8(reader)?;
+        if id == tag_id::END {
+            return Err(ServerError::Nbt("Unexpected end tag at root".to_string()));
+        }
+        let name = read_string(reader)?;
+        Ok((name, Self::read_payload(id, reader, 0)?))
+    }
+
+    /// Serialize the tag in network format into a new buffer
+    pub fn to_network_bytes(&self) -> Result<Vec<u8>> {
+        let mut buffer = Vec::new();
+        self.write_network(&mut buffer)?;
+        Ok(buffer)
+    }
+
+    /// Get the value as a compound, if it is one
+    pub fn as_compound(&self) -> Option<&NbtCompound> {
+        match self {
+            NbtTag::Compound(compound) => Some(compound),
+            _ => None,
+        }
+    }
+
+    /// Get the value as a string, if it is one
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            NbtTag::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get any integer value widened to an `i64`
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            NbtTag::Byte(value) => Some(value.into()),
+            NbtTag::Short(value) => Some(value.into()),
+            NbtTag::Int(value) => Some(value.into()),
+            NbtTag::Long(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get any numeric value as an `f64`
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            NbtTag::Float(value) => Some(value.into()),
+            NbtTag::Double(value) => Some(value),
+            _ => self.as_i64().map(|value| value as f64),
+        }
+    }
+
+    /// Write the payload of this tag
+    fn write_payload<W: Write>(&self, writer: &mut W) -> Result<()> {
+        match self {
+            NbtTag::Byte(value) => writer.write_all(&value.to_be_bytes())?,
+            NbtTag::Short(value) => writer.write_all(&value.to_be_bytes())?,
+            NbtTag::Int(value) => writer.write_all(&value.to_be_bytes())?,
+            NbtTag::Long(value) => writer.write_all(&value.to_be_bytes())?,
+            NbtTag::Float(value) => writer.write_all(&value.to_be_bytes())?,
+            NbtTag::Double(value) => writer.write_all(&value.to_be_bytes())?,
+            NbtTag::ByteArray(values) => {
+                write_length(values.len(), writer)?;
+                let bytes: Vec<u8> = values.iter().map(|&b| b as u8).collect();
+                writer.write_all(&bytes)?;
+            }
+            NbtTag::String(value) => write_string(value, writer)?,
+            NbtTag::List(values) => write_list(values, writer)?,
+            NbtTag::Compound(compound) => compound.write_payload(writer)?,
+            NbtTag::IntArray(values) => {
+                write_length(values.len(), writer)?;
+                for value in values {
+                    writer.write_all(&value.to_be_bytes())?;
+                }
+            }
+            NbtTag::LongArray(values) => {
+                write_length(values.len(), writer)?;
+                for value in values {
+                    writer.write_all(&value.to_be_bytes())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Read the payload of a tag with the given type ID
+    fn read_payload<R: Read>(id: u8, reader: &mut R, depth: usize) -> Result<Self> {
+        if depth > MAX_DEPTH {
+            return Err(ServerError::Nbt("NBT nested too deeply".to_string()));
+        }
+
+        let tag = match id {
+            tag_id::BYTE => NbtTag::Byte(i8::from_be_bytes(read_array(reader)?)),
+            tag_id::SHORT => NbtTag::Short(i16::from_be_bytes(read_array(reader)?)),
+            tag_id::INT => NbtTag::Int(i32::from_be_bytes(read_array(reader)?)),
+            tag_id::LONG => NbtTag::Long(i64::from_be_bytes(read_array(reader)?)),
+            tag_id::FLOAT => NbtTag::Float(f32::from_be_bytes(read_array(reader)?)),
+            tag_id::DOUBLE => NbtTag::Double(f64::from_be_bytes(read_array(reader)?)),
+            tag_id::BYTE_ARRAY => {
+                let length = read_length(reader)?;
+                let mut bytes = vec![0u8; length];
+                reader.read_exact(&mut bytes)?;
+                NbtTag::ByteArray(bytes.into_iter().map(|b| b as i8).collect())
+            }
+            tag_id::STRING => NbtTag::String(read_string(reader)?),
+            tag_id::LIST => {
+                let element_id = read_u8(reader)?;
+                let length = read_length(reader)?;
+                if element_id == tag_id::END && length > 0 {
+                    return Err(ServerError::Nbt("Non-empty list of end tags".to_string()));
+                }
+                let mut values = Vec::with_capacity(length.min(1024));
+                for _ in 0..length {
+                    values.push(Self::read_payload(element_id, reader, depth + 1)?);
+                }
+                NbtTag::List(values)
+            }
+            tag_id::COMPOUND => NbtTag::Compound(NbtCompound::read_payload(reader, depth)?),
+            tag_id::INT_ARRAY => {
+                let length = read_length(reader)?;
+                let mut values = Vec::with_capacity(length.min(1024));
+                for _ in 0..length {
+                    values.push(i32::from_be_bytes(read_array(reader)?));
+                }
+                NbtTag::IntArray(values)
+            }
+            tag_id::LONG_ARRAY => {
+                let length = read_length(reader)?;
+                let mut values = Vec::with_capacity(length.min(1024));
+                for _ in 0..length {
+                    values.push(i64::from_be_bytes(read_array(reader)?));
+                }
+                NbtTag::LongArray(values)
+            }
+            _ => return Err(ServerError::Nbt(format!("Unknown tag type: {}", id))),
+        };
+        Ok(tag)
+    }
+}
+
+macro_rules! impl_from_for_tag {
+    ($($ty:ty => $variant:ident),* $(,)?) => {
+        $(
+            impl From<$ty> for NbtTag {
+                fn from(value: $ty) -> Self {
+                    NbtTag::$variant(value.into())
+                }
+            }
+        )*
+    };
+}
+
+impl_from_for_tag! {
+    i8 => Byte,
+    i16 => Short,
+    i32 => Int,
+    i64 => Long,
+    f32 => Float,
+    f64 => Double,
+    String => String,
+    &str => String,
+    NbtCompound => Compound,
+    Vec<NbtTag> => List,
+    Vec<i32> => IntArray,
+    Vec<i64> => LongArray,
+}
+
+impl From<bool> for NbtTag {
+    fn from(value: bool) -> Self {
+        NbtTag::Byte(value as i8)
+    }
+}
+
+/// Write a list, checking that every element has the same type
+fn write_list<W: Write>(values: &[NbtTag], writer: &mut W) -> Result<()> {
+    let element_id = values.first().map_or(tag_id::END, NbtTag::id);
+    if values.iter().any(|value| value.id() != element_id) {
+        return Err(ServerError::Nbt(
+            "List elements must all have the same type".to_string(),
+        ));
+    }
+
+    writer.write_all(&[element_id])?;
+    write_length(values.len(), writer)?;
+    for value in values {
+        value.write_payload(writer)?;
+    }
+    Ok(())
+}
+
+/// Write an array or list length
+fn write_length<W: Write>(length: usize, writer: &mut W) -> Result<()> {
+    let length = i32::try_from(length)
+        .map_err(|_| ServerError::Nbt(format!("Length too large: {}", length)))?;
+    writer.write_all(&length.to_be_bytes())?;
+    Ok(())
+}
+
+/// Read an array or list length, rejecting negative values
+fn read_length<R: Read>(reader: &mut R) -> Result<usize> {
+    let length = i32::from_be_bytes(read_array(reader)?);
+    usize::try_from(length).map_err(|_| ServerError::Nbt(format!("Negative length: {}", length)))
+}
+
+/// Read a single byte
+fn read_u8<R: Read>(reader: &mut R) -> Result<u8> {
+    Ok(read_array::<1, R>(reader)?[0])
+}
+
+/// Read a fixed number of bytes
+fn read_array<const N: usize, R: Read>(reader: &mut R) -> Result<[u8; N]> {
+    let mut bytes = [0u8; N];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Write a string as length-prefixed modified UTF-8
+fn write_string<W: Write>(value: &str, writer: &mut W) -> Result<()> {
+    let mut bytes = Vec::with_capacity(value.len());
+    for unit in value.encode_utf16() {
+        match unit {
+            0x0001..=0x007F => bytes.push(unit as u8),
+            0x0000 | 0x0080..=0x07FF => {
+                bytes.push(0xC0 | (unit >> 6) as u8);
+                bytes.push(0x80 | (unit & 0x3F) as u8);
+            }
+            _ => {
+                bytes.push(0xE0 | (unit >> 12) as u8);
+                bytes.push(0x80 | ((unit >> 6) & 0x3F) as u8);
+                bytes.push(0x80 | (unit & 0x3F) as u8);
+            }
+        }
+    }
+
+    let length = u16::try_from(bytes.len())
+        .map_err(|_| ServerError::Nbt(format!("String too long: {} bytes", bytes.len())))?;
+    writer.write_all(&length.to_be_bytes())?;
+    writer.write_all(&bytes)?;
+    Ok(())
+}
+
+/// Read a length-prefixed modified UTF-8 string
+fn read_string<R: Read>(reader: &mut R) -> Result<String> {
+    let length = u16::from_be_bytes(read_array(reader)?) as usize;
+    let mut bytes = vec![0u8; length];
+    reader.read_exact(&mut bytes)?;
+
+    let invalid = || ServerError::Nbt("Invalid modified UTF-8 string".to_string());
+    let continuation = |byte: Option<&u8>| match byte {
+        Some(&byte) if byte & 0xC0 == 0x80 => Ok((byte & 0x3F) as u16),
+        _ => Err(invalid()),
+    };
+
+    let mut units = Vec::with_capacity(length);
+    let mut iter = bytes.iter();
+    while let Some(&byte) = iter.next() {
+        let unit = match byte {
+            0x01..=0x7F => byte as u16,
+            0xC0..=0xDF => ((byte as u16 & 0x1F) << 6) | continuation(iter.next())?,
+            0xE0..=0xEF => {
+                let high = continuation(iter.next())?;
+                let low = continuation(iter.next())?;
+                ((byte as u16 & 0x0F) << 12) | (high << 6) | low
+            }
+            _ => return Err(invalid()),
+        };
+        units.push(unit);
+    }
+
+    String::from_utf16(&units).map_err(|_| invalid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_hello_world_reference() {
+        // hello_world.nbt from the NBT specification
+        let bytes = [
+            0x0A, 0x00, 0x0B, b'h', b'e', b'l', b'l', b'o', b' ', b'w', b'o', b'r', b'l', b'd',
+            0x08, 0x00, 0x04, b'n', b'a', b'm', b'e', 0x00, 0x09, b'B', b'a', b'n', b'a', b'n',
+            b'r', b'a', b'm', b'a', 0x00,
+        ];
+
+        let (name, tag) = NbtTag::read_named(&mut Cursor::new(&bytes)).unwrap();
+        assert_eq!(name, "hello world");
+        let compound = tag.as_compound().unwrap();
+        assert_eq!(
+            compound.get("name").and_then(NbtTag::as_str),
+            Some("Bananrama")
+        );
+
+        let mut buffer = Vec::new();
+        tag.write_named(&name, &mut buffer).unwrap();
+        assert_eq!(buffer, bytes);
+    }
+
+    #[test]
+    fn test_network_roundtrip() {
+        let tag = NbtTag::Compound(
+            NbtCompound::new()
+                .with("byte", 1i8)
+                .with("short", -2i16)
+                .with("long", i64::MAX)
+                .with("double", 0.5f64)
+                .with("bytes", NbtTag::ByteArray(vec![-1, 0, 1]))
+                .with("ints", vec![1, 2, 3])
+                .with("longs", vec![4i64, 5])
+                .with("list", vec![NbtTag::from("a"), NbtTag::from("b")])
+                .with("empty", Vec::<NbtTag>::new())
+                .with("nested", NbtCompound::new().with("flag", true)),
+        );
+
+        let bytes = tag.to_network_bytes().unwrap();
+        assert_eq!(bytes[0], tag_id::COMPOUND);
+        assert_eq!(NbtTag::read_network(&mut Cursor::new(bytes)).unwrap(), tag);
+    }
+
+    #[test]
+    fn test_modified_utf8() {
+        let value = "nul\0 é \u{1F600}";
+        let mut buffer = Vec::new();
+        write_string(value, &mut buffer).unwrap();
+
+        // NUL is two bytes and the emoji is a surrogate pair of three bytes each
+        assert!(!buffer[2..].contains(&0));
+        assert_eq!(buffer.len(), 2 + 3 + 2 + 1 + 2 + 1 + 6);
+        assert_eq!(read_string(&mut Cursor::new(buffer)).unwrap(), value);
+    }
+
+    #[test]
+    fn test_mixed_list_rejected() {
+        let tag = NbtTag::List(vec![NbtTag::Int(1), NbtTag::Byte(1)]);
+        assert!(tag.to_network_bytes().is_err());
+    }
+
+    #[test]
+    fn test_compound_insert_replaces() {
+        let mut compound = NbtCompound::new().with("a", 1).with("b", 2);
+        compound.insert("a", 3);
+        let keys: Vec<&str> = compound.iter().map(|(key, _)| key).collect();
+        assert_eq!(keys, ["a", "b"]);
+        assert_eq!(compound.get("a"), Some(&NbtTag::Int(3)));
+    }
+}
@@ -4,10 +4,206 @@
 //! between login and play states. This phase allows the server to send
 //! various configuration data to the client before gameplay begins.
 
-use crate::error::Result;
+use crate::error::{Result, ServerError};
+use crate::protocol::nbt::NbtTag;
+use crate::protocol::packets::play::{
+    ClientSettingsPacket, ServerboundCustomPayloadPacket, StoreCookiePacket,
+};
 use crate::protocol::packets::{ClientboundPacket, Packet, ServerboundPacket};
-use crate::protocol::types::{McString, VarInt};
+use crate::protocol::text::TextComponent;
+use crate::protocol::types::{
+    JsonTextComponent, McIdentifier, McString, McUuid, VarInt, read_bool, read_uuid, write_bool,
+    write_uuid,
+};
 use std::io::{Read, Write};
+use std::sync::Arc;
+
+/// Highest serverbound packet ID defined in the configuration state
+const MAX_SERVERBOUND_PACKET_ID: i32 = 0x08;
+
+/// Check whether a serverbound packet ID is valid in the configuration state
+///
+/// Anything else (e.g. a play packet sent before the client acknowledged
+/// Finish Configuration) is a protocol violation.
+pub fn is_serverbound_packet_id(packet_id: i32) -> bool {
+    (0..=MAX_SERVERBOUND_PACKET_ID).contains(&packet_id)
+}
+
+/// Disconnect packet (clientbound, configuration state)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigurationDisconnectPacket {
+    /// Reason shown to the player, sent as NBT
+    pub reason: JsonTextComponent,
+}
+
+impl Packet for ConfigurationDisconnectPacket {
+    const ID: i32 = 0x02;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(ConfigurationDisconnectPacket {
+            reason: JsonTextComponent::read_nbt(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.reason.write_nbt(writer)
+    }
+}
+
+impl ClientboundPacket for ConfigurationDisconnectPacket {}
+
+/// Feature Flags packet (clientbound, configuration state)
+///
+/// Lists the feature flags enabled on the server, which include
+/// experimental content. Sent before the registries, as it decides which
+/// entries clients expect.
+#[doc(alias = "UpdateEnabledFeaturesPacket")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeaturesPacket {
+    /// Enabled feature flags, such as `minecraft:vanilla`
+    pub features: Vec<McIdentifier>,
+}
+
+impl Packet for FeaturesPacket {
+    const ID: i32 = 0x0C;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let count = VarInt::read(reader)?.0;
+        if count < 0 {
+            return Err(ServerError::Protocol(format!(
+                "Invalid feature flag count: {}",
+                count
+            )));
+        }
+        let features = (0..count)
+            .map(|_| McIdentifier::read(reader))
+            .collect::<Result<_>>()?;
+        Ok(FeaturesPacket { features })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        VarInt(self.features.len() as i32).write(writer)?;
+        for feature in &self.features {
+            feature.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl ClientboundPacket for FeaturesPacket {}
+
+/// A data pack, identified by its namespace, ID and version
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KnownPack {
+    /// Namespace of the pack, such as `minecraft`
+    pub namespace: McString,
+    /// ID of the pack within its namespace, such as `core`
+    pub id: McString,
+    /// Version of the pack, such as the game version
+    pub version: McString,
+}
+
+impl KnownPack {
+    /// Create a pack from its namespace, ID and version
+    pub fn new(namespace: &str, id: &str, version: &str) -> Self {
+        Self {
+            namespace: namespace.into(),
+            id: id.into(),
+            version: version.into(),
+        }
+    }
+
+    /// Read a pack from a reader
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            namespace: McString::read(reader)?,
+            id: McString::read(reader)?,
+            version: McString::read(reader)?,
+        })
+    }
+
+    /// Write a pack to a writer
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.namespace.write(writer)?;
+        self.id.write(writer)?;
+        self.version.write(writer)
+    }
+}
+
+/// Read a list of packs, allowing at most `max` of them
+fn read_known_packs<R: Read>(reader: &mut R, max: usize) -> Result<Vec<KnownPack>> {
+    let count = VarInt::read(reader)?.0;
+    if count < 0 || count as usize > max {
+        return Err(ServerError::Protocol(format!(
+            "Invalid known pack count: {}",
+            count
+        )));
+    }
+    (0..count).map(|_| KnownPack::read(reader)).collect()
+}
+
+/// Write a list of packs
+fn write_known_packs<W: Write>(packs: &[KnownPack], writer: &mut W) -> Result<()> {
+    VarInt(packs.len() as i32).write(writer)?;
+    for pack in packs {
+        pack.write(writer)?;
+    }
+    Ok(())
+}
+
+/// Known Packs packet (clientbound, configuration state)
+///
+/// Lists the data packs the server's registries come from. The client
+/// answers with the ones it has, whose registry entries the server can then
+/// send without their data.
+#[doc(alias = "ClientboundSelectKnownPacksPacket")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KnownPacksPacket {
+    /// Packs the server offers
+    pub packs: Vec<KnownPack>,
+}
+
+impl Packet for KnownPacksPacket {
+    const ID: i32 = 0x0E;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        read_known_packs(reader, i32::MAX as usize).map(|packs| KnownPacksPacket { packs })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_known_packs(&self.packs, writer)
+    }
+}
+
+impl ClientboundPacket for KnownPacksPacket {}
+
+/// Select Known Packs packet (serverbound, configuration state)
+///
+/// The packs offered by the server that the client has too.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectKnownPacksPacket {
+    /// Packs the client confirms knowing
+    pub packs: Vec<KnownPack>,
+}
+
+impl SelectKnownPacksPacket {
+    /// Maximum number of packs a client may confirm, as in vanilla
+    pub const MAX_PACKS: usize = 64;
+}
+
+impl Packet for SelectKnownPacksPacket {
+    const ID: i32 = 0x07;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        read_known_packs(reader, Self::MAX_PACKS).map(|packs| SelectKnownPacksPacket { packs })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_known_packs(&self.packs, writer)
+    }
+}
+
+impl ServerboundPacket for SelectKnownPacksPacket {}
 
 /// Finish Configuration packet (clientbound)
 ///
@@ -38,7 +234,7 @@ impl ClientboundPacket for FinishConfigurationPacket {}
 pub struct AcknowledgeFinishConfigurationPacket;
 
 impl Packet for AcknowledgeFinishConfigurationPacket {
-    const ID: i32 = 0x02;
+    const ID: i32 = 0x03;
 
     fn read<R: Read>(_reader: &mut R) -> Result<Self> {
         Ok(AcknowledgeFinishConfigurationPacket)
@@ -57,47 +253,80 @@ impl ServerboundPacket for AcknowledgeFinishConfigurationPacket {}
 #[derive(Debug, Clone)]
 pub struct RegistryDataPacket {
     /// Registry identifier
-    pub registry_id: McString,
+    pub registry_id: McIdentifier,
     /// Registry entries
     pub entries: Vec<RegistryEntry>,
 }
 
 /// Registry entry
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct RegistryEntry {
     /// Entry identifier
-    pub entry_id: McString,
-    /// Whether the entry has data
-    pub has_data: bool,
-    /// Entry data (if present)
-    pub data: Option<Vec<u8>>,
+    pub entry_id: McIdentifier,
+    /// Entry data, already encoded as a network NBT tag, or `None` if the
+    /// client should take it from a known pack
+    ///
+    /// The same encoded data is shared by the packets sent to every player.
+    pub data: Option<Arc<[u8]>>,
+}
+
+impl RegistryEntry {
+    /// Create a registry entry from encoded data
+    pub fn new(entry_id: McIdentifier, data: Option<Arc<[u8]>>) -> Self {
+        Self { entry_id, data }
+    }
+
+    /// Create a registry entry, encoding its data
+    pub fn from_nbt(entry_id: McIdentifier, data: Option<&NbtTag>) -> Result<Self> {
+        let data = data.map(NbtTag::to_network_bytes).transpose()?;
+        Ok(Self::new(entry_id, data.map(Into::into)))
+    }
+
+    /// Decode the entry data
+    pub fn nbt(&self) -> Result<Option<NbtTag>> {
+        self.data
+            .as_deref()
+            .map(|mut data| NbtTag::read_network(&mut data))
+            .transpose()
+    }
+}
+
+impl RegistryDataPacket {
+    /// Create a registry data packet from `(identifier, encoded data)` pairs
+    pub fn new(
+        registry_id: McIdentifier,
+        entries: impl IntoIterator<Item = (McIdentifier, Option<Arc<[u8]>>)>,
+    ) -> Self {
+        Self {
+            registry_id,
+            entries: entries
+                .into_iter()
+                .map(|(entry_id, data)| RegistryEntry::new(entry_id, data))
+                .collect(),
+        }
+    }
 }
 
 impl Packet for RegistryDataPacket {
     const ID: i32 = 0x07;
 
     fn read<R: Read>(reader: &mut R) -> Result<Self> {
-        let registry_id = McString::read(reader)?;
+        let registry_id = McIdentifier::read(reader)?;
         let entry_count = VarInt::read(reader)?;
 
         let mut entries = Vec::new();
         for _ in 0..entry_count.0 {
-            let entry_id = McString::read(reader)?;
+            let entry_id = McIdentifier::read(reader)?;
             let has_data = crate::protocol::types::read_bool(reader)?;
+
+            // The data is a network NBT tag, which is not length-prefixed
             let data = if has_data {
-                let data_length = VarInt::read(reader)?;
-                let mut data_bytes = vec![0u8; data_length.0 as usize];
-                reader.read_exact(&mut data_bytes)?;
-                Some(data_bytes)
+                Some(NbtTag::read_network(reader)?)
             } else {
                 None
             };
 
-            entries.push(RegistryEntry {
-                entry_id,
-                has_data,
-                data,
-            });
+            entries.push(RegistryEntry::from_nbt(entry_id, data.as_ref())?);
         }
 
         Ok(RegistryDataPacket {
@@ -112,9 +341,8 @@ impl Packet for RegistryDataPacket {
 
         for entry in &self.entries {
             entry.entry_id.write(writer)?;
-            crate::protocol::types::write_bool(entry.has_data, writer)?;
+            crate::protocol::types::write_bool(entry.data.is_some(), writer)?;
             if let Some(ref data) = entry.data {
-                VarInt(data.len() as i32).write(writer)?;
                 writer.write_all(data)?;
             }
         }
@@ -125,15 +353,259 @@ impl Packet for RegistryDataPacket {
 
 impl ClientboundPacket for RegistryDataPacket {}
 
+/// Client Information packet (serverbound, configuration state)
+///
+/// Same as the play state [`ClientSettingsPacket`], which the client sends
+/// again whenever its settings change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigurationClientSettingsPacket(pub ClientSettingsPacket);
+
+impl Packet for ConfigurationClientSettingsPacket {
+    const ID: i32 = 0x00;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        ClientSettingsPacket::read(reader).map(ConfigurationClientSettingsPacket)
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.0.write(writer)
+    }
+}
+
+impl ServerboundPacket for ConfigurationClientSettingsPacket {}
+
+/// Plugin Message packet (serverbound, configuration state)
+///
+/// Clients send their `minecraft:brand` in this state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigurationCustomPayloadPacket(pub ServerboundCustomPayloadPacket);
+
+impl Packet for ConfigurationCustomPayloadPacket {
+    const ID: i32 = 0x02;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        ServerboundCustomPayloadPacket::read(reader).map(ConfigurationCustomPayloadPacket)
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.0.write(writer)
+    }
+}
+
+impl ServerboundPacket for ConfigurationCustomPayloadPacket {}
+
+/// Store Cookie packet (clientbound, configuration state)
+///
+/// Same as the play state [`StoreCookiePacket`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigurationStoreCookiePacket(pub StoreCookiePacket);
+
+impl Packet for ConfigurationStoreCookiePacket {
+    const ID: i32 = 0x0A;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        StoreCookiePacket::read(reader).map(ConfigurationStoreCookiePacket)
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.0.write(writer)
+    }
+}
+
+impl ClientboundPacket for ConfigurationStoreCookiePacket {}
+
+/// Add Resource Pack packet (clientbound, configuration state)
+#[doc(alias = "AddResourcePackPacket")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourcePackSendPacket {
+    /// Pack ID, echoed in the client's responses
+    pub uuid: McUuid,
+    /// Download URL
+    pub url: McString,
+    /// SHA-1 hash of the pack as lowercase hex, or empty to skip the check
+    pub hash: McString,
+    /// Whether the client must accept the pack to join
+    pub forced: bool,
+    /// Message shown in the prompt
+    pub prompt: Option<TextComponent>,
+}
+
+impl ResourcePackSendPacket {
+    /// Maximum length of the pack hash
+    pub const MAX_HASH_LENGTH: usize = 40;
+
+    /// Check that the hash is at most 40 hexadecimal characters
+    fn validate_hash(hash: &str) -> Result<()> {
+        if hash.len() > Self::MAX_HASH_LENGTH || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(ServerError::Protocol(format!(
+                "Invalid resource pack hash: {:?}",
+                hash
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Packet for ResourcePackSendPacket {
+    const ID: i32 = 0x09;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let uuid = read_uuid(reader)?;
+        let url = McString::read(reader)?;
+        let hash = McString::read_with_max_length(reader, Self::MAX_HASH_LENGTH)?;
+        Self::validate_hash(&hash.0)?;
+        let forced = read_bool(reader)?;
+
+        let prompt = if read_bool(reader)? {
+            Some(TextComponent::read_nbt(reader)?)
+        } else {
+            None
+        };
+
+        Ok(ResourcePackSendPacket {
+            uuid,
+            url,
+            hash,
+            forced,
+            prompt,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        Self::validate_hash(&self.hash.0)?;
+
+        write_uuid(&self.uuid, writer)?;
+        self.url.write(writer)?;
+        self.hash.write(writer)?;
+        write_bool(self.forced, writer)?;
+
+        write_bool(self.prompt.is_some(), writer)?;
+        if let Some(prompt) = &self.prompt {
+            prompt.write_nbt(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl ClientboundPacket for ResourcePackSendPacket {}
+
+/// Status of a resource pack reported by the client
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ResourcePackResult {
+    /// The pack was downloaded and applied
+    SuccessfullyLoaded = 0,
+    /// The player declined the pack
+    Declined = 1,
+    /// The download failed
+    FailedDownload = 2,
+    /// The player accepted the pack and the download is starting
+    Accepted = 3,
+    /// The download finished and the pack is being applied
+    Downloaded = 4,
+    /// The URL was invalid
+    InvalidUrl = 5,
+    /// Applying the pack failed
+    FailedReload = 6,
+    /// The pack was discarded
+    Discarded = 7,
+}
+
+impl ResourcePackResult {
+    /// Whether the client is done with the pack, successfully or not
+    pub fn is_final(self) -> bool {
+        !matches!(
+            self,
+            ResourcePackResult::Accepted | ResourcePackResult::Downloaded
+        )
+    }
+}
+
+impl TryFrom<i32> for ResourcePackResult {
+    type Error = ServerError;
+
+    fn try_from(value: i32) -> Result<Self> {
+        match value {
+            0 => Ok(ResourcePackResult::SuccessfullyLoaded),
+            1 => Ok(ResourcePackResult::Declined),
+            2 => Ok(ResourcePackResult::FailedDownload),
+            3 => Ok(ResourcePackResult::Accepted),
+            4 => Ok(ResourcePackResult::Downloaded),
+            5 => Ok(ResourcePackResult::InvalidUrl),
+            6 => Ok(ResourcePackResult::FailedReload),
+            7 => Ok(ResourcePackResult::Discarded),
+            _ => Err(ServerError::Protocol(format!(
+                "Invalid resource pack result: {}",
+                value
+            ))),
+        }
+    }
+}
+
+/// Resource Pack Response packet (serverbound, configuration state)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResourcePackResponsePacket {
+    /// ID of the pack
+    pub uuid: McUuid,
+    /// Status of the pack
+    pub result: ResourcePackResult,
+}
+
+impl Packet for ResourcePackResponsePacket {
+    const ID: i32 = 0x06;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let uuid = read_uuid(reader)?;
+        let result = ResourcePackResult::try_from(VarInt::read(reader)?.0)?;
+        Ok(ResourcePackResponsePacket { uuid, result })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_uuid(&self.uuid, writer)?;
+        VarInt(self.result as i32).write(writer)
+    }
+}
+
+impl ServerboundPacket for ResourcePackResponsePacket {}
+
+/// Transfer packet (clientbound, configuration state)
+///
+/// Tells the client to disconnect and join another server, which it does
+/// with a handshake whose next state is Transfer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransferPacket {
+    /// Host name or IP address of the server
+    pub host: McString,
+    /// Port of the server
+    pub port: VarInt,
+}
+
+impl Packet for TransferPacket {
+    const ID: i32 = 0x0B;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let host = McString::read(reader)?;
+        let port = VarInt::read(reader)?;
+        Ok(TransferPacket { host, port })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.host.write(writer)?;
+        self.port.write(writer)
+    }
+}
+
+impl ClientboundPacket for TransferPacket {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::protocol::nbt::NbtCompound;
     use std::io::Cursor;
 
     #[test]
     fn test_registry_data_packet_empty() {
         let packet = RegistryDataPacket {
-            registry_id: "minecraft:dimension_type".into(),
+            registry_id: McIdentifier::minecraft("dimension_type"),
             entries: Vec::new(),
         };
 
@@ -143,10 +615,124 @@ mod tests {
         let mut cursor = Cursor::new(buffer);
         let decoded = RegistryDataPacket::read(&mut cursor).unwrap();
 
-        assert_eq!(packet.registry_id.0, decoded.registry_id.0);
+        assert_eq!(packet.registry_id, decoded.registry_id);
         assert_eq!(packet.entries.len(), decoded.entries.len());
     }
 
+    #[test]
+    fn test_registry_data_packet_reference_bytes() {
+        let packet = RegistryDataPacket::new(
+            McIdentifier::minecraft("test"),
+            [
+                (
+                    McIdentifier::minecraft("a"),
+                    Some(
+                        NbtTag::Compound(NbtCompound::new().with("x", 1))
+                            .to_network_bytes()
+                            .unwrap()
+                            .into(),
+                    ),
+                ),
+                (McIdentifier::new("custom", "b").unwrap(), None),
+            ],
+        );
+
+        let mut expected = vec![0x0E];
+        expected.extend_from_slice(b"minecraft:test");
+        expected.push(0x02); // Entry count
+        expected.push(0x0B);
+        expected.extend_from_slice(b"minecraft:a");
+        expected.push(0x01); // Has data
+        expected.extend_from_slice(&[0x0A, 0x03, 0x00, 0x01, b'x', 0x00, 0x00, 0x00, 0x01, 0x00]);
+        expected.push(0x08);
+        expected.extend_from_slice(b"custom:b");
+        expected.push(0x00); // No data
+
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(buffer, expected);
+
+        let decoded = RegistryDataPacket::read(&mut Cursor::new(expected)).unwrap();
+        assert_eq!(decoded.registry_id.to_string(), "minecraft:test");
+        assert_eq!(decoded.entries, packet.entries);
+        assert_eq!(
+            decoded.entries[0].nbt().unwrap(),
+            Some(NbtTag::Compound(NbtCompound::new().with("x", 1)))
+        );
+        assert_eq!(decoded.entries[1].nbt().unwrap(), None);
+    }
+
+    #[test]
+    fn test_transfer_packet() {
+        let packet = TransferPacket {
+            host: "lobby".into(),
+            port: VarInt(25565),
+        };
+
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(buffer, [5, b'l', b'o', b'b', b'b', b'y', 0xDD, 0xC7, 0x01]);
+
+        let decoded = TransferPacket::read(&mut Cursor::new(buffer)).unwrap();
+        assert_eq!(decoded, packet);
+    }
+
+    #[test]
+    fn test_features_packet_reference_bytes() {
+        let packet = FeaturesPacket {
+            features: vec![McIdentifier::minecraft("vanilla")],
+        };
+
+        let mut expected = vec![0x01, 0x11];
+        expected.extend_from_slice(b"minecraft:vanilla");
+
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(buffer, expected);
+        assert_eq!(
+            FeaturesPacket::read(&mut Cursor::new(expected)).unwrap(),
+            packet
+        );
+
+        // Identifiers are validated when read
+        let mut invalid = vec![0x01, 0x07];
+        invalid.extend_from_slice(b"Vanilla");
+        assert!(FeaturesPacket::read(&mut Cursor::new(invalid)).is_err());
+    }
+
+    #[test]
+    fn test_known_packs_roundtrip() {
+        let packet = KnownPacksPacket {
+            packs: vec![KnownPack::new("minecraft", "core", "1.21.6")],
+        };
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        let mut expected = vec![0x01, 0x09];
+        expected.extend_from_slice(b"minecraft");
+        expected.push(0x04);
+        expected.extend_from_slice(b"core");
+        expected.push(0x06);
+        expected.extend_from_slice(b"1.21.6");
+        assert_eq!(buffer, expected);
+
+        // The answer has the same layout
+        let selected = SelectKnownPacksPacket::read(&mut Cursor::new(buffer)).unwrap();
+        assert_eq!(selected.packs, packet.packs);
+    }
+
+    #[test]
+    fn test_too_many_known_packs() {
+        let packet = KnownPacksPacket {
+            packs: vec![
+                KnownPack::new("minecraft", "core", "1.21.6");
+                SelectKnownPacksPacket::MAX_PACKS + 1
+            ],
+        };
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert!(SelectKnownPacksPacket::read(&mut Cursor::new(buffer)).is_err());
+    }
+
     #[test]
     fn test_finish_configuration_packet() {
         let packet = FinishConfigurationPacket;
@@ -174,4 +760,43 @@ mod tests {
         // Should be empty packet
         assert_eq!(cursor.position(), 0);
     }
+
+    #[test]
+    fn test_resource_pack_send_packet() {
+        let packet = ResourcePackSendPacket {
+            uuid: McUuid::new_v4(),
+            url: "https://example.com/pack.zip".into(),
+            hash: "2fd4e1c67a2d28fced849ee1bb76e7391b93eb12".into(),
+            forced: true,
+            prompt: Some(TextComponent::text("Please accept")),
+        };
+
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        let decoded = ResourcePackSendPacket::read(&mut Cursor::new(buffer)).unwrap();
+        assert_eq!(decoded, packet);
+
+        for hash in ["not hex", "2fd4e1c67a2d28fced849ee1bb76e7391b93eb12a"] {
+            let packet = ResourcePackSendPacket {
+                hash: hash.into(),
+                ..packet.clone()
+            };
+            assert!(packet.write(&mut Vec::new()).is_err());
+        }
+    }
+
+    #[test]
+    fn test_resource_pack_response_packet() {
+        let uuid = McUuid::new_v4();
+        let mut data = uuid.as_bytes().to_vec();
+        data.push(0x03);
+
+        let packet = ResourcePackResponsePacket::read(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(packet.uuid, uuid);
+        assert_eq!(packet.result, ResourcePackResult::Accepted);
+        assert!(!packet.result.is_final());
+
+        data[16] = 0x08;
+        assert!(ResourcePackResponsePacket::read(&mut Cursor::new(data)).is_err());
+    }
 }
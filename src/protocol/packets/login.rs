@@ -2,11 +2,40 @@
 //!
 //! Login packets handle player authentication and encryption.
 
-use crate::error::Result;
+use crate::error::{Result, ServerError};
 use crate::protocol::packets::{ClientboundPacket, Packet, ServerboundPacket};
-use crate::protocol::types::{McString, McUuid, VarInt};
+use crate::protocol::types::{
+    ByteArray, JsonTextComponent, McIdentifier, McString, McUuid, ServerId, VarInt, read_bool,
+    write_bool,
+};
 use std::io::{Read, Write};
 
+/// Disconnect packet (clientbound, login state)
+///
+/// Unlike the other states, the login state still sends the reason as a
+/// JSON string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoginDisconnectPacket {
+    /// Reason shown to the player
+    pub reason: JsonTextComponent,
+}
+
+impl Packet for LoginDisconnectPacket {
+    const ID: i32 = 0x00;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(LoginDisconnectPacket {
+            reason: JsonTextComponent::read(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.reason.write(writer)
+    }
+}
+
+impl ClientboundPacket for LoginDisconnectPacket {}
+
 /// Login start packet (serverbound)
 #[derive(Debug, Clone)]
 pub struct LoginStartPacket {
@@ -34,6 +63,90 @@ impl Packet for LoginStartPacket {
 
 impl ServerboundPacket for LoginStartPacket {}
 
+/// Encryption request packet (clientbound)
+///
+/// Sent by the server to start the encryption handshake in online mode.
+#[derive(Debug, Clone)]
+pub struct EncryptionRequestPacket {
+    /// Server ID (always empty on modern servers)
+    pub server_id: McString,
+    /// Server's RSA public key in ASN.1 DER format
+    pub public_key: Vec<u8>,
+    /// Random token the client must encrypt and send back
+    pub verify_token: Vec<u8>,
+    /// Whether the client should authenticate with the Mojang session server
+    pub should_authenticate: bool,
+}
+
+impl Packet for EncryptionRequestPacket {
+    const ID: i32 = 0x01;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let server_id = McString::read_with_max_length(reader, ServerId::MAX_LENGTH)?;
+        let public_key = ByteArray::read(reader)?.into();
+        let verify_token = ByteArray::read(reader)?.into();
+        let should_authenticate = crate::protocol::types::read_bool(reader)?;
+
+        Ok(EncryptionRequestPacket {
+            server_id,
+            public_key,
+            verify_token,
+            should_authenticate,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.server_id.write(writer)?;
+
+        VarInt(self.public_key.len() as i32).write(writer)?;
+        writer.write_all(&self.public_key)?;
+
+        VarInt(self.verify_token.len() as i32).write(writer)?;
+        writer.write_all(&self.verify_token)?;
+
+        crate::protocol::types::write_bool(self.should_authenticate, writer)?;
+        Ok(())
+    }
+}
+
+impl ClientboundPacket for EncryptionRequestPacket {}
+
+/// Encryption response packet (serverbound)
+///
+/// Both fields are encrypted with the server's public key.
+#[derive(Debug, Clone)]
+pub struct EncryptionResponsePacket {
+    /// Shared secret chosen by the client
+    pub shared_secret: Vec<u8>,
+    /// Verify token from the encryption request
+    pub verify_token: Vec<u8>,
+}
+
+impl Packet for EncryptionResponsePacket {
+    const ID: i32 = 0x01;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let shared_secret = ByteArray::read(reader)?.into();
+        let verify_token = ByteArray::read(reader)?.into();
+
+        Ok(EncryptionResponsePacket {
+            shared_secret,
+            verify_token,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        VarInt(self.shared_secret.len() as i32).write(writer)?;
+        writer.write_all(&self.shared_secret)?;
+
+        VarInt(self.verify_token.len() as i32).write(writer)?;
+        writer.write_all(&self.verify_token)?;
+        Ok(())
+    }
+}
+
+impl ServerboundPacket for EncryptionResponsePacket {}
+
 /// Login success packet (clientbound)
 #[derive(Debug, Clone)]
 pub struct LoginSuccessPacket {
@@ -123,8 +236,79 @@ impl Packet for LoginAcknowledgedPacket {
 
 impl ServerboundPacket for LoginAcknowledgedPacket {}
 
+/// Cookie Request packet (clientbound, login state)
+///
+/// Asks the client for a cookie stored by this or an earlier server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CookieRequestPacket {
+    /// Key of the cookie
+    pub key: McIdentifier,
+}
+
+impl Packet for CookieRequestPacket {
+    const ID: i32 = 0x05;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(CookieRequestPacket {
+            key: McIdentifier::read(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.key.write(writer)
+    }
+}
+
+impl ClientboundPacket for CookieRequestPacket {}
+
+/// Cookie Response packet (serverbound, login state)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CookieResponsePacket {
+    /// Key of the requested cookie
+    pub key: McIdentifier,
+    /// Value of the cookie, or `None` if the client has no such cookie
+    pub payload: Option<Vec<u8>>,
+}
+
+impl CookieResponsePacket {
+    /// Maximum length of a cookie value
+    pub const MAX_PAYLOAD_LENGTH: usize = 5120;
+}
+
+impl Packet for CookieResponsePacket {
+    const ID: i32 = 0x04;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let key = McIdentifier::read(reader)?;
+        let payload = if read_bool(reader)? {
+            Some(ByteArray::read_with_max_length(reader, Self::MAX_PAYLOAD_LENGTH)?.into())
+        } else {
+            None
+        };
+        Ok(CookieResponsePacket { key, payload })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.key.write(writer)?;
+        write_bool(self.payload.is_some(), writer)?;
+        if let Some(payload) = &self.payload {
+            if payload.len() > Self::MAX_PAYLOAD_LENGTH {
+                return Err(ServerError::Protocol(format!(
+                    "Cookie too long: {} > {}",
+                    payload.len(),
+                    Self::MAX_PAYLOAD_LENGTH
+                )));
+            }
+            ByteArray(payload.clone()).write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl ServerboundPacket for CookieResponsePacket {}
+
 /// Player property (used in login success)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Property {
     /// Property name
     pub name: McString,
@@ -167,3 +351,53 @@ impl Property {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_cookie_packets() {
+        let request = CookieRequestPacket {
+            key: McIdentifier::new("lobby", "session").unwrap(),
+        };
+        let mut buffer = Vec::new();
+        request.write(&mut buffer).unwrap();
+        assert_eq!(buffer[0], 13);
+        assert_eq!(&buffer[1..], b"lobby:session");
+        assert_eq!(
+            CookieRequestPacket::read(&mut Cursor::new(buffer)).unwrap(),
+            request
+        );
+
+        for payload in [None, Some(vec![1, 2, 3])] {
+            let response = CookieResponsePacket {
+                key: request.key.clone(),
+                payload,
+            };
+            let mut buffer = Vec::new();
+            response.write(&mut buffer).unwrap();
+            assert_eq!(
+                CookieResponsePacket::read(&mut Cursor::new(buffer)).unwrap(),
+                response
+            );
+        }
+    }
+
+    #[test]
+    fn test_cookie_too_long() {
+        let response = CookieResponsePacket {
+            key: McIdentifier::minecraft("cookie"),
+            payload: Some(vec![0; CookieResponsePacket::MAX_PAYLOAD_LENGTH + 1]),
+        };
+        assert!(response.write(&mut Vec::new()).is_err());
+
+        // Reading checks the length before allocating the value
+        let mut buffer = Vec::new();
+        response.key.write(&mut buffer).unwrap();
+        write_bool(true, &mut buffer).unwrap();
+        VarInt(i32::MAX).write(&mut buffer).unwrap();
+        assert!(CookieResponsePacket::read(&mut Cursor::new(buffer)).is_err());
+    }
+}
@@ -0,0 +1,120 @@
+//! Player abilities packets
+//!
+//! The server tells the client what its player may do, such as flying, and
+//! the client tells the server when the player starts or stops flying.
+
+use crate::error::Result;
+use crate::protocol::packets::{ClientboundPacket, Packet, ServerboundPacket};
+use crate::protocol::types::{read_float, read_unsigned_byte, write_float, write_unsigned_byte};
+use std::io::{Read, Write};
+
+/// The player takes no damage
+pub const INVULNERABLE: u8 = 0x01;
+/// The player is flying
+pub const FLYING: u8 = 0x02;
+/// The player may start flying
+pub const ALLOW_FLYING: u8 = 0x04;
+/// The player breaks blocks instantly
+pub const INSTANT_BREAK: u8 = 0x08;
+
+/// Vanilla flying speed
+pub const DEFAULT_FLYING_SPEED: f32 = 0.05;
+/// Vanilla field of view modifier, which is the walking speed
+pub const DEFAULT_FIELD_OF_VIEW_MODIFIER: f32 = 0.1;
+
+/// Player Abilities packet (clientbound)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlayerAbilitiesPacket {
+    /// Ability flags, such as [`ALLOW_FLYING`]
+    pub flags: u8,
+    /// Flying speed
+    pub flying_speed: f32,
+    /// Field of view modifier
+    pub field_of_view_modifier: f32,
+}
+
+impl PlayerAbilitiesPacket {
+    /// Create the packet with vanilla speeds
+    pub fn new(flags: u8) -> Self {
+        Self {
+            flags,
+            flying_speed: DEFAULT_FLYING_SPEED,
+            field_of_view_modifier: DEFAULT_FIELD_OF_VIEW_MODIFIER,
+        }
+    }
+}
+
+impl Packet for PlayerAbilitiesPacket {
+    const ID: i32 = 0x39;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            flags: read_unsigned_byte(reader)?,
+            flying_speed: read_float(reader)?,
+            field_of_view_modifier: read_float(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_unsigned_byte(self.flags, writer)?;
+        write_float(self.flying_speed, writer)?;
+        write_float(self.field_of_view_modifier, writer)
+    }
+}
+
+impl ClientboundPacket for PlayerAbilitiesPacket {}
+
+/// Player Abilities packet (serverbound)
+///
+/// Only the [`FLYING`] flag is meaningful.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServerboundPlayerAbilitiesPacket {
+    /// Ability flags
+    pub flags: u8,
+}
+
+impl ServerboundPlayerAbilitiesPacket {
+    /// Whether the player says it is flying
+    pub fn is_flying(&self) -> bool {
+        self.flags & FLYING != 0
+    }
+}
+
+impl Packet for ServerboundPlayerAbilitiesPacket {
+    const ID: i32 = 0x27;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            flags: read_unsigned_byte(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_unsigned_byte(self.flags, writer)
+    }
+}
+
+impl ServerboundPacket for ServerboundPlayerAbilitiesPacket {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_player_abilities_roundtrip() {
+        let packet = PlayerAbilitiesPacket::new(INVULNERABLE | ALLOW_FLYING | INSTANT_BREAK);
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(buffer.len(), 9);
+        assert_eq!(buffer[0], 0x0D);
+        assert_eq!(
+            PlayerAbilitiesPacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+
+        let flying = ServerboundPlayerAbilitiesPacket::read(&mut Cursor::new([FLYING])).unwrap();
+        assert!(flying.is_flying());
+        assert!(!ServerboundPlayerAbilitiesPacket { flags: 0 }.is_flying());
+    }
+}
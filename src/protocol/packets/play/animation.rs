@@ -0,0 +1,136 @@
+//! Entity animation packets
+
+use crate::error::{Result, ServerError};
+use crate::protocol::packets::play::Hand;
+use crate::protocol::packets::{ClientboundPacket, Packet, ServerboundPacket};
+use crate::protocol::types::{VarInt, read_unsigned_byte, write_unsigned_byte};
+use std::io::{Read, Write};
+
+/// An animation played on an entity
+///
+/// Animation 1 used to show an entity taking damage, which now has a packet
+/// of its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Animation {
+    /// Swing the main arm
+    SwingMainArm = 0,
+    /// Get out of bed
+    LeaveBed = 2,
+    /// Swing the off-hand arm
+    SwingOffHand = 3,
+    /// Critical hit particles
+    CriticalHit = 4,
+    /// Critical hit particles of an enchanted weapon
+    MagicCriticalHit = 5,
+}
+
+impl Animation {
+    /// The animation of swinging the arm of a hand
+    pub fn swing(hand: Hand) -> Self {
+        match hand {
+            Hand::MainHand => Animation::SwingMainArm,
+            Hand::OffHand => Animation::SwingOffHand,
+        }
+    }
+}
+
+impl TryFrom<u8> for Animation {
+    type Error = ServerError;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(Animation::SwingMainArm),
+            2 => Ok(Animation::LeaveBed),
+            3 => Ok(Animation::SwingOffHand),
+            4 => Ok(Animation::CriticalHit),
+            5 => Ok(Animation::MagicCriticalHit),
+            _ => Err(ServerError::Protocol(format!(
+                "Invalid animation: {}",
+                value
+            ))),
+        }
+    }
+}
+
+/// Entity Animation packet (clientbound)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnimatePacket {
+    /// Entity ID
+    pub entity_id: VarInt,
+    /// Animation to play
+    pub animation: Animation,
+}
+
+impl Packet for AnimatePacket {
+    const ID: i32 = 0x02;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            entity_id: VarInt::read(reader)?,
+            animation: Animation::try_from(read_unsigned_byte(reader)?)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.entity_id.write(writer)?;
+        write_unsigned_byte(self.animation as u8, writer)
+    }
+}
+
+impl ClientboundPacket for AnimatePacket {}
+
+/// Swing Arm packet (serverbound)
+///
+/// Sent when the player swings an arm, such as when attacking or digging.
+#[doc(alias = "SwingPacket")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwingArmPacket {
+    /// Hand of the arm swung
+    pub hand: Hand,
+}
+
+impl Packet for SwingArmPacket {
+    const ID: i32 = 0x3C;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            hand: Hand::try_from(VarInt::read(reader)?.0)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        VarInt(self.hand as i32).write(writer)
+    }
+}
+
+impl ServerboundPacket for SwingArmPacket {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_animate_roundtrip() {
+        let packet = AnimatePacket {
+            entity_id: VarInt(300),
+            animation: Animation::swing(Hand::OffHand),
+        };
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(buffer, [0xAC, 0x02, 3]);
+        assert_eq!(
+            AnimatePacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+        assert!(AnimatePacket::read(&mut Cursor::new([1, 1])).is_err());
+    }
+
+    #[test]
+    fn test_swing_arm() {
+        let packet = SwingArmPacket::read(&mut Cursor::new([1])).unwrap();
+        assert_eq!(packet.hand, Hand::OffHand);
+        assert!(SwingArmPacket::read(&mut Cursor::new([2])).is_err());
+    }
+}
@@ -0,0 +1,178 @@
+//! Entity attribute packets
+//!
+//! Attributes such as movement speed and maximum health are sent as their ID
+//! in the `minecraft:attribute` registry, a base value and the modifiers
+//! applied on top of it. The client computes the final value itself.
+
+use crate::error::{Result, ServerError};
+use crate::protocol::packets::{ClientboundPacket, Packet};
+use crate::protocol::types::{McIdentifier, VarInt, read_double, write_double};
+use std::io::{Read, Write};
+
+/// How a modifier changes the value of an attribute
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i32)]
+pub enum ModifierOperation {
+    /// Add the amount to the base value
+    AddValue = 0,
+    /// Add the amount multiplied by the base value
+    AddMultipliedBase = 1,
+    /// Multiply the value so far by one plus the amount
+    AddMultipliedTotal = 2,
+}
+
+impl TryFrom<i32> for ModifierOperation {
+    type Error = ServerError;
+
+    fn try_from(value: i32) -> Result<Self> {
+        match value {
+            0 => Ok(ModifierOperation::AddValue),
+            1 => Ok(ModifierOperation::AddMultipliedBase),
+            2 => Ok(ModifierOperation::AddMultipliedTotal),
+            _ => Err(ServerError::Protocol(format!(
+                "Invalid attribute modifier operation: {}",
+                value
+            ))),
+        }
+    }
+}
+
+/// A modifier applied to an attribute
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeModifier {
+    /// Identifier of the modifier (e.g. "minecraft:sprinting"), unique per
+    /// attribute
+    pub id: McIdentifier,
+    /// Amount the modifier changes the value by
+    pub amount: f64,
+    /// How the amount is applied
+    pub operation: ModifierOperation,
+}
+
+impl AttributeModifier {
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            id: McIdentifier::read(reader)?,
+            amount: read_double(reader)?,
+            operation: ModifierOperation::try_from(VarInt::read(reader)?.0)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.id.write(writer)?;
+        write_double(self.amount, writer)?;
+        VarInt(self.operation as i32).write(writer)
+    }
+}
+
+/// An attribute of an entity as sent to the client
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeProperty {
+    /// ID of the attribute in the `minecraft:attribute` registry
+    pub attribute_id: VarInt,
+    /// Base value, before modifiers
+    pub base: f64,
+    /// Modifiers applied to the base value
+    pub modifiers: Vec<AttributeModifier>,
+}
+
+/// Update Attributes packet (clientbound)
+///
+/// Only the attributes listed change; the others keep their values.
+#[doc(alias = "UpdateEntityAttributesPacket")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpdateAttributesPacket {
+    /// Entity ID
+    pub entity_id: VarInt,
+    /// Attributes to update
+    pub attributes: Vec<AttributeProperty>,
+}
+
+impl Packet for UpdateAttributesPacket {
+    const ID: i32 = 0x7C;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let entity_id = VarInt::read(reader)?;
+        let count = VarInt::read(reader)?.0;
+        let mut attributes = Vec::new();
+        for _ in 0..count {
+            let attribute_id = VarInt::read(reader)?;
+            let base = read_double(reader)?;
+            let modifier_count = VarInt::read(reader)?.0;
+            let mut modifiers = Vec::new();
+            for _ in 0..modifier_count {
+                modifiers.push(AttributeModifier::read(reader)?);
+            }
+            attributes.push(AttributeProperty {
+                attribute_id,
+                base,
+                modifiers,
+            });
+        }
+        Ok(Self {
+            entity_id,
+            attributes,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.entity_id.write(writer)?;
+        VarInt(self.attributes.len() as i32).write(writer)?;
+        for attribute in &self.attributes {
+            attribute.attribute_id.write(writer)?;
+            write_double(attribute.base, writer)?;
+            VarInt(attribute.modifiers.len() as i32).write(writer)?;
+            for modifier in &attribute.modifiers {
+                modifier.write(writer)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ClientboundPacket for UpdateAttributesPacket {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_update_attributes_roundtrip() {
+        let packet = UpdateAttributesPacket {
+            entity_id: VarInt(7),
+            attributes: vec![
+                AttributeProperty {
+                    attribute_id: VarInt(22),
+                    base: 0.1,
+                    modifiers: vec![AttributeModifier {
+                        id: McIdentifier::minecraft("sprinting"),
+                        amount: 0.3,
+                        operation: ModifierOperation::AddMultipliedTotal,
+                    }],
+                },
+                AttributeProperty {
+                    attribute_id: VarInt(19),
+                    base: 20.0,
+                    modifiers: Vec::new(),
+                },
+            ],
+        };
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(&buffer[..3], [7, 2, 22]);
+        assert_eq!(
+            UpdateAttributesPacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+    }
+
+    #[test]
+    fn test_invalid_operation() {
+        assert!(ModifierOperation::try_from(3).is_err());
+        assert_eq!(
+            ModifierOperation::try_from(1).unwrap(),
+            ModifierOperation::AddMultipliedBase
+        );
+    }
+}
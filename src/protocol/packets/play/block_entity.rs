@@ -0,0 +1,76 @@
+//! Block entity packets
+//!
+//! Block entities hold the data of blocks that need more than a block state,
+//! such as the text of a sign.
+
+use crate::error::Result;
+use crate::protocol::nbt::NbtTag;
+use crate::protocol::packets::{ClientboundPacket, Packet};
+use crate::protocol::types::{Position, VarInt};
+use std::io::{Read, Write};
+
+/// ID of `minecraft:sign` in the `minecraft:block_entity_type` registry
+pub const SIGN_BLOCK_ENTITY: i32 = 7;
+
+/// Block Entity Data packet (clientbound)
+#[doc(alias = "BlockEntityUpdatePacket")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockEntityDataPacket {
+    /// Position of the block
+    pub position: Position,
+    /// ID of the type in the `minecraft:block_entity_type` registry
+    pub block_entity_type: VarInt,
+    /// Data of the block entity, or `None` to clear it
+    pub data: Option<NbtTag>,
+}
+
+impl Packet for BlockEntityDataPacket {
+    const ID: i32 = 0x06;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            position: Position::read(reader)?,
+            block_entity_type: VarInt::read(reader)?,
+            data: NbtTag::read_optional_network(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.position.write(writer)?;
+        self.block_entity_type.write(writer)?;
+        NbtTag::write_optional_network(self.data.as_ref(), writer)
+    }
+}
+
+impl ClientboundPacket for BlockEntityDataPacket {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::nbt::NbtCompound;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_block_entity_data_roundtrip() {
+        let packet = BlockEntityDataPacket {
+            position: Position::new(1, 64, -1),
+            block_entity_type: VarInt(SIGN_BLOCK_ENTITY),
+            data: Some(NbtTag::Compound(NbtCompound::new().with("is_waxed", false))),
+        };
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(buffer[8], SIGN_BLOCK_ENTITY as u8);
+        assert_eq!(
+            BlockEntityDataPacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+
+        let cleared = BlockEntityDataPacket {
+            data: None,
+            ..packet
+        };
+        let mut buffer = Vec::new();
+        cleared.write(&mut buffer).unwrap();
+        assert_eq!(buffer.len(), 10);
+    }
+}
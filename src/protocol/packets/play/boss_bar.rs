@@ -0,0 +1,268 @@
+//! Boss bar packets
+//!
+//! Boss bars are drawn at the top of the screen. Each is identified by a
+//! UUID, and every packet about it carries one action.
+
+use crate::error::{Result, ServerError};
+use crate::protocol::packets::{ClientboundPacket, Packet};
+use crate::protocol::text::TextComponent;
+use crate::protocol::types::{
+    McUuid, VarInt, read_float, read_unsigned_byte, read_uuid, write_float, write_unsigned_byte,
+    write_uuid,
+};
+use std::io::{Read, Write};
+
+/// Flag darkening the sky while the bar is shown
+pub const DARKEN_SKY: u8 = 0x01;
+/// Flag playing the end boss music while the bar is shown
+pub const PLAY_BOSS_MUSIC: u8 = 0x02;
+/// Flag creating fog while the bar is shown
+pub const CREATE_FOG: u8 = 0x04;
+
+/// Color of a boss bar
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[repr(i32)]
+pub enum BossBarColor {
+    /// Pink
+    #[default]
+    Pink = 0,
+    /// Blue
+    Blue = 1,
+    /// Red
+    Red = 2,
+    /// Green
+    Green = 3,
+    /// Yellow
+    Yellow = 4,
+    /// Purple
+    Purple = 5,
+    /// White
+    White = 6,
+}
+
+impl TryFrom<i32> for BossBarColor {
+    type Error = ServerError;
+
+    fn try_from(value: i32) -> Result<Self> {
+        match value {
+            0 => Ok(BossBarColor::Pink),
+            1 => Ok(BossBarColor::Blue),
+            2 => Ok(BossBarColor::Red),
+            3 => Ok(BossBarColor::Green),
+            4 => Ok(BossBarColor::Yellow),
+            5 => Ok(BossBarColor::Purple),
+            6 => Ok(BossBarColor::White),
+            _ => Err(ServerError::Protocol(format!(
+                "Invalid boss bar color: {}",
+                value
+            ))),
+        }
+    }
+}
+
+/// Number of notches a boss bar is divided into
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[repr(i32)]
+pub enum BossBarDivision {
+    /// A solid bar
+    #[default]
+    None = 0,
+    /// 6 notches
+    Six = 1,
+    /// 10 notches
+    Ten = 2,
+    /// 12 notches
+    Twelve = 3,
+    /// 20 notches
+    Twenty = 4,
+}
+
+impl TryFrom<i32> for BossBarDivision {
+    type Error = ServerError;
+
+    fn try_from(value: i32) -> Result<Self> {
+        match value {
+            0 => Ok(BossBarDivision::None),
+            1 => Ok(BossBarDivision::Six),
+            2 => Ok(BossBarDivision::Ten),
+            3 => Ok(BossBarDivision::Twelve),
+            4 => Ok(BossBarDivision::Twenty),
+            _ => Err(ServerError::Protocol(format!(
+                "Invalid boss bar division: {}",
+                value
+            ))),
+        }
+    }
+}
+
+/// Change made by a [`BossBarPacket`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum BossBarAction {
+    /// Show a new boss bar
+    Add {
+        /// Text above the bar
+        title: TextComponent,
+        /// How full the bar is, from 0.0 to 1.0
+        health: f32,
+        /// Color of the bar
+        color: BossBarColor,
+        /// Notches of the bar
+        division: BossBarDivision,
+        /// Bit mask of [`DARKEN_SKY`], [`PLAY_BOSS_MUSIC`] and [`CREATE_FOG`]
+        flags: u8,
+    },
+    /// Hide the boss bar
+    Remove,
+    /// Change how full the bar is
+    UpdateHealth(f32),
+    /// Change the text above the bar
+    UpdateTitle(TextComponent),
+    /// Change the color and notches of the bar
+    UpdateStyle {
+        /// Color of the bar
+        color: BossBarColor,
+        /// Notches of the bar
+        division: BossBarDivision,
+    },
+    /// Change the flags of the bar
+    UpdateFlags(u8),
+}
+
+impl BossBarAction {
+    /// Protocol ID of the action
+    pub fn id(&self) -> i32 {
+        match self {
+            BossBarAction::Add { .. } => 0,
+            BossBarAction::Remove => 1,
+            BossBarAction::UpdateHealth(_) => 2,
+            BossBarAction::UpdateTitle(_) => 3,
+            BossBarAction::UpdateStyle { .. } => 4,
+            BossBarAction::UpdateFlags(_) => 5,
+        }
+    }
+}
+
+/// Boss Bar packet (clientbound)
+#[doc(alias = "BossEventPacket")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BossBarPacket {
+    /// Boss bar the action applies to
+    pub uuid: McUuid,
+    /// What changes
+    pub action: BossBarAction,
+}
+
+impl Packet for BossBarPacket {
+    const ID: i32 = 0x09;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let uuid = read_uuid(reader)?;
+        let action = match VarInt::read(reader)?.0 {
+            0 => BossBarAction::Add {
+                title: TextComponent::read_nbt(reader)?,
+                health: read_float(reader)?,
+                color: BossBarColor::try_from(VarInt::read(reader)?.0)?,
+                division: BossBarDivision::try_from(VarInt::read(reader)?.0)?,
+                flags: read_unsigned_byte(reader)?,
+            },
+            1 => BossBarAction::Remove,
+            2 => BossBarAction::UpdateHealth(read_float(reader)?),
+            3 => BossBarAction::UpdateTitle(TextComponent::read_nbt(reader)?),
+            4 => BossBarAction::UpdateStyle {
+                color: BossBarColor::try_from(VarInt::read(reader)?.0)?,
+                division: BossBarDivision::try_from(VarInt::read(reader)?.0)?,
+            },
+            5 => BossBarAction::UpdateFlags(read_unsigned_byte(reader)?),
+            action => {
+                return Err(ServerError::Protocol(format!(
+                    "Invalid boss bar action: {}",
+                    action
+                )));
+            }
+        };
+        Ok(Self { uuid, action })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_uuid(&self.uuid, writer)?;
+        VarInt(self.action.id()).write(writer)?;
+        match &self.action {
+            BossBarAction::Add {
+                title,
+                health,
+                color,
+                division,
+                flags,
+            } => {
+                title.write_nbt(writer)?;
+                write_float(*health, writer)?;
+                VarInt(*color as i32).write(writer)?;
+                VarInt(*division as i32).write(writer)?;
+                write_unsigned_byte(*flags, writer)
+            }
+            BossBarAction::Remove => Ok(()),
+            BossBarAction::UpdateHealth(health) => write_float(*health, writer),
+            BossBarAction::UpdateTitle(title) => title.write_nbt(writer),
+            BossBarAction::UpdateStyle { color, division } => {
+                VarInt(*color as i32).write(writer)?;
+                VarInt(*division as i32).write(writer)
+            }
+            BossBarAction::UpdateFlags(flags) => write_unsigned_byte(*flags, writer),
+        }
+    }
+}
+
+impl ClientboundPacket for BossBarPacket {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn roundtrip(action: BossBarAction) -> Vec<u8> {
+        let packet = BossBarPacket {
+            uuid: McUuid::from_u128(7),
+            action,
+        };
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(
+            BossBarPacket::read(&mut Cursor::new(buffer.clone())).unwrap(),
+            packet
+        );
+        buffer
+    }
+
+    #[test]
+    fn test_boss_bar_roundtrip() {
+        roundtrip(BossBarAction::Add {
+            title: TextComponent::text("Ender Dragon"),
+            health: 0.5,
+            color: BossBarColor::Purple,
+            division: BossBarDivision::Twelve,
+            flags: PLAY_BOSS_MUSIC | CREATE_FOG,
+        });
+        roundtrip(BossBarAction::UpdateTitle(TextComponent::text("Wither")));
+        roundtrip(BossBarAction::UpdateStyle {
+            color: BossBarColor::White,
+            division: BossBarDivision::None,
+        });
+
+        assert_eq!(&roundtrip(BossBarAction::Remove)[16..], [1]);
+        assert_eq!(
+            &roundtrip(BossBarAction::UpdateHealth(1.0))[16..],
+            [2, 0x3F, 0x80, 0, 0]
+        );
+        assert_eq!(
+            &roundtrip(BossBarAction::UpdateFlags(DARKEN_SKY))[16..],
+            [5, 1]
+        );
+    }
+
+    #[test]
+    fn test_invalid_action() {
+        let mut data = vec![0; 16];
+        data.push(6);
+        assert!(BossBarPacket::read(&mut Cursor::new(data)).is_err());
+    }
+}
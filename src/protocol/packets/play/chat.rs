@@ -0,0 +1,324 @@
+//! Player chat packet
+//!
+//! Player chat carries the signed parts of a message so clients can check
+//! who wrote it. Messages without a signature are shown as not secure.
+
+use crate::error::{Result, ServerError};
+use crate::protocol::packets::play::ChatMessagePacket;
+use crate::protocol::packets::{ClientboundPacket, Packet};
+use crate::protocol::text::TextComponent;
+use crate::protocol::types::{
+    BitSet, McString, McUuid, VarInt, read_bool, read_long, read_uuid, write_bool, write_long,
+    write_uuid,
+};
+use std::io::{Read, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Length of a message signature
+const SIGNATURE_LENGTH: usize = ChatMessagePacket::SIGNATURE_LENGTH;
+
+/// Maximum number of previous messages a message refers to
+const MAX_PREVIOUS_MESSAGES: usize = 20;
+
+/// Read a signature, which has a fixed length and no length prefix
+fn read_signature<R: Read>(reader: &mut R) -> Result<Vec<u8>> {
+    let mut signature = vec![0u8; SIGNATURE_LENGTH];
+    reader.read_exact(&mut signature)?;
+    Ok(signature)
+}
+
+/// Write a signature, checking its length
+fn write_signature<W: Write>(signature: &[u8], writer: &mut W) -> Result<()> {
+    if signature.len() != SIGNATURE_LENGTH {
+        return Err(ServerError::Protocol(format!(
+            "Chat signature must be {} bytes, got {}",
+            SIGNATURE_LENGTH,
+            signature.len()
+        )));
+    }
+    writer.write_all(signature)?;
+    Ok(())
+}
+
+/// A message the client saw before the one being sent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PreviousMessage {
+    /// A message the client still has, by its ID in the client's cache
+    Cached(i32),
+    /// A message given by its signature
+    Signature(Vec<u8>),
+}
+
+impl PreviousMessage {
+    /// Read a previous message, whose ID is offset by one so zero means a
+    /// signature follows
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        match VarInt::read(reader)?.0 {
+            0 => Ok(PreviousMessage::Signature(read_signature(reader)?)),
+            id => Ok(PreviousMessage::Cached(id - 1)),
+        }
+    }
+
+    /// Write a previous message
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        match self {
+            PreviousMessage::Cached(id) => VarInt(id + 1).write(writer),
+            PreviousMessage::Signature(signature) => {
+                VarInt(0).write(writer)?;
+                write_signature(signature, writer)
+            }
+        }
+    }
+}
+
+/// How the server's chat filter changed a message
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum FilterType {
+    /// The message is shown as sent
+    #[default]
+    PassThrough,
+    /// The whole message is hidden
+    FullyFiltered,
+    /// The characters whose bit is set are hidden
+    PartiallyFiltered(BitSet),
+}
+
+impl FilterType {
+    /// Read a filter type and its mask
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        match VarInt::read(reader)?.0 {
+            0 => Ok(FilterType::PassThrough),
+            1 => Ok(FilterType::FullyFiltered),
+            2 => Ok(FilterType::PartiallyFiltered(BitSet::read(reader)?)),
+            value => Err(ServerError::Protocol(format!(
+                "Invalid chat filter type: {}",
+                value
+            ))),
+        }
+    }
+
+    /// Write a filter type and its mask
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        match self {
+            FilterType::PassThrough => VarInt(0).write(writer),
+            FilterType::FullyFiltered => VarInt(1).write(writer),
+            FilterType::PartiallyFiltered(mask) => {
+                VarInt(2).write(writer)?;
+                mask.write(writer)
+            }
+        }
+    }
+}
+
+/// Player Chat packet (clientbound)
+///
+/// Shows a message sent by a player. The client decorates it with the chat
+/// type, so `sender_name` is the player's name alone.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerChatPacket {
+    /// Number of player chat packets sent to this client before, which the
+    /// client checks to detect missing messages
+    pub global_index: VarInt,
+    /// Player who sent the message
+    pub sender: McUuid,
+    /// Index of the message in the sender's signed chain
+    pub index: VarInt,
+    /// Signature of the message, if it is signed
+    pub signature: Option<Vec<u8>>,
+    /// Message as the player typed it
+    pub message: McString,
+    /// Time the message was sent, in milliseconds since the Unix epoch
+    pub timestamp: i64,
+    /// Salt the message was signed with
+    pub salt: i64,
+    /// Messages the sender saw before this one
+    pub previous_messages: Vec<PreviousMessage>,
+    /// Content shown instead of the message, such as a decorated version
+    pub unsigned_content: Option<TextComponent>,
+    /// How the chat filter changed the message
+    pub filter: FilterType,
+    /// ID of the chat type in the `minecraft:chat_type` registry
+    pub chat_type: VarInt,
+    /// Name of the sender, used by the chat type
+    pub sender_name: TextComponent,
+    /// Name of the recipient or team, used by some chat types
+    pub target_name: Option<TextComponent>,
+}
+
+impl PlayerChatPacket {
+    /// Create an unsigned message sent now, with no previous messages
+    pub fn unsigned(
+        sender: McUuid,
+        sender_name: TextComponent,
+        message: impl Into<String>,
+        chat_type: i32,
+    ) -> Self {
+        let message = message.into();
+        Self {
+            global_index: VarInt(0),
+            sender,
+            // Unsigned messages are outside any chain, which clients treat
+            // as index 0
+            index: VarInt(0),
+            signature: None,
+            unsigned_content: Some(TextComponent::text(message.clone())),
+            message: McString(message),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_millis() as i64)
+                .unwrap_or_default(),
+            salt: 0,
+            previous_messages: Vec::new(),
+            filter: FilterType::PassThrough,
+            chat_type: VarInt(chat_type),
+            sender_name,
+            target_name: None,
+        }
+    }
+}
+
+impl Packet for PlayerChatPacket {
+    const ID: i32 = 0x3A;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let global_index = VarInt::read(reader)?;
+        let sender = read_uuid(reader)?;
+        let index = VarInt::read(reader)?;
+        let signature = if read_bool(reader)? {
+            Some(read_signature(reader)?)
+        } else {
+            None
+        };
+        let message =
+            McString::read_with_max_length(reader, ChatMessagePacket::MAX_MESSAGE_LENGTH)?;
+        let timestamp = read_long(reader)?;
+        let salt = read_long(reader)?;
+        let count = VarInt::read(reader)?.0;
+        if !(0..=MAX_PREVIOUS_MESSAGES as i32).contains(&count) {
+            return Err(ServerError::Protocol(format!(
+                "Invalid previous message count: {}",
+                count
+            )));
+        }
+        let previous_messages = (0..count)
+            .map(|_| PreviousMessage::read(reader))
+            .collect::<Result<_>>()?;
+        let unsigned_content = if read_bool(reader)? {
+            Some(TextComponent::read_nbt(reader)?)
+        } else {
+            None
+        };
+        let filter = FilterType::read(reader)?;
+        // Chat types are sent by registry ID plus one; zero would be an
+        // inline definition, which the server never sends
+        let chat_type = match VarInt::read(reader)?.0 {
+            0 => {
+                return Err(ServerError::Protocol(
+                    "Inline chat types are not supported".to_string(),
+                ));
+            }
+            id => VarInt(id - 1),
+        };
+        let sender_name = TextComponent::read_nbt(reader)?;
+        let target_name = if read_bool(reader)? {
+            Some(TextComponent::read_nbt(reader)?)
+        } else {
+            None
+        };
+        Ok(Self {
+            global_index,
+            sender,
+            index,
+            signature,
+            message,
+            timestamp,
+            salt,
+            previous_messages,
+            unsigned_content,
+            filter,
+            chat_type,
+            sender_name,
+            target_name,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.global_index.write(writer)?;
+        write_uuid(&self.sender, writer)?;
+        self.index.write(writer)?;
+        write_bool(self.signature.is_some(), writer)?;
+        if let Some(signature) = &self.signature {
+            write_signature(signature, writer)?;
+        }
+        self.message.write(writer)?;
+        write_long(self.timestamp, writer)?;
+        write_long(self.salt, writer)?;
+        VarInt(self.previous_messages.len() as i32).write(writer)?;
+        for previous in &self.previous_messages {
+            previous.write(writer)?;
+        }
+        write_bool(self.unsigned_content.is_some(), writer)?;
+        if let Some(content) = &self.unsigned_content {
+            content.write_nbt(writer)?;
+        }
+        self.filter.write(writer)?;
+        VarInt(self.chat_type.0 + 1).write(writer)?;
+        self.sender_name.write_nbt(writer)?;
+        write_bool(self.target_name.is_some(), writer)?;
+        if let Some(target_name) = &self.target_name {
+            target_name.write_nbt(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl ClientboundPacket for PlayerChatPacket {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_player_chat_roundtrip() {
+        let sender = McUuid::new_v4();
+        let mut packet = PlayerChatPacket::unsigned(sender, TextComponent::text("Alex"), "hi", 0);
+        packet.global_index = VarInt(3);
+        packet.timestamp = 1_700_000_000_000;
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        // Global index, sender, chain index, no signature, then the message
+        assert_eq!(buffer[0], 3);
+        assert_eq!(buffer[1..17], *sender.as_bytes());
+        assert_eq!(buffer[17..21], [0, 0, 2, b'h']);
+        assert_eq!(
+            PlayerChatPacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+
+        let signed = PlayerChatPacket {
+            signature: Some(vec![7; SIGNATURE_LENGTH]),
+            previous_messages: vec![
+                PreviousMessage::Cached(4),
+                PreviousMessage::Signature(vec![1; SIGNATURE_LENGTH]),
+            ],
+            filter: FilterType::PartiallyFiltered(BitSet(vec![0b10])),
+            target_name: Some(TextComponent::text("Steve")),
+            ..packet
+        };
+        let mut buffer = Vec::new();
+        signed.write(&mut buffer).unwrap();
+        assert_eq!(
+            PlayerChatPacket::read(&mut Cursor::new(buffer)).unwrap(),
+            signed
+        );
+    }
+
+    #[test]
+    fn test_invalid_signature_length() {
+        let mut packet =
+            PlayerChatPacket::unsigned(McUuid::new_v4(), TextComponent::text("Alex"), "hi", 0);
+        packet.signature = Some(vec![0; 16]);
+        assert!(packet.write(&mut Vec::new()).is_err());
+    }
+}
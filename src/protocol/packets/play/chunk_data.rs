@@ -0,0 +1,653 @@
+//! Chunk data packet
+//!
+//! The Chunk Data and Update Light packet carries a full chunk column: its
+//! heightmaps, every chunk section of the dimension, block entities and
+//! light. The layout follows the 1.21.5+ format, where heightmaps are sent as
+//! typed long arrays and paletted container data arrays have no length
+//! prefix (the client derives it from the bits per entry).
+
+use crate::error::{Result, ServerError};
+use crate::protocol::nbt::NbtTag;
+use crate::protocol::packets::{ClientboundPacket, Packet};
+use crate::protocol::types::{
+    BitSet, VarInt, read_int, read_long, read_short, read_unsigned_byte, write_int, write_long,
+    write_short, write_unsigned_byte,
+};
+use std::io::{Cursor, Read, Write};
+
+/// Number of block state entries in a chunk section (16x16x16)
+pub const SECTION_BLOCK_COUNT: usize = 4096;
+
+/// Number of biome entries in a chunk section (4x4x4)
+pub const SECTION_BIOME_COUNT: usize = 64;
+
+/// Length in bytes of a single section's light array (4 bits per block)
+pub const LIGHT_ARRAY_LENGTH: usize = 2048;
+
+/// Maximum number of array entries accepted when reading a chunk packet
+const MAX_ARRAY_LENGTH: usize = 1 << 16;
+
+/// What a paletted container stores, which decides its palette thresholds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PalettedContainerKind {
+    /// Block states of a section
+    BlockStates,
+    /// Biomes of a section
+    Biomes,
+}
+
+impl PalettedContainerKind {
+    /// Number of entries in a container of this kind
+    pub fn entry_count(self) -> usize {
+        match self {
+            PalettedContainerKind::BlockStates => SECTION_BLOCK_COUNT,
+            PalettedContainerKind::Biomes => SECTION_BIOME_COUNT,
+        }
+    }
+
+    /// Largest bits per entry that still uses an indirect palette
+    fn max_indirect_bits(self) -> u8 {
+        match self {
+            PalettedContainerKind::BlockStates => 8,
+            PalettedContainerKind::Biomes => 3,
+        }
+    }
+
+    /// Smallest bits per entry of an indirect palette
+    fn min_indirect_bits(self) -> u8 {
+        match self {
+            PalettedContainerKind::BlockStates => 4,
+            PalettedContainerKind::Biomes => 1,
+        }
+    }
+
+    /// Bits per entry of a direct palette, enough for every ID in the
+    /// 1.21.6 block state and biome registries
+    fn direct_bits(self) -> u8 {
+        match self {
+            PalettedContainerKind::BlockStates => 15,
+            PalettedContainerKind::Biomes => 7,
+        }
+    }
+}
+
+/// The palette of a paletted container
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Palette {
+    /// Every entry has the same value (bits per entry is 0)
+    SingleValue(i32),
+    /// Entries index into a list of registry IDs
+    Indirect(Vec<i32>),
+    /// Entries are registry IDs
+    Direct,
+}
+
+/// A paletted container as sent on the wire
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PalettedContainer {
+    /// Bits used by each entry in `data`
+    pub bits_per_entry: u8,
+    /// Palette mapping entries to registry IDs
+    pub palette: Palette,
+    /// Packed entries; entries never span two longs
+    pub data: Vec<i64>,
+}
+
+impl PalettedContainer {
+    /// Create a container where every entry has the same value
+    pub fn single_value(value: i32) -> Self {
+        Self {
+            bits_per_entry: 0,
+            palette: Palette::SingleValue(value),
+            data: Vec::new(),
+        }
+    }
+
+    /// Encode entries with the smallest palette that fits them
+    pub fn from_entries(entries: &[i32], kind: PalettedContainerKind) -> Self {
+        let mut palette: Vec<i32> = Vec::new();
+        for &entry in entries {
+            if !palette.contains(&entry) {
+                palette.push(entry);
+            }
+        }
+        if let [value] = palette[..] {
+            return Self::single_value(value);
+        }
+
+        let needed = (usize::BITS - (palette.len().max(1) - 1).leading_zeros()) as u8;
+        let indirect_bits = needed.max(kind.min_indirect_bits());
+        let (bits_per_entry, palette) = if indirect_bits <= kind.max_indirect_bits() {
+            (indirect_bits, Palette::Indirect(palette))
+        } else {
+            (kind.direct_bits(), Palette::Direct)
+        };
+
+        let bits = bits_per_entry as usize;
+        let per_long = 64 / bits;
+        let mut data = vec![0i64; Self::data_length(bits_per_entry, entries.len())];
+        for (index, &entry) in entries.iter().enumerate() {
+            let value = match &palette {
+                Palette::Indirect(ids) => ids.iter().position(|&id| id == entry).unwrap_or(0),
+                _ => entry as usize,
+            };
+            data[index / per_long] |= (value as i64) << ((index % per_long) * bits);
+        }
+
+        Self {
+            bits_per_entry,
+            palette,
+            data,
+        }
+    }
+
+    /// Number of longs needed to pack `entry_count` entries
+    pub fn data_length(bits_per_entry: u8, entry_count: usize) -> usize {
+        if bits_per_entry == 0 {
+            return 0;
+        }
+        let entries_per_long = 64 / bits_per_entry as usize;
+        entry_count.div_ceil(entries_per_long)
+    }
+
+    /// Read a paletted container of the given kind
+    pub fn read<R: Read>(reader: &mut R, kind: PalettedContainerKind) -> Result<Self> {
+        let bits_per_entry = read_unsigned_byte(reader)?;
+
+        let palette = if bits_per_entry == 0 {
+            Palette::SingleValue(VarInt::read(reader)?.0)
+        } else if bits_per_entry <= kind.max_indirect_bits() {
+            let length = read_array_length(reader, "palette")?;
+            let entries = (0..length)
+                .map(|_| VarInt::read(reader).map(|id| id.0))
+                .collect::<Result<Vec<_>>>()?;
+            Palette::Indirect(entries)
+        } else if bits_per_entry <= 32 {
+            Palette::Direct
+        } else {
+            return Err(ServerError::Protocol(format!(
+                "Invalid bits per entry: {}",
+                bits_per_entry
+            )));
+        };
+
+        let data = (0..Self::data_length(bits_per_entry, kind.entry_count()))
+            .map(|_| read_long(reader))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            bits_per_entry,
+            palette,
+            data,
+        })
+    }
+
+    /// Write the paletted container
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_unsigned_byte(self.bits_per_entry, writer)?;
+
+        match &self.palette {
+            Palette::SingleValue(value) => VarInt(*value).write(writer)?,
+            Palette::Indirect(entries) => {
+                VarInt(entries.len() as i32).write(writer)?;
+                for &entry in entries {
+                    VarInt(entry).write(writer)?;
+                }
+            }
+            Palette::Direct => {}
+        }
+
+        for &long in &self.data {
+            write_long(long, writer)?;
+        }
+        Ok(())
+    }
+}
+
+/// A 16x16x16 chunk section
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkSection {
+    /// Number of non-air blocks, used by the client for rendering decisions
+    pub block_count: i16,
+    /// Block states of the section
+    pub block_states: PalettedContainer,
+    /// Biomes of the section, in 4x4x4 cells
+    pub biomes: PalettedContainer,
+}
+
+impl ChunkSection {
+    /// Create a section filled with a single block state and biome
+    pub fn filled(block_state: i32, biome: i32, block_count: i16) -> Self {
+        Self {
+            block_count,
+            block_states: PalettedContainer::single_value(block_state),
+            biomes: PalettedContainer::single_value(biome),
+        }
+    }
+
+    /// Read a chunk section
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            block_count: read_short(reader)?,
+            block_states: PalettedContainer::read(reader, PalettedContainerKind::BlockStates)?,
+            biomes: PalettedContainer::read(reader, PalettedContainerKind::Biomes)?,
+        })
+    }
+
+    /// Write the chunk section
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_short(self.block_count, writer)?;
+        self.block_states.write(writer)?;
+        self.biomes.write(writer)
+    }
+}
+
+/// Heightmap types known to the client
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i32)]
+pub enum HeightmapKind {
+    /// Highest non-air block, used during world generation
+    WorldSurfaceWg = 0,
+    /// Highest non-air block
+    WorldSurface = 1,
+    /// Highest solid block, used during world generation
+    OceanFloorWg = 2,
+    /// Highest solid block
+    OceanFloor = 3,
+    /// Highest block that blocks motion or contains a fluid
+    MotionBlocking = 4,
+    /// Like `MotionBlocking`, but ignoring leaves
+    MotionBlockingNoLeaves = 5,
+}
+
+impl TryFrom<i32> for HeightmapKind {
+    type Error = ServerError;
+
+    fn try_from(value: i32) -> Result<Self> {
+        match value {
+            0 => Ok(HeightmapKind::WorldSurfaceWg),
+            1 => Ok(HeightmapKind::WorldSurface),
+            2 => Ok(HeightmapKind::OceanFloorWg),
+            3 => Ok(HeightmapKind::OceanFloor),
+            4 => Ok(HeightmapKind::MotionBlocking),
+            5 => Ok(HeightmapKind::MotionBlockingNoLeaves),
+            _ => Err(ServerError::Protocol(format!(
+                "Invalid heightmap type: {}",
+                value
+            ))),
+        }
+    }
+}
+
+/// A heightmap of a chunk column
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heightmap {
+    /// Heightmap type
+    pub kind: HeightmapKind,
+    /// 256 packed heights, each one more than the highest matching block
+    /// relative to the bottom of the world (0 means no block)
+    pub data: Vec<i64>,
+}
+
+impl Heightmap {
+    /// Create a heightmap with no blocks for a world of the given height
+    pub fn empty(kind: HeightmapKind, world_height: u32) -> Self {
+        Self {
+            kind,
+            data: vec![0; Self::data_length(world_height)],
+        }
+    }
+
+    /// Bits used by each height in a world of the given height
+    pub fn bits_per_entry(world_height: u32) -> u8 {
+        (u32::BITS - world_height.leading_zeros()) as u8
+    }
+
+    /// Number of longs in a heightmap for a world of the given height
+    pub fn data_length(world_height: u32) -> usize {
+        PalettedContainer::data_length(Self::bits_per_entry(world_height), 256)
+    }
+
+    /// Read a heightmap
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let kind = HeightmapKind::try_from(VarInt::read(reader)?.0)?;
+        let length = read_array_length(reader, "heightmap")?;
+        let data = (0..length)
+            .map(|_| read_long(reader))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { kind, data })
+    }
+
+    /// Write the heightmap
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        VarInt(self.kind as i32).write(writer)?;
+        VarInt(self.data.len() as i32).write(writer)?;
+        for &long in &self.data {
+            write_long(long, writer)?;
+        }
+        Ok(())
+    }
+}
+
+/// A block entity sent as part of a chunk
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChunkBlockEntity {
+    /// Section-relative X and Z packed as `(x << 4) | z`
+    pub packed_xz: u8,
+    /// Absolute Y coordinate
+    pub y: i16,
+    /// Block entity type registry ID
+    pub kind: VarInt,
+    /// Block entity data, without position or ID
+    pub data: Option<NbtTag>,
+}
+
+impl ChunkBlockEntity {
+    /// Read a chunk block entity
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let packed_xz = read_unsigned_byte(reader)?;
+        let y = read_short(reader)?;
+        let kind = VarInt::read(reader)?;
+        let data = NbtTag::read_optional_network(reader)?;
+        Ok(Self {
+            packed_xz,
+            y,
+            kind,
+            data,
+        })
+    }
+
+    /// Write the chunk block entity
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_unsigned_byte(self.packed_xz, writer)?;
+        write_short(self.y, writer)?;
+        self.kind.write(writer)?;
+        NbtTag::write_optional_network(self.data.as_ref(), writer)
+    }
+}
+
+/// Light data of a chunk column
+///
+/// Masks have one bit per section plus one below and one above the world.
+/// Each set bit in a light mask has a matching array, in bit order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LightData {
+    /// Sections with sky light arrays
+    pub sky_light_mask: BitSet,
+    /// Sections with block light arrays
+    pub block_light_mask: BitSet,
+    /// Sections whose sky light is all zero
+    pub empty_sky_light_mask: BitSet,
+    /// Sections whose block light is all zero
+    pub empty_block_light_mask: BitSet,
+    /// Sky light arrays of 2048 bytes each
+    pub sky_light: Vec<Vec<u8>>,
+    /// Block light arrays of 2048 bytes each
+    pub block_light: Vec<Vec<u8>>,
+}
+
+impl LightData {
+    /// Read light data
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            sky_light_mask: BitSet::read(reader)?,
+            block_light_mask: BitSet::read(reader)?,
+            empty_sky_light_mask: BitSet::read(reader)?,
+            empty_block_light_mask: BitSet::read(reader)?,
+            sky_light: read_light_arrays(reader)?,
+            block_light: read_light_arrays(reader)?,
+        })
+    }
+
+    /// Write light data
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.sky_light_mask.write(writer)?;
+        self.block_light_mask.write(writer)?;
+        self.empty_sky_light_mask.write(writer)?;
+        self.empty_block_light_mask.write(writer)?;
+        write_light_arrays(&self.sky_light, writer)?;
+        write_light_arrays(&self.block_light, writer)
+    }
+}
+
+/// Chunk Data and Update Light packet (clientbound)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChunkDataPacket {
+    /// Chunk X coordinate
+    pub chunk_x: i32,
+    /// Chunk Z coordinate
+    pub chunk_z: i32,
+    /// Heightmaps of the column
+    pub heightmaps: Vec<Heightmap>,
+    /// Every section of the dimension, from the bottom up
+    pub sections: Vec<ChunkSection>,
+    /// Block entities in the column
+    pub block_entities: Vec<ChunkBlockEntity>,
+    /// Light data of the column
+    pub light: LightData,
+}
+
+impl Packet for ChunkDataPacket {
+    const ID: i32 = 0x27;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let chunk_x = read_int(reader)?;
+        let chunk_z = read_int(reader)?;
+
+        let heightmap_count = read_array_length(reader, "heightmap list")?;
+        let heightmaps = (0..heightmap_count)
+            .map(|_| Heightmap::read(reader))
+            .collect::<Result<Vec<_>>>()?;
+
+        let data_length = VarInt::read(reader)?;
+        if data_length.0 < 0 || data_length.0 as usize > 2 * 1024 * 1024 {
+            return Err(ServerError::Protocol(format!(
+                "Invalid chunk data length: {}",
+                data_length.0
+            )));
+        }
+        let mut data = vec![0u8; data_length.0 as usize];
+        reader.read_exact(&mut data)?;
+
+        let mut cursor = Cursor::new(data.as_slice());
+        let mut sections = Vec::new();
+        while (cursor.position() as usize) < data.len() {
+            sections.push(ChunkSection::read(&mut cursor)?);
+        }
+
+        let block_entity_count = read_array_length(reader, "block entity list")?;
+        let block_entities = (0..block_entity_count)
+            .map(|_| ChunkBlockEntity::read(reader))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            chunk_x,
+            chunk_z,
+            heightmaps,
+            sections,
+            block_entities,
+            light: LightData::read(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_int(self.chunk_x, writer)?;
+        write_int(self.chunk_z, writer)?;
+
+        VarInt(self.heightmaps.len() as i32).write(writer)?;
+        for heightmap in &self.heightmaps {
+            heightmap.write(writer)?;
+        }
+
+        let mut data = Vec::new();
+        for section in &self.sections {
+            section.write(&mut data)?;
+        }
+        VarInt(data.len() as i32).write(writer)?;
+        writer.write_all(&data)?;
+
+        VarInt(self.block_entities.len() as i32).write(writer)?;
+        for block_entity in &self.block_entities {
+            block_entity.write(writer)?;
+        }
+
+        self.light.write(writer)
+    }
+}
+
+impl ClientboundPacket for ChunkDataPacket {}
+
+/// Read a VarInt array length, rejecting negative or oversized values
+fn read_array_length<R: Read>(reader: &mut R, what: &str) -> Result<usize> {
+    let length = VarInt::read(reader)?;
+    if length.0 < 0 || length.0 as usize > MAX_ARRAY_LENGTH {
+        return Err(ServerError::Protocol(format!(
+            "Invalid {} length: {}",
+            what, length.0
+        )));
+    }
+    Ok(length.0 as usize)
+}
+
+/// Read a list of light arrays
+fn read_light_arrays<R: Read>(reader: &mut R) -> Result<Vec<Vec<u8>>> {
+    let count = read_array_length(reader, "light array list")?;
+    (0..count)
+        .map(|_| {
+            let length = VarInt::read(reader)?;
+            if length.0 as usize != LIGHT_ARRAY_LENGTH {
+                return Err(ServerError::Protocol(format!(
+                    "Invalid light array length: {}",
+                    length.0
+                )));
+            }
+            let mut array = vec![0u8; LIGHT_ARRAY_LENGTH];
+            reader.read_exact(&mut array)?;
+            Ok(array)
+        })
+        .collect()
+}
+
+/// Write a list of light arrays
+fn write_light_arrays<W: Write>(arrays: &[Vec<u8>], writer: &mut W) -> Result<()> {
+    VarInt(arrays.len() as i32).write(writer)?;
+    for array in arrays {
+        if array.len() != LIGHT_ARRAY_LENGTH {
+            return Err(ServerError::Protocol(format!(
+                "Invalid light array length: {}",
+                array.len()
+            )));
+        }
+        VarInt(array.len() as i32).write(writer)?;
+        writer.write_all(array)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_value_section_layout() {
+        let section = ChunkSection::filled(1, 0, 4096);
+        let mut buffer = Vec::new();
+        section.write(&mut buffer).unwrap();
+
+        // Block count, then for each container: bits per entry and the value
+        assert_eq!(buffer, vec![0x10, 0x00, 0, 1, 0, 0]);
+    }
+
+    #[test]
+    fn test_indirect_container_has_no_data_length() {
+        let container = PalettedContainer {
+            bits_per_entry: 4,
+            palette: Palette::Indirect(vec![0, 9]),
+            data: vec![0x11; PalettedContainer::data_length(4, SECTION_BLOCK_COUNT)],
+        };
+        let mut buffer = Vec::new();
+        container.write(&mut buffer).unwrap();
+
+        // Bits, palette length, two entries, then 256 longs without a prefix
+        assert_eq!(&buffer[..4], &[4, 2, 0, 9]);
+        assert_eq!(buffer.len(), 4 + 256 * 8);
+
+        let decoded =
+            PalettedContainer::read(&mut Cursor::new(buffer), PalettedContainerKind::BlockStates)
+                .unwrap();
+        assert_eq!(decoded, container);
+    }
+
+    #[test]
+    fn test_container_from_entries() {
+        let kind = PalettedContainerKind::BlockStates;
+        assert_eq!(
+            PalettedContainer::from_entries(&[9; SECTION_BLOCK_COUNT], kind),
+            PalettedContainer::single_value(9)
+        );
+
+        let mut entries = [0; SECTION_BLOCK_COUNT];
+        entries[1] = 85;
+        entries[16] = 10;
+        let container = PalettedContainer::from_entries(&entries, kind);
+        assert_eq!(container.bits_per_entry, 4);
+        assert_eq!(container.palette, Palette::Indirect(vec![0, 85, 10]));
+        assert_eq!(container.data.len(), 256);
+        assert_eq!(container.data[0], 0x10);
+        assert_eq!(container.data[1], 0x2);
+
+        // More than 256 distinct states need the direct palette
+        let entries: Vec<i32> = (0..SECTION_BLOCK_COUNT as i32).collect();
+        let container = PalettedContainer::from_entries(&entries, kind);
+        assert_eq!(container.palette, Palette::Direct);
+        assert_eq!(container.data[0] & 0x7FFF, 0);
+        assert_eq!((container.data[0] >> 15) & 0x7FFF, 1);
+    }
+
+    #[test]
+    fn test_heightmap_length() {
+        // 384 blocks need 9 bits per height, so 7 heights fit in a long
+        assert_eq!(Heightmap::bits_per_entry(384), 9);
+        assert_eq!(Heightmap::data_length(384), 37);
+        assert_eq!(Heightmap::bits_per_entry(256), 9);
+        assert_eq!(Heightmap::bits_per_entry(255), 8);
+    }
+
+    #[test]
+    fn test_chunk_data_roundtrip() {
+        let mut sky_light_mask = BitSet::new();
+        sky_light_mask.set(1, true);
+
+        let packet = ChunkDataPacket {
+            chunk_x: -3,
+            chunk_z: 7,
+            heightmaps: vec![Heightmap::empty(HeightmapKind::MotionBlocking, 384)],
+            sections: vec![ChunkSection::filled(0, 0, 0); 24],
+            block_entities: vec![ChunkBlockEntity {
+                packed_xz: 0x12,
+                y: 64,
+                kind: VarInt(7),
+                data: None,
+            }],
+            light: LightData {
+                sky_light_mask,
+                sky_light: vec![vec![0xFF; LIGHT_ARRAY_LENGTH]],
+                ..LightData::default()
+            },
+        };
+
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        let decoded = ChunkDataPacket::read(&mut Cursor::new(buffer)).unwrap();
+        assert_eq!(decoded, packet);
+    }
+
+    #[test]
+    fn test_invalid_light_array_length() {
+        let light = LightData {
+            sky_light: vec![vec![0; 16]],
+            ..LightData::default()
+        };
+        assert!(light.write(&mut Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,171 @@
+//! Command suggestion packets
+//!
+//! While the player types a command, the client asks the server for
+//! completions of the text so far. The answer carries the range of the text
+//! the suggestions replace, so the client can highlight it.
+
+use crate::error::Result;
+use crate::protocol::packets::{ClientboundPacket, Packet, ServerboundPacket};
+use crate::protocol::text::TextComponent;
+use crate::protocol::types::{McString, VarInt, read_bool, write_bool};
+use std::io::{Read, Write};
+
+/// Command Suggestions Request packet (serverbound)
+#[doc(alias = "CommandSuggestionPacket")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandSuggestionsRequestPacket {
+    /// ID to echo in the response
+    pub transaction_id: VarInt,
+    /// Text typed so far, including the leading `/`
+    pub text: McString,
+}
+
+impl CommandSuggestionsRequestPacket {
+    /// Maximum length of the text
+    pub const MAX_TEXT_LENGTH: usize = 32500;
+}
+
+impl Packet for CommandSuggestionsRequestPacket {
+    const ID: i32 = 0x0E;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(CommandSuggestionsRequestPacket {
+            transaction_id: VarInt::read(reader)?,
+            text: McString::read_with_max_length(reader, Self::MAX_TEXT_LENGTH)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.transaction_id.write(writer)?;
+        self.text.write(writer)?;
+        Ok(())
+    }
+}
+
+impl ServerboundPacket for CommandSuggestionsRequestPacket {}
+
+/// A single completion offered to the client
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuggestionMatch {
+    /// Text replacing the range given in the response
+    pub match_: McString,
+    /// Tooltip shown next to the suggestion
+    pub tooltip: Option<TextComponent>,
+}
+
+impl SuggestionMatch {
+    /// Create a suggestion without a tooltip
+    pub fn new(match_: impl Into<McString>) -> Self {
+        Self {
+            match_: match_.into(),
+            tooltip: None,
+        }
+    }
+}
+
+/// Command Suggestions Response packet (clientbound)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandSuggestionsResponsePacket {
+    /// ID of the request being answered
+    pub transaction_id: VarInt,
+    /// Start of the replaced text, in characters
+    pub start: VarInt,
+    /// Length of the replaced text, in characters
+    pub length: VarInt,
+    /// Suggested completions
+    pub matches: Vec<SuggestionMatch>,
+}
+
+impl Packet for CommandSuggestionsResponsePacket {
+    const ID: i32 = 0x0F;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let transaction_id = VarInt::read(reader)?;
+        let start = VarInt::read(reader)?;
+        let length = VarInt::read(reader)?;
+
+        let count = VarInt::read(reader)?.0;
+        let mut matches = Vec::new();
+        for _ in 0..count {
+            let match_ = McString::read(reader)?;
+            let tooltip = if read_bool(reader)? {
+                Some(TextComponent::read_nbt(reader)?)
+            } else {
+                None
+            };
+            matches.push(SuggestionMatch { match_, tooltip });
+        }
+
+        Ok(CommandSuggestionsResponsePacket {
+            transaction_id,
+            start,
+            length,
+            matches,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.transaction_id.write(writer)?;
+        self.start.write(writer)?;
+        self.length.write(writer)?;
+
+        VarInt(self.matches.len() as i32).write(writer)?;
+        for suggestion in &self.matches {
+            suggestion.match_.write(writer)?;
+            write_bool(suggestion.tooltip.is_some(), writer)?;
+            if let Some(tooltip) = &suggestion.tooltip {
+                tooltip.write_nbt(writer)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ClientboundPacket for CommandSuggestionsResponsePacket {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_command_suggestions_request() {
+        let packet = CommandSuggestionsRequestPacket {
+            transaction_id: VarInt(3),
+            text: "/he".into(),
+        };
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(buffer, [0x03, 0x03, b'/', b'h', b'e']);
+        assert_eq!(
+            CommandSuggestionsRequestPacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+    }
+
+    #[test]
+    fn test_command_suggestions_response() {
+        let packet = CommandSuggestionsResponsePacket {
+            transaction_id: VarInt(3),
+            start: VarInt(1),
+            length: VarInt(2),
+            matches: vec![
+                SuggestionMatch::new("help"),
+                SuggestionMatch {
+                    match_: "hello".into(),
+                    tooltip: Some(TextComponent::text("Say hello")),
+                },
+            ],
+        };
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(
+            &buffer[..10],
+            [0x03, 0x01, 0x02, 0x02, 0x04, b'h', b'e', b'l', b'p', 0x00]
+        );
+        assert_eq!(
+            CommandSuggestionsResponsePacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+    }
+}
@@ -0,0 +1,543 @@
+//! Command tree packet
+//!
+//! The client parses, highlights and completes commands against a tree sent
+//! by the server. On the wire the tree is flattened into a list of nodes that
+//! refer to their children by index.
+
+use crate::error::{Result, ServerError};
+use crate::protocol::packets::{ClientboundPacket, Packet};
+use crate::protocol::types::{
+    McString, VarInt, read_float, read_int, read_unsigned_byte, write_float, write_int,
+    write_unsigned_byte,
+};
+use std::io::{Read, Write};
+
+/// Node type mask of the flags byte
+const NODE_TYPE_MASK: u8 = 0x03;
+/// Flag set on nodes that complete a runnable command
+const EXECUTABLE_FLAG: u8 = 0x04;
+/// Flag set on nodes that redirect to another node
+const REDIRECT_FLAG: u8 = 0x08;
+/// Flag set on arguments with custom suggestions
+const SUGGESTIONS_FLAG: u8 = 0x10;
+
+/// Node type of the root node
+const ROOT_TYPE: u8 = 0;
+/// Node type of literal nodes
+const LITERAL_TYPE: u8 = 1;
+/// Node type of argument nodes
+const ARGUMENT_TYPE: u8 = 2;
+
+/// How much text a string argument consumes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringKind {
+    /// A single word
+    SingleWord = 0,
+    /// A word, or a phrase in quotes
+    QuotablePhrase = 1,
+    /// The rest of the command
+    GreedyPhrase = 2,
+}
+
+/// Parser of a command argument, with its parser-specific options
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArgumentParser {
+    /// `brigadier:bool`, `true` or `false`
+    Bool,
+    /// `minecraft:entity`, an entity selector or player name
+    Entity {
+        /// Whether only one entity may be selected
+        single: bool,
+        /// Whether only players may be selected
+        players_only: bool,
+    },
+    /// `brigadier:string`
+    String(StringKind),
+    /// `minecraft:vec3`, three coordinates that may be relative (`~`)
+    Vec3,
+    /// `minecraft:item_stack`, an item ID with optional components
+    ItemStack,
+    /// `brigadier:integer`
+    Integer {
+        /// Smallest accepted value
+        min: Option<i32>,
+        /// Largest accepted value
+        max: Option<i32>,
+    },
+    /// `brigadier:float`
+    Float {
+        /// Smallest accepted value
+        min: Option<f32>,
+        /// Largest accepted value
+        max: Option<f32>,
+    },
+}
+
+impl ArgumentParser {
+    /// ID of `brigadier:bool` in the `minecraft:command_argument_type` registry
+    const BOOL_ID: i32 = 0;
+    /// ID of `brigadier:float`
+    const FLOAT_ID: i32 = 1;
+    /// ID of `brigadier:integer`
+    const INTEGER_ID: i32 = 3;
+    /// ID of `brigadier:string`
+    const STRING_ID: i32 = 5;
+    /// ID of `minecraft:entity`
+    const ENTITY_ID: i32 = 6;
+    /// ID of `minecraft:vec3`
+    const VEC3_ID: i32 = 10;
+    /// ID of `minecraft:item_stack`
+    const ITEM_STACK_ID: i32 = 14;
+
+    /// Protocol ID of the parser
+    pub fn id(&self) -> i32 {
+        match self {
+            ArgumentParser::Bool => Self::BOOL_ID,
+            ArgumentParser::Entity { .. } => Self::ENTITY_ID,
+            ArgumentParser::Vec3 => Self::VEC3_ID,
+            ArgumentParser::ItemStack => Self::ITEM_STACK_ID,
+            ArgumentParser::String(_) => Self::STRING_ID,
+            ArgumentParser::Integer { .. } => Self::INTEGER_ID,
+            ArgumentParser::Float { .. } => Self::FLOAT_ID,
+        }
+    }
+
+    /// Read the options of the parser with the given ID
+    fn read<R: Read>(id: i32, reader: &mut R) -> Result<Self> {
+        match id {
+            Self::BOOL_ID => Ok(ArgumentParser::Bool),
+            Self::VEC3_ID => Ok(ArgumentParser::Vec3),
+            Self::ITEM_STACK_ID => Ok(ArgumentParser::ItemStack),
+            Self::ENTITY_ID => {
+                let flags = read_unsigned_byte(reader)?;
+                Ok(ArgumentParser::Entity {
+                    single: flags & 0x01 != 0,
+                    players_only: flags & 0x02 != 0,
+                })
+            }
+            Self::STRING_ID => match VarInt::read(reader)?.0 {
+                0 => Ok(ArgumentParser::String(StringKind::SingleWord)),
+                1 => Ok(ArgumentParser::String(StringKind::QuotablePhrase)),
+                2 => Ok(ArgumentParser::String(StringKind::GreedyPhrase)),
+                kind => Err(ServerError::Protocol(format!(
+                    "Invalid string argument kind: {}",
+                    kind
+                ))),
+            },
+            Self::INTEGER_ID => {
+                let flags = read_unsigned_byte(reader)?;
+                let min = (flags & 0x01 != 0).then(|| read_int(reader)).transpose()?;
+                let max = (flags & 0x02 != 0).then(|| read_int(reader)).transpose()?;
+                Ok(ArgumentParser::Integer { min, max })
+            }
+            Self::FLOAT_ID => {
+                let flags = read_unsigned_byte(reader)?;
+                let min = (flags & 0x01 != 0)
+                    .then(|| read_float(reader))
+                    .transpose()?;
+                let max = (flags & 0x02 != 0)
+                    .then(|| read_float(reader))
+                    .transpose()?;
+                Ok(ArgumentParser::Float { min, max })
+            }
+            id => Err(ServerError::Protocol(format!(
+                "Unsupported argument parser: {}",
+                id
+            ))),
+        }
+    }
+
+    /// Write the parser's options, without its ID
+    fn write_properties<W: Write>(&self, writer: &mut W) -> Result<()> {
+        match *self {
+            ArgumentParser::Bool | ArgumentParser::Vec3 | ArgumentParser::ItemStack => Ok(()),
+            ArgumentParser::Entity {
+                single,
+                players_only,
+            } => write_unsigned_byte(u8::from(single) | u8::from(players_only) << 1, writer),
+            ArgumentParser::String(kind) => VarInt(kind as i32).write(writer),
+            ArgumentParser::Integer { min, max } => {
+                write_unsigned_byte(
+                    u8::from(min.is_some()) | u8::from(max.is_some()) << 1,
+                    writer,
+                )?;
+                for value in [min, max].into_iter().flatten() {
+                    write_int(value, writer)?;
+                }
+                Ok(())
+            }
+            ArgumentParser::Float { min, max } => {
+                write_unsigned_byte(
+                    u8::from(min.is_some()) | u8::from(max.is_some()) << 1,
+                    writer,
+                )?;
+                for value in [min, max].into_iter().flatten() {
+                    write_float(value, writer)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// A node of the command tree
+///
+/// Redirects (as used by `/execute`) are not supported, since a node can only
+/// appear in one place of the tree.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommandNode {
+    /// Root of the tree, whose children are the commands
+    Root {
+        /// Top-level commands
+        children: Vec<CommandNode>,
+    },
+    /// A fixed word, such as a command name
+    Literal {
+        /// The word
+        name: String,
+        /// Whether the command can be run after this word
+        executable: bool,
+        /// Nodes that may follow
+        children: Vec<CommandNode>,
+    },
+    /// A value parsed by an argument parser
+    Argument {
+        /// Argument name, shown in the usage hint
+        name: String,
+        /// Parser of the argument
+        parser: ArgumentParser,
+        /// Whether the command can be run after this argument
+        executable: bool,
+        /// Nodes that may follow
+        children: Vec<CommandNode>,
+    },
+}
+
+impl CommandNode {
+    /// Create a root node without commands
+    pub fn root() -> Self {
+        CommandNode::Root {
+            children: Vec::new(),
+        }
+    }
+
+    /// Create a non-executable literal node
+    pub fn literal(name: impl Into<String>) -> Self {
+        CommandNode::Literal {
+            name: name.into(),
+            executable: false,
+            children: Vec::new(),
+        }
+    }
+
+    /// Create a non-executable argument node
+    pub fn argument(name: impl Into<String>, parser: ArgumentParser) -> Self {
+        CommandNode::Argument {
+            name: name.into(),
+            parser,
+            executable: false,
+            children: Vec::new(),
+        }
+    }
+
+    /// Mark the node as completing a runnable command
+    ///
+    /// The root node cannot be executable and is left unchanged.
+    pub fn executable(mut self) -> Self {
+        match &mut self {
+            CommandNode::Root { .. } => {}
+            CommandNode::Literal { executable, .. } | CommandNode::Argument { executable, .. } => {
+                *executable = true;
+            }
+        }
+        self
+    }
+
+    /// Add a child node
+    pub fn then(mut self, child: CommandNode) -> Self {
+        self.children_mut().push(child);
+        self
+    }
+
+    /// Child nodes
+    pub fn children(&self) -> &[CommandNode] {
+        match self {
+            CommandNode::Root { children }
+            | CommandNode::Literal { children, .. }
+            | CommandNode::Argument { children, .. } => children,
+        }
+    }
+
+    /// Mutable child nodes
+    pub fn children_mut(&mut self) -> &mut Vec<CommandNode> {
+        match self {
+            CommandNode::Root { children }
+            | CommandNode::Literal { children, .. }
+            | CommandNode::Argument { children, .. } => children,
+        }
+    }
+
+    /// Write the node without its children, which are referred to by index
+    fn write_flat<W: Write>(&self, child_indices: &[i32], writer: &mut W) -> Result<()> {
+        let (node_type, executable) = match self {
+            CommandNode::Root { .. } => (ROOT_TYPE, false),
+            CommandNode::Literal { executable, .. } => (LITERAL_TYPE, *executable),
+            CommandNode::Argument { executable, .. } => (ARGUMENT_TYPE, *executable),
+        };
+        let flags = node_type | if executable { EXECUTABLE_FLAG } else { 0 };
+        write_unsigned_byte(flags, writer)?;
+
+        VarInt(child_indices.len() as i32).write(writer)?;
+        for &index in child_indices {
+            VarInt(index).write(writer)?;
+        }
+
+        match self {
+            CommandNode::Root { .. } => {}
+            CommandNode::Literal { name, .. } => McString::from(name.as_str()).write(writer)?,
+            CommandNode::Argument { name, parser, .. } => {
+                McString::from(name.as_str()).write(writer)?;
+                VarInt(parser.id()).write(writer)?;
+                parser.write_properties(writer)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A node as read from the wire, before its children are resolved
+struct FlatNode {
+    /// Node without children
+    node: CommandNode,
+    /// Indices of the child nodes
+    children: Vec<i32>,
+}
+
+impl FlatNode {
+    /// Read a node
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let flags = read_unsigned_byte(reader)?;
+        if flags & (REDIRECT_FLAG | SUGGESTIONS_FLAG) != 0 {
+            return Err(ServerError::Protocol(
+                "Command redirects and custom suggestions are not supported".to_string(),
+            ));
+        }
+
+        let count = VarInt::read(reader)?.0;
+        let mut children = Vec::new();
+        for _ in 0..count {
+            children.push(VarInt::read(reader)?.0);
+        }
+
+        let executable = flags & EXECUTABLE_FLAG != 0;
+        let node = match flags & NODE_TYPE_MASK {
+            ROOT_TYPE => CommandNode::root(),
+            LITERAL_TYPE => CommandNode::Literal {
+                name: McString::read(reader)?.0,
+                executable,
+                children: Vec::new(),
+            },
+            ARGUMENT_TYPE => {
+                let name = McString::read(reader)?.0;
+                let parser_id = VarInt::read(reader)?.0;
+                CommandNode::Argument {
+                    name,
+                    parser: ArgumentParser::read(parser_id, reader)?,
+                    executable,
+                    children: Vec::new(),
+                }
+            }
+            node_type => {
+                return Err(ServerError::Protocol(format!(
+                    "Invalid command node type: {}",
+                    node_type
+                )));
+            }
+        };
+        Ok(FlatNode { node, children })
+    }
+}
+
+/// Commands packet (clientbound)
+///
+/// Replaces the client's command tree.
+#[doc(alias = "CommandsPacket")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeclareCommandsPacket {
+    /// Root of the command tree
+    pub root: CommandNode,
+}
+
+impl DeclareCommandsPacket {
+    /// Create a packet sending a command tree
+    pub fn new(root: CommandNode) -> Self {
+        Self { root }
+    }
+}
+
+/// Add a node and its descendants to the flat list in depth-first order,
+/// returning the node's index
+fn flatten<'a>(node: &'a CommandNode, nodes: &mut Vec<(&'a CommandNode, Vec<i32>)>) -> i32 {
+    let index = nodes.len();
+    nodes.push((node, Vec::new()));
+    let children = node
+        .children()
+        .iter()
+        .map(|child| flatten(child, nodes))
+        .collect();
+    nodes[index].1 = children;
+    index as i32
+}
+
+/// Rebuild the subtree at an index from the flat list
+///
+/// The depth is bounded by the number of nodes, so cycles are rejected.
+fn unflatten(nodes: &[FlatNode], index: i32, depth: usize) -> Result<CommandNode> {
+    let flat = usize::try_from(index)
+        .ok()
+        .and_then(|index| nodes.get(index))
+        .filter(|_| depth <= nodes.len())
+        .ok_or_else(|| ServerError::Protocol(format!("Invalid command node index: {}", index)))?;
+
+    let mut node = flat.node.clone();
+    for &child in &flat.children {
+        let child = unflatten(nodes, child, depth + 1)?;
+        node.children_mut().push(child);
+    }
+    Ok(node)
+}
+
+impl Packet for DeclareCommandsPacket {
+    const ID: i32 = 0x10;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let count = VarInt::read(reader)?.0;
+        let mut nodes = Vec::new();
+        for _ in 0..count {
+            nodes.push(FlatNode::read(reader)?);
+        }
+        let root_index = VarInt::read(reader)?.0;
+
+        let root = unflatten(&nodes, root_index, 0)?;
+        if !matches!(root, CommandNode::Root { .. }) {
+            return Err(ServerError::Protocol(
+                "Command tree root is not a root node".to_string(),
+            ));
+        }
+        Ok(DeclareCommandsPacket { root })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let mut nodes = Vec::new();
+        let root_index = flatten(&self.root, &mut nodes);
+
+        // Only the top of the tree may be a root node
+        let roots = nodes
+            .iter()
+            .filter(|(node, _)| matches!(node, CommandNode::Root { .. }))
+            .count();
+        if roots != 1 || !matches!(self.root, CommandNode::Root { .. }) {
+            return Err(ServerError::Protocol(
+                "Command tree must have exactly one root node, at the top".to_string(),
+            ));
+        }
+
+        VarInt(nodes.len() as i32).write(writer)?;
+        for (node, children) in &nodes {
+            node.write_flat(children, writer)?;
+        }
+        VarInt(root_index).write(writer)?;
+        Ok(())
+    }
+}
+
+impl ClientboundPacket for DeclareCommandsPacket {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_declare_commands_packet() {
+        // /tp <target> and /time <ticks>
+        let root = CommandNode::root()
+            .then(
+                CommandNode::literal("tp").then(
+                    CommandNode::argument(
+                        "target",
+                        ArgumentParser::Entity {
+                            single: true,
+                            players_only: true,
+                        },
+                    )
+                    .executable(),
+                ),
+            )
+            .then(
+                CommandNode::literal("time").then(
+                    CommandNode::argument(
+                        "ticks",
+                        ArgumentParser::Integer {
+                            min: Some(0),
+                            max: None,
+                        },
+                    )
+                    .executable(),
+                ),
+            );
+        let packet = DeclareCommandsPacket::new(root);
+
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        #[rustfmt::skip]
+        let expected = [
+            0x05, // Node count
+            0x00, 0x02, 0x01, 0x03, // Root with children 1 and 3
+            0x01, 0x01, 0x02, 0x02, b't', b'p', // Literal "tp" with child 2
+            0x06, 0x00, 0x06, b't', b'a', b'r', b'g', b'e', b't', 0x06, 0x03, // Entity argument
+            0x01, 0x01, 0x04, 0x04, b't', b'i', b'm', b'e', // Literal "time" with child 4
+            0x06, 0x00, 0x05, b't', b'i', b'c', b'k', b's', 0x03, 0x01, 0x00, 0x00, 0x00, 0x00,
+            0x00, // Root index
+        ];
+        assert_eq!(buffer, expected);
+        assert_eq!(
+            DeclareCommandsPacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+    }
+
+    #[test]
+    fn test_argument_parsers() {
+        for parser in [
+            ArgumentParser::Bool,
+            ArgumentParser::Vec3,
+            ArgumentParser::ItemStack,
+            ArgumentParser::String(StringKind::GreedyPhrase),
+            ArgumentParser::Float {
+                min: Some(-1.5),
+                max: Some(1.5),
+            },
+        ] {
+            let packet = DeclareCommandsPacket::new(
+                CommandNode::root().then(CommandNode::argument("value", parser)),
+            );
+            let mut buffer = Vec::new();
+            packet.write(&mut buffer).unwrap();
+            assert_eq!(
+                DeclareCommandsPacket::read(&mut Cursor::new(buffer)).unwrap(),
+                packet
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_command_trees() {
+        let nested_root = DeclareCommandsPacket::new(CommandNode::root().then(CommandNode::root()));
+        assert!(nested_root.write(&mut Vec::new()).is_err());
+
+        // A root node that is its own child
+        let cycle = [0x01, 0x00, 0x01, 0x00, 0x00];
+        assert!(DeclareCommandsPacket::read(&mut Cursor::new(cycle)).is_err());
+    }
+}
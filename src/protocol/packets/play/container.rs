@@ -0,0 +1,595 @@
+//! Container packets
+//!
+//! Every open window, including the player's own inventory with ID 0, is a
+//! container of numbered slots. Each update carries a state ID which the
+//! client echoes back when clicking, so the server can detect clicks made
+//! against an out-of-date view.
+
+use crate::error::{Result, ServerError};
+use crate::protocol::packets::{ClientboundPacket, Packet, ServerboundPacket};
+use crate::protocol::text::TextComponent;
+use crate::protocol::types::{
+    Slot, VarInt, read_bool, read_int, read_short, read_unsigned_byte, write_bool, write_int,
+    write_short, write_unsigned_byte,
+};
+use std::io::{Read, Write};
+
+/// Set Container Content packet (clientbound)
+///
+/// Replaces every slot of a container, and the item carried by the cursor.
+#[doc(alias = "ContainerSetContentPacket")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetContainerContentPacket {
+    /// Window ID, 0 for the player inventory
+    pub window_id: VarInt,
+    /// State ID of the container after this update
+    pub state_id: VarInt,
+    /// Contents of every slot, in slot order
+    pub slots: Vec<Slot>,
+    /// Item carried by the cursor
+    pub carried_item: Slot,
+}
+
+impl Packet for SetContainerContentPacket {
+    const ID: i32 = 0x12;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let window_id = VarInt::read(reader)?;
+        let state_id = VarInt::read(reader)?;
+        let count = VarInt::read(reader)?.0;
+        let mut slots = Vec::new();
+        for _ in 0..count {
+            slots.push(Slot::read(reader)?);
+        }
+        Ok(SetContainerContentPacket {
+            window_id,
+            state_id,
+            slots,
+            carried_item: Slot::read(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.window_id.write(writer)?;
+        self.state_id.write(writer)?;
+        VarInt(self.slots.len() as i32).write(writer)?;
+        for slot in &self.slots {
+            slot.write(writer)?;
+        }
+        self.carried_item.write(writer)
+    }
+}
+
+impl ClientboundPacket for SetContainerContentPacket {}
+
+/// Set Container Slot packet (clientbound)
+#[doc(alias = "ContainerSetSlotPacket")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetContainerSlotPacket {
+    /// Window ID, 0 for the player inventory
+    pub window_id: VarInt,
+    /// State ID of the container after this update
+    pub state_id: VarInt,
+    /// Index of the changed slot
+    pub slot: i16,
+    /// New contents of the slot
+    pub slot_data: Slot,
+}
+
+impl Packet for SetContainerSlotPacket {
+    const ID: i32 = 0x14;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(SetContainerSlotPacket {
+            window_id: VarInt::read(reader)?,
+            state_id: VarInt::read(reader)?,
+            slot: read_short(reader)?,
+            slot_data: Slot::read(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.window_id.write(writer)?;
+        self.state_id.write(writer)?;
+        write_short(self.slot, writer)?;
+        self.slot_data.write(writer)
+    }
+}
+
+impl ClientboundPacket for SetContainerSlotPacket {}
+
+/// An item stack as described by the client
+///
+/// Since 1.21.5 clients send a CRC32C hash of each data component instead of
+/// its value, which is only enough to tell whether two stacks match.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HashedSlot {
+    /// Item ID in the `minecraft:item` registry, or `None` for an empty slot
+    pub item_id: Option<i32>,
+    /// Number of items in the stack
+    pub count: i32,
+    /// Added data components and the hashes of their values
+    pub added_components: Vec<(i32, i32)>,
+    /// Removed data components
+    pub removed_components: Vec<i32>,
+}
+
+impl HashedSlot {
+    /// Describe a slot without data components
+    pub fn new(slot: &Slot) -> Self {
+        match slot.item_id.filter(|_| !slot.is_empty()) {
+            Some(item_id) => Self {
+                item_id: Some(item_id),
+                count: slot.count as i32,
+                ..Self::default()
+            },
+            None => Self::default(),
+        }
+    }
+
+    /// Whether the client's stack plausibly matches a slot
+    ///
+    /// Component hashes are not checked, only whether the stack has custom
+    /// data at all.
+    pub fn matches(&self, slot: &Slot) -> bool {
+        match (self.item_id, slot.item_id.filter(|_| !slot.is_empty())) {
+            (None, None) => true,
+            (Some(item_id), Some(slot_item_id)) => {
+                item_id == slot_item_id
+                    && self.count == slot.count as i32
+                    && self.added_components.len() == slot.nbt.is_some() as usize
+                    && self.removed_components.is_empty()
+            }
+            _ => false,
+        }
+    }
+
+    /// Read a hashed slot from a reader
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        if !read_bool(reader)? {
+            return Ok(Self::default());
+        }
+        let item_id = VarInt::read(reader)?.0;
+        let count = VarInt::read(reader)?.0;
+
+        let added = read_count(reader, MAX_COMPONENTS)?;
+        let mut added_components = Vec::new();
+        for _ in 0..added {
+            added_components.push((VarInt::read(reader)?.0, read_int(reader)?));
+        }
+        let removed = read_count(reader, MAX_COMPONENTS)?;
+        let mut removed_components = Vec::new();
+        for _ in 0..removed {
+            removed_components.push(VarInt::read(reader)?.0);
+        }
+
+        Ok(Self {
+            item_id: Some(item_id),
+            count,
+            added_components,
+            removed_components,
+        })
+    }
+
+    /// Write a hashed slot to a writer
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let Some(item_id) = self.item_id else {
+            return write_bool(false, writer);
+        };
+        write_bool(true, writer)?;
+        VarInt(item_id).write(writer)?;
+        VarInt(self.count).write(writer)?;
+        VarInt(self.added_components.len() as i32).write(writer)?;
+        for (component, hash) in &self.added_components {
+            VarInt(*component).write(writer)?;
+            write_int(*hash, writer)?;
+        }
+        VarInt(self.removed_components.len() as i32).write(writer)?;
+        for component in &self.removed_components {
+            VarInt(*component).write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Maximum number of components added to or removed from a hashed slot
+const MAX_COMPONENTS: usize = 256;
+
+/// Read a non-negative array length no larger than `max`
+fn read_count<R: Read>(reader: &mut R, max: usize) -> Result<usize> {
+    let count = VarInt::read(reader)?.0;
+    usize::try_from(count)
+        .ok()
+        .filter(|&count| count <= max)
+        .ok_or_else(|| ServerError::Protocol(format!("Invalid array length: {}", count)))
+}
+
+/// Click Container packet (serverbound)
+///
+/// Sent when the player clicks a slot, along with the client's prediction of
+/// the resulting slot contents.
+#[doc(alias = "ContainerClickPacket")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClickContainerPacket {
+    /// Window ID, 0 for the player inventory
+    pub window_id: VarInt,
+    /// Last state ID the client received
+    pub state_id: VarInt,
+    /// Clicked slot, or -999 for outside the window
+    pub slot: i16,
+    /// Mouse button or hotbar key, depending on the mode
+    pub button: i8,
+    /// Click mode (0=Pickup, 1=Quick move, 2=Swap, 3=Clone, 4=Throw,
+    /// 5=Quick craft, 6=Pickup all)
+    pub mode: VarInt,
+    /// Slots the client predicts changed, with their new contents
+    pub changed_slots: Vec<(i16, HashedSlot)>,
+    /// Item the client predicts the cursor carries
+    pub carried_item: HashedSlot,
+}
+
+impl ClickContainerPacket {
+    /// Maximum number of changed slots
+    pub const MAX_CHANGED_SLOTS: usize = 128;
+}
+
+impl Packet for ClickContainerPacket {
+    const ID: i32 = 0x11;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let window_id = VarInt::read(reader)?;
+        let state_id = VarInt::read(reader)?;
+        let slot = read_short(reader)?;
+        let button = read_unsigned_byte(reader)? as i8;
+        let mode = VarInt::read(reader)?;
+
+        let count = read_count(reader, Self::MAX_CHANGED_SLOTS)?;
+        let mut changed_slots = Vec::new();
+        for _ in 0..count {
+            changed_slots.push((read_short(reader)?, HashedSlot::read(reader)?));
+        }
+
+        Ok(ClickContainerPacket {
+            window_id,
+            state_id,
+            slot,
+            button,
+            mode,
+            changed_slots,
+            carried_item: HashedSlot::read(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.window_id.write(writer)?;
+        self.state_id.write(writer)?;
+        write_short(self.slot, writer)?;
+        write_unsigned_byte(self.button as u8, writer)?;
+        self.mode.write(writer)?;
+        VarInt(self.changed_slots.len() as i32).write(writer)?;
+        for (index, slot) in &self.changed_slots {
+            write_short(*index, writer)?;
+            slot.write(writer)?;
+        }
+        self.carried_item.write(writer)
+    }
+}
+
+impl ServerboundPacket for ClickContainerPacket {}
+
+/// Kind of window a container is shown in, by its ID in the `minecraft:menu`
+/// registry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i32)]
+pub enum GuiType {
+    /// One row of 9 slots
+    Generic9x1 = 0,
+    /// Two rows of 9 slots
+    Generic9x2 = 1,
+    /// Three rows of 9 slots, as in a chest
+    Generic9x3 = 2,
+    /// Four rows of 9 slots
+    Generic9x4 = 3,
+    /// Five rows of 9 slots
+    Generic9x5 = 4,
+    /// Six rows of 9 slots, as in a double chest
+    Generic9x6 = 5,
+    /// A 3x3 grid, as in a dispenser
+    Generic3x3 = 6,
+    /// Crafter
+    Crafter3x3 = 7,
+    /// Anvil
+    Anvil = 8,
+    /// Beacon
+    Beacon = 9,
+    /// Blast furnace
+    BlastFurnace = 10,
+    /// Brewing stand
+    BrewingStand = 11,
+    /// Crafting table
+    Crafting = 12,
+    /// Enchanting table
+    Enchantment = 13,
+    /// Furnace
+    Furnace = 14,
+    /// Grindstone
+    Grindstone = 15,
+    /// Hopper
+    Hopper = 16,
+    /// Lectern
+    Lectern = 17,
+    /// Loom
+    Loom = 18,
+    /// Villager trading
+    Merchant = 19,
+    /// Shulker box
+    ShulkerBox = 20,
+    /// Smithing table
+    Smithing = 21,
+    /// Smoker
+    Smoker = 22,
+    /// Cartography table
+    CartographyTable = 23,
+    /// Stonecutter
+    Stonecutter = 24,
+}
+
+impl GuiType {
+    /// Number of slots of the container itself for generic windows, not
+    /// counting the player inventory shown below it
+    pub fn generic_size(self) -> Option<usize> {
+        match self {
+            GuiType::Generic9x1 => Some(9),
+            GuiType::Generic9x2 => Some(18),
+            GuiType::Generic9x3 => Some(27),
+            GuiType::Generic9x4 => Some(36),
+            GuiType::Generic9x5 => Some(45),
+            GuiType::Generic9x6 => Some(54),
+            GuiType::Generic3x3 => Some(9),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<i32> for GuiType {
+    type Error = ServerError;
+
+    fn try_from(value: i32) -> Result<Self> {
+        const TYPES: [GuiType; 25] = [
+            GuiType::Generic9x1,
+            GuiType::Generic9x2,
+            GuiType::Generic9x3,
+            GuiType::Generic9x4,
+            GuiType::Generic9x5,
+            GuiType::Generic9x6,
+            GuiType::Generic3x3,
+            GuiType::Crafter3x3,
+            GuiType::Anvil,
+            GuiType::Beacon,
+            GuiType::BlastFurnace,
+            GuiType::BrewingStand,
+            GuiType::Crafting,
+            GuiType::Enchantment,
+            GuiType::Furnace,
+            GuiType::Grindstone,
+            GuiType::Hopper,
+            GuiType::Lectern,
+            GuiType::Loom,
+            GuiType::Merchant,
+            GuiType::ShulkerBox,
+            GuiType::Smithing,
+            GuiType::Smoker,
+            GuiType::CartographyTable,
+            GuiType::Stonecutter,
+        ];
+        usize::try_from(value)
+            .ok()
+            .and_then(|index| TYPES.get(index).copied())
+            .ok_or_else(|| ServerError::Protocol(format!("Invalid window type: {}", value)))
+    }
+}
+
+/// Open Screen packet (clientbound)
+///
+/// Opens a window. Its contents follow in a [`SetContainerContentPacket`]
+/// with the same window ID.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpenScreenPacket {
+    /// Window ID, from 1 to 100
+    pub window_id: VarInt,
+    /// Kind of window
+    pub window_type: GuiType,
+    /// Title shown at the top of the window
+    pub title: TextComponent,
+}
+
+impl Packet for OpenScreenPacket {
+    const ID: i32 = 0x34;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            window_id: VarInt::read(reader)?,
+            window_type: GuiType::try_from(VarInt::read(reader)?.0)?,
+            title: TextComponent::read_nbt(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.window_id.write(writer)?;
+        VarInt(self.window_type as i32).write(writer)?;
+        self.title.write_nbt(writer)
+    }
+}
+
+impl ClientboundPacket for OpenScreenPacket {}
+
+/// Close Container packet (clientbound)
+///
+/// Forces the client to close a window, such as when its block is broken.
+#[doc(alias = "ContainerClosePacket")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CloseContainerPacket {
+    /// Window ID
+    pub window_id: VarInt,
+}
+
+impl Packet for CloseContainerPacket {
+    const ID: i32 = 0x11;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            window_id: VarInt::read(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.window_id.write(writer)
+    }
+}
+
+impl ClientboundPacket for CloseContainerPacket {}
+
+/// Close Container packet (serverbound)
+///
+/// Sent when the player closes a window, including their own inventory.
+#[doc(alias = "ServerboundContainerClosePacket")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServerboundCloseContainerPacket {
+    /// Window ID
+    pub window_id: VarInt,
+}
+
+impl Packet for ServerboundCloseContainerPacket {
+    const ID: i32 = 0x12;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            window_id: VarInt::read(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.window_id.write(writer)
+    }
+}
+
+impl ServerboundPacket for ServerboundCloseContainerPacket {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_set_container_content_packet() {
+        let packet = SetContainerContentPacket {
+            window_id: VarInt(0),
+            state_id: VarInt(2),
+            slots: vec![Slot::empty(), Slot::new(1, 64)],
+            carried_item: Slot::empty(),
+        };
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(
+            buffer,
+            [0x00, 0x02, 0x02, 0x00, 0x40, 0x01, 0x00, 0x00, 0x00]
+        );
+        assert_eq!(
+            SetContainerContentPacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+    }
+
+    #[test]
+    fn test_set_container_slot_packet() {
+        let packet = SetContainerSlotPacket {
+            window_id: VarInt(0),
+            state_id: VarInt(3),
+            slot: 36,
+            slot_data: Slot::new(1, 1),
+        };
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(buffer, [0x00, 0x03, 0x00, 0x24, 0x01, 0x01, 0x00, 0x00]);
+        assert_eq!(
+            SetContainerSlotPacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+    }
+
+    #[test]
+    fn test_click_container_packet() {
+        let packet = ClickContainerPacket {
+            window_id: VarInt(0),
+            state_id: VarInt(1),
+            slot: 36,
+            button: 0,
+            mode: VarInt(0),
+            changed_slots: vec![(36, HashedSlot::default())],
+            carried_item: HashedSlot {
+                item_id: Some(1),
+                count: 64,
+                added_components: vec![(0, -1)],
+                removed_components: vec![],
+            },
+        };
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(
+            &buffer[..10],
+            [0x00, 0x01, 0x00, 0x24, 0x00, 0x00, 0x01, 0x00, 0x24, 0x00]
+        );
+        assert_eq!(
+            ClickContainerPacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+    }
+
+    #[test]
+    fn test_hashed_slot_matches() {
+        let slot = Slot::new(1, 3);
+        assert!(HashedSlot::new(&slot).matches(&slot));
+        assert!(HashedSlot::default().matches(&Slot::empty()));
+        assert!(!HashedSlot::new(&Slot::new(1, 2)).matches(&slot));
+        assert!(!HashedSlot::default().matches(&slot));
+    }
+
+    #[test]
+    fn test_open_screen_packet() {
+        let packet = OpenScreenPacket {
+            window_id: VarInt(1),
+            window_type: GuiType::Generic9x3,
+            title: TextComponent::text("Chest"),
+        };
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(&buffer[..2], [1, 2]);
+        assert_eq!(
+            OpenScreenPacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+
+        assert_eq!(GuiType::try_from(24).unwrap(), GuiType::Stonecutter);
+        assert!(GuiType::try_from(25).is_err());
+        assert_eq!(GuiType::Generic9x6.generic_size(), Some(54));
+        assert_eq!(GuiType::Furnace.generic_size(), None);
+    }
+
+    #[test]
+    fn test_close_container_packets() {
+        let mut buffer = Vec::new();
+        CloseContainerPacket {
+            window_id: VarInt(3),
+        }
+        .write(&mut buffer)
+        .unwrap();
+        assert_eq!(buffer, [3]);
+        assert_eq!(
+            ServerboundCloseContainerPacket::read(&mut Cursor::new(buffer))
+                .unwrap()
+                .window_id
+                .0,
+            3
+        );
+    }
+}
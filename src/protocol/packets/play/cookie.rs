@@ -0,0 +1,81 @@
+//! Cookie packets
+
+use crate::error::{Result, ServerError};
+use crate::protocol::packets::login::CookieResponsePacket;
+use crate::protocol::packets::{ClientboundPacket, Packet};
+use crate::protocol::types::{ByteArray, McIdentifier};
+use std::io::{Read, Write};
+
+/// Store Cookie packet (clientbound)
+///
+/// Asks the client to keep a value for this server. Clients keep cookies
+/// until they quit the game, across reconnects and transfers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoreCookiePacket {
+    /// Key of the cookie
+    pub key: McIdentifier,
+    /// Value of the cookie, at most 5120 bytes
+    pub payload: Vec<u8>,
+}
+
+impl StoreCookiePacket {
+    /// Maximum length of a cookie value
+    pub const MAX_PAYLOAD_LENGTH: usize = CookieResponsePacket::MAX_PAYLOAD_LENGTH;
+}
+
+impl Packet for StoreCookiePacket {
+    const ID: i32 = 0x71;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let key = McIdentifier::read(reader)?;
+        let payload = ByteArray::read_with_max_length(reader, Self::MAX_PAYLOAD_LENGTH)?.into();
+        Ok(Self { key, payload })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        if self.payload.len() > Self::MAX_PAYLOAD_LENGTH {
+            return Err(ServerError::Protocol(format!(
+                "Cookie too long: {} > {}",
+                self.payload.len(),
+                Self::MAX_PAYLOAD_LENGTH
+            )));
+        }
+        self.key.write(writer)?;
+        ByteArray(self.payload.clone()).write(writer)
+    }
+}
+
+impl ClientboundPacket for StoreCookiePacket {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_store_cookie_roundtrip() {
+        let packet = StoreCookiePacket {
+            key: McIdentifier::new("lobby", "session").unwrap(),
+            payload: vec![4, 2],
+        };
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(&buffer[14..], [2, 4, 2]);
+        assert_eq!(
+            StoreCookiePacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+    }
+
+    #[test]
+    fn test_store_cookie_too_long() {
+        let packet = StoreCookiePacket {
+            key: McIdentifier::minecraft("cookie"),
+            payload: vec![0; StoreCookiePacket::MAX_PAYLOAD_LENGTH + 1],
+        };
+        assert!(matches!(
+            packet.write(&mut Vec::new()),
+            Err(ServerError::Protocol(_))
+        ));
+    }
+}
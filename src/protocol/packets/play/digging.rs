@@ -0,0 +1,196 @@
+//! Block digging packets
+//!
+//! The client reports when it starts, cancels and finishes breaking a block.
+//! Each report carries a sequence number, which the server acknowledges once
+//! it has applied the change so the client can drop its own prediction.
+
+use crate::error::{Result, ServerError};
+use crate::protocol::packets::{ClientboundPacket, Packet, ServerboundPacket};
+use crate::protocol::types::{Position, VarInt, read_unsigned_byte, write_unsigned_byte};
+use std::io::{Read, Write};
+
+/// Destroy stage that removes a block breaking animation
+pub const CLEAR_DESTROY_STAGE: u8 = 0xFF;
+
+/// What a player is doing in a [`PlayerActionPacket`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i32)]
+pub enum PlayerActionStatus {
+    /// Started breaking a block
+    StartedDigging = 0,
+    /// Stopped breaking a block before it broke
+    CancelledDigging = 1,
+    /// Broke a block in survival or adventure mode
+    FinishedDigging = 2,
+    /// Dropped the whole held stack
+    DropItemStack = 3,
+    /// Dropped one held item
+    DropItem = 4,
+    /// Released a bow, or finished eating or drinking
+    ShootArrowOrFinishEating = 5,
+    /// Swapped the items in the main and off hand
+    SwapItemInHand = 6,
+}
+
+impl TryFrom<i32> for PlayerActionStatus {
+    type Error = ServerError;
+
+    fn try_from(value: i32) -> Result<Self> {
+        match value {
+            0 => Ok(PlayerActionStatus::StartedDigging),
+            1 => Ok(PlayerActionStatus::CancelledDigging),
+            2 => Ok(PlayerActionStatus::FinishedDigging),
+            3 => Ok(PlayerActionStatus::DropItemStack),
+            4 => Ok(PlayerActionStatus::DropItem),
+            5 => Ok(PlayerActionStatus::ShootArrowOrFinishEating),
+            6 => Ok(PlayerActionStatus::SwapItemInHand),
+            _ => Err(ServerError::Protocol(format!(
+                "Invalid player action status: {}",
+                value
+            ))),
+        }
+    }
+}
+
+/// Player Action packet (serverbound)
+#[doc(alias = "PlayerDiggingPacket")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlayerActionPacket {
+    /// What the player is doing
+    pub status: PlayerActionStatus,
+    /// Block being broken, zero for actions without a block
+    pub position: Position,
+    /// Face of the block being hit, from 0 (bottom) to 5 (east)
+    pub face: i8,
+    /// Sequence number to acknowledge
+    pub sequence: VarInt,
+}
+
+impl Packet for PlayerActionPacket {
+    const ID: i32 = 0x28;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            status: PlayerActionStatus::try_from(VarInt::read(reader)?.0)?,
+            position: Position::read(reader)?,
+            face: read_unsigned_byte(reader)? as i8,
+            sequence: VarInt::read(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        VarInt(self.status as i32).write(writer)?;
+        self.position.write(writer)?;
+        write_unsigned_byte(self.face as u8, writer)?;
+        self.sequence.write(writer)
+    }
+}
+
+impl ServerboundPacket for PlayerActionPacket {}
+
+/// Block Destruction packet (clientbound)
+///
+/// Shows the crack animation of a block being broken by another player.
+#[doc(alias = "BlockDestructionPacket")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockBreakAnimationPacket {
+    /// Entity ID of the player breaking the block
+    pub entity_id: VarInt,
+    /// Block being broken
+    pub position: Position,
+    /// Destroy stage from 0 to 9, any other value removes the animation
+    pub destroy_stage: u8,
+}
+
+impl Packet for BlockBreakAnimationPacket {
+    const ID: i32 = 0x05;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            entity_id: VarInt::read(reader)?,
+            position: Position::read(reader)?,
+            destroy_stage: read_unsigned_byte(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.entity_id.write(writer)?;
+        self.position.write(writer)?;
+        write_unsigned_byte(self.destroy_stage, writer)
+    }
+}
+
+impl ClientboundPacket for BlockBreakAnimationPacket {}
+
+/// Block Changed Ack packet (clientbound)
+///
+/// Tells the client that every block change up to a sequence number has been
+/// applied, so it shows the blocks sent by the server from then on.
+#[doc(alias = "BlockChangedAckPacket")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AcknowledgeBlockChangePacket {
+    /// Sequence number of the last applied change
+    pub sequence: VarInt,
+}
+
+impl Packet for AcknowledgeBlockChangePacket {
+    const ID: i32 = 0x04;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            sequence: VarInt::read(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.sequence.write(writer)
+    }
+}
+
+impl ClientboundPacket for AcknowledgeBlockChangePacket {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_player_action_roundtrip() {
+        let packet = PlayerActionPacket {
+            status: PlayerActionStatus::FinishedDigging,
+            position: Position::new(-3, 63, 12),
+            face: 1,
+            sequence: VarInt(7),
+        };
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(buffer[0], 2);
+        assert_eq!(
+            PlayerActionPacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+
+        // Unknown statuses are rejected
+        let mut buffer = Vec::new();
+        VarInt(9).write(&mut buffer).unwrap();
+        Position::new(0, 0, 0).write(&mut buffer).unwrap();
+        buffer.extend([0, 0]);
+        assert!(PlayerActionPacket::read(&mut Cursor::new(buffer)).is_err());
+    }
+
+    #[test]
+    fn test_block_break_animation_roundtrip() {
+        let packet = BlockBreakAnimationPacket {
+            entity_id: VarInt(4),
+            position: Position::new(1, -2, 3),
+            destroy_stage: CLEAR_DESTROY_STAGE,
+        };
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(buffer.len(), 10);
+        assert_eq!(
+            BlockBreakAnimationPacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+    }
+}
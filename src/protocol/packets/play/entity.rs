@@ -0,0 +1,458 @@
+//! Entity packets
+//!
+//! Entities are referred to by a numeric ID that is unique for the lifetime of
+//! the server, and are created on the client by a Spawn Entity packet.
+
+use crate::error::Result;
+use crate::protocol::packets::{ClientboundPacket, Packet};
+use crate::protocol::types::{
+    Angle, FixedPoint, McUuid, VarInt, read_bool, read_double, read_float, read_int, read_short,
+    read_unsigned_byte, read_uuid, write_bool, write_double, write_float, write_int, write_short,
+    write_unsigned_byte, write_uuid,
+};
+use std::io::{Read, Write};
+
+/// Entity velocity, sent as a short in 1/8000 blocks per tick
+pub type Velocity = FixedPoint<8000, i16>;
+
+/// Spawn Entity packet (clientbound)
+#[doc(alias = "AddEntityPacket")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpawnEntityPacket {
+    /// Entity ID
+    pub entity_id: VarInt,
+    /// Entity UUID
+    pub uuid: McUuid,
+    /// ID in the `minecraft:entity_type` registry
+    pub entity_type: VarInt,
+    /// X coordinate
+    pub x: f64,
+    /// Y coordinate
+    pub y: f64,
+    /// Z coordinate
+    pub z: f64,
+    /// Pitch
+    pub pitch: Angle,
+    /// Yaw
+    pub yaw: Angle,
+    /// Head yaw, for living entities
+    pub head_yaw: Angle,
+    /// Type-specific data, such as the direction of an item frame
+    pub data: VarInt,
+    /// X velocity in blocks per tick
+    pub velocity_x: Velocity,
+    /// Y velocity in blocks per tick
+    pub velocity_y: Velocity,
+    /// Z velocity in blocks per tick
+    pub velocity_z: Velocity,
+}
+
+impl Packet for SpawnEntityPacket {
+    const ID: i32 = 0x01;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(SpawnEntityPacket {
+            entity_id: VarInt::read(reader)?,
+            uuid: read_uuid(reader)?,
+            entity_type: VarInt::read(reader)?,
+            x: read_double(reader)?,
+            y: read_double(reader)?,
+            z: read_double(reader)?,
+            pitch: Angle::read(reader)?,
+            yaw: Angle::read(reader)?,
+            head_yaw: Angle::read(reader)?,
+            data: VarInt::read(reader)?,
+            velocity_x: Velocity::read(reader)?,
+            velocity_y: Velocity::read(reader)?,
+            velocity_z: Velocity::read(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.entity_id.write(writer)?;
+        write_uuid(&self.uuid, writer)?;
+        self.entity_type.write(writer)?;
+        write_double(self.x, writer)?;
+        write_double(self.y, writer)?;
+        write_double(self.z, writer)?;
+        self.pitch.write(writer)?;
+        self.yaw.write(writer)?;
+        self.head_yaw.write(writer)?;
+        self.data.write(writer)?;
+        self.velocity_x.write(writer)?;
+        self.velocity_y.write(writer)?;
+        self.velocity_z.write(writer)?;
+        Ok(())
+    }
+}
+
+impl ClientboundPacket for SpawnEntityPacket {}
+
+/// Update Entity Position packet (clientbound)
+///
+/// Moves an entity by less than 8 blocks on each axis.
+#[doc(alias = "MoveEntityPosPacket")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntityRelativeMovePacket {
+    /// Entity ID
+    pub entity_id: VarInt,
+    /// X change in 1/4096 blocks
+    pub delta_x: i16,
+    /// Y change in 1/4096 blocks
+    pub delta_y: i16,
+    /// Z change in 1/4096 blocks
+    pub delta_z: i16,
+    /// Whether the entity is on the ground
+    pub on_ground: bool,
+}
+
+impl Packet for EntityRelativeMovePacket {
+    const ID: i32 = 0x2E;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(EntityRelativeMovePacket {
+            entity_id: VarInt::read(reader)?,
+            delta_x: read_short(reader)?,
+            delta_y: read_short(reader)?,
+            delta_z: read_short(reader)?,
+            on_ground: read_bool(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.entity_id.write(writer)?;
+        write_short(self.delta_x, writer)?;
+        write_short(self.delta_y, writer)?;
+        write_short(self.delta_z, writer)?;
+        write_bool(self.on_ground, writer)
+    }
+}
+
+impl ClientboundPacket for EntityRelativeMovePacket {}
+
+/// Update Entity Position and Rotation packet (clientbound)
+#[doc(alias = "MoveEntityPosRotPacket")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntityLookAndRelativeMovePacket {
+    /// Entity ID
+    pub entity_id: VarInt,
+    /// X change in 1/4096 blocks
+    pub delta_x: i16,
+    /// Y change in 1/4096 blocks
+    pub delta_y: i16,
+    /// Z change in 1/4096 blocks
+    pub delta_z: i16,
+    /// New yaw
+    pub yaw: Angle,
+    /// New pitch
+    pub pitch: Angle,
+    /// Whether the entity is on the ground
+    pub on_ground: bool,
+}
+
+impl Packet for EntityLookAndRelativeMovePacket {
+    const ID: i32 = 0x2F;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(EntityLookAndRelativeMovePacket {
+            entity_id: VarInt::read(reader)?,
+            delta_x: read_short(reader)?,
+            delta_y: read_short(reader)?,
+            delta_z: read_short(reader)?,
+            yaw: Angle::read(reader)?,
+            pitch: Angle::read(reader)?,
+            on_ground: read_bool(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.entity_id.write(writer)?;
+        write_short(self.delta_x, writer)?;
+        write_short(self.delta_y, writer)?;
+        write_short(self.delta_z, writer)?;
+        self.yaw.write(writer)?;
+        self.pitch.write(writer)?;
+        write_bool(self.on_ground, writer)
+    }
+}
+
+impl ClientboundPacket for EntityLookAndRelativeMovePacket {}
+
+/// Update Entity Rotation packet (clientbound)
+#[doc(alias = "MoveEntityRotPacket")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntityLookPacket {
+    /// Entity ID
+    pub entity_id: VarInt,
+    /// New yaw
+    pub yaw: Angle,
+    /// New pitch
+    pub pitch: Angle,
+    /// Whether the entity is on the ground
+    pub on_ground: bool,
+}
+
+impl Packet for EntityLookPacket {
+    const ID: i32 = 0x31;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(EntityLookPacket {
+            entity_id: VarInt::read(reader)?,
+            yaw: Angle::read(reader)?,
+            pitch: Angle::read(reader)?,
+            on_ground: read_bool(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.entity_id.write(writer)?;
+        self.yaw.write(writer)?;
+        self.pitch.write(writer)?;
+        write_bool(self.on_ground, writer)
+    }
+}
+
+impl ClientboundPacket for EntityLookPacket {}
+
+/// Teleport Entity packet (clientbound)
+///
+/// Moves an entity to an absolute position, for moves too large for the
+/// relative move packets.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TeleportEntityPacket {
+    /// Entity ID
+    pub entity_id: VarInt,
+    /// X coordinate
+    pub x: f64,
+    /// Y coordinate
+    pub y: f64,
+    /// Z coordinate
+    pub z: f64,
+    /// X velocity
+    pub velocity_x: f64,
+    /// Y velocity
+    pub velocity_y: f64,
+    /// Z velocity
+    pub velocity_z: f64,
+    /// Yaw in degrees
+    pub yaw: f32,
+    /// Pitch in degrees
+    pub pitch: f32,
+    /// Bit field of values that are relative, as in
+    /// [`PlayerPositionPacket`](super::PlayerPositionPacket)
+    pub flags: i32,
+    /// Whether the entity is on the ground
+    pub on_ground: bool,
+}
+
+impl Packet for TeleportEntityPacket {
+    const ID: i32 = 0x76;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(TeleportEntityPacket {
+            entity_id: VarInt::read(reader)?,
+            x: read_double(reader)?,
+            y: read_double(reader)?,
+            z: read_double(reader)?,
+            velocity_x: read_double(reader)?,
+            velocity_y: read_double(reader)?,
+            velocity_z: read_double(reader)?,
+            yaw: read_float(reader)?,
+            pitch: read_float(reader)?,
+            flags: read_int(reader)?,
+            on_ground: read_bool(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.entity_id.write(writer)?;
+        for value in [
+            self.x,
+            self.y,
+            self.z,
+            self.velocity_x,
+            self.velocity_y,
+            self.velocity_z,
+        ] {
+            write_double(value, writer)?;
+        }
+        write_float(self.yaw, writer)?;
+        write_float(self.pitch, writer)?;
+        write_int(self.flags, writer)?;
+        write_bool(self.on_ground, writer)
+    }
+}
+
+impl ClientboundPacket for TeleportEntityPacket {}
+
+/// Entity Event packet (clientbound)
+///
+/// Triggers an effect tied to an entity, such as an animation. A few
+/// statuses sent to a player about themselves change client settings
+/// instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntityEventPacket {
+    /// Entity ID, as a plain int rather than a VarInt
+    pub entity_id: i32,
+    /// Entity status, whose meaning depends on the entity type
+    pub status: u8,
+}
+
+impl EntityEventPacket {
+    /// Status hiding coordinates from a player's debug screen
+    pub const ENABLE_REDUCED_DEBUG_INFO: u8 = 22;
+    /// Status showing the full debug screen to a player again
+    pub const DISABLE_REDUCED_DEBUG_INFO: u8 = 23;
+    /// Status telling a player they have permission level 0; the statuses
+    /// for levels 1 to 4 follow it
+    pub const OP_PERMISSION_LEVEL_0: u8 = 24;
+
+    /// Create the event telling a player their permission level, which
+    /// enables client features such as the game mode switcher
+    pub fn permission_level(entity_id: i32, level: u8) -> Self {
+        Self {
+            entity_id,
+            status: Self::OP_PERMISSION_LEVEL_0 + level.min(4),
+        }
+    }
+}
+
+impl Packet for EntityEventPacket {
+    const ID: i32 = 0x1E;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(EntityEventPacket {
+            entity_id: read_int(reader)?,
+            status: read_unsigned_byte(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_int(self.entity_id, writer)?;
+        write_unsigned_byte(self.status, writer)
+    }
+}
+
+impl ClientboundPacket for EntityEventPacket {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_spawn_entity_packet() {
+        let packet = SpawnEntityPacket {
+            entity_id: VarInt(7),
+            uuid: McUuid::from_u128(0x0123_4567_89AB_CDEF_0123_4567_89AB_CDEF),
+            entity_type: VarInt(20),
+            x: 0.5,
+            y: 64.0,
+            z: -0.5,
+            pitch: Angle::from_degrees(-45.0),
+            yaw: Angle::from_degrees(90.0),
+            head_yaw: Angle::from_degrees(90.0),
+            data: VarInt(0),
+            velocity_x: Velocity::default(),
+            velocity_y: Velocity::from_float(-1.0),
+            velocity_z: Velocity::default(),
+        };
+
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        // ID, UUID, type, three doubles, three angles, data and three shorts
+        assert_eq!(buffer.len(), 1 + 16 + 1 + 24 + 3 + 1 + 6);
+        assert_eq!(&buffer[42..45], [0xE0, 0x40, 0x40]);
+        assert_eq!(
+            SpawnEntityPacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+    }
+
+    #[test]
+    fn test_entity_event_packet() {
+        let packet = EntityEventPacket {
+            entity_id: 258,
+            status: EntityEventPacket::ENABLE_REDUCED_DEBUG_INFO,
+        };
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(buffer, [0x00, 0x00, 0x01, 0x02, 22]);
+        assert_eq!(
+            EntityEventPacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+    }
+
+    #[test]
+    fn test_move_entity_packets() {
+        let relative = EntityRelativeMovePacket {
+            entity_id: VarInt(7),
+            delta_x: 4096,
+            delta_y: -1,
+            delta_z: 0,
+            on_ground: true,
+        };
+        let mut buffer = Vec::new();
+        relative.write(&mut buffer).unwrap();
+        assert_eq!(buffer, [0x07, 0x10, 0x00, 0xFF, 0xFF, 0x00, 0x00, 0x01]);
+        assert_eq!(
+            EntityRelativeMovePacket::read(&mut Cursor::new(buffer)).unwrap(),
+            relative
+        );
+
+        let look_and_move = EntityLookAndRelativeMovePacket {
+            entity_id: VarInt(7),
+            delta_x: 1,
+            delta_y: 2,
+            delta_z: 3,
+            yaw: Angle(64),
+            pitch: Angle(0),
+            on_ground: false,
+        };
+        let mut buffer = Vec::new();
+        look_and_move.write(&mut buffer).unwrap();
+        assert_eq!(
+            EntityLookAndRelativeMovePacket::read(&mut Cursor::new(buffer)).unwrap(),
+            look_and_move
+        );
+
+        let look = EntityLookPacket {
+            entity_id: VarInt(7),
+            yaw: Angle(128),
+            pitch: Angle(192),
+            on_ground: true,
+        };
+        let mut buffer = Vec::new();
+        look.write(&mut buffer).unwrap();
+        assert_eq!(buffer, [0x07, 0x80, 0xC0, 0x01]);
+        assert_eq!(
+            EntityLookPacket::read(&mut Cursor::new(buffer)).unwrap(),
+            look
+        );
+    }
+
+    #[test]
+    fn test_teleport_entity_packet() {
+        let packet = TeleportEntityPacket {
+            entity_id: VarInt(7),
+            x: 100.0,
+            y: 64.0,
+            z: -100.0,
+            velocity_x: 0.0,
+            velocity_y: 0.0,
+            velocity_z: 0.0,
+            yaw: 90.0,
+            pitch: 0.0,
+            flags: 0,
+            on_ground: true,
+        };
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(buffer.len(), 1 + 6 * 8 + 2 * 4 + 4 + 1);
+        assert_eq!(
+            TeleportEntityPacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+    }
+}
@@ -0,0 +1,157 @@
+//! Entity equipment packets
+//!
+//! Other players only see the items a player holds and wears through these
+//! packets; a client's own inventory is synced separately.
+
+use crate::error::{Result, ServerError};
+use crate::protocol::packets::{ClientboundPacket, Packet};
+use crate::protocol::types::{Slot, VarInt, read_unsigned_byte, write_unsigned_byte};
+use std::io::{Read, Write};
+
+/// Bit set on the slot byte of every entry but the last
+const MORE_ENTRIES: u8 = 0x80;
+
+/// A slot an entity can hold or wear an item in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum EquipmentSlot {
+    /// Main hand
+    MainHand = 0,
+    /// Off hand
+    OffHand = 1,
+    /// Boots
+    Feet = 2,
+    /// Leggings
+    Legs = 3,
+    /// Chestplate or elytra
+    Chest = 4,
+    /// Helmet
+    Head = 5,
+    /// Body armor of an animal, such as a wolf
+    Body = 6,
+    /// Saddle of a mount
+    Saddle = 7,
+}
+
+impl TryFrom<u8> for EquipmentSlot {
+    type Error = ServerError;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(EquipmentSlot::MainHand),
+            1 => Ok(EquipmentSlot::OffHand),
+            2 => Ok(EquipmentSlot::Feet),
+            3 => Ok(EquipmentSlot::Legs),
+            4 => Ok(EquipmentSlot::Chest),
+            5 => Ok(EquipmentSlot::Head),
+            6 => Ok(EquipmentSlot::Body),
+            7 => Ok(EquipmentSlot::Saddle),
+            _ => Err(ServerError::Protocol(format!(
+                "Invalid equipment slot: {}",
+                value
+            ))),
+        }
+    }
+}
+
+/// Set Equipment packet (clientbound)
+///
+/// Each entry starts with its slot byte, which has its top bit set when
+/// another entry follows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetEquipmentPacket {
+    /// Entity ID
+    pub entity_id: VarInt,
+    /// Changed slots and their new items, at least one
+    pub equipment: Vec<(EquipmentSlot, Slot)>,
+}
+
+impl Packet for SetEquipmentPacket {
+    const ID: i32 = 0x5F;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let entity_id = VarInt::read(reader)?;
+        let mut equipment = Vec::new();
+        loop {
+            let byte = read_unsigned_byte(reader)?;
+            let slot = EquipmentSlot::try_from(byte & !MORE_ENTRIES)?;
+            equipment.push((slot, Slot::read(reader)?));
+            if byte & MORE_ENTRIES == 0 {
+                break;
+            }
+        }
+        Ok(Self {
+            entity_id,
+            equipment,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        if self.equipment.is_empty() {
+            return Err(ServerError::Protocol(
+                "Set Equipment packet without equipment".to_string(),
+            ));
+        }
+        self.entity_id.write(writer)?;
+        let last = self.equipment.len() - 1;
+        for (index, (slot, item)) in self.equipment.iter().enumerate() {
+            let more = if index < last { MORE_ENTRIES } else { 0 };
+            write_unsigned_byte(*slot as u8 | more, writer)?;
+            item.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl ClientboundPacket for SetEquipmentPacket {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_slot_bytes() {
+        let packet = SetEquipmentPacket {
+            entity_id: VarInt(1),
+            equipment: vec![
+                (EquipmentSlot::MainHand, Slot::empty()),
+                (EquipmentSlot::Head, Slot::empty()),
+                (EquipmentSlot::Feet, Slot::empty()),
+            ],
+        };
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        // An empty slot is a single zero count
+        assert_eq!(buffer, [1, 0x80, 0, 0x85, 0, 0x02, 0]);
+        assert_eq!(
+            SetEquipmentPacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+    }
+
+    #[test]
+    fn test_single_entry_roundtrip() {
+        let packet = SetEquipmentPacket {
+            entity_id: VarInt(300),
+            equipment: vec![(EquipmentSlot::OffHand, Slot::new(1, 3))],
+        };
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(buffer[2], EquipmentSlot::OffHand as u8);
+        assert_eq!(
+            SetEquipmentPacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+    }
+
+    #[test]
+    fn test_invalid_equipment() {
+        let empty = SetEquipmentPacket {
+            entity_id: VarInt(1),
+            equipment: Vec::new(),
+        };
+        assert!(empty.write(&mut Vec::new()).is_err());
+        assert!(SetEquipmentPacket::read(&mut Cursor::new([1, 8, 0])).is_err());
+    }
+}
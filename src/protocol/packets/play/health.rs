@@ -0,0 +1,103 @@
+//! Health packets
+
+use crate::error::Result;
+use crate::protocol::packets::{ClientboundPacket, Packet};
+use crate::protocol::types::{VarInt, read_float, write_float};
+use std::io::{Read, Write};
+
+/// Set Health packet (clientbound)
+///
+/// Sets the health and food bars of the player. Health at or below 0 shows
+/// the death screen.
+#[doc(alias = "UpdateHealthPacket")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SetHealthPacket {
+    /// Health, where 20.0 is ten full hearts
+    pub health: f32,
+    /// Food level, from 0 to 20
+    pub food: VarInt,
+    /// Food saturation, from 0.0 up to the food level
+    pub saturation: f32,
+}
+
+impl Packet for SetHealthPacket {
+    const ID: i32 = 0x61;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            health: read_float(reader)?,
+            food: VarInt::read(reader)?,
+            saturation: read_float(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_float(self.health, writer)?;
+        self.food.write(writer)?;
+        write_float(self.saturation, writer)
+    }
+}
+
+impl ClientboundPacket for SetHealthPacket {}
+
+/// Hurt Animation packet (clientbound)
+///
+/// Flashes an entity red and tilts the camera of a hurt player.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HurtAnimationPacket {
+    /// Entity ID
+    pub entity_id: VarInt,
+    /// Direction the damage came from, relative to the entity's yaw
+    pub yaw: f32,
+}
+
+impl Packet for HurtAnimationPacket {
+    const ID: i32 = 0x24;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            entity_id: VarInt::read(reader)?,
+            yaw: read_float(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.entity_id.write(writer)?;
+        write_float(self.yaw, writer)
+    }
+}
+
+impl ClientboundPacket for HurtAnimationPacket {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_health_packets_roundtrip() {
+        let health = SetHealthPacket {
+            health: 20.0,
+            food: VarInt(18),
+            saturation: 5.0,
+        };
+        let mut buffer = Vec::new();
+        health.write(&mut buffer).unwrap();
+        assert_eq!(buffer, [0x41, 0xA0, 0, 0, 18, 0x40, 0xA0, 0, 0]);
+        assert_eq!(
+            SetHealthPacket::read(&mut Cursor::new(buffer)).unwrap(),
+            health
+        );
+
+        let hurt = HurtAnimationPacket {
+            entity_id: VarInt(3),
+            yaw: 90.0,
+        };
+        let mut buffer = Vec::new();
+        hurt.write(&mut buffer).unwrap();
+        assert_eq!(
+            HurtAnimationPacket::read(&mut Cursor::new(buffer)).unwrap(),
+            hurt
+        );
+    }
+}
@@ -0,0 +1,93 @@
+//! Held item packets
+//!
+//! The held item is the selected one of the nine hotbar slots, numbered
+//! from 0 to 8.
+
+use crate::error::Result;
+use crate::protocol::packets::{ClientboundPacket, Packet, ServerboundPacket};
+use crate::protocol::types::{VarInt, read_short, write_short};
+use std::io::{Read, Write};
+
+/// Number of hotbar slots
+pub const HOTBAR_SIZE: i16 = 9;
+
+/// Set Held Item packet (clientbound)
+///
+/// Selects a hotbar slot on the client.
+#[doc(alias = "SetHeldSlotPacket")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetHeldItemPacket {
+    /// Hotbar slot to select, from 0 to 8
+    pub slot: VarInt,
+}
+
+impl Packet for SetHeldItemPacket {
+    const ID: i32 = 0x62;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            slot: VarInt::read(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.slot.write(writer)
+    }
+}
+
+impl ClientboundPacket for SetHeldItemPacket {}
+
+/// Set Held Item packet (serverbound)
+///
+/// Sent when the player selects another hotbar slot. The slot is not
+/// validated when read.
+#[doc(alias = "SetCarriedItemPacket")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServerboundSetHeldItemPacket {
+    /// Selected hotbar slot, from 0 to 8
+    pub slot: i16,
+}
+
+impl ServerboundSetHeldItemPacket {
+    /// Whether the slot is a hotbar slot
+    pub fn is_valid(&self) -> bool {
+        (0..HOTBAR_SIZE).contains(&self.slot)
+    }
+}
+
+impl Packet for ServerboundSetHeldItemPacket {
+    const ID: i32 = 0x34;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            slot: read_short(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_short(self.slot, writer)
+    }
+}
+
+impl ServerboundPacket for ServerboundSetHeldItemPacket {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_held_item_packets() {
+        let mut buffer = Vec::new();
+        SetHeldItemPacket { slot: VarInt(8) }
+            .write(&mut buffer)
+            .unwrap();
+        assert_eq!(buffer, [8]);
+
+        let packet = ServerboundSetHeldItemPacket::read(&mut Cursor::new([0, 4])).unwrap();
+        assert_eq!(packet.slot, 4);
+        assert!(packet.is_valid());
+        assert!(!ServerboundSetHeldItemPacket { slot: 9 }.is_valid());
+        assert!(!ServerboundSetHeldItemPacket { slot: -1 }.is_valid());
+    }
+}
@@ -0,0 +1,263 @@
+//! Map packets
+//!
+//! A map item shows a 128x128 grid of colors, each an index into the map
+//! color palette, with icons such as players and banners drawn on top.
+//! Updates can send just the rectangle of colors that changed.
+
+use crate::error::{Result, ServerError};
+use crate::protocol::packets::{ClientboundPacket, Packet};
+use crate::protocol::text::TextComponent;
+use crate::protocol::types::{
+    VarInt, read_bool, read_unsigned_byte, write_bool, write_unsigned_byte,
+};
+use std::io::{Read, Write};
+
+/// Width and height of a map, in pixels
+pub const MAP_SIZE: usize = 128;
+
+/// Number of pixels on a map
+pub const MAP_PIXELS: usize = MAP_SIZE * MAP_SIZE;
+
+/// An icon drawn on a map
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapIcon {
+    /// ID of the icon in the `minecraft:map_decoration_type` registry
+    pub icon_type: VarInt,
+    /// X position, from -128 (left edge) to 127 (right edge)
+    pub x: i8,
+    /// Z position, from -128 (top edge) to 127 (bottom edge)
+    pub z: i8,
+    /// Rotation in sixteenths of a full turn clockwise, from 0 to 15
+    pub direction: i8,
+    /// Name shown below the icon
+    pub display_name: Option<TextComponent>,
+}
+
+impl MapIcon {
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            icon_type: VarInt::read(reader)?,
+            x: read_unsigned_byte(reader)? as i8,
+            z: read_unsigned_byte(reader)? as i8,
+            direction: read_unsigned_byte(reader)? as i8,
+            display_name: if read_bool(reader)? {
+                Some(TextComponent::read_nbt(reader)?)
+            } else {
+                None
+            },
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.icon_type.write(writer)?;
+        write_unsigned_byte(self.x as u8, writer)?;
+        write_unsigned_byte(self.z as u8, writer)?;
+        write_unsigned_byte(self.direction as u8, writer)?;
+        write_bool(self.display_name.is_some(), writer)?;
+        if let Some(name) = &self.display_name {
+            name.write_nbt(writer)?;
+        }
+        Ok(())
+    }
+}
+
+/// A rectangle of map colors to update
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapPatch {
+    /// Width of the rectangle, from 1 to 128
+    pub columns: u8,
+    /// Height of the rectangle, from 1 to 128
+    pub rows: u8,
+    /// X of the rectangle's left column
+    pub x: u8,
+    /// Z of the rectangle's top row
+    pub z: u8,
+    /// Color indices, row by row
+    pub data: Vec<u8>,
+}
+
+/// Map Data packet (clientbound)
+///
+/// Icons and colors left out keep their current values.
+#[doc(alias = "MapItemDataPacket")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapDataPacket {
+    /// ID of the map
+    pub map_id: VarInt,
+    /// Zoom level, from 0 (one block per pixel) to 4 (16 blocks per pixel)
+    pub scale: i8,
+    /// Whether the map was locked in a cartography table
+    pub locked: bool,
+    /// Icons replacing those on the map, if they changed
+    pub icons: Option<Vec<MapIcon>>,
+    /// Colors to update, if any changed
+    pub patch: Option<MapPatch>,
+}
+
+impl Packet for MapDataPacket {
+    const ID: i32 = 0x2C;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let map_id = VarInt::read(reader)?;
+        let scale = read_unsigned_byte(reader)? as i8;
+        let locked = read_bool(reader)?;
+        let icons = if read_bool(reader)? {
+            let count = VarInt::read(reader)?.0;
+            let mut icons = Vec::new();
+            for _ in 0..count {
+                icons.push(MapIcon::read(reader)?);
+            }
+            Some(icons)
+        } else {
+            None
+        };
+        let columns = read_unsigned_byte(reader)?;
+        let patch = if columns > 0 {
+            let rows = read_unsigned_byte(reader)?;
+            let x = read_unsigned_byte(reader)?;
+            let z = read_unsigned_byte(reader)?;
+            let length = VarInt::read(reader)?.0;
+            if length < 0 || length as usize > MAP_PIXELS {
+                return Err(ServerError::Protocol(format!(
+                    "Invalid map data length: {}",
+                    length
+                )));
+            }
+            let mut data = vec![0; length as usize];
+            reader.read_exact(&mut data)?;
+            Some(MapPatch {
+                columns,
+                rows,
+                x,
+                z,
+                data,
+            })
+        } else {
+            None
+        };
+        Ok(Self {
+            map_id,
+            scale,
+            locked,
+            icons,
+            patch,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.map_id.write(writer)?;
+        write_unsigned_byte(self.scale as u8, writer)?;
+        write_bool(self.locked, writer)?;
+        write_bool(self.icons.is_some(), writer)?;
+        if let Some(icons) = &self.icons {
+            VarInt(icons.len() as i32).write(writer)?;
+            for icon in icons {
+                icon.write(writer)?;
+            }
+        }
+        let Some(patch) = &self.patch else {
+            return write_unsigned_byte(0, writer);
+        };
+        if patch.columns == 0
+            || patch.data.len() != usize::from(patch.columns) * usize::from(patch.rows)
+        {
+            return Err(ServerError::Protocol(format!(
+                "Map patch of {}x{} pixels with {} colors",
+                patch.columns,
+                patch.rows,
+                patch.data.len()
+            )));
+        }
+        write_unsigned_byte(patch.columns, writer)?;
+        write_unsigned_byte(patch.rows, writer)?;
+        write_unsigned_byte(patch.x, writer)?;
+        write_unsigned_byte(patch.z, writer)?;
+        VarInt(patch.data.len() as i32).write(writer)?;
+        writer.write_all(&patch.data)?;
+        Ok(())
+    }
+}
+
+impl ClientboundPacket for MapDataPacket {}
+
+/// Build the packet drawing a whole map, at the closest zoom and without
+/// icons
+pub fn create_map(id: i32, pixels: &[u8; MAP_PIXELS]) -> MapDataPacket {
+    MapDataPacket {
+        map_id: VarInt(id),
+        scale: 0,
+        locked: false,
+        icons: Some(Vec::new()),
+        patch: Some(MapPatch {
+            columns: MAP_SIZE as u8,
+            rows: MAP_SIZE as u8,
+            x: 0,
+            z: 0,
+            data: pixels.to_vec(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_create_map() {
+        let mut pixels = [0; MAP_PIXELS];
+        pixels[MAP_SIZE + 1] = 34;
+        let packet = create_map(7, &pixels);
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        // ID, scale, locked, an empty icon list, then the 128x128 patch at
+        // 0, 0 and its three-byte length
+        assert_eq!(&buffer[..10], [7, 0, 0, 1, 0, 128, 128, 0, 0, 0x80]);
+        assert_eq!(buffer.len(), 12 + MAP_PIXELS);
+
+        let read = MapDataPacket::read(&mut Cursor::new(buffer)).unwrap();
+        assert_eq!(read.patch.unwrap().data[MAP_SIZE + 1], 34);
+    }
+
+    #[test]
+    fn test_icons_without_colors() {
+        let packet = MapDataPacket {
+            map_id: VarInt(1),
+            scale: 2,
+            locked: true,
+            icons: Some(vec![MapIcon {
+                icon_type: VarInt(0),
+                x: -128,
+                z: 5,
+                direction: 8,
+                display_name: Some(TextComponent::text("Spawn")),
+            }]),
+            patch: None,
+        };
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(buffer.last(), Some(&0));
+        assert_eq!(
+            MapDataPacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+    }
+
+    #[test]
+    fn test_invalid_patch() {
+        let packet = MapDataPacket {
+            map_id: VarInt(1),
+            scale: 0,
+            locked: false,
+            icons: None,
+            patch: Some(MapPatch {
+                columns: 2,
+                rows: 2,
+                x: 0,
+                z: 0,
+                data: vec![0; 3],
+            }),
+        };
+        assert!(packet.write(&mut Vec::new()).is_err());
+    }
+}
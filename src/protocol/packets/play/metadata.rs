@@ -0,0 +1,317 @@
+//! Entity metadata
+//!
+//! Metadata is a list of indexed values describing how an entity is rendered,
+//! such as its flags, custom name or pose. Each value is sent with a type ID
+//! and the list ends with the index `0xFF`.
+
+use crate::error::{Result, ServerError};
+use crate::protocol::packets::{ClientboundPacket, Packet};
+use crate::protocol::text::TextComponent;
+use crate::protocol::types::{
+    McString, McUuid, Position, Slot, VarInt, VarLong, read_bool, read_float, read_unsigned_byte,
+    read_uuid, write_bool, write_float, write_unsigned_byte, write_uuid,
+};
+use std::io::{Read, Write};
+
+/// Index ending a metadata list
+const END_OF_METADATA: u8 = 0xFF;
+
+/// A typed entity metadata value
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetadataValue {
+    /// Signed byte, often a bit field
+    Byte(i8),
+    /// VarInt
+    VarInt(i32),
+    /// VarLong
+    VarLong(i64),
+    /// Float
+    Float(f32),
+    /// String
+    String(McString),
+    /// Text component
+    TextComponent(TextComponent),
+    /// Optional text component, such as a custom name
+    OptTextComponent(Option<TextComponent>),
+    /// Item stack
+    Slot(Slot),
+    /// Boolean
+    Boolean(bool),
+    /// Rotation around the x, y and z axes in degrees
+    Rotations(f32, f32, f32),
+    /// Block position
+    BlockPos(Position),
+    /// Optional block position
+    OptBlockPos(Option<Position>),
+    /// Direction (0=Down, 1=Up, 2=North, 3=South, 4=West, 5=East)
+    Direction(i32),
+    /// Optional UUID, such as the owner of a tamed animal
+    OptUuid(Option<McUuid>),
+    /// Block state ID
+    BlockState(i32),
+    /// Optional block state ID, where air means absent
+    OptBlockState(Option<i32>),
+    /// Optional non-negative VarInt
+    OptVarInt(Option<i32>),
+    /// Pose (0=Standing, 1=Fall flying, 2=Sleeping, 3=Swimming, ...)
+    Pose(i32),
+    /// Vector of three floats
+    Vector3(f32, f32, f32),
+    /// Quaternion of four floats (x, y, z, w)
+    Quaternion(f32, f32, f32, f32),
+}
+
+impl MetadataValue {
+    /// Protocol ID of the value's type
+    pub fn type_id(&self) -> i32 {
+        match self {
+            MetadataValue::Byte(_) => 0,
+            MetadataValue::VarInt(_) => 1,
+            MetadataValue::VarLong(_) => 2,
+            MetadataValue::Float(_) => 3,
+            MetadataValue::String(_) => 4,
+            MetadataValue::TextComponent(_) => 5,
+            MetadataValue::OptTextComponent(_) => 6,
+            MetadataValue::Slot(_) => 7,
+            MetadataValue::Boolean(_) => 8,
+            MetadataValue::Rotations(..) => 9,
+            MetadataValue::BlockPos(_) => 10,
+            MetadataValue::OptBlockPos(_) => 11,
+            MetadataValue::Direction(_) => 12,
+            MetadataValue::OptUuid(_) => 13,
+            MetadataValue::BlockState(_) => 14,
+            MetadataValue::OptBlockState(_) => 15,
+            MetadataValue::OptVarInt(_) => 19,
+            MetadataValue::Pose(_) => 20,
+            MetadataValue::Vector3(..) => 32,
+            MetadataValue::Quaternion(..) => 33,
+        }
+    }
+
+    /// Read a value of the given type
+    ///
+    /// Types whose payload refers to registries (particles, villager data and
+    /// the mob variants) are not supported.
+    pub fn read<R: Read>(type_id: i32, reader: &mut R) -> Result<Self> {
+        Ok(match type_id {
+            0 => MetadataValue::Byte(read_unsigned_byte(reader)? as i8),
+            1 => MetadataValue::VarInt(VarInt::read(reader)?.0),
+            2 => MetadataValue::VarLong(VarLong::read(reader)?.0),
+            3 => MetadataValue::Float(read_float(reader)?),
+            4 => MetadataValue::String(McString::read(reader)?),
+            5 => MetadataValue::TextComponent(TextComponent::read_nbt(reader)?),
+            6 => MetadataValue::OptTextComponent(read_optional(reader, TextComponent::read_nbt)?),
+            7 => MetadataValue::Slot(Slot::read(reader)?),
+            8 => MetadataValue::Boolean(read_bool(reader)?),
+            9 => MetadataValue::Rotations(
+                read_float(reader)?,
+                read_float(reader)?,
+                read_float(reader)?,
+            ),
+            10 => MetadataValue::BlockPos(Position::read(reader)?),
+            11 => MetadataValue::OptBlockPos(read_optional(reader, Position::read)?),
+            12 => MetadataValue::Direction(VarInt::read(reader)?.0),
+            13 => MetadataValue::OptUuid(read_optional(reader, read_uuid)?),
+            14 => MetadataValue::BlockState(VarInt::read(reader)?.0),
+            15 => MetadataValue::OptBlockState(Some(VarInt::read(reader)?.0).filter(|&id| id != 0)),
+            19 => MetadataValue::OptVarInt(match VarInt::read(reader)?.0 {
+                0 => None,
+                value => Some(value - 1),
+            }),
+            20 => MetadataValue::Pose(VarInt::read(reader)?.0),
+            32 => MetadataValue::Vector3(
+                read_float(reader)?,
+                read_float(reader)?,
+                read_float(reader)?,
+            ),
+            33 => MetadataValue::Quaternion(
+                read_float(reader)?,
+                read_float(reader)?,
+                read_float(reader)?,
+                read_float(reader)?,
+            ),
+            type_id => {
+                return Err(ServerError::Protocol(format!(
+                    "Unsupported metadata type: {}",
+                    type_id
+                )));
+            }
+        })
+    }
+
+    /// Write the value's payload, without its type ID
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        match self {
+            MetadataValue::Byte(value) => write_unsigned_byte(*value as u8, writer),
+            MetadataValue::VarInt(value)
+            | MetadataValue::Direction(value)
+            | MetadataValue::BlockState(value)
+            | MetadataValue::Pose(value) => VarInt(*value).write(writer),
+            MetadataValue::VarLong(value) => VarLong(*value).write(writer),
+            MetadataValue::Float(value) => write_float(*value, writer),
+            MetadataValue::String(value) => value.write(writer),
+            MetadataValue::TextComponent(value) => value.write_nbt(writer),
+            MetadataValue::OptTextComponent(value) => {
+                write_optional(value.as_ref(), writer, |value, writer| {
+                    value.write_nbt(writer)
+                })
+            }
+            MetadataValue::Slot(value) => value.write(writer),
+            MetadataValue::Boolean(value) => write_bool(*value, writer),
+            MetadataValue::Rotations(x, y, z) | MetadataValue::Vector3(x, y, z) => [x, y, z]
+                .into_iter()
+                .try_for_each(|value| write_float(*value, writer)),
+            MetadataValue::BlockPos(value) => value.write(writer),
+            MetadataValue::OptBlockPos(value) => {
+                write_optional(value.as_ref(), writer, |value, writer| value.write(writer))
+            }
+            MetadataValue::OptUuid(value) => write_optional(value.as_ref(), writer, write_uuid),
+            MetadataValue::OptBlockState(value) => VarInt(value.unwrap_or(0)).write(writer),
+            MetadataValue::OptVarInt(value) => {
+                VarInt(value.map_or(0, |value| value + 1)).write(writer)
+            }
+            MetadataValue::Quaternion(x, y, z, w) => [x, y, z, w]
+                .into_iter()
+                .try_for_each(|value| write_float(*value, writer)),
+        }
+    }
+}
+
+/// Read a value prefixed by a boolean saying whether it is present
+fn read_optional<R: Read, T>(
+    reader: &mut R,
+    read: impl FnOnce(&mut R) -> Result<T>,
+) -> Result<Option<T>> {
+    if read_bool(reader)? {
+        read(reader).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+/// Write a value prefixed by a boolean saying whether it is present
+fn write_optional<W: Write, T>(
+    value: Option<&T>,
+    writer: &mut W,
+    write: impl FnOnce(&T, &mut W) -> Result<()>,
+) -> Result<()> {
+    write_bool(value.is_some(), writer)?;
+    match value {
+        Some(value) => write(value, writer),
+        None => Ok(()),
+    }
+}
+
+/// Set Entity Metadata packet (clientbound)
+#[doc(alias = "SetEntityDataPacket")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntityMetadataPacket {
+    /// Entity ID
+    pub entity_id: VarInt,
+    /// Changed values and their indices
+    pub entries: Vec<(u8, MetadataValue)>,
+}
+
+impl Packet for EntityMetadataPacket {
+    const ID: i32 = 0x5C;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let entity_id = VarInt::read(reader)?;
+        let mut entries = Vec::new();
+        loop {
+            let index = read_unsigned_byte(reader)?;
+            if index == END_OF_METADATA {
+                break;
+            }
+            let type_id = VarInt::read(reader)?.0;
+            entries.push((index, MetadataValue::read(type_id, reader)?));
+        }
+        Ok(EntityMetadataPacket { entity_id, entries })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.entity_id.write(writer)?;
+        for (index, value) in &self.entries {
+            if *index == END_OF_METADATA {
+                return Err(ServerError::Protocol(
+                    "Metadata index 255 is reserved".to_string(),
+                ));
+            }
+            write_unsigned_byte(*index, writer)?;
+            VarInt(value.type_id()).write(writer)?;
+            value.write(writer)?;
+        }
+        write_unsigned_byte(END_OF_METADATA, writer)
+    }
+}
+
+impl ClientboundPacket for EntityMetadataPacket {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_entity_metadata_packet() {
+        let packet = EntityMetadataPacket {
+            entity_id: VarInt(5),
+            entries: vec![(0, MetadataValue::Byte(0x02)), (6, MetadataValue::Pose(5))],
+        };
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        // Crouching flag, then the crouching pose
+        assert_eq!(buffer, [0x05, 0x00, 0x00, 0x02, 0x06, 0x14, 0x05, 0xFF]);
+        assert_eq!(
+            EntityMetadataPacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+    }
+
+    #[test]
+    fn test_metadata_values_roundtrip() {
+        let values = vec![
+            MetadataValue::VarInt(300),
+            MetadataValue::VarLong(-1),
+            MetadataValue::Float(0.5),
+            MetadataValue::String("name".into()),
+            MetadataValue::TextComponent(TextComponent::text("Hello")),
+            MetadataValue::OptTextComponent(None),
+            MetadataValue::OptTextComponent(Some(TextComponent::text("Bob"))),
+            MetadataValue::Slot(Slot::new(1, 3)),
+            MetadataValue::Boolean(true),
+            MetadataValue::Rotations(1.0, 2.0, 3.0),
+            MetadataValue::BlockPos(Position::new(1, -2, 3)),
+            MetadataValue::OptBlockPos(Some(Position::new(0, 64, 0))),
+            MetadataValue::Direction(3),
+            MetadataValue::OptUuid(Some(McUuid::new_v4())),
+            MetadataValue::BlockState(9),
+            MetadataValue::OptBlockState(None),
+            MetadataValue::OptVarInt(Some(0)),
+            MetadataValue::Vector3(0.0, 1.0, 0.0),
+            MetadataValue::Quaternion(0.0, 0.0, 0.0, 1.0),
+        ];
+        let packet = EntityMetadataPacket {
+            entity_id: VarInt(1),
+            entries: values
+                .into_iter()
+                .enumerate()
+                .map(|(i, v)| (i as u8, v))
+                .collect(),
+        };
+
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(
+            EntityMetadataPacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+    }
+
+    #[test]
+    fn test_unsupported_metadata_type() {
+        // Particle value
+        let data = [0x01, 0x00, 0x10, 0x00, 0xFF];
+        assert!(EntityMetadataPacket::read(&mut Cursor::new(data)).is_err());
+    }
+}
@@ -0,0 +1,1413 @@
+//! Play state packets
+//!
+//! Play packets handle the main gameplay functionality.
+//! This is where the bulk of the game packets are defined.
+
+pub mod abilities;
+pub mod animation;
+pub mod attributes;
+pub mod block_entity;
+pub mod boss_bar;
+pub mod chat;
+pub mod chunk_data;
+pub mod command_suggestions;
+pub mod commands;
+pub mod container;
+pub mod cookie;
+pub mod digging;
+pub mod entity;
+pub mod equipment;
+pub mod health;
+pub mod held_item;
+pub mod map;
+pub mod metadata;
+pub mod particle;
+pub mod placement;
+pub mod player_info;
+pub mod respawn;
+pub mod scoreboard;
+pub mod sign;
+pub mod sound;
+pub mod teams;
+pub mod teleport;
+pub mod time;
+pub mod title;
+
+pub use abilities::{PlayerAbilitiesPacket, ServerboundPlayerAbilitiesPacket};
+pub use animation::{AnimatePacket, Animation, SwingArmPacket};
+pub use attributes::{
+    AttributeModifier, AttributeProperty, ModifierOperation, UpdateAttributesPacket,
+};
+pub use block_entity::BlockEntityDataPacket;
+pub use boss_bar::{BossBarAction, BossBarColor, BossBarDivision, BossBarPacket};
+pub use chat::{FilterType, PlayerChatPacket, PreviousMessage};
+pub use chunk_data::ChunkDataPacket;
+pub use command_suggestions::{
+    CommandSuggestionsRequestPacket, CommandSuggestionsResponsePacket, SuggestionMatch,
+};
+pub use commands::{ArgumentParser, CommandNode, DeclareCommandsPacket, StringKind};
+pub use container::{
+    ClickContainerPacket, CloseContainerPacket, GuiType, HashedSlot, OpenScreenPacket,
+    ServerboundCloseContainerPacket, SetContainerContentPacket, SetContainerSlotPacket,
+};
+pub use cookie::StoreCookiePacket;
+pub use digging::{
+    AcknowledgeBlockChangePacket, BlockBreakAnimationPacket, PlayerActionPacket, PlayerActionStatus,
+};
+pub use entity::{
+    EntityEventPacket, EntityLookAndRelativeMovePacket, EntityLookPacket, EntityRelativeMovePacket,
+    SpawnEntityPacket, TeleportEntityPacket, Velocity,
+};
+pub use equipment::{EquipmentSlot, SetEquipmentPacket};
+pub use health::{HurtAnimationPacket, SetHealthPacket};
+pub use held_item::{ServerboundSetHeldItemPacket, SetHeldItemPacket};
+pub use map::{MapDataPacket, MapIcon, MapPatch, create_map};
+pub use metadata::{EntityMetadataPacket, MetadataValue};
+pub use particle::{ParticleEmitter, ParticlePacket, ParticleType};
+pub use placement::{BlockFace, Hand, UseItemOnPacket};
+pub use player_info::{PlayerInfoEntry, PlayerInfoRemovePacket, PlayerInfoUpdatePacket};
+pub use respawn::{ClientCommandAction, ClientCommandPacket, PlayerDeathPacket, RespawnPacket};
+pub use scoreboard::{
+    DisplayObjectivePacket, DisplaySlot, ObjectiveAction, ResetScorePacket,
+    ScoreboardObjectivePacket, ScoreboardRenderType, UpdateScorePacket,
+};
+pub use sign::{OpenSignEditorPacket, SignUpdatePacket};
+pub use sound::{NamedSoundEffectPacket, SoundEffectPacket, SoundSource};
+pub use teams::{CollisionRule, TeamAction, TeamColor, TeamInfo, TeamPacket, Visibility};
+pub use teleport::{ConfirmTeleportPacket, PlayerPositionPacket};
+pub use time::UpdateTimePacket;
+pub use title::{
+    SetActionBarTextPacket, SetSubtitleTextPacket, SetTitleAnimationTimesPacket, SetTitleTextPacket,
+};
+
+use crate::error::{Result, ServerError};
+use crate::protocol::packets::{ClientboundPacket, Packet, ServerboundPacket};
+use crate::protocol::text::TextComponent;
+use crate::protocol::types::{
+    FixedBitSet, JsonTextComponent, McIdentifier, McString, Position, VarInt,
+};
+use std::io::{Read, Write};
+
+/// Keep alive packet (clientbound)
+///
+/// The client must answer with a [`ServerboundKeepAlivePacket`] carrying the
+/// same ID.
+#[derive(Debug, Clone)]
+pub struct KeepAlivePacket {
+    /// Keep alive ID
+    pub keep_alive_id: i64,
+}
+
+impl Packet for KeepAlivePacket {
+    const ID: i32 = 0x26;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let mut bytes = [0u8; 8];
+        reader.read_exact(&mut bytes)?;
+        let keep_alive_id = i64::from_be_bytes(bytes);
+        Ok(KeepAlivePacket { keep_alive_id })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(&self.keep_alive_id.to_be_bytes())?;
+        Ok(())
+    }
+}
+
+impl ClientboundPacket for KeepAlivePacket {}
+
+/// Keep alive packet (serverbound)
+#[derive(Debug, Clone)]
+pub struct ServerboundKeepAlivePacket {
+    /// ID from the keep alive being answered
+    pub keep_alive_id: i64,
+}
+
+impl Packet for ServerboundKeepAlivePacket {
+    const ID: i32 = 0x1B;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(ServerboundKeepAlivePacket {
+            keep_alive_id: crate::protocol::types::read_long(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        crate::protocol::types::write_long(self.keep_alive_id, writer)
+    }
+}
+
+impl ServerboundPacket for ServerboundKeepAlivePacket {}
+
+/// Disconnect packet (clientbound, play state)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayDisconnectPacket {
+    /// Reason shown to the player, sent as NBT
+    pub reason: JsonTextComponent,
+}
+
+impl Packet for PlayDisconnectPacket {
+    const ID: i32 = 0x1C;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let reason = JsonTextComponent::read_nbt(reader)?;
+        Ok(PlayDisconnectPacket { reason })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.reason.write_nbt(writer)
+    }
+}
+
+impl ClientboundPacket for PlayDisconnectPacket {}
+
+/// Chat message packet (serverbound)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChatMessagePacket {
+    /// Message content
+    pub message: McString,
+    /// Timestamp
+    pub timestamp: i64,
+    /// Salt for message signing
+    pub salt: i64,
+    /// Optional signature
+    pub signature: Option<Vec<u8>>,
+    /// Message count
+    pub message_count: VarInt,
+    /// Acknowledged messages
+    pub acknowledged: FixedBitSet,
+    /// Checksum of the acknowledged messages
+    pub checksum: u8,
+}
+
+impl ChatMessagePacket {
+    /// Maximum length of a chat message
+    pub const MAX_MESSAGE_LENGTH: usize = 256;
+    /// Length of a message signature
+    pub const SIGNATURE_LENGTH: usize = 256;
+    /// Number of previous messages covered by the acknowledgements
+    pub const ACKNOWLEDGED_LENGTH: usize = 20;
+}
+
+impl Packet for ChatMessagePacket {
+    const ID: i32 = 0x08;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let message = McString::read_with_max_length(reader, Self::MAX_MESSAGE_LENGTH)?;
+        let timestamp = crate::protocol::types::read_long(reader)?;
+        let salt = crate::protocol::types::read_long(reader)?;
+
+        // Signatures have a fixed length and no length prefix
+        let has_signature = crate::protocol::types::read_bool(reader)?;
+        let signature = if has_signature {
+            let mut signature = vec![0u8; Self::SIGNATURE_LENGTH];
+            reader.read_exact(&mut signature)?;
+            Some(signature)
+        } else {
+            None
+        };
+
+        let message_count = VarInt::read(reader)?;
+        let acknowledged = FixedBitSet::read(reader, Self::ACKNOWLEDGED_LENGTH)?;
+        let checksum = crate::protocol::types::read_unsigned_byte(reader)?;
+
+        Ok(ChatMessagePacket {
+            message,
+            timestamp,
+            salt,
+            signature,
+            message_count,
+            acknowledged,
+            checksum,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.message.write(writer)?;
+        crate::protocol::types::write_long(self.timestamp, writer)?;
+        crate::protocol::types::write_long(self.salt, writer)?;
+
+        crate::protocol::types::write_bool(self.signature.is_some(), writer)?;
+        if let Some(ref signature) = self.signature {
+            if signature.len() != Self::SIGNATURE_LENGTH {
+                return Err(ServerError::Protocol(format!(
+                    "Chat signature must be {} bytes, got {}",
+                    Self::SIGNATURE_LENGTH,
+                    signature.len()
+                )));
+            }
+            writer.write_all(signature)?;
+        }
+
+        self.message_count.write(writer)?;
+        self.acknowledged.write(writer)?;
+        crate::protocol::types::write_unsigned_byte(self.checksum, writer)?;
+
+        Ok(())
+    }
+}
+
+impl ServerboundPacket for ChatMessagePacket {}
+
+/// Chat Command packet (serverbound)
+///
+/// Sent for unsigned commands, which is every command while the server does
+/// not enforce secure chat.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatCommandPacket {
+    /// Command line without the leading `/`
+    pub command: McString,
+}
+
+impl Packet for ChatCommandPacket {
+    const ID: i32 = 0x06;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(ChatCommandPacket {
+            command: McString::read(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.command.write(writer)
+    }
+}
+
+impl ServerboundPacket for ChatCommandPacket {}
+
+/// System Chat Message packet (clientbound)
+///
+/// Shows an unsigned message in the chat, or above the hotbar if `overlay`
+/// is set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemChatMessagePacket {
+    /// Message content
+    pub content: TextComponent,
+    /// Whether to show the message above the hotbar instead of in the chat
+    pub overlay: bool,
+}
+
+impl Packet for SystemChatMessagePacket {
+    const ID: i32 = 0x72;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let content = TextComponent::read_nbt(reader)?;
+        let overlay = crate::protocol::types::read_bool(reader)?;
+        Ok(SystemChatMessagePacket { content, overlay })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.content.write_nbt(writer)?;
+        crate::protocol::types::write_bool(self.overlay, writer)?;
+        Ok(())
+    }
+}
+
+impl ClientboundPacket for SystemChatMessagePacket {}
+
+/// Set Player Position packet (serverbound)
+#[derive(Debug, Clone)]
+pub struct SetPlayerPositionPacket {
+    /// X coordinate
+    pub x: f64,
+    /// Y coordinate (feet)
+    pub y: f64,
+    /// Z coordinate
+    pub z: f64,
+    /// Whether the player is on ground
+    pub on_ground: bool,
+    /// Whether the player is pushing against a wall
+    pub pushing_against_wall: bool,
+}
+
+impl SetPlayerPositionPacket {
+    /// Flag bit set when the player is on ground
+    const ON_GROUND: u8 = 0x01;
+    /// Flag bit set when the player is pushing against a wall
+    const PUSHING_AGAINST_WALL: u8 = 0x02;
+}
+
+impl Packet for SetPlayerPositionPacket {
+    const ID: i32 = 0x1D;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let mut x_bytes = [0u8; 8];
+        reader.read_exact(&mut x_bytes)?;
+        let x = f64::from_be_bytes(x_bytes);
+
+        let mut y_bytes = [0u8; 8];
+        reader.read_exact(&mut y_bytes)?;
+        let y = f64::from_be_bytes(y_bytes);
+
+        let mut z_bytes = [0u8; 8];
+        reader.read_exact(&mut z_bytes)?;
+        let z = f64::from_be_bytes(z_bytes);
+
+        let flags = crate::protocol::types::read_unsigned_byte(reader)?;
+
+        Ok(SetPlayerPositionPacket {
+            x,
+            y,
+            z,
+            on_ground: flags & Self::ON_GROUND != 0,
+            pushing_against_wall: flags & Self::PUSHING_AGAINST_WALL != 0,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(&self.x.to_be_bytes())?;
+        writer.write_all(&self.y.to_be_bytes())?;
+        writer.write_all(&self.z.to_be_bytes())?;
+
+        let mut flags = 0;
+        if self.on_ground {
+            flags |= Self::ON_GROUND;
+        }
+        if self.pushing_against_wall {
+            flags |= Self::PUSHING_AGAINST_WALL;
+        }
+        crate::protocol::types::write_unsigned_byte(flags, writer)?;
+        Ok(())
+    }
+}
+
+impl ServerboundPacket for SetPlayerPositionPacket {}
+
+/// Set Player Position and Rotation packet (serverbound)
+#[doc(alias = "MovePlayerPosRotPacket")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetPlayerPositionAndRotationPacket {
+    /// X coordinate
+    pub x: f64,
+    /// Y coordinate (feet)
+    pub y: f64,
+    /// Z coordinate
+    pub z: f64,
+    /// Yaw in degrees
+    pub yaw: f32,
+    /// Pitch in degrees
+    pub pitch: f32,
+    /// Whether the player is on ground
+    pub on_ground: bool,
+    /// Whether the player is pushing against a wall
+    pub pushing_against_wall: bool,
+}
+
+impl Packet for SetPlayerPositionAndRotationPacket {
+    const ID: i32 = 0x1E;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let x = crate::protocol::types::read_double(reader)?;
+        let y = crate::protocol::types::read_double(reader)?;
+        let z = crate::protocol::types::read_double(reader)?;
+        let yaw = crate::protocol::types::read_float(reader)?;
+        let pitch = crate::protocol::types::read_float(reader)?;
+        let flags = crate::protocol::types::read_unsigned_byte(reader)?;
+
+        Ok(SetPlayerPositionAndRotationPacket {
+            x,
+            y,
+            z,
+            yaw,
+            pitch,
+            on_ground: flags & SetPlayerPositionPacket::ON_GROUND != 0,
+            pushing_against_wall: flags & SetPlayerPositionPacket::PUSHING_AGAINST_WALL != 0,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        crate::protocol::types::write_double(self.x, writer)?;
+        crate::protocol::types::write_double(self.y, writer)?;
+        crate::protocol::types::write_double(self.z, writer)?;
+        crate::protocol::types::write_float(self.yaw, writer)?;
+        crate::protocol::types::write_float(self.pitch, writer)?;
+
+        let mut flags = 0;
+        if self.on_ground {
+            flags |= SetPlayerPositionPacket::ON_GROUND;
+        }
+        if self.pushing_against_wall {
+            flags |= SetPlayerPositionPacket::PUSHING_AGAINST_WALL;
+        }
+        crate::protocol::types::write_unsigned_byte(flags, writer)
+    }
+}
+
+impl ServerboundPacket for SetPlayerPositionAndRotationPacket {}
+
+/// Block Update packet (clientbound)
+#[doc(alias = "SetBlockPacket")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockChangePacket {
+    /// Block position
+    pub position: Position,
+    /// New block state ID
+    pub block_id: VarInt,
+}
+
+impl Packet for BlockChangePacket {
+    const ID: i32 = 0x08;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let position = Position::read(reader)?;
+        let block_id = VarInt::read(reader)?;
+        Ok(BlockChangePacket { position, block_id })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.position.write(writer)?;
+        self.block_id.write(writer)?;
+        Ok(())
+    }
+}
+
+impl ClientboundPacket for BlockChangePacket {}
+
+/// A block changed by a [`MultiBlockChangePacket`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SectionBlockChange {
+    /// X coordinate within the section (0-15)
+    pub local_x: u8,
+    /// Y coordinate within the section (0-15)
+    pub local_y: u8,
+    /// Z coordinate within the section (0-15)
+    pub local_z: u8,
+    /// New block state ID
+    pub block_state: i32,
+}
+
+impl SectionBlockChange {
+    /// Pack the change into a long, with the block state above the local
+    /// coordinates
+    pub fn pack(&self) -> i64 {
+        (i64::from(self.block_state) << 12)
+            | (i64::from(self.local_x & 0xF) << 8)
+            | (i64::from(self.local_z & 0xF) << 4)
+            | i64::from(self.local_y & 0xF)
+    }
+
+    /// Unpack a change from a long
+    pub fn unpack(packed: i64) -> Self {
+        Self {
+            local_x: ((packed >> 8) & 0xF) as u8,
+            local_y: (packed & 0xF) as u8,
+            local_z: ((packed >> 4) & 0xF) as u8,
+            block_state: (packed >> 12) as i32,
+        }
+    }
+}
+
+/// Update Section Blocks packet (clientbound)
+///
+/// Changes any number of blocks within one 16x16x16 chunk section.
+#[doc(alias = "SectionBlocksUpdatePacket")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiBlockChangePacket {
+    /// Section X coordinate (block X / 16)
+    pub section_x: i32,
+    /// Section Y coordinate (block Y / 16)
+    pub section_y: i32,
+    /// Section Z coordinate (block Z / 16)
+    pub section_z: i32,
+    /// Changed blocks
+    pub blocks: Vec<SectionBlockChange>,
+}
+
+impl MultiBlockChangePacket {
+    /// Pack the section coordinates into a long: 22 bits of X, 22 bits of Z
+    /// and 20 bits of Y
+    pub fn pack_section_position(&self) -> i64 {
+        ((i64::from(self.section_x) & 0x3F_FFFF) << 42)
+            | ((i64::from(self.section_z) & 0x3F_FFFF) << 20)
+            | (i64::from(self.section_y) & 0xF_FFFF)
+    }
+}
+
+impl Packet for MultiBlockChangePacket {
+    const ID: i32 = 0x4D;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let packed = crate::protocol::types::read_long(reader)?;
+        let count = VarInt::read(reader)?.0;
+        let mut blocks = Vec::new();
+        for _ in 0..count {
+            let change = crate::protocol::types::read_var_long(reader)?;
+            blocks.push(SectionBlockChange::unpack(change));
+        }
+
+        // Shift each field to the top of the long to sign-extend it
+        Ok(MultiBlockChangePacket {
+            section_x: (packed >> 42) as i32,
+            section_y: ((packed << 44) >> 44) as i32,
+            section_z: ((packed << 22) >> 42) as i32,
+            blocks,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        crate::protocol::types::write_long(self.pack_section_position(), writer)?;
+        VarInt(self.blocks.len() as i32).write(writer)?;
+        for block in &self.blocks {
+            crate::protocol::types::write_var_long(block.pack(), writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl ClientboundPacket for MultiBlockChangePacket {}
+
+/// Login (play) packet (clientbound)
+///
+/// This is the first packet sent when transitioning from configuration to play state.
+/// It contains essential world and gameplay configuration that the client needs
+/// to properly initialize its game state.
+///
+/// Packet ID: 0x2B
+#[doc(alias = "JoinGamePacket")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoginPlayPacket {
+    /// The player's Entity ID (EID)
+    pub entity_id: i32,
+    /// Whether hardcore mode is enabled
+    pub is_hardcore: bool,
+    /// Identifiers for all dimensions on the server
+    pub dimension_names: Vec<McIdentifier>,
+    /// Maximum players (legacy field, now ignored by client)
+    pub max_players: VarInt,
+    /// Render distance in chunks (2-32)
+    pub view_distance: VarInt,
+    /// Simulation distance in chunks (distance for entity processing)
+    pub simulation_distance: VarInt,
+    /// Whether to show reduced debug information in F3 screen
+    pub reduced_debug_info: bool,
+    /// Whether to show the respawn screen when the player dies
+    pub enable_respawn_screen: bool,
+    /// Whether crafting is limited (used for recipe book functionality)
+    pub do_limited_crafting: bool,
+    /// The ID of the dimension type in the minecraft:dimension_type registry
+    pub dimension_type: VarInt,
+    /// Name of the dimension being spawned into
+    pub dimension_name: McIdentifier,
+    /// First 8 bytes of SHA-256 hash of world seed (for client-side biome noise)
+    pub hashed_seed: i64,
+    /// Current game mode (0=Survival, 1=Creative, 2=Adventure, 3=Spectator)
+    pub game_mode: u8,
+    /// Previous game mode (-1=Undefined, 0=Survival, 1=Creative, 2=Adventure, 3=Spectator)
+    pub previous_game_mode: i8,
+    /// Whether this is a debug world
+    pub is_debug: bool,
+    /// Whether this is a flat/superflat world
+    pub is_flat: bool,
+    /// Whether the player has a death location
+    pub has_death_location: bool,
+    /// Death dimension name (if has_death_location is true)
+    pub death_dimension_name: Option<McIdentifier>,
+    /// Death location (if has_death_location is true)
+    pub death_location: Option<Position>,
+    /// Portal cooldown in ticks
+    pub portal_cooldown: VarInt,
+    /// Sea level
+    pub sea_level: VarInt,
+    /// Whether the server enforces secure chat (cryptographically signed messages)
+    pub enforces_secure_chat: bool,
+}
+
+impl Packet for LoginPlayPacket {
+    const ID: i32 = 0x2B;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let mut entity_id_bytes = [0u8; 4];
+        reader.read_exact(&mut entity_id_bytes)?;
+        let entity_id = i32::from_be_bytes(entity_id_bytes);
+
+        let is_hardcore = crate::protocol::types::read_bool(reader)?;
+
+        let dimension_count = VarInt::read(reader)?;
+        let mut dimension_names = Vec::new();
+        for _ in 0..dimension_count.0 {
+            dimension_names.push(McIdentifier::read(reader)?);
+        }
+
+        let max_players = VarInt::read(reader)?;
+        let view_distance = VarInt::read(reader)?;
+        let simulation_distance = VarInt::read(reader)?;
+        let reduced_debug_info = crate::protocol::types::read_bool(reader)?;
+        let enable_respawn_screen = crate::protocol::types::read_bool(reader)?;
+        let do_limited_crafting = crate::protocol::types::read_bool(reader)?;
+        let dimension_type = VarInt::read(reader)?;
+        let dimension_name = McIdentifier::read(reader)?;
+
+        let mut hashed_seed_bytes = [0u8; 8];
+        reader.read_exact(&mut hashed_seed_bytes)?;
+        let hashed_seed = i64::from_be_bytes(hashed_seed_bytes);
+
+        let mut game_mode_byte = [0u8; 1];
+        reader.read_exact(&mut game_mode_byte)?;
+        let game_mode = game_mode_byte[0];
+
+        let mut previous_game_mode_byte = [0u8; 1];
+        reader.read_exact(&mut previous_game_mode_byte)?;
+        let previous_game_mode = previous_game_mode_byte[0] as i8;
+
+        let is_debug = crate::protocol::types::read_bool(reader)?;
+        let is_flat = crate::protocol::types::read_bool(reader)?;
+        let has_death_location = crate::protocol::types::read_bool(reader)?;
+
+        let (death_dimension_name, death_location) = if has_death_location {
+            let dimension = McIdentifier::read(reader)?;
+            let position = Position::read(reader)?;
+            (Some(dimension), Some(position))
+        } else {
+            (None, None)
+        };
+
+        let portal_cooldown = VarInt::read(reader)?;
+        let sea_level = VarInt::read(reader)?;
+        let enforces_secure_chat = crate::protocol::types::read_bool(reader)?;
+
+        Ok(LoginPlayPacket {
+            entity_id,
+            is_hardcore,
+            dimension_names,
+            max_players,
+            view_distance,
+            simulation_distance,
+            reduced_debug_info,
+            enable_respawn_screen,
+            do_limited_crafting,
+            dimension_type,
+            dimension_name,
+            hashed_seed,
+            game_mode,
+            previous_game_mode,
+            is_debug,
+            is_flat,
+            has_death_location,
+            death_dimension_name,
+            death_location,
+            portal_cooldown,
+            sea_level,
+            enforces_secure_chat,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(&self.entity_id.to_be_bytes())?;
+        crate::protocol::types::write_bool(self.is_hardcore, writer)?;
+
+        VarInt(self.dimension_names.len() as i32).write(writer)?;
+        for dimension in &self.dimension_names {
+            dimension.write(writer)?;
+        }
+
+        self.max_players.write(writer)?;
+        self.view_distance.write(writer)?;
+        self.simulation_distance.write(writer)?;
+        crate::protocol::types::write_bool(self.reduced_debug_info, writer)?;
+        crate::protocol::types::write_bool(self.enable_respawn_screen, writer)?;
+        crate::protocol::types::write_bool(self.do_limited_crafting, writer)?;
+        self.dimension_type.write(writer)?;
+        self.dimension_name.write(writer)?;
+        writer.write_all(&self.hashed_seed.to_be_bytes())?;
+        writer.write_all(&[self.game_mode])?;
+        writer.write_all(&[self.previous_game_mode as u8])?;
+        crate::protocol::types::write_bool(self.is_debug, writer)?;
+        crate::protocol::types::write_bool(self.is_flat, writer)?;
+        crate::protocol::types::write_bool(self.has_death_location, writer)?;
+
+        if self.has_death_location {
+            let (Some(dimension), Some(position)) =
+                (&self.death_dimension_name, &self.death_location)
+            else {
+                return Err(ServerError::Protocol(
+                    "Death location flag set without a death location".to_string(),
+                ));
+            };
+            dimension.write(writer)?;
+            position.write(writer)?;
+        }
+
+        self.portal_cooldown.write(writer)?;
+        self.sea_level.write(writer)?;
+        crate::protocol::types::write_bool(self.enforces_secure_chat, writer)?;
+
+        Ok(())
+    }
+}
+
+impl ClientboundPacket for LoginPlayPacket {}
+
+impl LoginPlayPacket {
+    /// Create a new login play packet with default values
+    pub fn new() -> Self {
+        Self {
+            entity_id: 1,
+            is_hardcore: false,
+            dimension_names: vec![McIdentifier::minecraft("overworld")],
+            max_players: VarInt(20),
+            view_distance: VarInt(10),
+            simulation_distance: VarInt(10),
+            reduced_debug_info: false,
+            enable_respawn_screen: true,
+            do_limited_crafting: false,
+            dimension_type: VarInt(0),
+            dimension_name: McIdentifier::minecraft("overworld"),
+            hashed_seed: 0,
+            game_mode: 0,           // Survival
+            previous_game_mode: -1, // None
+            is_debug: false,
+            is_flat: false,
+            has_death_location: false,
+            death_dimension_name: None,
+            death_location: None,
+            portal_cooldown: VarInt(0),
+            sea_level: VarInt(63),
+            enforces_secure_chat: false,
+        }
+    }
+
+    /// Create login play packet from server config
+    pub fn from_server_config(config: &crate::config::ServerConfig, entity_id: i32) -> Self {
+        Self {
+            entity_id,
+            is_hardcore: false,
+            dimension_names: vec![McIdentifier::minecraft("overworld")],
+            max_players: VarInt(config.max_players as i32),
+            view_distance: VarInt(config.view_distance as i32),
+            simulation_distance: VarInt(config.simulation_distance as i32),
+            reduced_debug_info: false,
+            enable_respawn_screen: true,
+            do_limited_crafting: false,
+            dimension_type: VarInt(0),
+            dimension_name: McIdentifier::minecraft("overworld"),
+            hashed_seed: 12345,     // Use world seed hash
+            game_mode: 0,           // Survival mode
+            previous_game_mode: -1, // No previous game mode
+            is_debug: false,
+            is_flat: false,
+            has_death_location: false,
+            death_dimension_name: None,
+            death_location: None,
+            portal_cooldown: VarInt(0),
+            sea_level: VarInt(63),
+            enforces_secure_chat: false,
+        }
+    }
+}
+
+impl Default for LoginPlayPacket {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Game Event packet (clientbound)
+///
+/// Notifies the client of a change in game state, such as weather, game mode
+/// or that chunks are about to be sent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GameEventPacket {
+    /// Event type
+    pub event: u8,
+    /// Event-specific value
+    pub value: f32,
+}
+
+impl GameEventPacket {
+    /// Event starting rain
+    pub const BEGIN_RAINING: u8 = 1;
+
+    /// Event stopping rain
+    pub const END_RAINING: u8 = 2;
+
+    /// Event switching the player to the game mode with the value's ID
+    pub const CHANGE_GAME_MODE: u8 = 3;
+
+    /// Event setting the strength of the rain, from 0 to 1
+    pub const RAIN_LEVEL_CHANGE: u8 = 7;
+
+    /// Event setting the strength of the thunder, from 0 to 1
+    pub const THUNDER_LEVEL_CHANGE: u8 = 8;
+
+    /// Event turning the death screen off (value 1) or on (value 0), for the
+    /// `doImmediateRespawn` game rule
+    pub const ENABLE_RESPAWN_SCREEN: u8 = 11;
+
+    /// Event turning limited crafting on (value 1) or off (value 0), for the
+    /// `doLimitedCrafting` game rule
+    pub const LIMITED_CRAFTING: u8 = 12;
+
+    /// Event telling the client to wait for level chunks before leaving the
+    /// loading screen
+    pub const START_WAITING_FOR_CHUNKS: u8 = 13;
+
+    /// Create a game event packet
+    pub fn new(event: u8, value: f32) -> Self {
+        Self { event, value }
+    }
+
+    /// Create the event that tells the client chunks are about to be sent
+    pub fn start_waiting_for_chunks() -> Self {
+        Self::new(Self::START_WAITING_FOR_CHUNKS, 0.0)
+    }
+}
+
+impl Packet for GameEventPacket {
+    const ID: i32 = 0x22;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let event = crate::protocol::types::read_unsigned_byte(reader)?;
+        let mut value_bytes = [0u8; 4];
+        reader.read_exact(&mut value_bytes)?;
+        Ok(GameEventPacket {
+            event,
+            value: f32::from_be_bytes(value_bytes),
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        crate::protocol::types::write_unsigned_byte(self.event, writer)?;
+        writer.write_all(&self.value.to_be_bytes())?;
+        Ok(())
+    }
+}
+
+impl ClientboundPacket for GameEventPacket {}
+
+/// Set Center Chunk packet (clientbound)
+///
+/// Sets the chunk the client's loaded area is centered on. Chunks outside
+/// the view distance around it are ignored by the client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetCenterChunkPacket {
+    /// Chunk X coordinate
+    pub chunk_x: VarInt,
+    /// Chunk Z coordinate
+    pub chunk_z: VarInt,
+}
+
+impl Packet for SetCenterChunkPacket {
+    const ID: i32 = 0x57;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(SetCenterChunkPacket {
+            chunk_x: VarInt::read(reader)?,
+            chunk_z: VarInt::read(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.chunk_x.write(writer)?;
+        self.chunk_z.write(writer)?;
+        Ok(())
+    }
+}
+
+impl ClientboundPacket for SetCenterChunkPacket {}
+
+/// Unload Chunk packet (clientbound)
+///
+/// Tells the client to forget a chunk that left its view distance.
+#[doc(alias = "ForgetLevelChunkPacket")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnloadChunkPacket {
+    /// Chunk X coordinate
+    pub chunk_x: i32,
+    /// Chunk Z coordinate
+    pub chunk_z: i32,
+}
+
+impl Packet for UnloadChunkPacket {
+    const ID: i32 = 0x21;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        // Z comes first, as the coordinates are sent as one packed long
+        let chunk_z = crate::protocol::types::read_int(reader)?;
+        let chunk_x = crate::protocol::types::read_int(reader)?;
+        Ok(UnloadChunkPacket { chunk_x, chunk_z })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        crate::protocol::types::write_int(self.chunk_z, writer)?;
+        crate::protocol::types::write_int(self.chunk_x, writer)
+    }
+}
+
+impl ClientboundPacket for UnloadChunkPacket {}
+
+/// Client Information packet (serverbound)
+///
+/// Sent in the configuration state after login, and again in the play state
+/// whenever the player changes their settings.
+#[doc(alias = "ClientInformationPacket")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientSettingsPacket {
+    /// Client language (e.g. "en_us")
+    pub locale: McString,
+    /// Client render distance in chunks
+    pub view_distance: i8,
+    /// Chat mode (0=Enabled, 1=Commands only, 2=Hidden)
+    pub chat_mode: VarInt,
+    /// Whether chat colors are shown
+    pub chat_colors: bool,
+    /// Bit mask of the skin parts to display
+    pub displayed_skin_parts: u8,
+    /// Main hand (0=Left, 1=Right)
+    pub main_hand: VarInt,
+    /// Whether chat messages are filtered
+    pub enable_text_filtering: bool,
+    /// Whether the player may appear in the server list sample
+    pub allow_server_listings: bool,
+    /// Particles to show (0=All, 1=Decreased, 2=Minimal)
+    pub particle_status: VarInt,
+}
+
+impl ClientSettingsPacket {
+    /// Maximum length of the locale string
+    const MAX_LOCALE_LENGTH: usize = 16;
+}
+
+impl Packet for ClientSettingsPacket {
+    const ID: i32 = 0x0D;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(ClientSettingsPacket {
+            locale: McString::read_with_max_length(reader, Self::MAX_LOCALE_LENGTH)?,
+            view_distance: crate::protocol::types::read_unsigned_byte(reader)? as i8,
+            chat_mode: VarInt::read(reader)?,
+            chat_colors: crate::protocol::types::read_bool(reader)?,
+            displayed_skin_parts: crate::protocol::types::read_unsigned_byte(reader)?,
+            main_hand: VarInt::read(reader)?,
+            enable_text_filtering: crate::protocol::types::read_bool(reader)?,
+            allow_server_listings: crate::protocol::types::read_bool(reader)?,
+            particle_status: VarInt::read(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.locale.write(writer)?;
+        crate::protocol::types::write_unsigned_byte(self.view_distance as u8, writer)?;
+        self.chat_mode.write(writer)?;
+        crate::protocol::types::write_bool(self.chat_colors, writer)?;
+        crate::protocol::types::write_unsigned_byte(self.displayed_skin_parts, writer)?;
+        self.main_hand.write(writer)?;
+        crate::protocol::types::write_bool(self.enable_text_filtering, writer)?;
+        crate::protocol::types::write_bool(self.allow_server_listings, writer)?;
+        self.particle_status.write(writer)?;
+        Ok(())
+    }
+}
+
+impl ServerboundPacket for ClientSettingsPacket {}
+
+/// Plugin Message packet (clientbound)
+///
+/// Carries data on a plugin channel, such as `minecraft:brand`.
+#[doc(alias = "PluginMessagePacket")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomPayloadPacket {
+    /// Channel identifier
+    pub channel: McIdentifier,
+    /// Channel-specific data, filling the rest of the packet
+    pub data: Vec<u8>,
+}
+
+impl CustomPayloadPacket {
+    /// Maximum data length of a clientbound plugin message
+    pub const MAX_DATA_LENGTH: usize = 1_048_576;
+}
+
+impl Packet for CustomPayloadPacket {
+    const ID: i32 = 0x18;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let channel = McIdentifier::read(reader)?;
+        let data = read_payload_data(reader, Self::MAX_DATA_LENGTH)?;
+        Ok(CustomPayloadPacket { channel, data })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.channel.write(writer)?;
+        writer.write_all(&self.data)?;
+        Ok(())
+    }
+}
+
+impl ClientboundPacket for CustomPayloadPacket {}
+
+/// Plugin Message packet (serverbound)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerboundCustomPayloadPacket {
+    /// Channel identifier
+    pub channel: McIdentifier,
+    /// Channel-specific data, filling the rest of the packet
+    pub data: Vec<u8>,
+}
+
+impl ServerboundCustomPayloadPacket {
+    /// Maximum data length of a serverbound plugin message
+    pub const MAX_DATA_LENGTH: usize = 32767;
+}
+
+impl Packet for ServerboundCustomPayloadPacket {
+    const ID: i32 = 0x15;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let channel = McIdentifier::read(reader)?;
+        let data = read_payload_data(reader, Self::MAX_DATA_LENGTH)?;
+        Ok(ServerboundCustomPayloadPacket { channel, data })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.channel.write(writer)?;
+        writer.write_all(&self.data)?;
+        Ok(())
+    }
+}
+
+impl ServerboundPacket for ServerboundCustomPayloadPacket {}
+
+/// Read the rest of a plugin message, which has no length prefix
+fn read_payload_data<R: Read>(reader: &mut R, max_length: usize) -> Result<Vec<u8>> {
+    let mut data = Vec::new();
+    reader.take(max_length as u64 + 1).read_to_end(&mut data)?;
+    if data.len() > max_length {
+        return Err(ServerError::Protocol(format!(
+            "Plugin message too long: more than {} bytes",
+            max_length
+        )));
+    }
+    Ok(data)
+}
+
+// TODO: Add more play packets as needed
+// - Entity packets
+// - Inventory packets
+// - etc.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_set_player_position_and_rotation_packet() {
+        let packet = SetPlayerPositionAndRotationPacket {
+            x: 1.5,
+            y: 64.0,
+            z: -3.25,
+            yaw: 90.0,
+            pitch: -10.0,
+            on_ground: true,
+            pushing_against_wall: false,
+        };
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(buffer.len(), 33);
+        assert_eq!(buffer[32], 0x01);
+        assert_eq!(
+            SetPlayerPositionAndRotationPacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+    }
+
+    #[test]
+    fn test_block_change_packet() {
+        let packet = BlockChangePacket {
+            position: Position::new(1, 64, -1),
+            block_id: VarInt(1),
+        };
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(buffer.len(), 9);
+        assert_eq!(
+            BlockChangePacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+    }
+
+    #[test]
+    fn test_unload_chunk_packet() {
+        let packet = UnloadChunkPacket {
+            chunk_x: 1,
+            chunk_z: -1,
+        };
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(buffer, [0xFF, 0xFF, 0xFF, 0xFF, 0x00, 0x00, 0x00, 0x01]);
+        assert_eq!(
+            UnloadChunkPacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+    }
+
+    #[test]
+    fn test_multi_block_change_packet() {
+        let packet = MultiBlockChangePacket {
+            section_x: -1,
+            section_y: -4,
+            section_z: 2,
+            blocks: vec![
+                SectionBlockChange {
+                    local_x: 15,
+                    local_y: 0,
+                    local_z: 3,
+                    block_state: 1,
+                },
+                SectionBlockChange {
+                    local_x: 0,
+                    local_y: 7,
+                    local_z: 0,
+                    block_state: 27_000,
+                },
+            ],
+        };
+        assert_eq!(packet.blocks[0].pack(), 0x1F30);
+
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(
+            &buffer[..8],
+            (0xFFFF_FC00_002F_FFFCu64 as i64).to_be_bytes()
+        );
+        assert_eq!(
+            MultiBlockChangePacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+    }
+
+    #[test]
+    fn test_chat_message_packet() {
+        let mut acknowledged = FixedBitSet::new(ChatMessagePacket::ACKNOWLEDGED_LENGTH);
+        acknowledged.set(3, true);
+        let packet = ChatMessagePacket {
+            message: "hello".into(),
+            timestamp: 1_700_000_000_000,
+            salt: -7,
+            signature: Some(vec![0xAB; ChatMessagePacket::SIGNATURE_LENGTH]),
+            message_count: VarInt(1),
+            acknowledged,
+            checksum: 0x2A,
+        };
+
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        // Message, timestamp, salt, signature, count, acknowledgements, checksum
+        assert_eq!(buffer.len(), 6 + 8 + 8 + 1 + 256 + 1 + 3 + 1);
+
+        // Trailing data would mean the reader lost its place
+        let mut cursor = Cursor::new(buffer);
+        let decoded = ChatMessagePacket::read(&mut cursor).unwrap();
+        assert_eq!(cursor.position() as usize, cursor.get_ref().len());
+        assert_eq!(decoded, packet);
+    }
+
+    #[test]
+    fn test_system_chat_message_packet() {
+        let packet = SystemChatMessagePacket {
+            content: TextComponent::text("Server restarting"),
+            overlay: true,
+        };
+
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(buffer[0], 0x0A);
+        assert_eq!(buffer.last(), Some(&0x01));
+
+        let decoded = SystemChatMessagePacket::read(&mut Cursor::new(buffer)).unwrap();
+        assert_eq!(decoded, packet);
+    }
+
+    #[test]
+    fn test_custom_payload_packets() {
+        let packet = CustomPayloadPacket {
+            channel: McIdentifier::minecraft("brand"),
+            data: vec![0x08, b'o', b'b', b's', b'i', b'd', b'i', b'u', b'm'],
+        };
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(&buffer[1..16], b"minecraft:brand");
+        assert_eq!(
+            CustomPayloadPacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+
+        let mut data = vec![0x05];
+        data.extend_from_slice(b"a:b/c");
+        data.resize(
+            data.len() + ServerboundCustomPayloadPacket::MAX_DATA_LENGTH,
+            0,
+        );
+        let packet = ServerboundCustomPayloadPacket::read(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(packet.channel.path(), "b/c");
+
+        // One byte over the limit
+        data.push(0);
+        assert!(ServerboundCustomPayloadPacket::read(&mut Cursor::new(data)).is_err());
+    }
+
+    #[test]
+    fn test_client_settings_packet() {
+        let data = [
+            0x05, b'e', b'n', b'_', b'u', b's', // Locale
+            0x0C, // View distance
+            0x00, // Chat mode
+            0x01, // Chat colors
+            0x7F, // Skin parts
+            0x01, // Main hand
+            0x00, // Text filtering
+            0x01, // Server listings
+            0x00, // Particle status
+        ];
+
+        let packet = ClientSettingsPacket::read(&mut Cursor::new(data)).unwrap();
+        assert_eq!(packet.locale.0, "en_us");
+        assert_eq!(packet.view_distance, 12);
+        assert_eq!(packet.main_hand, VarInt(1));
+        assert!(packet.allow_server_listings);
+
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(buffer, data);
+    }
+
+    #[test]
+    fn test_login_play_packet_roundtrip() {
+        let packet = LoginPlayPacket::new();
+
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+
+        let mut cursor = Cursor::new(buffer);
+        let decoded = LoginPlayPacket::read(&mut cursor).unwrap();
+
+        assert_eq!(packet.entity_id, decoded.entity_id);
+        assert_eq!(packet.view_distance.0, decoded.view_distance.0);
+        assert_eq!(packet.simulation_distance.0, decoded.simulation_distance.0);
+        assert_eq!(packet.reduced_debug_info, decoded.reduced_debug_info);
+        assert_eq!(packet.enable_respawn_screen, decoded.enable_respawn_screen);
+        assert_eq!(packet.do_limited_crafting, decoded.do_limited_crafting);
+        assert_eq!(packet.dimension_name, decoded.dimension_name);
+        assert_eq!(packet.hashed_seed, decoded.hashed_seed);
+        assert_eq!(packet.game_mode, decoded.game_mode);
+        assert_eq!(packet.previous_game_mode, decoded.previous_game_mode);
+        assert_eq!(packet.is_debug, decoded.is_debug);
+        assert_eq!(packet.is_flat, decoded.is_flat);
+        assert_eq!(packet.has_death_location, decoded.has_death_location);
+        assert_eq!(packet.portal_cooldown.0, decoded.portal_cooldown.0);
+        assert_eq!(packet.enforces_secure_chat, decoded.enforces_secure_chat);
+    }
+
+    #[test]
+    fn test_login_play_packet_full_roundtrip() {
+        let packet = LoginPlayPacket {
+            entity_id: 7,
+            is_hardcore: true,
+            dimension_names: vec![
+                McIdentifier::minecraft("overworld"),
+                McIdentifier::minecraft("the_end"),
+            ],
+            max_players: VarInt(100),
+            view_distance: VarInt(12),
+            simulation_distance: VarInt(8),
+            reduced_debug_info: true,
+            enable_respawn_screen: false,
+            do_limited_crafting: true,
+            dimension_type: VarInt(2),
+            dimension_name: McIdentifier::minecraft("the_end"),
+            hashed_seed: -42,
+            game_mode: 1,
+            previous_game_mode: 3,
+            is_debug: true,
+            is_flat: true,
+            has_death_location: true,
+            death_dimension_name: Some(McIdentifier::minecraft("overworld")),
+            death_location: Some(Position::new(-5, -60, 9)),
+            portal_cooldown: VarInt(300),
+            sea_level: VarInt(-63),
+            enforces_secure_chat: true,
+        };
+
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        let decoded = LoginPlayPacket::read(&mut Cursor::new(buffer)).unwrap();
+        assert_eq!(decoded, packet);
+    }
+
+    #[test]
+    fn test_login_play_packet_missing_death_location() {
+        let mut packet = LoginPlayPacket::new();
+        packet.has_death_location = true;
+        assert!(packet.write(&mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_play_disconnect_packet_uses_nbt() {
+        let packet = PlayDisconnectPacket {
+            reason: JsonTextComponent::text("Bye"),
+        };
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+
+        // A compound tag with a single "text" string
+        assert_eq!(buffer[0], 0x0A);
+        assert_eq!(&buffer[1..4], &[0x08, 0x00, 0x04]);
+
+        let decoded = PlayDisconnectPacket::read(&mut Cursor::new(buffer)).unwrap();
+        assert_eq!(decoded, packet);
+    }
+
+    #[test]
+    fn test_game_event_packet() {
+        let mut buffer = Vec::new();
+        GameEventPacket::start_waiting_for_chunks()
+            .write(&mut buffer)
+            .unwrap();
+        assert_eq!(buffer, vec![13, 0, 0, 0, 0]);
+
+        let decoded = GameEventPacket::read(&mut Cursor::new(buffer)).unwrap();
+        assert_eq!(decoded.event, GameEventPacket::START_WAITING_FOR_CHUNKS);
+    }
+
+    #[test]
+    fn test_login_play_packet_with_death_location() {
+        let mut packet = LoginPlayPacket::new();
+        packet.has_death_location = true;
+        packet.death_dimension_name = Some(McIdentifier::minecraft("the_nether"));
+        packet.death_location = Some(Position::new(100, 64, -200));
+
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+
+        let mut cursor = Cursor::new(buffer);
+        let decoded = LoginPlayPacket::read(&mut cursor).unwrap();
+
+        assert!(decoded.has_death_location);
+        assert!(decoded.death_dimension_name.is_some());
+        assert!(decoded.death_location.is_some());
+
+        let dimension = decoded.death_dimension_name.unwrap();
+        let position = decoded.death_location.unwrap();
+
+        assert_eq!(dimension.to_string(), "minecraft:the_nether");
+        assert_eq!(position.x, 100);
+        assert_eq!(position.y, 64);
+        assert_eq!(position.z, -200);
+    }
+    #[test]
+    fn test_login_play_packet_from_server_config() {
+        use crate::config::ServerConfig;
+
+        let config = ServerConfig::new();
+        let packet = LoginPlayPacket::from_server_config(&config, 42);
+
+        assert_eq!(packet.entity_id, 42);
+        assert_eq!(packet.view_distance.0, config.view_distance as i32);
+        assert_eq!(
+            packet.simulation_distance.0,
+            config.simulation_distance as i32
+        );
+        assert!(!packet.is_debug);
+        assert_eq!(packet.max_players.0, config.max_players as i32);
+    }
+}
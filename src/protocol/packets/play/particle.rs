@@ -0,0 +1,322 @@
+//! Particle packets
+//!
+//! A particle packet spawns `count` particles of one type around a point.
+//! Some particle types carry extra data, such as a color or block state,
+//! written after the type ID.
+
+use crate::error::{Result, ServerError};
+use crate::protocol::packets::{ClientboundPacket, Packet};
+use crate::protocol::types::{
+    Position, Slot, VarInt, read_bool, read_double, read_float, read_int, write_bool, write_double,
+    write_float, write_int,
+};
+use std::io::{Read, Write};
+
+/// ID of `minecraft:block` in the `minecraft:particle_type` registry
+pub const BLOCK_PARTICLE_ID: i32 = 1;
+/// ID of `minecraft:block_marker`
+pub const BLOCK_MARKER_PARTICLE_ID: i32 = 2;
+/// ID of `minecraft:dust`
+pub const DUST_PARTICLE_ID: i32 = 13;
+/// ID of `minecraft:item`
+pub const ITEM_PARTICLE_ID: i32 = 46;
+
+/// A particle type and its extra data
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParticleType {
+    /// Colored dust, like redstone
+    Dust {
+        /// Red component, from 0.0 to 1.0
+        red: f32,
+        /// Green component, from 0.0 to 1.0
+        green: f32,
+        /// Blue component, from 0.0 to 1.0
+        blue: f32,
+        /// Size of the particles, from 0.01 to 4.0
+        size: f32,
+    },
+    /// Fragments of a block, as when it is broken
+    Block {
+        /// Block state ID of the block
+        block_state: VarInt,
+    },
+    /// A block shown in place, like barriers and light blocks
+    BlockMarker {
+        /// Block state ID of the block
+        block_state: VarInt,
+    },
+    /// Fragments of an item, as when it is eaten
+    Item(Slot),
+    /// A particle type without extra data, by its registry ID
+    Simple(i32),
+}
+
+impl ParticleType {
+    /// ID of the particle type in the `minecraft:particle_type` registry
+    pub fn id(&self) -> i32 {
+        match self {
+            ParticleType::Dust { .. } => DUST_PARTICLE_ID,
+            ParticleType::Block { .. } => BLOCK_PARTICLE_ID,
+            ParticleType::BlockMarker { .. } => BLOCK_MARKER_PARTICLE_ID,
+            ParticleType::Item(_) => ITEM_PARTICLE_ID,
+            ParticleType::Simple(id) => *id,
+        }
+    }
+
+    /// Read a particle type ID and its data
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        match VarInt::read(reader)?.0 {
+            DUST_PARTICLE_ID => {
+                let color = read_int(reader)?;
+                let channel = |shift: u32| ((color >> shift) & 0xFF) as f32 / 255.0;
+                Ok(ParticleType::Dust {
+                    red: channel(16),
+                    green: channel(8),
+                    blue: channel(0),
+                    size: read_float(reader)?,
+                })
+            }
+            BLOCK_PARTICLE_ID => Ok(ParticleType::Block {
+                block_state: VarInt::read(reader)?,
+            }),
+            BLOCK_MARKER_PARTICLE_ID => Ok(ParticleType::BlockMarker {
+                block_state: VarInt::read(reader)?,
+            }),
+            ITEM_PARTICLE_ID => Ok(ParticleType::Item(Slot::read(reader)?)),
+            id if id >= 0 => Ok(ParticleType::Simple(id)),
+            id => Err(ServerError::Protocol(format!(
+                "Invalid particle type: {}",
+                id
+            ))),
+        }
+    }
+
+    /// Write the particle type ID and its data
+    ///
+    /// Dust colors are packed into an RGB integer, with each component
+    /// clamped to between 0.0 and 1.0.
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        VarInt(self.id()).write(writer)?;
+        match self {
+            ParticleType::Dust {
+                red,
+                green,
+                blue,
+                size,
+            } => {
+                let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as i32;
+                write_int(
+                    channel(*red) << 16 | channel(*green) << 8 | channel(*blue),
+                    writer,
+                )?;
+                write_float(*size, writer)
+            }
+            ParticleType::Block { block_state } | ParticleType::BlockMarker { block_state } => {
+                block_state.write(writer)
+            }
+            ParticleType::Item(item) => item.write(writer),
+            ParticleType::Simple(_) => Ok(()),
+        }
+    }
+}
+
+/// Particle packet (clientbound)
+#[doc(alias = "LevelParticlesPacket")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParticlePacket {
+    /// Whether the particles are shown from up to 512 blocks away instead of
+    /// 32
+    pub long_distance: bool,
+    /// Whether the particles are shown even when the client has particles
+    /// turned down
+    pub always_visible: bool,
+    /// X coordinate of the center
+    pub x: f64,
+    /// Y coordinate of the center
+    pub y: f64,
+    /// Z coordinate of the center
+    pub z: f64,
+    /// Spread along the X axis, multiplied by a random number
+    pub offset_x: f32,
+    /// Spread along the Y axis
+    pub offset_y: f32,
+    /// Spread along the Z axis
+    pub offset_z: f32,
+    /// Speed of the particles
+    pub max_speed: f32,
+    /// Number of particles, where 0 spawns one particle moving along the
+    /// offset instead
+    pub count: i32,
+    /// Type of the particles
+    pub particle: ParticleType,
+}
+
+impl Packet for ParticlePacket {
+    const ID: i32 = 0x29;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            long_distance: read_bool(reader)?,
+            always_visible: read_bool(reader)?,
+            x: read_double(reader)?,
+            y: read_double(reader)?,
+            z: read_double(reader)?,
+            offset_x: read_float(reader)?,
+            offset_y: read_float(reader)?,
+            offset_z: read_float(reader)?,
+            max_speed: read_float(reader)?,
+            count: read_int(reader)?,
+            particle: ParticleType::read(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_bool(self.long_distance, writer)?;
+        write_bool(self.always_visible, writer)?;
+        write_double(self.x, writer)?;
+        write_double(self.y, writer)?;
+        write_double(self.z, writer)?;
+        write_float(self.offset_x, writer)?;
+        write_float(self.offset_y, writer)?;
+        write_float(self.offset_z, writer)?;
+        write_float(self.max_speed, writer)?;
+        write_int(self.count, writer)?;
+        self.particle.write(writer)
+    }
+}
+
+impl ClientboundPacket for ParticlePacket {}
+
+/// Builds particle packets for the same effect at different places
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParticleEmitter {
+    /// Type of the particles
+    particle: ParticleType,
+    /// Spread along each axis
+    offset: (f32, f32, f32),
+    /// Speed of the particles
+    max_speed: f32,
+    /// Number of particles per packet
+    count: i32,
+    /// Whether the particles are shown from far away
+    long_distance: bool,
+}
+
+impl ParticleEmitter {
+    /// Create an emitter of a single, motionless particle
+    pub fn new(particle: ParticleType) -> Self {
+        Self {
+            particle,
+            offset: (0.0, 0.0, 0.0),
+            max_speed: 0.0,
+            count: 1,
+            long_distance: false,
+        }
+    }
+
+    /// Set how far the particles spread along each axis
+    pub fn with_offset(mut self, x: f32, y: f32, z: f32) -> Self {
+        self.offset = (x, y, z);
+        self
+    }
+
+    /// Set the speed of the particles
+    pub fn with_speed(mut self, max_speed: f32) -> Self {
+        self.max_speed = max_speed;
+        self
+    }
+
+    /// Set the number of particles
+    pub fn with_count(mut self, count: i32) -> Self {
+        self.count = count;
+        self
+    }
+
+    /// Set whether the particles are shown from far away
+    pub fn with_long_distance(mut self, long_distance: bool) -> Self {
+        self.long_distance = long_distance;
+        self
+    }
+
+    /// Build the packet emitting the particles around a point in the world
+    pub fn at(&self, x: f64, y: f64, z: f64) -> ParticlePacket {
+        ParticlePacket {
+            long_distance: self.long_distance,
+            always_visible: false,
+            x,
+            y,
+            z,
+            offset_x: self.offset.0,
+            offset_y: self.offset.1,
+            offset_z: self.offset.2,
+            max_speed: self.max_speed,
+            count: self.count,
+            particle: self.particle.clone(),
+        }
+    }
+
+    /// Build the packet emitting the particles around the center of a block
+    pub fn at_block(&self, position: Position) -> ParticlePacket {
+        self.at(
+            f64::from(position.x) + 0.5,
+            f64::from(position.y) + 0.5,
+            f64::from(position.z) + 0.5,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn roundtrip(particle: ParticleType) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        particle.write(&mut buffer).unwrap();
+        assert_eq!(
+            ParticleType::read(&mut Cursor::new(buffer.clone())).unwrap(),
+            particle
+        );
+        buffer
+    }
+
+    #[test]
+    fn test_particle_data() {
+        let dust = roundtrip(ParticleType::Dust {
+            red: 1.0,
+            green: 0.0,
+            blue: 1.0,
+            size: 2.0,
+        });
+        assert_eq!(dust, [13, 0, 0xFF, 0, 0xFF, 0x40, 0, 0, 0]);
+        assert_eq!(
+            roundtrip(ParticleType::BlockMarker {
+                block_state: VarInt(14)
+            }),
+            [2, 14]
+        );
+        roundtrip(ParticleType::Block {
+            block_state: VarInt(300),
+        });
+        roundtrip(ParticleType::Item(Slot::new(1, 1)));
+        assert_eq!(roundtrip(ParticleType::Simple(44)), [44]);
+    }
+
+    #[test]
+    fn test_emitter() {
+        let emitter = ParticleEmitter::new(ParticleType::Simple(44))
+            .with_offset(0.5, 0.0, 0.5)
+            .with_count(5);
+        let packet = emitter.at_block(Position::new(-1, 64, 2));
+        assert_eq!((packet.x, packet.y, packet.z), (-0.5, 64.5, 2.5));
+        assert_eq!(packet.count, 5);
+
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(buffer.len(), 2 + 24 + 16 + 4 + 1);
+        assert_eq!(
+            ParticlePacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+    }
+}
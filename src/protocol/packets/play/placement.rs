@@ -0,0 +1,180 @@
+//! Block placement packets
+//!
+//! Right-clicking a block sends the hand used, the clicked face and where on
+//! that face the cursor was. Like digging, the client predicts the result and
+//! waits for the server to acknowledge the sequence number.
+
+use crate::error::{Result, ServerError};
+use crate::protocol::packets::{Packet, ServerboundPacket};
+use crate::protocol::types::{Position, VarInt, read_bool, read_float, write_bool, write_float};
+use std::io::{Read, Write};
+
+/// A hand holding an item
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[repr(i32)]
+pub enum Hand {
+    /// The hand holding the selected hotbar slot
+    #[default]
+    MainHand = 0,
+    /// The other hand
+    OffHand = 1,
+}
+
+impl TryFrom<i32> for Hand {
+    type Error = ServerError;
+
+    fn try_from(value: i32) -> Result<Self> {
+        match value {
+            0 => Ok(Hand::MainHand),
+            1 => Ok(Hand::OffHand),
+            _ => Err(ServerError::Protocol(format!("Invalid hand: {}", value))),
+        }
+    }
+}
+
+/// A face of a block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i32)]
+pub enum BlockFace {
+    /// Facing -Y
+    Bottom = 0,
+    /// Facing +Y
+    Top = 1,
+    /// Facing -Z
+    North = 2,
+    /// Facing +Z
+    South = 3,
+    /// Facing -X
+    West = 4,
+    /// Facing +X
+    East = 5,
+}
+
+impl BlockFace {
+    /// The position next to `position` on this face
+    pub fn offset(self, position: Position) -> Position {
+        let Position { x, y, z } = position;
+        match self {
+            BlockFace::Bottom => Position::new(x, y - 1, z),
+            BlockFace::Top => Position::new(x, y + 1, z),
+            BlockFace::North => Position::new(x, y, z - 1),
+            BlockFace::South => Position::new(x, y, z + 1),
+            BlockFace::West => Position::new(x - 1, y, z),
+            BlockFace::East => Position::new(x + 1, y, z),
+        }
+    }
+}
+
+impl TryFrom<i32> for BlockFace {
+    type Error = ServerError;
+
+    fn try_from(value: i32) -> Result<Self> {
+        match value {
+            0 => Ok(BlockFace::Bottom),
+            1 => Ok(BlockFace::Top),
+            2 => Ok(BlockFace::North),
+            3 => Ok(BlockFace::South),
+            4 => Ok(BlockFace::West),
+            5 => Ok(BlockFace::East),
+            _ => Err(ServerError::Protocol(format!(
+                "Invalid block face: {}",
+                value
+            ))),
+        }
+    }
+}
+
+/// Use Item On packet (serverbound)
+///
+/// Sent when the player right-clicks a block, which places the held block.
+#[doc(alias = "PlayerBlockPlacementPacket")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UseItemOnPacket {
+    /// Hand holding the item
+    pub hand: Hand,
+    /// Clicked block
+    pub position: Position,
+    /// Clicked face
+    pub face: BlockFace,
+    /// Cursor X position on the block, from 0 to 1
+    pub cursor_x: f32,
+    /// Cursor Y position on the block, from 0 to 1
+    pub cursor_y: f32,
+    /// Cursor Z position on the block, from 0 to 1
+    pub cursor_z: f32,
+    /// Whether the player's head is inside a block
+    pub inside_block: bool,
+    /// Whether the clicked block is on the world border
+    pub world_border_hit: bool,
+    /// Sequence number to acknowledge
+    pub sequence: VarInt,
+}
+
+impl Packet for UseItemOnPacket {
+    const ID: i32 = 0x3F;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            hand: Hand::try_from(VarInt::read(reader)?.0)?,
+            position: Position::read(reader)?,
+            face: BlockFace::try_from(VarInt::read(reader)?.0)?,
+            cursor_x: read_float(reader)?,
+            cursor_y: read_float(reader)?,
+            cursor_z: read_float(reader)?,
+            inside_block: read_bool(reader)?,
+            world_border_hit: read_bool(reader)?,
+            sequence: VarInt::read(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        VarInt(self.hand as i32).write(writer)?;
+        self.position.write(writer)?;
+        VarInt(self.face as i32).write(writer)?;
+        write_float(self.cursor_x, writer)?;
+        write_float(self.cursor_y, writer)?;
+        write_float(self.cursor_z, writer)?;
+        write_bool(self.inside_block, writer)?;
+        write_bool(self.world_border_hit, writer)?;
+        self.sequence.write(writer)
+    }
+}
+
+impl ServerboundPacket for UseItemOnPacket {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_use_item_on_roundtrip() {
+        let packet = UseItemOnPacket {
+            hand: Hand::OffHand,
+            position: Position::new(4, 63, -9),
+            face: BlockFace::Top,
+            cursor_x: 0.5,
+            cursor_y: 1.0,
+            cursor_z: 0.25,
+            inside_block: false,
+            world_border_hit: false,
+            sequence: VarInt(12),
+        };
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(buffer.len(), 1 + 8 + 1 + 12 + 2 + 1);
+        assert_eq!(
+            UseItemOnPacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+    }
+
+    #[test]
+    fn test_block_face_offset() {
+        let position = Position::new(0, 64, 0);
+        assert_eq!(BlockFace::Top.offset(position), Position::new(0, 65, 0));
+        assert_eq!(BlockFace::North.offset(position), Position::new(0, 64, -1));
+        assert_eq!(BlockFace::East.offset(position), Position::new(1, 64, 0));
+        assert!(BlockFace::try_from(6).is_err());
+    }
+}
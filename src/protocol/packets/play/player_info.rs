@@ -0,0 +1,345 @@
+//! Tab list player info packets
+
+use crate::error::{Result, ServerError};
+use crate::protocol::packets::login::Property;
+use crate::protocol::packets::{ClientboundPacket, Packet};
+use crate::protocol::text::TextComponent;
+use crate::protocol::types::{
+    McString, McUuid, VarInt, read_bool, read_unsigned_byte, read_uuid, write_bool,
+    write_unsigned_byte, write_uuid,
+};
+use std::io::{Read, Write};
+
+/// Player Info Update packet (clientbound)
+///
+/// Changes the tab list entries of some players. The same actions are
+/// applied to every entry, and each entry carries the fields of those
+/// actions in bit order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerInfoUpdatePacket {
+    /// Bit set of the actions applied
+    pub actions: u8,
+    /// Players to update
+    pub entries: Vec<PlayerInfoEntry>,
+}
+
+/// Fields of one player in a [`PlayerInfoUpdatePacket`]
+///
+/// Only the fields of the packet's actions are sent; the others keep their
+/// defaults when read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerInfoEntry {
+    /// Player UUID
+    pub uuid: McUuid,
+    /// Username, for [`PlayerInfoUpdatePacket::ADD_PLAYER`]
+    pub name: McString,
+    /// Profile properties such as the skin, for
+    /// [`PlayerInfoUpdatePacket::ADD_PLAYER`]
+    pub properties: Vec<Property>,
+    /// Game mode ID, for [`PlayerInfoUpdatePacket::UPDATE_GAME_MODE`]
+    pub game_mode: VarInt,
+    /// Whether the player is shown, for
+    /// [`PlayerInfoUpdatePacket::UPDATE_LISTED`]
+    pub listed: bool,
+    /// Latency in milliseconds, for
+    /// [`PlayerInfoUpdatePacket::UPDATE_LATENCY`]
+    pub latency: VarInt,
+    /// Name shown instead of the username, for
+    /// [`PlayerInfoUpdatePacket::UPDATE_DISPLAY_NAME`]
+    pub display_name: Option<TextComponent>,
+}
+
+impl PlayerInfoEntry {
+    /// Create an entry for a player with every field at its default
+    pub fn new(uuid: McUuid) -> Self {
+        Self {
+            uuid,
+            name: McString(String::new()),
+            properties: Vec::new(),
+            game_mode: VarInt(0),
+            listed: false,
+            latency: VarInt(0),
+            display_name: None,
+        }
+    }
+
+    /// Read the fields of the given actions
+    fn read<R: Read>(actions: u8, reader: &mut R) -> Result<Self> {
+        let mut entry = Self::new(read_uuid(reader)?);
+        if actions & PlayerInfoUpdatePacket::ADD_PLAYER != 0 {
+            entry.name = McString::read(reader)?;
+            let count = VarInt::read(reader)?.0;
+            if count < 0 {
+                return Err(ServerError::Protocol(format!(
+                    "Invalid profile property count: {}",
+                    count
+                )));
+            }
+            entry.properties = (0..count)
+                .map(|_| Property::read(reader))
+                .collect::<Result<_>>()?;
+        }
+        if actions & PlayerInfoUpdatePacket::UPDATE_GAME_MODE != 0 {
+            entry.game_mode = VarInt::read(reader)?;
+        }
+        if actions & PlayerInfoUpdatePacket::UPDATE_LISTED != 0 {
+            entry.listed = read_bool(reader)?;
+        }
+        if actions & PlayerInfoUpdatePacket::UPDATE_LATENCY != 0 {
+            entry.latency = VarInt::read(reader)?;
+        }
+        if actions & PlayerInfoUpdatePacket::UPDATE_DISPLAY_NAME != 0 && read_bool(reader)? {
+            entry.display_name = Some(TextComponent::read_nbt(reader)?);
+        }
+        Ok(entry)
+    }
+
+    /// Write the fields of the given actions
+    fn write<W: Write>(&self, actions: u8, writer: &mut W) -> Result<()> {
+        write_uuid(&self.uuid, writer)?;
+        if actions & PlayerInfoUpdatePacket::ADD_PLAYER != 0 {
+            self.name.write(writer)?;
+            VarInt(self.properties.len() as i32).write(writer)?;
+            for property in &self.properties {
+                property.write(writer)?;
+            }
+        }
+        if actions & PlayerInfoUpdatePacket::UPDATE_GAME_MODE != 0 {
+            self.game_mode.write(writer)?;
+        }
+        if actions & PlayerInfoUpdatePacket::UPDATE_LISTED != 0 {
+            write_bool(self.listed, writer)?;
+        }
+        if actions & PlayerInfoUpdatePacket::UPDATE_LATENCY != 0 {
+            self.latency.write(writer)?;
+        }
+        if actions & PlayerInfoUpdatePacket::UPDATE_DISPLAY_NAME != 0 {
+            write_bool(self.display_name.is_some(), writer)?;
+            if let Some(display_name) = &self.display_name {
+                display_name.write_nbt(writer)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl PlayerInfoUpdatePacket {
+    /// Action adding a player with their profile
+    pub const ADD_PLAYER: u8 = 0x01;
+
+    /// Action setting a player's chat session
+    pub const INITIALIZE_CHAT: u8 = 0x02;
+
+    /// Action setting the game mode shown for a player
+    pub const UPDATE_GAME_MODE: u8 = 0x04;
+
+    /// Action showing or hiding a player in the tab list
+    pub const UPDATE_LISTED: u8 = 0x08;
+
+    /// Action setting the latency shown for a player
+    pub const UPDATE_LATENCY: u8 = 0x10;
+
+    /// Action setting the name shown for a player
+    pub const UPDATE_DISPLAY_NAME: u8 = 0x20;
+
+    /// Action setting where a player is sorted in the tab list
+    pub const UPDATE_LIST_PRIORITY: u8 = 0x40;
+
+    /// Action showing or hiding a player's hat layer
+    pub const UPDATE_HAT: u8 = 0x80;
+
+    /// Actions adding players to the tab list with everything it shows
+    pub const ADD_ACTIONS: u8 = Self::ADD_PLAYER
+        | Self::UPDATE_GAME_MODE
+        | Self::UPDATE_LISTED
+        | Self::UPDATE_LATENCY
+        | Self::UPDATE_DISPLAY_NAME;
+
+    /// Actions whose fields can be read and written so far
+    const SUPPORTED_ACTIONS: u8 = Self::ADD_ACTIONS;
+
+    /// Create the packet adding players to the tab list
+    pub fn add_players(entries: Vec<PlayerInfoEntry>) -> Self {
+        Self {
+            actions: Self::ADD_ACTIONS,
+            entries,
+        }
+    }
+
+    /// Create the packet changing the game mode shown for one player
+    pub fn game_mode(uuid: McUuid, game_mode: i32) -> Self {
+        Self {
+            actions: Self::UPDATE_GAME_MODE,
+            entries: vec![PlayerInfoEntry {
+                game_mode: VarInt(game_mode),
+                ..PlayerInfoEntry::new(uuid)
+            }],
+        }
+    }
+
+    /// Create the packet changing the latency shown for some players
+    pub fn latency(latencies: impl IntoIterator<Item = (McUuid, i32)>) -> Self {
+        Self {
+            actions: Self::UPDATE_LATENCY,
+            entries: latencies
+                .into_iter()
+                .map(|(uuid, latency)| PlayerInfoEntry {
+                    latency: VarInt(latency),
+                    ..PlayerInfoEntry::new(uuid)
+                })
+                .collect(),
+        }
+    }
+
+    /// Fail if any action is not supported yet
+    fn check_actions(actions: u8) -> Result<()> {
+        if actions & !Self::SUPPORTED_ACTIONS != 0 {
+            return Err(ServerError::Protocol(format!(
+                "Unsupported player info actions: 0x{:02X}",
+                actions
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Packet for PlayerInfoUpdatePacket {
+    const ID: i32 = 0x3F;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let actions = read_unsigned_byte(reader)?;
+        Self::check_actions(actions)?;
+        let count = VarInt::read(reader)?.0;
+        let count = usize::try_from(count).map_err(|_| {
+            ServerError::Protocol(format!("Invalid player info entry count: {}", count))
+        })?;
+        let mut entries = Vec::new();
+        for _ in 0..count {
+            entries.push(PlayerInfoEntry::read(actions, reader)?);
+        }
+        Ok(Self { actions, entries })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        Self::check_actions(self.actions)?;
+        write_unsigned_byte(self.actions, writer)?;
+        VarInt(self.entries.len() as i32).write(writer)?;
+        for entry in &self.entries {
+            entry.write(self.actions, writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl ClientboundPacket for PlayerInfoUpdatePacket {}
+
+/// Player Info Remove packet (clientbound)
+///
+/// Removes players from the tab list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerInfoRemovePacket {
+    /// Players to remove
+    pub uuids: Vec<McUuid>,
+}
+
+impl Packet for PlayerInfoRemovePacket {
+    const ID: i32 = 0x3E;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let count = VarInt::read(reader)?.0;
+        let count = usize::try_from(count).map_err(|_| {
+            ServerError::Protocol(format!("Invalid player info entry count: {}", count))
+        })?;
+        let uuids = (0..count)
+            .map(|_| read_uuid(reader))
+            .collect::<Result<_>>()?;
+        Ok(Self { uuids })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        VarInt(self.uuids.len() as i32).write(writer)?;
+        for uuid in &self.uuids {
+            write_uuid(uuid, writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl ClientboundPacket for PlayerInfoRemovePacket {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_game_mode_roundtrip() {
+        let uuid = McUuid::new_v4();
+        let packet = PlayerInfoUpdatePacket::game_mode(uuid, 1);
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(buffer[..2], [PlayerInfoUpdatePacket::UPDATE_GAME_MODE, 1]);
+        assert_eq!(buffer[2..18], *uuid.as_bytes());
+        assert_eq!(buffer[18..], [1]);
+        assert_eq!(
+            PlayerInfoUpdatePacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+    }
+
+    #[test]
+    fn test_add_players_roundtrip() {
+        let uuid = McUuid::new_v4();
+        let packet = PlayerInfoUpdatePacket::add_players(vec![PlayerInfoEntry {
+            name: McString("Steve".to_string()),
+            properties: vec![Property {
+                name: McString("textures".to_string()),
+                value: McString("e30=".to_string()),
+                signature: None,
+            }],
+            game_mode: VarInt(0),
+            listed: true,
+            latency: VarInt(150),
+            display_name: Some(TextComponent::text("Steve the Great")),
+            ..PlayerInfoEntry::new(uuid)
+        }]);
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(buffer[..2], [0x3D, 1]);
+        // Name, then the single unsigned property
+        assert_eq!(buffer[18..25], *b"\x05Steve\x01");
+        assert_eq!(
+            PlayerInfoUpdatePacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+
+        let latency = PlayerInfoUpdatePacket::latency([(uuid, 42)]);
+        let mut buffer = Vec::new();
+        latency.write(&mut buffer).unwrap();
+        assert_eq!(buffer[18..], [42]);
+    }
+
+    #[test]
+    fn test_player_info_remove_roundtrip() {
+        let packet = PlayerInfoRemovePacket {
+            uuids: vec![McUuid::new_v4(), McUuid::new_v4()],
+        };
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(buffer.len(), 33);
+        assert_eq!(
+            PlayerInfoRemovePacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+    }
+
+    #[test]
+    fn test_unsupported_actions() {
+        let packet = PlayerInfoUpdatePacket {
+            actions: PlayerInfoUpdatePacket::INITIALIZE_CHAT,
+            entries: Vec::new(),
+        };
+        assert!(packet.write(&mut Vec::new()).is_err());
+        assert!(PlayerInfoUpdatePacket::read(&mut Cursor::new([0x02, 0])).is_err());
+    }
+}
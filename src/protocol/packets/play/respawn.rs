@@ -0,0 +1,253 @@
+//! Death and respawn packets
+//!
+//! A player whose health runs out is shown the death screen. Once they click
+//! respawn, the client asks for it with a client command and the server
+//! answers with a respawn packet, which makes the client start over in a
+//! fresh copy of the world.
+
+use crate::error::{Result, ServerError};
+use crate::protocol::packets::play::LoginPlayPacket;
+use crate::protocol::packets::{ClientboundPacket, Packet, ServerboundPacket};
+use crate::protocol::text::TextComponent;
+use crate::protocol::types::{
+    McIdentifier, Position, VarInt, read_bool, read_long, read_unsigned_byte, write_bool,
+    write_long, write_unsigned_byte,
+};
+use std::io::{Read, Write};
+
+/// Respawn flag keeping the player's attributes
+pub const KEEP_ATTRIBUTES: u8 = 0x01;
+/// Respawn flag keeping the player's entity metadata
+pub const KEEP_METADATA: u8 = 0x02;
+
+/// Player Death packet (clientbound)
+///
+/// Shows the death screen with a message to the player who died.
+#[doc(alias = "PlayerCombatKillPacket")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerDeathPacket {
+    /// Entity ID of the player who died
+    pub player_id: VarInt,
+    /// Death message shown on the death screen
+    pub message: TextComponent,
+}
+
+impl Packet for PlayerDeathPacket {
+    const ID: i32 = 0x3D;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            player_id: VarInt::read(reader)?,
+            message: TextComponent::read_nbt(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.player_id.write(writer)?;
+        self.message.write_nbt(writer)
+    }
+}
+
+impl ClientboundPacket for PlayerDeathPacket {}
+
+/// Action requested by a Client Command packet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i32)]
+pub enum ClientCommandAction {
+    /// Respawn after dying
+    PerformRespawn = 0,
+    /// Ask for the player's statistics
+    RequestStats = 1,
+}
+
+impl TryFrom<i32> for ClientCommandAction {
+    type Error = ServerError;
+
+    fn try_from(value: i32) -> Result<Self> {
+        match value {
+            0 => Ok(ClientCommandAction::PerformRespawn),
+            1 => Ok(ClientCommandAction::RequestStats),
+            _ => Err(ServerError::Protocol(format!(
+                "Invalid client command action: {}",
+                value
+            ))),
+        }
+    }
+}
+
+/// Client Command packet (serverbound)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientCommandPacket {
+    /// Requested action
+    pub action: ClientCommandAction,
+}
+
+impl Packet for ClientCommandPacket {
+    const ID: i32 = 0x0B;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            action: ClientCommandAction::try_from(VarInt::read(reader)?.0)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        VarInt(self.action as i32).write(writer)
+    }
+}
+
+impl ServerboundPacket for ClientCommandPacket {}
+
+/// Respawn packet (clientbound)
+///
+/// Also sent to move the player to another dimension.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RespawnPacket {
+    /// The ID of the dimension type in the minecraft:dimension_type registry
+    pub dimension_type: VarInt,
+    /// Name of the dimension being spawned into
+    pub dimension_name: McIdentifier,
+    /// First 8 bytes of SHA-256 hash of world seed
+    pub hashed_seed: i64,
+    /// Game mode (0=Survival, 1=Creative, 2=Adventure, 3=Spectator)
+    pub game_mode: u8,
+    /// Previous game mode, or -1 if there is none
+    pub previous_game_mode: i8,
+    /// Whether this is a debug world
+    pub is_debug: bool,
+    /// Whether this is a flat/superflat world
+    pub is_flat: bool,
+    /// Dimension and position the player last died at, if any
+    pub death_location: Option<(McIdentifier, Position)>,
+    /// Portal cooldown in ticks
+    pub portal_cooldown: VarInt,
+    /// Sea level
+    pub sea_level: VarInt,
+    /// Player data kept, of [`KEEP_ATTRIBUTES`] and [`KEEP_METADATA`]
+    pub data_kept: u8,
+}
+
+impl RespawnPacket {
+    /// Respawn in the world the player logged into, keeping some data
+    pub fn from_login(login: &LoginPlayPacket, data_kept: u8) -> Self {
+        let death_location = login
+            .death_dimension_name
+            .clone()
+            .zip(login.death_location)
+            .filter(|_| login.has_death_location);
+        Self {
+            dimension_type: login.dimension_type,
+            dimension_name: login.dimension_name.clone(),
+            hashed_seed: login.hashed_seed,
+            game_mode: login.game_mode,
+            previous_game_mode: login.previous_game_mode,
+            is_debug: login.is_debug,
+            is_flat: login.is_flat,
+            death_location,
+            portal_cooldown: login.portal_cooldown,
+            sea_level: login.sea_level,
+            data_kept,
+        }
+    }
+
+    /// Set where the player last died
+    pub fn with_death_location(mut self, dimension: McIdentifier, position: Position) -> Self {
+        self.death_location = Some((dimension, position));
+        self
+    }
+}
+
+impl Packet for RespawnPacket {
+    const ID: i32 = 0x4B;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let dimension_type = VarInt::read(reader)?;
+        let dimension_name = McIdentifier::read(reader)?;
+        let hashed_seed = read_long(reader)?;
+        let game_mode = read_unsigned_byte(reader)?;
+        let previous_game_mode = read_unsigned_byte(reader)? as i8;
+        let is_debug = read_bool(reader)?;
+        let is_flat = read_bool(reader)?;
+        let death_location = if read_bool(reader)? {
+            Some((McIdentifier::read(reader)?, Position::read(reader)?))
+        } else {
+            None
+        };
+        Ok(Self {
+            dimension_type,
+            dimension_name,
+            hashed_seed,
+            game_mode,
+            previous_game_mode,
+            is_debug,
+            is_flat,
+            death_location,
+            portal_cooldown: VarInt::read(reader)?,
+            sea_level: VarInt::read(reader)?,
+            data_kept: read_unsigned_byte(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.dimension_type.write(writer)?;
+        self.dimension_name.write(writer)?;
+        write_long(self.hashed_seed, writer)?;
+        write_unsigned_byte(self.game_mode, writer)?;
+        write_unsigned_byte(self.previous_game_mode as u8, writer)?;
+        write_bool(self.is_debug, writer)?;
+        write_bool(self.is_flat, writer)?;
+        write_bool(self.death_location.is_some(), writer)?;
+        if let Some((dimension, position)) = &self.death_location {
+            dimension.write(writer)?;
+            position.write(writer)?;
+        }
+        self.portal_cooldown.write(writer)?;
+        self.sea_level.write(writer)?;
+        write_unsigned_byte(self.data_kept, writer)
+    }
+}
+
+impl ClientboundPacket for RespawnPacket {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_respawn_roundtrip() {
+        let login = LoginPlayPacket::new();
+        let packet = RespawnPacket::from_login(&login, KEEP_ATTRIBUTES).with_death_location(
+            McIdentifier::minecraft("overworld"),
+            Position::new(3, -70, 4),
+        );
+        assert_eq!(packet.dimension_name, login.dimension_name);
+        assert_eq!(packet.previous_game_mode, -1);
+
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(buffer.last(), Some(&KEEP_ATTRIBUTES));
+        assert_eq!(
+            RespawnPacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+    }
+
+    #[test]
+    fn test_death_and_client_command() {
+        let death = PlayerDeathPacket {
+            player_id: VarInt(5),
+            message: TextComponent::text("Steve fell out of the world"),
+        };
+        let mut buffer = Vec::new();
+        death.write(&mut buffer).unwrap();
+        assert_eq!(
+            PlayerDeathPacket::read(&mut Cursor::new(buffer)).unwrap(),
+            death
+        );
+
+        let command = ClientCommandPacket::read(&mut Cursor::new([0])).unwrap();
+        assert_eq!(command.action, ClientCommandAction::PerformRespawn);
+        assert!(ClientCommandPacket::read(&mut Cursor::new([2])).is_err());
+    }
+}
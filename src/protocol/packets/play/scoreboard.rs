@@ -0,0 +1,420 @@
+//! Scoreboard packets
+//!
+//! An objective is a named set of scores, one per entity name, which the
+//! client shows once the objective is assigned a display slot.
+
+use crate::error::{Result, ServerError};
+use crate::protocol::nbt::NbtTag;
+use crate::protocol::packets::{ClientboundPacket, Packet};
+use crate::protocol::text::TextComponent;
+use crate::protocol::types::{
+    McString, VarInt, read_bool, read_unsigned_byte, write_bool, write_unsigned_byte,
+};
+use std::io::{Read, Write};
+
+/// How the scores of an objective are drawn
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[repr(i32)]
+pub enum ScoreboardRenderType {
+    /// Scores are drawn as numbers
+    #[default]
+    Integer = 0,
+    /// Scores are drawn as hearts
+    Hearts = 1,
+}
+
+impl TryFrom<i32> for ScoreboardRenderType {
+    type Error = ServerError;
+
+    fn try_from(value: i32) -> Result<Self> {
+        match value {
+            0 => Ok(ScoreboardRenderType::Integer),
+            1 => Ok(ScoreboardRenderType::Hearts),
+            _ => Err(ServerError::Protocol(format!(
+                "Invalid scoreboard render type: {}",
+                value
+            ))),
+        }
+    }
+}
+
+/// How score numbers are formatted
+#[derive(Debug, Clone, PartialEq)]
+pub enum NumberFormat {
+    /// Scores are hidden
+    Blank,
+    /// Scores are drawn with the given style compound
+    Styled(NbtTag),
+    /// Scores are replaced with fixed text
+    Fixed(TextComponent),
+}
+
+impl NumberFormat {
+    /// Read a number format
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        match VarInt::read(reader)?.0 {
+            0 => Ok(NumberFormat::Blank),
+            1 => Ok(NumberFormat::Styled(NbtTag::read_network(reader)?)),
+            2 => Ok(NumberFormat::Fixed(TextComponent::read_nbt(reader)?)),
+            kind => Err(ServerError::Protocol(format!(
+                "Invalid number format: {}",
+                kind
+            ))),
+        }
+    }
+
+    /// Write a number format
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        match self {
+            NumberFormat::Blank => VarInt(0).write(writer),
+            NumberFormat::Styled(style) => {
+                VarInt(1).write(writer)?;
+                style.write_network(writer)
+            }
+            NumberFormat::Fixed(text) => {
+                VarInt(2).write(writer)?;
+                text.write_nbt(writer)
+            }
+        }
+    }
+
+    /// Read a number format prefixed with whether it is present
+    fn read_optional<R: Read>(reader: &mut R) -> Result<Option<Self>> {
+        if read_bool(reader)? {
+            Ok(Some(Self::read(reader)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Write a number format prefixed with whether it is present
+    fn write_optional<W: Write>(format: Option<&Self>, writer: &mut W) -> Result<()> {
+        write_bool(format.is_some(), writer)?;
+        match format {
+            Some(format) => format.write(writer),
+            None => Ok(()),
+        }
+    }
+}
+
+/// How an objective is displayed
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectiveDisplay {
+    /// Title of the objective
+    pub display_name: TextComponent,
+    /// How scores are drawn
+    pub render_type: ScoreboardRenderType,
+    /// Default format of the scores
+    pub number_format: Option<NumberFormat>,
+}
+
+impl ObjectiveDisplay {
+    /// Read the display of an objective
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            display_name: TextComponent::read_nbt(reader)?,
+            render_type: ScoreboardRenderType::try_from(VarInt::read(reader)?.0)?,
+            number_format: NumberFormat::read_optional(reader)?,
+        })
+    }
+
+    /// Write the display of an objective
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.display_name.write_nbt(writer)?;
+        VarInt(self.render_type as i32).write(writer)?;
+        NumberFormat::write_optional(self.number_format.as_ref(), writer)
+    }
+}
+
+/// Change made by a [`ScoreboardObjectivePacket`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum ObjectiveAction {
+    /// Create the objective
+    Create(ObjectiveDisplay),
+    /// Remove the objective and its scores
+    Remove,
+    /// Change how the objective is displayed
+    Update(ObjectiveDisplay),
+}
+
+/// Update Objectives packet (clientbound)
+#[doc(alias = "SetObjectivePacket")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoreboardObjectivePacket {
+    /// Unique name of the objective
+    pub objective_name: McString,
+    /// What changes
+    pub action: ObjectiveAction,
+}
+
+impl Packet for ScoreboardObjectivePacket {
+    const ID: i32 = 0x63;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let objective_name = McString::read(reader)?;
+        let action = match read_unsigned_byte(reader)? {
+            0 => ObjectiveAction::Create(ObjectiveDisplay::read(reader)?),
+            1 => ObjectiveAction::Remove,
+            2 => ObjectiveAction::Update(ObjectiveDisplay::read(reader)?),
+            mode => {
+                return Err(ServerError::Protocol(format!(
+                    "Invalid objective mode: {}",
+                    mode
+                )));
+            }
+        };
+        Ok(Self {
+            objective_name,
+            action,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.objective_name.write(writer)?;
+        match &self.action {
+            ObjectiveAction::Create(display) => {
+                write_unsigned_byte(0, writer)?;
+                display.write(writer)
+            }
+            ObjectiveAction::Remove => write_unsigned_byte(1, writer),
+            ObjectiveAction::Update(display) => {
+                write_unsigned_byte(2, writer)?;
+                display.write(writer)
+            }
+        }
+    }
+}
+
+impl ClientboundPacket for ScoreboardObjectivePacket {}
+
+/// Update Score packet (clientbound)
+///
+/// Creates or changes the score of an entity in an objective.
+#[doc(alias = "SetScorePacket")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpdateScorePacket {
+    /// Username of the player, or UUID of another entity
+    pub entity_name: McString,
+    /// Objective the score belongs to
+    pub objective_name: McString,
+    /// New score
+    pub value: VarInt,
+    /// Text shown instead of the entity name
+    pub display_name: Option<TextComponent>,
+    /// Format of this score, overriding the objective's
+    pub number_format: Option<NumberFormat>,
+}
+
+impl Packet for UpdateScorePacket {
+    const ID: i32 = 0x67;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let entity_name = McString::read(reader)?;
+        let objective_name = McString::read(reader)?;
+        let value = VarInt::read(reader)?;
+        let display_name = if read_bool(reader)? {
+            Some(TextComponent::read_nbt(reader)?)
+        } else {
+            None
+        };
+        Ok(Self {
+            entity_name,
+            objective_name,
+            value,
+            display_name,
+            number_format: NumberFormat::read_optional(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.entity_name.write(writer)?;
+        self.objective_name.write(writer)?;
+        self.value.write(writer)?;
+        write_bool(self.display_name.is_some(), writer)?;
+        if let Some(display_name) = &self.display_name {
+            display_name.write_nbt(writer)?;
+        }
+        NumberFormat::write_optional(self.number_format.as_ref(), writer)
+    }
+}
+
+impl ClientboundPacket for UpdateScorePacket {}
+
+/// Reset Score packet (clientbound)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResetScorePacket {
+    /// Username of the player, or UUID of another entity
+    pub entity_name: McString,
+    /// Objective to remove the score from, or `None` for every objective
+    pub objective_name: Option<McString>,
+}
+
+impl Packet for ResetScorePacket {
+    const ID: i32 = 0x48;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let entity_name = McString::read(reader)?;
+        let objective_name = if read_bool(reader)? {
+            Some(McString::read(reader)?)
+        } else {
+            None
+        };
+        Ok(Self {
+            entity_name,
+            objective_name,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.entity_name.write(writer)?;
+        write_bool(self.objective_name.is_some(), writer)?;
+        match &self.objective_name {
+            Some(objective_name) => objective_name.write(writer),
+            None => Ok(()),
+        }
+    }
+}
+
+impl ClientboundPacket for ResetScorePacket {}
+
+/// Where an objective is displayed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplaySlot {
+    /// Next to names in the tab list
+    List,
+    /// On the right of the screen
+    Sidebar,
+    /// Below the name tags of players
+    BelowName,
+    /// On the sidebar of players in a team of the given color, from 0 to 15
+    TeamSidebar(u8),
+}
+
+impl DisplaySlot {
+    /// Protocol ID of the slot
+    pub fn id(self) -> i32 {
+        match self {
+            DisplaySlot::List => 0,
+            DisplaySlot::Sidebar => 1,
+            DisplaySlot::BelowName => 2,
+            DisplaySlot::TeamSidebar(color) => 3 + i32::from(color),
+        }
+    }
+}
+
+impl TryFrom<i32> for DisplaySlot {
+    type Error = ServerError;
+
+    fn try_from(value: i32) -> Result<Self> {
+        match value {
+            0 => Ok(DisplaySlot::List),
+            1 => Ok(DisplaySlot::Sidebar),
+            2 => Ok(DisplaySlot::BelowName),
+            3..=18 => Ok(DisplaySlot::TeamSidebar((value - 3) as u8)),
+            _ => Err(ServerError::Protocol(format!(
+                "Invalid display slot: {}",
+                value
+            ))),
+        }
+    }
+}
+
+/// Display Objective packet (clientbound)
+#[doc(alias = "SetDisplayObjectivePacket")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisplayObjectivePacket {
+    /// Slot to show the objective in
+    pub slot: DisplaySlot,
+    /// Objective to show, or empty to clear the slot
+    pub objective_name: McString,
+}
+
+impl Packet for DisplayObjectivePacket {
+    const ID: i32 = 0x5B;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            slot: DisplaySlot::try_from(VarInt::read(reader)?.0)?,
+            objective_name: McString::read(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        VarInt(self.slot.id()).write(writer)?;
+        self.objective_name.write(writer)
+    }
+}
+
+impl ClientboundPacket for DisplayObjectivePacket {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_objective_packet_roundtrip() {
+        let packet = ScoreboardObjectivePacket {
+            objective_name: McString("kills".to_string()),
+            action: ObjectiveAction::Create(ObjectiveDisplay {
+                display_name: TextComponent::text("Kills"),
+                render_type: ScoreboardRenderType::Hearts,
+                number_format: Some(NumberFormat::Fixed(TextComponent::text("-"))),
+            }),
+        };
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(&buffer[..7], b"\x05kills\x00");
+        assert_eq!(
+            ScoreboardObjectivePacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+
+        let remove = ScoreboardObjectivePacket {
+            objective_name: McString("kills".to_string()),
+            action: ObjectiveAction::Remove,
+        };
+        let mut buffer = Vec::new();
+        remove.write(&mut buffer).unwrap();
+        assert_eq!(buffer, b"\x05kills\x01");
+    }
+
+    #[test]
+    fn test_score_packets_roundtrip() {
+        let update = UpdateScorePacket {
+            entity_name: McString("Steve".to_string()),
+            objective_name: McString("kills".to_string()),
+            value: VarInt(3),
+            display_name: None,
+            number_format: Some(NumberFormat::Blank),
+        };
+        let mut buffer = Vec::new();
+        update.write(&mut buffer).unwrap();
+        assert_eq!(&buffer[12..], [3, 0, 1, 0]);
+        assert_eq!(
+            UpdateScorePacket::read(&mut Cursor::new(buffer)).unwrap(),
+            update
+        );
+
+        let reset = ResetScorePacket {
+            entity_name: McString("Steve".to_string()),
+            objective_name: None,
+        };
+        let mut buffer = Vec::new();
+        reset.write(&mut buffer).unwrap();
+        assert_eq!(
+            ResetScorePacket::read(&mut Cursor::new(buffer)).unwrap(),
+            reset
+        );
+    }
+
+    #[test]
+    fn test_display_slots() {
+        assert_eq!(DisplaySlot::TeamSidebar(15).id(), 18);
+        assert_eq!(
+            DisplaySlot::try_from(4).unwrap(),
+            DisplaySlot::TeamSidebar(1)
+        );
+        assert!(DisplaySlot::try_from(19).is_err());
+    }
+}
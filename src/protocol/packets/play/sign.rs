@@ -0,0 +1,145 @@
+//! Sign editing packets
+//!
+//! After placing a sign, the client is told to open the sign editor. When
+//! the player is done, the client sends the four lines of one side back.
+
+use crate::error::Result;
+use crate::protocol::packets::{ClientboundPacket, Packet, ServerboundPacket};
+use crate::protocol::types::{McString, Position, read_bool, write_bool};
+use std::io::{Read, Write};
+
+/// Number of lines on each side of a sign
+pub const SIGN_LINES: usize = 4;
+
+/// Open Sign Editor packet (clientbound)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpenSignEditorPacket {
+    /// Position of the sign
+    pub position: Position,
+    /// Whether to edit the front of the sign rather than the back
+    pub is_front_text: bool,
+}
+
+impl Packet for OpenSignEditorPacket {
+    const ID: i32 = 0x35;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            position: Position::read(reader)?,
+            is_front_text: read_bool(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.position.write(writer)?;
+        write_bool(self.is_front_text, writer)
+    }
+}
+
+impl ClientboundPacket for OpenSignEditorPacket {}
+
+/// Sign Update packet (serverbound)
+///
+/// The lines are not limited in length when read; see
+/// [`SignUpdatePacket::MAX_LINE_LENGTH`].
+#[doc(alias = "UpdateSignPacket")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignUpdatePacket {
+    /// Position of the sign
+    pub position: Position,
+    /// Whether the lines are for the front of the sign rather than the back
+    pub is_front_text: bool,
+    /// Lines from top to bottom
+    pub lines: [McString; SIGN_LINES],
+}
+
+impl SignUpdatePacket {
+    /// Longest line vanilla servers accept, in characters
+    pub const MAX_LINE_LENGTH: usize = 384;
+
+    /// Whether every line is short enough to accept
+    pub fn is_valid(&self) -> bool {
+        self.lines
+            .iter()
+            .all(|line| line.0.chars().count() <= Self::MAX_LINE_LENGTH)
+    }
+}
+
+impl Packet for SignUpdatePacket {
+    const ID: i32 = 0x3B;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let position = Position::read(reader)?;
+        let is_front_text = read_bool(reader)?;
+        let lines = [
+            McString::read(reader)?,
+            McString::read(reader)?,
+            McString::read(reader)?,
+            McString::read(reader)?,
+        ];
+        Ok(Self {
+            position,
+            is_front_text,
+            lines,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.position.write(writer)?;
+        write_bool(self.is_front_text, writer)?;
+        for line in &self.lines {
+            line.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl ServerboundPacket for SignUpdatePacket {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_sign_update_roundtrip() {
+        let packet = SignUpdatePacket {
+            position: Position::new(0, 64, 0),
+            is_front_text: true,
+            lines: ["Welcome".into(), "to".into(), "".into(), "spawn".into()],
+        };
+        assert!(packet.is_valid());
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(
+            SignUpdatePacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+
+        let long = SignUpdatePacket {
+            lines: [
+                "a".repeat(SignUpdatePacket::MAX_LINE_LENGTH + 1).into(),
+                "".into(),
+                "".into(),
+                "".into(),
+            ],
+            ..packet
+        };
+        assert!(!long.is_valid());
+    }
+
+    #[test]
+    fn test_open_sign_editor() {
+        let packet = OpenSignEditorPacket {
+            position: Position::new(2, 70, 3),
+            is_front_text: false,
+        };
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(buffer.len(), 9);
+        assert_eq!(
+            OpenSignEditorPacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+    }
+}
@@ -0,0 +1,263 @@
+//! Sound packets
+//!
+//! Sounds are played at a position in the world. A sound event is sent
+//! either as its ID in the `minecraft:sound_event` registry, or inline by
+//! name for sounds the client may not have registered, such as those of a
+//! resource pack.
+
+use crate::error::{Result, ServerError};
+use crate::protocol::packets::{ClientboundPacket, Packet};
+use crate::protocol::types::{
+    McIdentifier, VarInt, read_bool, read_float, read_int, read_long, write_bool, write_float,
+    write_int, write_long,
+};
+use std::io::{Read, Write};
+
+/// Sound category, which decides the volume slider that applies
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[repr(i32)]
+pub enum SoundSource {
+    /// Master volume only
+    #[default]
+    Master = 0,
+    /// Background music
+    Music = 1,
+    /// Jukeboxes and note blocks
+    Record = 2,
+    /// Rain and thunder
+    Weather = 3,
+    /// Blocks
+    Block = 4,
+    /// Hostile mobs
+    Hostile = 5,
+    /// Friendly mobs
+    Neutral = 6,
+    /// Players
+    Player = 7,
+    /// Ambient sounds such as caves
+    Ambient = 8,
+    /// Voice and speech
+    Voice = 9,
+}
+
+impl TryFrom<i32> for SoundSource {
+    type Error = ServerError;
+
+    fn try_from(value: i32) -> Result<Self> {
+        match value {
+            0 => Ok(SoundSource::Master),
+            1 => Ok(SoundSource::Music),
+            2 => Ok(SoundSource::Record),
+            3 => Ok(SoundSource::Weather),
+            4 => Ok(SoundSource::Block),
+            5 => Ok(SoundSource::Hostile),
+            6 => Ok(SoundSource::Neutral),
+            7 => Ok(SoundSource::Player),
+            8 => Ok(SoundSource::Ambient),
+            9 => Ok(SoundSource::Voice),
+            _ => Err(ServerError::Protocol(format!(
+                "Invalid sound source: {}",
+                value
+            ))),
+        }
+    }
+}
+
+/// Convert a coordinate to the fixed-point form used by sound packets,
+/// with 3 fractional bits
+pub fn to_fixed_point(coordinate: f64) -> i32 {
+    (coordinate * 8.0) as i32
+}
+
+/// Where a sound is played, how loud, and with which random seed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SoundPlacement {
+    /// Category of the sound
+    pub source: SoundSource,
+    /// X coordinate, in fixed-point (see [`to_fixed_point`])
+    pub x: i32,
+    /// Y coordinate, in fixed-point
+    pub y: i32,
+    /// Z coordinate, in fixed-point
+    pub z: i32,
+    /// Volume, where 1.0 is heard up to 16 blocks away
+    pub volume: f32,
+    /// Pitch, from 0.5 to 2.0
+    pub pitch: f32,
+    /// Seed picking between the variants of the sound
+    pub seed: i64,
+}
+
+impl SoundPlacement {
+    /// Read the fields following the sound event
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            source: SoundSource::try_from(VarInt::read(reader)?.0)?,
+            x: read_int(reader)?,
+            y: read_int(reader)?,
+            z: read_int(reader)?,
+            volume: read_float(reader)?,
+            pitch: read_float(reader)?,
+            seed: read_long(reader)?,
+        })
+    }
+
+    /// Write the fields following the sound event
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        VarInt(self.source as i32).write(writer)?;
+        write_int(self.x, writer)?;
+        write_int(self.y, writer)?;
+        write_int(self.z, writer)?;
+        write_float(self.volume, writer)?;
+        write_float(self.pitch, writer)?;
+        write_long(self.seed, writer)
+    }
+}
+
+/// Sound Effect packet (clientbound), for a registered sound event
+///
+/// The sound event is written as its registry ID plus one, since 0 means
+/// the event follows inline (see [`NamedSoundEffectPacket`]).
+#[doc(alias = "SoundPacket")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoundEffectPacket {
+    /// ID of the sound event in the `minecraft:sound_event` registry
+    pub sound_id: i32,
+    /// Where and how the sound plays
+    pub placement: SoundPlacement,
+}
+
+impl Packet for SoundEffectPacket {
+    const ID: i32 = 0x6E;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let sound_id = match VarInt::read(reader)?.0 {
+            0 => {
+                return Err(ServerError::Protocol(
+                    "Expected a registered sound event, got an inline one".to_string(),
+                ));
+            }
+            id => id - 1,
+        };
+        Ok(Self {
+            sound_id,
+            placement: SoundPlacement::read(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        VarInt(self.sound_id + 1).write(writer)?;
+        self.placement.write(writer)
+    }
+}
+
+impl ClientboundPacket for SoundEffectPacket {}
+
+/// Sound Effect packet (clientbound), for a sound event sent by name
+///
+/// Shares its ID with [`SoundEffectPacket`].
+#[doc(alias = "CustomSoundPacket")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamedSoundEffectPacket {
+    /// Identifier of the sound event
+    pub sound_name: McIdentifier,
+    /// Distance the sound is heard from regardless of volume
+    pub fixed_range: Option<f32>,
+    /// Where and how the sound plays
+    pub placement: SoundPlacement,
+}
+
+impl Packet for NamedSoundEffectPacket {
+    const ID: i32 = SoundEffectPacket::ID;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let id = VarInt::read(reader)?.0;
+        if id != 0 {
+            return Err(ServerError::Protocol(format!(
+                "Expected an inline sound event, got registered ID {}",
+                id - 1
+            )));
+        }
+        let sound_name = McIdentifier::read(reader)?;
+        let fixed_range = if read_bool(reader)? {
+            Some(read_float(reader)?)
+        } else {
+            None
+        };
+        Ok(Self {
+            sound_name,
+            fixed_range,
+            placement: SoundPlacement::read(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        VarInt(0).write(writer)?;
+        self.sound_name.write(writer)?;
+        write_bool(self.fixed_range.is_some(), writer)?;
+        if let Some(range) = self.fixed_range {
+            write_float(range, writer)?;
+        }
+        self.placement.write(writer)
+    }
+}
+
+impl ClientboundPacket for NamedSoundEffectPacket {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn placement() -> SoundPlacement {
+        SoundPlacement {
+            source: SoundSource::Block,
+            x: to_fixed_point(1.5),
+            y: to_fixed_point(-0.5),
+            z: 0,
+            volume: 1.0,
+            pitch: 0.5,
+            seed: 7,
+        }
+    }
+
+    #[test]
+    fn test_sound_effect_roundtrip() {
+        let packet = SoundEffectPacket {
+            sound_id: 3,
+            placement: placement(),
+        };
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(&buffer[..6], [4, 4, 0, 0, 0, 12]);
+        assert_eq!(
+            SoundEffectPacket::read(&mut Cursor::new(buffer.clone())).unwrap(),
+            packet
+        );
+        assert!(NamedSoundEffectPacket::read(&mut Cursor::new(buffer)).is_err());
+    }
+
+    #[test]
+    fn test_named_sound_effect_roundtrip() {
+        let packet = NamedSoundEffectPacket {
+            sound_name: McIdentifier::new("custom", "horn").unwrap(),
+            fixed_range: Some(32.0),
+            placement: placement(),
+        };
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(buffer[0], 0);
+        assert_eq!(
+            NamedSoundEffectPacket::read(&mut Cursor::new(buffer.clone())).unwrap(),
+            packet
+        );
+        assert!(SoundEffectPacket::read(&mut Cursor::new(buffer)).is_err());
+    }
+
+    #[test]
+    fn test_fixed_point() {
+        assert_eq!(to_fixed_point(0.125), 1);
+        assert_eq!(to_fixed_point(-2.0), -16);
+        assert_eq!(placement().y, -4);
+    }
+}
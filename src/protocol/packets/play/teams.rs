@@ -0,0 +1,379 @@
+//! Team packets
+//!
+//! Teams group entities, by player name or entity UUID, and control how
+//! their members' names are drawn and whether they collide or hurt each
+//! other.
+
+use crate::error::{Result, ServerError};
+use crate::protocol::packets::{ClientboundPacket, Packet};
+use crate::protocol::text::{NamedColor, TextComponent};
+use crate::protocol::types::{McString, VarInt, read_unsigned_byte, write_unsigned_byte};
+use std::io::{Read, Write};
+
+/// Friendly flag allowing members to hurt each other
+const FRIENDLY_FIRE: u8 = 0x01;
+
+/// Friendly flag letting members see invisible teammates
+const SEE_INVISIBLE: u8 = 0x02;
+
+/// Who sees the name tags of a team's members
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[repr(i32)]
+pub enum Visibility {
+    /// Everyone sees them
+    #[default]
+    Always = 0,
+    /// Nobody sees them
+    Never = 1,
+    /// Only members of the team see them
+    HideForOtherTeams = 2,
+    /// Only players outside the team see them
+    HideForOwnTeam = 3,
+}
+
+impl TryFrom<i32> for Visibility {
+    type Error = ServerError;
+
+    fn try_from(value: i32) -> Result<Self> {
+        match value {
+            0 => Ok(Visibility::Always),
+            1 => Ok(Visibility::Never),
+            2 => Ok(Visibility::HideForOtherTeams),
+            3 => Ok(Visibility::HideForOwnTeam),
+            _ => Err(ServerError::Protocol(format!(
+                "Invalid name tag visibility: {}",
+                value
+            ))),
+        }
+    }
+}
+
+/// Which entities push a team's members
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[repr(i32)]
+pub enum CollisionRule {
+    /// Every entity
+    #[default]
+    Always = 0,
+    /// No entity
+    Never = 1,
+    /// Only members of the team
+    PushOtherTeams = 2,
+    /// Only entities outside the team
+    PushOwnTeam = 3,
+}
+
+impl TryFrom<i32> for CollisionRule {
+    type Error = ServerError;
+
+    fn try_from(value: i32) -> Result<Self> {
+        match value {
+            0 => Ok(CollisionRule::Always),
+            1 => Ok(CollisionRule::Never),
+            2 => Ok(CollisionRule::PushOtherTeams),
+            3 => Ok(CollisionRule::PushOwnTeam),
+            _ => Err(ServerError::Protocol(format!(
+                "Invalid collision rule: {}",
+                value
+            ))),
+        }
+    }
+}
+
+/// Color of the names of a team's members
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TeamColor {
+    /// One of the named chat colors
+    Named(NamedColor),
+    /// No color of its own
+    #[default]
+    Reset,
+}
+
+impl TeamColor {
+    /// Named colors, in the order of their protocol IDs
+    const COLORS: [NamedColor; 16] = [
+        NamedColor::Black,
+        NamedColor::DarkBlue,
+        NamedColor::DarkGreen,
+        NamedColor::DarkAqua,
+        NamedColor::DarkRed,
+        NamedColor::DarkPurple,
+        NamedColor::Gold,
+        NamedColor::Gray,
+        NamedColor::DarkGray,
+        NamedColor::Blue,
+        NamedColor::Green,
+        NamedColor::Aqua,
+        NamedColor::Red,
+        NamedColor::LightPurple,
+        NamedColor::Yellow,
+        NamedColor::White,
+    ];
+
+    /// Protocol ID of the reset formatting code
+    const RESET: i32 = 21;
+
+    /// Protocol ID of the color, the index of its formatting code
+    pub fn id(self) -> i32 {
+        match self {
+            TeamColor::Named(color) => Self::COLORS
+                .iter()
+                .position(|&named| named == color)
+                .map_or(Self::RESET, |index| index as i32),
+            TeamColor::Reset => Self::RESET,
+        }
+    }
+}
+
+impl TryFrom<i32> for TeamColor {
+    type Error = ServerError;
+
+    fn try_from(value: i32) -> Result<Self> {
+        match usize::try_from(value)
+            .ok()
+            .and_then(|i| Self::COLORS.get(i))
+        {
+            Some(&color) => Ok(TeamColor::Named(color)),
+            // Formatting codes such as bold are accepted but have no color
+            None if (16..=Self::RESET).contains(&value) => Ok(TeamColor::Reset),
+            None => Err(ServerError::Protocol(format!(
+                "Invalid team color: {}",
+                value
+            ))),
+        }
+    }
+}
+
+/// How a team and its members are displayed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TeamInfo {
+    /// Name of the team shown to players
+    pub display_name: TextComponent,
+    /// Whether members can hurt each other
+    pub friendly_fire: bool,
+    /// Whether members see invisible teammates
+    pub see_invisible: bool,
+    /// Who sees the members' name tags
+    pub name_tag_visibility: Visibility,
+    /// Which entities push the members
+    pub collision_rule: CollisionRule,
+    /// Color of the members' names
+    pub color: TeamColor,
+    /// Text shown before the members' names
+    pub prefix: TextComponent,
+    /// Text shown after the members' names
+    pub suffix: TextComponent,
+}
+
+impl TeamInfo {
+    /// Read the display of a team
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let display_name = TextComponent::read_nbt(reader)?;
+        let flags = read_unsigned_byte(reader)?;
+        Ok(Self {
+            display_name,
+            friendly_fire: flags & FRIENDLY_FIRE != 0,
+            see_invisible: flags & SEE_INVISIBLE != 0,
+            name_tag_visibility: Visibility::try_from(VarInt::read(reader)?.0)?,
+            collision_rule: CollisionRule::try_from(VarInt::read(reader)?.0)?,
+            color: TeamColor::try_from(VarInt::read(reader)?.0)?,
+            prefix: TextComponent::read_nbt(reader)?,
+            suffix: TextComponent::read_nbt(reader)?,
+        })
+    }
+
+    /// Write the display of a team
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.display_name.write_nbt(writer)?;
+        let mut flags = 0;
+        if self.friendly_fire {
+            flags |= FRIENDLY_FIRE;
+        }
+        if self.see_invisible {
+            flags |= SEE_INVISIBLE;
+        }
+        write_unsigned_byte(flags, writer)?;
+        VarInt(self.name_tag_visibility as i32).write(writer)?;
+        VarInt(self.collision_rule as i32).write(writer)?;
+        VarInt(self.color.id()).write(writer)?;
+        self.prefix.write_nbt(writer)?;
+        self.suffix.write_nbt(writer)
+    }
+}
+
+/// Change made by a [`TeamPacket`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TeamAction {
+    /// Create the team with its first members
+    Create(TeamInfo, Vec<McString>),
+    /// Remove the team
+    Remove,
+    /// Change how the team is displayed
+    UpdateInfo(TeamInfo),
+    /// Add members, taking them out of any other team
+    AddEntities(Vec<McString>),
+    /// Remove members
+    RemoveEntities(Vec<McString>),
+}
+
+/// Read a list of team members
+fn read_entities<R: Read>(reader: &mut R) -> Result<Vec<McString>> {
+    let count = VarInt::read(reader)?.0;
+    if count < 0 {
+        return Err(ServerError::Protocol(format!(
+            "Invalid team member count: {}",
+            count
+        )));
+    }
+    (0..count).map(|_| McString::read(reader)).collect()
+}
+
+/// Write a list of team members
+fn write_entities<W: Write>(entities: &[McString], writer: &mut W) -> Result<()> {
+    VarInt(entities.len() as i32).write(writer)?;
+    for entity in entities {
+        entity.write(writer)?;
+    }
+    Ok(())
+}
+
+/// Update Teams packet (clientbound)
+#[doc(alias = "SetPlayerTeamPacket")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TeamPacket {
+    /// Unique name of the team
+    pub team_name: McString,
+    /// What changes
+    pub action: TeamAction,
+}
+
+impl Packet for TeamPacket {
+    const ID: i32 = 0x66;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let team_name = McString::read(reader)?;
+        let action = match read_unsigned_byte(reader)? {
+            0 => {
+                let info = TeamInfo::read(reader)?;
+                TeamAction::Create(info, read_entities(reader)?)
+            }
+            1 => TeamAction::Remove,
+            2 => TeamAction::UpdateInfo(TeamInfo::read(reader)?),
+            3 => TeamAction::AddEntities(read_entities(reader)?),
+            4 => TeamAction::RemoveEntities(read_entities(reader)?),
+            method => {
+                return Err(ServerError::Protocol(format!(
+                    "Invalid team method: {}",
+                    method
+                )));
+            }
+        };
+        Ok(Self { team_name, action })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.team_name.write(writer)?;
+        match &self.action {
+            TeamAction::Create(info, entities) => {
+                write_unsigned_byte(0, writer)?;
+                info.write(writer)?;
+                write_entities(entities, writer)
+            }
+            TeamAction::Remove => write_unsigned_byte(1, writer),
+            TeamAction::UpdateInfo(info) => {
+                write_unsigned_byte(2, writer)?;
+                info.write(writer)
+            }
+            TeamAction::AddEntities(entities) => {
+                write_unsigned_byte(3, writer)?;
+                write_entities(entities, writer)
+            }
+            TeamAction::RemoveEntities(entities) => {
+                write_unsigned_byte(4, writer)?;
+                write_entities(entities, writer)
+            }
+        }
+    }
+}
+
+impl ClientboundPacket for TeamPacket {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// Write a packet and read it back
+    fn roundtrip(packet: &TeamPacket) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(
+            &TeamPacket::read(&mut Cursor::new(buffer.clone())).unwrap(),
+            packet
+        );
+        buffer
+    }
+
+    #[test]
+    fn test_team_packet_roundtrip() {
+        let info = TeamInfo {
+            display_name: TextComponent::text("Red"),
+            friendly_fire: false,
+            see_invisible: true,
+            name_tag_visibility: Visibility::HideForOtherTeams,
+            collision_rule: CollisionRule::PushOwnTeam,
+            color: TeamColor::Named(NamedColor::Red),
+            prefix: TextComponent::text("[Red] "),
+            suffix: TextComponent::text(""),
+        };
+        let create = TeamPacket {
+            team_name: McString("red".to_string()),
+            action: TeamAction::Create(info.clone(), vec![McString("Steve".to_string())]),
+        };
+        let buffer = roundtrip(&create);
+        assert_eq!(&buffer[..5], b"\x03red\x00");
+        // Members come last, after the suffix
+        assert_eq!(&buffer[buffer.len() - 7..], b"\x01\x05Steve");
+
+        let update = TeamPacket {
+            team_name: McString("red".to_string()),
+            action: TeamAction::UpdateInfo(info),
+        };
+        roundtrip(&update);
+
+        let remove = TeamPacket {
+            team_name: McString("red".to_string()),
+            action: TeamAction::Remove,
+        };
+        assert_eq!(roundtrip(&remove), b"\x03red\x01");
+
+        let members = vec![McString("Alex".to_string())];
+        let add = TeamPacket {
+            team_name: McString("red".to_string()),
+            action: TeamAction::AddEntities(members.clone()),
+        };
+        assert_eq!(roundtrip(&add), b"\x03red\x03\x01\x04Alex");
+        let remove_members = TeamPacket {
+            team_name: McString("red".to_string()),
+            action: TeamAction::RemoveEntities(members),
+        };
+        assert_eq!(roundtrip(&remove_members), b"\x03red\x04\x01\x04Alex");
+    }
+
+    #[test]
+    fn test_team_color_ids() {
+        assert_eq!(TeamColor::Named(NamedColor::Black).id(), 0);
+        assert_eq!(TeamColor::Named(NamedColor::Red).id(), 12);
+        assert_eq!(TeamColor::Named(NamedColor::White).id(), 15);
+        assert_eq!(TeamColor::Reset.id(), 21);
+        assert_eq!(
+            TeamColor::try_from(14).unwrap(),
+            TeamColor::Named(NamedColor::Yellow)
+        );
+        assert_eq!(TeamColor::try_from(17).unwrap(), TeamColor::Reset);
+        assert!(TeamColor::try_from(22).is_err());
+        assert!(Visibility::try_from(4).is_err());
+        assert!(CollisionRule::try_from(-1).is_err());
+    }
+}
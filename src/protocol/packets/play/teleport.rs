@@ -0,0 +1,170 @@
+//! Teleport packets
+//!
+//! The server moves a player by sending a position with a teleport ID. The
+//! client applies it and answers with a confirmation carrying the same ID;
+//! until then, movement packets from the client are stale.
+
+use crate::error::Result;
+use crate::protocol::packets::{ClientboundPacket, Packet, ServerboundPacket};
+use crate::protocol::types::{
+    VarInt, read_double, read_float, read_int, write_double, write_float, write_int,
+};
+use std::io::{Read, Write};
+
+/// Synchronize Player Position packet (clientbound)
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerPositionPacket {
+    /// ID the client must echo in its teleport confirmation
+    pub teleport_id: VarInt,
+    /// X coordinate
+    pub x: f64,
+    /// Y coordinate (feet)
+    pub y: f64,
+    /// Z coordinate
+    pub z: f64,
+    /// X velocity
+    pub velocity_x: f64,
+    /// Y velocity
+    pub velocity_y: f64,
+    /// Z velocity
+    pub velocity_z: f64,
+    /// Yaw in degrees
+    pub yaw: f32,
+    /// Pitch in degrees
+    pub pitch: f32,
+    /// Bit field of values that are relative to the current ones
+    pub flags: i32,
+}
+
+impl PlayerPositionPacket {
+    /// X is relative
+    pub const RELATIVE_X: i32 = 0x0001;
+    /// Y is relative
+    pub const RELATIVE_Y: i32 = 0x0002;
+    /// Z is relative
+    pub const RELATIVE_Z: i32 = 0x0004;
+    /// Yaw is relative
+    pub const RELATIVE_YAW: i32 = 0x0008;
+    /// Pitch is relative
+    pub const RELATIVE_PITCH: i32 = 0x0010;
+    /// X velocity is relative
+    pub const RELATIVE_VELOCITY_X: i32 = 0x0020;
+    /// Y velocity is relative
+    pub const RELATIVE_VELOCITY_Y: i32 = 0x0040;
+    /// Z velocity is relative
+    pub const RELATIVE_VELOCITY_Z: i32 = 0x0080;
+    /// Rotate the velocity by the change in rotation
+    pub const ROTATE_VELOCITY: i32 = 0x0100;
+
+    /// Create an absolute teleport with no velocity
+    pub fn absolute(teleport_id: i32, x: f64, y: f64, z: f64, yaw: f32, pitch: f32) -> Self {
+        Self {
+            teleport_id: VarInt(teleport_id),
+            x,
+            y,
+            z,
+            velocity_x: 0.0,
+            velocity_y: 0.0,
+            velocity_z: 0.0,
+            yaw,
+            pitch,
+            flags: 0,
+        }
+    }
+}
+
+impl Packet for PlayerPositionPacket {
+    const ID: i32 = 0x41;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(PlayerPositionPacket {
+            teleport_id: VarInt::read(reader)?,
+            x: read_double(reader)?,
+            y: read_double(reader)?,
+            z: read_double(reader)?,
+            velocity_x: read_double(reader)?,
+            velocity_y: read_double(reader)?,
+            velocity_z: read_double(reader)?,
+            yaw: read_float(reader)?,
+            pitch: read_float(reader)?,
+            flags: read_int(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.teleport_id.write(writer)?;
+        for value in [
+            self.x,
+            self.y,
+            self.z,
+            self.velocity_x,
+            self.velocity_y,
+            self.velocity_z,
+        ] {
+            write_double(value, writer)?;
+        }
+        write_float(self.yaw, writer)?;
+        write_float(self.pitch, writer)?;
+        write_int(self.flags, writer)
+    }
+}
+
+impl ClientboundPacket for PlayerPositionPacket {}
+
+/// Confirm Teleportation packet (serverbound)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfirmTeleportPacket {
+    /// ID from the Synchronize Player Position packet being confirmed
+    pub teleport_id: VarInt,
+}
+
+impl Packet for ConfirmTeleportPacket {
+    const ID: i32 = 0x00;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(ConfirmTeleportPacket {
+            teleport_id: VarInt::read(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.teleport_id.write(writer)
+    }
+}
+
+impl ServerboundPacket for ConfirmTeleportPacket {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_player_position_roundtrip() {
+        let mut packet = PlayerPositionPacket::absolute(5, 0.5, 64.0, -0.5, 90.0, -10.0);
+        packet.flags = PlayerPositionPacket::RELATIVE_YAW | PlayerPositionPacket::RELATIVE_PITCH;
+
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        // Teleport ID, six doubles, two floats and the flags
+        assert_eq!(buffer.len(), 1 + 6 * 8 + 2 * 4 + 4);
+        assert_eq!(&buffer[buffer.len() - 4..], &[0, 0, 0, 0x18]);
+
+        let decoded = PlayerPositionPacket::read(&mut Cursor::new(buffer)).unwrap();
+        assert_eq!(decoded, packet);
+    }
+
+    #[test]
+    fn test_confirm_teleport_roundtrip() {
+        let packet = ConfirmTeleportPacket {
+            teleport_id: VarInt(300),
+        };
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(buffer, vec![0xAC, 0x02]);
+        assert_eq!(
+            ConfirmTeleportPacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+    }
+}
@@ -0,0 +1,64 @@
+//! World time packets
+
+use crate::error::Result;
+use crate::protocol::packets::{ClientboundPacket, Packet};
+use crate::protocol::types::{read_bool, read_long, write_bool, write_long};
+use std::io::{Read, Write};
+
+/// Update Time packet (clientbound)
+///
+/// Sent every second so the client's clock, which advances on its own,
+/// does not drift from the server's.
+#[doc(alias = "SetTimePacket")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UpdateTimePacket {
+    /// Ticks the world has run for
+    pub world_age: i64,
+    /// Time of day in ticks, where 0 is sunrise and 24000 a full day
+    pub time_of_day: i64,
+    /// Whether the client should advance the time of day between updates
+    pub time_of_day_increasing: bool,
+}
+
+impl Packet for UpdateTimePacket {
+    const ID: i32 = 0x6A;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            world_age: read_long(reader)?,
+            time_of_day: read_long(reader)?,
+            time_of_day_increasing: read_bool(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_long(self.world_age, writer)?;
+        write_long(self.time_of_day, writer)?;
+        write_bool(self.time_of_day_increasing, writer)
+    }
+}
+
+impl ClientboundPacket for UpdateTimePacket {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_update_time_roundtrip() {
+        let packet = UpdateTimePacket {
+            world_age: 24_001,
+            time_of_day: 13_000,
+            time_of_day_increasing: false,
+        };
+        let mut buffer = Vec::new();
+        packet.write(&mut buffer).unwrap();
+        assert_eq!(buffer.len(), 17);
+        assert_eq!(&buffer[8..16], 13_000i64.to_be_bytes());
+        assert_eq!(
+            UpdateTimePacket::read(&mut Cursor::new(buffer)).unwrap(),
+            packet
+        );
+    }
+}
@@ -0,0 +1,174 @@
+//! Title packets
+//!
+//! Titles are large text in the middle of the screen, with an optional
+//! subtitle below. The action bar is a single line above the hotbar.
+
+use crate::error::Result;
+use crate::protocol::packets::{ClientboundPacket, Packet};
+use crate::protocol::text::TextComponent;
+use crate::protocol::types::{read_int, write_int};
+use std::io::{Read, Write};
+
+/// Set Title Text packet (clientbound)
+///
+/// Shows the title, along with the last subtitle sent.
+#[doc(alias = "SetTitlePacket")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetTitleTextPacket {
+    /// Text of the title
+    pub text: TextComponent,
+}
+
+impl Packet for SetTitleTextPacket {
+    const ID: i32 = 0x6B;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            text: TextComponent::read_nbt(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.text.write_nbt(writer)
+    }
+}
+
+impl ClientboundPacket for SetTitleTextPacket {}
+
+/// Set Subtitle Text packet (clientbound)
+///
+/// The subtitle is only shown with the next title.
+#[doc(alias = "SetSubtitlePacket")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetSubtitleTextPacket {
+    /// Text of the subtitle
+    pub text: TextComponent,
+}
+
+impl Packet for SetSubtitleTextPacket {
+    const ID: i32 = 0x69;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            text: TextComponent::read_nbt(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.text.write_nbt(writer)
+    }
+}
+
+impl ClientboundPacket for SetSubtitleTextPacket {}
+
+/// Set Action Bar Text packet (clientbound)
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetActionBarTextPacket {
+    /// Text shown above the hotbar
+    pub text: TextComponent,
+}
+
+impl Packet for SetActionBarTextPacket {
+    const ID: i32 = 0x50;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            text: TextComponent::read_nbt(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.text.write_nbt(writer)
+    }
+}
+
+impl ClientboundPacket for SetActionBarTextPacket {}
+
+/// Set Title Animation Times packet (clientbound)
+///
+/// Times are in ticks and apply to every following title.
+#[doc(alias = "SetTitlesAnimationPacket")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetTitleAnimationTimesPacket {
+    /// Ticks the title takes to fade in
+    pub fade_in: i32,
+    /// Ticks the title stays fully shown
+    pub stay: i32,
+    /// Ticks the title takes to fade out
+    pub fade_out: i32,
+}
+
+impl Default for SetTitleAnimationTimesPacket {
+    /// The times vanilla clients use until told otherwise
+    fn default() -> Self {
+        Self {
+            fade_in: 10,
+            stay: 70,
+            fade_out: 20,
+        }
+    }
+}
+
+impl Packet for SetTitleAnimationTimesPacket {
+    const ID: i32 = 0x6C;
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            fade_in: read_int(reader)?,
+            stay: read_int(reader)?,
+            fade_out: read_int(reader)?,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_int(self.fade_in, writer)?;
+        write_int(self.stay, writer)?;
+        write_int(self.fade_out, writer)
+    }
+}
+
+impl ClientboundPacket for SetTitleAnimationTimesPacket {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_text_packets_roundtrip() {
+        let title = SetTitleTextPacket {
+            text: TextComponent::text("Welcome"),
+        };
+        let mut buffer = Vec::new();
+        title.write(&mut buffer).unwrap();
+        assert_eq!(
+            SetTitleTextPacket::read(&mut Cursor::new(buffer.clone())).unwrap(),
+            title
+        );
+
+        // Every text packet carries just the component
+        let mut action_bar = Vec::new();
+        SetActionBarTextPacket {
+            text: TextComponent::text("Welcome"),
+        }
+        .write(&mut action_bar)
+        .unwrap();
+        assert_eq!(action_bar, buffer);
+    }
+
+    #[test]
+    fn test_animation_times() {
+        let times = SetTitleAnimationTimesPacket {
+            fade_in: 20,
+            stay: 60,
+            fade_out: 1,
+        };
+        let mut buffer = Vec::new();
+        times.write(&mut buffer).unwrap();
+        assert_eq!(buffer, [0, 0, 0, 20, 0, 0, 0, 60, 0, 0, 0, 1]);
+        assert_eq!(
+            SetTitleAnimationTimesPacket::read(&mut Cursor::new(buffer)).unwrap(),
+            times
+        );
+    }
+}
@@ -0,0 +1,270 @@
+//! Text components
+//!
+//! Chat messages, disconnect reasons and other text shown to players are
+//! structured components rather than plain strings. This module provides a
+//! typed [`TextComponent`] that serializes to the same JSON as vanilla.
+
+use crate::error::{Result, ServerError};
+use crate::protocol::types::JsonTextComponent;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+
+/// One of the sixteen named chat colors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NamedColor {
+    /// `#000000`
+    Black,
+    /// `#0000AA`
+    DarkBlue,
+    /// `#00AA00`
+    DarkGreen,
+    /// `#00AAAA`
+    DarkAqua,
+    /// `#AA0000`
+    DarkRed,
+    /// `#AA00AA`
+    DarkPurple,
+    /// `#FFAA00`
+    Gold,
+    /// `#AAAAAA`
+    Gray,
+    /// `#555555`
+    DarkGray,
+    /// `#5555FF`
+    Blue,
+    /// `#55FF55`
+    Green,
+    /// `#55FFFF`
+    Aqua,
+    /// `#FF5555`
+    Red,
+    /// `#FF55FF`
+    LightPurple,
+    /// `#FFFF55`
+    Yellow,
+    /// `#FFFFFF`
+    White,
+}
+
+/// Action run when the player clicks a text component
+///
+/// Uses the 1.21.5 format, where each action has its own named field
+/// instead of a generic `value`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ClickEvent {
+    /// Open a URL in the player's browser, after confirmation
+    OpenUrl {
+        /// URL to open
+        url: String,
+    },
+    /// Run a command as the player
+    RunCommand {
+        /// Command to run, with or without the leading slash
+        command: String,
+    },
+    /// Put a command in the player's chat input
+    SuggestCommand {
+        /// Command to suggest
+        command: String,
+    },
+    /// Turn to a page of the open book
+    ChangePage {
+        /// Page number, starting at 1
+        page: i32,
+    },
+    /// Copy text to the player's clipboard
+    CopyToClipboard {
+        /// Text to copy
+        value: String,
+    },
+}
+
+/// A text component with styling and children
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextComponent {
+    /// Literal text of this component
+    #[serde(default)]
+    pub text: String,
+    /// Text color, inherited from the parent if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<NamedColor>,
+    /// Bold style, inherited from the parent if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bold: Option<bool>,
+    /// Italic style, inherited from the parent if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub italic: Option<bool>,
+    /// Underlined style, inherited from the parent if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub underlined: Option<bool>,
+    /// Action run when the component is clicked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub click_event: Option<ClickEvent>,
+    /// Child components, shown after this one and inheriting its style
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra: Vec<TextComponent>,
+}
+
+impl TextComponent {
+    /// Create an unstyled text component
+    pub fn text(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Start building a text component
+    pub fn builder(text: impl Into<String>) -> TextComponentBuilder {
+        TextComponentBuilder::new(text)
+    }
+
+    /// Get the text of the component and its children, without styling
+    pub fn plain_text(&self) -> String {
+        let mut text = self.text.clone();
+        for child in &self.extra {
+            text.push_str(&child.plain_text());
+        }
+        text
+    }
+
+    /// Serialize the component to Minecraft JSON chat format
+    pub fn to_json_string(&self) -> String {
+        // Every field serializes to a string, bool, number or nested object
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// Read a component sent as NBT, as in play and configuration packets
+    pub fn read_nbt<R: Read>(reader: &mut R) -> Result<Self> {
+        let json = JsonTextComponent::read_nbt(reader)?;
+        serde_json::from_str(&json.0)
+            .map_err(|e| ServerError::Protocol(format!("Unsupported text component: {}", e)))
+    }
+
+    /// Write the component as NBT, as in play and configuration packets
+    pub fn write_nbt<W: Write>(&self, writer: &mut W) -> Result<()> {
+        JsonTextComponent::from(self.clone()).write_nbt(writer)
+    }
+}
+
+impl From<TextComponent> for JsonTextComponent {
+    fn from(component: TextComponent) -> Self {
+        JsonTextComponent(component.to_json_string())
+    }
+}
+
+/// Builder for [`TextComponent`]
+#[derive(Debug, Clone, Default)]
+pub struct TextComponentBuilder {
+    component: TextComponent,
+}
+
+impl TextComponentBuilder {
+    /// Create a builder for a component with the given text
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            component: TextComponent::text(text),
+        }
+    }
+
+    /// Set the text color
+    pub fn color(mut self, color: NamedColor) -> Self {
+        self.component.color = Some(color);
+        self
+    }
+
+    /// Set whether the text is bold
+    pub fn bold(mut self, bold: bool) -> Self {
+        self.component.bold = Some(bold);
+        self
+    }
+
+    /// Set whether the text is italic
+    pub fn italic(mut self, italic: bool) -> Self {
+        self.component.italic = Some(italic);
+        self
+    }
+
+    /// Set whether the text is underlined
+    pub fn underlined(mut self, underlined: bool) -> Self {
+        self.component.underlined = Some(underlined);
+        self
+    }
+
+    /// Set the action run when the component is clicked
+    pub fn click_event(mut self, click_event: ClickEvent) -> Self {
+        self.component.click_event = Some(click_event);
+        self
+    }
+
+    /// Append a child component
+    pub fn extra(mut self, child: TextComponent) -> Self {
+        self.component.extra.push(child);
+        self
+    }
+
+    /// Build the text component
+    pub fn build(self) -> TextComponent {
+        self.component
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_text() {
+        assert_eq!(
+            TextComponent::text("Hello world").to_json_string(),
+            r#"{"text":"Hello world"}"#
+        );
+    }
+
+    #[test]
+    fn test_styled_text() {
+        let component = TextComponent::builder("Hello")
+            .color(NamedColor::DarkRed)
+            .bold(true)
+            .italic(false)
+            .build();
+        assert_eq!(
+            component.to_json_string(),
+            r#"{"text":"Hello","color":"dark_red","bold":true,"italic":false}"#
+        );
+    }
+
+    #[test]
+    fn test_click_event_and_extra() {
+        let component = TextComponent::builder("Click ")
+            .extra(
+                TextComponent::builder("here")
+                    .underlined(true)
+                    .click_event(ClickEvent::RunCommand {
+                        command: "/say hi".to_string(),
+                    })
+                    .build(),
+            )
+            .build();
+        assert_eq!(
+            component.to_json_string(),
+            r#"{"text":"Click ","extra":[{"text":"here","underlined":true,"click_event":{"action":"run_command","command":"/say hi"}}]}"#
+        );
+
+        let parsed: TextComponent = serde_json::from_str(&component.to_json_string()).unwrap();
+        assert_eq!(parsed, component);
+        assert_eq!(component.plain_text(), "Click here");
+    }
+
+    #[test]
+    fn test_into_json_text_component() {
+        let component = TextComponent::builder("Kicked")
+            .color(NamedColor::Red)
+            .build();
+        let json: JsonTextComponent = component.into();
+        assert_eq!(json.0, r#"{"text":"Kicked","color":"red"}"#);
+        assert!(json.to_nbt().is_ok());
+    }
+}
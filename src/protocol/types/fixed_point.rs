@@ -0,0 +1,140 @@
+//! Fixed-point numbers
+//!
+//! Some fields send a fraction as an integer count of `1 / D` units, such as
+//! entity velocities in 1/8000 blocks per tick as a short.
+
+use crate::error::Result;
+use crate::protocol::types::{read_int, read_short, write_int, write_short};
+use std::io::{Read, Write};
+
+/// Integer type a [`FixedPoint`] is sent as
+pub trait FixedPointRepr: Copy + Default + PartialEq + std::fmt::Debug {
+    /// Convert a count of units, rounded and saturated to the type's range
+    fn from_f64(value: f64) -> Self;
+
+    /// Convert to a count of units
+    fn to_f64(self) -> f64;
+
+    /// Read the integer from a reader
+    fn read<R: Read>(reader: &mut R) -> Result<Self>;
+
+    /// Write the integer to a writer
+    fn write<W: Write>(self, writer: &mut W) -> Result<()>;
+}
+
+impl FixedPointRepr for i16 {
+    fn from_f64(value: f64) -> Self {
+        value.round() as i16
+    }
+
+    fn to_f64(self) -> f64 {
+        f64::from(self)
+    }
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        read_short(reader)
+    }
+
+    fn write<W: Write>(self, writer: &mut W) -> Result<()> {
+        write_short(self, writer)
+    }
+}
+
+impl FixedPointRepr for i32 {
+    fn from_f64(value: f64) -> Self {
+        value.round() as i32
+    }
+
+    fn to_f64(self) -> f64 {
+        f64::from(self)
+    }
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        read_int(reader)
+    }
+
+    fn write<W: Write>(self, writer: &mut W) -> Result<()> {
+        write_int(self, writer)
+    }
+}
+
+/// A number sent as an integer count of `1 / D` units
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FixedPoint<const D: i32, T = i32>(pub T);
+
+impl<const D: i32, T: FixedPointRepr> FixedPoint<D, T> {
+    /// Convert a number to the nearest representable value, saturating at
+    /// the ends of the range
+    pub fn from_float(value: f64) -> Self {
+        Self(T::from_f64(value * f64::from(D)))
+    }
+
+    /// Convert to a number
+    pub fn to_float(self) -> f64 {
+        self.0.to_f64() / f64::from(D)
+    }
+
+    /// Read a fixed-point number from a reader
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        T::read(reader).map(Self)
+    }
+
+    /// Write a fixed-point number to a writer
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.0.write(writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// Check that values across a range survive a round trip within half a
+    /// unit
+    fn check_roundtrip<const D: i32, T: FixedPointRepr>(min: f64, max: f64) {
+        let steps = 10_000;
+        for step in 0..=steps {
+            let value = min + (max - min) * f64::from(step) / f64::from(steps);
+            let fixed = FixedPoint::<D, T>::from_float(value);
+            let error = (fixed.to_float() - value).abs();
+            assert!(
+                error <= 0.5 / f64::from(D) + f64::EPSILON,
+                "{} came back as {}",
+                value,
+                fixed.to_float()
+            );
+
+            let mut buffer = Vec::new();
+            fixed.write(&mut buffer).unwrap();
+            assert_eq!(buffer.len(), size_of::<T>());
+            assert_eq!(
+                FixedPoint::<D, T>::read(&mut Cursor::new(buffer)).unwrap(),
+                fixed
+            );
+        }
+    }
+
+    #[test]
+    fn test_roundtrip_within_range() {
+        // Shorts in 1/8000 cover about 4 blocks per tick either way
+        check_roundtrip::<8000, i16>(-4.0, 4.0);
+        check_roundtrip::<4096, i16>(-7.9, 7.9);
+        check_roundtrip::<32, i32>(-30_000_000.0, 30_000_000.0);
+    }
+
+    #[test]
+    fn test_encoding() {
+        assert_eq!(FixedPoint::<8000, i16>::from_float(0.5).0, 4000);
+        assert_eq!(FixedPoint::<8000, i16>::from_float(-1.0).0, -8000);
+        assert_eq!(FixedPoint::<8000, i16>(-4000).to_float(), -0.5);
+
+        // Values out of range saturate instead of wrapping
+        assert_eq!(FixedPoint::<8000, i16>::from_float(10.0).0, i16::MAX);
+        assert_eq!(FixedPoint::<8000, i16>::from_float(-10.0).0, i16::MIN);
+
+        let mut buffer = Vec::new();
+        FixedPoint::<8000, i16>(4000).write(&mut buffer).unwrap();
+        assert_eq!(buffer, [0x0F, 0xA0]);
+    }
+}
@@ -0,0 +1,180 @@
+//! Namespaced identifiers
+//!
+//! Registries, dimensions, blocks and most other game objects are referred to
+//! by identifiers such as `minecraft:stone`, made of a namespace and a path.
+
+use crate::error::{Result, ServerError};
+use crate::protocol::types::McString;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{Read, Write};
+use std::str::FromStr;
+
+/// A namespaced identifier (e.g. `minecraft:stone`)
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct McIdentifier {
+    /// Namespace, such as `minecraft` or a mod ID
+    namespace: String,
+    /// Path within the namespace
+    path: String,
+}
+
+impl McIdentifier {
+    /// Namespace used when an identifier does not specify one
+    pub const DEFAULT_NAMESPACE: &str = "minecraft";
+
+    /// Create an identifier, validating both parts
+    ///
+    /// Namespaces may contain `a-z`, `0-9`, `_`, `-` and `.`; paths may also
+    /// contain `/`.
+    pub fn new(namespace: impl Into<String>, path: impl Into<String>) -> Result<Self> {
+        let namespace = namespace.into();
+        let path = path.into();
+
+        if !namespace.chars().all(is_namespace_char) {
+            return Err(ServerError::Protocol(format!(
+                "Invalid identifier namespace {:?}: only a-z, 0-9, '_', '-' and '.' are allowed",
+                namespace
+            )));
+        }
+        if !path.chars().all(|c| is_namespace_char(c) || c == '/') {
+            return Err(ServerError::Protocol(format!(
+                "Invalid identifier path {:?}: only a-z, 0-9, '_', '-', '.' and '/' are allowed",
+                path
+            )));
+        }
+
+        Ok(Self { namespace, path })
+    }
+
+    /// Create an identifier in the `minecraft` namespace
+    ///
+    /// Meant for the server's own vanilla identifiers; the path is only
+    /// validated in debug builds.
+    pub fn minecraft(path: &str) -> Self {
+        debug_assert!(
+            path.chars().all(|c| is_namespace_char(c) || c == '/'),
+            "invalid identifier path {path:?}"
+        );
+        Self {
+            namespace: Self::DEFAULT_NAMESPACE.to_string(),
+            path: path.to_string(),
+        }
+    }
+
+    /// Namespace of the identifier
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    /// Path of the identifier
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Read an identifier from a reader
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        McString::read(reader)?.0.parse()
+    }
+
+    /// Write an identifier to a writer
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        McString(self.to_string()).write(writer)
+    }
+}
+
+/// Whether a character is allowed in an identifier namespace
+fn is_namespace_char(c: char) -> bool {
+    matches!(c, 'a'..='z' | '0'..='9' | '_' | '-' | '.')
+}
+
+impl FromStr for McIdentifier {
+    type Err = ServerError;
+
+    /// Parse `namespace:path`, or just `path` for the `minecraft` namespace
+    fn from_str(identifier: &str) -> Result<Self> {
+        let (namespace, path) = identifier
+            .split_once(':')
+            .unwrap_or((Self::DEFAULT_NAMESPACE, identifier));
+        Self::new(namespace, path)
+    }
+}
+
+impl TryFrom<String> for McIdentifier {
+    type Error = ServerError;
+
+    fn try_from(identifier: String) -> Result<Self> {
+        identifier.parse()
+    }
+}
+
+impl From<McIdentifier> for String {
+    fn from(identifier: McIdentifier) -> Self {
+        identifier.to_string()
+    }
+}
+
+impl fmt::Display for McIdentifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.namespace, self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_parse_identifier() {
+        let identifier: McIdentifier = "mymod:blocks/custom_block".parse().unwrap();
+        assert_eq!(identifier.namespace(), "mymod");
+        assert_eq!(identifier.path(), "blocks/custom_block");
+        assert_eq!(identifier.to_string(), "mymod:blocks/custom_block");
+
+        // The namespace defaults to minecraft
+        let identifier: McIdentifier = "stone".parse().unwrap();
+        assert_eq!(identifier, McIdentifier::minecraft("stone"));
+    }
+
+    #[test]
+    fn test_invalid_identifiers() {
+        for identifier in [
+            "Minecraft:stone",
+            "a/b:stone",
+            "minecraft:Stone",
+            "a:b:c",
+            "x y",
+        ] {
+            assert!(
+                identifier.parse::<McIdentifier>().is_err(),
+                "{identifier} should be rejected"
+            );
+        }
+
+        let error = "minecraft:Stone".parse::<McIdentifier>().unwrap_err();
+        assert!(error.to_string().contains("Invalid identifier path"));
+    }
+
+    #[test]
+    fn test_identifier_serialization() {
+        let identifier = McIdentifier::minecraft("overworld");
+
+        let mut buffer = Vec::new();
+        identifier.write(&mut buffer).unwrap();
+        assert_eq!(&buffer[1..], b"minecraft:overworld");
+        assert_eq!(
+            McIdentifier::read(&mut Cursor::new(buffer)).unwrap(),
+            identifier
+        );
+
+        let json = serde_json::to_string(&identifier).unwrap();
+        assert_eq!(json, r#""minecraft:overworld""#);
+        assert_eq!(
+            serde_json::from_str::<McIdentifier>(&json).unwrap(),
+            identifier
+        );
+        assert!(serde_json::from_str::<McIdentifier>(r#""bad id""#).is_err());
+    }
+}
@@ -3,7 +3,16 @@
 //! This module implements all the data types used in the Minecraft protocol,
 //! including VarInt, VarLong, String, and other composite types.
 
+pub mod fixed_point;
+pub mod identifier;
+pub mod slot;
+
+pub use fixed_point::FixedPoint;
+pub use identifier::McIdentifier;
+pub use slot::Slot;
+
 use crate::error::{Result, ServerError};
+use crate::protocol::nbt::{NbtCompound, NbtTag};
 use serde_json::Value as JsonValue;
 use std::io::{Read, Write};
 use uuid::Uuid;
@@ -265,8 +274,12 @@ impl From<McString> for String {
     }
 }
 
-/// A Minecraft position (3D coordinates packed into a single i64)
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A Minecraft block position (3D coordinates packed into a single i64)
+///
+/// X and Z take 26 bits each and Y takes 12 bits, so X and Z range from
+/// -33554432 to 33554431 and Y from -2048 to 2047.
+#[doc(alias = "BlockPosition")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Deserialize)]
 pub struct Position {
     /// X coordinate
     pub x: i32,
@@ -282,27 +295,61 @@ impl Position {
         Self { x, y, z }
     }
 
-    /// Read a position from a reader
-    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
-        let mut bytes = [0u8; 8];
-        reader.read_exact(&mut bytes)?;
-        let val = i64::from_be_bytes(bytes);
+    /// Pack the position into its wire representation
+    ///
+    /// Coordinates outside the encodable range wrap around.
+    pub fn pack(&self) -> i64 {
+        ((self.x as i64 & 0x3FFFFFF) << 38)
+            | ((self.z as i64 & 0x3FFFFFF) << 12)
+            | (self.y as i64 & 0xFFF)
+    }
 
-        let x = (val >> 38) as i32;
-        let y = (val << 52 >> 52) as i32;
-        let z = (val << 26 >> 38) as i32;
+    /// Unpack a position from its wire representation
+    pub fn unpack(packed: i64) -> Self {
+        // Arithmetic shifts sign-extend each field
+        let x = (packed >> 38) as i32;
+        let y = (packed << 52 >> 52) as i32;
+        let z = (packed << 26 >> 38) as i32;
+        Position::new(x, y, z)
+    }
 
-        Ok(Position::new(x, y, z))
+    /// Read a position from a reader
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self::unpack(read_long(reader)?))
     }
 
     /// Write a position to a writer
     pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
-        let val = ((self.x as i64 & 0x3FFFFFF) << 38)
-            | ((self.z as i64 & 0x3FFFFFF) << 12)
-            | (self.y as i64 & 0xFFF);
+        write_long(self.pack(), writer)
+    }
+}
 
-        writer.write_all(&val.to_be_bytes())?;
-        Ok(())
+/// A rotation angle in steps of 1/256 of a full turn
+///
+/// Used for entity rotations, which are sent as a single byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Angle(pub u8);
+
+impl Angle {
+    /// Convert an angle in degrees, wrapping it to a single turn
+    pub fn from_degrees(degrees: f32) -> Self {
+        // Casting through i64 wraps negative and out of range angles
+        Angle((degrees / 360.0 * 256.0).round() as i64 as u8)
+    }
+
+    /// Convert the angle to degrees, in the range `[0, 360)`
+    pub fn to_degrees(self) -> f32 {
+        self.0 as f32 * 360.0 / 256.0
+    }
+
+    /// Read an angle from a reader
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        read_unsigned_byte(reader).map(Angle)
+    }
+
+    /// Write an angle to a writer
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_unsigned_byte(self.0, writer)
     }
 }
 
@@ -310,16 +357,19 @@ impl Position {
 pub type McUuid = Uuid;
 
 /// Read a UUID from a reader
+///
+/// UUIDs are sent as two big-endian longs, most significant bits first.
 pub fn read_uuid<R: Read>(reader: &mut R) -> Result<McUuid> {
-    let mut bytes = [0u8; 16];
-    reader.read_exact(&mut bytes)?;
-    Ok(Uuid::from_bytes(bytes))
+    let most_significant = read_long(reader)? as u64;
+    let least_significant = read_long(reader)? as u64;
+    Ok(Uuid::from_u64_pair(most_significant, least_significant))
 }
 
 /// Write a UUID to a writer
 pub fn write_uuid<W: Write>(uuid: &McUuid, writer: &mut W) -> Result<()> {
-    writer.write_all(uuid.as_bytes())?;
-    Ok(())
+    let (most_significant, least_significant) = uuid.as_u64_pair();
+    write_long(most_significant as i64, writer)?;
+    write_long(least_significant as i64, writer)
 }
 
 /// Read a boolean from a reader
@@ -387,6 +437,55 @@ pub fn write_int<W: Write>(value: i32, writer: &mut W) -> Result<()> {
     Ok(())
 }
 
+/// Read a short (i16) from a reader
+pub fn read_short<R: Read>(reader: &mut R) -> Result<i16> {
+    let mut bytes = [0u8; 2];
+    reader.read_exact(&mut bytes)?;
+    Ok(i16::from_be_bytes(bytes))
+}
+
+/// Write a short (i16) to a writer
+pub fn write_short<W: Write>(value: i16, writer: &mut W) -> Result<()> {
+    writer.write_all(&value.to_be_bytes())?;
+    Ok(())
+}
+
+/// Read a float (f32) from a reader
+pub fn read_float<R: Read>(reader: &mut R) -> Result<f32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(f32::from_be_bytes(bytes))
+}
+
+/// Write a float (f32) to a writer
+pub fn write_float<W: Write>(value: f32, writer: &mut W) -> Result<()> {
+    writer.write_all(&value.to_be_bytes())?;
+    Ok(())
+}
+
+/// Read a double (f64) from a reader
+pub fn read_double<R: Read>(reader: &mut R) -> Result<f64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(f64::from_be_bytes(bytes))
+}
+
+/// Write a double (f64) to a writer
+pub fn write_double<W: Write>(value: f64, writer: &mut W) -> Result<()> {
+    writer.write_all(&value.to_be_bytes())?;
+    Ok(())
+}
+
+/// Read a VarLong-encoded long (i64) from a reader
+pub fn read_var_long<R: Read>(reader: &mut R) -> Result<i64> {
+    VarLong::read(reader).map(i64::from)
+}
+
+/// Write a long (i64) to a writer as a VarLong
+pub fn write_var_long<W: Write>(value: i64, writer: &mut W) -> Result<()> {
+    VarLong(value).write(writer)
+}
+
 /// A byte array with VarInt length prefix
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ByteArray(pub Vec<u8>);
@@ -454,15 +553,17 @@ impl From<ByteArray> for Vec<u8> {
 pub struct JsonTextComponent(pub String);
 
 impl JsonTextComponent {
+    /// Create a text component from a JSON string, validating it
+    pub fn new(json: String) -> Result<Self> {
+        serde_json::from_str::<JsonValue>(&json)
+            .map_err(|_| ServerError::Protocol("Invalid JSON text component".to_string()))?;
+        Ok(JsonTextComponent(json))
+    }
+
     /// Read a JSON text component from a reader
     pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
         let json_string = McString::read(reader)?;
-
-        // Validate that it's valid JSON
-        serde_json::from_str::<JsonValue>(&json_string.0)
-            .map_err(|_| ServerError::Protocol("Invalid JSON text component".to_string()))?;
-
-        Ok(JsonTextComponent(json_string.0))
+        Self::new(json_string.0)
     }
 
     /// Write a JSON text component to a writer
@@ -471,6 +572,27 @@ impl JsonTextComponent {
         mc_string.write(writer)
     }
 
+    /// Read a text component sent as network NBT
+    ///
+    /// Since 1.20.3, text components outside the login state are sent as NBT
+    /// rather than JSON strings.
+    pub fn read_nbt<R: Read>(reader: &mut R) -> Result<Self> {
+        let json = text_nbt_to_json(&NbtTag::read_network(reader)?);
+        Ok(JsonTextComponent(json.to_string()))
+    }
+
+    /// Write the text component as network NBT
+    pub fn write_nbt<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.to_nbt()?.write_network(writer)
+    }
+
+    /// Convert the text component to its NBT form
+    pub fn to_nbt(&self) -> Result<NbtTag> {
+        let json = serde_json::from_str::<JsonValue>(&self.0)
+            .map_err(|_| ServerError::Protocol("Invalid JSON text component".to_string()))?;
+        text_json_to_nbt(&json)
+    }
+
     /// Create a simple text component
     pub fn text(text: &str) -> Self {
         let json = serde_json::json!({
@@ -492,6 +614,89 @@ impl From<&str> for JsonTextComponent {
     }
 }
 
+/// Convert a JSON text component value to NBT
+///
+/// NBT lists must hold a single tag type, so the elements of mixed lists are
+/// wrapped in compounds: strings as `{"text": ...}` and anything else under
+/// an empty key, which is how the client unwraps heterogeneous lists.
+fn text_json_to_nbt(value: &JsonValue) -> Result<NbtTag> {
+    match value {
+        JsonValue::Null => Err(ServerError::Protocol(
+            "Null in JSON text component".to_string(),
+        )),
+        JsonValue::Bool(value) => Ok(NbtTag::from(*value)),
+        JsonValue::Number(number) => Ok(match number.as_i64() {
+            Some(value) => i32::try_from(value).map_or(NbtTag::Long(value), NbtTag::Int),
+            None => NbtTag::Double(number.as_f64().unwrap_or_default()),
+        }),
+        JsonValue::String(value) => Ok(NbtTag::String(value.clone())),
+        JsonValue::Array(items) => {
+            let tags = items
+                .iter()
+                .map(text_json_to_nbt)
+                .collect::<Result<Vec<_>>>()?;
+            let mixed = tags.windows(2).any(|pair| pair[0].id() != pair[1].id());
+            if !mixed {
+                return Ok(NbtTag::List(tags));
+            }
+
+            Ok(NbtTag::List(
+                tags.into_iter()
+                    .map(|tag| match tag {
+                        NbtTag::Compound(_) => tag,
+                        NbtTag::String(text) => NbtCompound::new().with("text", text).into(),
+                        other => NbtCompound::new().with("", other).into(),
+                    })
+                    .collect(),
+            ))
+        }
+        JsonValue::Object(map) => map
+            .iter()
+            .map(|(key, value)| Ok((key.clone(), text_json_to_nbt(value)?)))
+            .collect::<Result<NbtCompound>>()
+            .map(NbtTag::Compound),
+    }
+}
+
+/// Convert an NBT text component back to JSON
+fn text_nbt_to_json(tag: &NbtTag) -> JsonValue {
+    match tag {
+        NbtTag::Byte(value @ (0 | 1)) => JsonValue::Bool(*value == 1),
+        NbtTag::Byte(value) => (*value).into(),
+        NbtTag::Short(value) => (*value).into(),
+        NbtTag::Int(value) => (*value).into(),
+        NbtTag::Long(value) => (*value).into(),
+        NbtTag::Float(value) => (*value).into(),
+        NbtTag::Double(value) => (*value).into(),
+        NbtTag::String(value) => value.clone().into(),
+        NbtTag::ByteArray(values) => {
+            JsonValue::Array(values.iter().map(|&value| value.into()).collect())
+        }
+        NbtTag::IntArray(values) => {
+            JsonValue::Array(values.iter().map(|&value| value.into()).collect())
+        }
+        NbtTag::LongArray(values) => {
+            JsonValue::Array(values.iter().map(|&value| value.into()).collect())
+        }
+        NbtTag::List(tags) => tags
+            .iter()
+            .map(|tag| match tag.as_compound() {
+                // Unwrap the elements of heterogeneous lists
+                Some(compound) if compound.len() == 1 && compound.contains_key("") => {
+                    compound.get("").map(text_nbt_to_json).unwrap_or_default()
+                }
+                _ => text_nbt_to_json(tag),
+            })
+            .collect(),
+        NbtTag::Compound(compound) => JsonValue::Object(
+            compound
+                .iter()
+                .map(|(key, value)| (key.to_string(), text_nbt_to_json(value)))
+                .collect(),
+        ),
+    }
+}
+
 /// An identifier (namespaced string)
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Identifier(pub String);
@@ -573,6 +778,134 @@ impl<T> From<Optional<T>> for Option<T> {
     }
 }
 
+/// A bit set, sent as a VarInt-prefixed array of longs
+///
+/// Bit `i` is stored in long `i / 64` at position `i % 64`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BitSet(pub Vec<i64>);
+
+impl BitSet {
+    /// Maximum number of longs accepted when reading a bit set
+    const MAX_LONGS: usize = 1 << 16;
+
+    /// Create an empty bit set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check whether a bit is set
+    pub fn get(&self, index: usize) -> bool {
+        self.0
+            .get(index / 64)
+            .is_some_and(|word| (word >> (index % 64)) & 1 != 0)
+    }
+
+    /// Set a bit, growing the set as needed
+    pub fn set(&mut self, index: usize, value: bool) {
+        let word = index / 64;
+        if word >= self.0.len() {
+            if !value {
+                return;
+            }
+            self.0.resize(word + 1, 0);
+        }
+
+        if value {
+            self.0[word] |= 1 << (index % 64);
+        } else {
+            self.0[word] &= !(1 << (index % 64));
+        }
+    }
+
+    /// Read a bit set from a reader
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let length = VarInt::read(reader)?;
+
+        if length.0 < 0 || length.0 as usize > Self::MAX_LONGS {
+            return Err(ServerError::Protocol(format!(
+                "Invalid bit set length: {}",
+                length.0
+            )));
+        }
+
+        let longs = (0..length.0)
+            .map(|_| read_long(reader))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(BitSet(longs))
+    }
+
+    /// Write a bit set to a writer
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        VarInt(self.0.len() as i32).write(writer)?;
+        for &long in &self.0 {
+            write_long(long, writer)?;
+        }
+        Ok(())
+    }
+}
+
+/// A bit set of a length known to both sides, sent without a length prefix
+///
+/// Bits are packed into `ceil(length / 8)` bytes, least significant bit of
+/// the first byte first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixedBitSet {
+    /// Number of bits
+    length: usize,
+    /// Packed bits
+    bytes: Vec<u8>,
+}
+
+impl FixedBitSet {
+    /// Create a bit set with all bits cleared
+    pub fn new(length: usize) -> Self {
+        Self {
+            length,
+            bytes: vec![0; length.div_ceil(8)],
+        }
+    }
+
+    /// Number of bits in the set
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    /// Whether the set has no bits
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// Check whether a bit is set
+    pub fn get(&self, index: usize) -> bool {
+        index < self.length && (self.bytes[index / 8] >> (index % 8)) & 1 != 0
+    }
+
+    /// Set a bit, ignoring indices past the end of the set
+    pub fn set(&mut self, index: usize, value: bool) {
+        if index >= self.length {
+            return;
+        }
+        if value {
+            self.bytes[index / 8] |= 1 << (index % 8);
+        } else {
+            self.bytes[index / 8] &= !(1 << (index % 8));
+        }
+    }
+
+    /// Read a bit set of the given length from a reader
+    pub fn read<R: Read>(reader: &mut R, length: usize) -> Result<Self> {
+        let mut set = Self::new(length);
+        reader.read_exact(&mut set.bytes)?;
+        Ok(set)
+    }
+
+    /// Write the bit set to a writer
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(&self.bytes)?;
+        Ok(())
+    }
+}
+
 /// Specialized string types with length limits as defined in the protocol
 /// Server address string (max 255 characters)
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -697,6 +1030,97 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_varlong_encoding() {
+        // Sample values from the protocol documentation
+        let samples: [(i64, &[u8]); 8] = [
+            (0, &[0x00]),
+            (1, &[0x01]),
+            (127, &[0x7f]),
+            (128, &[0x80, 0x01]),
+            (2147483647, &[0xff, 0xff, 0xff, 0xff, 0x07]),
+            (
+                9223372036854775807,
+                &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f],
+            ),
+            (
+                -1,
+                &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01],
+            ),
+            (
+                -9223372036854775808,
+                &[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x01],
+            ),
+        ];
+
+        for (value, encoded) in samples {
+            let mut buffer = Vec::new();
+            write_var_long(value, &mut buffer).unwrap();
+            assert_eq!(buffer, encoded);
+            assert_eq!(read_var_long(&mut Cursor::new(encoded)).unwrap(), value);
+        }
+
+        // An eleventh byte is never valid
+        let too_long = [0xff; VarLong::MAX_SIZE + 1];
+        assert!(VarLong::read(&mut Cursor::new(too_long)).is_err());
+    }
+
+    #[test]
+    fn test_position_encoding() {
+        // Sample value from the protocol documentation
+        let position = Position::new(18357644, 831, -20882616);
+        assert_eq!(position.pack(), 0x4607_632C_15B4_833F);
+
+        let limits = [
+            Position::new(33554431, 2047, 33554431),
+            Position::new(-33554432, -2048, -33554432),
+            Position::new(-33554432, 2047, 33554431),
+            Position::new(33554431, -2048, -33554432),
+            Position::new(-1, -1, -1),
+            Position::new(0, 0, 0),
+        ];
+        for position in limits {
+            assert_eq!(Position::unpack(position.pack()), position);
+
+            let mut buffer = Vec::new();
+            position.write(&mut buffer).unwrap();
+            assert_eq!(buffer, position.pack().to_be_bytes());
+            assert_eq!(Position::read(&mut Cursor::new(buffer)).unwrap(), position);
+        }
+    }
+
+    #[test]
+    fn test_angle_conversion() {
+        let samples = [(0.0, 0), (90.0, 64), (180.0, 128), (270.0, 192), (360.0, 0)];
+        for (degrees, steps) in samples {
+            let angle = Angle::from_degrees(degrees);
+            assert_eq!(angle, Angle(steps));
+            assert_eq!(angle.to_degrees(), degrees % 360.0);
+        }
+        assert_eq!(Angle::from_degrees(-90.0), Angle(192));
+
+        let mut buffer = Vec::new();
+        Angle(64).write(&mut buffer).unwrap();
+        assert_eq!(buffer, [64]);
+        assert_eq!(Angle::read(&mut Cursor::new(buffer)).unwrap(), Angle(64));
+    }
+
+    #[test]
+    fn test_uuid_encoding() {
+        let uuid = Uuid::parse_str("550e8400-e29b-41d4-a716-446655440000").unwrap();
+
+        let mut buffer = Vec::new();
+        write_uuid(&uuid, &mut buffer).unwrap();
+        assert_eq!(
+            buffer,
+            [
+                0x55, 0x0e, 0x84, 0x00, 0xe2, 0x9b, 0x41, 0xd4, 0xa7, 0x16, 0x44, 0x66, 0x55, 0x44,
+                0x00, 0x00
+            ]
+        );
+        assert_eq!(read_uuid(&mut Cursor::new(buffer)).unwrap(), uuid);
+    }
+
     #[test]
     fn test_string_roundtrip() {
         let test_strings = ["", "Hello", "Hello, 世界!", "🚀"];
@@ -741,6 +1165,41 @@ mod tests {
         assert_eq!(component, decoded);
     }
 
+    #[test]
+    fn test_json_text_component_validation() {
+        assert!(JsonTextComponent::new(r#"{"text":"hi"}"#.to_string()).is_ok());
+        assert!(JsonTextComponent::new("{not json".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_json_text_component_nbt() {
+        let component = JsonTextComponent::new(
+            r#"{"text":"Kicked","bold":true,"extra":["by ",{"text":"admin","color":"red"}]}"#
+                .to_string(),
+        )
+        .unwrap();
+
+        let nbt = component.to_nbt().unwrap();
+        let compound = nbt.as_compound().unwrap();
+        assert_eq!(compound.get("bold"), Some(&NbtTag::Byte(1)));
+        // Strings in a mixed list are wrapped as text components
+        let NbtTag::List(extra) = compound.get("extra").unwrap() else {
+            unreachable!("extra is a list");
+        };
+        assert_eq!(
+            extra[0].as_compound().unwrap().get("text"),
+            Some(&"by ".into())
+        );
+
+        let mut buffer = Vec::new();
+        component.write_nbt(&mut buffer).unwrap();
+        let decoded = JsonTextComponent::read_nbt(&mut Cursor::new(buffer)).unwrap();
+        let decoded: JsonValue = serde_json::from_str(&decoded.0).unwrap();
+        assert_eq!(decoded["bold"], JsonValue::Bool(true));
+        assert_eq!(decoded["extra"][0]["text"], "by ");
+        assert_eq!(decoded["extra"][1]["color"], "red");
+    }
+
     #[test]
     fn test_identifier() {
         let identifier = Identifier::new("minecraft", "stone");
@@ -782,4 +1241,37 @@ mod tests {
         let decoded = read_int(&mut cursor).unwrap();
         assert_eq!(value, decoded);
     }
+
+    #[test]
+    fn test_bit_set() {
+        let mut bits = BitSet::new();
+        bits.set(0, true);
+        bits.set(65, true);
+        bits.set(200, false);
+        assert!(bits.get(0) && bits.get(65));
+        assert!(!bits.get(1) && !bits.get(200));
+        assert_eq!(bits.0, vec![1, 2]);
+
+        let mut buffer = Vec::new();
+        bits.write(&mut buffer).unwrap();
+        assert_eq!(buffer.len(), 1 + 16);
+        let decoded = BitSet::read(&mut Cursor::new(buffer)).unwrap();
+        assert_eq!(bits, decoded);
+    }
+
+    #[test]
+    fn test_fixed_bit_set() {
+        let mut bits = FixedBitSet::new(20);
+        bits.set(0, true);
+        bits.set(19, true);
+        bits.set(20, true);
+        assert!(bits.get(0) && bits.get(19));
+        assert!(!bits.get(1) && !bits.get(20));
+
+        let mut buffer = Vec::new();
+        bits.write(&mut buffer).unwrap();
+        assert_eq!(buffer, [0x01, 0x00, 0x08]);
+        let decoded = FixedBitSet::read(&mut Cursor::new(buffer), 20).unwrap();
+        assert_eq!(bits, decoded);
+    }
 }
@@ -0,0 +1,165 @@
+//! Inventory slots
+//!
+//! Since 1.20.5 an item stack is sent as a count, an item ID and a list of
+//! data components, which replaced the free-form NBT tag items used to carry.
+
+use crate::error::{Result, ServerError};
+use crate::protocol::nbt::NbtTag;
+use crate::protocol::types::VarInt;
+use std::io::{Read, Write};
+
+/// ID of the `minecraft:custom_data` data component, an NBT compound
+const CUSTOM_DATA_COMPONENT: i32 = 0;
+
+/// An inventory slot, either empty or holding an item stack
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Slot {
+    /// Item ID in the `minecraft:item` registry, or `None` for an empty slot
+    pub item_id: Option<i32>,
+    /// Number of items in the stack
+    pub count: u8,
+    /// Custom NBT data of the stack, sent as its `minecraft:custom_data`
+    /// component
+    pub nbt: Option<NbtTag>,
+}
+
+impl Slot {
+    /// Create an empty slot
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    /// Create a slot holding a stack of an item without custom data
+    pub fn new(item_id: i32, count: u8) -> Self {
+        Self {
+            item_id: Some(item_id),
+            count,
+            nbt: None,
+        }
+    }
+
+    /// Set the custom NBT data of the stack
+    pub fn with_nbt(mut self, nbt: NbtTag) -> Self {
+        self.nbt = Some(nbt);
+        self
+    }
+
+    /// Whether the slot holds no items
+    pub fn is_empty(&self) -> bool {
+        self.item_id.is_none() || self.count == 0
+    }
+
+    /// Read a slot from a reader
+    ///
+    /// Only the custom data component is supported, since the encoding of
+    /// the other components depends on their type.
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let count = VarInt::read(reader)?.0;
+        if count <= 0 {
+            return Ok(Self::empty());
+        }
+        let count = u8::try_from(count)
+            .map_err(|_| ServerError::Protocol(format!("Item count too large: {}", count)))?;
+
+        let item_id = VarInt::read(reader)?.0;
+        let added_components = VarInt::read(reader)?.0;
+        let removed_components = VarInt::read(reader)?.0;
+        if removed_components != 0 {
+            return Err(ServerError::Protocol(
+                "Removing item components is not supported".to_string(),
+            ));
+        }
+
+        let mut nbt = None;
+        for _ in 0..added_components {
+            match VarInt::read(reader)?.0 {
+                CUSTOM_DATA_COMPONENT => nbt = Some(NbtTag::read_network(reader)?),
+                component => {
+                    return Err(ServerError::Protocol(format!(
+                        "Unsupported item component: {}",
+                        component
+                    )));
+                }
+            }
+        }
+
+        Ok(Self {
+            item_id: Some(item_id),
+            count,
+            nbt,
+        })
+    }
+
+    /// Write a slot to a writer
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let Some(item_id) = self.item_id.filter(|_| self.count > 0) else {
+            return VarInt(0).write(writer);
+        };
+
+        VarInt(self.count as i32).write(writer)?;
+        VarInt(item_id).write(writer)?;
+        VarInt(self.nbt.is_some() as i32).write(writer)?;
+        VarInt(0).write(writer)?; // Components to remove
+
+        if let Some(nbt) = &self.nbt {
+            VarInt(CUSTOM_DATA_COMPONENT).write(writer)?;
+            nbt.write_network(writer)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::nbt::NbtCompound;
+    use std::io::Cursor;
+
+    fn roundtrip(slot: &Slot) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        slot.write(&mut buffer).unwrap();
+        assert_eq!(&Slot::read(&mut Cursor::new(buffer.clone())).unwrap(), slot);
+        buffer
+    }
+
+    #[test]
+    fn test_empty_slot() {
+        assert_eq!(roundtrip(&Slot::empty()), [0x00]);
+
+        // A stack of zero items is empty as well
+        let mut buffer = Vec::new();
+        Slot::new(1, 0).write(&mut buffer).unwrap();
+        assert_eq!(buffer, [0x00]);
+    }
+
+    #[test]
+    fn test_item_stack() {
+        // 64 stone (item ID 1), no components added or removed
+        assert_eq!(roundtrip(&Slot::new(1, 64)), [0x40, 0x01, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn test_item_stack_with_nbt() {
+        let enchantment = NbtCompound::new()
+            .with("id", "minecraft:sharpness")
+            .with("lvl", 5i16);
+        let nbt = NbtTag::Compound(
+            NbtCompound::new().with("Enchantments", vec![NbtTag::Compound(enchantment)]),
+        );
+        let slot = Slot::new(840, 1).with_nbt(nbt.clone());
+
+        let buffer = roundtrip(&slot);
+        assert_eq!(&buffer[..6], [0x01, 0xC8, 0x06, 0x01, 0x00, 0x00]);
+
+        let mut expected_nbt = Vec::new();
+        nbt.write_network(&mut expected_nbt).unwrap();
+        assert_eq!(&buffer[6..], expected_nbt);
+    }
+
+    #[test]
+    fn test_unsupported_component() {
+        // One stone with a max stack size component
+        let data = [0x01, 0x01, 0x01, 0x00, 0x01, 0x10];
+        assert!(Slot::read(&mut Cursor::new(data)).is_err());
+    }
+}
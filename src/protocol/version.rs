@@ -0,0 +1,86 @@
+//! Protocol versions
+//!
+//! Clients announce their protocol version in the handshake. Each version
+//! covers one or more Minecraft releases that speak the same protocol.
+
+use std::fmt;
+
+/// A protocol version and the releases it belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(i32)]
+pub enum ProtocolVersion {
+    /// 1.20.5 and 1.20.6
+    V1_20_5 = 766,
+    /// 1.21 and 1.21.1
+    V1_21 = 767,
+    /// 1.21.2 and 1.21.3
+    V1_21_2 = 768,
+    /// 1.21.4
+    V1_21_4 = 769,
+    /// 1.21.5
+    V1_21_5 = 770,
+    /// 1.21.6
+    V1_21_6 = 771,
+}
+
+impl ProtocolVersion {
+    /// Every known version, oldest first
+    pub const ALL: [ProtocolVersion; 6] = [
+        ProtocolVersion::V1_20_5,
+        ProtocolVersion::V1_21,
+        ProtocolVersion::V1_21_2,
+        ProtocolVersion::V1_21_4,
+        ProtocolVersion::V1_21_5,
+        ProtocolVersion::V1_21_6,
+    ];
+
+    /// Version the server speaks
+    pub const CURRENT: ProtocolVersion = ProtocolVersion::V1_21_6;
+
+    /// Find a version by its protocol number
+    pub fn from_id(id: i32) -> Option<Self> {
+        Self::ALL.into_iter().find(|version| version.id() == id)
+    }
+
+    /// Protocol number sent in the handshake
+    pub fn id(self) -> i32 {
+        self as i32
+    }
+
+    /// Name of the first release using this version
+    pub fn name(self) -> &'static str {
+        match self {
+            ProtocolVersion::V1_20_5 => "1.20.5",
+            ProtocolVersion::V1_21 => "1.21",
+            ProtocolVersion::V1_21_2 => "1.21.2",
+            ProtocolVersion::V1_21_4 => "1.21.4",
+            ProtocolVersion::V1_21_5 => "1.21.5",
+            ProtocolVersion::V1_21_6 => "1.21.6",
+        }
+    }
+}
+
+impl fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::{MINECRAFT_VERSION, PROTOCOL_VERSION};
+
+    #[test]
+    fn test_protocol_versions() {
+        assert_eq!(ProtocolVersion::CURRENT.id(), PROTOCOL_VERSION);
+        assert_eq!(ProtocolVersion::CURRENT.name(), MINECRAFT_VERSION);
+        assert_eq!(
+            ProtocolVersion::from_id(769),
+            Some(ProtocolVersion::V1_21_4)
+        );
+        assert_eq!(ProtocolVersion::from_id(765), None);
+        assert_eq!(ProtocolVersion::V1_21.to_string(), "1.21");
+        assert!(ProtocolVersion::ALL.is_sorted());
+    }
+}
@@ -0,0 +1,365 @@
+//! Query protocol
+//!
+//! Monitoring tools ask a [`QueryServer`] about the server over UDP with
+//! the GameSpy4 protocol Minecraft uses. A client first sends a handshake
+//! and gets a challenge token, then sends the token back in a stat request
+//! and gets either the basic or the full statistics, the full ones
+//! including the names of the players online.
+//!
+//! Every request starts with the magic bytes `FE FD`, a type byte and a
+//! big-endian session ID; responses start with the type and session ID.
+
+use crate::error::{Result, ServerError};
+use crate::protocol::MINECRAFT_VERSION;
+use crate::server::context::ServerContext;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::net::UdpSocket;
+
+/// Port vanilla servers answer queries on, the same as the game's
+pub const DEFAULT_PORT: u16 = 25565;
+
+/// Bytes every request starts with
+pub const MAGIC: [u8; 2] = [0xFE, 0xFD];
+
+/// Type of a handshake, asking for a challenge token
+pub const HANDSHAKE: u8 = 9;
+/// Type of a stat request, carrying the challenge token
+pub const STAT: u8 = 0;
+
+/// Bits of session IDs clients may use
+pub const SESSION_ID_MASK: i32 = 0x0F0F_0F0F;
+
+/// Time a challenge token stays valid after the handshake
+pub const CHALLENGE_LIFETIME: Duration = Duration::from_secs(30);
+
+/// Largest request read; real requests are at most 15 bytes
+const MAX_REQUEST_SIZE: usize = 1460;
+
+/// Padding between the session ID and the values of a full stat
+const FULL_STAT_HEADER: &[u8] = b"splitnum\0\x80\0";
+
+/// Padding between the values and the players of a full stat
+const PLAYERS_HEADER: &[u8] = b"\x01player_\0\0";
+
+/// A challenge token handed out to a client
+#[derive(Debug, Clone, Copy)]
+struct Challenge {
+    /// Token the client must send back
+    token: i32,
+    /// When the token was handed out
+    issued: Instant,
+}
+
+/// Server answering queries
+pub struct QueryServer {
+    /// Socket receiving requests
+    socket: UdpSocket,
+    /// Server described in the answers
+    context: Arc<ServerContext>,
+    /// Challenge tokens handed out, by client address
+    challenges: HashMap<SocketAddr, Challenge>,
+}
+
+impl QueryServer {
+    /// Listen for queries on an address
+    pub async fn bind(address: SocketAddr, context: Arc<ServerContext>) -> Result<Self> {
+        Ok(Self {
+            socket: UdpSocket::bind(address).await?,
+            context,
+            challenges: HashMap::new(),
+        })
+    }
+
+    /// Get the address the server is listening on
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        self.socket.local_addr().map_err(ServerError::from)
+    }
+
+    /// Answer queries until the task is aborted
+    pub async fn run(mut self) -> Result<()> {
+        let mut buffer = [0u8; MAX_REQUEST_SIZE];
+        loop {
+            let (length, address) = self.socket.recv_from(&mut buffer).await?;
+            match self.respond(address, &buffer[..length]).await {
+                Some(response) => {
+                    self.socket.send_to(&response, address).await?;
+                }
+                None => tracing::debug!("Ignored invalid query from {}", address),
+            }
+        }
+    }
+
+    /// Build the answer to a request, or `None` if it is invalid or its
+    /// challenge token is wrong or expired
+    async fn respond(&mut self, address: SocketAddr, request: &[u8]) -> Option<Vec<u8>> {
+        let rest = request.strip_prefix(&MAGIC)?;
+        let (&kind, rest) = rest.split_first()?;
+        let (session_id, rest) = read_i32(rest)?;
+        let session_id = session_id & SESSION_ID_MASK;
+
+        let mut response = vec![kind];
+        response.extend_from_slice(&session_id.to_be_bytes());
+        match kind {
+            HANDSHAKE => {
+                let token = self.issue_challenge(address);
+                push_string(&mut response, &token.to_string());
+            }
+            STAT => {
+                let (token, padding) = read_i32(rest)?;
+                if !self.check_challenge(address, token) {
+                    return None;
+                }
+                if padding.is_empty() {
+                    self.write_basic_stat(&mut response).await;
+                } else {
+                    self.write_full_stat(&mut response).await;
+                }
+            }
+            _ => return None,
+        }
+        Some(response)
+    }
+
+    /// Hand out a new challenge token to a client, forgetting expired ones
+    fn issue_challenge(&mut self, address: SocketAddr) -> i32 {
+        self.challenges
+            .retain(|_, challenge| challenge.issued.elapsed() < CHALLENGE_LIFETIME);
+        let token = rand::random::<i32>() & i32::MAX;
+        let challenge = Challenge {
+            token,
+            issued: Instant::now(),
+        };
+        self.challenges.insert(address, challenge);
+        token
+    }
+
+    /// Whether a token is the one handed out to a client and not expired
+    fn check_challenge(&self, address: SocketAddr, token: i32) -> bool {
+        self.challenges.get(&address).is_some_and(|challenge| {
+            challenge.token == token && challenge.issued.elapsed() < CHALLENGE_LIFETIME
+        })
+    }
+
+    /// Name of the world, shown as the map
+    fn map_name(&self) -> String {
+        self.context
+            .config
+            .world_directory
+            .as_deref()
+            .and_then(|path| path.file_name())
+            .map_or_else(
+                || "world".to_string(),
+                |name| name.to_string_lossy().into_owned(),
+            )
+    }
+
+    /// Write the MOTD, game type, map, player counts and address
+    async fn write_basic_stat(&self, response: &mut Vec<u8>) {
+        let config = &self.context.config;
+        push_string(response, &config.motd);
+        push_string(response, "SMP");
+        push_string(response, &self.map_name());
+        push_string(response, &self.context.player_list.len().await.to_string());
+        push_string(response, &config.max_players.to_string());
+        response.extend_from_slice(&config.bind_address.port().to_le_bytes());
+        push_string(response, &config.bind_address.ip().to_string());
+    }
+
+    /// Write every value as key and value pairs, then the players online
+    async fn write_full_stat(&self, response: &mut Vec<u8>) {
+        let config = &self.context.config;
+        let mut players: Vec<String> = self
+            .context
+            .player_list
+            .get_all_players()
+            .await
+            .into_iter()
+            .map(|(_, player)| player.username)
+            .collect();
+        players.sort();
+
+        response.extend_from_slice(FULL_STAT_HEADER);
+        let values = [
+            ("hostname", config.motd.clone()),
+            ("gametype", "SMP".to_string()),
+            ("game_id", "MINECRAFT".to_string()),
+            ("version", MINECRAFT_VERSION.to_string()),
+            ("plugins", String::new()),
+            ("map", self.map_name()),
+            ("numplayers", players.len().to_string()),
+            ("maxplayers", config.max_players.to_string()),
+            ("hostport", config.bind_address.port().to_string()),
+            ("hostip", config.bind_address.ip().to_string()),
+        ];
+        for (key, value) in values {
+            push_string(response, key);
+            push_string(response, &value);
+        }
+        response.push(0);
+
+        response.extend_from_slice(PLAYERS_HEADER);
+        for player in &players {
+            push_string(response, player);
+        }
+        response.push(0);
+    }
+}
+
+/// Split a big-endian `i32` off the start of some bytes
+fn read_i32(bytes: &[u8]) -> Option<(i32, &[u8])> {
+    let (value, rest) = bytes.split_first_chunk::<4>()?;
+    Some((i32::from_be_bytes(*value), rest))
+}
+
+/// Write a null-terminated string
+fn push_string(bytes: &mut Vec<u8>, value: &str) {
+    bytes.extend_from_slice(value.as_bytes());
+    bytes.push(0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ServerConfig;
+    use crate::server::commands::tests::join;
+
+    /// Build a request
+    fn request(kind: u8, session_id: i32, payload: &[u8]) -> Vec<u8> {
+        let mut request = MAGIC.to_vec();
+        request.push(kind);
+        request.extend_from_slice(&session_id.to_be_bytes());
+        request.extend_from_slice(payload);
+        request
+    }
+
+    /// Split a response into its null-terminated strings
+    fn strings(bytes: &[u8]) -> Vec<String> {
+        bytes
+            .split(|&b| b == 0)
+            .map(|s| String::from_utf8_lossy(s).into_owned())
+            .collect()
+    }
+
+    /// Do the handshake, returning the challenge token
+    async fn handshake(server: &mut QueryServer, address: SocketAddr) -> i32 {
+        let response = server
+            .respond(address, &request(HANDSHAKE, 0x0102_0304, &[]))
+            .await
+            .unwrap();
+        assert_eq!(response[..5], [HANDSHAKE, 1, 2, 3, 4]);
+        assert_eq!(response.last(), Some(&0));
+        strings(&response[5..])[0].parse().unwrap()
+    }
+
+    async fn server() -> QueryServer {
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_motd("A query test".to_string())
+            .with_max_players(20);
+        let context = ServerContext::for_tests(config);
+        join(&context, "Steve").await;
+        join(&context, "Alex").await;
+        let address = SocketAddr::from(([127, 0, 0, 1], 0));
+        QueryServer::bind(address, context).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_basic_stat() {
+        let mut server = server().await;
+        let client = SocketAddr::from(([127, 0, 0, 1], 40000));
+        let token = handshake(&mut server, client).await;
+
+        let response = server
+            .respond(client, &request(STAT, 1, &token.to_be_bytes()))
+            .await
+            .unwrap();
+        assert_eq!(response[..5], [STAT, 0, 0, 0, 1]);
+        let values = strings(&response[5..]);
+        assert_eq!(values[..5], ["A query test", "SMP", "world", "2", "20"]);
+        let port = server.context.config.bind_address.port();
+        let address = &response[response.len() - 10..];
+        assert_eq!(address[..2], port.to_le_bytes());
+        assert_eq!(&address[2..], b"0.0.0.0\0");
+    }
+
+    #[tokio::test]
+    async fn test_full_stat() {
+        let mut server = server().await;
+        let client = SocketAddr::from(([127, 0, 0, 1], 40000));
+        let token = handshake(&mut server, client).await;
+
+        let mut payload = token.to_be_bytes().to_vec();
+        payload.extend_from_slice(&[0; 4]);
+        let response = server
+            .respond(client, &request(STAT, 1, &payload))
+            .await
+            .unwrap();
+        let body = response[5..].strip_prefix(FULL_STAT_HEADER).unwrap();
+        let split = body
+            .windows(PLAYERS_HEADER.len())
+            .position(|window| window == PLAYERS_HEADER)
+            .unwrap();
+        let values = strings(&body[..split]);
+        let values: HashMap<_, _> = values
+            .chunks(2)
+            .filter(|pair| pair.len() == 2)
+            .map(|pair| (pair[0].as_str(), pair[1].as_str()))
+            .collect();
+        assert_eq!(values["hostname"], "A query test");
+        assert_eq!(values["game_id"], "MINECRAFT");
+        assert_eq!(values["version"], MINECRAFT_VERSION);
+        assert_eq!(values["numplayers"], "2");
+        assert_eq!(values["maxplayers"], "20");
+        let players = strings(&body[split + PLAYERS_HEADER.len()..]);
+        assert_eq!(players, ["Alex", "Steve", "", ""]);
+    }
+
+    #[tokio::test]
+    async fn test_challenge_is_checked() {
+        let mut server = server().await;
+        let client = SocketAddr::from(([127, 0, 0, 1], 40000));
+        let other = SocketAddr::from(([127, 0, 0, 1], 40001));
+
+        // Stats need the token handed out to the same address
+        assert!(
+            server
+                .respond(client, &request(STAT, 1, &[0; 4]))
+                .await
+                .is_none()
+        );
+        let token = handshake(&mut server, client).await;
+        let stat = request(STAT, 1, &token.to_be_bytes());
+        assert!(server.respond(other, &stat).await.is_none());
+        let wrong = request(STAT, 1, &token.wrapping_add(1).to_be_bytes());
+        assert!(server.respond(client, &wrong).await.is_none());
+        assert!(server.respond(client, &stat).await.is_some());
+
+        // Without the magic bytes, requests are ignored
+        assert!(server.respond(client, &stat[1..]).await.is_none());
+        assert!(server.respond(client, &request(5, 1, &[])).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_query_server() {
+        let server = server().await;
+        let address = server.local_addr().unwrap();
+        let task = tokio::spawn(server.run());
+
+        let socket = UdpSocket::bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+            .await
+            .unwrap();
+        socket
+            .send_to(&request(HANDSHAKE, 7, &[]), address)
+            .await
+            .unwrap();
+        let mut buffer = [0u8; 64];
+        let length = socket.recv(&mut buffer).await.unwrap();
+        assert_eq!(buffer[..5], [HANDSHAKE, 0, 0, 0, 7]);
+        let token: i32 = strings(&buffer[5..length])[0].parse().unwrap();
+        assert!(token >= 0);
+        task.abort();
+    }
+}
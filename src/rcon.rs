@@ -0,0 +1,338 @@
+//! Remote console
+//!
+//! An [`RconServer`] lets administration tools run commands over TCP with
+//! the Source RCON protocol. A client logs in with the configured password,
+//! then sends command lines and gets back the replies the console would
+//! have seen. Every packet is a little-endian `i32` length, followed by an
+//! `i32` request ID, an `i32` type and a null-terminated payload, with one
+//! more null byte as padding.
+
+use crate::error::{Result, ServerError};
+use crate::server::commands::CommandSource;
+use crate::server::context::ServerContext;
+use std::io::ErrorKind;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Port vanilla servers accept RCON clients on
+pub const DEFAULT_PORT: u16 = 25575;
+
+/// Failed logins allowed on one connection before it is closed
+pub const MAX_AUTH_ATTEMPTS: u32 = 3;
+
+/// Type of a login request, carrying the password
+pub const AUTH: i32 = 3;
+/// Type of the answer to a login request
+pub const AUTH_RESPONSE: i32 = 2;
+/// Type of a request running a command line
+pub const EXEC_COMMAND: i32 = 2;
+/// Type of the answer to a command
+pub const RESPONSE_VALUE: i32 = 0;
+
+/// Request ID answering a failed login
+pub const AUTH_FAILED_ID: i32 = -1;
+
+/// Largest packet accepted from clients, after the length field
+const MAX_PACKET_LENGTH: i32 = 1460;
+
+/// Smallest packet, with an empty payload: ID, type and two null bytes
+const MIN_PACKET_LENGTH: i32 = 10;
+
+/// Most payload bytes sent in one response; longer replies are split
+const MAX_RESPONSE_PAYLOAD: usize = 4096;
+
+/// An RCON packet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RconPacket {
+    /// ID chosen by the client, echoed in the answers
+    pub request_id: i32,
+    /// What the packet asks for or answers
+    pub kind: i32,
+    /// Password, command line or command output
+    pub payload: String,
+}
+
+impl RconPacket {
+    /// Create a packet
+    pub fn new(request_id: i32, kind: i32, payload: impl Into<String>) -> Self {
+        Self {
+            request_id,
+            kind,
+            payload: payload.into(),
+        }
+    }
+
+    /// Encode the packet with its length
+    pub fn encode(&self) -> Vec<u8> {
+        let length = self.payload.len() as i32 + MIN_PACKET_LENGTH;
+        let mut bytes = Vec::with_capacity(length as usize + 4);
+        bytes.extend_from_slice(&length.to_le_bytes());
+        bytes.extend_from_slice(&self.request_id.to_le_bytes());
+        bytes.extend_from_slice(&self.kind.to_le_bytes());
+        bytes.extend_from_slice(self.payload.as_bytes());
+        bytes.extend_from_slice(&[0, 0]);
+        bytes
+    }
+
+    /// Read one packet
+    ///
+    /// Packets shorter than an empty packet or longer than 1460 bytes are
+    /// rejected with a protocol error.
+    pub async fn read<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Self> {
+        let length = reader.read_i32_le().await?;
+        if !(MIN_PACKET_LENGTH..=MAX_PACKET_LENGTH).contains(&length) {
+            return Err(ServerError::Protocol(format!(
+                "Invalid RCON packet length: {}",
+                length
+            )));
+        }
+        let request_id = reader.read_i32_le().await?;
+        let kind = reader.read_i32_le().await?;
+        let mut payload = vec![0; length as usize - 8];
+        reader.read_exact(&mut payload).await?;
+        let end = payload
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(payload.len());
+        Ok(Self {
+            request_id,
+            kind,
+            payload: String::from_utf8_lossy(&payload[..end]).into_owned(),
+        })
+    }
+}
+
+/// Server accepting RCON clients
+pub struct RconServer {
+    /// Socket accepting clients
+    listener: TcpListener,
+    /// Password clients log in with
+    password: Arc<str>,
+    /// Server the commands run on
+    context: Arc<ServerContext>,
+}
+
+impl RconServer {
+    /// Listen for clients on an address
+    pub async fn bind(
+        address: SocketAddr,
+        password: impl Into<Arc<str>>,
+        context: Arc<ServerContext>,
+    ) -> Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(address).await?,
+            password: password.into(),
+            context,
+        })
+    }
+
+    /// Get the address the server is listening on
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        self.listener.local_addr().map_err(ServerError::from)
+    }
+
+    /// Serve clients until the task is aborted
+    pub async fn run(self) -> Result<()> {
+        loop {
+            let (stream, address) = self.listener.accept().await?;
+            let password = Arc::clone(&self.password);
+            let context = Arc::clone(&self.context);
+            tokio::spawn(async move {
+                if let Err(e) = serve(stream, &password, context).await {
+                    tracing::debug!("RCON client {} failed: {}", address, e);
+                }
+            });
+        }
+    }
+}
+
+/// Answer a client's packets until it disconnects or fails to log in too
+/// many times
+async fn serve(mut stream: TcpStream, password: &str, context: Arc<ServerContext>) -> Result<()> {
+    let mut authenticated = false;
+    let mut failures = 0;
+    loop {
+        let packet = match RconPacket::read(&mut stream).await {
+            Ok(packet) => packet,
+            Err(ServerError::Io(e)) if e.kind() == ErrorKind::UnexpectedEof => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        match packet.kind {
+            AUTH if passwords_match(&packet.payload, password) => {
+                authenticated = true;
+                let answer = RconPacket::new(packet.request_id, AUTH_RESPONSE, "");
+                stream.write_all(&answer.encode()).await?;
+            }
+            AUTH => {
+                failures += 1;
+                let answer = RconPacket::new(AUTH_FAILED_ID, AUTH_RESPONSE, "");
+                stream.write_all(&answer.encode()).await?;
+                if failures >= MAX_AUTH_ATTEMPTS {
+                    tracing::warn!(
+                        "Closing RCON connection from {} after {} failed logins",
+                        stream.peer_addr()?,
+                        failures
+                    );
+                    stream.shutdown().await?;
+                    return Ok(());
+                }
+            }
+            EXEC_COMMAND if authenticated => {
+                let output = context
+                    .commands
+                    .dispatch_with_output(
+                        CommandSource::Rcon,
+                        &packet.payload,
+                        Arc::clone(&context),
+                    )
+                    .await?;
+                for answer in responses(packet.request_id, &output) {
+                    stream.write_all(&answer.encode()).await?;
+                }
+            }
+            EXEC_COMMAND => {
+                let answer = RconPacket::new(AUTH_FAILED_ID, AUTH_RESPONSE, "");
+                stream.write_all(&answer.encode()).await?;
+            }
+            kind => {
+                let message = format!("Unknown request {:x}", kind);
+                let answer = RconPacket::new(packet.request_id, RESPONSE_VALUE, message);
+                stream.write_all(&answer.encode()).await?;
+            }
+        }
+    }
+}
+
+/// Split a command's output into responses of at most 4096 bytes
+fn responses(request_id: i32, output: &str) -> Vec<RconPacket> {
+    let mut packets = Vec::new();
+    let mut rest = output;
+    loop {
+        let mut end = rest.len().min(MAX_RESPONSE_PAYLOAD);
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let (chunk, tail) = rest.split_at(end);
+        packets.push(RconPacket::new(request_id, RESPONSE_VALUE, chunk));
+        if tail.is_empty() {
+            return packets;
+        }
+        rest = tail;
+    }
+}
+
+/// Compare a password without stopping at the first difference, so the
+/// time taken does not tell how much of a guess was right
+fn passwords_match(guess: &str, password: &str) -> bool {
+    guess.len() == password.len()
+        && guess
+            .bytes()
+            .zip(password.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ServerConfig;
+    use std::sync::atomic::Ordering;
+
+    #[tokio::test]
+    async fn test_packet_round_trip() {
+        let packet = RconPacket::new(7, EXEC_COMMAND, "list");
+        let bytes = packet.encode();
+        assert_eq!(
+            bytes,
+            [
+                14, 0, 0, 0, 7, 0, 0, 0, 2, 0, 0, 0, b'l', b'i', b's', b't', 0, 0
+            ]
+        );
+        assert_eq!(RconPacket::read(&mut &bytes[..]).await.unwrap(), packet);
+
+        let mut too_long = (MAX_PACKET_LENGTH + 1).to_le_bytes().to_vec();
+        too_long.extend_from_slice(&[0; 16]);
+        assert!(RconPacket::read(&mut &too_long[..]).await.is_err());
+        let too_short = [9, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        assert!(RconPacket::read(&mut &too_short[..]).await.is_err());
+    }
+
+    #[test]
+    fn test_responses_are_split() {
+        assert_eq!(responses(1, ""), [RconPacket::new(1, RESPONSE_VALUE, "")]);
+        let output = "é".repeat(MAX_RESPONSE_PAYLOAD);
+        let packets = responses(1, &output);
+        assert_eq!(packets.len(), 2);
+        assert!(
+            packets
+                .iter()
+                .all(|p| p.payload.len() <= MAX_RESPONSE_PAYLOAD)
+        );
+        let joined: String = packets.into_iter().map(|p| p.payload).collect();
+        assert_eq!(joined, output);
+    }
+
+    #[test]
+    fn test_passwords_match() {
+        assert!(passwords_match("hunter2", "hunter2"));
+        assert!(!passwords_match("hunter3", "hunter2"));
+        assert!(!passwords_match("hunter", "hunter2"));
+        assert!(!passwords_match("", "hunter2"));
+    }
+
+    /// Start a server on a free port and connect to it
+    async fn connect(context: &Arc<ServerContext>) -> TcpStream {
+        let address = SocketAddr::from(([127, 0, 0, 1], 0));
+        let server = RconServer::bind(address, "hunter2", Arc::clone(context))
+            .await
+            .unwrap();
+        let address = server.local_addr().unwrap();
+        tokio::spawn(server.run());
+        TcpStream::connect(address).await.unwrap()
+    }
+
+    /// Send a packet and read the answer
+    async fn request(stream: &mut TcpStream, packet: RconPacket) -> RconPacket {
+        stream.write_all(&packet.encode()).await.unwrap();
+        RconPacket::read(stream).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_run_commands() {
+        let context = ServerContext::for_tests(ServerConfig::new().with_online_mode(false));
+        let mut stream = connect(&context).await;
+
+        // Commands are refused until the client logs in
+        let answer = request(&mut stream, RconPacket::new(1, EXEC_COMMAND, "stop")).await;
+        assert_eq!(answer.request_id, AUTH_FAILED_ID);
+        assert!(!context.shutdown.load(Ordering::Relaxed));
+
+        let answer = request(&mut stream, RconPacket::new(2, AUTH, "hunter2")).await;
+        assert_eq!(answer, RconPacket::new(2, AUTH_RESPONSE, ""));
+
+        let answer = request(&mut stream, RconPacket::new(3, EXEC_COMMAND, "list")).await;
+        assert_eq!(answer.request_id, 3);
+        assert_eq!(answer.kind, RESPONSE_VALUE);
+        assert!(answer.payload.starts_with("There are 0 of a max of"));
+
+        let answer = request(&mut stream, RconPacket::new(4, EXEC_COMMAND, "stop")).await;
+        assert_eq!(answer.request_id, 4);
+        assert!(context.shutdown.load(Ordering::Relaxed));
+    }
+
+    #[tokio::test]
+    async fn test_failed_logins_close_connection() {
+        let context = ServerContext::for_tests(ServerConfig::new().with_online_mode(false));
+        let mut stream = connect(&context).await;
+
+        for attempt in 1..=MAX_AUTH_ATTEMPTS as i32 {
+            let answer = request(&mut stream, RconPacket::new(attempt, AUTH, "guess")).await;
+            assert_eq!(answer, RconPacket::new(AUTH_FAILED_ID, AUTH_RESPONSE, ""));
+        }
+        let mut rest = Vec::new();
+        stream.read_to_end(&mut rest).await.unwrap();
+        assert!(rest.is_empty());
+    }
+}
@@ -0,0 +1,111 @@
+//! Online-mode authentication
+//!
+//! This module verifies players against the Mojang session server once the
+//! encryption handshake has completed.
+
+use crate::error::{Result, ServerError};
+use crate::protocol::packets::login::Property;
+use crate::protocol::types::McUuid;
+use serde::Deserialize;
+use std::sync::OnceLock;
+
+/// Session server endpoint used to verify that a client joined this server
+const HAS_JOINED_URL: &str = "https://sessionserver.mojang.com/session/minecraft/hasJoined";
+
+/// Authenticated player profile returned by the session server
+#[derive(Debug, Clone, Deserialize)]
+pub struct GameProfile {
+    /// Player UUID
+    pub id: McUuid,
+    /// Player username with the correct capitalization
+    pub name: String,
+    /// Profile properties (e.g. skin textures)
+    #[serde(default)]
+    pub properties: Vec<ProfileProperty>,
+}
+
+/// A single profile property
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProfileProperty {
+    /// Property name
+    pub name: String,
+    /// Base64-encoded property value
+    pub value: String,
+    /// Optional Yggdrasil signature of the value
+    pub signature: Option<String>,
+}
+
+impl From<ProfileProperty> for Property {
+    fn from(property: ProfileProperty) -> Self {
+        Property {
+            name: property.name.into(),
+            value: property.value.into(),
+            signature: property.signature.map(Into::into),
+        }
+    }
+}
+
+/// Get the shared HTTP client used for session server requests
+fn http_client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(reqwest::Client::new)
+}
+
+/// Ask the session server whether `username` joined using `server_hash`
+///
+/// Returns the player's authenticated profile on success.
+pub async fn has_joined(username: &str, server_hash: &str) -> Result<GameProfile> {
+    let request_error =
+        |e: reqwest::Error| ServerError::Authentication(format!("Session server error: {}", e));
+
+    let response = http_client()
+        .get(HAS_JOINED_URL)
+        .query(&[("username", username), ("serverId", server_hash)])
+        .send()
+        .await
+        .map_err(request_error)?;
+
+    // The session server answers with 204 No Content when verification fails
+    if response.status() == reqwest::StatusCode::NO_CONTENT {
+        return Err(ServerError::Authentication(format!(
+            "Failed to verify username {}",
+            username
+        )));
+    }
+
+    let profile = response
+        .error_for_status()
+        .map_err(request_error)?
+        .json::<GameProfile>()
+        .await
+        .map_err(request_error)?;
+
+    Ok(profile)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_game_profile_deserialize() {
+        let json = r#"{
+            "id": "069a79f444e94726a5befca90e38aaf5",
+            "name": "Notch",
+            "properties": [
+                { "name": "textures", "value": "e30=", "signature": "c2lnbmF0dXJl" }
+            ]
+        }"#;
+
+        let profile: GameProfile = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            profile.id.to_string(),
+            "069a79f4-44e9-4726-a5be-fca90e38aaf5"
+        );
+        assert_eq!(profile.name, "Notch");
+
+        let property: Property = profile.properties[0].clone().into();
+        assert_eq!(property.name.0, "textures");
+        assert_eq!(property.signature.map(|s| s.0), Some("c2lnbmF0dXJl".into()));
+    }
+}
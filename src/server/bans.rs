@@ -0,0 +1,371 @@
+//! Banned players
+//!
+//! Bans are kept in a [`BanList`] and saved to `banned-players.json` in the
+//! same format as vanilla, so existing ban lists keep working. Players are
+//! matched by UUID when the ban has one, and by name otherwise.
+
+use crate::error::{Result, ServerError};
+use crate::protocol::types::McUuid;
+use crate::storage::json_list;
+use serde::de::Error as _;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use time::{Date, Month, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
+
+/// Name of the ban list file in the server directory
+pub const BAN_LIST_FILE: &str = "banned-players.json";
+
+/// Reason given when a ban has none
+pub const DEFAULT_BAN_REASON: &str = "Banned by an operator.";
+
+/// Source recorded for bans made from the server
+pub const SERVER_SOURCE: &str = "Server";
+
+/// `expires` value of a ban that never ends
+const FOREVER: &str = "forever";
+
+/// A banned player
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BanEntry {
+    /// Name of the player when they were banned
+    pub name: String,
+    /// Player UUID, unknown for players banned while offline
+    pub uuid: Option<McUuid>,
+    /// Reason shown to the player
+    pub reason: String,
+    /// Who made the ban
+    pub source: String,
+    /// When the ban was made
+    pub created: OffsetDateTime,
+    /// When the ban ends, or `None` if it never does
+    pub expires: Option<OffsetDateTime>,
+}
+
+impl BanEntry {
+    /// Create a permanent ban made from the server now
+    pub fn new(name: impl Into<String>, uuid: Option<McUuid>, reason: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            uuid,
+            reason: reason.into(),
+            source: SERVER_SOURCE.to_string(),
+            created: OffsetDateTime::now_utc(),
+            expires: None,
+        }
+    }
+
+    /// Whether the ban has ended at a point in time
+    pub fn is_expired(&self, now: OffsetDateTime) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+
+    /// Whether the ban is for a player
+    pub fn matches(&self, uuid: &McUuid, name: &str) -> bool {
+        match self.uuid {
+            Some(banned) => banned == *uuid,
+            None => self.name.eq_ignore_ascii_case(name),
+        }
+    }
+
+    /// Message shown to the player when they are disconnected
+    pub fn disconnect_reason(&self) -> String {
+        let mut reason = format!("You are banned from this server.\nReason: {}", self.reason);
+        if let Some(expires) = self.expires {
+            reason.push_str(&format!(
+                "\nYour ban will be removed on {}",
+                format_date(expires)
+            ));
+        }
+        reason
+    }
+}
+
+/// A ban as stored in `banned-players.json`
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredBan {
+    /// Player UUID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    uuid: Option<McUuid>,
+    /// Player name
+    name: String,
+    /// Creation date
+    created: String,
+    /// Who made the ban
+    source: String,
+    /// End date, or "forever"
+    expires: String,
+    /// Reason shown to the player
+    reason: String,
+}
+
+impl TryFrom<StoredBan> for BanEntry {
+    type Error = ServerError;
+
+    fn try_from(stored: StoredBan) -> Result<Self> {
+        let invalid = || {
+            let message = format!("Invalid ban date for {}", stored.name);
+            ServerError::Json(serde_json::Error::custom(message))
+        };
+        let created = parse_date(&stored.created).ok_or_else(invalid)?;
+        let expires = match stored.expires.as_str() {
+            FOREVER => None,
+            date => Some(parse_date(date).ok_or_else(invalid)?),
+        };
+        Ok(Self {
+            name: stored.name,
+            uuid: stored.uuid,
+            reason: stored.reason,
+            source: stored.source,
+            created,
+            expires,
+        })
+    }
+}
+
+impl From<&BanEntry> for StoredBan {
+    fn from(entry: &BanEntry) -> Self {
+        Self {
+            uuid: entry.uuid,
+            name: entry.name.clone(),
+            created: format_date(entry.created),
+            source: entry.source.clone(),
+            expires: entry
+                .expires
+                .map_or_else(|| FOREVER.to_string(), format_date),
+            reason: entry.reason.clone(),
+        }
+    }
+}
+
+/// Format a date as vanilla does, such as `2024-06-01 18:30:00 +0000`
+fn format_date(date: OffsetDateTime) -> String {
+    let offset = date.offset();
+    let sign = if offset.is_negative() { '-' } else { '+' };
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} {}{:02}{:02}",
+        date.year(),
+        u8::from(date.month()),
+        date.day(),
+        date.hour(),
+        date.minute(),
+        date.second(),
+        sign,
+        offset.whole_hours().unsigned_abs(),
+        offset.minutes_past_hour().unsigned_abs()
+    )
+}
+
+/// Parse a date in the format of [`format_date`]
+fn parse_date(text: &str) -> Option<OffsetDateTime> {
+    let mut parts = text.split(' ');
+    let (date, time, offset) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some() {
+        return None;
+    }
+    let numbers = |text: &str, separator: char| -> Option<Vec<i32>> {
+        text.split(separator)
+            .map(|number| number.parse().ok())
+            .collect()
+    };
+    let (&[year, month, day], &[hour, minute, second]) = (
+        numbers(date, '-')?.as_slice(),
+        numbers(time, ':')?.as_slice(),
+    ) else {
+        return None;
+    };
+
+    let small = |number: i32| u8::try_from(number).ok();
+    let month = Month::try_from(small(month)?).ok()?;
+    let date = Date::from_calendar_date(year, month, small(day)?).ok()?;
+    let time = Time::from_hms(small(hour)?, small(minute)?, small(second)?).ok()?;
+    let (sign, digits) = match offset.split_at_checked(1)? {
+        ("+", digits) => (1, digits),
+        ("-", digits) => (-1, digits),
+        _ => return None,
+    };
+    if digits.len() != 4 {
+        return None;
+    }
+    let hours: i8 = digits[..2].parse().ok()?;
+    let minutes: i8 = digits[2..].parse().ok()?;
+    let offset = UtcOffset::from_hms(sign * hours, sign * minutes, 0).ok()?;
+    Some(PrimitiveDateTime::new(date, time).assume_offset(offset))
+}
+
+/// The players banned from the server
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BanList {
+    /// Bans in the order they were made
+    entries: Vec<BanEntry>,
+}
+
+impl BanList {
+    /// Create an empty ban list
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a ban list from the entries of its file
+    fn from_stored(stored: Vec<StoredBan>) -> Result<Self> {
+        let entries = stored
+            .into_iter()
+            .map(BanEntry::try_from)
+            .collect::<Result<_>>()?;
+        Ok(Self { entries })
+    }
+
+    /// Entries of the ban list as they are written to its file
+    fn to_stored(&self) -> Vec<StoredBan> {
+        self.entries.iter().map(StoredBan::from).collect()
+    }
+
+    /// Read a ban list from the contents of `banned-players.json`
+    pub fn from_json(json: &str) -> Result<Self> {
+        Self::from_stored(json_list::from_json(json)?)
+    }
+
+    /// Convert the ban list to the contents of `banned-players.json`
+    pub fn to_json(&self) -> Result<String> {
+        json_list::to_json(&self.to_stored())
+    }
+
+    /// Load a ban list file, or an empty list if there is none
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::from_stored(json_list::load(path)?)
+    }
+
+    /// Save the ban list to a file
+    pub fn save(&self, path: &Path) -> Result<()> {
+        json_list::save(path, &self.to_stored())
+    }
+
+    /// Every ban, including expired ones
+    pub fn entries(&self) -> &[BanEntry] {
+        &self.entries
+    }
+
+    /// Number of bans
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether nobody is banned
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Ban a player, replacing any earlier ban of the same player
+    pub fn add(&mut self, entry: BanEntry) {
+        self.entries.retain(|existing| {
+            let same_uuid = entry.uuid.is_some() && existing.uuid == entry.uuid;
+            !same_uuid && !existing.name.eq_ignore_ascii_case(&entry.name)
+        });
+        self.entries.push(entry);
+    }
+
+    /// Lift the ban of a player by name, returning it
+    pub fn remove(&mut self, name: &str) -> Option<BanEntry> {
+        let index = self
+            .entries
+            .iter()
+            .position(|entry| entry.name.eq_ignore_ascii_case(name))?;
+        Some(self.entries.remove(index))
+    }
+
+    /// Find the ban of a player that has not expired
+    pub fn find(&self, uuid: &McUuid, name: &str) -> Option<&BanEntry> {
+        let now = OffsetDateTime::now_utc();
+        self.entries
+            .iter()
+            .find(|entry| entry.matches(uuid, name) && !entry.is_expired(now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::Duration;
+
+    #[test]
+    fn test_dates() {
+        let date = parse_date("2024-06-01 18:30:05 +0200").unwrap();
+        assert_eq!((date.hour(), date.minute(), date.second()), (18, 30, 5));
+        assert_eq!(date.offset().whole_hours(), 2);
+        assert_eq!(format_date(date), "2024-06-01 18:30:05 +0200");
+        let west = parse_date("2024-01-31 00:00:00 -0530").unwrap();
+        assert_eq!(format_date(west), "2024-01-31 00:00:00 -0530");
+
+        for invalid in ["2024-02-30 00:00:00 +0000", "2024-06-01 18:30", "forever"] {
+            assert_eq!(parse_date(invalid), None, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_vanilla_format_roundtrip() {
+        let uuid = McUuid::new_v4();
+        let json = format!(
+            r#"[
+  {{
+    "uuid": "{}",
+    "name": "Steve",
+    "created": "2024-06-01 18:30:00 +0000",
+    "source": "Server",
+    "expires": "forever",
+    "reason": "Griefing"
+  }}
+]"#,
+            uuid
+        );
+        let list = BanList::from_json(&json).unwrap();
+        assert_eq!(list.len(), 1);
+        let ban = &list.entries()[0];
+        assert_eq!((ban.uuid, ban.reason.as_str()), (Some(uuid), "Griefing"));
+        assert_eq!(ban.expires, None);
+        assert_eq!(list.to_json().unwrap(), json);
+        assert!(BanList::from_json("{}").is_err());
+        let bad_date = json.replace("2024-06-01 18:30:00 +0000", "yesterday");
+        assert!(matches!(
+            BanList::from_json(&bad_date),
+            Err(ServerError::Json(_))
+        ));
+    }
+
+    #[test]
+    fn test_add_find_and_remove() {
+        let mut list = BanList::new();
+        let steve = McUuid::new_v4();
+        list.add(BanEntry::new("Steve", Some(steve), DEFAULT_BAN_REASON));
+        list.add(BanEntry::new("Alex", None, "Spam"));
+
+        // Bans with a UUID follow the player through name changes
+        assert!(list.find(&steve, "Steve2").is_some());
+        assert!(list.find(&McUuid::new_v4(), "Steve").is_none());
+        assert_eq!(list.find(&McUuid::new_v4(), "alex").unwrap().reason, "Spam");
+
+        // Banning again replaces the old ban
+        list.add(BanEntry::new("alex", None, "More spam"));
+        assert_eq!(list.len(), 2);
+
+        let mut expired = BanEntry::new("Notch", None, "Temporary");
+        expired.expires = Some(OffsetDateTime::now_utc() - Duration::minutes(1));
+        list.add(expired);
+        assert!(list.find(&McUuid::new_v4(), "Notch").is_none());
+
+        assert_eq!(list.remove("ALEX").unwrap().reason, "More spam");
+        assert!(list.remove("Alex").is_none());
+    }
+
+    #[test]
+    fn test_disconnect_reason() {
+        let mut ban = BanEntry::new("Steve", None, "Griefing");
+        assert_eq!(
+            ban.disconnect_reason(),
+            "You are banned from this server.\nReason: Griefing"
+        );
+        ban.expires = parse_date("2030-01-01 00:00:00 +0000");
+        assert!(
+            ban.disconnect_reason()
+                .ends_with("\nYour ban will be removed on 2030-01-01 00:00:00 +0000")
+        );
+    }
+}
@@ -0,0 +1,140 @@
+//! Block update broadcasting
+//!
+//! Blocks changed during a tick are collected by the [`World`] and sent to
+//! clients once the tick ends. A lone change in a chunk section is sent as a
+//! [`BlockChangePacket`], while several changes in the same section are
+//! batched into one [`MultiBlockChangePacket`].
+//!
+//! [`World`]: crate::game::World
+
+use crate::error::Result;
+use crate::game::world::WorldMutation;
+use crate::network::RawPacket;
+use crate::protocol::packets::play::{
+    BlockChangePacket, MultiBlockChangePacket, SectionBlockChange,
+};
+use crate::protocol::types::VarInt;
+use std::collections::BTreeMap;
+
+/// A packet telling clients about changed blocks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlockUpdate {
+    /// A single changed block
+    Single(BlockChangePacket),
+    /// Several changed blocks in one chunk section
+    Section(MultiBlockChangePacket),
+}
+
+impl BlockUpdate {
+    /// Encode the update as a packet
+    pub fn to_raw_packet(&self) -> Result<RawPacket> {
+        match self {
+            BlockUpdate::Single(packet) => RawPacket::from_packet(packet),
+            BlockUpdate::Section(packet) => RawPacket::from_packet(packet),
+        }
+    }
+}
+
+/// Convert the mutations of a tick into block update packets
+///
+/// When a block changed more than once, only its final state is sent.
+pub fn block_updates(mutations: &[WorldMutation]) -> Vec<BlockUpdate> {
+    let mut sections: BTreeMap<(i32, i32, i32), Vec<WorldMutation>> = BTreeMap::new();
+    for mutation in mutations {
+        let position = mutation.position;
+        let section = (position.x >> 4, position.y >> 4, position.z >> 4);
+        let changes = sections.entry(section).or_default();
+        match changes
+            .iter_mut()
+            .find(|change| change.position == position)
+        {
+            Some(change) => change.block_id = mutation.block_id,
+            None => changes.push(*mutation),
+        }
+    }
+
+    sections
+        .into_iter()
+        .map(
+            |((section_x, section_y, section_z), changes)| match changes[..] {
+                [change] => BlockUpdate::Single(BlockChangePacket {
+                    position: change.position,
+                    block_id: VarInt(change.block_id as i32),
+                }),
+                _ => BlockUpdate::Section(MultiBlockChangePacket {
+                    section_x,
+                    section_y,
+                    section_z,
+                    blocks: changes
+                        .iter()
+                        .map(|change| SectionBlockChange {
+                            local_x: (change.position.x & 0xF) as u8,
+                            local_y: (change.position.y & 0xF) as u8,
+                            local_z: (change.position.z & 0xF) as u8,
+                            block_state: change.block_id as i32,
+                        })
+                        .collect(),
+                }),
+            },
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::types::Position;
+
+    fn mutation(x: i32, y: i32, z: i32, block_id: u32) -> WorldMutation {
+        WorldMutation {
+            position: Position::new(x, y, z),
+            block_id,
+        }
+    }
+
+    #[test]
+    fn test_single_mutation() {
+        let updates = block_updates(&[mutation(-1, 64, 3, 1)]);
+        assert_eq!(
+            updates,
+            [BlockUpdate::Single(BlockChangePacket {
+                position: Position::new(-1, 64, 3),
+                block_id: VarInt(1),
+            })]
+        );
+    }
+
+    #[test]
+    fn test_mutations_batched_by_section() {
+        let updates = block_updates(&[
+            mutation(0, 64, 0, 1),
+            mutation(15, 79, 15, 2),
+            mutation(0, 64, 0, 3),
+            mutation(16, 64, 0, 4),
+        ]);
+        assert_eq!(updates.len(), 2);
+        assert_eq!(
+            updates[0],
+            BlockUpdate::Section(MultiBlockChangePacket {
+                section_x: 0,
+                section_y: 4,
+                section_z: 0,
+                blocks: vec![
+                    SectionBlockChange {
+                        local_x: 0,
+                        local_y: 0,
+                        local_z: 0,
+                        block_state: 3,
+                    },
+                    SectionBlockChange {
+                        local_x: 15,
+                        local_y: 15,
+                        local_z: 15,
+                        block_state: 2,
+                    },
+                ],
+            })
+        );
+        assert!(matches!(updates[1], BlockUpdate::Single(_)));
+    }
+}
@@ -0,0 +1,280 @@
+//! Boss bars shown to every player
+//!
+//! The manager keeps the state of each active boss bar, so players joining
+//! later see the same bars, and broadcasts every change to the player list.
+
+use crate::error::Result;
+use crate::protocol::TextComponent;
+use crate::protocol::packets::play::{BossBarAction, BossBarColor, BossBarDivision, BossBarPacket};
+use crate::protocol::types::McUuid;
+use crate::server::PlayerList;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// State of a boss bar
+#[derive(Debug, Clone, PartialEq)]
+pub struct BossBar {
+    /// Text above the bar
+    pub title: TextComponent,
+    /// How full the bar is, from 0.0 to 1.0
+    pub health: f32,
+    /// Color of the bar
+    pub color: BossBarColor,
+    /// Notches of the bar
+    pub division: BossBarDivision,
+    /// Flags of the bar
+    pub flags: u8,
+}
+
+impl BossBar {
+    /// Create a full, pink boss bar without notches or flags
+    pub fn new(title: TextComponent) -> Self {
+        Self {
+            title,
+            health: 1.0,
+            color: BossBarColor::default(),
+            division: BossBarDivision::default(),
+            flags: 0,
+        }
+    }
+
+    /// Set the color of the bar
+    pub fn with_color(mut self, color: BossBarColor) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Set the notches of the bar
+    pub fn with_division(mut self, division: BossBarDivision) -> Self {
+        self.division = division;
+        self
+    }
+
+    /// Set the flags of the bar
+    pub fn with_flags(mut self, flags: u8) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Build the packet showing this bar to a client
+    pub fn add_packet(&self, uuid: McUuid) -> BossBarPacket {
+        BossBarPacket {
+            uuid,
+            action: BossBarAction::Add {
+                title: self.title.clone(),
+                health: self.health,
+                color: self.color,
+                division: self.division,
+                flags: self.flags,
+            },
+        }
+    }
+}
+
+/// Thread-safe set of the boss bars shown to every player
+#[derive(Clone)]
+pub struct BossBarManager {
+    /// Active boss bars by UUID
+    bars: Arc<RwLock<HashMap<McUuid, BossBar>>>,
+    /// Players the bars are shown to
+    player_list: PlayerList,
+}
+
+impl BossBarManager {
+    /// Create a manager showing boss bars to the given players
+    pub fn new(player_list: PlayerList) -> Self {
+        Self {
+            bars: Arc::default(),
+            player_list,
+        }
+    }
+
+    /// Show a boss bar to every player, replacing any bar with the same UUID
+    pub async fn add(&self, uuid: McUuid, bar: BossBar) -> Result<()> {
+        let packet = bar.add_packet(uuid);
+        let replaced = self.bars.write().await.insert(uuid, bar).is_some();
+        if replaced {
+            self.broadcast(uuid, BossBarAction::Remove).await?;
+        }
+        self.player_list.broadcast(&packet).await?;
+        Ok(())
+    }
+
+    /// Hide a boss bar from every player, returning it if it was shown
+    pub async fn remove(&self, uuid: &McUuid) -> Result<Option<BossBar>> {
+        let Some(bar) = self.bars.write().await.remove(uuid) else {
+            return Ok(None);
+        };
+        self.broadcast(*uuid, BossBarAction::Remove).await?;
+        Ok(Some(bar))
+    }
+
+    /// Get a copy of a boss bar
+    pub async fn get(&self, uuid: &McUuid) -> Option<BossBar> {
+        self.bars.read().await.get(uuid).cloned()
+    }
+
+    /// Change how full a boss bar is, clamped to between 0.0 and 1.0,
+    /// returning whether the bar exists
+    pub async fn update_health(&self, uuid: &McUuid, health: f32) -> Result<bool> {
+        let health = health.clamp(0.0, 1.0);
+        match self.bars.write().await.get_mut(uuid) {
+            Some(bar) => bar.health = health,
+            None => return Ok(false),
+        }
+        self.broadcast(*uuid, BossBarAction::UpdateHealth(health))
+            .await?;
+        Ok(true)
+    }
+
+    /// Change the text above a boss bar, returning whether the bar exists
+    pub async fn update_title(&self, uuid: &McUuid, title: TextComponent) -> Result<bool> {
+        match self.bars.write().await.get_mut(uuid) {
+            Some(bar) => bar.title = title.clone(),
+            None => return Ok(false),
+        }
+        self.broadcast(*uuid, BossBarAction::UpdateTitle(title))
+            .await?;
+        Ok(true)
+    }
+
+    /// Change the color and notches of a boss bar, returning whether the bar
+    /// exists
+    pub async fn update_style(
+        &self,
+        uuid: &McUuid,
+        color: BossBarColor,
+        division: BossBarDivision,
+    ) -> Result<bool> {
+        match self.bars.write().await.get_mut(uuid) {
+            Some(bar) => {
+                bar.color = color;
+                bar.division = division;
+            }
+            None => return Ok(false),
+        }
+        self.broadcast(*uuid, BossBarAction::UpdateStyle { color, division })
+            .await?;
+        Ok(true)
+    }
+
+    /// Change the flags of a boss bar, returning whether the bar exists
+    pub async fn update_flags(&self, uuid: &McUuid, flags: u8) -> Result<bool> {
+        match self.bars.write().await.get_mut(uuid) {
+            Some(bar) => bar.flags = flags,
+            None => return Ok(false),
+        }
+        self.broadcast(*uuid, BossBarAction::UpdateFlags(flags))
+            .await?;
+        Ok(true)
+    }
+
+    /// Build the packets showing every active bar, for a player that just
+    /// joined
+    pub async fn add_packets(&self) -> Vec<BossBarPacket> {
+        self.bars
+            .read()
+            .await
+            .iter()
+            .map(|(&uuid, bar)| bar.add_packet(uuid))
+            .collect()
+    }
+
+    /// Send an action on a bar to every player
+    async fn broadcast(&self, uuid: McUuid, action: BossBarAction) -> Result<()> {
+        self.player_list
+            .broadcast(&BossBarPacket { uuid, action })
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::player::GameMode;
+    use crate::network::PacketReceiver;
+    use crate::server::player_list::PlayerInfo;
+    use tokio::sync::mpsc;
+
+    async fn manager_with_player() -> (BossBarManager, PacketReceiver) {
+        let list = PlayerList::new();
+        let (sender, receiver) = mpsc::unbounded_channel();
+        list.add_player(
+            McUuid::new_v4(),
+            PlayerInfo::new("Steve".to_string(), GameMode::Survival),
+            sender,
+        )
+        .await;
+        (BossBarManager::new(list), receiver)
+    }
+
+    fn next_action(receiver: &mut PacketReceiver) -> BossBarAction {
+        receiver
+            .try_recv()
+            .unwrap()
+            .parse::<BossBarPacket>()
+            .unwrap()
+            .action
+    }
+
+    #[tokio::test]
+    async fn test_updates_are_broadcast() {
+        let (bars, mut steve) = manager_with_player().await;
+        let uuid = McUuid::new_v4();
+        bars.add(
+            uuid,
+            BossBar::new(TextComponent::text("Raid")).with_color(BossBarColor::Red),
+        )
+        .await
+        .unwrap();
+        assert!(matches!(
+            next_action(&mut steve),
+            BossBarAction::Add {
+                color: BossBarColor::Red,
+                ..
+            }
+        ));
+
+        assert!(bars.update_health(&uuid, 1.5).await.unwrap());
+        assert_eq!(next_action(&mut steve), BossBarAction::UpdateHealth(1.0));
+        assert!(
+            bars.update_title(&uuid, TextComponent::text("Raid - Victory"))
+                .await
+                .unwrap()
+        );
+        assert_eq!(
+            next_action(&mut steve),
+            BossBarAction::UpdateTitle(TextComponent::text("Raid - Victory"))
+        );
+        assert_eq!(
+            bars.get(&uuid).await.unwrap().title,
+            TextComponent::text("Raid - Victory")
+        );
+
+        assert!(bars.remove(&uuid).await.unwrap().is_some());
+        assert_eq!(next_action(&mut steve), BossBarAction::Remove);
+        assert!(!bars.update_health(&uuid, 0.5).await.unwrap());
+        assert!(steve.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_add_packets_for_new_players() {
+        let (bars, _steve) = manager_with_player().await;
+        assert!(bars.add_packets().await.is_empty());
+
+        let uuid = McUuid::new_v4();
+        bars.add(uuid, BossBar::new(TextComponent::text("Wither")))
+            .await
+            .unwrap();
+        bars.update_health(&uuid, 0.25).await.unwrap();
+        let packets = bars.add_packets().await;
+        assert_eq!(packets.len(), 1);
+        assert_eq!(packets[0].uuid, uuid);
+        assert!(matches!(
+            packets[0].action,
+            BossBarAction::Add { health: 0.25, .. }
+        ));
+    }
+}
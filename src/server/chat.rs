@@ -0,0 +1,118 @@
+//! Chat routing
+//!
+//! Chat messages from players are passed to the server's [`ChatRouter`],
+//! which decides who receives them.
+
+use crate::error::Result;
+use crate::protocol::packets::play::{ChatMessagePacket, PlayerChatPacket};
+use crate::protocol::text::TextComponent;
+use crate::protocol::types::McUuid;
+use crate::server::PlayerList;
+use async_trait::async_trait;
+
+/// Delivers chat messages sent by players
+#[async_trait]
+pub trait ChatRouter: Send + Sync {
+    /// Route a message sent by a player in the player list
+    async fn route(&self, sender: &McUuid, message: &str, players: &PlayerList) -> Result<()>;
+}
+
+/// ID of `minecraft:chat`, the chat type of messages typed in the chat
+///
+/// It is the first entry of the bundled `minecraft:chat_type` registry.
+pub const CHAT_TYPE: i32 = 0;
+
+/// Sends every message to all players with chat enabled, as player chat
+/// formatted as `<name> message`
+///
+/// Messages are not signed yet, so clients show them as not secure.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BroadcastChatRouter;
+
+#[async_trait]
+impl ChatRouter for BroadcastChatRouter {
+    async fn route(&self, sender: &McUuid, message: &str, players: &PlayerList) -> Result<()> {
+        let Some(player) = players.get_player(sender).await else {
+            tracing::debug!("Dropping chat message from unknown player {}", sender);
+            return Ok(());
+        };
+
+        tracing::info!("<{}> {}", player.username, message);
+        let sender_name = TextComponent::text(player.username);
+        let packet = PlayerChatPacket::unsigned(*sender, sender_name, message, CHAT_TYPE);
+        players.broadcast_player_chat(&packet).await?;
+        Ok(())
+    }
+}
+
+/// Verify the signature of a chat message
+///
+/// Chat is not signed yet (the server does not enforce secure chat), so
+/// every message is accepted.
+pub fn verify_signature(_packet: &ChatMessagePacket) -> bool {
+    // TODO: Check the signature against the player's chat session key
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::GameData;
+    use crate::game::player::GameMode;
+    use crate::protocol::packets::Packet;
+    use crate::server::player_list::PlayerInfo;
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn test_broadcast_chat_router() {
+        let players = PlayerList::new();
+        let (alex, steve) = (McUuid::new_v4(), McUuid::new_v4());
+        let (alex_tx, mut alex_rx) = mpsc::unbounded_channel();
+        let (steve_tx, mut steve_rx) = mpsc::unbounded_channel();
+        players
+            .add_player(
+                alex,
+                PlayerInfo::new("Alex".to_string(), GameMode::Survival),
+                alex_tx,
+            )
+            .await;
+        players
+            .add_player(
+                steve,
+                PlayerInfo::new("Steve".to_string(), GameMode::Survival),
+                steve_tx,
+            )
+            .await;
+
+        BroadcastChatRouter
+            .route(&alex, "hi", &players)
+            .await
+            .unwrap();
+
+        for receiver in [&mut alex_rx, &mut steve_rx] {
+            let packet = receiver.try_recv().unwrap();
+            assert_eq!(packet.id.0, PlayerChatPacket::ID);
+            let chat = packet.parse::<PlayerChatPacket>().unwrap();
+            assert_eq!(chat.sender, alex);
+            assert_eq!(chat.sender_name, TextComponent::text("Alex"));
+            assert_eq!(chat.unsigned_content, Some(TextComponent::text("hi")));
+            assert_eq!(chat.chat_type.0, CHAT_TYPE);
+        }
+
+        // Messages from players that already left are dropped
+        BroadcastChatRouter
+            .route(&McUuid::new_v4(), "hi", &players)
+            .await
+            .unwrap();
+        assert!(steve_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_chat_type_id() {
+        let data = GameData::load().unwrap();
+        assert_eq!(
+            data.registry_entry_id("minecraft:chat_type", "minecraft:chat"),
+            Some(CHAT_TYPE)
+        );
+    }
+}
@@ -0,0 +1,130 @@
+//! `/ban` command
+
+use super::kick::{disconnect, player_and_reason};
+use super::{Command, CommandContext};
+use crate::error::Result;
+use crate::protocol::packets::play::{ArgumentParser, CommandNode, StringKind};
+use crate::server::bans::{BanEntry, DEFAULT_BAN_REASON};
+use async_trait::async_trait;
+
+/// Usage shown when no player is given
+const USAGE: &str = "Usage: /ban <player> [<reason>]";
+
+/// Bans a player, disconnecting them if they are online
+///
+/// Players who are not online can be banned by name, and the ban then
+/// applies to whoever joins with that name.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BanCommand;
+
+#[async_trait]
+impl Command for BanCommand {
+    fn name(&self) -> &'static str {
+        "ban"
+    }
+
+    fn permission_level(&self) -> u8 {
+        4
+    }
+
+    fn node(&self) -> CommandNode {
+        CommandNode::literal(self.name()).then(
+            CommandNode::argument("targets", ArgumentParser::String(StringKind::SingleWord))
+                .executable()
+                .then(
+                    CommandNode::argument(
+                        "reason",
+                        ArgumentParser::String(StringKind::GreedyPhrase),
+                    )
+                    .executable(),
+                ),
+        )
+    }
+
+    async fn execute(&self, ctx: CommandContext) -> Result<()> {
+        let Some((target, reason)) = player_and_reason(&ctx.args) else {
+            return ctx.reply(USAGE).await;
+        };
+        let reason = reason.unwrap_or(DEFAULT_BAN_REASON);
+        let server = &ctx.server;
+        let online = server.player_list.find_by_name(target).await;
+        let entry = match &online {
+            Some((uuid, info)) => BanEntry::new(&info.username, Some(*uuid), reason),
+            None => BanEntry::new(target, None, reason),
+        };
+        let name = entry.name.clone();
+        let disconnect_reason = entry.disconnect_reason();
+        server.bans.write().await.add(entry);
+        server.save_bans().await?;
+
+        if let Some((uuid, _)) = online {
+            disconnect(server, &uuid, &disconnect_reason).await?;
+        }
+        ctx.reply(format!("Banned {}: {}", name, reason)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ServerConfig;
+    use crate::protocol::packets::Packet;
+    use crate::protocol::packets::play::PlayDisconnectPacket;
+    use crate::protocol::text::TextComponent;
+    use crate::protocol::types::{JsonTextComponent, McUuid};
+    use crate::server::commands::CommandDispatcher;
+    use crate::server::commands::tests::{join, next_message, op};
+    use crate::server::context::ServerContext;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_ban() {
+        let context = ServerContext::for_tests(ServerConfig::new().with_online_mode(false));
+        let (steve, mut steve_receiver) = join(&context, "Steve").await;
+        op(&context, steve, "Steve").await;
+        let (alex, mut alex_receiver) = join(&context, "Alex").await;
+        let dispatcher = CommandDispatcher::with_builtin_commands();
+
+        dispatcher
+            .dispatch(steve, "/ban alex Griefing", Arc::clone(&context))
+            .await
+            .unwrap();
+        let packet = alex_receiver.try_recv().unwrap();
+        assert_eq!(packet.id.0, PlayDisconnectPacket::ID);
+        assert_eq!(
+            packet.parse::<PlayDisconnectPacket>().unwrap().reason,
+            JsonTextComponent::text("You are banned from this server.\nReason: Griefing")
+        );
+        assert_eq!(
+            next_message(&mut steve_receiver),
+            TextComponent::text("Banned Alex: Griefing")
+        );
+        let bans = context.bans.read().await;
+        let ban = bans.find(&alex, "Alex").unwrap();
+        assert_eq!((ban.name.as_str(), ban.uuid), ("Alex", Some(alex)));
+        drop(bans);
+
+        // Offline players are banned by name
+        dispatcher
+            .dispatch(steve, "/ban Herobrine", Arc::clone(&context))
+            .await
+            .unwrap();
+        assert_eq!(
+            next_message(&mut steve_receiver),
+            TextComponent::text(format!("Banned Herobrine: {}", DEFAULT_BAN_REASON))
+        );
+        let bans = context.bans.read().await;
+        assert!(bans.find(&McUuid::new_v4(), "herobrine").is_some());
+        assert_eq!(bans.len(), 2);
+        drop(bans);
+
+        dispatcher
+            .dispatch(steve, "/ban", Arc::clone(&context))
+            .await
+            .unwrap();
+        assert_eq!(
+            next_message(&mut steve_receiver),
+            TextComponent::text(USAGE)
+        );
+    }
+}
@@ -0,0 +1,84 @@
+//! `/banlist` command
+
+use super::{Command, CommandContext};
+use crate::error::Result;
+use async_trait::async_trait;
+
+/// Lists the banned players
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BanListCommand;
+
+#[async_trait]
+impl Command for BanListCommand {
+    fn name(&self) -> &'static str {
+        "banlist"
+    }
+
+    fn permission_level(&self) -> u8 {
+        3
+    }
+
+    async fn execute(&self, ctx: CommandContext) -> Result<()> {
+        let lines: Vec<String> = ctx
+            .server
+            .bans
+            .read()
+            .await
+            .entries()
+            .iter()
+            .map(|ban| format!("{} was banned by {}: {}", ban.name, ban.source, ban.reason))
+            .collect();
+        let summary = match lines.len() {
+            0 => return ctx.reply("There are no bans").await,
+            1 => "There is 1 ban".to_string(),
+            count => format!("There are {} bans", count),
+        };
+        ctx.reply(format!("{}:\n{}", summary, lines.join("\n")))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::ServerConfig;
+    use crate::protocol::text::TextComponent;
+    use crate::server::bans::BanEntry;
+    use crate::server::commands::CommandDispatcher;
+    use crate::server::commands::tests::{join, next_message, op};
+    use crate::server::context::ServerContext;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_ban_list() {
+        let context = ServerContext::for_tests(ServerConfig::new().with_online_mode(false));
+        let (steve, mut receiver) = join(&context, "Steve").await;
+        op(&context, steve, "Steve").await;
+        let dispatcher = CommandDispatcher::with_builtin_commands();
+
+        dispatcher
+            .dispatch(steve, "/banlist", Arc::clone(&context))
+            .await
+            .unwrap();
+        assert_eq!(
+            next_message(&mut receiver),
+            TextComponent::text("There are no bans")
+        );
+
+        {
+            let mut bans = context.bans.write().await;
+            bans.add(BanEntry::new("Alex", None, "Griefing"));
+            bans.add(BanEntry::new("Notch", None, "Spam"));
+        }
+        dispatcher
+            .dispatch(steve, "/banlist", Arc::clone(&context))
+            .await
+            .unwrap();
+        assert_eq!(
+            next_message(&mut receiver),
+            TextComponent::text(
+                "There are 2 bans:\nAlex was banned by Server: Griefing\n\
+                 Notch was banned by Server: Spam"
+            )
+        );
+    }
+}
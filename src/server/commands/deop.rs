@@ -0,0 +1,120 @@
+//! `/deop` command
+
+use super::op::send_permission_level;
+use super::{Command, CommandContext};
+use crate::error::Result;
+use crate::protocol::packets::play::{ArgumentParser, CommandNode, StringKind};
+use crate::server::ops::MAX_PERMISSION_LEVEL;
+use async_trait::async_trait;
+
+/// Usage shown when the arguments are not understood
+const USAGE: &str = "Usage: /deop <player>";
+
+/// Takes away a player's operator status
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DeopCommand;
+
+#[async_trait]
+impl Command for DeopCommand {
+    fn name(&self) -> &'static str {
+        "deop"
+    }
+
+    fn permission_level(&self) -> u8 {
+        MAX_PERMISSION_LEVEL
+    }
+
+    fn node(&self) -> CommandNode {
+        CommandNode::literal(self.name()).then(
+            CommandNode::argument("targets", ArgumentParser::String(StringKind::SingleWord))
+                .executable(),
+        )
+    }
+
+    async fn execute(&self, ctx: CommandContext) -> Result<()> {
+        let args: Vec<&str> = ctx.args.split_whitespace().collect();
+        let [target] = args.as_slice() else {
+            return ctx.reply(USAGE).await;
+        };
+        let server = &ctx.server;
+        let removed = {
+            let mut ops = server.ops.write().await;
+            let uuid = ops.find_by_name(target).map(|entry| entry.uuid);
+            uuid.and_then(|uuid| ops.remove(&uuid))
+        };
+        let Some(entry) = removed else {
+            return ctx
+                .reply("Nothing changed. The player is not an operator")
+                .await;
+        };
+        server.save_ops().await?;
+        send_permission_level(server, &entry.uuid).await?;
+        ctx.reply(format!("Made {} no longer a server operator", entry.name))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ServerConfig;
+    use crate::protocol::packets::play::DeclareCommandsPacket;
+    use crate::protocol::text::TextComponent;
+    use crate::server::commands::CommandDispatcher;
+    use crate::server::commands::tests::{join, next_message, op};
+    use crate::server::context::ServerContext;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_deop() {
+        let context = ServerContext::for_tests(ServerConfig::new().with_online_mode(false));
+        let (steve, mut steve_receiver) = join(&context, "Steve").await;
+        op(&context, steve, "Steve").await;
+        let (alex, mut alex_receiver) = join(&context, "Alex").await;
+        op(&context, alex, "Alex").await;
+        let dispatcher = CommandDispatcher::with_builtin_commands();
+
+        dispatcher
+            .dispatch(steve, "/deop alex", Arc::clone(&context))
+            .await
+            .unwrap();
+        let commands = alex_receiver.try_recv().unwrap();
+        assert_eq!(
+            commands.parse::<DeclareCommandsPacket>().unwrap().root,
+            context.commands.command_tree_for(0)
+        );
+        assert_eq!(
+            next_message(&mut steve_receiver),
+            TextComponent::text("Made Alex no longer a server operator")
+        );
+        assert_eq!(context.ops.read().await.level(&alex), 0);
+
+        for (line, message) in [
+            (
+                "/deop Alex",
+                "Nothing changed. The player is not an operator",
+            ),
+            ("/deop", USAGE),
+        ] {
+            dispatcher
+                .dispatch(steve, line, Arc::clone(&context))
+                .await
+                .unwrap();
+            assert_eq!(
+                next_message(&mut steve_receiver),
+                TextComponent::text(message)
+            );
+        }
+
+        // Non-operators cannot take away operator status
+        dispatcher
+            .dispatch(alex, "/deop Steve", Arc::clone(&context))
+            .await
+            .unwrap();
+        assert_eq!(
+            next_message(&mut alex_receiver),
+            TextComponent::text("You do not have permission to use this command")
+        );
+        assert_eq!(context.ops.read().await.level(&steve), 4);
+    }
+}
@@ -0,0 +1,206 @@
+//! `/gamemode` command
+
+use super::{Command, CommandContext, PLAYER_REQUIRED};
+use crate::error::Result;
+use crate::game::player::GameMode;
+use crate::protocol::packets::play::{
+    ArgumentParser, CommandNode, GameEventPacket, PlayerAbilitiesPacket,
+};
+use async_trait::async_trait;
+
+/// Usage shown when the arguments are not understood
+const USAGE: &str = "Usage: /gamemode <survival|creative|adventure|spectator> [<player>]";
+
+/// Changes the game mode of a player
+#[derive(Debug, Default, Clone, Copy)]
+pub struct GamemodeCommand;
+
+#[async_trait]
+impl Command for GamemodeCommand {
+    fn name(&self) -> &'static str {
+        "gamemode"
+    }
+
+    fn permission_level(&self) -> u8 {
+        2
+    }
+
+    fn node(&self) -> CommandNode {
+        let target = CommandNode::argument(
+            "target",
+            ArgumentParser::Entity {
+                single: true,
+                players_only: true,
+            },
+        )
+        .executable();
+        GameMode::ALL
+            .into_iter()
+            .fold(CommandNode::literal(self.name()), |command, mode| {
+                command.then(
+                    CommandNode::literal(mode.name())
+                        .executable()
+                        .then(target.clone()),
+                )
+            })
+    }
+
+    async fn execute(&self, ctx: CommandContext) -> Result<()> {
+        let args: Vec<&str> = ctx.args.split_whitespace().collect();
+        let (mode, target) = match args.as_slice() {
+            [mode] => (*mode, None),
+            [mode, target] => (*mode, Some(*target)),
+            _ => return ctx.reply(USAGE).await,
+        };
+        let Some(mode) = GameMode::from_name(mode) else {
+            return ctx.reply(format!("Unknown game mode: {}", mode)).await;
+        };
+
+        let server = &ctx.server;
+        let found = match (target, ctx.sender.player()) {
+            (Some(name), _) => server.player_list.find_by_name(name).await,
+            (None, Some(sender)) => server
+                .player_list
+                .get_player(&sender)
+                .await
+                .map(|info| (sender, info)),
+            (None, None) => return ctx.reply(PLAYER_REQUIRED).await,
+        };
+        let Some((uuid, info)) = found else {
+            return ctx
+                .reply(format!(
+                    "No player named {} was found",
+                    target.unwrap_or_default()
+                ))
+                .await;
+        };
+        let Some(mut player) = server.players.get_player(&uuid).await else {
+            return ctx
+                .reply(format!("{} is not in the world", info.username))
+                .await;
+        };
+
+        player.set_game_mode(mode);
+        server.players.update_player(&uuid, player.clone()).await;
+        let game_event = GameEventPacket::new(GameEventPacket::CHANGE_GAME_MODE, mode as u8 as f32);
+        server.player_list.send_to(&uuid, &game_event).await?;
+        let abilities = PlayerAbilitiesPacket::new(player.ability_flags());
+        server.player_list.send_to(&uuid, &abilities).await?;
+        server.player_list.update_game_mode(&uuid, mode).await?;
+
+        ctx.reply(if ctx.sender.player() == Some(uuid) {
+            format!("Set own game mode to {}", mode.name())
+        } else {
+            format!("Set {}'s game mode to {}", info.username, mode.name())
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ServerConfig;
+    use crate::game::player::Player;
+    use crate::network::PacketReceiver;
+    use crate::protocol::packets::Packet;
+    use crate::protocol::packets::play::PlayerInfoUpdatePacket;
+    use crate::protocol::text::TextComponent;
+    use crate::protocol::types::McUuid;
+    use crate::server::commands::CommandDispatcher;
+    use crate::server::commands::tests::{join, next_message, op};
+    use crate::server::context::ServerContext;
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+
+    /// Add a player in the world, connected from a port of its own
+    async fn spawn(context: &ServerContext, name: &str, port: u16) -> (McUuid, PacketReceiver) {
+        let (uuid, receiver) = join(context, name).await;
+        op(context, uuid, name).await;
+        let address = SocketAddr::from(([127, 0, 0, 1], port));
+        context
+            .players
+            .add_player(Player::new(uuid, name.to_string()), address)
+            .await;
+        (uuid, receiver)
+    }
+
+    #[tokio::test]
+    async fn test_set_game_mode() {
+        let context = ServerContext::for_tests(ServerConfig::new().with_online_mode(false));
+        let (steve, mut steve_receiver) = spawn(&context, "Steve", 1).await;
+        let (alex, mut alex_receiver) = spawn(&context, "Alex", 2).await;
+        let dispatcher = CommandDispatcher::with_builtin_commands();
+
+        dispatcher
+            .dispatch(steve, "/gamemode creative Alex", Arc::clone(&context))
+            .await
+            .unwrap();
+        let game_event = alex_receiver.try_recv().unwrap();
+        assert_eq!(
+            game_event.parse::<GameEventPacket>().unwrap(),
+            GameEventPacket::new(GameEventPacket::CHANGE_GAME_MODE, 1.0)
+        );
+        let abilities = alex_receiver.try_recv().unwrap();
+        assert_eq!(abilities.id.0, PlayerAbilitiesPacket::ID);
+        assert_eq!(
+            abilities.parse::<PlayerAbilitiesPacket>().unwrap().flags,
+            0x0D
+        );
+
+        // Everyone's tab list shows the new mode
+        for receiver in [&mut steve_receiver, &mut alex_receiver] {
+            let update = receiver.try_recv().unwrap();
+            assert_eq!(
+                update.parse::<PlayerInfoUpdatePacket>().unwrap(),
+                PlayerInfoUpdatePacket::game_mode(alex, 1)
+            );
+        }
+        assert_eq!(
+            next_message(&mut steve_receiver),
+            TextComponent::text("Set Alex's game mode to creative")
+        );
+        let player = context.players.get_player(&alex).await.unwrap();
+        assert_eq!(player.game_mode, GameMode::Creative);
+        let info = context.player_list.get_player(&alex).await.unwrap();
+        assert_eq!(info.game_mode, GameMode::Creative);
+
+        dispatcher
+            .dispatch(steve, "/gamemode spectator", Arc::clone(&context))
+            .await
+            .unwrap();
+        for _ in 0..3 {
+            steve_receiver.try_recv().unwrap();
+        }
+        assert_eq!(
+            next_message(&mut steve_receiver),
+            TextComponent::text("Set own game mode to spectator")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_game_mode_errors() {
+        let context = ServerContext::for_tests(ServerConfig::new().with_online_mode(false));
+        let (steve, mut receiver) = spawn(&context, "Steve", 1).await;
+        join(&context, "Alex").await;
+        let dispatcher = CommandDispatcher::with_builtin_commands();
+
+        for (line, message) in [
+            ("/gamemode hardcore", "Unknown game mode: hardcore"),
+            (
+                "/gamemode creative Herobrine",
+                "No player named Herobrine was found",
+            ),
+            ("/gamemode creative Alex", "Alex is not in the world"),
+            ("/gamemode", USAGE),
+        ] {
+            dispatcher
+                .dispatch(steve, line, Arc::clone(&context))
+                .await
+                .unwrap();
+            assert_eq!(next_message(&mut receiver), TextComponent::text(message));
+        }
+        let player = context.players.get_player(&steve).await.unwrap();
+        assert_eq!(player.game_mode, GameMode::Survival);
+    }
+}
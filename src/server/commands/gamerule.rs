@@ -0,0 +1,169 @@
+//! `/gamerule` command
+
+use super::{Command, CommandContext};
+use crate::error::Result;
+use crate::game::{GameRuleRegistry, GameRuleValue};
+use crate::protocol::packets::play::{ArgumentParser, CommandNode};
+use async_trait::async_trait;
+
+/// Shows or changes a game rule of the world
+#[derive(Debug, Default, Clone, Copy)]
+pub struct GameRuleCommand;
+
+#[async_trait]
+impl Command for GameRuleCommand {
+    fn name(&self) -> &'static str {
+        "gamerule"
+    }
+
+    fn permission_level(&self) -> u8 {
+        2
+    }
+
+    fn node(&self) -> CommandNode {
+        GameRuleRegistry::new().iter().fold(
+            CommandNode::literal(self.name()),
+            |command, (name, value)| {
+                let parser = match value {
+                    GameRuleValue::Bool(_) => ArgumentParser::Bool,
+                    GameRuleValue::Int(_) => ArgumentParser::Integer {
+                        min: None,
+                        max: None,
+                    },
+                };
+                command.then(
+                    CommandNode::literal(name)
+                        .executable()
+                        .then(CommandNode::argument("value", parser).executable()),
+                )
+            },
+        )
+    }
+
+    async fn execute(&self, ctx: CommandContext) -> Result<()> {
+        let (name, value) = match ctx.args.split_once(' ') {
+            Some((name, value)) => (name, Some(value)),
+            None => (ctx.args.as_str(), None),
+        };
+        let rules = ctx.server.level.read().await.game_rules.clone();
+        let Some(current) = rules.get(name) else {
+            return ctx.reply(format!("Unknown game rule: {}", name)).await;
+        };
+        let Some(value) = value else {
+            return ctx
+                .reply(format!(
+                    "Gamerule {} is currently set to: {}",
+                    name, current
+                ))
+                .await;
+        };
+        let Some(value) = current.parse_same(value) else {
+            return ctx
+                .reply(format!("Invalid value '{}' for game rule {}", value, name))
+                .await;
+        };
+
+        ctx.server.set_game_rule(name, value).await?;
+        ctx.reply(format!("Gamerule {} is now set to: {}", name, value))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::ServerConfig;
+    use crate::game::game_rules::{DO_DAYLIGHT_CYCLE, DO_IMMEDIATE_RESPAWN, KEEP_INVENTORY};
+    use crate::protocol::packets::Packet;
+    use crate::protocol::packets::play::{GameEventPacket, UpdateTimePacket};
+    use crate::protocol::text::TextComponent;
+    use crate::server::commands::CommandDispatcher;
+    use crate::server::commands::tests::{join, next_message, op};
+    use crate::server::context::ServerContext;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_gamerule_command() {
+        let context = ServerContext::for_tests(ServerConfig::new().with_online_mode(false));
+        let (steve, mut receiver) = join(&context, "Steve").await;
+        op(&context, steve, "Steve").await;
+        let dispatcher = CommandDispatcher::with_builtin_commands();
+        let run = async |line: &str| {
+            dispatcher
+                .dispatch(steve, line, Arc::clone(&context))
+                .await
+                .unwrap();
+        };
+
+        run("/gamerule keepInventory").await;
+        assert_eq!(
+            next_message(&mut receiver),
+            TextComponent::text("Gamerule keepInventory is currently set to: false")
+        );
+        run("/gamerule keepInventory true").await;
+        assert_eq!(
+            next_message(&mut receiver),
+            TextComponent::text("Gamerule keepInventory is now set to: true")
+        );
+        run("/gamerule spawnRadius yes").await;
+        assert_eq!(
+            next_message(&mut receiver),
+            TextComponent::text("Invalid value 'yes' for game rule spawnRadius")
+        );
+        run("/gamerule flying true").await;
+        assert_eq!(
+            next_message(&mut receiver),
+            TextComponent::text("Unknown game rule: flying")
+        );
+
+        let rules = context.level.read().await.game_rules.clone();
+        assert_eq!(rules.get_bool(KEEP_INVENTORY), Some(true));
+        assert_eq!(rules.get_int("spawnRadius"), Some(10));
+    }
+
+    #[tokio::test]
+    async fn test_gamerule_updates_clients() {
+        let context = ServerContext::for_tests(ServerConfig::new().with_online_mode(false));
+        let (steve, mut receiver) = join(&context, "Steve").await;
+        op(&context, steve, "Steve").await;
+        let dispatcher = CommandDispatcher::with_builtin_commands();
+
+        dispatcher
+            .dispatch(
+                steve,
+                "/gamerule doDaylightCycle false",
+                Arc::clone(&context),
+            )
+            .await
+            .unwrap();
+        let packet = receiver.try_recv().unwrap();
+        assert_eq!(packet.id.0, UpdateTimePacket::ID);
+        assert!(
+            !packet
+                .parse::<UpdateTimePacket>()
+                .unwrap()
+                .time_of_day_increasing
+        );
+        next_message(&mut receiver);
+        assert!(!context.time.read().await.do_daylight_cycle);
+
+        dispatcher
+            .dispatch(
+                steve,
+                "/gamerule doImmediateRespawn true",
+                Arc::clone(&context),
+            )
+            .await
+            .unwrap();
+        let packet = receiver.try_recv().unwrap();
+        assert_eq!(packet.id.0, GameEventPacket::ID);
+        assert_eq!(
+            packet.parse::<GameEventPacket>().unwrap(),
+            GameEventPacket::new(GameEventPacket::ENABLE_RESPAWN_SCREEN, 1.0)
+        );
+        next_message(&mut receiver);
+
+        let rules = context.level.read().await.game_rules.clone();
+        assert_eq!(rules.get_bool(DO_DAYLIGHT_CYCLE), Some(false));
+        assert_eq!(rules.get_bool(DO_IMMEDIATE_RESPAWN), Some(true));
+    }
+}
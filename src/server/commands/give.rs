@@ -0,0 +1,291 @@
+//! `/give` command
+
+use super::{Command, CommandContext};
+use crate::error::Result;
+use crate::protocol::packets::play::{
+    ArgumentParser, CommandNode, EntityMetadataPacket, MetadataValue,
+};
+use crate::protocol::types::{McUuid, Slot};
+use crate::server::entities::{EntityState, ITEM_ENTITY_TYPE, ITEM_STACK_METADATA};
+use crate::server::inventory::manager::MAX_STACK_SIZE;
+use async_trait::async_trait;
+
+/// Usage shown when the number of arguments is wrong
+const USAGE: &str = "Usage: /give <player> <item> [<count>]";
+
+/// Height above a player's feet that items they drop appear at
+const DROP_HEIGHT: f64 = 1.32;
+
+/// Adds items to a player's inventory
+#[derive(Debug, Default, Clone, Copy)]
+pub struct GiveCommand;
+
+/// Add the `minecraft` namespace to an item ID that has none
+fn item_name(item: &str) -> String {
+    if item.contains(':') {
+        item.to_string()
+    } else {
+        format!("minecraft:{}", item)
+    }
+}
+
+impl GiveCommand {
+    /// Give items to a player, dropping whatever does not fit at their feet
+    async fn give(ctx: &CommandContext, target: &str, item: &str, count: u8) -> Result<()> {
+        let server = &ctx.server;
+        let name = item_name(item);
+        let stack = match server.data.items().id_of(&name) {
+            Some(item_id) => Slot::new(item_id, count),
+            None => Slot::empty(),
+        };
+        if stack.is_empty() || name == "minecraft:air" {
+            return ctx.reply(format!("Unknown item: {}", item)).await;
+        }
+        let Some((uuid, info)) = server.player_list.find_by_name(target).await else {
+            return ctx
+                .reply(format!("No player named {} was found", target))
+                .await;
+        };
+        let Some(inventory) = server.inventories.get(&uuid).await else {
+            return ctx
+                .reply(format!("{} is not in the world", info.username))
+                .await;
+        };
+
+        let remaining = {
+            let mut inventory = inventory.lock().await;
+            let (changed, remaining) = inventory.add_item(stack);
+            for index in changed {
+                if let Some(packet) = inventory.container_mut().slot_packet(index) {
+                    server.player_list.send_to(&uuid, &packet).await?;
+                }
+            }
+            remaining
+        };
+        if !remaining.is_empty() {
+            Self::drop_item(ctx, &uuid, remaining).await?;
+        }
+        ctx.reply(format!("Gave {} {} to {}", count, name, info.username))
+            .await
+    }
+
+    /// Spawn an item entity at a player, showing it to them and the players
+    /// around them
+    async fn drop_item(ctx: &CommandContext, uuid: &McUuid, item: Slot) -> Result<()> {
+        let server = &ctx.server;
+        let Some((_, player)) = server.entities.find_player(uuid).await else {
+            return Ok(());
+        };
+        let state = EntityState::new(
+            McUuid::new_v4(),
+            ITEM_ENTITY_TYPE,
+            player.x,
+            player.y + DROP_HEIGHT,
+            player.z,
+        );
+        let spawn = server.entities.spawn(state.clone()).await;
+        let spawn_packet = state.spawn_packet(spawn);
+        let metadata = EntityMetadataPacket {
+            entity_id: spawn_packet.entity_id,
+            entries: vec![(ITEM_STACK_METADATA, MetadataValue::Slot(item))],
+        };
+        let view_distance = i32::from(server.config.view_distance);
+        let mut viewers = server
+            .entities
+            .players_near(player.x, player.z, view_distance, uuid)
+            .await;
+        viewers.push(*uuid);
+        for viewer in viewers {
+            server.player_list.send_to(&viewer, &spawn_packet).await?;
+            server.player_list.send_to(&viewer, &metadata).await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Command for GiveCommand {
+    fn name(&self) -> &'static str {
+        "give"
+    }
+
+    fn permission_level(&self) -> u8 {
+        2
+    }
+
+    fn node(&self) -> CommandNode {
+        let count = ArgumentParser::Integer {
+            min: Some(1),
+            max: Some(i32::from(MAX_STACK_SIZE)),
+        };
+        CommandNode::literal(self.name()).then(
+            CommandNode::argument(
+                "targets",
+                ArgumentParser::Entity {
+                    single: true,
+                    players_only: true,
+                },
+            )
+            .then(
+                CommandNode::argument("item", ArgumentParser::ItemStack)
+                    .executable()
+                    .then(CommandNode::argument("count", count).executable()),
+            ),
+        )
+    }
+
+    async fn execute(&self, ctx: CommandContext) -> Result<()> {
+        let args: Vec<&str> = ctx.args.split_whitespace().collect();
+        let (target, item, count) = match args.as_slice() {
+            [target, item] => (*target, *item, "1"),
+            [target, item, count] => (*target, *item, *count),
+            _ => return ctx.reply(USAGE).await,
+        };
+        match count.parse::<u8>() {
+            Ok(count @ 1..=MAX_STACK_SIZE) => Self::give(&ctx, target, item, count).await,
+            _ => {
+                ctx.reply(format!(
+                    "Invalid count '{}', expected 1 to {}",
+                    count, MAX_STACK_SIZE
+                ))
+                .await
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ServerConfig;
+    use crate::network::PacketReceiver;
+    use crate::protocol::packets::Packet;
+    use crate::protocol::packets::play::{SetContainerSlotPacket, SpawnEntityPacket};
+    use crate::protocol::text::TextComponent;
+    use crate::server::commands::CommandDispatcher;
+    use crate::server::commands::tests::{join, next_message, op};
+    use crate::server::context::ServerContext;
+    use crate::server::entities::PLAYER_ENTITY_TYPE;
+    use crate::server::inventory::{Container, InventoryManager};
+    use std::sync::Arc;
+    use tokio::sync::Mutex;
+
+    /// Add a player in the world with an empty inventory
+    async fn spawn(context: &ServerContext, name: &str) -> (McUuid, PacketReceiver) {
+        let (uuid, receiver) = join(context, name).await;
+        op(context, uuid, name).await;
+        let state = EntityState::new(uuid, PLAYER_ENTITY_TYPE, 0.5, 64.0, 0.5);
+        context.entities.spawn(state).await;
+        let inventory = InventoryManager::new(Container::player_inventory());
+        context
+            .inventories
+            .insert(uuid, Arc::new(Mutex::new(inventory)))
+            .await;
+        (uuid, receiver)
+    }
+
+    /// Take the next slot change queued for a player
+    fn next_slot(receiver: &mut PacketReceiver) -> (i16, Slot) {
+        let packet = receiver.try_recv().unwrap();
+        assert_eq!(packet.id.0, SetContainerSlotPacket::ID);
+        let packet = packet.parse::<SetContainerSlotPacket>().unwrap();
+        (packet.slot, packet.slot_data)
+    }
+
+    #[test]
+    fn test_item_name() {
+        assert_eq!(item_name("stone"), "minecraft:stone");
+        assert_eq!(item_name("minecraft:stone"), "minecraft:stone");
+    }
+
+    #[tokio::test]
+    async fn test_give_fills_and_overflows_inventory() {
+        let context = ServerContext::for_tests(ServerConfig::new().with_online_mode(false));
+        let (steve, mut receiver) = spawn(&context, "Steve").await;
+        let (_, mut alex_receiver) = spawn(&context, "Alex").await;
+        let dispatcher = CommandDispatcher::with_builtin_commands();
+        let stone = context.data.items().id_of("minecraft:stone").unwrap();
+
+        dispatcher
+            .dispatch(steve, "/give Steve stone 40", Arc::clone(&context))
+            .await
+            .unwrap();
+        assert_eq!(next_slot(&mut receiver), (36, Slot::new(stone, 40)));
+        assert_eq!(
+            next_message(&mut receiver),
+            TextComponent::text("Gave 40 minecraft:stone to Steve")
+        );
+
+        // The first stack is topped up and the rest starts a new one
+        dispatcher
+            .dispatch(
+                steve,
+                "/give steve minecraft:stone 64",
+                Arc::clone(&context),
+            )
+            .await
+            .unwrap();
+        assert_eq!(next_slot(&mut receiver), (36, Slot::new(stone, 64)));
+        assert_eq!(next_slot(&mut receiver), (37, Slot::new(stone, 40)));
+        next_message(&mut receiver);
+
+        // Once every slot is full, the items are dropped instead
+        let inventory = context.inventories.get(&steve).await.unwrap();
+        for index in 9..45 {
+            let slot = Slot::new(stone, MAX_STACK_SIZE);
+            inventory.lock().await.container_mut().set_slot(index, slot);
+        }
+        dispatcher
+            .dispatch(steve, "/give Steve stone 5", Arc::clone(&context))
+            .await
+            .unwrap();
+        for receiver in [&mut alex_receiver, &mut receiver] {
+            let spawn = receiver.try_recv().unwrap();
+            assert_eq!(spawn.id.0, SpawnEntityPacket::ID);
+            let spawn = spawn.parse::<SpawnEntityPacket>().unwrap();
+            assert_eq!(spawn.entity_type.0, ITEM_ENTITY_TYPE);
+            assert_eq!((spawn.x, spawn.y, spawn.z), (0.5, 64.0 + DROP_HEIGHT, 0.5));
+            let metadata = receiver.try_recv().unwrap();
+            let metadata = metadata.parse::<EntityMetadataPacket>().unwrap();
+            assert_eq!(
+                metadata.entries,
+                [(
+                    ITEM_STACK_METADATA,
+                    MetadataValue::Slot(Slot::new(stone, 5))
+                )]
+            );
+        }
+        next_message(&mut receiver);
+        assert_eq!(context.entities.len().await, 3);
+    }
+
+    #[tokio::test]
+    async fn test_give_errors() {
+        let context = ServerContext::for_tests(ServerConfig::new().with_online_mode(false));
+        let (steve, mut receiver) = spawn(&context, "Steve").await;
+        join(&context, "Alex").await;
+        let dispatcher = CommandDispatcher::with_builtin_commands();
+
+        for (line, message) in [
+            ("/give Steve diamond_sword", "Unknown item: diamond_sword"),
+            ("/give Steve air", "Unknown item: air"),
+            (
+                "/give Herobrine stone",
+                "No player named Herobrine was found",
+            ),
+            ("/give Alex stone", "Alex is not in the world"),
+            ("/give Steve stone 0", "Invalid count '0', expected 1 to 64"),
+            (
+                "/give Steve stone 65",
+                "Invalid count '65', expected 1 to 64",
+            ),
+            ("/give Steve", USAGE),
+        ] {
+            dispatcher
+                .dispatch(steve, line, Arc::clone(&context))
+                .await
+                .unwrap();
+            assert_eq!(next_message(&mut receiver), TextComponent::text(message));
+        }
+    }
+}
@@ -0,0 +1,165 @@
+//! `/kick` command
+
+use super::{Command, CommandContext};
+use crate::error::Result;
+use crate::protocol::packets::play::{
+    ArgumentParser, CommandNode, PlayDisconnectPacket, StringKind,
+};
+use crate::protocol::types::{JsonTextComponent, McUuid};
+use crate::server::context::ServerContext;
+use async_trait::async_trait;
+
+/// Usage shown when no player is given
+const USAGE: &str = "Usage: /kick <player> [<reason>]";
+
+/// Reason given when a kick has none
+pub const DEFAULT_KICK_REASON: &str = "Kicked by an operator";
+
+/// Disconnects a player with a message
+#[derive(Debug, Default, Clone, Copy)]
+pub struct KickCommand;
+
+/// Split command arguments into a player name and the rest of the line
+pub(crate) fn player_and_reason(args: &str) -> Option<(&str, Option<&str>)> {
+    let args = args.trim();
+    if args.is_empty() {
+        return None;
+    }
+    Some(match args.split_once(char::is_whitespace) {
+        Some((player, reason)) => (player, Some(reason.trim_start())),
+        None => (args, None),
+    })
+}
+
+/// Disconnect a player in the play state with a reason
+///
+/// The disconnect is queued for the player's connection, which closes once
+/// it has been sent. Returns whether the player was connected.
+pub(crate) async fn disconnect(
+    server: &ServerContext,
+    uuid: &McUuid,
+    reason: &str,
+) -> Result<bool> {
+    let packet = PlayDisconnectPacket {
+        reason: JsonTextComponent::text(reason),
+    };
+    server.player_list.send_to(uuid, &packet).await
+}
+
+#[async_trait]
+impl Command for KickCommand {
+    fn name(&self) -> &'static str {
+        "kick"
+    }
+
+    fn permission_level(&self) -> u8 {
+        3
+    }
+
+    fn node(&self) -> CommandNode {
+        CommandNode::literal(self.name()).then(
+            CommandNode::argument(
+                "targets",
+                ArgumentParser::Entity {
+                    single: true,
+                    players_only: true,
+                },
+            )
+            .executable()
+            .then(
+                CommandNode::argument("reason", ArgumentParser::String(StringKind::GreedyPhrase))
+                    .executable(),
+            ),
+        )
+    }
+
+    async fn execute(&self, ctx: CommandContext) -> Result<()> {
+        let Some((target, reason)) = player_and_reason(&ctx.args) else {
+            return ctx.reply(USAGE).await;
+        };
+        let reason = reason.unwrap_or(DEFAULT_KICK_REASON);
+        let Some((uuid, info)) = ctx.server.player_list.find_by_name(target).await else {
+            return ctx
+                .reply(format!("No player named {} was found", target))
+                .await;
+        };
+        disconnect(&ctx.server, &uuid, reason).await?;
+        ctx.reply(format!("Kicked {}: {}", info.username, reason))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ServerConfig;
+    use crate::network::PacketReceiver;
+    use crate::protocol::packets::Packet;
+    use crate::protocol::text::TextComponent;
+    use crate::server::commands::CommandDispatcher;
+    use crate::server::commands::tests::{join, next_message, op};
+    use std::sync::Arc;
+
+    /// Take the disconnect reason queued for a player
+    fn next_disconnect(receiver: &mut PacketReceiver) -> String {
+        let packet = receiver.try_recv().unwrap();
+        assert_eq!(packet.id.0, PlayDisconnectPacket::ID);
+        packet.parse::<PlayDisconnectPacket>().unwrap().reason.0
+    }
+
+    #[test]
+    fn test_player_and_reason() {
+        assert_eq!(player_and_reason("  "), None);
+        assert_eq!(player_and_reason("Steve"), Some(("Steve", None)));
+        assert_eq!(
+            player_and_reason("Steve  go  away "),
+            Some(("Steve", Some("go  away")))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_kick() {
+        let context = ServerContext::for_tests(ServerConfig::new().with_online_mode(false));
+        let (steve, mut steve_receiver) = join(&context, "Steve").await;
+        op(&context, steve, "Steve").await;
+        let (_, mut alex_receiver) = join(&context, "Alex").await;
+        let dispatcher = CommandDispatcher::with_builtin_commands();
+
+        dispatcher
+            .dispatch(steve, "/kick alex Stop spamming", Arc::clone(&context))
+            .await
+            .unwrap();
+        assert_eq!(
+            next_disconnect(&mut alex_receiver),
+            JsonTextComponent::text("Stop spamming").0
+        );
+        assert_eq!(
+            next_message(&mut steve_receiver),
+            TextComponent::text("Kicked Alex: Stop spamming")
+        );
+
+        dispatcher
+            .dispatch(steve, "/kick Alex", Arc::clone(&context))
+            .await
+            .unwrap();
+        assert_eq!(
+            next_disconnect(&mut alex_receiver),
+            JsonTextComponent::text(DEFAULT_KICK_REASON).0
+        );
+        next_message(&mut steve_receiver);
+
+        for (line, message) in [
+            ("/kick Herobrine", "No player named Herobrine was found"),
+            ("/kick", USAGE),
+        ] {
+            dispatcher
+                .dispatch(steve, line, Arc::clone(&context))
+                .await
+                .unwrap();
+            assert_eq!(
+                next_message(&mut steve_receiver),
+                TextComponent::text(message)
+            );
+        }
+    }
+}
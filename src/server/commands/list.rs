@@ -0,0 +1,65 @@
+//! `/list` command
+
+use super::{Command, CommandContext};
+use crate::error::Result;
+use async_trait::async_trait;
+
+/// Lists the players online
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ListCommand;
+
+#[async_trait]
+impl Command for ListCommand {
+    fn name(&self) -> &'static str {
+        "list"
+    }
+
+    async fn execute(&self, ctx: CommandContext) -> Result<()> {
+        let mut names: Vec<String> = ctx
+            .server
+            .player_list
+            .get_all_players()
+            .await
+            .into_iter()
+            .map(|(_, player)| player.username)
+            .collect();
+        names.sort();
+
+        ctx.reply(format!(
+            "There are {} of a max of {} players online: {}",
+            names.len(),
+            ctx.server.config.max_players,
+            names.join(", ")
+        ))
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::ServerConfig;
+    use crate::protocol::text::TextComponent;
+    use crate::server::commands::CommandDispatcher;
+    use crate::server::commands::tests::{join, next_message};
+    use crate::server::context::ServerContext;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_list_command() {
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_max_players(10);
+        let context = ServerContext::for_tests(config);
+        let (steve, mut receiver) = join(&context, "Steve").await;
+        join(&context, "Alex").await;
+
+        CommandDispatcher::with_builtin_commands()
+            .dispatch(steve, "/list", Arc::clone(&context))
+            .await
+            .unwrap();
+        assert_eq!(
+            next_message(&mut receiver),
+            TextComponent::text("There are 2 of a max of 10 players online: Alex, Steve")
+        );
+    }
+}
@@ -0,0 +1,595 @@
+//! Server commands
+//!
+//! Commands typed by players (`/name args`) are looked up in the server's
+//! [`CommandDispatcher`] and run with a [`CommandContext`] describing the
+//! sender. The dispatcher also provides the command tree sent to clients.
+
+pub mod ban;
+pub mod banlist;
+pub mod deop;
+pub mod gamemode;
+pub mod gamerule;
+pub mod give;
+pub mod kick;
+pub mod list;
+pub mod op;
+pub mod pardon;
+pub mod stop;
+pub mod time;
+pub mod tp;
+pub mod tps;
+pub mod weather;
+pub mod whitelist;
+
+pub use ban::BanCommand;
+pub use banlist::BanListCommand;
+pub use deop::DeopCommand;
+pub use gamemode::GamemodeCommand;
+pub use gamerule::GameRuleCommand;
+pub use give::GiveCommand;
+pub use kick::KickCommand;
+pub use list::ListCommand;
+pub use op::OpCommand;
+pub use pardon::PardonCommand;
+pub use stop::StopCommand;
+pub use time::SetTimeCommand;
+pub use tp::TeleportCommand;
+pub use tps::TpsCommand;
+pub use weather::WeatherCommand;
+pub use whitelist::WhitelistCommand;
+
+use crate::error::Result;
+use crate::protocol::packets::play::{CommandNode, SystemChatMessagePacket};
+use crate::protocol::text::TextComponent;
+use crate::protocol::types::McUuid;
+use crate::server::context::ServerContext;
+use crate::server::ops::MAX_PERMISSION_LEVEL;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Reply to the console running a command that needs a player
+pub const PLAYER_REQUIRED: &str = "A player is required to run this command here";
+
+/// A command that players can run
+#[async_trait]
+pub trait Command: Send + Sync {
+    /// Name of the command, without the leading `/`
+    fn name(&self) -> &'static str;
+
+    /// Node of the command in the client's command tree
+    ///
+    /// Defaults to the bare command name, taking no arguments.
+    fn node(&self) -> CommandNode {
+        CommandNode::literal(self.name()).executable()
+    }
+
+    /// Permission level needed to run the command
+    ///
+    /// Defaults to 0, which lets every player run it.
+    fn permission_level(&self) -> u8 {
+        0
+    }
+
+    /// Run the command
+    async fn execute(&self, ctx: CommandContext) -> Result<()>;
+}
+
+/// Who ran a command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandSource {
+    /// A player in the play state
+    Player(McUuid),
+    /// The server console, which can run every command
+    Console,
+    /// A remote console client, which can run every command
+    Rcon,
+}
+
+impl CommandSource {
+    /// UUID of the player who ran the command, if it was a player
+    pub fn player(self) -> Option<McUuid> {
+        match self {
+            Self::Player(uuid) => Some(uuid),
+            Self::Console | Self::Rcon => None,
+        }
+    }
+}
+
+impl From<McUuid> for CommandSource {
+    fn from(uuid: McUuid) -> Self {
+        Self::Player(uuid)
+    }
+}
+
+/// The sender and arguments of a running command
+pub struct CommandContext {
+    /// Who sent the command
+    pub sender: CommandSource,
+    /// Everything after the command name, without the separating space
+    pub args: String,
+    /// Shared server state
+    pub server: Arc<ServerContext>,
+    /// Collects the replies instead of delivering them, if set
+    output: Option<CommandOutput>,
+}
+
+impl CommandContext {
+    /// Send a message to whoever ran the command
+    pub async fn reply(&self, message: impl Into<String>) -> Result<()> {
+        self.reply_text(TextComponent::text(message)).await
+    }
+
+    /// Send a styled message to whoever ran the command
+    ///
+    /// The console only sees the plain text of the message.
+    pub async fn reply_text(&self, content: TextComponent) -> Result<()> {
+        tell(&self.server, self.sender, self.output.as_ref(), content).await
+    }
+}
+
+/// Replies to a command, collected for a sender that reads them back
+/// instead of seeing them in chat or the log
+#[derive(Debug, Clone, Default)]
+pub struct CommandOutput {
+    /// Plain text of each reply, in order
+    lines: Arc<Mutex<Vec<String>>>,
+}
+
+impl CommandOutput {
+    /// Create an output without replies
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a reply
+    pub fn push(&self, line: impl Into<String>) {
+        let mut lines = self.lines.lock().unwrap_or_else(|e| e.into_inner());
+        lines.push(line.into());
+    }
+
+    /// Every reply so far, one per line
+    pub fn text(&self) -> String {
+        let lines = self.lines.lock().unwrap_or_else(|e| e.into_inner());
+        lines.join("\n")
+    }
+}
+
+/// Send a message to a command source, as system chat for players and to
+/// the log for the consoles, or add it to the output collecting replies
+async fn tell(
+    server: &ServerContext,
+    source: CommandSource,
+    output: Option<&CommandOutput>,
+    content: TextComponent,
+) -> Result<()> {
+    if let Some(output) = output {
+        output.push(content.plain_text());
+        return Ok(());
+    }
+    match source {
+        CommandSource::Player(uuid) => {
+            let packet = SystemChatMessagePacket {
+                content,
+                overlay: false,
+            };
+            server.player_list.send_to(&uuid, &packet).await?;
+        }
+        CommandSource::Console | CommandSource::Rcon => {
+            tracing::info!("{}", content.plain_text());
+        }
+    }
+    Ok(())
+}
+
+/// Registered commands, keyed by name
+#[derive(Default)]
+pub struct CommandDispatcher {
+    /// Commands by name
+    commands: HashMap<String, Box<dyn Command>>,
+}
+
+impl CommandDispatcher {
+    /// Create a dispatcher without commands
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a dispatcher with the commands built into the server
+    pub fn with_builtin_commands() -> Self {
+        let mut dispatcher = Self::new();
+        dispatcher.register(Box::new(StopCommand));
+        dispatcher.register(Box::new(ListCommand));
+        dispatcher.register(Box::new(SetTimeCommand));
+        dispatcher.register(Box::new(GameRuleCommand));
+        dispatcher.register(Box::new(WeatherCommand));
+        dispatcher.register(Box::new(TeleportCommand));
+        dispatcher.register(Box::new(GiveCommand));
+        dispatcher.register(Box::new(GamemodeCommand));
+        dispatcher.register(Box::new(KickCommand));
+        dispatcher.register(Box::new(BanCommand));
+        dispatcher.register(Box::new(PardonCommand));
+        dispatcher.register(Box::new(BanListCommand));
+        dispatcher.register(Box::new(WhitelistCommand));
+        dispatcher.register(Box::new(OpCommand));
+        dispatcher.register(Box::new(DeopCommand));
+        dispatcher.register(Box::new(TpsCommand));
+        dispatcher
+    }
+
+    /// Register a command, replacing any command with the same name
+    pub fn register(&mut self, command: Box<dyn Command>) {
+        self.commands.insert(command.name().to_string(), command);
+    }
+
+    /// Names of the registered commands, in no particular order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.commands.keys().map(String::as_str)
+    }
+
+    /// Build the tree of every command
+    pub fn command_tree(&self) -> CommandNode {
+        self.command_tree_for(MAX_PERMISSION_LEVEL)
+    }
+
+    /// Build the command tree sent to a player with a permission level,
+    /// leaving out the commands they cannot run
+    pub fn command_tree_for(&self, level: u8) -> CommandNode {
+        let mut names: Vec<&String> = self
+            .commands
+            .iter()
+            .filter(|(_, command)| command.permission_level() <= level)
+            .map(|(name, _)| name)
+            .collect();
+        names.sort();
+        names.into_iter().fold(CommandNode::root(), |root, name| {
+            root.then(self.commands[name].node())
+        })
+    }
+
+    /// Permission level of a command source
+    ///
+    /// Players have the level of their operator entry, or 0, and the
+    /// console has the highest level.
+    pub async fn permission_level(server: &ServerContext, source: CommandSource) -> u8 {
+        match source {
+            CommandSource::Player(uuid) => server.ops.read().await.level(&uuid),
+            CommandSource::Console | CommandSource::Rcon => MAX_PERMISSION_LEVEL,
+        }
+    }
+
+    /// Whether a command source has at least a permission level
+    pub async fn has_permission(
+        server: &ServerContext,
+        source: CommandSource,
+        required_level: u8,
+    ) -> bool {
+        Self::permission_level(server, source).await >= required_level
+    }
+
+    /// Run a command line sent by a player, with or without the leading `/`
+    ///
+    /// Unknown commands and command failures are reported to the sender;
+    /// only failing to reach the sender is returned as an error.
+    pub async fn dispatch(
+        &self,
+        sender: impl Into<CommandSource>,
+        line: &str,
+        server: Arc<ServerContext>,
+    ) -> Result<()> {
+        self.run(sender.into(), line, server, None).await
+    }
+
+    /// Run a command line and collect the replies instead of sending them,
+    /// returning their text one per line
+    pub async fn dispatch_with_output(
+        &self,
+        sender: impl Into<CommandSource>,
+        line: &str,
+        server: Arc<ServerContext>,
+    ) -> Result<String> {
+        let output = CommandOutput::new();
+        self.run(sender.into(), line, server, Some(output.clone()))
+            .await?;
+        Ok(output.text())
+    }
+
+    /// Run a command line, sending replies to the sender or collecting them
+    async fn run(
+        &self,
+        sender: CommandSource,
+        line: &str,
+        server: Arc<ServerContext>,
+        output: Option<CommandOutput>,
+    ) -> Result<()> {
+        let line = line.strip_prefix('/').unwrap_or(line);
+        let (name, args) = line.split_once(' ').unwrap_or((line, ""));
+        let Some(command) = self.commands.get(name) else {
+            let message = TextComponent::text(format!("Unknown command: {}", name));
+            return tell(&server, sender, output.as_ref(), message).await;
+        };
+        if !Self::has_permission(&server, sender, command.permission_level()).await {
+            let message = TextComponent::text("You do not have permission to use this command");
+            return tell(&server, sender, output.as_ref(), message).await;
+        }
+
+        match sender {
+            CommandSource::Player(uuid) => tracing::info!("Player {} ran command: /{}", uuid, line),
+            CommandSource::Console => tracing::info!("Console ran command: /{}", line),
+            CommandSource::Rcon => tracing::info!("RCON client ran command: /{}", line),
+        }
+        let ctx = CommandContext {
+            sender,
+            args: args.to_string(),
+            server: Arc::clone(&server),
+            output: output.clone(),
+        };
+        if let Err(e) = command.execute(ctx).await {
+            tracing::debug!("Command /{} failed: {}", name, e);
+            let message = TextComponent::text(format!("Command failed: {}", e));
+            tell(&server, sender, output.as_ref(), message).await?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for CommandDispatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.commands.keys()).finish()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::config::ServerConfig;
+    use crate::error::ServerError;
+    use crate::game::player::GameMode;
+    use crate::network::PacketReceiver;
+    use crate::protocol::packets::Packet;
+    use crate::protocol::packets::play::PlayDisconnectPacket;
+    use crate::server::ops::OpEntry;
+    use crate::server::player_list::PlayerInfo;
+    use std::sync::atomic::Ordering;
+    use tokio::sync::mpsc;
+
+    /// Add a player to the context's player list, returning its packet queue
+    pub(crate) async fn join(context: &ServerContext, name: &str) -> (McUuid, PacketReceiver) {
+        let uuid = McUuid::new_v4();
+        let (sender, receiver) = mpsc::unbounded_channel();
+        context
+            .player_list
+            .add_player(
+                uuid,
+                PlayerInfo::new(name.to_string(), GameMode::Survival),
+                sender,
+            )
+            .await;
+        (uuid, receiver)
+    }
+
+    /// Make a player an operator with the highest permission level
+    pub(crate) async fn op(context: &ServerContext, uuid: McUuid, name: &str) {
+        let entry = OpEntry::new(uuid, name, MAX_PERMISSION_LEVEL);
+        context.ops.write().await.add(entry);
+    }
+
+    /// Take the next system chat message queued for a player
+    pub(crate) fn next_message(receiver: &mut PacketReceiver) -> TextComponent {
+        let packet = receiver.try_recv().unwrap();
+        assert_eq!(packet.id.0, SystemChatMessagePacket::ID);
+        packet.parse::<SystemChatMessagePacket>().unwrap().content
+    }
+
+    /// Command that always fails
+    struct FailingCommand;
+
+    #[async_trait]
+    impl Command for FailingCommand {
+        fn name(&self) -> &'static str {
+            "fail"
+        }
+
+        async fn execute(&self, ctx: CommandContext) -> Result<()> {
+            Err(ServerError::Protocol(format!("bad args '{}'", ctx.args)))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_errors() {
+        let context = ServerContext::for_tests(ServerConfig::new().with_online_mode(false));
+        let (uuid, mut receiver) = join(&context, "Steve").await;
+
+        let mut dispatcher = CommandDispatcher::new();
+        dispatcher.register(Box::new(FailingCommand));
+
+        dispatcher
+            .dispatch(uuid, "/nope", Arc::clone(&context))
+            .await
+            .unwrap();
+        assert_eq!(
+            next_message(&mut receiver),
+            TextComponent::text("Unknown command: nope")
+        );
+
+        dispatcher
+            .dispatch(uuid, "/fail a b", Arc::clone(&context))
+            .await
+            .unwrap();
+        assert_eq!(
+            next_message(&mut receiver),
+            TextComponent::text("Command failed: Protocol error: bad args 'a b'")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_with_output() {
+        let context = ServerContext::for_tests(ServerConfig::new().with_online_mode(false));
+        let (_, mut receiver) = join(&context, "Steve").await;
+        let mut dispatcher = CommandDispatcher::with_builtin_commands();
+        dispatcher.register(Box::new(FailingCommand));
+
+        let output = dispatcher
+            .dispatch_with_output(CommandSource::Rcon, "list", Arc::clone(&context))
+            .await
+            .unwrap();
+        assert!(output.contains("Steve"), "{}", output);
+        let output = dispatcher
+            .dispatch_with_output(CommandSource::Rcon, "fail x", Arc::clone(&context))
+            .await
+            .unwrap();
+        assert_eq!(output, "Command failed: Protocol error: bad args 'x'");
+        let output = dispatcher
+            .dispatch_with_output(CommandSource::Rcon, "nope", Arc::clone(&context))
+            .await
+            .unwrap();
+        assert_eq!(output, "Unknown command: nope");
+        // Nothing reaches the players
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_permission_levels() {
+        let context = ServerContext::for_tests(ServerConfig::new().with_online_mode(false));
+        let (steve, mut receiver) = join(&context, "Steve").await;
+        let dispatcher = CommandDispatcher::with_builtin_commands();
+        assert!(!CommandDispatcher::has_permission(&context, steve.into(), 1).await);
+        assert!(CommandDispatcher::has_permission(&context, CommandSource::Console, 4).await);
+
+        // Players who are not operators cannot stop the server
+        for line in [
+            "/stop",
+            "/kick Steve",
+            "/ban Steve",
+            "/op Steve",
+            "/tp 0 0 0",
+        ] {
+            dispatcher
+                .dispatch(steve, line, Arc::clone(&context))
+                .await
+                .unwrap();
+            assert_eq!(
+                next_message(&mut receiver),
+                TextComponent::text("You do not have permission to use this command"),
+                "{}",
+                line
+            );
+        }
+        assert!(!context.shutdown.load(Ordering::Relaxed));
+        assert!(context.bans.read().await.is_empty());
+
+        // Level 3 is enough to kick but not to stop the server
+        context
+            .ops
+            .write()
+            .await
+            .add(OpEntry::new(steve, "Steve", 3));
+        dispatcher
+            .dispatch(steve, "/stop", Arc::clone(&context))
+            .await
+            .unwrap();
+        next_message(&mut receiver);
+        assert!(!context.shutdown.load(Ordering::Relaxed));
+        dispatcher
+            .dispatch(steve, "/kick Steve", Arc::clone(&context))
+            .await
+            .unwrap();
+        assert_eq!(receiver.try_recv().unwrap().id.0, PlayDisconnectPacket::ID);
+
+        dispatcher
+            .dispatch(CommandSource::Console, "stop", Arc::clone(&context))
+            .await
+            .unwrap();
+        assert!(context.shutdown.load(Ordering::Relaxed));
+    }
+
+    #[tokio::test]
+    async fn test_game_commands_need_level_2() {
+        let context = ServerContext::for_tests(ServerConfig::new().with_online_mode(false));
+        let (steve, mut receiver) = join(&context, "Steve").await;
+        let dispatcher = CommandDispatcher::with_builtin_commands();
+        let lines = [
+            "/gamemode creative",
+            "/give Steve stone",
+            "/gamerule keepInventory true",
+            "/time set night",
+            "/weather rain",
+        ];
+
+        // Players who are not operators cannot change the game
+        for line in lines {
+            dispatcher
+                .dispatch(steve, line, Arc::clone(&context))
+                .await
+                .unwrap();
+            assert_eq!(
+                next_message(&mut receiver),
+                TextComponent::text("You do not have permission to use this command"),
+                "{}",
+                line
+            );
+        }
+
+        // Level 2 is enough for all of them
+        context
+            .ops
+            .write()
+            .await
+            .add(OpEntry::new(steve, "Steve", 2));
+        for line in lines {
+            dispatcher
+                .dispatch(steve, line, Arc::clone(&context))
+                .await
+                .unwrap();
+            while let Ok(packet) = receiver.try_recv() {
+                if packet.id.0 == SystemChatMessagePacket::ID {
+                    let reply = packet.parse::<SystemChatMessagePacket>().unwrap();
+                    let reply = reply.content.plain_text();
+                    assert!(!reply.contains("permission"), "{}: {}", line, reply);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_command_tree_for_level() {
+        let dispatcher = CommandDispatcher::with_builtin_commands();
+        let CommandNode::Root { children } = dispatcher.command_tree_for(0) else {
+            unreachable!("The command tree should have a root");
+        };
+        assert!(children.contains(&CommandNode::literal("list").executable()));
+        assert!(!children.contains(&StopCommand.node()));
+        assert!(!children.contains(&KickCommand.node()));
+
+        let CommandNode::Root { children } = dispatcher.command_tree_for(3) else {
+            unreachable!("The command tree should have a root");
+        };
+        assert!(children.contains(&KickCommand.node()));
+        assert!(!children.contains(&StopCommand.node()));
+    }
+
+    #[test]
+    fn test_command_tree() {
+        let tree = CommandDispatcher::with_builtin_commands().command_tree();
+        assert_eq!(
+            tree,
+            CommandNode::root()
+                .then(BanCommand.node())
+                .then(CommandNode::literal("banlist").executable())
+                .then(DeopCommand.node())
+                .then(GamemodeCommand.node())
+                .then(GameRuleCommand.node())
+                .then(GiveCommand.node())
+                .then(KickCommand.node())
+                .then(CommandNode::literal("list").executable())
+                .then(OpCommand.node())
+                .then(PardonCommand.node())
+                .then(CommandNode::literal("stop").executable())
+                .then(SetTimeCommand.node())
+                .then(TeleportCommand.node())
+                .then(CommandNode::literal("tps").executable())
+                .then(WeatherCommand.node())
+                .then(WhitelistCommand.node())
+        );
+    }
+}
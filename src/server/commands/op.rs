@@ -0,0 +1,172 @@
+//! `/op` command
+
+use super::{Command, CommandContext};
+use crate::error::Result;
+use crate::protocol::packets::play::{
+    ArgumentParser, CommandNode, DeclareCommandsPacket, EntityEventPacket,
+};
+use crate::protocol::types::McUuid;
+use crate::server::context::ServerContext;
+use crate::server::ops::{MAX_PERMISSION_LEVEL, OpEntry};
+use async_trait::async_trait;
+
+/// Usage shown when the arguments are not understood
+const USAGE: &str = "Usage: /op <player>";
+
+/// Makes an online player a server operator
+#[derive(Debug, Default, Clone, Copy)]
+pub struct OpCommand;
+
+/// Tell an online player about their current permission level
+///
+/// They are sent the commands they can now run and the level itself, which
+/// the client uses for features such as the game mode switcher.
+pub(crate) async fn send_permission_level(server: &ServerContext, uuid: &McUuid) -> Result<()> {
+    let level = server.ops.read().await.level(uuid);
+    let commands = DeclareCommandsPacket::new(server.commands.command_tree_for(level));
+    server.player_list.send_to(uuid, &commands).await?;
+    if let Some((entity_id, _)) = server.entities.find_player(uuid).await {
+        let event = EntityEventPacket::permission_level(entity_id, level);
+        server.player_list.send_to(uuid, &event).await?;
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl Command for OpCommand {
+    fn name(&self) -> &'static str {
+        "op"
+    }
+
+    fn permission_level(&self) -> u8 {
+        MAX_PERMISSION_LEVEL
+    }
+
+    fn node(&self) -> CommandNode {
+        CommandNode::literal(self.name()).then(
+            CommandNode::argument(
+                "targets",
+                ArgumentParser::Entity {
+                    single: true,
+                    players_only: true,
+                },
+            )
+            .executable(),
+        )
+    }
+
+    async fn execute(&self, ctx: CommandContext) -> Result<()> {
+        let args: Vec<&str> = ctx.args.split_whitespace().collect();
+        let [target] = args.as_slice() else {
+            return ctx.reply(USAGE).await;
+        };
+        let server = &ctx.server;
+        let Some((uuid, info)) = server.player_list.find_by_name(target).await else {
+            return ctx
+                .reply(format!("No player named {} was found", target))
+                .await;
+        };
+        let entry = OpEntry::new(uuid, &info.username, server.config.op_permission_level);
+        if !server.ops.write().await.add(entry) {
+            return ctx
+                .reply("Nothing changed. The player already is an operator")
+                .await;
+        }
+        server.save_ops().await?;
+        send_permission_level(server, &uuid).await?;
+        ctx.reply(format!("Made {} a server operator", info.username))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ServerConfig;
+    use crate::network::PacketReceiver;
+    use crate::protocol::packets::Packet;
+    use crate::protocol::text::TextComponent;
+    use crate::server::commands::CommandDispatcher;
+    use crate::server::commands::tests::{join, next_message, op};
+    use crate::server::entities::{EntityState, PLAYER_ENTITY_TYPE};
+    use std::sync::Arc;
+
+    /// Take the command tree and permission level sent to a player
+    fn next_permissions(receiver: &mut PacketReceiver) -> (CommandNode, u8) {
+        let commands = receiver.try_recv().unwrap();
+        let commands = commands.parse::<DeclareCommandsPacket>().unwrap();
+        let event = receiver.try_recv().unwrap();
+        assert_eq!(event.id.0, EntityEventPacket::ID);
+        let event = event.parse::<EntityEventPacket>().unwrap();
+        (
+            commands.root,
+            event.status - EntityEventPacket::OP_PERMISSION_LEVEL_0,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_op() {
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_op_permission_level(3);
+        let context = ServerContext::for_tests(config);
+        let (steve, mut steve_receiver) = join(&context, "Steve").await;
+        op(&context, steve, "Steve").await;
+        let (alex, mut alex_receiver) = join(&context, "Alex").await;
+        let state = EntityState::new(alex, PLAYER_ENTITY_TYPE, 0.5, 64.0, 0.5);
+        context.entities.spawn(state).await;
+        let dispatcher = CommandDispatcher::with_builtin_commands();
+
+        dispatcher
+            .dispatch(steve, "/op alex", Arc::clone(&context))
+            .await
+            .unwrap();
+        let (tree, level) = next_permissions(&mut alex_receiver);
+        assert_eq!(level, 3);
+        assert_eq!(tree, context.commands.command_tree_for(3));
+        assert_eq!(
+            next_message(&mut steve_receiver),
+            TextComponent::text("Made Alex a server operator")
+        );
+        assert_eq!(context.ops.read().await.level(&alex), 3);
+
+        // Level 3 is not enough to make others operators
+        for (sender, receiver, message) in [
+            (
+                steve,
+                &mut steve_receiver,
+                "Nothing changed. The player already is an operator",
+            ),
+            (
+                alex,
+                &mut alex_receiver,
+                "You do not have permission to use this command",
+            ),
+        ] {
+            dispatcher
+                .dispatch(sender, "/op Alex", Arc::clone(&context))
+                .await
+                .unwrap();
+            assert_eq!(next_message(receiver), TextComponent::text(message));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_op_errors() {
+        let context = ServerContext::for_tests(ServerConfig::new().with_online_mode(false));
+        let (steve, mut receiver) = join(&context, "Steve").await;
+        op(&context, steve, "Steve").await;
+        let dispatcher = CommandDispatcher::with_builtin_commands();
+
+        for (line, message) in [
+            ("/op Herobrine", "No player named Herobrine was found"),
+            ("/op", USAGE),
+        ] {
+            dispatcher
+                .dispatch(steve, line, Arc::clone(&context))
+                .await
+                .unwrap();
+            assert_eq!(next_message(&mut receiver), TextComponent::text(message));
+        }
+    }
+}
@@ -0,0 +1,83 @@
+//! `/pardon` command
+
+use super::{Command, CommandContext};
+use crate::error::Result;
+use crate::protocol::packets::play::{ArgumentParser, CommandNode, StringKind};
+use async_trait::async_trait;
+
+/// Usage shown when the arguments are not understood
+const USAGE: &str = "Usage: /pardon <player>";
+
+/// Lifts the ban of a player
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PardonCommand;
+
+#[async_trait]
+impl Command for PardonCommand {
+    fn name(&self) -> &'static str {
+        "pardon"
+    }
+
+    fn permission_level(&self) -> u8 {
+        4
+    }
+
+    fn node(&self) -> CommandNode {
+        CommandNode::literal(self.name()).then(
+            CommandNode::argument("targets", ArgumentParser::String(StringKind::SingleWord))
+                .executable(),
+        )
+    }
+
+    async fn execute(&self, ctx: CommandContext) -> Result<()> {
+        let args: Vec<&str> = ctx.args.split_whitespace().collect();
+        let [target] = args.as_slice() else {
+            return ctx.reply(USAGE).await;
+        };
+        let removed = ctx.server.bans.write().await.remove(target);
+        let Some(ban) = removed else {
+            return ctx.reply("Nothing changed. The player isn't banned").await;
+        };
+        ctx.server.save_bans().await?;
+        ctx.reply(format!("Unbanned {}", ban.name)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::ServerConfig;
+    use crate::protocol::text::TextComponent;
+    use crate::protocol::types::McUuid;
+    use crate::server::bans::BanEntry;
+    use crate::server::commands::CommandDispatcher;
+    use crate::server::commands::tests::{join, next_message, op};
+    use crate::server::context::ServerContext;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_pardon() {
+        let context = ServerContext::for_tests(ServerConfig::new().with_online_mode(false));
+        let (steve, mut receiver) = join(&context, "Steve").await;
+        op(&context, steve, "Steve").await;
+        let alex = McUuid::new_v4();
+        context
+            .bans
+            .write()
+            .await
+            .add(BanEntry::new("Alex", Some(alex), "Griefing"));
+        let dispatcher = CommandDispatcher::with_builtin_commands();
+
+        for (line, message) in [
+            ("/pardon alex", "Unbanned Alex"),
+            ("/pardon Alex", "Nothing changed. The player isn't banned"),
+            ("/pardon", super::USAGE),
+        ] {
+            dispatcher
+                .dispatch(steve, line, Arc::clone(&context))
+                .await
+                .unwrap();
+            assert_eq!(next_message(&mut receiver), TextComponent::text(message));
+        }
+        assert!(context.bans.read().await.is_empty());
+    }
+}
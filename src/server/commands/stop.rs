@@ -0,0 +1,51 @@
+//! `/stop` command
+
+use super::{Command, CommandContext};
+use crate::error::Result;
+use async_trait::async_trait;
+use std::sync::atomic::Ordering;
+
+/// Shuts the server down
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StopCommand;
+
+#[async_trait]
+impl Command for StopCommand {
+    fn name(&self) -> &'static str {
+        "stop"
+    }
+
+    fn permission_level(&self) -> u8 {
+        4
+    }
+
+    async fn execute(&self, ctx: CommandContext) -> Result<()> {
+        ctx.reply("Stopping the server").await?;
+        ctx.server.shutdown.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ServerConfig;
+    use crate::server::commands::CommandDispatcher;
+    use crate::server::commands::tests::{join, op};
+    use crate::server::context::ServerContext;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_stop_command() {
+        let context = ServerContext::for_tests(ServerConfig::new().with_online_mode(false));
+        let (uuid, _receiver) = join(&context, "Steve").await;
+        op(&context, uuid, "Steve").await;
+        assert!(!context.shutdown.load(Ordering::Relaxed));
+
+        CommandDispatcher::with_builtin_commands()
+            .dispatch(uuid, "/stop", Arc::clone(&context))
+            .await
+            .unwrap();
+        assert!(context.shutdown.load(Ordering::Relaxed));
+    }
+}
@@ -0,0 +1,125 @@
+//! `/time` command
+
+use super::{Command, CommandContext};
+use crate::error::Result;
+use crate::protocol::packets::play::{ArgumentParser, CommandNode};
+use crate::server::world::time::{DAY, MIDNIGHT, NIGHT, NOON};
+use async_trait::async_trait;
+
+/// Usage shown when the arguments are not understood
+const USAGE: &str = "Usage: /time set <day|noon|night|midnight|ticks>";
+
+/// Sets the time of day of the world
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SetTimeCommand;
+
+/// Parse the time given to `/time set`
+fn parse_time(time: &str) -> Option<i64> {
+    match time {
+        "day" => Some(DAY),
+        "noon" => Some(NOON),
+        "night" => Some(NIGHT),
+        "midnight" => Some(MIDNIGHT),
+        ticks => ticks
+            .parse::<i32>()
+            .ok()
+            .filter(|ticks| *ticks >= 0)
+            .map(i64::from),
+    }
+}
+
+#[async_trait]
+impl Command for SetTimeCommand {
+    fn name(&self) -> &'static str {
+        "time"
+    }
+
+    fn permission_level(&self) -> u8 {
+        2
+    }
+
+    fn node(&self) -> CommandNode {
+        let set = ["day", "midnight", "night", "noon"]
+            .into_iter()
+            .fold(CommandNode::literal("set"), |set, name| {
+                set.then(CommandNode::literal(name).executable())
+            })
+            .then(
+                CommandNode::argument(
+                    "time",
+                    ArgumentParser::Integer {
+                        min: Some(0),
+                        max: None,
+                    },
+                )
+                .executable(),
+            );
+        CommandNode::literal(self.name()).then(set)
+    }
+
+    async fn execute(&self, ctx: CommandContext) -> Result<()> {
+        let time = match ctx.args.split_once(' ') {
+            Some(("set", time)) => parse_time(time),
+            _ => None,
+        };
+        let Some(time) = time else {
+            return ctx.reply(USAGE).await;
+        };
+
+        let packet = {
+            let mut world_time = ctx.server.time.write().await;
+            world_time.day_time = time;
+            world_time.packet()
+        };
+        ctx.server.player_list.broadcast(&packet).await?;
+        ctx.reply(format!("Set the time to {}", time)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::ServerConfig;
+    use crate::protocol::packets::Packet;
+    use crate::protocol::packets::play::UpdateTimePacket;
+    use crate::protocol::text::TextComponent;
+    use crate::server::commands::CommandDispatcher;
+    use crate::server::commands::tests::{join, next_message, op};
+    use crate::server::context::ServerContext;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_time_set() {
+        let context = ServerContext::for_tests(ServerConfig::new().with_online_mode(false));
+        let (steve, mut receiver) = join(&context, "Steve").await;
+        op(&context, steve, "Steve").await;
+        let dispatcher = CommandDispatcher::with_builtin_commands();
+
+        for (line, day_time) in [("/time set night", 13_000), ("/time set 500", 500)] {
+            dispatcher
+                .dispatch(steve, line, Arc::clone(&context))
+                .await
+                .unwrap();
+            let packet = receiver.try_recv().unwrap();
+            assert_eq!(packet.id.0, UpdateTimePacket::ID);
+            let packet = packet.parse::<UpdateTimePacket>().unwrap();
+            assert_eq!(packet.time_of_day, day_time);
+            assert_eq!(
+                next_message(&mut receiver),
+                TextComponent::text(format!("Set the time to {}", day_time))
+            );
+        }
+        assert_eq!(context.time.read().await.day_time, 500);
+
+        for line in ["/time set dusk", "/time set -1", "/time"] {
+            dispatcher
+                .dispatch(steve, line, Arc::clone(&context))
+                .await
+                .unwrap();
+            assert_eq!(
+                next_message(&mut receiver),
+                TextComponent::text(super::USAGE)
+            );
+        }
+        assert_eq!(context.time.read().await.day_time, 500);
+    }
+}
@@ -0,0 +1,361 @@
+//! `/tp` command
+
+use super::{Command, CommandContext};
+use crate::error::Result;
+use crate::game::entity::EntityId;
+use crate::protocol::packets::play::{
+    ArgumentParser, CommandNode, PlayerPositionPacket, TeleportEntityPacket,
+};
+use crate::protocol::types::{McUuid, VarInt};
+use async_trait::async_trait;
+
+/// Usage shown when the number of arguments is wrong
+const USAGE: &str = "Usage: /tp <x> <y> <z> or /tp <player> [<destination>]";
+
+/// Largest horizontal coordinate a player can be teleported to
+const MAX_HORIZONTAL: f64 = 30_000_000.0;
+
+/// Largest vertical distance from 0 a player can be teleported to
+const MAX_VERTICAL: f64 = 20_000_000.0;
+
+/// Teleports a player to coordinates or to another player
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TeleportCommand;
+
+/// Parse a coordinate, which is relative to `current` when it starts with `~`
+///
+/// Whole absolute coordinates are moved to the center of their block when
+/// `center` is set, as for the horizontal axes.
+fn parse_coordinate(arg: &str, current: f64, center: bool) -> Option<f64> {
+    let value = match arg.strip_prefix('~') {
+        Some("") => current,
+        Some(offset) => current + offset.parse::<f64>().ok()?,
+        None if center && !arg.contains('.') => f64::from(arg.parse::<i32>().ok()?) + 0.5,
+        None => arg.parse().ok()?,
+    };
+    value.is_finite().then_some(value)
+}
+
+/// A player named in the command, or the sender
+#[derive(Debug, Clone, Copy)]
+enum Player<'a> {
+    /// The player who ran the command
+    Sender,
+    /// A player by username
+    Named(&'a str),
+}
+
+/// A player that can be teleported
+struct Teleportee {
+    /// Player UUID
+    uuid: McUuid,
+    /// Username
+    username: String,
+    /// Entity ID
+    entity_id: EntityId,
+    /// Position of the player's feet
+    position: (f64, f64, f64),
+}
+
+impl TeleportCommand {
+    /// Look up the entity of an online player
+    async fn find(ctx: &CommandContext, player: Player<'_>) -> Option<Teleportee> {
+        let (uuid, info) = match player {
+            Player::Named(name) => ctx.server.player_list.find_by_name(name).await?,
+            Player::Sender => {
+                let uuid = ctx.sender.player()?;
+                (uuid, ctx.server.player_list.get_player(&uuid).await?)
+            }
+        };
+        let (entity_id, state) = ctx.server.entities.find_player(&uuid).await?;
+        Some(Teleportee {
+            uuid,
+            username: info.username,
+            entity_id,
+            position: (state.x, state.y, state.z),
+        })
+    }
+
+    /// Move the sender to coordinates, which may be relative to where they
+    /// are
+    async fn teleport_to_coordinates(
+        ctx: &CommandContext,
+        (x, y, z): (&str, &str, &str),
+    ) -> Result<()> {
+        let Some(sender) = Self::find(ctx, Player::Sender).await else {
+            return ctx.reply("Only players in the world can teleport").await;
+        };
+        let (current_x, current_y, current_z) = sender.position;
+        let position = (
+            parse_coordinate(x, current_x, true),
+            parse_coordinate(y, current_y, false),
+            parse_coordinate(z, current_z, true),
+        );
+        let (Some(x), Some(y), Some(z)) = position else {
+            return ctx
+                .reply(format!("Invalid coordinates: {}", ctx.args))
+                .await;
+        };
+        if x.abs() > MAX_HORIZONTAL || z.abs() > MAX_HORIZONTAL || y.abs() > MAX_VERTICAL {
+            return ctx.reply("Invalid position for teleport").await;
+        }
+
+        Self::teleport(ctx, &sender, (x, y, z)).await?;
+        ctx.reply(format!(
+            "Teleported {} to {:.2}, {:.2}, {:.2}",
+            sender.username, x, y, z
+        ))
+        .await
+    }
+
+    /// Move a player to where another player is
+    async fn teleport_to_player(
+        ctx: &CommandContext,
+        target: Player<'_>,
+        destination: &str,
+    ) -> Result<()> {
+        let Some(target_player) = Self::find(ctx, target).await else {
+            return ctx
+                .reply(match target {
+                    Player::Named(name) => format!("No player named {} was found", name),
+                    Player::Sender => "Only players in the world can teleport".to_string(),
+                })
+                .await;
+        };
+        let Some(destination) = Self::find(ctx, Player::Named(destination)).await else {
+            return ctx
+                .reply(format!("No player named {} was found", destination))
+                .await;
+        };
+
+        Self::teleport(ctx, &target_player, destination.position).await?;
+        ctx.reply(format!(
+            "Teleported {} to {}",
+            target_player.username, destination.username
+        ))
+        .await
+    }
+
+    /// Move a player, telling them and the players around the destination
+    async fn teleport(
+        ctx: &CommandContext,
+        player: &Teleportee,
+        (x, y, z): (f64, f64, f64),
+    ) -> Result<()> {
+        let server = &ctx.server;
+        server
+            .entities
+            .update_position(player.entity_id, x, y, z)
+            .await;
+
+        // The player's connection replaces the teleport ID with its own
+        let mut position = PlayerPositionPacket::absolute(0, x, y, z, 0.0, 0.0);
+        position.flags = PlayerPositionPacket::RELATIVE_YAW | PlayerPositionPacket::RELATIVE_PITCH;
+        server.player_list.send_to(&player.uuid, &position).await?;
+
+        let Some(state) = server.entities.get(player.entity_id).await else {
+            return Ok(());
+        };
+        let packet = TeleportEntityPacket {
+            entity_id: VarInt(player.entity_id),
+            x,
+            y,
+            z,
+            velocity_x: 0.0,
+            velocity_y: 0.0,
+            velocity_z: 0.0,
+            yaw: state.yaw,
+            pitch: state.pitch,
+            flags: 0,
+            on_ground: state.on_ground,
+        };
+        let view_distance = i32::from(server.config.view_distance);
+        for viewer in server
+            .entities
+            .players_near(x, z, view_distance, &player.uuid)
+            .await
+        {
+            server.player_list.send_to(&viewer, &packet).await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Command for TeleportCommand {
+    fn name(&self) -> &'static str {
+        "tp"
+    }
+
+    fn permission_level(&self) -> u8 {
+        3
+    }
+
+    fn node(&self) -> CommandNode {
+        let player = |name| {
+            CommandNode::argument(
+                name,
+                ArgumentParser::Entity {
+                    single: true,
+                    players_only: true,
+                },
+            )
+        };
+        CommandNode::literal(self.name())
+            .then(CommandNode::argument("location", ArgumentParser::Vec3).executable())
+            .then(player("destination").executable())
+            .then(player("target").then(player("destination").executable()))
+    }
+
+    async fn execute(&self, ctx: CommandContext) -> Result<()> {
+        let args: Vec<&str> = ctx.args.split_whitespace().collect();
+        match args.as_slice() {
+            [x, y, z] => Self::teleport_to_coordinates(&ctx, (x, y, z)).await,
+            [destination] => Self::teleport_to_player(&ctx, Player::Sender, destination).await,
+            [target, destination] => {
+                Self::teleport_to_player(&ctx, Player::Named(target), destination).await
+            }
+            _ => ctx.reply(USAGE).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ServerConfig;
+    use crate::network::PacketReceiver;
+    use crate::protocol::packets::Packet;
+    use crate::protocol::text::TextComponent;
+    use crate::server::commands::CommandDispatcher;
+    use crate::server::commands::tests::{join, next_message, op};
+    use crate::server::context::ServerContext;
+    use crate::server::entities::{EntityState, PLAYER_ENTITY_TYPE};
+    use std::sync::Arc;
+
+    /// Add an operator with an entity at a position
+    async fn spawn(
+        context: &ServerContext,
+        name: &str,
+        (x, y, z): (f64, f64, f64),
+    ) -> (McUuid, PacketReceiver) {
+        let (uuid, receiver) = join(context, name).await;
+        op(context, uuid, name).await;
+        let state = EntityState::new(uuid, PLAYER_ENTITY_TYPE, x, y, z);
+        context.entities.spawn(state).await;
+        (uuid, receiver)
+    }
+
+    /// Take the next teleport queued for a player
+    fn next_teleport(receiver: &mut PacketReceiver) -> (f64, f64, f64) {
+        let packet = receiver.try_recv().unwrap();
+        assert_eq!(packet.id.0, PlayerPositionPacket::ID);
+        let packet = packet.parse::<PlayerPositionPacket>().unwrap();
+        (packet.x, packet.y, packet.z)
+    }
+
+    #[test]
+    fn test_parse_coordinate() {
+        assert_eq!(parse_coordinate("10", 0.0, true), Some(10.5));
+        assert_eq!(parse_coordinate("-3", 0.0, true), Some(-2.5));
+        assert_eq!(parse_coordinate("64", 0.0, false), Some(64.0));
+        assert_eq!(parse_coordinate("1.25", 0.0, true), Some(1.25));
+        assert_eq!(parse_coordinate("~", 7.5, true), Some(7.5));
+        assert_eq!(parse_coordinate("~-2.5", 7.5, true), Some(5.0));
+        assert_eq!(parse_coordinate("~x", 7.5, true), None);
+        assert_eq!(parse_coordinate("NaN", 0.0, false), None);
+        assert_eq!(parse_coordinate("", 0.0, false), None);
+    }
+
+    #[tokio::test]
+    async fn test_teleport_to_coordinates() {
+        let context = ServerContext::for_tests(ServerConfig::new().with_online_mode(false));
+        let (steve, mut steve_receiver) = spawn(&context, "Steve", (0.5, 64.0, 0.5)).await;
+        let (_, mut alex_receiver) = spawn(&context, "Alex", (40.0, 64.0, 40.0)).await;
+        let dispatcher = CommandDispatcher::with_builtin_commands();
+
+        dispatcher
+            .dispatch(steve, "/tp 40 ~2 ~-0.5", Arc::clone(&context))
+            .await
+            .unwrap();
+        assert_eq!(next_teleport(&mut steve_receiver), (40.5, 66.0, 0.0));
+        assert_eq!(
+            next_message(&mut steve_receiver),
+            TextComponent::text("Teleported Steve to 40.50, 66.00, 0.00")
+        );
+        let (_, state) = context.entities.find_player(&steve).await.unwrap();
+        assert_eq!((state.x, state.y, state.z), (40.5, 66.0, 0.0));
+
+        // Alex is near the destination and sees Steve arrive
+        let packet = alex_receiver.try_recv().unwrap();
+        assert_eq!(packet.id.0, TeleportEntityPacket::ID);
+        let packet = packet.parse::<TeleportEntityPacket>().unwrap();
+        assert_eq!((packet.x, packet.y, packet.z), (40.5, 66.0, 0.0));
+    }
+
+    #[tokio::test]
+    async fn test_teleport_to_player() {
+        let context = ServerContext::for_tests(ServerConfig::new().with_online_mode(false));
+        let (steve, mut steve_receiver) = spawn(&context, "Steve", (0.5, 64.0, 0.5)).await;
+        let (_, mut alex_receiver) = spawn(&context, "Alex", (1000.5, 80.0, 0.5)).await;
+        let dispatcher = CommandDispatcher::with_builtin_commands();
+
+        dispatcher
+            .dispatch(steve, "/tp alex", Arc::clone(&context))
+            .await
+            .unwrap();
+        assert_eq!(next_teleport(&mut steve_receiver), (1000.5, 80.0, 0.5));
+        assert_eq!(
+            next_message(&mut steve_receiver),
+            TextComponent::text("Teleported Steve to Alex")
+        );
+        assert_eq!(
+            alex_receiver.try_recv().unwrap().id.0,
+            TeleportEntityPacket::ID
+        );
+
+        context
+            .entities
+            .update_position(
+                context.entities.find_player(&steve).await.unwrap().0,
+                5.0,
+                64.0,
+                5.0,
+            )
+            .await;
+        dispatcher
+            .dispatch(steve, "/tp Alex Steve", Arc::clone(&context))
+            .await
+            .unwrap();
+        assert_eq!(next_teleport(&mut alex_receiver), (5.0, 64.0, 5.0));
+        let packet = steve_receiver.try_recv().unwrap();
+        assert_eq!(packet.id.0, TeleportEntityPacket::ID);
+        assert_eq!(
+            next_message(&mut steve_receiver),
+            TextComponent::text("Teleported Alex to Steve")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_teleport_errors() {
+        let context = ServerContext::for_tests(ServerConfig::new().with_online_mode(false));
+        let (steve, mut receiver) = spawn(&context, "Steve", (0.5, 64.0, 0.5)).await;
+        let dispatcher = CommandDispatcher::with_builtin_commands();
+
+        for (line, message) in [
+            ("/tp Herobrine", "No player named Herobrine was found"),
+            ("/tp Steve Herobrine", "No player named Herobrine was found"),
+            ("/tp ~ up ~", "Invalid coordinates: ~ up ~"),
+            ("/tp 40000000 64 0", "Invalid position for teleport"),
+            ("/tp 1 2 3 4 5", USAGE),
+        ] {
+            dispatcher
+                .dispatch(steve, line, Arc::clone(&context))
+                .await
+                .unwrap();
+            assert_eq!(next_message(&mut receiver), TextComponent::text(message));
+        }
+        let (_, state) = context.entities.find_player(&steve).await.unwrap();
+        assert_eq!((state.x, state.y, state.z), (0.5, 64.0, 0.5));
+    }
+}
@@ -0,0 +1,110 @@
+//! `/tps` command
+
+use super::{Command, CommandContext};
+use crate::error::Result;
+use crate::protocol::text::{NamedColor, TextComponent};
+use async_trait::async_trait;
+
+/// Shows the server's recent tick rate
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TpsCommand;
+
+/// Color a tick rate by how far it is below the target
+fn colored_tps(tps: f64) -> TextComponent {
+    let color = if tps >= 18.0 {
+        NamedColor::Green
+    } else if tps >= 15.0 {
+        NamedColor::Yellow
+    } else {
+        NamedColor::Red
+    };
+    TextComponent::builder(format!("{:.1}", tps))
+        .color(color)
+        .build()
+}
+
+#[async_trait]
+impl Command for TpsCommand {
+    fn name(&self) -> &'static str {
+        "tps"
+    }
+
+    async fn execute(&self, ctx: CommandContext) -> Result<()> {
+        let averages = {
+            let times = ctx.server.tick_times.read().await;
+            [
+                (times.last_minute(), " (1m) / "),
+                (times.last_minutes(5), " (5m) / "),
+                (times.last_minutes(15), " (15m)"),
+            ]
+        };
+        let mut message = TextComponent::builder("TPS: ");
+        for (tps, label) in averages {
+            message = message
+                .extra(colored_tps(tps))
+                .extra(TextComponent::text(label));
+        }
+        ctx.reply_text(message.build()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ServerConfig;
+    use crate::server::commands::CommandDispatcher;
+    use crate::server::commands::tests::{join, next_message};
+    use crate::server::context::ServerContext;
+    use crate::server::tps::TICKS_PER_MINUTE;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_tps_command() {
+        let context = ServerContext::for_tests(ServerConfig::new().with_online_mode(false));
+        let (steve, mut receiver) = join(&context, "Steve").await;
+        {
+            let mut times = context.tick_times.write().await;
+            for _ in 0..TICKS_PER_MINUTE {
+                times.record(Duration::from_millis(60));
+            }
+            for _ in 0..TICKS_PER_MINUTE {
+                times.record(Duration::from_millis(50));
+            }
+        }
+
+        CommandDispatcher::with_builtin_commands()
+            .dispatch(steve, "/tps", Arc::clone(&context))
+            .await
+            .unwrap();
+        let message = next_message(&mut receiver);
+        assert_eq!(
+            message.plain_text(),
+            "TPS: 20.0 (1m) / 18.2 (5m) / 18.2 (15m)"
+        );
+        let colors: Vec<_> = message.extra.iter().map(|child| child.color).collect();
+        assert_eq!(
+            colors,
+            [Some(NamedColor::Green), None]
+                .into_iter()
+                .cycle()
+                .take(6)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_colored_tps() {
+        for (tps, text, color) in [
+            (20.0, "20.0", NamedColor::Green),
+            (18.0, "18.0", NamedColor::Green),
+            (17.96, "18.0", NamedColor::Yellow),
+            (15.0, "15.0", NamedColor::Yellow),
+            (9.44, "9.4", NamedColor::Red),
+        ] {
+            let component = colored_tps(tps);
+            assert_eq!(component.text, text);
+            assert_eq!(component.color, Some(color));
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! `/weather` command
+
+use super::{Command, CommandContext};
+use crate::error::Result;
+use crate::protocol::packets::play::{ArgumentParser, CommandNode};
+use crate::server::world::Weather;
+use async_trait::async_trait;
+
+/// Usage shown when the arguments are not understood
+const USAGE: &str = "Usage: /weather <clear|rain|thunder> [duration]";
+
+/// Sets the weather of the world
+#[derive(Debug, Default, Clone, Copy)]
+pub struct WeatherCommand;
+
+#[async_trait]
+impl Command for WeatherCommand {
+    fn name(&self) -> &'static str {
+        "weather"
+    }
+
+    fn permission_level(&self) -> u8 {
+        2
+    }
+
+    fn node(&self) -> CommandNode {
+        let duration = CommandNode::argument(
+            "duration",
+            ArgumentParser::Integer {
+                min: Some(1),
+                max: None,
+            },
+        )
+        .executable();
+        ["clear", "rain", "thunder"].into_iter().fold(
+            CommandNode::literal(self.name()),
+            |command, name| {
+                command.then(
+                    CommandNode::literal(name)
+                        .executable()
+                        .then(duration.clone()),
+                )
+            },
+        )
+    }
+
+    async fn execute(&self, ctx: CommandContext) -> Result<()> {
+        let mut args = ctx.args.split(' ');
+        let weather = match args.next() {
+            Some("clear") => Weather::Clear,
+            Some("rain") => Weather::Rain,
+            Some("thunder") => Weather::Thunder,
+            _ => return ctx.reply(USAGE).await,
+        };
+        let duration = match (args.next(), args.next()) {
+            (None, None) => weather.random_duration(&mut rand::thread_rng()),
+            (Some(ticks), None) => match ticks.parse::<i32>() {
+                Ok(ticks) if ticks > 0 => ticks,
+                _ => return ctx.reply(USAGE).await,
+            },
+            _ => return ctx.reply(USAGE).await,
+        };
+
+        ctx.server
+            .weather
+            .write()
+            .await
+            .set_weather(weather, duration);
+        ctx.reply(match weather {
+            Weather::Clear => "Set the weather to clear",
+            Weather::Rain => "Set the weather to rain",
+            Weather::Thunder => "Set the weather to rain & thunder",
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::ServerConfig;
+    use crate::protocol::text::TextComponent;
+    use crate::server::commands::CommandDispatcher;
+    use crate::server::commands::tests::{join, next_message, op};
+    use crate::server::context::ServerContext;
+    use crate::server::world::Weather;
+    use crate::server::world::weather::THUNDER_DURATION;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_weather_command() {
+        let context = ServerContext::for_tests(ServerConfig::new().with_online_mode(false));
+        let (steve, mut receiver) = join(&context, "Steve").await;
+        op(&context, steve, "Steve").await;
+        let dispatcher = CommandDispatcher::with_builtin_commands();
+
+        dispatcher
+            .dispatch(steve, "/weather rain 600", Arc::clone(&context))
+            .await
+            .unwrap();
+        assert_eq!(
+            next_message(&mut receiver),
+            TextComponent::text("Set the weather to rain")
+        );
+        let weather = *context.weather.read().await;
+        assert_eq!(weather.weather(), Weather::Rain);
+        assert_eq!((weather.rain_time, weather.clear_time), (600, 0));
+
+        dispatcher
+            .dispatch(steve, "/weather thunder", Arc::clone(&context))
+            .await
+            .unwrap();
+        assert_eq!(
+            next_message(&mut receiver),
+            TextComponent::text("Set the weather to rain & thunder")
+        );
+        let weather = *context.weather.read().await;
+        assert_eq!(weather.weather(), Weather::Thunder);
+        assert!(THUNDER_DURATION.contains(&weather.thunder_time));
+
+        for line in ["/weather snow", "/weather clear 0", "/weather clear 10 20"] {
+            dispatcher
+                .dispatch(steve, line, Arc::clone(&context))
+                .await
+                .unwrap();
+            assert_eq!(
+                next_message(&mut receiver),
+                TextComponent::text(super::USAGE)
+            );
+        }
+        assert_eq!(context.weather.read().await.weather(), Weather::Thunder);
+    }
+}
@@ -0,0 +1,167 @@
+//! `/whitelist` command
+
+use super::{Command, CommandContext};
+use crate::error::Result;
+use crate::protocol::packets::play::{ArgumentParser, CommandNode, StringKind};
+use crate::server::whitelist::WhitelistEntry;
+use async_trait::async_trait;
+
+/// Usage shown when the arguments are not understood
+const USAGE: &str = "Usage: /whitelist <on|off|list|reload|add <player>|remove <player>>";
+
+/// Manages the players allowed to join
+#[derive(Debug, Default, Clone, Copy)]
+pub struct WhitelistCommand;
+
+impl WhitelistCommand {
+    /// Turn the whitelist on or off
+    async fn set_enabled(ctx: &CommandContext, enabled: bool) -> Result<()> {
+        let changed = ctx.server.whitelist.write().await.set_enabled(enabled);
+        let state = if enabled { "on" } else { "off" };
+        ctx.reply(if changed {
+            format!("Whitelist is now turned {}", state)
+        } else {
+            format!("Whitelist is already turned {}", state)
+        })
+        .await
+    }
+
+    /// Whitelist a player, by UUID if they are online
+    async fn add(ctx: &CommandContext, target: &str) -> Result<()> {
+        let server = &ctx.server;
+        let entry = match server.player_list.find_by_name(target).await {
+            Some((uuid, info)) => WhitelistEntry::new(info.username, Some(uuid)),
+            None => WhitelistEntry::new(target, None),
+        };
+        let name = entry.name.clone();
+        if !server.whitelist.write().await.add(entry) {
+            return ctx.reply("Player is already whitelisted").await;
+        }
+        server.save_whitelist().await?;
+        ctx.reply(format!("Added {} to the whitelist", name)).await
+    }
+
+    /// Remove a player from the whitelist
+    async fn remove(ctx: &CommandContext, target: &str) -> Result<()> {
+        let removed = ctx.server.whitelist.write().await.remove(target);
+        let Some(entry) = removed else {
+            return ctx.reply("Player is not whitelisted").await;
+        };
+        ctx.server.save_whitelist().await?;
+        ctx.reply(format!("Removed {} from the whitelist", entry.name))
+            .await
+    }
+
+    /// List the whitelisted players
+    async fn list(ctx: &CommandContext) -> Result<()> {
+        let names: Vec<String> = ctx
+            .server
+            .whitelist
+            .read()
+            .await
+            .entries()
+            .iter()
+            .map(|entry| entry.name.clone())
+            .collect();
+        let summary = match names.len() {
+            0 => return ctx.reply("There are no whitelisted players").await,
+            1 => "There is 1 whitelisted player".to_string(),
+            count => format!("There are {} whitelisted players", count),
+        };
+        ctx.reply(format!("{}: {}", summary, names.join(", ")))
+            .await
+    }
+}
+
+#[async_trait]
+impl Command for WhitelistCommand {
+    fn name(&self) -> &'static str {
+        "whitelist"
+    }
+
+    fn permission_level(&self) -> u8 {
+        3
+    }
+
+    fn node(&self) -> CommandNode {
+        let target =
+            CommandNode::argument("targets", ArgumentParser::String(StringKind::SingleWord))
+                .executable();
+        let command = ["on", "off", "list", "reload"]
+            .into_iter()
+            .fold(CommandNode::literal(self.name()), |command, action| {
+                command.then(CommandNode::literal(action).executable())
+            });
+        ["add", "remove"]
+            .into_iter()
+            .fold(command, |command, action| {
+                command.then(CommandNode::literal(action).then(target.clone()))
+            })
+    }
+
+    async fn execute(&self, ctx: CommandContext) -> Result<()> {
+        let args: Vec<&str> = ctx.args.split_whitespace().collect();
+        match args.as_slice() {
+            ["on"] => Self::set_enabled(&ctx, true).await,
+            ["off"] => Self::set_enabled(&ctx, false).await,
+            ["add", target] => Self::add(&ctx, target).await,
+            ["remove", target] => Self::remove(&ctx, target).await,
+            ["list"] => Self::list(&ctx).await,
+            ["reload"] => {
+                ctx.server.reload_whitelist().await?;
+                ctx.reply("Reloaded the whitelist").await
+            }
+            _ => ctx.reply(USAGE).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ServerConfig;
+    use crate::protocol::text::TextComponent;
+    use crate::protocol::types::McUuid;
+    use crate::server::commands::CommandDispatcher;
+    use crate::server::commands::tests::{join, next_message, op};
+    use crate::server::context::ServerContext;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_whitelist() {
+        let context = ServerContext::for_tests(ServerConfig::new().with_online_mode(false));
+        let (steve, mut receiver) = join(&context, "Steve").await;
+        op(&context, steve, "Steve").await;
+        let dispatcher = CommandDispatcher::with_builtin_commands();
+
+        for (line, message) in [
+            ("/whitelist list", "There are no whitelisted players"),
+            ("/whitelist on", "Whitelist is now turned on"),
+            ("/whitelist on", "Whitelist is already turned on"),
+            ("/whitelist add steve", "Added Steve to the whitelist"),
+            ("/whitelist add Steve", "Player is already whitelisted"),
+            ("/whitelist add Alex", "Added Alex to the whitelist"),
+            (
+                "/whitelist list",
+                "There are 2 whitelisted players: Steve, Alex",
+            ),
+            ("/whitelist remove alex", "Removed Alex from the whitelist"),
+            ("/whitelist remove Alex", "Player is not whitelisted"),
+            ("/whitelist list", "There is 1 whitelisted player: Steve"),
+            ("/whitelist reload", "Reloaded the whitelist"),
+            ("/whitelist add", USAGE),
+        ] {
+            dispatcher
+                .dispatch(steve, line, Arc::clone(&context))
+                .await
+                .unwrap();
+            assert_eq!(next_message(&mut receiver), TextComponent::text(message));
+        }
+
+        // Online players are whitelisted by UUID
+        let whitelist = context.whitelist.read().await;
+        assert!(whitelist.is_enabled());
+        assert!(whitelist.allows(&steve, "Steve2"));
+        assert!(!whitelist.allows(&McUuid::new_v4(), "Steve"));
+    }
+}
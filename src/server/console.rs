@@ -0,0 +1,86 @@
+//! Server console
+//!
+//! Lines typed into the server's standard input are run as commands, with
+//! the highest permission level. Replies are written to the log.
+
+use crate::error::Result;
+use crate::server::commands::CommandSource;
+use crate::server::context::ServerContext;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, BufReader, Stdin};
+
+/// Reads commands from the console until it closes
+pub struct ConsoleInput<R> {
+    /// Lines typed by the operator
+    reader: R,
+    /// Server the commands run on
+    context: Arc<ServerContext>,
+}
+
+impl ConsoleInput<BufReader<Stdin>> {
+    /// Read commands from standard input
+    pub fn stdin(context: Arc<ServerContext>) -> Self {
+        Self::new(BufReader::new(tokio::io::stdin()), context)
+    }
+}
+
+impl<R: AsyncBufRead + Unpin> ConsoleInput<R> {
+    /// Read commands from any source of lines
+    pub fn new(reader: R, context: Arc<ServerContext>) -> Self {
+        Self { reader, context }
+    }
+
+    /// Run every line as a command, a leading `/` being optional
+    ///
+    /// Commands that fail are logged and the next line is read. Once the
+    /// input closes the server is stopped, since nothing could stop it
+    /// from the console any more.
+    pub async fn run(mut self) -> Result<()> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.reader.read_line(&mut line).await? == 0 {
+                tracing::info!("Console closed, stopping the server");
+                self.context.shutdown.store(true, Ordering::Relaxed);
+                return Ok(());
+            }
+            let command = line.trim();
+            if command.is_empty() {
+                continue;
+            }
+            // A failing command must not leave the server without a console
+            if let Err(e) = self
+                .context
+                .commands
+                .dispatch(CommandSource::Console, command, Arc::clone(&self.context))
+                .await
+            {
+                tracing::error!("Console command {} failed: {}", command, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ServerConfig;
+    use crate::protocol::types::McUuid;
+
+    #[tokio::test]
+    async fn test_console_commands() {
+        let context = ServerContext::for_tests(ServerConfig::new().with_online_mode(false));
+        let input: &[u8] = b"\n/ban Herobrine\r\nwhitelist add Steve\nnope\n";
+        ConsoleInput::new(input, Arc::clone(&context))
+            .run()
+            .await
+            .unwrap();
+
+        let bans = context.bans.read().await;
+        assert!(bans.find(&McUuid::new_v4(), "Herobrine").is_some());
+        assert_eq!(context.whitelist.read().await.entries().len(), 1);
+        // Running out of input stops the server
+        assert!(context.shutdown.load(Ordering::Relaxed));
+    }
+}
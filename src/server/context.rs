@@ -0,0 +1,433 @@
+//! Shared server state
+//!
+//! This module defines the state that is shared between the main server loop
+//! and every connection handler.
+
+use crate::config::{LevelType, ServerConfig};
+use crate::data::GameData;
+use crate::error::{Result, ServerError};
+use crate::game::game_rules::{self, GameRuleValue};
+use crate::game::world::generators::{ChunkProvider, FlatWorldGenerator, VoidWorldChunkProvider};
+use crate::game::world::{ChunkCache, ChunkStore};
+use crate::game::{player::PlayerManager, world::World};
+use crate::protocol::encryption::ServerKeys;
+use crate::protocol::packets::play::{EntityEventPacket, GameEventPacket};
+use crate::protocol::packets::status::ServerStatus;
+use crate::server::chat::{BroadcastChatRouter, ChatRouter};
+use crate::server::commands::CommandDispatcher;
+use crate::server::entities::{EntityRegistry, PLAYER_ENTITY_TYPE};
+use crate::server::events::EventBus;
+use crate::server::inventory::{ChestStore, PlayerInventories};
+use crate::server::plugin_channel::PluginChannelRegistry;
+use crate::server::suggestions::{CommandListSuggestionProvider, CommandSuggestionProvider};
+use crate::server::tick::Scheduler;
+use crate::server::{
+    BanList, BossBarManager, CookieManager, OpList, PlayerList, SignStore, TabListManager,
+    TeamManager, TickTimes, TransferManager, WeatherState, Whitelist, WorldTime,
+};
+use crate::storage::{ChunkStorage, LevelData, PaletteNames, PlayerDataStore, level_data};
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::atomic::AtomicBool;
+use tokio::sync::{RwLock, watch};
+
+/// State shared by the whole server
+pub struct ServerContext {
+    /// Server configuration
+    pub config: ServerConfig,
+    /// Player manager
+    pub players: PlayerManager,
+    /// Players in the play state, with channels to their connections
+    pub player_list: PlayerList,
+    /// Boss bars shown to every player
+    pub boss_bars: BossBarManager,
+    /// Tab list shown to every player
+    pub tab_list: TabListManager,
+    /// Servers transferred players are sent on to
+    pub transfers: TransferManager,
+    /// Cookies stored on and sent by players
+    pub cookies: CookieManager,
+    /// Entities visible to clients, including players
+    pub entities: EntityRegistry,
+    /// Main world
+    pub world: RwLock<World>,
+    /// Metadata of the main world, saved to its `level.dat`
+    pub level: RwLock<LevelData>,
+    /// Age and time of day of the main world
+    pub time: RwLock<WorldTime>,
+    /// Weather of the main world
+    pub weather: RwLock<WeatherState>,
+    /// Contents of the chests in the main world
+    pub chests: ChestStore,
+    /// Inventories of the players in the world
+    pub inventories: PlayerInventories,
+    /// Text of the signs in the main world
+    pub signs: SignStore,
+    /// Players banned from the server
+    pub bans: RwLock<BanList>,
+    /// Players allowed to join while the whitelist is enabled
+    pub whitelist: RwLock<Whitelist>,
+    /// Players who can run privileged commands
+    pub ops: RwLock<OpList>,
+    /// Saved data of players, if player data is saved
+    pub player_data: Option<PlayerDataStore>,
+    /// Chunks sent to clients, holding the blocks changed in the main world
+    pub chunk_provider: Arc<ChunkStore>,
+    /// Delivers chat messages sent by players
+    pub chat_router: Box<dyn ChatRouter>,
+    /// Handlers for serverbound plugin messages
+    pub plugin_channels: PluginChannelRegistry,
+    /// Commands players can run
+    pub commands: CommandDispatcher,
+    /// Completes commands typed by players
+    pub command_suggestions: Box<dyn CommandSuggestionProvider>,
+    /// Vanilla game data
+    pub data: GameData,
+    /// Server status template (the online player count is filled in on request)
+    pub status: ServerStatus,
+    /// RSA key pair for the login encryption handshake (online mode only)
+    pub keys: Option<ServerKeys>,
+    /// Teams players are grouped in
+    pub teams: RwLock<TeamManager>,
+    /// Listeners told about what players do
+    pub events: EventBus,
+    /// Tasks waiting to run on a later tick
+    pub scheduler: Mutex<Scheduler>,
+    /// How long recent ticks of the main loop took
+    pub tick_times: RwLock<TickTimes>,
+    /// Set to make the main loop stop the server
+    pub shutdown: AtomicBool,
+    /// Set once the server is closing, telling every connection to
+    /// disconnect its client
+    closing: watch::Sender<bool>,
+}
+
+impl ServerContext {
+    /// Create a new server context
+    pub fn new(config: ServerConfig, status: ServerStatus) -> Result<Self> {
+        let keys = if config.online_mode {
+            tracing::debug!("Generating {}-bit RSA key pair", ServerKeys::KEY_BITS);
+            Some(ServerKeys::generate()?)
+        } else {
+            None
+        };
+
+        let commands = CommandDispatcher::with_builtin_commands();
+        let command_suggestions =
+            CommandListSuggestionProvider::new(commands.names().map(str::to_string));
+
+        let level = load_level(&config)?;
+        let mut time = WorldTime::new(level.time, level.day_time);
+        time.do_daylight_cycle = level
+            .game_rules
+            .get_bool(game_rules::DO_DAYLIGHT_CYCLE)
+            .unwrap_or(true);
+        let mut weather = WeatherState::new(level.raining, level.thundering);
+        weather.rain_time = level.rain_time;
+        weather.thunder_time = level.thunder_time;
+        weather.clear_time = level.clear_weather_time;
+        weather.do_weather_cycle = level
+            .game_rules
+            .get_bool(game_rules::DO_WEATHER_CYCLE)
+            .unwrap_or(true);
+        let generator: Box<dyn ChunkProvider> = match config.level_type {
+            LevelType::Void => Box::new(VoidWorldChunkProvider::new()),
+            LevelType::Flat => Box::new(FlatWorldGenerator::default()),
+        };
+        let data = GameData::load()?;
+        let cache = ChunkCache::new(generator, config.chunk_cache_size);
+        let mut chunks = ChunkStore::new(Box::new(cache));
+        if let Some(directory) = &config.world_directory {
+            chunks = chunks.with_storage(ChunkStorage::new(directory, PaletteNames::new(&data)));
+        }
+        let chunk_provider = Arc::new(chunks);
+        let mut world = World::new("world".to_string(), 12345, Arc::clone(&chunk_provider));
+        world.set_spawn_position(level.spawn_position());
+
+        let player_data = config
+            .world_directory
+            .as_ref()
+            .map(|directory| PlayerDataStore::new(directory.join("playerdata")));
+        let bans = match &config.ban_list_file {
+            Some(path) => BanList::load(path)?,
+            None => BanList::new(),
+        };
+        let whitelist = match &config.whitelist_file {
+            Some(path) => Whitelist::load(config.white_list, path)?,
+            None => Whitelist::new(config.white_list),
+        };
+        let ops = match &config.op_list_file {
+            Some(path) => OpList::load(path)?,
+            None => OpList::new(),
+        };
+        let transfers = TransferManager::new(config.transfer_backends.clone());
+        let player_list = PlayerList::new();
+        Ok(Self {
+            config,
+            players: PlayerManager::new(),
+            boss_bars: BossBarManager::new(player_list.clone()),
+            tab_list: TabListManager::new(player_list.clone()),
+            transfers,
+            cookies: CookieManager::new(player_list.clone()),
+            player_list,
+            entities: EntityRegistry::new(),
+            world: RwLock::new(world),
+            level: RwLock::new(level),
+            time: RwLock::new(time),
+            weather: RwLock::new(weather),
+            chests: ChestStore::new(),
+            inventories: PlayerInventories::new(),
+            signs: SignStore::new(),
+            bans: RwLock::new(bans),
+            whitelist: RwLock::new(whitelist),
+            ops: RwLock::new(ops),
+            player_data,
+            chunk_provider,
+            chat_router: Box::new(BroadcastChatRouter),
+            plugin_channels: PluginChannelRegistry::new(),
+            commands,
+            command_suggestions: Box::new(command_suggestions),
+            data,
+            status,
+            keys,
+            teams: RwLock::new(TeamManager::new()),
+            events: EventBus::new(),
+            scheduler: Mutex::new(Scheduler::new()),
+            tick_times: RwLock::new(TickTimes::new()),
+            shutdown: AtomicBool::new(false),
+            closing: watch::Sender::new(false),
+        })
+    }
+
+    /// Tell every connection to disconnect its client, saving the data of
+    /// the players in the world
+    pub fn close_connections(&self) {
+        self.closing.send_replace(true);
+    }
+
+    /// Watch for the server closing, see [`close_connections`]
+    ///
+    /// [`close_connections`]: Self::close_connections
+    pub fn closing(&self) -> watch::Receiver<bool> {
+        self.closing.subscribe()
+    }
+
+    /// Get the current server status with an up-to-date player count
+    pub async fn current_status(&self) -> ServerStatus {
+        let mut status = self.status.clone();
+        status.players.online = self.players.player_count().await as u32;
+        status
+    }
+
+    /// Change a game rule of the main world, returning whether the rule
+    /// exists and has the value's type
+    ///
+    /// Rules the client knows about are sent to every player, and the new
+    /// value is saved to `level.dat` right away.
+    pub async fn set_game_rule(&self, name: &str, value: GameRuleValue) -> Result<bool> {
+        if !self.level.write().await.game_rules.set(name, value) {
+            return Ok(false);
+        }
+        let enabled = value == GameRuleValue::Bool(true);
+        match name {
+            game_rules::DO_DAYLIGHT_CYCLE => {
+                let packet = {
+                    let mut time = self.time.write().await;
+                    time.do_daylight_cycle = enabled;
+                    time.packet()
+                };
+                self.player_list.broadcast(&packet).await?;
+            }
+            game_rules::DO_WEATHER_CYCLE => {
+                self.weather.write().await.do_weather_cycle = enabled;
+            }
+            game_rules::DO_IMMEDIATE_RESPAWN => {
+                let event = GameEventPacket::new(
+                    GameEventPacket::ENABLE_RESPAWN_SCREEN,
+                    f32::from(u8::from(enabled)),
+                );
+                self.player_list.broadcast(&event).await?;
+            }
+            game_rules::DO_LIMITED_CRAFTING => {
+                let event = GameEventPacket::new(
+                    GameEventPacket::LIMITED_CRAFTING,
+                    f32::from(u8::from(enabled)),
+                );
+                self.player_list.broadcast(&event).await?;
+            }
+            game_rules::REDUCED_DEBUG_INFO => {
+                let status = if enabled {
+                    EntityEventPacket::ENABLE_REDUCED_DEBUG_INFO
+                } else {
+                    EntityEventPacket::DISABLE_REDUCED_DEBUG_INFO
+                };
+                // Each player is told about their own entity
+                for (entity_id, state) in self.entities.get_all().await {
+                    if state.entity_type == PLAYER_ENTITY_TYPE {
+                        let event = EntityEventPacket { entity_id, status };
+                        self.player_list.send_to(&state.uuid, &event).await?;
+                    }
+                }
+            }
+            _ => {}
+        }
+        self.save_level().await?;
+        Ok(true)
+    }
+
+    /// Save the main world's `level.dat` and loaded chunks, if the world is
+    /// saved
+    pub async fn save_level(&self) -> Result<()> {
+        let Some(directory) = self.config.world_directory.clone() else {
+            return Ok(());
+        };
+        let mut level = self.level.read().await.clone();
+        level.set_spawn_position(self.world.read().await.spawn_position());
+        let time = *self.time.read().await;
+        (level.time, level.day_time) = (time.world_age, time.day_time);
+        let weather = *self.weather.read().await;
+        (level.raining, level.thundering) = (weather.raining, weather.thundering);
+        (level.rain_time, level.thunder_time) = (weather.rain_time, weather.thunder_time);
+        level.clear_weather_time = weather.clear_time;
+        tokio::task::spawn_blocking(move || level_data::save(&directory, &level))
+            .await
+            .map_err(|e| ServerError::Io(std::io::Error::other(e)))??;
+        let chunks = Arc::clone(&self.chunk_provider);
+        tokio::task::spawn_blocking(move || chunks.save())
+            .await
+            .map_err(|e| ServerError::Io(std::io::Error::other(e)))?
+    }
+
+    /// Save the ban list, if it is saved
+    pub async fn save_bans(&self) -> Result<()> {
+        let Some(path) = self.config.ban_list_file.clone() else {
+            return Ok(());
+        };
+        let bans = self.bans.read().await.clone();
+        tokio::task::spawn_blocking(move || bans.save(&path))
+            .await
+            .map_err(|e| ServerError::Io(std::io::Error::other(e)))?
+    }
+
+    /// Save the operators, if they are saved
+    pub async fn save_ops(&self) -> Result<()> {
+        let Some(path) = self.config.op_list_file.clone() else {
+            return Ok(());
+        };
+        let ops = self.ops.read().await.clone();
+        tokio::task::spawn_blocking(move || ops.save(&path))
+            .await
+            .map_err(|e| ServerError::Io(std::io::Error::other(e)))?
+    }
+
+    /// Save the whitelisted players, if they are saved
+    pub async fn save_whitelist(&self) -> Result<()> {
+        let Some(path) = self.config.whitelist_file.clone() else {
+            return Ok(());
+        };
+        let whitelist = self.whitelist.read().await.clone();
+        tokio::task::spawn_blocking(move || whitelist.save(&path))
+            .await
+            .map_err(|e| ServerError::Io(std::io::Error::other(e)))?
+    }
+
+    /// Read the whitelisted players from their file again, keeping whether
+    /// the whitelist is enabled
+    pub async fn reload_whitelist(&self) -> Result<()> {
+        let Some(path) = self.config.whitelist_file.clone() else {
+            return Ok(());
+        };
+        let enabled = self.whitelist.read().await.is_enabled();
+        let whitelist = tokio::task::spawn_blocking(move || Whitelist::load(enabled, &path))
+            .await
+            .map_err(|e| ServerError::Io(std::io::Error::other(e)))??;
+        *self.whitelist.write().await = whitelist;
+        Ok(())
+    }
+}
+
+/// Load the main world's `level.dat`
+///
+/// New worlds, and every world when it is not saved, start with the
+/// configured spawn position and default game rules.
+fn load_level(config: &ServerConfig) -> Result<LevelData> {
+    let new_level = || LevelData::at_spawn(config.spawn_position);
+    let Some(directory) = &config.world_directory else {
+        return Ok(new_level());
+    };
+    match level_data::load(directory) {
+        Ok(level) => Ok(level),
+        Err(ServerError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => Ok(new_level()),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+impl ServerContext {
+    /// Create a context for tests, with a status matching the configuration
+    pub(crate) fn for_tests(config: ServerConfig) -> std::sync::Arc<Self> {
+        use crate::protocol::packets::status::{Description, PlayersInfo, VersionInfo};
+        use crate::protocol::{MINECRAFT_VERSION, PROTOCOL_VERSION};
+
+        let status = ServerStatus {
+            version: VersionInfo {
+                name: MINECRAFT_VERSION.to_string(),
+                protocol: PROTOCOL_VERSION,
+            },
+            players: PlayersInfo {
+                max: config.max_players,
+                online: 0,
+                sample: None,
+            },
+            description: Description::Text(config.motd.clone()),
+            favicon: None,
+            enforces_secure_chat: false,
+        };
+        std::sync::Arc::new(Self::new(config, status).unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::world::BlockState;
+    use crate::protocol::types::Position;
+
+    #[tokio::test]
+    async fn test_level_is_saved_and_restored() {
+        let directory =
+            std::env::temp_dir().join(format!("obsidium-{}-context-level", std::process::id()));
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_world_directory(Some(directory.clone()));
+        let context = ServerContext::for_tests(config.clone());
+        context
+            .world
+            .write()
+            .await
+            .set_spawn_position(Position::new(4, 80, 4));
+        context.time.write().await.world_age = 600;
+        let stone = Position::new(3, 70, -20);
+        context
+            .world
+            .write()
+            .await
+            .set_block(stone, BlockState(1))
+            .unwrap();
+        context.save_level().await.unwrap();
+
+        let restarted =
+            ServerContext::for_tests(config.with_spawn_position(Position::new(0, 0, 0)));
+        assert_eq!(
+            restarted.world.read().await.spawn_position(),
+            Position::new(4, 80, 4)
+        );
+        assert_eq!(restarted.level.read().await.time, 600);
+        assert_eq!(restarted.time.read().await.world_age, 600);
+        assert_eq!(
+            restarted.world.read().await.get_block(stone).unwrap(),
+            Some(BlockState(1))
+        );
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
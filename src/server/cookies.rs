@@ -0,0 +1,182 @@
+//! Cookies
+//!
+//! Clients keep small values, called cookies, for the servers they play
+//! on, and send them back when asked, even after being transferred to
+//! another server. Each connection remembers the cookies it asked for and
+//! the values the client answered with; the manager stores cookies on
+//! players in the world and keeps the answers of every player online.
+
+use crate::error::{Result, ServerError};
+use crate::protocol::packets::play::StoreCookiePacket;
+use crate::protocol::types::{McIdentifier, McUuid};
+use crate::server::PlayerList;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Cookies requested from one client
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CookieStore {
+    /// Values received, or `None` for cookies the client did not have
+    cookies: HashMap<McIdentifier, Option<Vec<u8>>>,
+    /// Cookies requested but not answered yet
+    pending: HashSet<McIdentifier>,
+}
+
+impl CookieStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remember that a cookie was requested, returning `false` if a request
+    /// is already waiting for an answer
+    pub fn request(&mut self, key: McIdentifier) -> bool {
+        self.pending.insert(key)
+    }
+
+    /// Whether a cookie was requested and not answered yet
+    pub fn is_pending(&self, key: &McIdentifier) -> bool {
+        self.pending.contains(key)
+    }
+
+    /// Store the client's answer to a request
+    ///
+    /// Answers for cookies that were never requested, or answered twice,
+    /// are a protocol violation.
+    pub fn receive(&mut self, key: McIdentifier, payload: Option<Vec<u8>>) -> Result<()> {
+        if !self.pending.remove(&key) {
+            return Err(ServerError::Protocol(format!(
+                "Unexpected cookie response: {}",
+                key
+            )));
+        }
+        self.cookies.insert(key, payload);
+        Ok(())
+    }
+
+    /// Value of a cookie, if the client answered with one
+    pub fn get(&self, key: &McIdentifier) -> Option<&[u8]> {
+        self.cookies.get(key)?.as_deref()
+    }
+}
+
+/// Stores cookies on players and remembers the cookies they sent
+#[derive(Clone)]
+pub struct CookieManager {
+    /// Players cookies can be stored on
+    player_list: PlayerList,
+    /// Cookies each player online sent while logging in
+    received: Arc<RwLock<HashMap<McUuid, CookieStore>>>,
+}
+
+impl CookieManager {
+    /// Create a manager for the given players
+    pub fn new(player_list: PlayerList) -> Self {
+        Self {
+            player_list,
+            received: Arc::default(),
+        }
+    }
+
+    /// Ask a player's client to keep a cookie
+    ///
+    /// Returns `false` if the player is not in the world. Values over 5120
+    /// bytes are rejected with a protocol error.
+    pub async fn store_cookie(
+        &self,
+        uuid: &McUuid,
+        key: McIdentifier,
+        value: Vec<u8>,
+    ) -> Result<bool> {
+        let packet = StoreCookiePacket {
+            key,
+            payload: value,
+        };
+        self.player_list.send_to(uuid, &packet).await
+    }
+
+    /// Remember the cookies a player sent while logging in
+    pub async fn remember(&self, uuid: McUuid, cookies: CookieStore) {
+        self.received.write().await.insert(uuid, cookies);
+    }
+
+    /// Forget the cookies of a player that left
+    pub async fn forget(&self, uuid: &McUuid) {
+        self.received.write().await.remove(uuid);
+    }
+
+    /// Value of a cookie a player online sent while logging in
+    pub async fn cookie(&self, uuid: &McUuid, key: &McIdentifier) -> Option<Vec<u8>> {
+        let received = self.received.read().await;
+        received.get(uuid)?.get(key).map(<[u8]>::to_vec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::player::GameMode;
+    use crate::server::player_list::PlayerInfo;
+    use tokio::sync::mpsc;
+
+    #[test]
+    fn test_cookie_responses() {
+        let mut cookies = CookieStore::new();
+        let session = McIdentifier::new("lobby", "session").unwrap();
+        let missing = McIdentifier::new("lobby", "missing").unwrap();
+
+        assert!(cookies.request(session.clone()));
+        assert!(!cookies.request(session.clone()));
+        assert!(cookies.request(missing.clone()));
+        assert!(cookies.is_pending(&session));
+        assert_eq!(cookies.get(&session), None);
+
+        cookies.receive(session.clone(), Some(vec![1, 2])).unwrap();
+        cookies.receive(missing.clone(), None).unwrap();
+        assert!(!cookies.is_pending(&session));
+        assert_eq!(cookies.get(&session), Some(&[1, 2][..]));
+        assert_eq!(cookies.get(&missing), None);
+
+        // A second answer is rejected and keeps the first value
+        assert!(cookies.receive(session.clone(), Some(vec![3])).is_err());
+        assert!(
+            cookies
+                .receive(McIdentifier::minecraft("other"), None)
+                .is_err()
+        );
+        assert_eq!(cookies.get(&session), Some(&[1, 2][..]));
+    }
+
+    #[tokio::test]
+    async fn test_store_cookie() {
+        let list = PlayerList::new();
+        let cookies = CookieManager::new(list.clone());
+        let uuid = McUuid::new_v4();
+        let key = McIdentifier::new("lobby", "session").unwrap();
+        assert!(
+            !cookies
+                .store_cookie(&uuid, key.clone(), vec![1])
+                .await
+                .unwrap()
+        );
+
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let info = PlayerInfo::new("Steve".to_string(), GameMode::Survival);
+        list.add_player(uuid, info, sender).await;
+        assert!(
+            cookies
+                .store_cookie(&uuid, key.clone(), vec![1])
+                .await
+                .unwrap()
+        );
+        let packet = receiver.try_recv().unwrap();
+        let packet = packet.parse::<StoreCookiePacket>().unwrap();
+        assert_eq!(packet.key, key);
+        assert_eq!(packet.payload, [1]);
+
+        let too_long = vec![0; StoreCookiePacket::MAX_PAYLOAD_LENGTH + 1];
+        assert!(cookies.store_cookie(&uuid, key, too_long).await.is_err());
+        assert!(receiver.try_recv().is_err());
+    }
+}
@@ -0,0 +1,320 @@
+//! Entity tracking
+//!
+//! The [`EntityRegistry`] hands out entity IDs and keeps the state of every
+//! entity that clients can see, so any connection can describe them to its
+//! player.
+
+pub mod movement;
+
+pub use movement::{MovementTracker, MovementUpdate};
+
+use crate::game::entity::EntityId;
+use crate::protocol::packets::play::{SpawnEntityPacket, Velocity};
+use crate::protocol::types::{Angle, McUuid, Position, VarInt};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI32, Ordering};
+use tokio::sync::RwLock;
+
+/// ID of `minecraft:player` in the 1.21.6 `minecraft:entity_type` registry
+pub const PLAYER_ENTITY_TYPE: i32 = 149;
+
+/// ID of `minecraft:item`, a dropped item stack
+pub const ITEM_ENTITY_TYPE: i32 = 69;
+
+/// Metadata index of the stack an item entity shows
+pub const ITEM_STACK_METADATA: u8 = 8;
+
+/// Width of a standing player's bounding box
+const PLAYER_WIDTH: f64 = 0.6;
+
+/// Height of a standing player's bounding box
+const PLAYER_HEIGHT: f64 = 1.8;
+
+/// Position and type of an entity
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntityState {
+    /// Entity UUID
+    pub uuid: McUuid,
+    /// ID in the `minecraft:entity_type` registry
+    pub entity_type: i32,
+    /// X coordinate
+    pub x: f64,
+    /// Y coordinate
+    pub y: f64,
+    /// Z coordinate
+    pub z: f64,
+    /// Yaw in degrees
+    pub yaw: f32,
+    /// Pitch in degrees
+    pub pitch: f32,
+    /// Whether the entity is on the ground
+    pub on_ground: bool,
+}
+
+impl EntityState {
+    /// Create the state of an entity facing south
+    pub fn new(uuid: McUuid, entity_type: i32, x: f64, y: f64, z: f64) -> Self {
+        Self {
+            uuid,
+            entity_type,
+            x,
+            y,
+            z,
+            yaw: 0.0,
+            pitch: 0.0,
+            on_ground: false,
+        }
+    }
+
+    /// Whether the entity's bounding box overlaps a block
+    ///
+    /// Only players block placement, so every entity is given the bounding
+    /// box of a standing player.
+    pub fn intersects_block(&self, position: Position) -> bool {
+        let half_width = PLAYER_WIDTH / 2.0;
+        let (block_x, block_y, block_z) = (
+            f64::from(position.x),
+            f64::from(position.y),
+            f64::from(position.z),
+        );
+        self.x + half_width > block_x
+            && self.x - half_width < block_x + 1.0
+            && self.y + PLAYER_HEIGHT > block_y
+            && self.y < block_y + 1.0
+            && self.z + half_width > block_z
+            && self.z - half_width < block_z + 1.0
+    }
+
+    /// Build the packet spawning this entity on a client
+    pub fn spawn_packet(&self, entity_id: EntityId) -> SpawnEntityPacket {
+        SpawnEntityPacket {
+            entity_id: VarInt(entity_id),
+            uuid: self.uuid,
+            entity_type: VarInt(self.entity_type),
+            x: self.x,
+            y: self.y,
+            z: self.z,
+            pitch: Angle::from_degrees(self.pitch),
+            yaw: Angle::from_degrees(self.yaw),
+            head_yaw: Angle::from_degrees(self.yaw),
+            data: VarInt(0),
+            velocity_x: Velocity::default(),
+            velocity_y: Velocity::default(),
+            velocity_z: Velocity::default(),
+        }
+    }
+}
+
+/// Thread-safe registry of the entities in the world
+#[derive(Debug)]
+pub struct EntityRegistry {
+    /// Next entity ID to hand out
+    next_id: AtomicI32,
+    /// Entities keyed by ID
+    entities: RwLock<HashMap<EntityId, EntityState>>,
+}
+
+impl EntityRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self {
+            // Start from 1, as 0 might be reserved
+            next_id: AtomicI32::new(1),
+            entities: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Allocate a new entity ID
+    ///
+    /// IDs increase monotonically and are never reused.
+    pub fn allocate_id(&self) -> EntityId {
+        self.next_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Add an entity under a newly allocated ID, returning the ID
+    pub async fn spawn(&self, state: EntityState) -> EntityId {
+        let entity_id = self.allocate_id();
+        self.entities.write().await.insert(entity_id, state);
+        entity_id
+    }
+
+    /// Remove an entity, returning its last state
+    pub async fn remove(&self, entity_id: EntityId) -> Option<EntityState> {
+        self.entities.write().await.remove(&entity_id)
+    }
+
+    /// Get the state of an entity
+    pub async fn get(&self, entity_id: EntityId) -> Option<EntityState> {
+        self.entities.read().await.get(&entity_id).cloned()
+    }
+
+    /// Find the entity of a player
+    pub async fn find_player(&self, uuid: &McUuid) -> Option<(EntityId, EntityState)> {
+        self.entities
+            .read()
+            .await
+            .iter()
+            .find(|(_, state)| state.entity_type == PLAYER_ENTITY_TYPE && state.uuid == *uuid)
+            .map(|(entity_id, state)| (*entity_id, state.clone()))
+    }
+
+    /// Get the IDs and states of every entity
+    pub async fn get_all(&self) -> Vec<(EntityId, EntityState)> {
+        self.entities
+            .read()
+            .await
+            .iter()
+            .map(|(entity_id, state)| (*entity_id, state.clone()))
+            .collect()
+    }
+
+    /// Move an entity, returning whether it exists
+    pub async fn update_position(&self, entity_id: EntityId, x: f64, y: f64, z: f64) -> bool {
+        match self.entities.write().await.get_mut(&entity_id) {
+            Some(state) => {
+                (state.x, state.y, state.z) = (x, y, z);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Turn an entity, returning whether it exists
+    pub async fn update_rotation(&self, entity_id: EntityId, yaw: f32, pitch: f32) -> bool {
+        match self.entities.write().await.get_mut(&entity_id) {
+            Some(state) => {
+                (state.yaw, state.pitch) = (yaw, pitch);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Get the UUIDs of the players, other than one, whose chunk is within a
+    /// number of chunks of a point
+    pub async fn players_near(
+        &self,
+        x: f64,
+        z: f64,
+        chunk_radius: i32,
+        except: &McUuid,
+    ) -> Vec<McUuid> {
+        let chunk = |coordinate: f64| (coordinate.floor() as i32).div_euclid(16);
+        let (center_x, center_z) = (chunk(x), chunk(z));
+        self.entities
+            .read()
+            .await
+            .values()
+            .filter(|state| state.entity_type == PLAYER_ENTITY_TYPE && state.uuid != *except)
+            .filter(|state| {
+                (chunk(state.x) - center_x).abs() <= chunk_radius
+                    && (chunk(state.z) - center_z).abs() <= chunk_radius
+            })
+            .map(|state| state.uuid)
+            .collect()
+    }
+
+    /// Number of tracked entities
+    pub async fn len(&self) -> usize {
+        self.entities.read().await.len()
+    }
+
+    /// Whether no entities are tracked
+    pub async fn is_empty(&self) -> bool {
+        self.entities.read().await.is_empty()
+    }
+}
+
+impl Default for EntityRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_entity_registry() {
+        let registry = EntityRegistry::new();
+        let uuid = McUuid::new_v4();
+        let first = registry
+            .spawn(EntityState::new(uuid, PLAYER_ENTITY_TYPE, 0.5, 64.0, 0.5))
+            .await;
+        let second = registry.allocate_id();
+        assert!(second > first);
+
+        assert!(registry.update_position(first, 1.0, 65.0, 2.0).await);
+        assert!(!registry.update_position(second, 1.0, 65.0, 2.0).await);
+        assert!(registry.update_rotation(first, 90.0, 0.0).await);
+        let state = registry.get(first).await.unwrap();
+        assert_eq!((state.x, state.y, state.z), (1.0, 65.0, 2.0));
+
+        let packet = state.spawn_packet(first);
+        assert_eq!(packet.entity_id.0, first);
+        assert_eq!(packet.uuid, uuid);
+
+        assert_eq!(registry.get_all().await.len(), 1);
+        assert_eq!(registry.remove(first).await.unwrap().uuid, uuid);
+        assert!(registry.is_empty().await);
+    }
+
+    #[tokio::test]
+    async fn test_players_near() {
+        let registry = EntityRegistry::new();
+        let (me, near, far) = (McUuid::new_v4(), McUuid::new_v4(), McUuid::new_v4());
+        registry
+            .spawn(EntityState::new(me, PLAYER_ENTITY_TYPE, 0.5, 64.0, 0.5))
+            .await;
+        // Two chunks west, and three chunks north
+        registry
+            .spawn(EntityState::new(near, PLAYER_ENTITY_TYPE, -17.0, 64.0, 0.5))
+            .await;
+        registry
+            .spawn(EntityState::new(far, PLAYER_ENTITY_TYPE, 0.5, 64.0, -33.0))
+            .await;
+
+        assert_eq!(registry.players_near(0.5, 0.5, 2, &me).await, [near]);
+        assert_eq!(registry.players_near(0.5, 0.5, 3, &me).await.len(), 2);
+        assert!(registry.players_near(0.5, 0.5, 1, &me).await.is_empty());
+    }
+
+    #[test]
+    fn test_intersects_block() {
+        let state = EntityState::new(McUuid::new_v4(), PLAYER_ENTITY_TYPE, 0.5, 64.0, 0.5);
+        // Feet and head
+        assert!(state.intersects_block(Position::new(0, 64, 0)));
+        assert!(state.intersects_block(Position::new(0, 65, 0)));
+        // Below the feet, above the head and beside the player
+        assert!(!state.intersects_block(Position::new(0, 63, 0)));
+        assert!(!state.intersects_block(Position::new(0, 66, 0)));
+        assert!(!state.intersects_block(Position::new(1, 64, 0)));
+
+        // Standing on the edge of a block overlaps its neighbour
+        let state = EntityState::new(McUuid::new_v4(), PLAYER_ENTITY_TYPE, 0.9, 64.0, 0.5);
+        assert!(state.intersects_block(Position::new(1, 64, 0)));
+    }
+
+    #[tokio::test]
+    async fn test_unique_ids_across_tasks() {
+        let registry = Arc::new(EntityRegistry::new());
+        let tasks: Vec<_> = (0..8)
+            .map(|_| {
+                let registry = Arc::clone(&registry);
+                tokio::spawn(
+                    async move { (0..100).map(|_| registry.allocate_id()).collect::<Vec<_>>() },
+                )
+            })
+            .collect();
+
+        let mut ids = Vec::new();
+        for task in tasks {
+            ids.extend(task.await.unwrap());
+        }
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), 800);
+    }
+}
@@ -0,0 +1,220 @@
+//! Entity movement updates
+//!
+//! Clients only learn about entity movement through the packets the server
+//! sends, so each entity has a [`MovementTracker`] remembering what clients
+//! were last told. Small moves are sent as deltas in 1/4096 blocks and larger
+//! ones as absolute teleports.
+
+use crate::error::Result;
+use crate::game::entity::EntityId;
+use crate::network::RawPacket;
+use crate::protocol::packets::Packet;
+use crate::protocol::packets::play::{
+    EntityLookAndRelativeMovePacket, EntityLookPacket, EntityRelativeMovePacket,
+    TeleportEntityPacket,
+};
+use crate::protocol::types::{Angle, VarInt};
+
+/// Units per block of relative moves
+const DELTA_UNITS_PER_BLOCK: f64 = 4096.0;
+
+/// A packet telling clients how an entity moved
+#[derive(Debug, Clone, PartialEq)]
+pub enum MovementUpdate {
+    /// Small move without rotation
+    RelativeMove(EntityRelativeMovePacket),
+    /// Small move with rotation
+    LookAndRelativeMove(EntityLookAndRelativeMovePacket),
+    /// Rotation only
+    Look(EntityLookPacket),
+    /// Move of 8 blocks or more on some axis
+    Teleport(TeleportEntityPacket),
+}
+
+impl MovementUpdate {
+    /// Encode the update as a packet
+    pub fn to_raw_packet(&self) -> Result<RawPacket> {
+        match self {
+            MovementUpdate::RelativeMove(packet) => RawPacket::from_packet(packet),
+            MovementUpdate::LookAndRelativeMove(packet) => RawPacket::from_packet(packet),
+            MovementUpdate::Look(packet) => RawPacket::from_packet(packet),
+            MovementUpdate::Teleport(packet) => RawPacket::from_packet(packet),
+        }
+    }
+
+    /// Protocol ID of the packet
+    pub fn packet_id(&self) -> i32 {
+        match self {
+            MovementUpdate::RelativeMove(_) => EntityRelativeMovePacket::ID,
+            MovementUpdate::LookAndRelativeMove(_) => EntityLookAndRelativeMovePacket::ID,
+            MovementUpdate::Look(_) => EntityLookPacket::ID,
+            MovementUpdate::Teleport(_) => TeleportEntityPacket::ID,
+        }
+    }
+}
+
+/// The position and rotation clients last saw for an entity
+#[derive(Debug, Clone)]
+pub struct MovementTracker {
+    /// Entity being tracked
+    entity_id: EntityId,
+    /// Last sent position, in 1/4096 blocks
+    position: (i64, i64, i64),
+    /// Last sent yaw
+    yaw: Angle,
+    /// Last sent pitch
+    pitch: Angle,
+    /// Last sent on-ground flag
+    on_ground: bool,
+}
+
+impl MovementTracker {
+    /// Start tracking an entity at the position it was spawned at
+    pub fn new(entity_id: EntityId, x: f64, y: f64, z: f64, yaw: f32, pitch: f32) -> Self {
+        Self {
+            entity_id,
+            position: (encode(x), encode(y), encode(z)),
+            yaw: Angle::from_degrees(yaw),
+            pitch: Angle::from_degrees(pitch),
+            on_ground: false,
+        }
+    }
+
+    /// Record a new position and rotation, returning the packet that brings
+    /// clients up to date, if anything they can see changed
+    pub fn update(
+        &mut self,
+        (x, y, z): (f64, f64, f64),
+        yaw: f32,
+        pitch: f32,
+        on_ground: bool,
+    ) -> Option<MovementUpdate> {
+        let position = (encode(x), encode(y), encode(z));
+        let (yaw_angle, pitch_angle) = (Angle::from_degrees(yaw), Angle::from_degrees(pitch));
+        let moved = position != self.position;
+        let rotated = (yaw_angle, pitch_angle) != (self.yaw, self.pitch);
+        if !moved && !rotated && on_ground == self.on_ground {
+            return None;
+        }
+
+        let entity_id = VarInt(self.entity_id);
+        let deltas = (
+            i16::try_from(position.0 - self.position.0),
+            i16::try_from(position.1 - self.position.1),
+            i16::try_from(position.2 - self.position.2),
+        );
+        let update = match deltas {
+            (Ok(delta_x), Ok(delta_y), Ok(delta_z)) if rotated && moved => {
+                MovementUpdate::LookAndRelativeMove(EntityLookAndRelativeMovePacket {
+                    entity_id,
+                    delta_x,
+                    delta_y,
+                    delta_z,
+                    yaw: yaw_angle,
+                    pitch: pitch_angle,
+                    on_ground,
+                })
+            }
+            _ if rotated && !moved => MovementUpdate::Look(EntityLookPacket {
+                entity_id,
+                yaw: yaw_angle,
+                pitch: pitch_angle,
+                on_ground,
+            }),
+            (Ok(delta_x), Ok(delta_y), Ok(delta_z)) => {
+                MovementUpdate::RelativeMove(EntityRelativeMovePacket {
+                    entity_id,
+                    delta_x,
+                    delta_y,
+                    delta_z,
+                    on_ground,
+                })
+            }
+            _ => MovementUpdate::Teleport(TeleportEntityPacket {
+                entity_id,
+                x,
+                y,
+                z,
+                velocity_x: 0.0,
+                velocity_y: 0.0,
+                velocity_z: 0.0,
+                yaw,
+                pitch,
+                flags: 0,
+                on_ground,
+            }),
+        };
+
+        self.position = position;
+        self.yaw = yaw_angle;
+        self.pitch = pitch_angle;
+        self.on_ground = on_ground;
+        Some(update)
+    }
+}
+
+/// Convert a coordinate to 1/4096 blocks
+fn encode(coordinate: f64) -> i64 {
+    (coordinate * DELTA_UNITS_PER_BLOCK).round() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_small_moves_are_relative() {
+        let mut tracker = MovementTracker::new(3, 0.0, 64.0, 0.0, 0.0, 0.0);
+        assert_eq!(tracker.update((0.0, 64.0, 0.0), 0.0, 0.0, false), None);
+
+        let update = tracker.update((1.5, 64.0, -0.25), 0.0, 0.0, true);
+        assert_eq!(
+            update,
+            Some(MovementUpdate::RelativeMove(EntityRelativeMovePacket {
+                entity_id: VarInt(3),
+                delta_x: 6144,
+                delta_y: 0,
+                delta_z: -1024,
+                on_ground: true,
+            }))
+        );
+
+        // Moving and turning at once
+        let update = tracker.update((2.0, 64.0, -0.25), 90.0, 0.0, true).unwrap();
+        assert_eq!(update.packet_id(), EntityLookAndRelativeMovePacket::ID);
+
+        // Turning in place
+        let update = tracker
+            .update((2.0, 64.0, -0.25), 180.0, 0.0, true)
+            .unwrap();
+        assert_eq!(
+            update,
+            MovementUpdate::Look(EntityLookPacket {
+                entity_id: VarInt(3),
+                yaw: Angle(128),
+                pitch: Angle(0),
+                on_ground: true,
+            })
+        );
+    }
+
+    #[test]
+    fn test_large_moves_teleport() {
+        let mut tracker = MovementTracker::new(3, 0.0, 64.0, 0.0, 0.0, 0.0);
+        let update = tracker.update((8.0, 64.0, 0.0), 0.0, 0.0, false).unwrap();
+        assert_eq!(update.packet_id(), TeleportEntityPacket::ID);
+
+        // Deltas are relative to the teleport destination afterwards
+        let update = tracker.update((8.5, 64.0, 0.0), 0.0, 0.0, false).unwrap();
+        assert_eq!(
+            update,
+            MovementUpdate::RelativeMove(EntityRelativeMovePacket {
+                entity_id: VarInt(3),
+                delta_x: 2048,
+                delta_y: 0,
+                delta_z: 0,
+                on_ground: false,
+            })
+        );
+    }
+}
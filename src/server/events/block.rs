@@ -0,0 +1,35 @@
+//! Block events
+
+use super::Event;
+use crate::protocol::types::{McUuid, Position};
+
+/// A player is breaking a block, which is still in the world
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockBreakEvent {
+    /// Player breaking the block
+    pub player: McUuid,
+    /// Position of the block
+    pub position: Position,
+    /// Block state being broken
+    pub block: u32,
+    /// Whether the block stays, the client being told to restore it
+    pub cancelled: bool,
+}
+
+impl BlockBreakEvent {
+    /// Create an event for a block broken by a player
+    pub fn new(player: McUuid, position: Position, block: u32) -> Self {
+        Self {
+            player,
+            position,
+            block,
+            cancelled: false,
+        }
+    }
+}
+
+impl Event for BlockBreakEvent {
+    fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
+}
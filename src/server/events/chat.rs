@@ -0,0 +1,32 @@
+//! Chat events
+
+use super::Event;
+use crate::protocol::types::McUuid;
+
+/// A player sent a chat message, which has not been delivered yet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatEvent {
+    /// Player who sent the message
+    pub sender: McUuid,
+    /// Message delivered to the chat router, which listeners may change
+    pub message: String,
+    /// Whether the message is dropped instead of delivered
+    pub cancelled: bool,
+}
+
+impl ChatEvent {
+    /// Create an event for a message sent by a player
+    pub fn new(sender: McUuid, message: impl Into<String>) -> Self {
+        Self {
+            sender,
+            message: message.into(),
+            cancelled: false,
+        }
+    }
+}
+
+impl Event for ChatEvent {
+    fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
+}
@@ -0,0 +1,148 @@
+//! Server events
+//!
+//! Code outside the connection handlers can react to what players do by
+//! registering a [`Listener`] on the server's [`EventBus`]. Events are fired
+//! before they take effect, so listeners may change them, and cancelling an
+//! event stops it from happening at all.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+pub mod block;
+pub mod chat;
+pub mod player;
+
+pub use block::BlockBreakEvent;
+pub use chat::ChatEvent;
+pub use player::{PlayerJoinEvent, PlayerQuitEvent};
+
+/// Something that happened on the server which listeners are told about
+pub trait Event: Any + Send {
+    /// Check whether a listener cancelled the event
+    ///
+    /// Events that cannot be cancelled always return `false`.
+    fn is_cancelled(&self) -> bool {
+        false
+    }
+}
+
+/// Reacts to events of one type
+pub trait Listener<E: Event>: Send + Sync {
+    /// Handle an event, which may be changed or cancelled
+    fn on_event(&self, event: &mut E);
+}
+
+impl<E: Event, F: Fn(&mut E) + Send + Sync> Listener<E> for F {
+    fn on_event(&self, event: &mut E) {
+        self(event)
+    }
+}
+
+/// Listeners of every event type, in registration order
+#[derive(Default)]
+pub struct EventBus {
+    /// Listeners by event type, each stored as an `Arc<dyn Listener<E>>`
+    listeners: RwLock<HashMap<TypeId, Vec<Box<dyn Any + Send + Sync>>>>,
+}
+
+impl EventBus {
+    /// Create a bus without listeners
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a listener for events of type `E`
+    pub fn register<E: Event>(&self, listener: impl Listener<E> + 'static) {
+        let listener: Arc<dyn Listener<E>> = Arc::new(listener);
+        self.listeners
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(TypeId::of::<E>())
+            .or_default()
+            .push(Box::new(listener));
+    }
+
+    /// Get the number of listeners registered for events of type `E`
+    pub fn listener_count<E: Event>(&self) -> usize {
+        self.listeners
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&TypeId::of::<E>())
+            .map_or(0, Vec::len)
+    }
+
+    /// Pass an event to its listeners in registration order
+    ///
+    /// Once a listener cancels the event, the remaining ones are skipped.
+    /// Callers check [`Event::is_cancelled`] afterwards.
+    pub fn fire<E: Event>(&self, event: &mut E) {
+        // Listeners are collected first, so they can register others
+        let listeners: Vec<Arc<dyn Listener<E>>> = self
+            .listeners
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&TypeId::of::<E>())
+            .into_iter()
+            .flatten()
+            .filter_map(|listener| listener.downcast_ref::<Arc<dyn Listener<E>>>())
+            .cloned()
+            .collect();
+        for listener in listeners {
+            if event.is_cancelled() {
+                break;
+            }
+            listener.on_event(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::types::McUuid;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Censors a word, cancelling messages that are only that word
+    struct Censor(&'static str);
+
+    impl Listener<ChatEvent> for Censor {
+        fn on_event(&self, event: &mut ChatEvent) {
+            if event.message == self.0 {
+                event.cancelled = true;
+            } else {
+                event.message = event.message.replace(self.0, "***");
+            }
+        }
+    }
+
+    #[test]
+    fn test_listeners_modify_and_cancel() {
+        let bus = EventBus::new();
+        bus.register(Censor("creeper"));
+        let seen = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&seen);
+        bus.register(move |_: &mut ChatEvent| {
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+        assert_eq!(bus.listener_count::<ChatEvent>(), 2);
+        assert_eq!(bus.listener_count::<BlockBreakEvent>(), 0);
+
+        let mut event = ChatEvent::new(McUuid::new_v4(), "a creeper blew up");
+        bus.fire(&mut event);
+        assert_eq!(event.message, "a *** blew up");
+        assert!(!event.is_cancelled());
+        assert_eq!(seen.load(Ordering::Relaxed), 1);
+
+        // Listeners after the one that cancelled are not called
+        let mut event = ChatEvent::new(McUuid::new_v4(), "creeper");
+        bus.fire(&mut event);
+        assert!(event.is_cancelled());
+        assert_eq!(seen.load(Ordering::Relaxed), 1);
+
+        // Other event types have their own listeners
+        let mut quit = PlayerQuitEvent::new(McUuid::new_v4(), "Steve");
+        bus.fire(&mut quit);
+        assert_eq!(seen.load(Ordering::Relaxed), 1);
+    }
+}
@@ -0,0 +1,59 @@
+//! Player events
+
+use super::Event;
+use crate::protocol::types::McUuid;
+
+/// Reason a player is turned away when a listener cancels their join
+pub const DEFAULT_JOIN_DENIED: &str = "You are not allowed to join this server";
+
+/// A player passed the ban and whitelist checks and is about to log in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerJoinEvent {
+    /// Player's UUID
+    pub uuid: McUuid,
+    /// Player's name
+    pub username: String,
+    /// Whether the player is turned away
+    pub cancelled: bool,
+    /// Reason shown to the player if the join is cancelled
+    pub kick_message: String,
+}
+
+impl PlayerJoinEvent {
+    /// Create an event for a player logging in
+    pub fn new(uuid: McUuid, username: impl Into<String>) -> Self {
+        Self {
+            uuid,
+            username: username.into(),
+            cancelled: false,
+            kick_message: DEFAULT_JOIN_DENIED.to_string(),
+        }
+    }
+}
+
+impl Event for PlayerJoinEvent {
+    fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
+}
+
+/// A player in the play state left the server
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerQuitEvent {
+    /// Player's UUID
+    pub uuid: McUuid,
+    /// Player's name
+    pub username: String,
+}
+
+impl PlayerQuitEvent {
+    /// Create an event for a player leaving
+    pub fn new(uuid: McUuid, username: impl Into<String>) -> Self {
+        Self {
+            uuid,
+            username: username.into(),
+        }
+    }
+}
+
+impl Event for PlayerQuitEvent {}
@@ -0,0 +1,282 @@
+//! Container windows
+//!
+//! Opening chests, closing their windows and keeping the chest contents in
+//! sync with the windows showing them.
+
+use super::ConnectionHandler;
+use crate::error::Result;
+use crate::protocol::packets::play::{
+    ClickContainerPacket, CloseContainerPacket, OpenScreenPacket, ServerboundCloseContainerPacket,
+};
+use crate::protocol::types::Position;
+use crate::protocol::{TextComponent, VarInt};
+use crate::server::inventory::chest::{CHEST_GUI, CHEST_SIZE, chest_window};
+use crate::server::inventory::{Container, PLAYER_INVENTORY_WINDOW};
+
+/// Largest window ID before IDs wrap around, as vanilla servers do
+const MAX_WINDOW_ID: i32 = 100;
+
+/// A window other than the player inventory that the client has open
+pub(super) struct OpenWindow {
+    /// Position of the block the window belongs to
+    position: Position,
+    /// Slots shown in the window
+    container: Container,
+}
+
+impl ConnectionHandler {
+    /// Open the window of a chest, sending its contents along with the
+    /// player's inventory
+    pub(super) async fn open_chest(&mut self, position: Position) -> Result<()> {
+        self.release_window().await;
+        self.last_window_id = self.last_window_id % MAX_WINDOW_ID + 1;
+        let window_id = self.last_window_id;
+        let contents = self.context.chests.contents(position).await;
+        let mut container = chest_window(
+            window_id,
+            &contents,
+            self.inventory.lock().await.container(),
+        );
+
+        self.connection
+            .write_packet(&OpenScreenPacket {
+                window_id: VarInt(window_id),
+                window_type: CHEST_GUI,
+                title: TextComponent::text("Chest"),
+            })
+            .await?;
+        self.connection
+            .write_packet(&container.content_packet())
+            .await?;
+        if let Some(uuid) = self.player_uuid {
+            self.context.chests.open(position, uuid, window_id).await;
+        }
+        self.client_state.open_window_id = Some(window_id);
+        self.open_window = Some(OpenWindow {
+            position,
+            container,
+        });
+        Ok(())
+    }
+
+    /// Handle the client closing a window
+    pub(super) async fn handle_close_container(&mut self, close: ServerboundCloseContainerPacket) {
+        let window_id = close.window_id.0;
+        if window_id == PLAYER_INVENTORY_WINDOW {
+            return;
+        }
+        if self.client_state.open_window_id != Some(window_id) {
+            tracing::debug!(
+                "Client closed window {}, but {:?} is open",
+                window_id,
+                self.client_state.open_window_id
+            );
+            return;
+        }
+        self.release_window().await;
+    }
+
+    /// Apply a click in a window, correcting the client if the server
+    /// disagrees with it
+    pub(super) async fn handle_click_container(
+        &mut self,
+        click: ClickContainerPacket,
+    ) -> Result<()> {
+        let correction = self.inventory.lock().await.handle_click(&click);
+        if let Some(correction) = correction {
+            self.connection.write_packet(&correction).await?;
+        }
+        self.sync_equipment().await
+    }
+
+    /// Stop tracking the open window, saving the chest slots it shows unless
+    /// the chest was broken meanwhile
+    pub(super) async fn release_window(&mut self) {
+        let Some(window) = self.open_window.take() else {
+            return;
+        };
+        debug_assert_eq!(
+            self.client_state.open_window_id,
+            Some(window.container.window_id()),
+            "Open window ID out of sync"
+        );
+        self.client_state.open_window_id = None;
+
+        let Some(uuid) = self.player_uuid else {
+            return;
+        };
+        let chests = &self.context.chests;
+        if chests
+            .close(window.position, &uuid, window.container.window_id())
+            .await
+        {
+            let slots = (0..CHEST_SIZE)
+                .filter_map(|index| window.container.get_slot(index).cloned())
+                .collect();
+            chests.set_contents(window.position, slots).await;
+        }
+    }
+
+    /// Close the windows of every player with a broken chest open
+    pub(super) async fn close_chest_windows(&mut self, position: Position) -> Result<()> {
+        if self
+            .open_window
+            .as_ref()
+            .is_some_and(|window| window.position == position)
+        {
+            self.open_window = None;
+            self.client_state.open_window_id = None;
+        }
+        for (viewer, window_id) in self.context.chests.take_viewers(position).await {
+            self.context
+                .player_list
+                .send_to(
+                    &viewer,
+                    &CloseContainerPacket {
+                        window_id: VarInt(window_id),
+                    },
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ServerConfig;
+    use crate::game::world::BlockState;
+    use crate::protocol::packets::Packet;
+    use crate::protocol::packets::play::{
+        AcknowledgeBlockChangePacket, BlockFace, GuiType, Hand, PlayerActionPacket,
+        PlayerActionStatus, SetContainerContentPacket, UseItemOnPacket,
+    };
+    use crate::protocol::types::{McUuid, Slot};
+    use crate::server::handler::tests::{TestClient, connect, dig, enter_play};
+    use crate::server::inventory::chest::CHEST_BLOCK;
+
+    /// Place a chest and right-click it, returning the window it opens and
+    /// the window contents
+    async fn open_test_chest(
+        client: &mut TestClient,
+        chest: Position,
+    ) -> (OpenScreenPacket, SetContainerContentPacket) {
+        let context = &client.context;
+        let chest_state = context.data.blocks().default_state_of(CHEST_BLOCK).unwrap();
+        context
+            .world
+            .write()
+            .await
+            .set_block(chest, BlockState(chest_state as u16))
+            .unwrap();
+
+        client
+            .connection
+            .write_packet(&UseItemOnPacket {
+                hand: Hand::MainHand,
+                position: chest,
+                face: BlockFace::North,
+                cursor_x: 0.5,
+                cursor_y: 0.5,
+                cursor_z: 0.0,
+                inside_block: false,
+                world_border_hit: false,
+                sequence: VarInt(2),
+            })
+            .await
+            .unwrap();
+
+        let (packet_id, data) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, OpenScreenPacket::ID);
+        let screen = OpenScreenPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        let (packet_id, data) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, SetContainerContentPacket::ID);
+        let content = SetContainerContentPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        let (packet_id, _) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, AcknowledgeBlockChangePacket::ID);
+        (screen, content)
+    }
+
+    #[tokio::test]
+    async fn test_right_click_opens_chest() {
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_compression_threshold(None)
+            .with_view_distance(1);
+        let mut client = connect(config).await;
+        let uuid = McUuid::new_v4();
+        enter_play(&mut client, uuid).await;
+
+        let chest = Position::new(2, 64, 2);
+        let mut slots = vec![Slot::empty(); CHEST_SIZE];
+        slots[4] = Slot::new(1, 32);
+        client.context.chests.set_contents(chest, slots).await;
+
+        let (screen, content) = open_test_chest(&mut client, chest).await;
+        assert_eq!(screen.window_type, GuiType::Generic9x3);
+        assert_eq!(content.window_id, screen.window_id);
+        assert_eq!(content.slots.len(), CHEST_SIZE + 36);
+        assert_eq!(content.slots[4], Slot::new(1, 32));
+
+        // Closing the window stops tracking it, so later packets are answered
+        // only once it has been handled
+        client
+            .connection
+            .write_packet(&ServerboundCloseContainerPacket {
+                window_id: screen.window_id,
+            })
+            .await
+            .unwrap();
+        dig(&mut client, PlayerActionStatus::StartedDigging, 3).await;
+        assert!(client.context.chests.take_viewers(chest).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_breaking_chest_closes_window() {
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_compression_threshold(None)
+            .with_view_distance(1);
+        let mut client = connect(config).await;
+        enter_play(&mut client, McUuid::new_v4()).await;
+        client
+            .context
+            .chests
+            .set_contents(Position::new(0, 63, 0), vec![Slot::new(1, 1); CHEST_SIZE])
+            .await;
+        let (screen, _) = open_test_chest(&mut client, Position::new(0, 63, 0)).await;
+
+        // The acknowledgement is written straight away, while the close is
+        // queued like any packet sent to another player
+        client
+            .connection
+            .write_packet(&PlayerActionPacket {
+                status: PlayerActionStatus::FinishedDigging,
+                position: Position::new(0, 63, 0),
+                face: 1,
+                sequence: VarInt(3),
+            })
+            .await
+            .unwrap();
+        let mut ids = Vec::new();
+        let mut close = None;
+        for _ in 0..2 {
+            let (packet_id, data) = client.connection.read_packet().await.unwrap();
+            if packet_id.0 == CloseContainerPacket::ID {
+                close = Some(CloseContainerPacket::read(&mut std::io::Cursor::new(data)).unwrap());
+            }
+            ids.push(packet_id.0);
+        }
+        assert!(ids.contains(&AcknowledgeBlockChangePacket::ID));
+        assert_eq!(close.unwrap().window_id, screen.window_id);
+        assert!(
+            client
+                .context
+                .chests
+                .contents(Position::new(0, 63, 0))
+                .await[0]
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,273 @@
+//! Digging
+//!
+//! Breaking blocks as players dig them, and showing the crack animation of
+//! blocks being dug to the other players.
+
+use super::ConnectionHandler;
+use crate::error::Result;
+use crate::game::player::GameMode;
+use crate::game::world::BlockState;
+use crate::protocol::VarInt;
+use crate::protocol::packets::play::digging::CLEAR_DESTROY_STAGE;
+use crate::protocol::packets::play::{
+    AcknowledgeBlockChangePacket, BlockBreakAnimationPacket, PlayerActionPacket, PlayerActionStatus,
+};
+use crate::protocol::types::Position;
+use crate::server::events::BlockBreakEvent;
+use crate::server::placement::block_at;
+
+impl ConnectionHandler {
+    /// Ask the event listeners whether the player may break a block
+    async fn allow_break(&self, position: Position) -> Result<bool> {
+        let Some(uuid) = self.player_uuid else {
+            return Ok(true);
+        };
+        let block = block_at(&*self.context.world.read().await, position)?;
+        let mut event = BlockBreakEvent::new(uuid, position, u32::from(block.id()));
+        self.context.events.fire(&mut event);
+        Ok(!event.cancelled)
+    }
+
+    /// Replace a block with air, returning whether it was broken
+    async fn break_block(&self, position: Position) -> bool {
+        let broken = self
+            .context
+            .world
+            .write()
+            .await
+            .set_block(position, BlockState::AIR);
+        match broken {
+            Ok(true) => true,
+            Ok(false) => {
+                tracing::debug!("Ignoring digging outside the world at {:?}", position);
+                false
+            }
+            Err(e) => {
+                tracing::warn!("Failed to break the block at {:?}: {}", position, e);
+                false
+            }
+        }
+    }
+
+    /// Break blocks as the player digs them
+    ///
+    /// Creative players break blocks instantly, while survival players break
+    /// them once they finish digging, showing the crack animation to others
+    /// meanwhile. The tick loop sends the changed blocks to every player.
+    pub(super) async fn handle_player_action(&mut self, action: PlayerActionPacket) -> Result<()> {
+        let (breaks, destroy_stage) = match (action.status, self.game_mode().await) {
+            (PlayerActionStatus::StartedDigging, GameMode::Creative) => (true, None),
+            (PlayerActionStatus::StartedDigging, GameMode::Survival) => (false, Some(0)),
+            (PlayerActionStatus::CancelledDigging, GameMode::Survival) => {
+                (false, Some(CLEAR_DESTROY_STAGE))
+            }
+            (PlayerActionStatus::FinishedDigging, GameMode::Survival) => {
+                (true, Some(CLEAR_DESTROY_STAGE))
+            }
+            (
+                PlayerActionStatus::StartedDigging
+                | PlayerActionStatus::CancelledDigging
+                | PlayerActionStatus::FinishedDigging,
+                _,
+            ) => (false, None),
+            (status, _) => {
+                tracing::debug!("Ignoring player action {:?}", status);
+                return Ok(());
+            }
+        };
+
+        // When a listener keeps the block, or it cannot be broken,
+        // acknowledging the change makes the client restore it
+        if breaks
+            && self.allow_break(action.position).await?
+            && self.break_block(action.position).await
+        {
+            self.context.chests.remove(action.position).await;
+            self.context.signs.remove(action.position).await;
+            self.close_chest_windows(action.position).await?;
+        }
+        if let (Some(destroy_stage), Some(uuid), Some(entity_id)) =
+            (destroy_stage, self.player_uuid, self.entity_id)
+        {
+            self.context
+                .player_list
+                .broadcast_except(
+                    &uuid,
+                    &BlockBreakAnimationPacket {
+                        entity_id: VarInt(entity_id),
+                        position: action.position,
+                        destroy_stage,
+                    },
+                )
+                .await?;
+        }
+        self.connection
+            .write_packet(&AcknowledgeBlockChangePacket {
+                sequence: action.sequence,
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{LevelType, ServerConfig};
+    use crate::game::world::generators::ChunkProvider;
+    use crate::game::world::generators::flat::DIRT;
+    use crate::game::world::{ChunkData, ChunkPosition};
+    use crate::protocol::types::McUuid;
+    use crate::server::handler::tests::{connect, dig, dig_at, enter_play};
+
+    #[tokio::test]
+    async fn test_digging_breaks_blocks() {
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_compression_threshold(None)
+            .with_view_distance(1);
+        let mut client = connect(config).await;
+        let uuid = McUuid::new_v4();
+        enter_play(&mut client, uuid).await;
+        let position = Position::new(0, 63, 0);
+        client
+            .context
+            .world
+            .write()
+            .await
+            .set_block(position, BlockState(9))
+            .unwrap();
+        client.context.world.write().await.take_mutations();
+
+        // Survival players break blocks once they finish digging
+        assert_eq!(
+            dig(&mut client, PlayerActionStatus::StartedDigging, 1).await,
+            1
+        );
+        assert_eq!(
+            client
+                .context
+                .world
+                .read()
+                .await
+                .get_block(position)
+                .unwrap(),
+            Some(BlockState(9))
+        );
+        assert_eq!(
+            dig(&mut client, PlayerActionStatus::FinishedDigging, 2).await,
+            2
+        );
+        assert_eq!(
+            client
+                .context
+                .world
+                .read()
+                .await
+                .get_block(position)
+                .unwrap(),
+            Some(BlockState::AIR)
+        );
+        assert_eq!(client.context.world.write().await.take_mutations().len(), 1);
+
+        // Creative players break them straight away
+        client
+            .context
+            .world
+            .write()
+            .await
+            .set_block(position, BlockState(9))
+            .unwrap();
+        let mut player = client.context.players.get_player(&uuid).await.unwrap();
+        player.set_game_mode(GameMode::Creative);
+        client.context.players.update_player(&uuid, player).await;
+        assert_eq!(
+            dig(&mut client, PlayerActionStatus::StartedDigging, 3).await,
+            3
+        );
+        assert_eq!(
+            client
+                .context
+                .world
+                .read()
+                .await
+                .get_block(position)
+                .unwrap(),
+            Some(BlockState::AIR)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_digging_the_ground() {
+        let config = ServerConfig::new()
+            .with_level_type(LevelType::Flat)
+            .with_online_mode(false)
+            .with_compression_threshold(None)
+            .with_view_distance(1);
+        let mut client = connect(config).await;
+        let uuid = McUuid::new_v4();
+        enter_play(&mut client, uuid).await;
+        let mut player = client.context.players.get_player(&uuid).await.unwrap();
+        player.set_game_mode(GameMode::Creative);
+        client.context.players.update_player(&uuid, player).await;
+
+        // The grass of the flat world is broken in the chunks sent to clients
+        let grass = Position::new(1, -61, 2);
+        dig_at(&mut client, grass, PlayerActionStatus::StartedDigging, 1).await;
+        let world = client.context.world.read().await;
+        assert_eq!(world.get_block(grass).unwrap(), Some(BlockState::AIR));
+        let packet = world.chunks().provide_chunk(ChunkPosition::new(0, 0));
+        let chunk = ChunkData::from_packet(&packet).unwrap();
+        assert_eq!(chunk.get_block(1, 3, 2), Some(BlockState::AIR));
+        assert_eq!(chunk.get_block(1, 2, 2), Some(BlockState(DIRT)));
+        drop(world);
+
+        // Nothing is broken outside the world
+        let below = Position::new(1, -65, 2);
+        assert_eq!(
+            dig_at(&mut client, below, PlayerActionStatus::StartedDigging, 2).await,
+            2
+        );
+        assert_eq!(client.context.world.write().await.take_mutations().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_block_break_keeps_block() {
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_compression_threshold(None)
+            .with_view_distance(1);
+        let mut client = connect(config).await;
+        enter_play(&mut client, McUuid::new_v4()).await;
+        let position = Position::new(0, 63, 0);
+        client
+            .context
+            .world
+            .write()
+            .await
+            .set_block(position, BlockState(9))
+            .unwrap();
+        client
+            .context
+            .events
+            .register(move |event: &mut BlockBreakEvent| {
+                assert_eq!((event.position, event.block), (position, 9));
+                event.cancelled = true;
+            });
+
+        dig(&mut client, PlayerActionStatus::StartedDigging, 1).await;
+        assert_eq!(
+            dig(&mut client, PlayerActionStatus::FinishedDigging, 2).await,
+            2
+        );
+        assert_eq!(
+            client
+                .context
+                .world
+                .read()
+                .await
+                .get_block(position)
+                .unwrap(),
+            Some(BlockState(9))
+        );
+    }
+}
@@ -0,0 +1,165 @@
+//! Equipment
+//!
+//! Hotbar selection, and showing the held items, armor and arm swings of a
+//! player to the players near them.
+
+use super::ConnectionHandler;
+use crate::error::Result;
+use crate::protocol::VarInt;
+use crate::protocol::packets::play::{
+    AnimatePacket, Animation, ServerboundSetHeldItemPacket, SetEquipmentPacket, SetHeldItemPacket,
+    SwingArmPacket,
+};
+
+impl ConnectionHandler {
+    /// Handle the player selecting another hotbar slot
+    ///
+    /// An invalid slot is ignored, and the client is told to select the slot
+    /// the server still has.
+    pub(super) async fn handle_set_held_item(
+        &mut self,
+        held_item: ServerboundSetHeldItemPacket,
+    ) -> Result<()> {
+        if !held_item.is_valid() {
+            tracing::warn!(
+                "Ignoring selection of invalid hotbar slot {}",
+                held_item.slot
+            );
+            return self
+                .connection
+                .write_packet(&SetHeldItemPacket {
+                    slot: VarInt(i32::from(self.client_state.held_slot)),
+                })
+                .await;
+        }
+        self.client_state.held_slot = held_item.slot as u8;
+        self.inventory
+            .lock()
+            .await
+            .select_slot(held_item.slot as usize);
+        self.sync_equipment().await
+    }
+
+    /// Show the changes to the player's held items and armor to the players
+    /// within render distance
+    pub(super) async fn sync_equipment(&mut self) -> Result<()> {
+        let changes = self.inventory.lock().await.take_equipment_changes();
+        if changes.is_empty() {
+            return Ok(());
+        }
+        let Some(entity_id) = self.entity_id else {
+            return Ok(());
+        };
+        self.send_to_nearby(&SetEquipmentPacket {
+            entity_id: VarInt(entity_id),
+            equipment: changes,
+        })
+        .await
+    }
+
+    /// Handle the player swinging an arm by showing it to the players within
+    /// render distance
+    pub(super) async fn handle_swing_arm(&mut self, swing: SwingArmPacket) -> Result<()> {
+        let Some(entity_id) = self.entity_id else {
+            return Ok(());
+        };
+        self.send_to_nearby(&AnimatePacket {
+            entity_id: VarInt(entity_id),
+            animation: Animation::swing(swing.hand),
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ServerConfig;
+    use crate::game::player::GameMode;
+    use crate::protocol::packets::Packet;
+    use crate::protocol::packets::play::Hand;
+    use crate::protocol::types::McUuid;
+    use crate::server::entities::{EntityState, PLAYER_ENTITY_TYPE};
+    use crate::server::handler::tests::{connect, enter_play};
+    use crate::server::player_list::PlayerInfo;
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn test_invalid_held_slot_is_corrected() {
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_compression_threshold(None)
+            .with_view_distance(1);
+        let mut client = connect(config).await;
+        enter_play(&mut client, McUuid::new_v4()).await;
+
+        for slot in [4, 12] {
+            client
+                .connection
+                .write_packet(&ServerboundSetHeldItemPacket { slot })
+                .await
+                .unwrap();
+        }
+
+        // The valid selection is kept when the invalid one is reverted
+        let (packet_id, data) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, SetHeldItemPacket::ID);
+        let correction = SetHeldItemPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        assert_eq!(correction.slot.0, 4);
+    }
+
+    #[tokio::test]
+    async fn test_swinging_is_shown_to_nearby_players() {
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_compression_threshold(None)
+            .with_view_distance(1);
+        let mut client = connect(config).await;
+        enter_play(&mut client, McUuid::new_v4()).await;
+
+        let viewer = McUuid::new_v4();
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        client
+            .context
+            .player_list
+            .add_player(
+                viewer,
+                PlayerInfo::new("Alex".to_string(), GameMode::Survival),
+                sender,
+            )
+            .await;
+        client
+            .context
+            .entities
+            .spawn(EntityState::new(
+                viewer,
+                PLAYER_ENTITY_TYPE,
+                20.0,
+                64.0,
+                0.0,
+            ))
+            .await;
+
+        client
+            .connection
+            .write_packet(&SwingArmPacket {
+                hand: Hand::OffHand,
+            })
+            .await
+            .unwrap();
+        let animate = receiver
+            .recv()
+            .await
+            .unwrap()
+            .parse::<AnimatePacket>()
+            .unwrap();
+        assert_eq!(animate.animation, Animation::SwingOffHand);
+        let swinging = client
+            .context
+            .entities
+            .get(animate.entity_id.0)
+            .await
+            .unwrap();
+        assert_ne!(swinging.uuid, viewer);
+    }
+}
@@ -0,0 +1,90 @@
+//! Health
+//!
+//! Regeneration, starvation and damage, and the deaths they lead to.
+
+use super::ConnectionHandler;
+use crate::error::Result;
+use crate::game::game_rules;
+use crate::protocol::packets::play::{HurtAnimationPacket, PlayerDeathPacket};
+use crate::protocol::types::Position;
+use crate::protocol::{TextComponent, VarInt};
+use crate::server::health::{DamageType, HealthChange, VOID_DAMAGE, VOID_DAMAGE_Y};
+
+impl ConnectionHandler {
+    /// Regenerate or starve the player, showing them the change, and hurt
+    /// them if they fell out of the world
+    pub(super) async fn tick_health(&mut self) -> Result<()> {
+        let natural_regeneration = self
+            .context
+            .level
+            .read()
+            .await
+            .game_rules
+            .get_bool(game_rules::NATURAL_REGENERATION)
+            .unwrap_or(true);
+        if let Some(change) = self.health.tick(natural_regeneration) {
+            self.connection.write_packet(&self.health.packet()).await?;
+            if let HealthChange::Damaged(_) = change {
+                self.show_hurt().await?;
+            }
+        }
+        if self
+            .movement
+            .last_valid()
+            .is_some_and(|(_, y, _)| y < VOID_DAMAGE_Y)
+        {
+            self.hurt(VOID_DAMAGE, DamageType::OutOfWorld).await?;
+        }
+        Ok(())
+    }
+
+    /// Damage the player, unless their game mode protects them
+    ///
+    /// A player whose health runs out dies.
+    async fn hurt(&mut self, amount: f32, cause: DamageType) -> Result<()> {
+        let Some(uuid) = self.player_uuid else {
+            return Ok(());
+        };
+        let Some(player) = self.context.players.get_player(&uuid).await else {
+            return Ok(());
+        };
+        if player.game_mode.is_invulnerable() && !cause.bypasses_invulnerability() {
+            return Ok(());
+        }
+        if self.health.take_damage(amount, cause) <= 0.0 {
+            return Ok(());
+        }
+        self.connection.write_packet(&self.health.packet()).await?;
+        self.show_hurt().await?;
+        if !self.health.is_dead() {
+            return Ok(());
+        }
+
+        let Some(entity_id) = self.entity_id else {
+            return Ok(());
+        };
+        self.death_location = self
+            .movement
+            .last_valid()
+            .map(|(x, y, z)| Position::new(x.floor() as i32, y.floor() as i32, z.floor() as i32));
+        self.connection
+            .write_packet(&PlayerDeathPacket {
+                player_id: VarInt(entity_id),
+                message: TextComponent::text(cause.death_message(&player.username)),
+            })
+            .await
+    }
+
+    /// Show the player and the players near them that they were hurt
+    async fn show_hurt(&mut self) -> Result<()> {
+        let Some(entity_id) = self.entity_id else {
+            return Ok(());
+        };
+        let hurt = HurtAnimationPacket {
+            entity_id: VarInt(entity_id),
+            yaw: 0.0,
+        };
+        self.connection.write_packet(&hurt).await?;
+        self.send_to_nearby(&hurt).await
+    }
+}
@@ -0,0 +1,2647 @@
+//! Connection handler
+//!
+//! This module drives a single client connection through the protocol
+//! states, dispatching each incoming packet to the handler for the current
+//! state. What players do in the world is handled in the submodules, one
+//! per feature.
+
+use crate::error::{Result, ServerError};
+use crate::game::PlayerAttributes;
+use crate::game::entity::EntityId;
+use crate::game::game_rules;
+use crate::game::player::GameMode;
+use crate::metrics::Metrics;
+use crate::network::{
+    Connection, KeepAliveHandle, KeepAliveManager, PacketReceiver, RawPacket, StatusHandler,
+    ViewDistanceTracker,
+};
+use crate::protocol::encryption::{VERIFY_TOKEN_LENGTH, minecraft_hex_digest};
+use crate::protocol::packets::{
+    Packet,
+    configuration::{
+        ConfigurationClientSettingsPacket, ConfigurationCustomPayloadPacket,
+        ConfigurationDisconnectPacket, FeaturesPacket, FinishConfigurationPacket, KnownPacksPacket,
+        RegistryDataPacket, ResourcePackResponsePacket, ResourcePackResult, ResourcePackSendPacket,
+        SelectKnownPacksPacket,
+    },
+    handshaking::{HandshakePacket, NextState},
+    login::{
+        CookieRequestPacket, CookieResponsePacket, EncryptionRequestPacket,
+        EncryptionResponsePacket, LoginAcknowledgedPacket, LoginDisconnectPacket, LoginStartPacket,
+        LoginSuccessPacket, Property, SetCompressionPacket,
+    },
+    play::{
+        ChatCommandPacket, ChatMessagePacket, ClickContainerPacket, ClientCommandPacket,
+        ClientSettingsPacket, CommandSuggestionsRequestPacket, CommandSuggestionsResponsePacket,
+        ConfirmTeleportPacket, DeclareCommandsPacket, DisplaySlot, EntityEventPacket,
+        GameEventPacket, LoginPlayPacket, PlayDisconnectPacket, PlayerAbilitiesPacket,
+        PlayerActionPacket, PlayerPositionPacket, ServerboundCloseContainerPacket,
+        ServerboundCustomPayloadPacket, ServerboundKeepAlivePacket,
+        ServerboundPlayerAbilitiesPacket, ServerboundSetHeldItemPacket, SetContainerSlotPacket,
+        SetPlayerPositionAndRotationPacket, SetPlayerPositionPacket, SignUpdatePacket,
+        SwingArmPacket, UseItemOnPacket,
+    },
+};
+use crate::protocol::types::{JsonTextComponent, McIdentifier, McString, McUuid, Position};
+use crate::protocol::{
+    ConnectionState, MINECRAFT_VERSION, PROTOCOL_VERSION, ProtocolVersion, TextComponent, VarInt,
+};
+use crate::server::cookies::CookieStore;
+use crate::server::entities::{EntityState, PLAYER_ENTITY_TYPE};
+use crate::server::events::{ChatEvent, PlayerJoinEvent, PlayerQuitEvent};
+use crate::server::health::HealthManager;
+use crate::server::inventory::{Container, InventoryManager, SharedInventory};
+use crate::server::movement::{MovementCheck, MovementValidator};
+use crate::server::player_list::PlayerInfo;
+use crate::server::tick::TICK_DURATION;
+use crate::server::transfer::transfer_packet;
+use crate::server::whitelist::NOT_WHITELISTED;
+use crate::server::{auth, chat, context::ServerContext};
+use crate::storage::PlayerData;
+use rand::RngCore;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{Mutex, mpsc, watch};
+use tokio::task::JoinHandle;
+use tokio::time::{Duration, Interval, interval};
+
+mod containers;
+mod digging;
+mod equipment;
+mod health;
+mod placing;
+mod respawn;
+mod signs;
+
+use containers::OpenWindow;
+
+/// Reason given to clients disconnected by the server closing
+const SERVER_CLOSED: &str = "Server closed";
+
+/// Login details remembered while waiting for the client's encryption response
+struct PendingLogin {
+    /// Username sent in the login start packet
+    username: String,
+    /// UUID sent in the login start packet
+    uuid: McUuid,
+    /// Verify token sent in the encryption request
+    verify_token: Vec<u8>,
+}
+
+/// What the client has told the server about itself
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientState {
+    /// Latest settings from the Client Information packet
+    pub settings: Option<ClientSettings>,
+    /// Brand sent on the `minecraft:brand` channel (e.g. "vanilla")
+    pub brand: Option<String>,
+    /// ID of the window the client has open, other than its inventory
+    pub open_window_id: Option<i32>,
+    /// Selected hotbar slot, from 0 to 8
+    pub held_slot: u8,
+    /// Round trip of the latest answered keep alive, in milliseconds
+    pub latency: i32,
+    /// Cookies requested from the client and its answers
+    pub cookies: CookieStore,
+}
+
+impl ClientState {
+    /// Value of a cookie the client sent when asked, if it had one
+    pub fn get_cookie(&self, key: &McIdentifier) -> Option<&[u8]> {
+        self.cookies.get(key)
+    }
+}
+
+/// Settings reported by the client in its Client Information packet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientSettings {
+    /// Client language (e.g. "en_us")
+    pub locale: String,
+    /// Client render distance in chunks
+    pub view_distance: i8,
+    /// Chat mode (0=Enabled, 1=Commands only, 2=Hidden)
+    pub chat_mode: i32,
+    /// Whether chat colors are shown
+    pub chat_colors: bool,
+    /// Bit mask of the skin parts to display
+    pub displayed_skin_parts: u8,
+    /// Main hand (0=Left, 1=Right)
+    pub main_hand: i32,
+    /// Whether chat messages are filtered
+    pub enable_text_filtering: bool,
+    /// Whether the player may appear in the server list sample
+    pub allow_server_listings: bool,
+}
+
+impl From<ClientSettingsPacket> for ClientSettings {
+    fn from(settings: ClientSettingsPacket) -> Self {
+        Self {
+            locale: settings.locale.0,
+            view_distance: settings.view_distance,
+            chat_mode: settings.chat_mode.0,
+            chat_colors: settings.chat_colors,
+            displayed_skin_parts: settings.displayed_skin_parts,
+            main_hand: settings.main_hand.0,
+            enable_text_filtering: settings.enable_text_filtering,
+            allow_server_listings: settings.allow_server_listings,
+        }
+    }
+}
+
+/// Handles a single client connection
+pub struct ConnectionHandler {
+    /// Client connection
+    connection: Connection,
+    /// Shared server state
+    context: Arc<ServerContext>,
+    /// Whether the client was transferred here by another server
+    transferred: bool,
+    /// Login waiting for the encryption handshake to complete
+    pending_login: Option<PendingLogin>,
+    /// ID of the next teleport sent to the client
+    next_teleport_id: i32,
+    /// ID of the last window opened, cycling from 1 to 100
+    last_window_id: i32,
+    /// Window the client has open
+    open_window: Option<OpenWindow>,
+    /// UUID of the logged in player
+    player_uuid: Option<McUuid>,
+    /// Profile properties of the logged in player, such as their skin
+    profile_properties: Vec<Property>,
+    /// Entity ID of the player, once in the play state
+    entity_id: Option<EntityId>,
+    /// Latest settings sent by the client
+    client_state: ClientState,
+    /// Inventory of the player, shared with commands once in the world
+    inventory: SharedInventory,
+    /// Attributes of the player's entity
+    attributes: PlayerAttributes,
+    /// Health and food of the player
+    health: HealthManager,
+    /// Ticks the player's health and food (play state only)
+    health_timer: Option<Interval>,
+    /// Where the player last died
+    death_location: Option<Position>,
+    /// Chunks loaded on the client (play state only)
+    view: ViewDistanceTracker,
+    /// Checks the positions the client reports
+    movement: MovementValidator,
+    /// Resource packs the client has not finished loading, and whether each
+    /// is forced
+    pending_resource_packs: HashMap<McUuid, bool>,
+    /// Whether the registries have been sent, once the client said which
+    /// packs it knows
+    registries_sent: bool,
+    /// Whether Finish Configuration has been sent
+    configuration_finished: bool,
+    /// Packets queued for this connection by other tasks (play state only)
+    outgoing: Option<PacketReceiver>,
+    /// Handle to the keep alive task (play state only)
+    keep_alive: Option<KeepAliveHandle>,
+    /// Keep alive task, which fails if the client stops answering
+    keep_alive_task: Option<JoinHandle<Result<()>>>,
+    /// Latency measured by the keep alive task (play state only)
+    latency: Option<watch::Receiver<Duration>>,
+    /// Whether the server is closing
+    closing: watch::Receiver<bool>,
+}
+
+impl ConnectionHandler {
+    /// Create a new connection handler
+    pub fn new(connection: Connection, context: Arc<ServerContext>) -> Self {
+        Self {
+            connection,
+            closing: context.closing(),
+            context,
+            transferred: false,
+            pending_login: None,
+            next_teleport_id: 0,
+            last_window_id: 0,
+            open_window: None,
+            player_uuid: None,
+            profile_properties: Vec::new(),
+            entity_id: None,
+            client_state: ClientState::default(),
+            inventory: Arc::new(Mutex::new(InventoryManager::new(
+                Container::player_inventory(),
+            ))),
+            attributes: PlayerAttributes::new(),
+            health: HealthManager::new(),
+            health_timer: None,
+            death_location: None,
+            view: ViewDistanceTracker::new(),
+            movement: MovementValidator::new(),
+            pending_resource_packs: HashMap::new(),
+            registries_sent: false,
+            configuration_finished: false,
+            outgoing: None,
+            keep_alive: None,
+            keep_alive_task: None,
+            latency: None,
+        }
+    }
+
+    /// Process packets until the connection closes
+    pub async fn run(mut self) -> Result<()> {
+        let peer_addr = self.connection.peer_addr();
+        tracing::debug!("Handling connection from {}", peer_addr);
+
+        let result = self.process_packets().await;
+
+        // Tell the client why it is being disconnected, unless the
+        // connection itself failed or the client was already told
+        if let Err(error) = &result {
+            if !matches!(error, ServerError::Io(_) | ServerError::Disconnected(_)) {
+                if let Err(kick_error) = self.kick_player(&error.to_string()).await {
+                    tracing::debug!("Failed to send disconnect: {}", kick_error);
+                }
+            }
+        }
+
+        // Remove player when connection closes, even if it closed with an error
+        self.release_window().await;
+        if let Err(e) = self.save_player_data().await {
+            tracing::error!("Failed to save player data: {}", e);
+        }
+        self.context.players.remove_player(peer_addr).await;
+        if let Some(uuid) = self.player_uuid {
+            if let Some(info) = self.context.player_list.remove_player(&uuid).await {
+                Metrics::global().player_left();
+                if let Err(e) = self.context.tab_list.remove(&uuid).await {
+                    tracing::debug!("Failed to remove player from the tab list: {}", e);
+                }
+                let mut quit = PlayerQuitEvent::new(uuid, info.username);
+                self.context.events.fire(&mut quit);
+            }
+            self.context.inventories.remove(&uuid).await;
+            self.context.cookies.forget(&uuid).await;
+        }
+        if let Some(entity_id) = self.entity_id {
+            self.context.entities.remove(entity_id).await;
+        }
+        if let Some(task) = self.keep_alive_task.take() {
+            task.abort();
+        }
+
+        match result {
+            Err(ServerError::Disconnected(reason)) => {
+                tracing::debug!("Disconnected {}: {}", peer_addr, reason);
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
+    /// Disconnect the client with a reason
+    ///
+    /// Sends the disconnect packet for the current state, if it has one, then
+    /// flushes and closes the connection.
+    pub async fn kick_player(&mut self, reason: &str) -> Result<()> {
+        let reason = JsonTextComponent::text(reason);
+        match self.connection.state() {
+            ConnectionState::Login => {
+                self.connection
+                    .write_packet(&LoginDisconnectPacket { reason })
+                    .await?;
+            }
+            ConnectionState::Configuration => {
+                self.connection
+                    .write_packet(&ConfigurationDisconnectPacket { reason })
+                    .await?;
+            }
+            ConnectionState::Play => {
+                self.connection
+                    .write_packet(&PlayDisconnectPacket { reason })
+                    .await?;
+            }
+            ConnectionState::Handshaking | ConnectionState::Status => {}
+        }
+        self.connection.close().await
+    }
+
+    /// Read and dispatch packets until the client disconnects
+    async fn process_packets(&mut self) -> Result<()> {
+        loop {
+            // Read packet, writing any packets queued for this connection meanwhile
+            let read_result = tokio::select! {
+                result = self.connection.read_packet() => result,
+                Some(packet) = recv_outgoing(&mut self.outgoing) => {
+                    self.write_outgoing(packet).await?;
+                    continue;
+                }
+                result = keep_alive_finished(&mut self.keep_alive_task) => {
+                    self.keep_alive_task = None;
+                    result?;
+                    continue;
+                }
+                latency = latency_changed(&mut self.latency) => {
+                    match latency {
+                        Some(latency) => {
+                            let millis = latency.as_millis();
+                            self.client_state.latency = i32::try_from(millis).unwrap_or(i32::MAX);
+                        }
+                        None => self.latency = None,
+                    }
+                    continue;
+                }
+                _ = next_tick(&mut self.health_timer) => {
+                    self.tick_health().await?;
+                    continue;
+                }
+                _ = server_closing(&mut self.closing) => {
+                    self.kick_player(SERVER_CLOSED).await?;
+                    return Err(ServerError::Disconnected(SERVER_CLOSED.to_string()));
+                }
+            };
+
+            let (packet_id, data) = match read_result {
+                Ok((pid, pdata)) => {
+                    tracing::debug!(
+                        "Received packet ID: 0x{:02X}, data length: {}, state: {:?}",
+                        pid.0,
+                        pdata.len(),
+                        self.connection.state()
+                    );
+                    (pid, pdata)
+                }
+                Err(e) => {
+                    tracing::debug!("Connection closed: {}", e);
+                    return Ok(());
+                }
+            };
+
+            let should_break = match self.connection.state() {
+                ConnectionState::Handshaking => {
+                    self.handle_handshaking_packet(packet_id, &data).await?;
+                    false
+                }
+                ConnectionState::Status => {
+                    StatusHandler::new(self.context.current_status().await)
+                        .handle_packet(&mut self.connection, packet_id, &data)
+                        .await?
+                }
+                ConnectionState::Login => {
+                    self.handle_login_packet(packet_id, &data).await?;
+                    false
+                }
+                ConnectionState::Configuration => {
+                    self.handle_configuration_packet(packet_id, &data).await?;
+                    false
+                }
+                ConnectionState::Play => {
+                    self.handle_play_packet(packet_id, &data).await?;
+                    false
+                }
+            };
+
+            if should_break {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Handle handshaking state packets
+    async fn handle_handshaking_packet(&mut self, packet_id: VarInt, data: &[u8]) -> Result<()> {
+        if packet_id.0 == HandshakePacket::ID {
+            let handshake = HandshakePacket::read(&mut std::io::Cursor::new(data))?;
+
+            tracing::debug!(
+                "Handshake: version={}, address={}, port={}, next_state={}",
+                handshake.protocol_version.0,
+                handshake.server_address.0,
+                handshake.server_port,
+                handshake.next_state.0
+            );
+
+            self.connection
+                .set_protocol_version(handshake.protocol_version.0);
+
+            match NextState::try_from(handshake.next_state)? {
+                NextState::Status => self.connection.set_state(ConnectionState::Status),
+                NextState::Login => self.connection.set_state(ConnectionState::Login),
+                NextState::Transfer => {
+                    // Transferred clients log in as usual, and may be sent on
+                    // to a backend once they have
+                    self.transferred = true;
+                    self.connection.set_state(ConnectionState::Login);
+                }
+            }
+            if self.connection.state() == ConnectionState::Login {
+                self.check_protocol_version(handshake.protocol_version.0)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Turn away a client logging in with a protocol version that is not
+    /// accepted
+    async fn check_protocol_version(&mut self, version: i32) -> Result<()> {
+        let accepted = &self.context.config.accepted_protocol_versions;
+        if accepted.contains(&version) {
+            return Ok(());
+        }
+        let newest = accepted.iter().copied().max().unwrap_or(PROTOCOL_VERSION);
+        let newest_name = ProtocolVersion::from_id(newest).map_or(MINECRAFT_VERSION, |v| v.name());
+        let reason = if version > newest {
+            format!("Outdated server! I'm still on {}", newest_name)
+        } else {
+            format!("Outdated client! Please use {}", newest_name)
+        };
+        tracing::info!(
+            "Rejecting client {} with protocol version {}",
+            self.connection.peer_addr(),
+            version
+        );
+        self.kick_player(&reason).await?;
+        Err(ServerError::Disconnected(reason))
+    }
+
+    /// Handle login state packets
+    async fn handle_login_packet(&mut self, packet_id: VarInt, data: &[u8]) -> Result<()> {
+        if packet_id.0 == LoginStartPacket::ID {
+            let login_start = LoginStartPacket::read(&mut std::io::Cursor::new(data))?;
+            self.handle_login_start(login_start).await?;
+        } else if packet_id.0 == EncryptionResponsePacket::ID {
+            let response = EncryptionResponsePacket::read(&mut std::io::Cursor::new(data))?;
+            self.handle_encryption_response(response).await?;
+        } else if packet_id.0 == CookieResponsePacket::ID {
+            let response = CookieResponsePacket::read(&mut std::io::Cursor::new(data))?;
+            self.client_state
+                .cookies
+                .receive(response.key, response.payload)?;
+        } else if packet_id.0 == LoginAcknowledgedPacket::ID {
+            let _login_ack = LoginAcknowledgedPacket::read(&mut std::io::Cursor::new(data))?;
+
+            tracing::debug!("Login acknowledged, transitioning to configuration state");
+            self.connection.set_state(ConnectionState::Configuration);
+            if let Some(uuid) = self.player_uuid {
+                self.context
+                    .cookies
+                    .remember(uuid, self.client_state.cookies.clone())
+                    .await;
+            }
+            if self.transferred {
+                self.transfer_to_backend().await?;
+            }
+
+            let features = FeaturesPacket {
+                features: self.context.config.enabled_features.clone(),
+            };
+            self.connection.write_packet(&features).await?;
+            // The registries follow once the client says which packs it has
+            let known_packs = KnownPacksPacket {
+                packs: self.context.data.packs().packs(),
+            };
+            self.connection.write_packet(&known_packs).await?;
+        }
+        Ok(())
+    }
+
+    /// Send the registries, leaving out the data the client has in the
+    /// packs it knows, then the rest of the configuration
+    async fn handle_known_packs(&mut self, selected: SelectKnownPacksPacket) -> Result<()> {
+        if self.registries_sent {
+            return Err(ServerError::Protocol(
+                "Known packs were already selected".to_string(),
+            ));
+        }
+        self.registries_sent = true;
+
+        let context = Arc::clone(&self.context);
+        let known = context.data.packs().known_entries(&selected.packs);
+        tracing::debug!(
+            "Client knows {} packs with {} registry entries",
+            selected.packs.len(),
+            known.len()
+        );
+        let registries = context.data.registry_packets_with_known(&known)?;
+        self.send_all_registries(&registries).await?;
+        self.send_resource_packs().await?;
+        self.finish_configuration_if_ready().await
+    }
+
+    /// Send a transferred client on to the next backend, if there are any,
+    /// and close the connection
+    async fn transfer_to_backend(&mut self) -> Result<()> {
+        let Some(backend) = self.context.transfers.next_backend() else {
+            return Ok(());
+        };
+        tracing::info!(
+            "Transferring {} to {}",
+            self.connection.peer_addr(),
+            backend
+        );
+        let reason = format!("Transferred to {}", backend);
+        self.connection
+            .write_packet(&transfer_packet(backend))
+            .await?;
+        self.connection.close().await?;
+        Err(ServerError::Disconnected(reason))
+    }
+
+    /// Ask the client for a cookie, unless it was already asked
+    async fn request_cookie(&mut self, key: McIdentifier) -> Result<()> {
+        if self.client_state.cookies.request(key.clone()) {
+            self.connection
+                .write_packet(&CookieRequestPacket { key })
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Start the login, requesting encryption first when in online mode
+    async fn handle_login_start(&mut self, login_start: LoginStartPacket) -> Result<()> {
+        tracing::info!(
+            "Player {} ({}) logging in from {}",
+            login_start.name.0,
+            login_start.player_uuid,
+            self.connection.peer_addr()
+        );
+
+        // Answers arrive before the client acknowledges the login
+        let context = Arc::clone(&self.context);
+        for key in &context.config.login_cookies {
+            self.request_cookie(key.clone()).await?;
+        }
+
+        let Some(keys) = self.context.keys.as_ref() else {
+            // Offline mode - trust the client's name and UUID
+            return self
+                .finish_login(login_start.player_uuid, login_start.name.0, Vec::new())
+                .await;
+        };
+
+        let mut verify_token = vec![0u8; VERIFY_TOKEN_LENGTH];
+        rand::thread_rng().fill_bytes(&mut verify_token);
+
+        let request = EncryptionRequestPacket {
+            server_id: String::new().into(),
+            public_key: keys.public_key_der().to_vec(),
+            verify_token: verify_token.clone(),
+            should_authenticate: true,
+        };
+        self.connection.write_packet(&request).await?;
+
+        self.pending_login = Some(PendingLogin {
+            username: login_start.name.0,
+            uuid: login_start.player_uuid,
+            verify_token,
+        });
+
+        tracing::debug!("Encryption request sent");
+        Ok(())
+    }
+
+    /// Complete the encryption handshake and authenticate the player
+    async fn handle_encryption_response(
+        &mut self,
+        response: EncryptionResponsePacket,
+    ) -> Result<()> {
+        let (Some(pending), Some(keys)) = (self.pending_login.take(), self.context.keys.as_ref())
+        else {
+            return Err(ServerError::Protocol(
+                "Unexpected encryption response".to_string(),
+            ));
+        };
+
+        let verify_token = keys.decrypt(&response.verify_token)?;
+        if verify_token != pending.verify_token {
+            return Err(ServerError::Protocol("Verify token mismatch".to_string()));
+        }
+
+        let shared_secret = keys.decrypt(&response.shared_secret)?;
+        self.connection.enable_encryption(&shared_secret)?;
+
+        let server_hash = minecraft_hex_digest("", &shared_secret, keys.public_key_der());
+        let profile = auth::has_joined(&pending.username, &server_hash).await?;
+
+        tracing::debug!(
+            "Authenticated {} as {} ({})",
+            pending.username,
+            profile.name,
+            profile.id
+        );
+        if profile.id != pending.uuid {
+            tracing::debug!(
+                "Client sent UUID {}, using authenticated UUID {}",
+                pending.uuid,
+                profile.id
+            );
+        }
+
+        let properties = profile.properties.into_iter().map(Into::into).collect();
+        self.finish_login(profile.id, profile.name, properties)
+            .await
+    }
+
+    /// Enable compression, send login success and register the player
+    async fn finish_login(
+        &mut self,
+        uuid: McUuid,
+        username: String,
+        properties: Vec<Property>,
+    ) -> Result<()> {
+        let ban = self
+            .context
+            .bans
+            .read()
+            .await
+            .find(&uuid, &username)
+            .cloned();
+        if let Some(ban) = ban {
+            tracing::info!("Rejecting banned player {} ({})", username, uuid);
+            let reason = ban.disconnect_reason();
+            self.kick_player(&reason).await?;
+            return Err(ServerError::Disconnected(reason));
+        }
+        if !self.context.whitelist.read().await.allows(&uuid, &username) {
+            tracing::info!(
+                "Rejecting player {} ({}) not in the whitelist",
+                username,
+                uuid
+            );
+            self.kick_player(NOT_WHITELISTED).await?;
+            return Err(ServerError::Disconnected(NOT_WHITELISTED.to_string()));
+        }
+        let mut join = PlayerJoinEvent::new(uuid, &username);
+        self.context.events.fire(&mut join);
+        if join.cancelled {
+            tracing::info!("Join of {} ({}) was cancelled", username, uuid);
+            self.kick_player(&join.kick_message).await?;
+            return Err(ServerError::Disconnected(join.kick_message));
+        }
+
+        // Enable compression if configured
+        if let Some(threshold) = self.context.config.compression_threshold {
+            let compression_packet = SetCompressionPacket {
+                threshold: (threshold as i32).into(),
+            };
+            self.connection.write_packet(&compression_packet).await?;
+            self.connection.enable_compression(threshold)?;
+        }
+
+        // Send login success
+        let login_success = LoginSuccessPacket {
+            uuid,
+            username: username.clone().into(),
+            properties: properties.clone(),
+        };
+        self.connection.write_packet(&login_success).await?;
+        self.profile_properties = properties;
+
+        // Create player
+        let player = crate::game::player::Player::new(uuid, username);
+        self.player_uuid = Some(uuid);
+
+        self.context
+            .players
+            .add_player(player, self.connection.peer_addr())
+            .await;
+
+        // The client switches to the configuration state once it acknowledges
+        // the login, so stay in the login state until then
+        tracing::info!("Player logged in successfully, waiting for login acknowledgement");
+        Ok(())
+    }
+
+    /// Send a Registry Data packet for each registry
+    pub async fn send_all_registries(&mut self, registries: &[RegistryDataPacket]) -> Result<()> {
+        for registry in registries {
+            tracing::debug!(
+                "Sending registry {} ({} entries)",
+                registry.registry_id,
+                registry.entries.len()
+            );
+            self.connection.write_packet(registry).await?;
+        }
+        Ok(())
+    }
+
+    /// Handle configuration state packets
+    async fn handle_configuration_packet(&mut self, packet_id: VarInt, data: &[u8]) -> Result<()> {
+        use crate::protocol::packets::configuration::{
+            AcknowledgeFinishConfigurationPacket, is_serverbound_packet_id,
+        };
+
+        if !is_serverbound_packet_id(packet_id.0) {
+            return Err(ServerError::Protocol(format!(
+                "Unexpected packet 0x{:02X} in configuration state",
+                packet_id.0
+            )));
+        }
+
+        if packet_id.0 == ConfigurationClientSettingsPacket::ID {
+            let settings =
+                ConfigurationClientSettingsPacket::read(&mut std::io::Cursor::new(data))?;
+            self.update_client_settings(settings.0).await;
+        } else if packet_id.0 == ConfigurationCustomPayloadPacket::ID {
+            let message = ConfigurationCustomPayloadPacket::read(&mut std::io::Cursor::new(data))?;
+            self.handle_plugin_message(message.0)?;
+        } else if packet_id.0 == ResourcePackResponsePacket::ID {
+            let response = ResourcePackResponsePacket::read(&mut std::io::Cursor::new(data))?;
+            self.handle_resource_pack_response(response).await?;
+        } else if packet_id.0 == SelectKnownPacksPacket::ID {
+            let selected = SelectKnownPacksPacket::read(&mut std::io::Cursor::new(data))?;
+            self.handle_known_packs(selected).await?;
+        } else if packet_id.0 == AcknowledgeFinishConfigurationPacket::ID {
+            if !self.configuration_finished {
+                return Err(ServerError::Protocol(
+                    "Configuration acknowledged before it finished".to_string(),
+                ));
+            }
+            // Acknowledge Finish Configuration packet
+            let _ack_finish =
+                AcknowledgeFinishConfigurationPacket::read(&mut std::io::Cursor::new(data))?;
+
+            tracing::debug!(
+                "Acknowledge finish configuration received, transitioning to play state"
+            );
+
+            self.connection.set_state(ConnectionState::Play);
+
+            // Send login play packet after transitioning to play state
+            let (x, y, z) = self.load_player_data().await?;
+            let uuid = self.player_uuid.unwrap_or_default();
+            let entity_id = self
+                .context
+                .entities
+                .spawn(EntityState::new(uuid, PLAYER_ENTITY_TYPE, x, y, z))
+                .await;
+            self.entity_id = Some(entity_id);
+            self.context
+                .inventories
+                .insert(uuid, Arc::clone(&self.inventory))
+                .await;
+            let mut login_play =
+                LoginPlayPacket::from_server_config(&self.context.config, entity_id);
+            login_play.game_mode = self.game_mode().await as u8;
+            {
+                let rules = &self.context.level.read().await.game_rules;
+                let rule = |name| rules.get_bool(name).unwrap_or_default();
+                login_play.reduced_debug_info = rule(game_rules::REDUCED_DEBUG_INFO);
+                login_play.enable_respawn_screen = !rule(game_rules::DO_IMMEDIATE_RESPAWN);
+                login_play.do_limited_crafting = rule(game_rules::DO_LIMITED_CRAFTING);
+            }
+            self.connection.write_packet(&login_play).await?;
+            self.send_abilities().await?;
+            self.connection
+                .write_packet(&self.attributes.spawn_packet(entity_id))
+                .await?;
+            self.connection.write_packet(&self.health.packet()).await?;
+            let content = self.inventory.lock().await.container_mut().content_packet();
+            self.connection.write_packet(&content).await?;
+            let level = self.context.ops.read().await.level(&uuid);
+            self.connection
+                .write_packet(&DeclareCommandsPacket::new(
+                    self.context.commands.command_tree_for(level),
+                ))
+                .await?;
+            if level > 0 {
+                let event = EntityEventPacket::permission_level(entity_id, level);
+                self.connection.write_packet(&event).await?;
+            }
+            let time = self.context.time.read().await.packet();
+            self.connection.write_packet(&time).await?;
+            for event in self.context.weather.read().await.join_events() {
+                self.connection.write_packet(&event).await?;
+            }
+
+            // Chunks are held back until the client has confirmed its spawn
+            // position, so it does not fall through unloaded terrain
+            let teleport_id = self.teleport(x, y, z).await?;
+            self.await_teleport_confirm(teleport_id).await?;
+            self.send_spawn_chunks(x, z).await?;
+            self.join_player_list().await?;
+
+            tracing::info!("Login play packet sent, player is now in play state");
+        }
+        Ok(())
+    }
+
+    /// Restore the player's saved inventory, health and game mode, returning
+    /// where they were
+    ///
+    /// New players, and every player when player data is not saved, start
+    /// at the world spawn.
+    async fn load_player_data(&mut self) -> Result<(f64, f64, f64)> {
+        let spawn = self.context.world.read().await.spawn_position();
+        let new_player = PlayerData::at_spawn(spawn);
+        let Some(uuid) = self.player_uuid else {
+            return Ok(new_player.position);
+        };
+        let data = match &self.context.player_data {
+            Some(store) => store.load_or(&uuid, new_player).await?,
+            None => new_player,
+        };
+
+        {
+            let mut inventory = self.inventory.lock().await;
+            let container = inventory.container_mut();
+            for (index, slot) in data.inventory.into_iter().enumerate() {
+                container.set_slot(index, slot);
+            }
+        }
+        self.health.set_health(data.health);
+        if let Some(mut player) = self.context.players.get_player(&uuid).await {
+            player.game_mode = GameMode::try_from(data.gamemode)?;
+            self.context.players.update_player(&uuid, player).await;
+        }
+        Ok(data.position)
+    }
+
+    /// Save the player's position, inventory, health and game mode, if
+    /// player data is saved and the player made it into the world
+    async fn save_player_data(&mut self) -> Result<()> {
+        let (Some(store), Some(uuid), Some(entity_id)) =
+            (&self.context.player_data, self.player_uuid, self.entity_id)
+        else {
+            return Ok(());
+        };
+        let Some(entity) = self.context.entities.get(entity_id).await else {
+            return Ok(());
+        };
+        let inventory = self.inventory.lock().await;
+        let container = inventory.container();
+        let data = PlayerData {
+            position: (entity.x, entity.y, entity.z),
+            inventory: (0..container.size())
+                .filter_map(|index| container.get_slot(index).cloned())
+                .collect(),
+            health: self.health.health(),
+            gamemode: self.game_mode().await as u8,
+        };
+        store.save(&uuid, &data).await
+    }
+
+    /// Offer the configured resource packs to the client
+    async fn send_resource_packs(&mut self) -> Result<()> {
+        let context = Arc::clone(&self.context);
+        for pack in &context.config.resource_packs {
+            self.connection
+                .write_packet(&ResourcePackSendPacket {
+                    uuid: pack.id,
+                    url: pack.url.as_str().into(),
+                    hash: pack.hash.to_lowercase().into(),
+                    forced: pack.forced,
+                    prompt: pack.prompt.as_deref().map(TextComponent::text),
+                })
+                .await?;
+            self.pending_resource_packs.insert(pack.id, pack.forced);
+        }
+        Ok(())
+    }
+
+    /// Track the client's progress with a resource pack
+    ///
+    /// Failing to load a forced pack ends the connection.
+    async fn handle_resource_pack_response(
+        &mut self,
+        response: ResourcePackResponsePacket,
+    ) -> Result<()> {
+        tracing::debug!("Resource pack {}: {:?}", response.uuid, response.result);
+        if !response.result.is_final() {
+            return Ok(());
+        }
+
+        let Some(forced) = self.pending_resource_packs.remove(&response.uuid) else {
+            return Ok(());
+        };
+        if forced && response.result != ResourcePackResult::SuccessfullyLoaded {
+            return Err(ServerError::Protocol(format!(
+                "Required resource pack was not loaded ({:?})",
+                response.result
+            )));
+        }
+        self.finish_configuration_if_ready().await
+    }
+
+    /// Finish the configuration once no forced resource pack is pending
+    async fn finish_configuration_if_ready(&mut self) -> Result<()> {
+        if self.configuration_finished || self.pending_resource_packs.values().any(|&forced| forced)
+        {
+            return Ok(());
+        }
+
+        self.connection
+            .write_packet(&FinishConfigurationPacket)
+            .await?;
+        self.configuration_finished = true;
+        tracing::debug!("Finish configuration packet sent");
+        Ok(())
+    }
+
+    /// Add the player to the player list so other tasks can send it packets
+    async fn join_player_list(&mut self) -> Result<()> {
+        let Some(uuid) = self.player_uuid else {
+            return Ok(());
+        };
+        let Some(player) = self.context.players.get_player(&uuid).await else {
+            return Ok(());
+        };
+        let username = player.username;
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let (keep_alive, handle) = KeepAliveManager::new(
+            self.context.config.keep_alive_interval,
+            self.context.config.keep_alive_timeout,
+            sender.clone(),
+        );
+        self.latency = Some(handle.latency());
+        self.context
+            .tab_list
+            .track_latency(uuid, handle.latency())
+            .await;
+        self.keep_alive = Some(handle);
+        self.keep_alive_task = Some(keep_alive.spawn());
+        self.health_timer = Some(interval(TICK_DURATION));
+
+        self.context
+            .player_list
+            .add_player(
+                uuid,
+                PlayerInfo::new(username.clone(), player.game_mode)
+                    .with_properties(self.profile_properties.clone())
+                    .with_chat_mode(self.chat_mode()),
+                sender,
+            )
+            .await;
+        Metrics::global().player_joined();
+        self.outgoing = Some(receiver);
+
+        let tab_list = self.context.tab_list.list_packet().await;
+        self.connection.write_packet(&tab_list).await?;
+        self.context.tab_list.announce(&uuid).await?;
+
+        // Show everyone's ping in the tab list, and this player's to everyone
+        // else
+        let scoreboard = self.context.player_list.ping_scoreboard().await;
+        self.connection
+            .write_packet(&scoreboard.create_packet())
+            .await?;
+        self.connection
+            .write_packet(&scoreboard.display_packet(DisplaySlot::List))
+            .await?;
+        for score in scoreboard.score_packets() {
+            self.connection.write_packet(&score).await?;
+        }
+        if let Some(score) = scoreboard.score_update(&username) {
+            self.context
+                .player_list
+                .broadcast_except(&uuid, &score)
+                .await?;
+        }
+
+        for boss_bar in self.context.boss_bars.add_packets().await {
+            self.connection.write_packet(&boss_bar).await?;
+        }
+        let teams = self.context.teams.read().await.create_packets();
+        for team in teams {
+            self.connection.write_packet(&team).await?;
+        }
+        Ok(())
+    }
+
+    /// Send the client to a position, returning the teleport ID to confirm
+    async fn teleport(&mut self, x: f64, y: f64, z: f64) -> Result<i32> {
+        self.send_position(PlayerPositionPacket::absolute(0, x, y, z, 0.0, 0.0))
+            .await
+    }
+
+    /// Send a position to the client under the next teleport ID, returning
+    /// the ID to confirm
+    ///
+    /// The position must be absolute; only the rotation may be relative.
+    async fn send_position(&mut self, mut packet: PlayerPositionPacket) -> Result<i32> {
+        let teleport_id = self.next_teleport_id;
+        self.next_teleport_id = self.next_teleport_id.wrapping_add(1);
+        packet.teleport_id = VarInt(teleport_id);
+
+        self.connection.write_packet(&packet).await?;
+        self.movement
+            .teleported(teleport_id, (packet.x, packet.y, packet.z));
+        Ok(teleport_id)
+    }
+
+    /// Write a packet queued by another task
+    ///
+    /// Queued teleports, such as those of `/tp`, are sent under this
+    /// connection's next teleport ID so the client's confirmation is
+    /// recognized, and bring the chunks around the destination into view.
+    /// Slots changed by commands such as `/give` may change what the player
+    /// holds, which the players around them are then shown. A queued
+    /// disconnect, such as from `/kick`, closes the connection.
+    async fn write_outgoing(&mut self, packet: RawPacket) -> Result<()> {
+        if packet.id.0 == PlayDisconnectPacket::ID {
+            self.connection.write_raw_packet(&packet).await?;
+            self.connection.close().await?;
+            return Err(ServerError::Disconnected(
+                "Kicked by the server".to_string(),
+            ));
+        }
+        if packet.id.0 == SetContainerSlotPacket::ID {
+            self.connection.write_raw_packet(&packet).await?;
+            return self.sync_equipment().await;
+        }
+        if packet.id.0 != PlayerPositionPacket::ID {
+            return self.connection.write_raw_packet(&packet).await;
+        }
+        let position = packet.parse::<PlayerPositionPacket>()?;
+        let (x, z) = (position.x, position.z);
+        self.send_position(position).await?;
+        self.recenter_view(x, z).await
+    }
+
+    /// Wait for the client to confirm a teleport
+    ///
+    /// Other play packets received in the meantime are handled as usual.
+    async fn await_teleport_confirm(&mut self, teleport_id: i32) -> Result<()> {
+        let deadline = tokio::time::Instant::now() + self.context.config.teleport_confirm_timeout;
+
+        loop {
+            let (packet_id, data) =
+                tokio::time::timeout_at(deadline, self.connection.read_packet())
+                    .await
+                    .map_err(|_| ServerError::Protocol("Teleport confirm timeout".to_string()))??;
+
+            if packet_id.0 != ConfirmTeleportPacket::ID {
+                self.handle_play_packet(packet_id, &data).await?;
+                continue;
+            }
+
+            let confirm = ConfirmTeleportPacket::read(&mut std::io::Cursor::new(data))?;
+            if confirm.teleport_id.0 == teleport_id {
+                self.movement.confirm_teleport(teleport_id);
+                tracing::debug!("Teleport {} confirmed", teleport_id);
+                return Ok(());
+            }
+            tracing::debug!(
+                "Ignoring confirmation of teleport {} while waiting for {}",
+                confirm.teleport_id.0,
+                teleport_id
+            );
+        }
+    }
+
+    /// Send the chunks within view distance of where the player spawned
+    async fn send_spawn_chunks(&mut self, x: f64, z: f64) -> Result<()> {
+        self.connection
+            .write_packet(&GameEventPacket::start_waiting_for_chunks())
+            .await?;
+        self.update_view(chunk_of(x, z)).await
+    }
+
+    /// Load the chunks around a chunk and unload the ones out of view
+    async fn update_view(&mut self, center: (i32, i32)) -> Result<()> {
+        let radius = self.view_distance();
+        self.view
+            .sync(
+                &mut self.connection,
+                self.context.chunk_provider.as_ref(),
+                center,
+                radius,
+            )
+            .await
+    }
+
+    /// Pass a chat message from the client to the server's chat router
+    async fn handle_chat_message(&mut self, chat: ChatMessagePacket) -> Result<()> {
+        if !chat::verify_signature(&chat) {
+            return Err(ServerError::Protocol(
+                "Invalid chat message signature".to_string(),
+            ));
+        }
+        let Some(uuid) = self.player_uuid else {
+            return Ok(());
+        };
+        if chat.message.0.starts_with('/') {
+            return self.run_command(&chat.message.0).await;
+        }
+        let mut event = ChatEvent::new(uuid, chat.message.0);
+        self.context.events.fire(&mut event);
+        if event.cancelled {
+            return Ok(());
+        }
+        self.context
+            .chat_router
+            .route(&uuid, &event.message, &self.context.player_list)
+            .await
+    }
+
+    /// Run a command line sent by the player
+    async fn run_command(&mut self, line: &str) -> Result<()> {
+        let Some(uuid) = self.player_uuid else {
+            return Ok(());
+        };
+        let context = Arc::clone(&self.context);
+        context
+            .commands
+            .dispatch(uuid, line, Arc::clone(&context))
+            .await
+    }
+
+    /// Pass the client's answer to a keep alive on to the task that sent it
+    fn handle_keep_alive(&self, keep_alive: ServerboundKeepAlivePacket) {
+        if let Some(handle) = &self.keep_alive {
+            handle.respond(keep_alive.keep_alive_id);
+        }
+    }
+
+    /// Apply settings the client changed while playing, sending or
+    /// unloading chunks if its view distance changed
+    async fn handle_client_settings(&mut self, settings: ClientSettingsPacket) -> Result<()> {
+        self.update_client_settings(settings).await;
+        if self.view.loaded_count() > 0 {
+            return self.update_view(self.view.center()).await;
+        }
+        Ok(())
+    }
+
+    /// Remember the settings sent by the client, and apply its chat mode if
+    /// it is in the player list
+    async fn update_client_settings(&mut self, settings: ClientSettingsPacket) {
+        tracing::debug!(
+            "Client settings: locale {}, view distance {}",
+            settings.locale.0,
+            settings.view_distance
+        );
+        self.client_state.settings = Some(settings.into());
+        if let Some(uuid) = self.player_uuid {
+            self.context
+                .player_list
+                .update_chat_mode(&uuid, self.chat_mode())
+                .await;
+        }
+    }
+
+    /// Chat mode from the client's settings, enabled until it sends them
+    fn chat_mode(&self) -> i32 {
+        self.client_state
+            .settings
+            .as_ref()
+            .map_or(PlayerInfo::CHAT_ENABLED, |settings| settings.chat_mode)
+    }
+
+    /// Handle a plugin message, storing the client's brand and passing
+    /// anything else to the registered channel handler
+    fn handle_plugin_message(&mut self, message: ServerboundCustomPayloadPacket) -> Result<()> {
+        if message.channel == McIdentifier::minecraft("brand") {
+            let brand = McString::read(&mut std::io::Cursor::new(message.data))?;
+            tracing::debug!("Client brand: {}", brand.0);
+            self.client_state.brand = Some(brand.0);
+            return Ok(());
+        }
+
+        let Some(uuid) = self.player_uuid else {
+            return Ok(());
+        };
+        let handled =
+            self.context
+                .plugin_channels
+                .dispatch(&message.channel, &uuid, &message.data)?;
+        if !handled {
+            tracing::debug!("Ignoring message on unknown channel {}", message.channel);
+        }
+        Ok(())
+    }
+
+    /// Get what the client has told the server about itself
+    pub fn client_state(&self) -> &ClientState {
+        &self.client_state
+    }
+
+    /// Chunk radius to send: the server's view distance, limited by the
+    /// client's render distance if it has sent its settings
+    fn view_distance(&self) -> u8 {
+        ViewDistanceTracker::effective_radius(
+            self.context.config.view_distance,
+            self.client_state
+                .settings
+                .as_ref()
+                .map(|settings| settings.view_distance),
+        )
+    }
+
+    /// Answer a tab-completion request
+    async fn handle_command_suggestions(
+        &mut self,
+        request: CommandSuggestionsRequestPacket,
+    ) -> Result<()> {
+        let Some(uuid) = self.player_uuid else {
+            return Ok(());
+        };
+
+        let suggestions = self
+            .context
+            .command_suggestions
+            .suggest(&uuid, &request.text.0);
+        self.connection
+            .write_packet(&CommandSuggestionsResponsePacket {
+                transaction_id: request.transaction_id,
+                start: VarInt(suggestions.start as i32),
+                length: VarInt(suggestions.length as i32),
+                matches: suggestions.matches,
+            })
+            .await
+    }
+
+    /// Move the player to a reported position, if the move is plausible
+    ///
+    /// Impossible moves teleport the player back to its last valid position.
+    async fn handle_move(
+        &mut self,
+        (x, y, z): (f64, f64, f64),
+        rotation: Option<(f32, f32)>,
+    ) -> Result<()> {
+        match self.movement.check(x, y, z) {
+            MovementCheck::Accepted => {}
+            MovementCheck::Ignored => return Ok(()),
+            MovementCheck::Rejected(last_x, last_y, last_z) => {
+                tracing::warn!(
+                    "Rejected move to ({:.1}, {:.1}, {:.1}), teleporting back",
+                    x,
+                    y,
+                    z
+                );
+                self.teleport(last_x, last_y, last_z).await?;
+                return Ok(());
+            }
+        }
+
+        if let Some(entity_id) = self.entity_id {
+            self.context
+                .entities
+                .update_position(entity_id, x, y, z)
+                .await;
+            if let Some((yaw, pitch)) = rotation {
+                self.context
+                    .entities
+                    .update_rotation(entity_id, yaw, pitch)
+                    .await;
+            }
+        }
+        self.recenter_view(x, z).await
+    }
+
+    /// Move the loaded chunks along with the player, once the spawn chunks
+    /// have been sent
+    async fn recenter_view(&mut self, x: f64, z: f64) -> Result<()> {
+        let center = chunk_of(x, z);
+        if self.view.loaded_count() > 0 && center != self.view.center() {
+            return self.update_view(center).await;
+        }
+        Ok(())
+    }
+
+    /// Tell the client what its player may do
+    async fn send_abilities(&mut self) -> Result<()> {
+        let Some(uuid) = self.player_uuid else {
+            return Ok(());
+        };
+        let Some(player) = self.context.players.get_player(&uuid).await else {
+            return Ok(());
+        };
+        self.connection
+            .write_packet(&PlayerAbilitiesPacket::new(player.ability_flags()))
+            .await
+    }
+
+    /// Start or stop flying, as long as the game mode allows it
+    ///
+    /// Players that try to fly without being allowed to are landed again.
+    async fn handle_player_abilities(
+        &mut self,
+        abilities: ServerboundPlayerAbilitiesPacket,
+    ) -> Result<()> {
+        let Some(uuid) = self.player_uuid else {
+            return Ok(());
+        };
+        let Some(mut player) = self.context.players.get_player(&uuid).await else {
+            return Ok(());
+        };
+
+        let flying = abilities.is_flying();
+        if flying && !player.game_mode.allows_flight() {
+            tracing::warn!(
+                "{} tried to fly in {:?} mode",
+                player.username,
+                player.game_mode
+            );
+            player.flying = false;
+            self.context.players.update_player(&uuid, player).await;
+            return self.send_abilities().await;
+        }
+        player.flying = flying;
+        self.context.players.update_player(&uuid, player).await;
+        Ok(())
+    }
+
+    /// Game mode of the player
+    async fn game_mode(&self) -> GameMode {
+        match self.player_uuid {
+            Some(uuid) => self.context.players.get_player(&uuid).await,
+            None => None,
+        }
+        .map_or(GameMode::Survival, |player| player.game_mode)
+    }
+
+    /// Send a packet to the other players within render distance of the
+    /// player
+    async fn send_to_nearby<P: Packet>(&self, packet: &P) -> Result<()> {
+        let (Some(uuid), Some(entity_id)) = (self.player_uuid, self.entity_id) else {
+            return Ok(());
+        };
+        let Some(state) = self.context.entities.get(entity_id).await else {
+            return Ok(());
+        };
+        let view_distance = i32::from(self.context.config.view_distance);
+        for viewer in self
+            .context
+            .entities
+            .players_near(state.x, state.z, view_distance, &uuid)
+            .await
+        {
+            self.context.player_list.send_to(&viewer, packet).await?;
+        }
+        Ok(())
+    }
+
+    /// Handle play state packets
+    async fn handle_play_packet(&mut self, packet_id: VarInt, data: &[u8]) -> Result<()> {
+        match packet_id.0 {
+            ServerboundKeepAlivePacket::ID => {
+                self.handle_keep_alive(decode(data)?);
+                Ok(())
+            }
+            ClientSettingsPacket::ID => self.handle_client_settings(decode(data)?).await,
+            SetPlayerPositionPacket::ID => {
+                let position: SetPlayerPositionPacket = decode(data)?;
+                self.handle_move((position.x, position.y, position.z), None)
+                    .await
+            }
+            SetPlayerPositionAndRotationPacket::ID => {
+                let position: SetPlayerPositionAndRotationPacket = decode(data)?;
+                let rotation = (position.yaw, position.pitch);
+                self.handle_move((position.x, position.y, position.z), Some(rotation))
+                    .await
+            }
+            ConfirmTeleportPacket::ID => {
+                let confirm: ConfirmTeleportPacket = decode(data)?;
+                self.movement.confirm_teleport(confirm.teleport_id.0);
+                Ok(())
+            }
+            ServerboundCustomPayloadPacket::ID => self.handle_plugin_message(decode(data)?),
+            ChatMessagePacket::ID => self.handle_chat_message(decode(data)?).await,
+            ChatCommandPacket::ID => {
+                let command: ChatCommandPacket = decode(data)?;
+                self.run_command(&command.command.0).await
+            }
+            CommandSuggestionsRequestPacket::ID => {
+                self.handle_command_suggestions(decode(data)?).await
+            }
+            ServerboundPlayerAbilitiesPacket::ID => {
+                self.handle_player_abilities(decode(data)?).await
+            }
+            UseItemOnPacket::ID => self.handle_use_item_on(decode(data)?).await,
+            PlayerActionPacket::ID => self.handle_player_action(decode(data)?).await,
+            ServerboundCloseContainerPacket::ID => {
+                self.handle_close_container(decode(data)?).await;
+                Ok(())
+            }
+            ClickContainerPacket::ID => self.handle_click_container(decode(data)?).await,
+            ServerboundSetHeldItemPacket::ID => self.handle_set_held_item(decode(data)?).await,
+            ClientCommandPacket::ID => self.handle_client_command(decode(data)?).await,
+            SignUpdatePacket::ID => self.handle_sign_update(decode(data)?).await,
+            SwingArmPacket::ID => self.handle_swing_arm(decode(data)?).await,
+            id => {
+                tracing::debug!("Ignoring unsupported play packet 0x{:02X}", id);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Receive the next queued packet, or wait forever if there is no queue
+async fn recv_outgoing(outgoing: &mut Option<PacketReceiver>) -> Option<RawPacket> {
+    match outgoing {
+        Some(receiver) => receiver.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Wait for the next tick of a timer, or forever if there is none
+async fn next_tick(timer: &mut Option<Interval>) {
+    match timer {
+        Some(timer) => {
+            timer.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// Wait for a new latency, or forever if there is nothing measuring it
+///
+/// Returns `None` once the keep alive task has stopped.
+async fn latency_changed(latency: &mut Option<watch::Receiver<Duration>>) -> Option<Duration> {
+    match latency {
+        Some(latency) => match latency.changed().await {
+            Ok(()) => Some(*latency.borrow_and_update()),
+            Err(_) => None,
+        },
+        None => std::future::pending().await,
+    }
+}
+
+/// Wait for the server to start closing
+async fn server_closing(closing: &mut watch::Receiver<bool>) {
+    if closing.wait_for(|closing| *closing).await.is_err() {
+        // The server is gone without closing, so it never will
+        std::future::pending().await
+    }
+}
+
+/// Wait for the keep alive task to finish, or forever if there is none
+async fn keep_alive_finished(task: &mut Option<JoinHandle<Result<()>>>) -> Result<()> {
+    match task {
+        Some(task) => task.await.unwrap_or_else(|e| {
+            Err(ServerError::Protocol(format!(
+                "Keep alive task failed: {}",
+                e
+            )))
+        }),
+        None => std::future::pending().await,
+    }
+}
+
+/// Decode the body of a packet
+fn decode<P: Packet>(data: &[u8]) -> Result<P> {
+    P::read(&mut std::io::Cursor::new(data))
+}
+
+/// Chunk holding a horizontal position
+fn chunk_of(x: f64, z: f64) -> (i32, i32) {
+    ((x.floor() as i32) >> 4, (z.floor() as i32) >> 4)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ResourcePack, ServerConfig, TransferBackend};
+    use crate::game::attributes::STANDARD_ATTRIBUTES;
+    use crate::protocol::PROTOCOL_VERSION;
+    use crate::protocol::packets::configuration::{
+        AcknowledgeFinishConfigurationPacket, FinishConfigurationPacket, TransferPacket,
+    };
+    use crate::protocol::packets::play::abilities;
+    use crate::protocol::packets::play::{
+        AcknowledgeBlockChangePacket, ChunkDataPacket, DisplayObjectivePacket, KeepAlivePacket,
+        PlayerActionStatus, PlayerInfoUpdatePacket, ScoreboardObjectivePacket,
+        SetCenterChunkPacket, SetContainerContentPacket, SetHealthPacket, StoreCookiePacket,
+        UnloadChunkPacket, UpdateAttributesPacket, UpdateScorePacket, UpdateTimePacket,
+    };
+    use crate::protocol::types::Slot;
+    use crate::server::bans::BanEntry;
+    use crate::server::whitelist::WhitelistEntry;
+    use crate::storage::PlayerDataStore;
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::task::JoinHandle;
+
+    /// Mock client connected to a handler running in the background
+    pub(super) struct TestClient {
+        /// Client side of the connection
+        pub(super) connection: Connection,
+        /// Shared server state used by the handler
+        pub(super) context: Arc<ServerContext>,
+        /// Handler task, resolving to the handler's result
+        pub(super) handler: JoinHandle<Result<()>>,
+    }
+
+    /// Start a handler for a single connection and return a connected mock client
+    pub(super) async fn connect(config: ServerConfig) -> TestClient {
+        reconnect(ServerContext::for_tests(config)).await
+    }
+
+    /// Start a handler for a single connection to an existing server and
+    /// return a connected mock client
+    async fn reconnect(context: Arc<ServerContext>) -> TestClient {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server_context = Arc::clone(&context);
+        let handler = tokio::spawn(async move {
+            let (stream, peer_addr) = listener.accept().await?;
+            let handler =
+                ConnectionHandler::new(Connection::new(stream, peer_addr), server_context);
+            handler.run().await
+        });
+
+        let stream = TcpStream::connect(addr).await.unwrap();
+        TestClient {
+            connection: Connection::new(stream, addr),
+            context,
+            handler,
+        }
+    }
+
+    /// Drive the client through handshake and login into the configuration state
+    async fn login(client: &mut Connection, uuid: McUuid) {
+        let (packet_id, _) = start_configuration(client, uuid).await;
+        assert_eq!(packet_id.0, FinishConfigurationPacket::ID);
+    }
+
+    /// Send the handshake and login start of a player named Steve
+    async fn start_login(client: &mut Connection, uuid: McUuid) {
+        start_login_with_intent(client, uuid, NextState::Login).await;
+    }
+
+    /// Send the handshake with an intent and the login start of a player
+    /// named Steve
+    async fn start_login_with_intent(client: &mut Connection, uuid: McUuid, intent: NextState) {
+        client
+            .write_packet(&HandshakePacket {
+                protocol_version: VarInt(PROTOCOL_VERSION),
+                server_address: "localhost".into(),
+                server_port: 25565,
+                next_state: intent.into(),
+            })
+            .await
+            .unwrap();
+        client.set_state(ConnectionState::Login);
+
+        client
+            .write_packet(&LoginStartPacket {
+                name: "Steve".into(),
+                player_uuid: uuid,
+            })
+            .await
+            .unwrap();
+    }
+
+    /// Log the client in and skip the registries, returning the first packet
+    /// sent after them
+    async fn start_configuration(client: &mut Connection, uuid: McUuid) -> (VarInt, Vec<u8>) {
+        start_login(client, uuid).await;
+
+        // The mock client has no cookies
+        let (mut packet_id, mut data) = client.read_packet().await.unwrap();
+        while packet_id.0 == CookieRequestPacket::ID {
+            let request = CookieRequestPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+            let response = CookieResponsePacket {
+                key: request.key,
+                payload: None,
+            };
+            client.write_packet(&response).await.unwrap();
+            (packet_id, data) = client.read_packet().await.unwrap();
+        }
+
+        // Compression, if enabled, is negotiated before login success
+        if packet_id.0 == SetCompressionPacket::ID {
+            let compression = SetCompressionPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+            client
+                .enable_compression(compression.threshold.0 as u32)
+                .unwrap();
+            (packet_id, data) = client.read_packet().await.unwrap();
+        }
+
+        assert_eq!(packet_id.0, LoginSuccessPacket::ID);
+        let success = LoginSuccessPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        assert_eq!(success.uuid, uuid);
+        assert_eq!(success.username.0, "Steve");
+        assert!(success.properties.is_empty());
+
+        client.write_packet(&LoginAcknowledgedPacket).await.unwrap();
+        client.set_state(ConnectionState::Configuration);
+
+        // The server only starts configuration after the acknowledgement,
+        // with the feature flags first
+        let (packet_id, data) = client.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, FeaturesPacket::ID);
+        let features = FeaturesPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        assert_eq!(features.features, [McIdentifier::minecraft("vanilla")]);
+
+        // The mock client knows no packs, so it gets every registry in full
+        let (packet_id, _) = client.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, KnownPacksPacket::ID);
+        client
+            .write_packet(&SelectKnownPacksPacket { packs: Vec::new() })
+            .await
+            .unwrap();
+
+        let mut registries = 0;
+        let (mut packet_id, mut data) = client.read_packet().await.unwrap();
+        while packet_id.0 == RegistryDataPacket::ID {
+            registries += 1;
+            (packet_id, data) = client.read_packet().await.unwrap();
+        }
+        assert!(registries > 0);
+        (packet_id, data)
+    }
+
+    #[tokio::test]
+    async fn test_login_to_configuration_transition() {
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_compression_threshold(Some(256));
+        let mut client = connect(config).await;
+
+        let uuid = McUuid::new_v4();
+        login(&mut client.connection, uuid).await;
+        assert!(client.context.players.get_player(&uuid).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_known_packs_skip_registry_data() {
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_compression_threshold(None);
+        let mut client = connect(config).await;
+        start_login(&mut client.connection, McUuid::new_v4()).await;
+        let (packet_id, _) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, LoginSuccessPacket::ID);
+        client
+            .connection
+            .write_packet(&LoginAcknowledgedPacket)
+            .await
+            .unwrap();
+        client.connection.set_state(ConnectionState::Configuration);
+
+        let (packet_id, _) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, FeaturesPacket::ID);
+        let (packet_id, data) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, KnownPacksPacket::ID);
+        let offered = KnownPacksPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        assert_eq!(offered.packs.len(), 1);
+        assert_eq!(offered.packs[0].id.0, "core");
+        client
+            .connection
+            .write_packet(&SelectKnownPacksPacket {
+                packs: offered.packs,
+            })
+            .await
+            .unwrap();
+
+        // Every entry is listed, but its data comes from the client's pack
+        let (mut packet_id, mut data) = client.connection.read_packet().await.unwrap();
+        while packet_id.0 == RegistryDataPacket::ID {
+            let registry = RegistryDataPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+            assert!(!registry.entries.is_empty());
+            assert!(registry.entries.iter().all(|entry| entry.data.is_none()));
+            (packet_id, data) = client.connection.read_packet().await.unwrap();
+        }
+        assert_eq!(packet_id.0, FinishConfigurationPacket::ID);
+    }
+
+    #[tokio::test]
+    async fn test_configuration_to_play_transition() {
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_compression_threshold(None)
+            .with_view_distance(2);
+        let mut client = connect(config).await;
+        let uuid = McUuid::new_v4();
+        login(&mut client.connection, uuid).await;
+
+        client
+            .connection
+            .write_packet(&AcknowledgeFinishConfigurationPacket)
+            .await
+            .unwrap();
+
+        let (packet_id, _) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, LoginPlayPacket::ID);
+        let (packet_id, data) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, PlayerAbilitiesPacket::ID);
+        let abilities = PlayerAbilitiesPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        assert_eq!(abilities.flags, 0);
+        let (packet_id, data) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, UpdateAttributesPacket::ID);
+        let attributes = UpdateAttributesPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        assert_eq!(attributes.attributes.len(), STANDARD_ATTRIBUTES.len());
+        let (packet_id, data) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, SetHealthPacket::ID);
+        let health = SetHealthPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        assert_eq!((health.health, health.food.0), (20.0, 20));
+        let (packet_id, _) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, SetContainerContentPacket::ID);
+        let (packet_id, data) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, DeclareCommandsPacket::ID);
+        let commands = DeclareCommandsPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        assert_eq!(commands.root, client.context.commands.command_tree_for(0));
+        let (packet_id, data) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, UpdateTimePacket::ID);
+        let time = UpdateTimePacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        assert_eq!((time.world_age, time.time_of_day), (0, 0));
+        assert!(time.time_of_day_increasing);
+
+        let (packet_id, data) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, PlayerPositionPacket::ID);
+        let position = PlayerPositionPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        assert_eq!((position.x, position.y, position.z), (0.5, 64.0, 0.5));
+
+        // A stale confirmation is ignored; chunks follow the matching one
+        for teleport_id in [position.teleport_id.0 + 1, position.teleport_id.0] {
+            client
+                .connection
+                .write_packet(&ConfirmTeleportPacket {
+                    teleport_id: VarInt(teleport_id),
+                })
+                .await
+                .unwrap();
+        }
+
+        let (packet_id, _) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, GameEventPacket::ID);
+        let (packet_id, data) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, SetCenterChunkPacket::ID);
+        let center = SetCenterChunkPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        assert_eq!((center.chunk_x.0, center.chunk_z.0), (0, 0));
+
+        // A view distance of 2 covers a 5x5 area of chunks
+        for _ in 0..25 {
+            let (packet_id, data) = client.connection.read_packet().await.unwrap();
+            assert_eq!(packet_id.0, ChunkDataPacket::ID);
+            let chunk = ChunkDataPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+            assert!(chunk.chunk_x.abs() <= 2 && chunk.chunk_z.abs() <= 2);
+        }
+
+        // The tab list starts with the client itself
+        let (packet_id, data) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, PlayerInfoUpdatePacket::ID);
+        let tab_list = PlayerInfoUpdatePacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        assert_eq!(tab_list.actions, PlayerInfoUpdatePacket::ADD_ACTIONS);
+        assert_eq!(tab_list.entries.len(), 1);
+        assert_eq!(tab_list.entries[0].name.0, "Steve");
+
+        // The ping objective is shown in the tab list with the client's score
+        let (packet_id, _) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, ScoreboardObjectivePacket::ID);
+        let (packet_id, _) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, DisplayObjectivePacket::ID);
+        let (packet_id, data) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, UpdateScorePacket::ID);
+        let score = UpdateScorePacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        assert_eq!(score.entity_name.0, "Steve");
+
+        // Once in the player list, broadcasts reach the client
+        let player_list = &client.context.player_list;
+        while player_list.get_player(&uuid).await.is_none() {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(
+            player_list.get_player(&uuid).await.unwrap().username,
+            "Steve"
+        );
+        let sent = player_list
+            .broadcast(&KeepAlivePacket { keep_alive_id: 42 })
+            .await
+            .unwrap();
+        assert_eq!(sent, 1);
+
+        let (packet_id, data) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, KeepAlivePacket::ID);
+        let keep_alive = KeepAlivePacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        assert_eq!(keep_alive.keep_alive_id, 42);
+    }
+
+    #[tokio::test]
+    async fn test_forced_resource_pack() {
+        let pack = ResourcePack {
+            id: McUuid::new_v4(),
+            url: "https://example.com/pack.zip".to_string(),
+            hash: String::new(),
+            forced: true,
+            prompt: Some("Please".to_string()),
+        };
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_compression_threshold(None)
+            .with_resource_pack(pack.clone());
+        let mut client = connect(config).await;
+
+        let (packet_id, data) = start_configuration(&mut client.connection, McUuid::new_v4()).await;
+        assert_eq!(packet_id.0, ResourcePackSendPacket::ID);
+        let offer = ResourcePackSendPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        assert_eq!(offer.uuid, pack.id);
+        assert!(offer.forced);
+
+        // Configuration only finishes once the pack is loaded
+        for result in [
+            ResourcePackResult::Accepted,
+            ResourcePackResult::SuccessfullyLoaded,
+        ] {
+            client
+                .connection
+                .write_packet(&ResourcePackResponsePacket {
+                    uuid: pack.id,
+                    result,
+                })
+                .await
+                .unwrap();
+        }
+        let (packet_id, _) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, FinishConfigurationPacket::ID);
+    }
+
+    #[tokio::test]
+    async fn test_declined_forced_resource_pack() {
+        let pack = ResourcePack {
+            id: McUuid::new_v4(),
+            url: "https://example.com/pack.zip".to_string(),
+            hash: String::new(),
+            forced: true,
+            prompt: None,
+        };
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_compression_threshold(None)
+            .with_resource_pack(pack.clone());
+        let mut client = connect(config).await;
+
+        let (packet_id, _) = start_configuration(&mut client.connection, McUuid::new_v4()).await;
+        assert_eq!(packet_id.0, ResourcePackSendPacket::ID);
+
+        client
+            .connection
+            .write_packet(&ResourcePackResponsePacket {
+                uuid: pack.id,
+                result: ResourcePackResult::Declined,
+            })
+            .await
+            .unwrap();
+        let (packet_id, _) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, ConfigurationDisconnectPacket::ID);
+        assert!(client.handler.await.unwrap().is_err());
+    }
+
+    /// Try to log in, expecting to be turned away with a reason
+    async fn assert_rejected(mut client: TestClient, reason: &str) {
+        start_login(&mut client.connection, McUuid::new_v4()).await;
+        let (packet_id, data) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, LoginDisconnectPacket::ID);
+        let disconnect = LoginDisconnectPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        assert_eq!(disconnect.reason, JsonTextComponent::text(reason));
+        // Being turned away is not a connection error
+        client.handler.await.unwrap().unwrap();
+        assert_eq!(client.context.players.player_count().await, 0);
+    }
+
+    /// Send a login handshake with a protocol version and return the
+    /// disconnect reason
+    async fn handshake_rejection(config: ServerConfig, version: i32) -> JsonTextComponent {
+        let mut client = connect(config).await;
+        client
+            .connection
+            .write_packet(&HandshakePacket {
+                protocol_version: VarInt(version),
+                server_address: "localhost".into(),
+                server_port: 25565,
+                next_state: VarInt(2),
+            })
+            .await
+            .unwrap();
+        client.connection.set_state(ConnectionState::Login);
+        let (packet_id, data) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, LoginDisconnectPacket::ID);
+        client.handler.await.unwrap().unwrap();
+        LoginDisconnectPacket::read(&mut std::io::Cursor::new(data))
+            .unwrap()
+            .reason
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_protocol_version_is_rejected() {
+        let config = ServerConfig::new().with_online_mode(false);
+        assert_eq!(
+            handshake_rejection(config.clone(), 769).await,
+            JsonTextComponent::text("Outdated client! Please use 1.21.6")
+        );
+
+        let config = config.with_accepted_protocol_versions(vec![769, 770]);
+        assert_eq!(
+            handshake_rejection(config, PROTOCOL_VERSION).await,
+            JsonTextComponent::text("Outdated server! I'm still on 1.21.5")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_transferred_player_is_sent_to_backend() {
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_compression_threshold(None)
+            .with_transfer_backend(TransferBackend::new("lobby-1", 25566))
+            .with_transfer_backend(TransferBackend::new("lobby-2", 25567));
+        let mut client = connect(config).await;
+        let uuid = McUuid::new_v4();
+        start_login_with_intent(&mut client.connection, uuid, NextState::Transfer).await;
+
+        let (packet_id, _) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, LoginSuccessPacket::ID);
+        client
+            .connection
+            .write_packet(&LoginAcknowledgedPacket)
+            .await
+            .unwrap();
+        client.connection.set_state(ConnectionState::Configuration);
+
+        // The transfer comes instead of the registries
+        let (packet_id, data) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, TransferPacket::ID);
+        let transfer = TransferPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        assert_eq!(transfer.host.0, "lobby-1");
+        assert_eq!(transfer.port.0, 25566);
+        client.handler.await.unwrap().unwrap();
+        assert_eq!(client.context.players.player_count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_login_cookies_are_requested() {
+        let key = McIdentifier::new("lobby", "session").unwrap();
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_compression_threshold(None)
+            .with_login_cookie(key.clone());
+        let mut client = connect(config).await;
+        start_login(&mut client.connection, McUuid::new_v4()).await;
+
+        let (packet_id, data) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, CookieRequestPacket::ID);
+        let request = CookieRequestPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        assert_eq!(request.key, key);
+        let (packet_id, _) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, LoginSuccessPacket::ID);
+
+        let response = CookieResponsePacket {
+            key,
+            payload: Some(vec![1, 2, 3]),
+        };
+        client.connection.write_packet(&response).await.unwrap();
+        // Answering twice is a protocol violation
+        client.connection.write_packet(&response).await.unwrap();
+        let (packet_id, data) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, LoginDisconnectPacket::ID);
+        let disconnect = LoginDisconnectPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        assert!(
+            disconnect
+                .reason
+                .0
+                .contains("Unexpected cookie response: lobby:session")
+        );
+        assert!(client.handler.await.unwrap().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cookie_survives_reconnect() {
+        let key = McIdentifier::new("lobby", "session").unwrap();
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_compression_threshold(None)
+            .with_view_distance(1)
+            .with_login_cookie(key.clone());
+        let mut client = connect(config).await;
+        let context = Arc::clone(&client.context);
+        let uuid = McUuid::new_v4();
+        enter_play(&mut client, uuid).await;
+        assert_eq!(context.cookies.cookie(&uuid, &key).await, None);
+        while context.player_list.get_player(&uuid).await.is_none() {
+            tokio::task::yield_now().await;
+        }
+
+        let stored = context
+            .cookies
+            .store_cookie(&uuid, key.clone(), vec![4, 2])
+            .await
+            .unwrap();
+        assert!(stored);
+        let stored = loop {
+            let (packet_id, data) = client.connection.read_packet().await.unwrap();
+            if packet_id.0 == StoreCookiePacket::ID {
+                break StoreCookiePacket::read(&mut std::io::Cursor::new(data)).unwrap();
+            }
+        };
+        assert_eq!(stored.key, key);
+        drop(client.connection);
+        let _ = client.handler.await.unwrap();
+
+        // The client sends the cookie back when it joins again
+        let mut client = reconnect(context).await;
+        start_login(&mut client.connection, uuid).await;
+        let (packet_id, data) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, CookieRequestPacket::ID);
+        let request = CookieRequestPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        assert_eq!(request.key, key);
+        let response = CookieResponsePacket {
+            key: key.clone(),
+            payload: Some(stored.payload),
+        };
+        client.connection.write_packet(&response).await.unwrap();
+        let (packet_id, _) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, LoginSuccessPacket::ID);
+        client
+            .connection
+            .write_packet(&LoginAcknowledgedPacket)
+            .await
+            .unwrap();
+        client.connection.set_state(ConnectionState::Configuration);
+        let (packet_id, _) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, FeaturesPacket::ID);
+        assert_eq!(
+            client.context.cookies.cookie(&uuid, &key).await,
+            Some(vec![4, 2])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_banned_player_is_rejected() {
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_compression_threshold(None);
+        let client = connect(config).await;
+        let ban = BanEntry::new("steve", None, "Griefing");
+        let reason = ban.disconnect_reason();
+        client.context.bans.write().await.add(ban);
+        assert_rejected(client, &reason).await;
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_join_is_rejected() {
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_compression_threshold(None);
+        let client = connect(config).await;
+        client
+            .context
+            .events
+            .register(|event: &mut PlayerJoinEvent| {
+                event.cancelled = event.username == "Steve";
+                event.kick_message = "Maintenance".to_string();
+            });
+        assert_rejected(client, "Maintenance").await;
+    }
+
+    #[tokio::test]
+    async fn test_whitelist_is_enforced() {
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_compression_threshold(None)
+            .with_white_list(true);
+        let client = connect(config).await;
+        client
+            .context
+            .whitelist
+            .write()
+            .await
+            .add(WhitelistEntry::new("Alex", None));
+        assert_rejected(client, NOT_WHITELISTED).await;
+
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_white_list(true);
+        let mut client = connect(config).await;
+        client
+            .context
+            .whitelist
+            .write()
+            .await
+            .add(WhitelistEntry::new("Steve", None));
+        login(&mut client.connection, McUuid::new_v4()).await;
+    }
+
+    #[tokio::test]
+    async fn test_client_view_distance_limits_chunks() {
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_compression_threshold(None)
+            .with_view_distance(8);
+        let mut client = connect(config).await;
+        login(&mut client.connection, McUuid::new_v4()).await;
+
+        let settings = ClientSettingsPacket {
+            locale: "en_us".into(),
+            view_distance: 2,
+            chat_mode: VarInt(0),
+            chat_colors: true,
+            displayed_skin_parts: 0x7F,
+            main_hand: VarInt(1),
+            enable_text_filtering: false,
+            allow_server_listings: true,
+            particle_status: VarInt(0),
+        };
+        client
+            .connection
+            .write_packet(&ConfigurationClientSettingsPacket(settings))
+            .await
+            .unwrap();
+        client
+            .connection
+            .write_packet(&AcknowledgeFinishConfigurationPacket)
+            .await
+            .unwrap();
+
+        let (mut packet_id, mut data) = client.connection.read_packet().await.unwrap();
+        while packet_id.0 != PlayerPositionPacket::ID {
+            (packet_id, data) = client.connection.read_packet().await.unwrap();
+        }
+        let position = PlayerPositionPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        client
+            .connection
+            .write_packet(&ConfirmTeleportPacket {
+                teleport_id: position.teleport_id,
+            })
+            .await
+            .unwrap();
+
+        // Game event and center chunk, then only the client's 5x5 area
+        for _ in 0..2 {
+            client.connection.read_packet().await.unwrap();
+        }
+        for _ in 0..25 {
+            let (packet_id, data) = client.connection.read_packet().await.unwrap();
+            assert_eq!(packet_id.0, ChunkDataPacket::ID);
+            let chunk = ChunkDataPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+            assert!(chunk.chunk_x.abs() <= 2 && chunk.chunk_z.abs() <= 2);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chunks_follow_player() {
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_compression_threshold(None)
+            .with_view_distance(1);
+        let mut client = connect(config).await;
+        login(&mut client.connection, McUuid::new_v4()).await;
+        client
+            .connection
+            .write_packet(&AcknowledgeFinishConfigurationPacket)
+            .await
+            .unwrap();
+
+        let (mut packet_id, mut data) = client.connection.read_packet().await.unwrap();
+        while packet_id.0 != PlayerPositionPacket::ID {
+            (packet_id, data) = client.connection.read_packet().await.unwrap();
+        }
+        let position = PlayerPositionPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        client
+            .connection
+            .write_packet(&ConfirmTeleportPacket {
+                teleport_id: position.teleport_id,
+            })
+            .await
+            .unwrap();
+        // Game event, center chunk, the 3x3 area around spawn, the tab list
+        // and the ping objective with its only score
+        for _ in 0..15 {
+            client.connection.read_packet().await.unwrap();
+        }
+
+        // Walk into the next chunk east
+        client
+            .connection
+            .write_packet(&SetPlayerPositionPacket {
+                x: 16.5,
+                y: 64.0,
+                z: 0.5,
+                on_ground: true,
+                pushing_against_wall: false,
+            })
+            .await
+            .unwrap();
+
+        let (packet_id, data) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, SetCenterChunkPacket::ID);
+        let center = SetCenterChunkPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        assert_eq!((center.chunk_x.0, center.chunk_z.0), (1, 0));
+        for _ in 0..3 {
+            let (packet_id, data) = client.connection.read_packet().await.unwrap();
+            assert_eq!(packet_id.0, UnloadChunkPacket::ID);
+            let unload = UnloadChunkPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+            assert_eq!(unload.chunk_x, -1);
+        }
+        for _ in 0..3 {
+            let (packet_id, data) = client.connection.read_packet().await.unwrap();
+            assert_eq!(packet_id.0, ChunkDataPacket::ID);
+            let chunk = ChunkDataPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+            assert_eq!(chunk.chunk_x, 2);
+        }
+    }
+
+    /// Log a client into the play state with a view distance of 1 and read
+    /// everything sent until its spawn chunks
+    pub(super) async fn enter_play(client: &mut TestClient, uuid: McUuid) {
+        login(&mut client.connection, uuid).await;
+        client
+            .connection
+            .write_packet(&AcknowledgeFinishConfigurationPacket)
+            .await
+            .unwrap();
+
+        let (mut packet_id, mut data) = client.connection.read_packet().await.unwrap();
+        while packet_id.0 != PlayerPositionPacket::ID {
+            (packet_id, data) = client.connection.read_packet().await.unwrap();
+        }
+        let position = PlayerPositionPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        client
+            .connection
+            .write_packet(&ConfirmTeleportPacket {
+                teleport_id: position.teleport_id,
+            })
+            .await
+            .unwrap();
+        // Game event, center chunk, the 3x3 area around spawn, the tab list
+        // and the ping objective with its only score
+        for _ in 0..15 {
+            client.connection.read_packet().await.unwrap();
+        }
+    }
+
+    /// Send a digging action and return the acknowledged sequence number
+    pub(super) async fn dig(
+        client: &mut TestClient,
+        status: PlayerActionStatus,
+        sequence: i32,
+    ) -> i32 {
+        dig_at(client, Position::new(0, 63, 0), status, sequence).await
+    }
+
+    /// Send a digging action for a block and return the acknowledged
+    /// sequence number
+    pub(super) async fn dig_at(
+        client: &mut TestClient,
+        position: Position,
+        status: PlayerActionStatus,
+        sequence: i32,
+    ) -> i32 {
+        client
+            .connection
+            .write_packet(&PlayerActionPacket {
+                status,
+                position,
+                face: 1,
+                sequence: VarInt(sequence),
+            })
+            .await
+            .unwrap();
+        let (packet_id, data) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, AcknowledgeBlockChangePacket::ID);
+        AcknowledgeBlockChangePacket::read(&mut std::io::Cursor::new(data))
+            .unwrap()
+            .sequence
+            .0
+    }
+
+    #[tokio::test]
+    async fn test_survival_players_cannot_fly() {
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_compression_threshold(None)
+            .with_view_distance(1);
+        let mut client = connect(config).await;
+        let uuid = McUuid::new_v4();
+        enter_play(&mut client, uuid).await;
+
+        client
+            .connection
+            .write_packet(&ServerboundPlayerAbilitiesPacket {
+                flags: abilities::FLYING,
+            })
+            .await
+            .unwrap();
+        let (packet_id, data) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, PlayerAbilitiesPacket::ID);
+        let reverted = PlayerAbilitiesPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        assert_eq!(reverted.flags & abilities::FLYING, 0);
+
+        // Creative players may fly
+        let mut player = client.context.players.get_player(&uuid).await.unwrap();
+        player.set_game_mode(GameMode::Creative);
+        client.context.players.update_player(&uuid, player).await;
+        client
+            .connection
+            .write_packet(&ServerboundPlayerAbilitiesPacket {
+                flags: abilities::FLYING,
+            })
+            .await
+            .unwrap();
+        // Round trip a command to know the abilities were handled
+        client
+            .connection
+            .write_packet(&ChatCommandPacket {
+                command: "unknown".into(),
+            })
+            .await
+            .unwrap();
+        client.connection.read_packet().await.unwrap();
+        assert!(
+            client
+                .context
+                .players
+                .get_player(&uuid)
+                .await
+                .unwrap()
+                .flying
+        );
+    }
+
+    #[tokio::test]
+    async fn test_player_data_is_restored() {
+        let directory =
+            std::env::temp_dir().join(format!("obsidium-{}-handler-data", std::process::id()));
+        let store = PlayerDataStore::new(directory.join("playerdata"));
+        let uuid = McUuid::new_v4();
+        let mut inventory = vec![Slot::empty(); 37];
+        inventory[36] = Slot::new(1, 5);
+        let saved = PlayerData {
+            position: (100.5, 70.0, -40.5),
+            inventory,
+            health: 7.0,
+            gamemode: GameMode::Creative as u8,
+        };
+        store.save(&uuid, &saved).await.unwrap();
+
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_compression_threshold(None)
+            .with_view_distance(1)
+            .with_world_directory(Some(directory.clone()));
+        let mut client = connect(config).await;
+        login(&mut client.connection, uuid).await;
+        client
+            .connection
+            .write_packet(&AcknowledgeFinishConfigurationPacket)
+            .await
+            .unwrap();
+        loop {
+            let (packet_id, data) = client.connection.read_packet().await.unwrap();
+            let mut cursor = std::io::Cursor::new(data);
+            if packet_id.0 == LoginPlayPacket::ID {
+                let login_play = LoginPlayPacket::read(&mut cursor).unwrap();
+                assert_eq!(login_play.game_mode, GameMode::Creative as u8);
+            } else if packet_id.0 == SetHealthPacket::ID {
+                assert_eq!(SetHealthPacket::read(&mut cursor).unwrap().health, 7.0);
+            } else if packet_id.0 == SetContainerContentPacket::ID {
+                let content = SetContainerContentPacket::read(&mut cursor).unwrap();
+                assert_eq!(content.slots[36], Slot::new(1, 5));
+            } else if packet_id.0 == PlayerPositionPacket::ID {
+                let position = PlayerPositionPacket::read(&mut cursor).unwrap();
+                assert_eq!((position.x, position.y, position.z), saved.position);
+                client
+                    .connection
+                    .write_packet(&ConfirmTeleportPacket {
+                        teleport_id: position.teleport_id,
+                    })
+                    .await
+                    .unwrap();
+                break;
+            }
+        }
+
+        // The chunks sent are the ones around the saved position, in chunk
+        // (6, -3), rather than around the world spawn
+        let mut chunks = Vec::new();
+        while chunks.len() < 9 {
+            let (packet_id, data) = client.connection.read_packet().await.unwrap();
+            let mut cursor = std::io::Cursor::new(data);
+            if packet_id.0 == SetCenterChunkPacket::ID {
+                let center = SetCenterChunkPacket::read(&mut cursor).unwrap();
+                assert_eq!((center.chunk_x.0, center.chunk_z.0), (6, -3));
+            } else if packet_id.0 == ChunkDataPacket::ID {
+                let chunk = ChunkDataPacket::read(&mut cursor).unwrap();
+                chunks.push((chunk.chunk_x, chunk.chunk_z));
+            }
+        }
+        chunks.sort_unstable();
+        let expected: Vec<_> = (5..=7)
+            .flat_map(|x| (-4..=-2).map(move |z| (x, z)))
+            .collect();
+        assert_eq!(chunks, expected);
+
+        // Leaving saves the player again
+        tokio::fs::remove_dir_all(&directory).await.unwrap();
+        client.connection.close().await.unwrap();
+        client.handler.await.unwrap().ok();
+        let data = store.load(&uuid).await.unwrap();
+        assert_eq!((data.position, data.health), (saved.position, 7.0));
+        assert_eq!(data.inventory[36], Slot::new(1, 5));
+        tokio::fs::remove_dir_all(&directory).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_server_closing_saves_players() {
+        let directory =
+            std::env::temp_dir().join(format!("obsidium-{}-handler-closing", std::process::id()));
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_compression_threshold(None)
+            .with_view_distance(1)
+            .with_world_directory(Some(directory.clone()));
+        let mut client = connect(config).await;
+        let uuid = McUuid::new_v4();
+        enter_play(&mut client, uuid).await;
+
+        client.context.close_connections();
+        let (mut packet_id, mut data) = client.connection.read_packet().await.unwrap();
+        while packet_id.0 != PlayDisconnectPacket::ID {
+            (packet_id, data) = client.connection.read_packet().await.unwrap();
+        }
+        let disconnect = PlayDisconnectPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        assert_eq!(disconnect.reason, JsonTextComponent::text(SERVER_CLOSED));
+        assert!(client.handler.await.unwrap().is_ok());
+
+        let saved = directory.join("playerdata").join(format!("{}.dat", uuid));
+        assert!(saved.exists());
+        tokio::fs::remove_dir_all(&directory).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_impossible_movement_is_corrected() {
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_compression_threshold(None)
+            .with_view_distance(1);
+        let mut client = connect(config).await;
+        enter_play(&mut client, McUuid::new_v4()).await;
+
+        client
+            .connection
+            .write_packet(&SetPlayerPositionAndRotationPacket {
+                x: 500.5,
+                y: 64.0,
+                z: 0.5,
+                yaw: 90.0,
+                pitch: 0.0,
+                on_ground: true,
+                pushing_against_wall: false,
+            })
+            .await
+            .unwrap();
+
+        let (packet_id, data) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, PlayerPositionPacket::ID);
+        let correction = PlayerPositionPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        assert_eq!((correction.x, correction.y, correction.z), (0.5, 64.0, 0.5));
+        let (_, state) = client.context.entities.get_all().await.remove(0);
+        assert_eq!((state.x, state.z), (0.5, 0.5));
+    }
+
+    #[tokio::test]
+    async fn test_client_brand() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let _client = TcpStream::connect(addr).await.unwrap();
+        let (stream, peer_addr) = listener.accept().await.unwrap();
+        let mut handler = ConnectionHandler::new(
+            Connection::new(stream, peer_addr),
+            ServerContext::for_tests(ServerConfig::new().with_online_mode(false)),
+        );
+
+        let mut data = Vec::new();
+        McString::from("vanilla").write(&mut data).unwrap();
+        let brand = ConfigurationCustomPayloadPacket(ServerboundCustomPayloadPacket {
+            channel: McIdentifier::minecraft("brand"),
+            data,
+        });
+        let mut buffer = Vec::new();
+        brand.write(&mut buffer).unwrap();
+
+        handler.connection.set_state(ConnectionState::Configuration);
+        handler
+            .handle_configuration_packet(VarInt(ConfigurationCustomPayloadPacket::ID), &buffer)
+            .await
+            .unwrap();
+        assert_eq!(handler.client_state().brand.as_deref(), Some("vanilla"));
+        assert!(handler.client_state().settings.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_keep_alive_timeout() {
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_compression_threshold(None)
+            .with_view_distance(0)
+            .with_keep_alive(
+                std::time::Duration::from_millis(10),
+                std::time::Duration::from_millis(40),
+            );
+        let mut client = connect(config).await;
+        login(&mut client.connection, McUuid::new_v4()).await;
+        client
+            .connection
+            .write_packet(&AcknowledgeFinishConfigurationPacket)
+            .await
+            .unwrap();
+
+        // Confirm the spawn teleport, then answer a single keep alive
+        let mut answered = false;
+        loop {
+            let (packet_id, data) = match client.connection.read_packet().await {
+                Ok(packet) => packet,
+                Err(_) => break,
+            };
+            let mut cursor = std::io::Cursor::new(data);
+            if packet_id.0 == PlayerPositionPacket::ID {
+                let position = PlayerPositionPacket::read(&mut cursor).unwrap();
+                client
+                    .connection
+                    .write_packet(&ConfirmTeleportPacket {
+                        teleport_id: position.teleport_id,
+                    })
+                    .await
+                    .unwrap();
+            } else if packet_id.0 == KeepAlivePacket::ID && !answered {
+                let keep_alive = KeepAlivePacket::read(&mut cursor).unwrap();
+                client
+                    .connection
+                    .write_packet(&ServerboundKeepAlivePacket {
+                        keep_alive_id: keep_alive.keep_alive_id,
+                    })
+                    .await
+                    .unwrap();
+                answered = true;
+            }
+        }
+
+        assert!(answered);
+        let result = client.handler.await.unwrap();
+        assert!(
+            matches!(result, Err(ServerError::Protocol(message)) if message == "Keep alive timeout")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_queued_teleport() {
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_compression_threshold(None)
+            .with_view_distance(1);
+        let mut client = connect(config).await;
+        let uuid = McUuid::new_v4();
+        enter_play(&mut client, uuid).await;
+
+        let queued = PlayerPositionPacket::absolute(99, 100.5, 70.0, 0.5, 0.0, 0.0);
+        client
+            .context
+            .player_list
+            .send_to(&uuid, &queued)
+            .await
+            .unwrap();
+
+        // The spawn teleport had ID 0
+        let (packet_id, data) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, PlayerPositionPacket::ID);
+        let position = PlayerPositionPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        assert_eq!(position.teleport_id.0, 1);
+        assert_eq!((position.x, position.y, position.z), (100.5, 70.0, 0.5));
+
+        let (packet_id, data) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, SetCenterChunkPacket::ID);
+        let center = SetCenterChunkPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        assert_eq!((center.chunk_x.0, center.chunk_z.0), (6, 0));
+    }
+
+    #[tokio::test]
+    async fn test_teleport_confirm_timeout() {
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_compression_threshold(None)
+            .with_teleport_confirm_timeout(std::time::Duration::from_millis(50));
+        let mut client = connect(config).await;
+        login(&mut client.connection, McUuid::new_v4()).await;
+
+        client
+            .connection
+            .write_packet(&AcknowledgeFinishConfigurationPacket)
+            .await
+            .unwrap();
+
+        let result = client.handler.await.unwrap();
+        assert!(
+            matches!(result, Err(ServerError::Protocol(message)) if message == "Teleport confirm timeout")
+        );
+
+        // The client is kicked with the reason after Login (play) and the teleport
+        let (mut packet_id, mut data) = client.connection.read_packet().await.unwrap();
+        while packet_id.0 != PlayDisconnectPacket::ID {
+            (packet_id, data) = client.connection.read_packet().await.unwrap();
+        }
+        let disconnect = PlayDisconnectPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        assert_eq!(
+            disconnect.reason,
+            JsonTextComponent::text("Protocol error: Teleport confirm timeout")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_play_packet_before_play_state() {
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_compression_threshold(None);
+        let mut client = connect(config).await;
+        login(&mut client.connection, McUuid::new_v4()).await;
+
+        // Set Player Position (play state only), without any fields
+        client.connection.write_bytes(&[0x01, 0x1D]).await.unwrap();
+
+        let result = client.handler.await.unwrap();
+        assert!(matches!(result, Err(ServerError::Protocol(_))));
+    }
+}
@@ -0,0 +1,227 @@
+//! Block placement
+//!
+//! Placing the held block against the block a player clicks, or opening
+//! the chest they clicked.
+
+use super::ConnectionHandler;
+use crate::error::Result;
+use crate::game::player::GameMode;
+use crate::game::world::BlockState;
+use crate::protocol::VarInt;
+use crate::protocol::packets::play::{
+    AcknowledgeBlockChangePacket, BlockChangePacket, OpenSignEditorPacket, UseItemOnPacket,
+};
+use crate::server::entities::{EntityState, PLAYER_ENTITY_TYPE};
+use crate::server::inventory::chest::CHEST_BLOCK;
+use crate::server::placement::{Placement, block_at, place_block};
+
+impl ConnectionHandler {
+    /// Place the held block against the clicked block
+    ///
+    /// If the placement is blocked, or the player cannot place the held item,
+    /// the client is sent the block that is really there to undo its
+    /// prediction.
+    pub(super) async fn handle_use_item_on(&mut self, use_item: UseItemOnPacket) -> Result<()> {
+        let clicked = block_at(&*self.context.world.read().await, use_item.position)?;
+        let chest = self.context.data.blocks().default_state_of(CHEST_BLOCK);
+        if Some(i32::from(clicked.id())) == chest {
+            self.open_chest(use_item.position).await?;
+            return self
+                .connection
+                .write_packet(&AcknowledgeBlockChangePacket {
+                    sequence: use_item.sequence,
+                })
+                .await;
+        }
+
+        let game_mode = self.game_mode().await;
+        let held_item = self.inventory.lock().await.held_item(use_item.hand);
+        let block_state = match held_item.item_id {
+            Some(item_id) if matches!(game_mode, GameMode::Survival | GameMode::Creative) => self
+                .context
+                .data
+                .placed_block_state(item_id)
+                .and_then(|state| u16::try_from(state).ok())
+                .map(BlockState)
+                .filter(|state| !state.is_air()),
+            _ => None,
+        };
+
+        let entities: Vec<EntityState> = self
+            .context
+            .entities
+            .get_all()
+            .await
+            .into_iter()
+            .map(|(_, state)| state)
+            .filter(|state| state.entity_type == PLAYER_ENTITY_TYPE)
+            .collect();
+        let placement = {
+            let mut world = self.context.world.write().await;
+            match block_state {
+                Some(block_state) => place_block(
+                    &mut world,
+                    &entities,
+                    use_item.position,
+                    use_item.face,
+                    block_state,
+                )?,
+                None => {
+                    let target = use_item.face.offset(use_item.position);
+                    Placement::Blocked(target, block_at(&world, target)?)
+                }
+            }
+        };
+
+        let mut edited_sign = None;
+        match placement {
+            Placement::Placed(position) => {
+                if block_state.map(|state| i32::from(state.id())) == self.sign_state() {
+                    edited_sign = Some(position);
+                }
+                if game_mode == GameMode::Survival {
+                    self.inventory.lock().await.consume_held_item(use_item.hand);
+                    self.sync_equipment().await?;
+                }
+            }
+            Placement::Blocked(position, block) => {
+                self.connection
+                    .write_packet(&BlockChangePacket {
+                        position,
+                        block_id: VarInt(i32::from(block.id())),
+                    })
+                    .await?;
+            }
+        }
+        self.connection
+            .write_packet(&AcknowledgeBlockChangePacket {
+                sequence: use_item.sequence,
+            })
+            .await?;
+        if let Some(position) = edited_sign {
+            self.connection
+                .write_packet(&OpenSignEditorPacket {
+                    position,
+                    is_front_text: true,
+                })
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{LevelType, ServerConfig};
+    use crate::game::world::generators::ChunkProvider;
+    use crate::game::world::{ChunkData, ChunkPosition};
+    use crate::protocol::packets::Packet;
+    use crate::protocol::packets::play::{BlockFace, Hand};
+    use crate::protocol::types::{McUuid, Position, Slot};
+    use crate::server::handler::tests::{connect, enter_play};
+
+    #[tokio::test]
+    async fn test_place_block_on_the_ground() {
+        let config = ServerConfig::new()
+            .with_level_type(LevelType::Flat)
+            .with_online_mode(false)
+            .with_compression_threshold(None)
+            .with_view_distance(1);
+        let mut client = connect(config).await;
+        let uuid = McUuid::new_v4();
+        enter_play(&mut client, uuid).await;
+        let mut player = client.context.players.get_player(&uuid).await.unwrap();
+        player.set_game_mode(GameMode::Creative);
+        client.context.players.update_player(&uuid, player).await;
+        let stone = client
+            .context
+            .data
+            .items()
+            .id_of("minecraft:stone")
+            .unwrap();
+        {
+            let inventory = client.context.inventories.get(&uuid).await.unwrap();
+            let mut inventory = inventory.lock().await;
+            let slot = inventory.held_slot(Hand::MainHand);
+            inventory
+                .container_mut()
+                .set_slot(slot, Slot::new(stone, 1));
+        }
+
+        // The grass of the flat world tops the terrain at y -61
+        client
+            .connection
+            .write_packet(&UseItemOnPacket {
+                hand: Hand::MainHand,
+                position: Position::new(3, -61, 3),
+                face: BlockFace::Top,
+                cursor_x: 0.5,
+                cursor_y: 1.0,
+                cursor_z: 0.5,
+                inside_block: false,
+                world_border_hit: false,
+                sequence: VarInt(4),
+            })
+            .await
+            .unwrap();
+        let (packet_id, _) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, AcknowledgeBlockChangePacket::ID);
+
+        let placed = Position::new(3, -60, 3);
+        let stone_state = client.context.data.placed_block_state(stone).unwrap();
+        let block = client.context.world.read().await.get_block(placed).unwrap();
+        assert_eq!(block, Some(BlockState(stone_state as u16)));
+        // Chunks sent from now on hold the block
+        let packet = client
+            .context
+            .chunk_provider
+            .provide_chunk(ChunkPosition::new(0, 0));
+        let chunk = ChunkData::from_packet(&packet).unwrap();
+        assert_eq!(chunk.get_block(3, 4, 3), block);
+    }
+
+    #[tokio::test]
+    async fn test_use_item_on_with_empty_hand() {
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_compression_threshold(None)
+            .with_view_distance(1);
+        let mut client = connect(config).await;
+        enter_play(&mut client, McUuid::new_v4()).await;
+
+        client
+            .connection
+            .write_packet(&UseItemOnPacket {
+                hand: Hand::MainHand,
+                position: Position::new(3, 63, 3),
+                face: BlockFace::Top,
+                cursor_x: 0.5,
+                cursor_y: 1.0,
+                cursor_z: 0.5,
+                inside_block: false,
+                world_border_hit: false,
+                sequence: VarInt(5),
+            })
+            .await
+            .unwrap();
+
+        // Nothing is placed, and the client is told the block is still air
+        let (packet_id, data) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, BlockChangePacket::ID);
+        let change = BlockChangePacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        assert_eq!(change.position, Position::new(3, 64, 3));
+        assert_eq!(change.block_id.0, 0);
+        let (packet_id, _) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, AcknowledgeBlockChangePacket::ID);
+        assert!(
+            client
+                .context
+                .world
+                .write()
+                .await
+                .take_mutations()
+                .is_empty()
+        );
+    }
+}
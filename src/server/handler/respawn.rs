@@ -0,0 +1,224 @@
+//! Respawning
+//!
+//! Bringing dead players back at the world spawn when they ask to.
+
+use super::ConnectionHandler;
+use crate::error::Result;
+use crate::game::PlayerAttributes;
+use crate::game::game_rules;
+use crate::network::ViewDistanceTracker;
+use crate::protocol::packets::play::{
+    ClientCommandAction, ClientCommandPacket, LoginPlayPacket, RespawnPacket,
+};
+use crate::server::health::HealthManager;
+use crate::server::inventory::{Container, InventoryManager};
+
+impl ConnectionHandler {
+    /// Handle a client command, respawning a dead player that asks to
+    pub(super) async fn handle_client_command(
+        &mut self,
+        command: ClientCommandPacket,
+    ) -> Result<()> {
+        match command.action {
+            ClientCommandAction::PerformRespawn if self.health.is_dead() => self.respawn().await,
+            ClientCommandAction::PerformRespawn => Ok(()),
+            ClientCommandAction::RequestStats => {
+                tracing::debug!("Statistics are not supported yet");
+                Ok(())
+            }
+        }
+    }
+
+    /// Bring a dead player back at the world spawn, with full health and an
+    /// empty inventory, unless the keepInventory game rule is on
+    ///
+    /// The client starts over in a fresh copy of the world, so its
+    /// attributes, inventory and chunks are sent again.
+    async fn respawn(&mut self) -> Result<()> {
+        let (Some(uuid), Some(entity_id)) = (self.player_uuid, self.entity_id) else {
+            return Ok(());
+        };
+        let Some(player) = self.context.players.get_player(&uuid).await else {
+            return Ok(());
+        };
+        self.release_window().await;
+        self.health = HealthManager::new();
+        self.attributes = PlayerAttributes::new();
+        let keep_inventory = self
+            .context
+            .level
+            .read()
+            .await
+            .game_rules
+            .get_bool(game_rules::KEEP_INVENTORY)
+            .unwrap_or_default();
+        if !keep_inventory {
+            *self.inventory.lock().await = InventoryManager::new(Container::player_inventory());
+        }
+        self.view = ViewDistanceTracker::new();
+
+        let login = LoginPlayPacket::from_server_config(&self.context.config, entity_id);
+        let mut respawn = RespawnPacket::from_login(&login, 0);
+        respawn.game_mode = player.game_mode as u8;
+        if let Some(position) = self.death_location.take() {
+            respawn = respawn.with_death_location(login.dimension_name, position);
+        }
+        self.connection.write_packet(&respawn).await?;
+        self.connection
+            .write_packet(&self.attributes.spawn_packet(entity_id))
+            .await?;
+        self.connection.write_packet(&self.health.packet()).await?;
+        self.send_abilities().await?;
+        let content = self.inventory.lock().await.container_mut().content_packet();
+        self.connection.write_packet(&content).await?;
+
+        let spawn = self.context.world.read().await.spawn_position();
+        let (x, y, z) = (
+            f64::from(spawn.x) + 0.5,
+            f64::from(spawn.y),
+            f64::from(spawn.z) + 0.5,
+        );
+        self.context
+            .entities
+            .update_position(entity_id, x, y, z)
+            .await;
+        self.teleport(x, y, z).await?;
+        self.send_spawn_chunks(x, z).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ServerConfig;
+    use crate::game::game_rules::GameRuleValue;
+    use crate::protocol::TextComponent;
+    use crate::protocol::packets::Packet;
+    use crate::protocol::packets::play::{
+        HurtAnimationPacket, PlayerAbilitiesPacket, PlayerDeathPacket, PlayerPositionPacket,
+        SetContainerContentPacket, SetHealthPacket, SetPlayerPositionPacket,
+        UpdateAttributesPacket,
+    };
+    use crate::protocol::types::{McUuid, Position, Slot};
+    use crate::server::handler::tests::{TestClient, connect, enter_play};
+
+    /// Give a player an item in the first hotbar slot
+    async fn give_item(client: &TestClient, uuid: McUuid, item: Slot) {
+        let inventory = client.context.inventories.get(&uuid).await.unwrap();
+        inventory.lock().await.container_mut().set_slot(36, item);
+    }
+
+    /// Drop the player out of the world and return their death message
+    async fn fall_to_death(client: &mut TestClient) -> PlayerDeathPacket {
+        for y in [-30.0, -130.0] {
+            client
+                .connection
+                .write_packet(&SetPlayerPositionPacket {
+                    x: 0.5,
+                    y,
+                    z: 0.5,
+                    on_ground: false,
+                    pushing_against_wall: false,
+                })
+                .await
+                .unwrap();
+        }
+
+        // Falling out of the world hurts every half second until the player
+        // dies
+        let mut health = None;
+        let death = loop {
+            let (packet_id, data) = client.connection.read_packet().await.unwrap();
+            let mut data = std::io::Cursor::new(data);
+            match packet_id.0 {
+                SetHealthPacket::ID => health = Some(SetHealthPacket::read(&mut data).unwrap()),
+                HurtAnimationPacket::ID => {}
+                PlayerDeathPacket::ID => break PlayerDeathPacket::read(&mut data).unwrap(),
+                id => unreachable!("unexpected packet 0x{:02X}", id),
+            }
+        };
+        assert_eq!(health.unwrap().health, 0.0);
+        death
+    }
+
+    #[tokio::test]
+    async fn test_death_and_respawn() {
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_compression_threshold(None)
+            .with_view_distance(1);
+        let mut client = connect(config).await;
+        let uuid = McUuid::new_v4();
+        enter_play(&mut client, uuid).await;
+        give_item(&client, uuid, Slot::new(1, 5)).await;
+
+        let death = fall_to_death(&mut client).await;
+        assert_eq!(
+            death.message,
+            TextComponent::text("Steve fell out of the world")
+        );
+
+        client
+            .connection
+            .write_packet(&ClientCommandPacket {
+                action: ClientCommandAction::PerformRespawn,
+            })
+            .await
+            .unwrap();
+        let (packet_id, data) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, RespawnPacket::ID);
+        let respawn = RespawnPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        assert_eq!(
+            respawn.death_location.map(|(_, position)| position),
+            Some(Position::new(0, -130, 0))
+        );
+        let (packet_id, _) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, UpdateAttributesPacket::ID);
+        let (packet_id, data) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, SetHealthPacket::ID);
+        let health = SetHealthPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        assert_eq!(health.health, 20.0);
+        let (packet_id, _) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, PlayerAbilitiesPacket::ID);
+        let (packet_id, data) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, SetContainerContentPacket::ID);
+        let content = SetContainerContentPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        assert!(content.slots.iter().all(Slot::is_empty));
+        let (packet_id, data) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, PlayerPositionPacket::ID);
+        let position = PlayerPositionPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        assert_eq!((position.x, position.y, position.z), (0.5, 64.0, 0.5));
+    }
+
+    #[tokio::test]
+    async fn test_keep_inventory() {
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_compression_threshold(None)
+            .with_view_distance(1);
+        let mut client = connect(config).await;
+        let uuid = McUuid::new_v4();
+        enter_play(&mut client, uuid).await;
+        give_item(&client, uuid, Slot::new(1, 5)).await;
+        client
+            .context
+            .set_game_rule(game_rules::KEEP_INVENTORY, GameRuleValue::Bool(true))
+            .await
+            .unwrap();
+
+        fall_to_death(&mut client).await;
+        client
+            .connection
+            .write_packet(&ClientCommandPacket {
+                action: ClientCommandAction::PerformRespawn,
+            })
+            .await
+            .unwrap();
+        let (mut packet_id, mut data) = client.connection.read_packet().await.unwrap();
+        while packet_id.0 != SetContainerContentPacket::ID {
+            (packet_id, data) = client.connection.read_packet().await.unwrap();
+        }
+        let content = SetContainerContentPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        assert_eq!(content.slots[36], Slot::new(1, 5));
+    }
+}
@@ -0,0 +1,101 @@
+//! Sign editing
+//!
+//! Storing the text players write on signs and showing it to everyone.
+
+use super::ConnectionHandler;
+use crate::error::Result;
+use crate::protocol::packets::play::SignUpdatePacket;
+use crate::server::placement::block_at;
+use crate::server::signs::{SIGN_BLOCK, SignText};
+
+impl ConnectionHandler {
+    /// Block state of the sign players place
+    pub(super) fn sign_state(&self) -> Option<i32> {
+        self.context.data.blocks().default_state_of(SIGN_BLOCK)
+    }
+
+    /// Store the text the player wrote on a sign and show it to everyone
+    ///
+    /// Updates with overlong lines, or for a block that is not a sign, are
+    /// ignored.
+    pub(super) async fn handle_sign_update(&mut self, update: SignUpdatePacket) -> Result<()> {
+        if !update.is_valid() {
+            tracing::warn!("Ignoring sign update with overlong lines");
+            return Ok(());
+        }
+        let block = block_at(&*self.context.world.read().await, update.position)?;
+        if Some(i32::from(block.id())) != self.sign_state() {
+            tracing::debug!("Ignoring sign update for a block that is not a sign");
+            return Ok(());
+        }
+
+        let text = SignText {
+            lines: update.lines.map(|line| line.0),
+        };
+        let sign = self
+            .context
+            .signs
+            .set_text(update.position, update.is_front_text, text)
+            .await;
+        self.context
+            .player_list
+            .broadcast(&sign.data_packet(update.position))
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ServerConfig;
+    use crate::game::world::BlockState;
+    use crate::protocol::packets::Packet;
+    use crate::protocol::packets::play::BlockEntityDataPacket;
+    use crate::protocol::types::{McUuid, Position};
+    use crate::server::handler::tests::{connect, enter_play};
+
+    #[tokio::test]
+    async fn test_sign_update() {
+        let config = ServerConfig::new()
+            .with_online_mode(false)
+            .with_compression_threshold(None)
+            .with_view_distance(1);
+        let mut client = connect(config).await;
+        enter_play(&mut client, McUuid::new_v4()).await;
+        let sign = Position::new(2, 64, 2);
+        let sign_state = client
+            .context
+            .data
+            .blocks()
+            .default_state_of(SIGN_BLOCK)
+            .unwrap();
+        client
+            .context
+            .world
+            .write()
+            .await
+            .set_block(sign, BlockState(sign_state as u16))
+            .unwrap();
+
+        let update = |position, first_line: String| SignUpdatePacket {
+            position,
+            is_front_text: true,
+            lines: [first_line.into(), "".into(), "".into(), "".into()],
+        };
+        // Overlong lines and blocks other than signs are ignored
+        for ignored in [
+            update(sign, "a".repeat(SignUpdatePacket::MAX_LINE_LENGTH + 1)),
+            update(Position::new(0, 63, 0), "Hello".into()),
+            update(sign, "Hello".into()),
+        ] {
+            client.connection.write_packet(&ignored).await.unwrap();
+        }
+
+        let (packet_id, data) = client.connection.read_packet().await.unwrap();
+        assert_eq!(packet_id.0, BlockEntityDataPacket::ID);
+        let packet = BlockEntityDataPacket::read(&mut std::io::Cursor::new(data)).unwrap();
+        assert_eq!(packet.position, sign);
+        assert_eq!(client.context.signs.get(sign).await.front.lines[0], "Hello");
+    }
+}
@@ -0,0 +1,326 @@
+//! Player health and food
+//!
+//! The [`HealthManager`] follows vanilla survival rules: a well-fed player
+//! slowly regains health, a starving one slowly loses it, and a player who
+//! was just hurt is briefly protected from more damage.
+
+use crate::protocol::packets::play::SetHealthPacket;
+use crate::protocol::types::VarInt;
+
+/// Highest food level
+pub const MAX_FOOD: i32 = 20;
+
+/// Lowest food level at which health regenerates
+pub const REGENERATION_FOOD: i32 = 18;
+
+/// Ticks between each point of health regained or lost to starvation
+pub const FOOD_TICKS: u32 = 80;
+
+/// Ticks a player is protected from damage after being hurt
+pub const INVULNERABILITY_TICKS: u32 = 20;
+
+/// Height below which players take damage from falling out of the world
+pub const VOID_DAMAGE_Y: f64 = -128.0;
+
+/// Damage taken each tick below [`VOID_DAMAGE_Y`]
+pub const VOID_DAMAGE: f32 = 4.0;
+
+/// Exhaustion that costs a point of saturation, or of food once saturation
+/// runs out
+const EXHAUSTION_PER_FOOD: f32 = 4.0;
+
+/// Exhaustion added by regaining a point of health
+const REGENERATION_EXHAUSTION: f32 = 6.0;
+
+/// What hurt a player
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DamageType {
+    /// Damage without a more specific cause
+    Generic,
+    /// Having no food
+    Starve,
+    /// Falling
+    Fall,
+    /// Falling out of the world
+    OutOfWorld,
+}
+
+impl DamageType {
+    /// Whether the damage applies even right after the player was hurt
+    pub fn bypasses_invulnerability(self) -> bool {
+        self == DamageType::OutOfWorld
+    }
+
+    /// Message shown when a player dies of this damage
+    pub fn death_message(self, username: &str) -> String {
+        match self {
+            DamageType::Generic => format!("{} died", username),
+            DamageType::Starve => format!("{} starved to death", username),
+            DamageType::Fall => format!("{} hit the ground too hard", username),
+            DamageType::OutOfWorld => format!("{} fell out of the world", username),
+        }
+    }
+}
+
+/// What changed the player's health during a tick
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HealthChange {
+    /// The player regained health
+    Healed,
+    /// The player was hurt
+    Damaged(DamageType),
+}
+
+/// Health, food and damage protection of a player
+#[derive(Debug, Clone, PartialEq)]
+pub struct HealthManager {
+    /// Current health
+    health: f32,
+    /// Health the player regenerates up to
+    max_health: f32,
+    /// Food level, from 0 to 20
+    food: i32,
+    /// Food saturation, used up before the food level
+    food_saturation: f32,
+    /// Exhaustion that has not cost saturation or food yet
+    exhaustion: f32,
+    /// Ticks since health last regenerated or was lost to starvation
+    food_timer: u32,
+    /// Ticks left of protection from damage
+    invulnerable_ticks: u32,
+    /// Damage taken when the protection started
+    last_damage: f32,
+}
+
+impl HealthManager {
+    /// Create the health of a new player: full health and food, and 5.0
+    /// saturation
+    pub fn new() -> Self {
+        Self {
+            health: 20.0,
+            max_health: 20.0,
+            food: MAX_FOOD,
+            food_saturation: 5.0,
+            exhaustion: 0.0,
+            food_timer: 0,
+            invulnerable_ticks: 0,
+            last_damage: 0.0,
+        }
+    }
+
+    /// Current health
+    pub fn health(&self) -> f32 {
+        self.health
+    }
+
+    /// Health the player regenerates up to
+    pub fn max_health(&self) -> f32 {
+        self.max_health
+    }
+
+    /// Food level
+    pub fn food(&self) -> i32 {
+        self.food
+    }
+
+    /// Food saturation
+    pub fn food_saturation(&self) -> f32 {
+        self.food_saturation
+    }
+
+    /// Whether the player has no health left
+    pub fn is_dead(&self) -> bool {
+        self.health <= 0.0
+    }
+
+    /// Set the health, limited to between 0 and the maximum
+    pub fn set_health(&mut self, health: f32) {
+        self.health = health.clamp(0.0, self.max_health);
+    }
+
+    /// Set the maximum health, lowering the health to it if needed
+    pub fn set_max_health(&mut self, max_health: f32) {
+        self.max_health = max_health.max(1.0);
+        self.health = self.health.min(self.max_health);
+    }
+
+    /// Set the food level, limited to between 0 and 20
+    pub fn set_food(&mut self, food: i32) {
+        self.food = food.clamp(0, MAX_FOOD);
+        self.food_saturation = self.food_saturation.min(self.food as f32);
+    }
+
+    /// Regain health, up to the maximum
+    pub fn heal(&mut self, amount: f32) {
+        if !self.is_dead() {
+            self.health = (self.health + amount).min(self.max_health);
+        }
+    }
+
+    /// Make the player hungrier, using up saturation and then food
+    pub fn add_exhaustion(&mut self, amount: f32) {
+        self.exhaustion += amount;
+        while self.exhaustion >= EXHAUSTION_PER_FOOD {
+            self.exhaustion -= EXHAUSTION_PER_FOOD;
+            if self.food_saturation > 0.0 {
+                self.food_saturation = (self.food_saturation - 1.0).max(0.0);
+            } else {
+                self.food = (self.food - 1).max(0);
+            }
+        }
+    }
+
+    /// Hurt the player, returning the damage actually taken
+    ///
+    /// For a while after being hurt, the player only takes the part of new
+    /// damage that exceeds what hurt them, unless the damage bypasses this.
+    pub fn take_damage(&mut self, amount: f32, cause: DamageType) -> f32 {
+        if self.is_dead() || amount <= 0.0 {
+            return 0.0;
+        }
+        let damage = if self.invulnerable_ticks > INVULNERABILITY_TICKS / 2
+            && !cause.bypasses_invulnerability()
+        {
+            if amount <= self.last_damage {
+                return 0.0;
+            }
+            let excess = amount - self.last_damage;
+            self.last_damage = amount;
+            excess
+        } else {
+            self.last_damage = amount;
+            self.invulnerable_ticks = INVULNERABILITY_TICKS;
+            amount
+        };
+        let taken = damage.min(self.health);
+        self.health -= taken;
+        taken
+    }
+
+    /// Advance by a tick, regenerating or starving every 80 ticks
+    ///
+    /// Health only regenerates with `natural_regeneration`, the game rule of
+    /// that name. Starvation stops at half a heart, as on normal difficulty.
+    pub fn tick(&mut self, natural_regeneration: bool) -> Option<HealthChange> {
+        self.invulnerable_ticks = self.invulnerable_ticks.saturating_sub(1);
+        if self.is_dead() {
+            return None;
+        }
+
+        let regenerating =
+            natural_regeneration && self.food >= REGENERATION_FOOD && self.health < self.max_health;
+        let starving = self.food == 0 && self.health > 1.0;
+        if !regenerating && !starving {
+            self.food_timer = 0;
+            return None;
+        }
+        self.food_timer += 1;
+        if self.food_timer < FOOD_TICKS {
+            return None;
+        }
+        self.food_timer = 0;
+
+        if regenerating {
+            self.heal(1.0);
+            self.add_exhaustion(REGENERATION_EXHAUSTION);
+            Some(HealthChange::Healed)
+        } else if self.take_damage(1.0, DamageType::Starve) > 0.0 {
+            Some(HealthChange::Damaged(DamageType::Starve))
+        } else {
+            None
+        }
+    }
+
+    /// Build the packet showing the current health and food to the player
+    pub fn packet(&self) -> SetHealthPacket {
+        SetHealthPacket {
+            health: self.health,
+            food: VarInt(self.food),
+            saturation: self.food_saturation,
+        }
+    }
+}
+
+impl Default for HealthManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tick_food_timer(health: &mut HealthManager) -> Option<HealthChange> {
+        (0..FOOD_TICKS).filter_map(|_| health.tick(true)).last()
+    }
+
+    #[test]
+    fn test_regeneration() {
+        let mut health = HealthManager::new();
+        assert_eq!(tick_food_timer(&mut health), None);
+
+        health.take_damage(5.0, DamageType::Generic);
+        assert_eq!(tick_food_timer(&mut health), Some(HealthChange::Healed));
+        assert_eq!(health.health(), 16.0);
+        // Regenerating costs saturation before food
+        assert_eq!(health.food_saturation(), 4.0);
+        assert_eq!(health.food(), MAX_FOOD);
+
+        health.set_food(REGENERATION_FOOD - 1);
+        assert_eq!(tick_food_timer(&mut health), None);
+        assert_eq!(health.health(), 16.0);
+    }
+
+    #[test]
+    fn test_regeneration_can_be_turned_off() {
+        let mut health = HealthManager::new();
+        health.take_damage(5.0, DamageType::Generic);
+        for _ in 0..FOOD_TICKS * 2 {
+            assert_eq!(health.tick(false), None);
+        }
+        assert_eq!(health.health(), 15.0);
+
+        // Starving still hurts
+        health.set_food(0);
+        let starved = (0..FOOD_TICKS).filter_map(|_| health.tick(false)).last();
+        assert_eq!(starved, Some(HealthChange::Damaged(DamageType::Starve)));
+        assert_eq!(health.health(), 14.0);
+    }
+
+    #[test]
+    fn test_starvation() {
+        let mut health = HealthManager::new();
+        health.set_food(0);
+        assert_eq!(
+            tick_food_timer(&mut health),
+            Some(HealthChange::Damaged(DamageType::Starve))
+        );
+        assert_eq!(health.health(), 19.0);
+
+        health.take_damage(18.0, DamageType::OutOfWorld);
+        assert_eq!(health.health(), 1.0);
+        assert_eq!(tick_food_timer(&mut health), None);
+        assert!(!health.is_dead());
+    }
+
+    #[test]
+    fn test_invulnerability() {
+        let mut health = HealthManager::new();
+        assert_eq!(health.take_damage(4.0, DamageType::Generic), 4.0);
+        // Weaker hits during the protection do nothing, stronger ones only
+        // deal the difference
+        assert_eq!(health.take_damage(3.0, DamageType::Fall), 0.0);
+        assert_eq!(health.take_damage(6.0, DamageType::Generic), 2.0);
+        assert_eq!(health.take_damage(1.0, DamageType::OutOfWorld), 1.0);
+        assert_eq!(health.health(), 13.0);
+
+        for _ in 0..INVULNERABILITY_TICKS / 2 {
+            health.tick(true);
+        }
+        assert_eq!(health.take_damage(1.0, DamageType::Generic), 1.0);
+        assert_eq!(health.take_damage(20.0, DamageType::Generic), 12.0);
+        assert!(health.is_dead());
+        assert_eq!(health.packet().health, 0.0);
+    }
+}
@@ -0,0 +1,168 @@
+//! Chests
+//!
+//! The server keeps the contents of every chest a player has opened, and
+//! which players have it open so their windows can be closed when it is
+//! broken. An open chest is shown as a window of the chest's slots followed
+//! by the player's main inventory and hotbar.
+
+use crate::protocol::packets::play::GuiType;
+use crate::protocol::types::{McUuid, Position, Slot};
+use crate::server::inventory::Container;
+use std::collections::HashMap;
+use std::ops::Range;
+use tokio::sync::RwLock;
+
+/// Identifier of the chest block
+pub const CHEST_BLOCK: &str = "minecraft:chest";
+
+/// Number of slots in a single chest
+pub const CHEST_SIZE: usize = 27;
+
+/// Window type of a single chest
+pub const CHEST_GUI: GuiType = GuiType::Generic9x3;
+
+/// Player inventory slots shown below the container in a window: the main
+/// inventory followed by the hotbar
+const PLAYER_SLOTS: Range<usize> = 9..45;
+
+/// Contents of the chests in the world, by position
+#[derive(Debug, Default)]
+pub struct ChestStore {
+    /// Slots of each chest
+    chests: RwLock<HashMap<Position, Vec<Slot>>>,
+    /// Players with each chest open, and the ID of their window
+    viewers: RwLock<HashMap<Position, HashMap<McUuid, i32>>>,
+}
+
+impl ChestStore {
+    /// Create a store without any chests
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the contents of a chest, which are empty until first changed
+    pub async fn contents(&self, position: Position) -> Vec<Slot> {
+        self.chests
+            .read()
+            .await
+            .get(&position)
+            .cloned()
+            .unwrap_or_else(|| vec![Slot::empty(); CHEST_SIZE])
+    }
+
+    /// Replace the contents of a chest
+    pub async fn set_contents(&self, position: Position, slots: Vec<Slot>) {
+        self.chests.write().await.insert(position, slots);
+    }
+
+    /// Forget a chest, such as when it is broken, returning its contents
+    pub async fn remove(&self, position: Position) -> Option<Vec<Slot>> {
+        self.chests.write().await.remove(&position)
+    }
+
+    /// Record that a player opened a chest in a window
+    pub async fn open(&self, position: Position, viewer: McUuid, window_id: i32) {
+        self.viewers
+            .write()
+            .await
+            .entry(position)
+            .or_default()
+            .insert(viewer, window_id);
+    }
+
+    /// Record that a player closed a chest, returning whether they still
+    /// had it open in the given window
+    ///
+    /// Players whose window was closed by [`ChestStore::take_viewers`] no
+    /// longer have it open, so their contents must not be stored.
+    pub async fn close(&self, position: Position, viewer: &McUuid, window_id: i32) -> bool {
+        let mut viewers = self.viewers.write().await;
+        let Some(chest_viewers) = viewers.get_mut(&position) else {
+            return false;
+        };
+        if chest_viewers.get(viewer) != Some(&window_id) {
+            return false;
+        }
+        chest_viewers.remove(viewer);
+        if chest_viewers.is_empty() {
+            viewers.remove(&position);
+        }
+        true
+    }
+
+    /// Forget every player with a chest open, returning each of them with
+    /// the ID of their window
+    pub async fn take_viewers(&self, position: Position) -> Vec<(McUuid, i32)> {
+        self.viewers
+            .write()
+            .await
+            .remove(&position)
+            .map(|viewers| viewers.into_iter().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Build the window of an open chest, holding its slots followed by the
+/// player's main inventory and hotbar
+pub fn chest_window(window_id: i32, chest: &[Slot], player_inventory: &Container) -> Container {
+    let mut window = Container::new(window_id, chest.len() + PLAYER_SLOTS.len());
+    let player_slots = PLAYER_SLOTS.filter_map(|index| player_inventory.get_slot(index));
+    for (index, slot) in chest.iter().chain(player_slots).enumerate() {
+        window.set_slot(index, slot.clone());
+    }
+    window
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_chest_store() {
+        let chests = ChestStore::new();
+        let position = Position::new(1, 64, 1);
+        assert_eq!(chests.contents(position).await.len(), CHEST_SIZE);
+
+        let mut slots = vec![Slot::empty(); CHEST_SIZE];
+        slots[0] = Slot::new(1, 16);
+        chests.set_contents(position, slots.clone()).await;
+        assert_eq!(chests.contents(position).await, slots);
+        assert_eq!(chests.remove(position).await, Some(slots));
+        assert!(chests.contents(position).await[0].is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_chest_viewers() {
+        let chests = ChestStore::new();
+        let position = Position::new(0, 64, 0);
+        let (steve, alex) = (McUuid::new_v4(), McUuid::new_v4());
+        chests.open(position, steve, 1).await;
+        chests.open(position, alex, 7).await;
+
+        assert!(!chests.close(position, &steve, 2).await);
+        assert!(chests.close(position, &steve, 1).await);
+        assert!(!chests.close(position, &steve, 1).await);
+
+        assert_eq!(chests.take_viewers(position).await, [(alex, 7)]);
+        assert!(!chests.close(position, &alex, 7).await);
+        assert!(chests.take_viewers(position).await.is_empty());
+    }
+
+    #[test]
+    fn test_chest_window() {
+        let mut chest = vec![Slot::empty(); CHEST_SIZE];
+        chest[3] = Slot::new(1, 1);
+        let mut inventory = Container::player_inventory();
+        // First main inventory slot and first hotbar slot
+        inventory.set_slot(9, Slot::new(2, 2));
+        inventory.set_slot(36, Slot::new(3, 3));
+
+        let mut window = chest_window(1, &chest, &inventory);
+        let content = window.content_packet();
+        assert_eq!(content.window_id.0, 1);
+        assert_eq!(content.slots.len(), CHEST_SIZE + 36);
+        assert_eq!(content.slots[3], Slot::new(1, 1));
+        assert_eq!(content.slots[CHEST_SIZE], Slot::new(2, 2));
+        assert_eq!(content.slots[CHEST_SIZE + 27], Slot::new(3, 3));
+    }
+}
@@ -0,0 +1,609 @@
+//! Inventory clicks
+//!
+//! The client applies a click to its own view of the inventory straight
+//! away and tells the server what it predicts changed. The
+//! [`InventoryManager`] applies the same click to the server's copy and
+//! corrects the client if the two disagree.
+
+use crate::protocol::packets::play::{
+    ClickContainerPacket, EquipmentSlot, Hand, SetContainerContentPacket,
+};
+use crate::protocol::types::Slot;
+use crate::server::inventory::Container;
+use std::ops::Range;
+
+/// Largest stack of any item
+///
+/// Items with smaller stacks, such as tools, are not distinguished yet.
+pub const MAX_STACK_SIZE: u8 = 64;
+
+/// Slot index meaning a click outside the window
+pub const OUTSIDE_WINDOW_SLOT: i16 = -999;
+
+/// Player inventory main slots, above the hotbar
+const MAIN_SLOTS: Range<usize> = 9..36;
+
+/// Player inventory hotbar slots
+const HOTBAR_SLOTS: Range<usize> = 36..45;
+
+/// Player inventory offhand slot
+const OFFHAND_SLOT: usize = 45;
+
+/// Equipment other players see, in the order it is tracked
+const EQUIPMENT_SLOTS: [EquipmentSlot; 6] = [
+    EquipmentSlot::MainHand,
+    EquipmentSlot::OffHand,
+    EquipmentSlot::Feet,
+    EquipmentSlot::Legs,
+    EquipmentSlot::Chest,
+    EquipmentSlot::Head,
+];
+
+/// Swap button used by the offhand key
+const OFFHAND_BUTTON: i8 = 40;
+
+/// How a slot was clicked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Click {
+    /// Left (whole stack) or right (half or one) click
+    Pickup {
+        /// Clicked slot, or `None` outside the window
+        slot: Option<usize>,
+        /// Whether the right button was used
+        right: bool,
+    },
+    /// Shift click, moving the stack to the other part of the inventory
+    QuickMove {
+        /// Clicked slot
+        slot: usize,
+    },
+    /// Number or offhand key, swapping the slot with a hotbar or offhand slot
+    Swap {
+        /// Hovered slot
+        slot: usize,
+        /// Slot bound to the pressed key
+        target: usize,
+    },
+}
+
+/// Applies a player's clicks to their inventory
+#[derive(Debug, Clone)]
+pub struct InventoryManager {
+    /// The player inventory
+    container: Container,
+    /// Selected hotbar slot, from 0 to 8
+    selected_slot: usize,
+    /// Equipment other players were last told about, in
+    /// [`EQUIPMENT_SLOTS`] order
+    sent_equipment: [Slot; EQUIPMENT_SLOTS.len()],
+}
+
+impl InventoryManager {
+    /// Manage a player inventory, with the first hotbar slot selected
+    pub fn new(container: Container) -> Self {
+        Self {
+            container,
+            selected_slot: 0,
+            sent_equipment: Default::default(),
+        }
+    }
+
+    /// The managed inventory
+    pub fn container(&self) -> &Container {
+        &self.container
+    }
+
+    /// The managed inventory, for changes made by the server
+    pub fn container_mut(&mut self) -> &mut Container {
+        &mut self.container
+    }
+
+    /// Selected hotbar slot, from 0 to 8
+    pub fn selected_slot(&self) -> usize {
+        self.selected_slot
+    }
+
+    /// Select a hotbar slot, returning whether it exists
+    pub fn select_slot(&mut self, slot: usize) -> bool {
+        if slot >= HOTBAR_SLOTS.len() {
+            return false;
+        }
+        self.selected_slot = slot;
+        true
+    }
+
+    /// Inventory slot of the item held in a hand
+    pub fn held_slot(&self, hand: Hand) -> usize {
+        match hand {
+            Hand::MainHand => HOTBAR_SLOTS.start + self.selected_slot,
+            Hand::OffHand => OFFHAND_SLOT,
+        }
+    }
+
+    /// Item held in a hand
+    pub fn held_item(&self, hand: Hand) -> Slot {
+        self.container
+            .get_slot(self.held_slot(hand))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Item in an equipment slot
+    ///
+    /// Player inventories hold the helmet, chestplate, leggings and boots in
+    /// slots 5 to 8. Players have no body armor or saddle.
+    pub fn equipment(&self, slot: EquipmentSlot) -> Slot {
+        let index = match slot {
+            EquipmentSlot::MainHand => self.held_slot(Hand::MainHand),
+            EquipmentSlot::OffHand => self.held_slot(Hand::OffHand),
+            EquipmentSlot::Head => 5,
+            EquipmentSlot::Chest => 6,
+            EquipmentSlot::Legs => 7,
+            EquipmentSlot::Feet => 8,
+            EquipmentSlot::Body | EquipmentSlot::Saddle => return Slot::empty(),
+        };
+        self.container.get_slot(index).cloned().unwrap_or_default()
+    }
+
+    /// Get the equipment that changed since the last call, to send to
+    /// other players
+    pub fn take_equipment_changes(&mut self) -> Vec<(EquipmentSlot, Slot)> {
+        let mut changes = Vec::new();
+        for (index, slot) in EQUIPMENT_SLOTS.into_iter().enumerate() {
+            let item = self.equipment(slot);
+            if item != self.sent_equipment[index] {
+                self.sent_equipment[index] = item.clone();
+                changes.push((slot, item));
+            }
+        }
+        changes
+    }
+
+    /// Use up one held item, as the client does when placing it
+    pub fn consume_held_item(&mut self, hand: Hand) {
+        let index = self.held_slot(hand);
+        let mut slot = self.held_item(hand);
+        slot.count = slot.count.saturating_sub(1);
+        if slot.count == 0 {
+            slot = Slot::empty();
+        }
+        self.container.set_slot(index, slot);
+        self.container.acknowledge_slot(index);
+    }
+
+    /// Add items to the hotbar and main inventory, returning the slots that
+    /// changed and the items that did not fit
+    ///
+    /// Matching stacks are filled before empty slots, and the hotbar before
+    /// the main inventory.
+    pub fn add_item(&mut self, item: Slot) -> (Vec<usize>, Slot) {
+        let mut remaining = item;
+        let mut changed = Vec::new();
+        let slots = HOTBAR_SLOTS.chain(MAIN_SLOTS);
+        for index in slots.clone() {
+            if remaining.is_empty() {
+                break;
+            }
+            let existing = self.slot(index);
+            if existing.is_empty() || !stacks_with(&existing, &remaining) {
+                continue;
+            }
+            let moved = remaining
+                .count
+                .min(MAX_STACK_SIZE.saturating_sub(existing.count));
+            if moved == 0 {
+                continue;
+            }
+            self.container
+                .set_slot(index, with_count(&existing, existing.count + moved));
+            remaining = with_count(&remaining, remaining.count - moved);
+            changed.push(index);
+        }
+        for index in slots {
+            if remaining.is_empty() {
+                break;
+            }
+            if !self.slot(index).is_empty() {
+                continue;
+            }
+            let moved = remaining.count.min(MAX_STACK_SIZE);
+            self.container
+                .set_slot(index, with_count(&remaining, moved));
+            remaining = with_count(&remaining, remaining.count - moved);
+            changed.push(index);
+        }
+        (changed, remaining)
+    }
+
+    /// Apply a click, returning the contents to send back if the client's
+    /// prediction of the result was wrong
+    ///
+    /// Clicks made against an out-of-date state, invalid clicks and click
+    /// modes that are not supported yet are not applied and the client is
+    /// resynchronised instead. Items dropped outside the window are
+    /// destroyed.
+    pub fn handle_click(
+        &mut self,
+        click: &ClickContainerPacket,
+    ) -> Option<SetContainerContentPacket> {
+        if click.window_id.0 != self.container.window_id() {
+            tracing::debug!("Ignoring click in window {}", click.window_id.0);
+            return None;
+        }
+
+        let before: Vec<Slot> = (0..self.container.size())
+            .filter_map(|index| self.container.get_slot(index).cloned())
+            .collect();
+        let current = click.state_id.0 == self.container.state_id();
+        let applied = match self.parse_click(click) {
+            Some(parsed) if current => {
+                self.apply(parsed);
+                true
+            }
+            _ => false,
+        };
+
+        if applied && self.prediction_matches(click, &before) {
+            for (index, _) in &click.changed_slots {
+                if let Ok(index) = usize::try_from(*index) {
+                    self.container.acknowledge_slot(index);
+                }
+            }
+            self.container.acknowledge_carried_item();
+            return None;
+        }
+        Some(self.container.content_packet())
+    }
+
+    /// Validate a click, returning `None` if it is invalid or unsupported
+    fn parse_click(&self, click: &ClickContainerPacket) -> Option<Click> {
+        let slot = usize::try_from(click.slot)
+            .ok()
+            .filter(|&slot| slot < self.container.size());
+        match (click.mode.0, click.button) {
+            (0, 0 | 1) if click.slot == OUTSIDE_WINDOW_SLOT || slot.is_some() => {
+                Some(Click::Pickup {
+                    slot,
+                    right: click.button == 1,
+                })
+            }
+            (1, 0 | 1) => Some(Click::QuickMove { slot: slot? }),
+            (2, button @ 0..=8) => Some(Click::Swap {
+                slot: slot?,
+                target: HOTBAR_SLOTS.start + button as usize,
+            }),
+            (2, OFFHAND_BUTTON) => Some(Click::Swap {
+                slot: slot?,
+                target: OFFHAND_SLOT,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Apply a valid click to the server's copy of the inventory
+    fn apply(&mut self, click: Click) {
+        match click {
+            Click::Pickup { slot: None, right } => {
+                let carried = self.container.carried_item().clone();
+                let count = if right {
+                    carried.count.saturating_sub(1)
+                } else {
+                    0
+                };
+                self.container.set_carried_item(with_count(&carried, count));
+            }
+            Click::Pickup {
+                slot: Some(index),
+                right,
+            } => self.pickup(index, right),
+            Click::QuickMove { slot } => self.quick_move(slot),
+            Click::Swap { slot, target } => {
+                let first = self.slot(slot);
+                let second = self.slot(target);
+                self.container.set_slot(slot, second);
+                self.container.set_slot(target, first);
+            }
+        }
+    }
+
+    /// Pick up, place or swap items between a slot and the cursor
+    fn pickup(&mut self, index: usize, right: bool) {
+        let slot = self.slot(index);
+        let carried = self.container.carried_item().clone();
+        let (slot, carried) = match (slot.is_empty(), carried.is_empty()) {
+            (true, true) => return,
+            (false, true) if right => {
+                let taken = slot.count.div_ceil(2);
+                (
+                    with_count(&slot, slot.count - taken),
+                    with_count(&slot, taken),
+                )
+            }
+            (false, true) => (Slot::empty(), slot),
+            (true, false) if right => (
+                with_count(&carried, 1),
+                with_count(&carried, carried.count - 1),
+            ),
+            (true, false) => (carried, Slot::empty()),
+            (false, false) if stacks_with(&slot, &carried) => {
+                let offered = if right { 1 } else { carried.count };
+                let moved = offered.min(MAX_STACK_SIZE.saturating_sub(slot.count));
+                (
+                    with_count(&slot, slot.count + moved),
+                    with_count(&carried, carried.count - moved),
+                )
+            }
+            (false, false) => (carried, slot),
+        };
+        self.container.set_slot(index, slot);
+        self.container.set_carried_item(carried);
+    }
+
+    /// Move a stack between the hotbar and the main inventory, filling
+    /// matching stacks before empty slots
+    fn quick_move(&mut self, index: usize) {
+        let mut moving = self.slot(index);
+        if moving.is_empty() {
+            return;
+        }
+        let targets = if MAIN_SLOTS.contains(&index) {
+            HOTBAR_SLOTS
+        } else if HOTBAR_SLOTS.contains(&index) {
+            MAIN_SLOTS
+        } else {
+            MAIN_SLOTS.start..HOTBAR_SLOTS.end
+        };
+
+        for target in targets.clone() {
+            let existing = self.slot(target);
+            if existing.is_empty() || !stacks_with(&existing, &moving) {
+                continue;
+            }
+            let moved = moving
+                .count
+                .min(MAX_STACK_SIZE.saturating_sub(existing.count));
+            self.container
+                .set_slot(target, with_count(&existing, existing.count + moved));
+            moving.count -= moved;
+            if moving.count == 0 {
+                break;
+            }
+        }
+        if moving.count > 0 {
+            if let Some(target) = targets.clone().find(|&target| self.slot(target).is_empty()) {
+                self.container.set_slot(target, moving.clone());
+                moving.count = 0;
+            }
+        }
+        self.container
+            .set_slot(index, with_count(&moving, moving.count));
+    }
+
+    /// Whether the client's prediction matches the server's result
+    ///
+    /// Every slot the server changed must be listed, and every listed slot
+    /// and the carried item must hold what the server holds.
+    fn prediction_matches(&self, click: &ClickContainerPacket, before: &[Slot]) -> bool {
+        let listed = |index: usize| {
+            click
+                .changed_slots
+                .iter()
+                .any(|(changed, _)| usize::try_from(*changed) == Ok(index))
+        };
+        let all_listed = before
+            .iter()
+            .enumerate()
+            .all(|(index, slot)| self.container.get_slot(index) == Some(slot) || listed(index));
+        let all_match = click.changed_slots.iter().all(|(index, predicted)| {
+            usize::try_from(*index)
+                .ok()
+                .and_then(|index| self.container.get_slot(index))
+                .is_some_and(|slot| predicted.matches(slot))
+        });
+        all_listed && all_match && click.carried_item.matches(self.container.carried_item())
+    }
+
+    /// Copy the contents of a slot
+    fn slot(&self, index: usize) -> Slot {
+        self.container.get_slot(index).cloned().unwrap_or_default()
+    }
+}
+
+/// Whether two stacks can be merged
+fn stacks_with(first: &Slot, second: &Slot) -> bool {
+    first.item_id == second.item_id && first.nbt == second.nbt
+}
+
+/// Copy a stack with a different count, or an empty slot for zero
+fn with_count(slot: &Slot, count: u8) -> Slot {
+    if count == 0 {
+        return Slot::empty();
+    }
+    Slot {
+        count,
+        ..slot.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::packets::play::HashedSlot;
+    use crate::protocol::types::VarInt;
+
+    /// Build a click predicting the given slot contents
+    fn click(
+        slot: i16,
+        button: i8,
+        mode: i32,
+        changed_slots: &[(i16, Slot)],
+        carried_item: &Slot,
+    ) -> ClickContainerPacket {
+        ClickContainerPacket {
+            window_id: VarInt(0),
+            state_id: VarInt(1),
+            slot,
+            button,
+            mode: VarInt(mode),
+            changed_slots: changed_slots
+                .iter()
+                .map(|(index, slot)| (*index, HashedSlot::new(slot)))
+                .collect(),
+            carried_item: HashedSlot::new(carried_item),
+        }
+    }
+
+    fn manager_with(slots: &[(usize, Slot)]) -> InventoryManager {
+        let mut container = Container::player_inventory();
+        for (index, slot) in slots {
+            container.set_slot(*index, slot.clone());
+        }
+        // Clicks are made against the state of this first sync
+        container.take_update();
+        InventoryManager::new(container)
+    }
+
+    #[test]
+    fn test_left_click_pick_up_and_place() {
+        let mut manager = manager_with(&[(36, Slot::new(1, 10))]);
+        let pick_up = click(36, 0, 0, &[(36, Slot::empty())], &Slot::new(1, 10));
+        assert_eq!(manager.handle_click(&pick_up), None);
+        assert_eq!(manager.container().carried_item(), &Slot::new(1, 10));
+
+        let place = click(9, 0, 0, &[(9, Slot::new(1, 10))], &Slot::empty());
+        assert_eq!(manager.handle_click(&place), None);
+        assert_eq!(manager.container().get_slot(9), Some(&Slot::new(1, 10)));
+        assert!(manager.container().carried_item().is_empty());
+        assert_eq!(manager.container_mut().take_update(), None);
+    }
+
+    #[test]
+    fn test_right_click_splits_and_places_one() {
+        let mut manager = manager_with(&[(36, Slot::new(1, 5))]);
+        let split = click(36, 1, 0, &[(36, Slot::new(1, 2))], &Slot::new(1, 3));
+        assert_eq!(manager.handle_click(&split), None);
+
+        let place = click(37, 1, 0, &[(37, Slot::new(1, 1))], &Slot::new(1, 2));
+        assert_eq!(manager.handle_click(&place), None);
+        assert_eq!(manager.container().get_slot(37), Some(&Slot::new(1, 1)));
+    }
+
+    #[test]
+    fn test_shift_click_merges_into_main_inventory() {
+        let mut manager = manager_with(&[(36, Slot::new(1, 40)), (20, Slot::new(1, 60))]);
+        let shift = click(
+            36,
+            0,
+            1,
+            &[
+                (9, Slot::new(1, 36)),
+                (20, Slot::new(1, 64)),
+                (36, Slot::empty()),
+            ],
+            &Slot::empty(),
+        );
+        assert_eq!(manager.handle_click(&shift), None);
+        assert_eq!(manager.container().get_slot(9), Some(&Slot::new(1, 36)));
+    }
+
+    #[test]
+    fn test_number_key_swaps_with_hotbar() {
+        let mut manager = manager_with(&[(9, Slot::new(1, 1)), (38, Slot::new(2, 1))]);
+        let swap = click(
+            9,
+            2,
+            2,
+            &[(9, Slot::new(2, 1)), (38, Slot::new(1, 1))],
+            &Slot::empty(),
+        );
+        assert_eq!(manager.handle_click(&swap), None);
+        assert_eq!(manager.container().get_slot(38), Some(&Slot::new(1, 1)));
+    }
+
+    #[test]
+    fn test_wrong_prediction_is_corrected() {
+        let mut manager = manager_with(&[(36, Slot::new(1, 10))]);
+        // The client claims to have picked up more than the slot held
+        let pick_up = click(36, 0, 0, &[(36, Slot::empty())], &Slot::new(1, 64));
+        let correction = manager.handle_click(&pick_up).unwrap();
+        assert_eq!(correction.carried_item, Slot::new(1, 10));
+        assert_eq!(correction.state_id, VarInt(2));
+
+        // Clicks against the old state are not applied
+        let stale = click(9, 0, 0, &[(9, Slot::new(1, 10))], &Slot::empty());
+        assert!(manager.handle_click(&stale).is_some());
+        assert!(manager.container().get_slot(9).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_unsupported_mode_resyncs() {
+        let mut manager = manager_with(&[(36, Slot::new(1, 10))]);
+        let throw = click(36, 0, 4, &[(36, Slot::new(1, 9))], &Slot::empty());
+        assert!(manager.handle_click(&throw).is_some());
+        assert_eq!(manager.container().get_slot(36), Some(&Slot::new(1, 10)));
+    }
+
+    #[test]
+    fn test_equipment_changes() {
+        let mut manager = InventoryManager::new(Container::player_inventory());
+        assert!(manager.take_equipment_changes().is_empty());
+
+        manager.container_mut().set_slot(5, Slot::new(1, 1));
+        manager.container_mut().set_slot(36, Slot::new(2, 64));
+        assert_eq!(
+            manager.take_equipment_changes(),
+            [
+                (EquipmentSlot::MainHand, Slot::new(2, 64)),
+                (EquipmentSlot::Head, Slot::new(1, 1)),
+            ]
+        );
+        assert!(manager.take_equipment_changes().is_empty());
+
+        manager.consume_held_item(Hand::MainHand);
+        assert_eq!(
+            manager.take_equipment_changes(),
+            [(EquipmentSlot::MainHand, Slot::new(2, 63))]
+        );
+
+        assert!(!manager.select_slot(9));
+        assert!(manager.select_slot(1));
+        assert_eq!(manager.held_slot(Hand::MainHand), 37);
+        assert_eq!(
+            manager.take_equipment_changes(),
+            [(EquipmentSlot::MainHand, Slot::empty())]
+        );
+        assert_eq!(manager.equipment(EquipmentSlot::Saddle), Slot::empty());
+    }
+
+    #[test]
+    fn test_consume_held_item() {
+        let mut manager = manager_with(&[(36, Slot::new(1, 2)), (45, Slot::new(2, 1))]);
+        assert_eq!(manager.held_item(Hand::MainHand), Slot::new(1, 2));
+
+        manager.consume_held_item(Hand::MainHand);
+        manager.consume_held_item(Hand::OffHand);
+        assert_eq!(manager.held_item(Hand::MainHand), Slot::new(1, 1));
+        assert!(manager.held_item(Hand::OffHand).is_empty());
+
+        // The client predicted the change, so there is nothing to sync
+        assert!(manager.container_mut().take_update().is_none());
+    }
+
+    #[test]
+    fn test_add_item_fills_stacks_then_empty_slots() {
+        let mut manager = manager_with(&[(9, Slot::new(1, 60)), (36, Slot::new(2, 1))]);
+        let (changed, remaining) = manager.add_item(Slot::new(1, 68));
+        assert_eq!(changed, [9, 37]);
+        assert!(remaining.is_empty());
+        assert_eq!(manager.container().get_slot(9), Some(&Slot::new(1, 64)));
+        assert_eq!(manager.container().get_slot(37), Some(&Slot::new(1, 64)));
+
+        // Every hotbar and main slot is taken, so nothing more fits
+        for index in HOTBAR_SLOTS.chain(MAIN_SLOTS) {
+            manager.container_mut().set_slot(index, Slot::new(3, 64));
+        }
+        let (changed, remaining) = manager.add_item(Slot::new(1, 5));
+        assert!(changed.is_empty());
+        assert_eq!(remaining, Slot::new(1, 5));
+    }
+}
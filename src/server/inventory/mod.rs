@@ -0,0 +1,233 @@
+//! Inventories
+//!
+//! A [`Container`] holds the slots of one window and remembers which of them
+//! changed since clients were last told, so a sync sends a single slot when
+//! that is all that changed and the full contents otherwise.
+
+use crate::error::Result;
+use crate::protocol::packets::play::{SetContainerContentPacket, SetContainerSlotPacket};
+use crate::protocol::types::{McUuid, Slot, VarInt};
+use crate::server::PlayerList;
+use std::collections::BTreeSet;
+
+pub mod chest;
+pub mod manager;
+pub mod players;
+
+pub use chest::ChestStore;
+pub use manager::InventoryManager;
+pub use players::{PlayerInventories, SharedInventory};
+
+/// Window ID of the player inventory
+pub const PLAYER_INVENTORY_WINDOW: i32 = 0;
+
+/// Number of slots in the player inventory, including crafting, armor and
+/// offhand slots
+pub const PLAYER_INVENTORY_SIZE: usize = 46;
+
+/// A packet bringing a client's view of a container up to date
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContainerUpdate {
+    /// A single changed slot
+    Slot(SetContainerSlotPacket),
+    /// The full contents
+    Content(SetContainerContentPacket),
+}
+
+/// The slots of a window
+#[derive(Debug, Clone)]
+pub struct Container {
+    /// Window ID
+    window_id: i32,
+    /// State ID sent with the last update
+    state_id: i32,
+    /// Slot contents
+    slots: Vec<Slot>,
+    /// Item carried by the cursor
+    carried_item: Slot,
+    /// Slots changed since the last sync
+    changed: BTreeSet<usize>,
+    /// Whether the carried item changed since the last sync
+    carried_item_changed: bool,
+    /// Whether the full contents must be sent
+    needs_full_sync: bool,
+}
+
+impl Container {
+    /// Create a container of empty slots
+    ///
+    /// Clients know nothing about a new container, so the first sync sends
+    /// its full contents.
+    pub fn new(window_id: i32, size: usize) -> Self {
+        Self {
+            window_id,
+            state_id: 0,
+            slots: vec![Slot::empty(); size],
+            carried_item: Slot::empty(),
+            changed: BTreeSet::new(),
+            carried_item_changed: false,
+            needs_full_sync: true,
+        }
+    }
+
+    /// Create an empty player inventory
+    pub fn player_inventory() -> Self {
+        Self::new(PLAYER_INVENTORY_WINDOW, PLAYER_INVENTORY_SIZE)
+    }
+
+    /// Window ID
+    pub fn window_id(&self) -> i32 {
+        self.window_id
+    }
+
+    /// State ID sent with the last update
+    pub fn state_id(&self) -> i32 {
+        self.state_id
+    }
+
+    /// Number of slots
+    pub fn size(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Get the contents of a slot
+    pub fn get_slot(&self, index: usize) -> Option<&Slot> {
+        self.slots.get(index)
+    }
+
+    /// Replace the contents of a slot, returning whether the slot exists
+    pub fn set_slot(&mut self, index: usize, slot: Slot) -> bool {
+        let Some(current) = self.slots.get_mut(index) else {
+            return false;
+        };
+        if *current != slot {
+            *current = slot;
+            self.changed.insert(index);
+        }
+        true
+    }
+
+    /// Item carried by the cursor
+    pub fn carried_item(&self) -> &Slot {
+        &self.carried_item
+    }
+
+    /// Replace the item carried by the cursor
+    pub fn set_carried_item(&mut self, slot: Slot) {
+        if self.carried_item != slot {
+            self.carried_item = slot;
+            self.carried_item_changed = true;
+        }
+    }
+
+    /// Record that the client already knows the contents of a slot, such as
+    /// after predicting the result of a click correctly
+    pub fn acknowledge_slot(&mut self, index: usize) {
+        self.changed.remove(&index);
+    }
+
+    /// Record that the client already knows the carried item
+    pub fn acknowledge_carried_item(&mut self) {
+        self.carried_item_changed = false;
+    }
+
+    /// Build a packet with the full contents, replacing whatever the client
+    /// believes the container holds
+    pub fn content_packet(&mut self) -> SetContainerContentPacket {
+        self.state_id = self.state_id.wrapping_add(1);
+        self.changed.clear();
+        self.carried_item_changed = false;
+        self.needs_full_sync = false;
+        SetContainerContentPacket {
+            window_id: VarInt(self.window_id),
+            state_id: VarInt(self.state_id),
+            slots: self.slots.clone(),
+            carried_item: self.carried_item.clone(),
+        }
+    }
+
+    /// Build the packet syncing the changes made since the last call, if any
+    pub fn take_update(&mut self) -> Option<ContainerUpdate> {
+        let full = self.needs_full_sync || self.carried_item_changed;
+        match (full, self.changed.len()) {
+            (false, 0) => None,
+            (false, 1) => {
+                let index = self.changed.first().copied()?;
+                self.slot_packet(index).map(ContainerUpdate::Slot)
+            }
+            _ => Some(ContainerUpdate::Content(self.content_packet())),
+        }
+    }
+
+    /// Build a packet with the contents of one slot, after which the client
+    /// knows that slot
+    pub fn slot_packet(&mut self, index: usize) -> Option<SetContainerSlotPacket> {
+        let slot_data = self.slots.get(index)?.clone();
+        self.state_id = self.state_id.wrapping_add(1);
+        self.changed.remove(&index);
+        Some(SetContainerSlotPacket {
+            window_id: VarInt(self.window_id),
+            state_id: VarInt(self.state_id),
+            slot: index as i16,
+            slot_data,
+        })
+    }
+
+    /// Send the changes made since the last sync to a player, returning
+    /// whether anything was sent
+    pub async fn sync_to_player(&mut self, players: &PlayerList, uuid: &McUuid) -> Result<bool> {
+        match self.take_update() {
+            Some(ContainerUpdate::Slot(packet)) => players.send_to(uuid, &packet).await,
+            Some(ContainerUpdate::Content(packet)) => players.send_to(uuid, &packet).await,
+            None => Ok(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_sync_sends_content() {
+        let mut container = Container::player_inventory();
+        container.set_slot(36, Slot::new(1, 64));
+        let update = container.take_update();
+        let Some(ContainerUpdate::Content(packet)) = update else {
+            unreachable!("Expected the full contents, got {:?}", update);
+        };
+        assert_eq!(packet.slots.len(), PLAYER_INVENTORY_SIZE);
+        assert_eq!(packet.slots[36], Slot::new(1, 64));
+        assert_eq!(container.take_update(), None);
+    }
+
+    #[test]
+    fn test_sync_single_and_many_slots() {
+        let mut container = Container::new(1, 9);
+        container.take_update();
+        assert!(!container.set_slot(9, Slot::new(1, 1)));
+
+        container.set_slot(4, Slot::new(1, 1));
+        assert_eq!(
+            container.take_update(),
+            Some(ContainerUpdate::Slot(SetContainerSlotPacket {
+                window_id: VarInt(1),
+                state_id: VarInt(2),
+                slot: 4,
+                slot_data: Slot::new(1, 1),
+            }))
+        );
+
+        // Setting a slot to what it holds is not a change
+        container.set_slot(4, Slot::new(1, 1));
+        assert_eq!(container.take_update(), None);
+
+        container.set_slot(0, Slot::new(2, 1));
+        container.set_slot(8, Slot::new(3, 1));
+        assert!(matches!(
+            container.take_update(),
+            Some(ContainerUpdate::Content(_))
+        ));
+        assert_eq!(container.state_id(), 3);
+    }
+}
@@ -0,0 +1,69 @@
+//! Inventories of the players in the world
+//!
+//! Each connection owns its player's [`InventoryManager`], and shares it
+//! here so commands such as `/give` can change another player's inventory.
+
+use crate::protocol::types::McUuid;
+use crate::server::inventory::InventoryManager;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{Mutex, RwLock};
+
+/// Inventory of one player, shared with their connection
+pub type SharedInventory = Arc<Mutex<InventoryManager>>;
+
+/// Thread-safe registry of the inventories of the players in the world
+#[derive(Debug, Default)]
+pub struct PlayerInventories {
+    /// Inventories keyed by player UUID
+    inventories: RwLock<HashMap<McUuid, SharedInventory>>,
+}
+
+impl PlayerInventories {
+    /// Create a registry without any inventories
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Share a player's inventory, replacing any shared before
+    pub async fn insert(&self, uuid: McUuid, inventory: SharedInventory) {
+        self.inventories.write().await.insert(uuid, inventory);
+    }
+
+    /// Get a player's inventory
+    pub async fn get(&self, uuid: &McUuid) -> Option<SharedInventory> {
+        self.inventories.read().await.get(uuid).cloned()
+    }
+
+    /// Stop sharing a player's inventory, such as when they leave
+    pub async fn remove(&self, uuid: &McUuid) -> Option<SharedInventory> {
+        self.inventories.write().await.remove(uuid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::packets::play::Hand;
+    use crate::protocol::types::Slot;
+    use crate::server::inventory::Container;
+
+    #[tokio::test]
+    async fn test_shared_inventory() {
+        let inventories = PlayerInventories::new();
+        let uuid = McUuid::new_v4();
+        let inventory = Arc::new(Mutex::new(InventoryManager::new(
+            Container::player_inventory(),
+        )));
+        inventories.insert(uuid, Arc::clone(&inventory)).await;
+
+        // Changes made through the registry are seen by the owner
+        let shared = inventories.get(&uuid).await.unwrap();
+        shared.lock().await.add_item(Slot::new(1, 3));
+        let held = inventory.lock().await.held_item(Hand::MainHand);
+        assert_eq!(held, Slot::new(1, 3));
+
+        assert!(inventories.remove(&uuid).await.is_some());
+        assert!(inventories.get(&uuid).await.is_none());
+    }
+}
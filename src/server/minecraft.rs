@@ -4,79 +4,44 @@
 //! the other modules to create a functioning Minecraft server.
 
 use crate::config::ServerConfig;
-use crate::error::{Result, ServerError};
-use crate::game::{player::PlayerManager, world::World};
-use crate::network::{Connection, ServerListener};
-use crate::protocol::packets::{
-    Packet,
-    handshaking::HandshakePacket,
-    login::{LoginAcknowledgedPacket, LoginStartPacket, LoginSuccessPacket, SetCompressionPacket},
-    play::LoginPlayPacket,
-    status::{
-        Description, PingRequestPacket, PingResponsePacket, PlayersInfo, ServerStatus,
-        StatusRequestPacket, StatusResponsePacket, VersionInfo,
-    },
+use crate::error::Result;
+use crate::metrics::{Metrics, MetricsServer};
+use crate::network::ServerListener;
+use crate::protocol::packets::status::{Description, PlayersInfo, ServerStatus, VersionInfo};
+use crate::protocol::{MINECRAFT_VERSION, PROTOCOL_VERSION};
+use crate::query::QueryServer;
+use crate::rcon::RconServer;
+use crate::server::{
+    ConsoleInput, FaviconLoader, TickLoop, context::ServerContext, handler::ConnectionHandler,
 };
-use crate::protocol::{ConnectionState, MINECRAFT_VERSION, PROTOCOL_VERSION};
+use std::net::SocketAddr;
 use std::sync::Arc;
-use tokio::sync::{RwLock, mpsc};
-use tokio::time::{Duration, interval};
+use tokio::sync::mpsc;
+use tokio::task::{JoinHandle, JoinSet};
+use tokio::time::{Duration, Instant, interval_at};
+
+/// How often the world's metadata is saved while the server runs
+const LEVEL_SAVE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// How long connections get to disconnect their clients when the server
+/// stops, before they are dropped
+const DISCONNECT_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// Main Minecraft server
 pub struct MinecraftServer {
-    /// Server configuration
-    config: ServerConfig,
-    /// Player manager
-    players: Arc<PlayerManager>,
-    /// Main world
-    world: Arc<RwLock<World>>,
-    /// Server status
-    status: ServerStatus,
+    /// State shared with every connection
+    context: Arc<ServerContext>,
 }
 
 impl MinecraftServer {
     /// Create a new Minecraft server
     pub async fn new(config: ServerConfig) -> Result<Self> {
-        // Load favicon if configured
-        let favicon = if let Some(ref favicon_path) = config.favicon {
-            if favicon_path.starts_with("data:image/png;base64,") {
-                // Already a data URL, use as-is
-                tracing::info!(
-                    "Using provided favicon data URL (length: {})",
-                    favicon_path.len()
-                );
-                Some(favicon_path.clone())
-            } else {
-                // Try to load from file path
-                match crate::favicon::load_favicon_from_file(favicon_path) {
-                    Ok(favicon_data) => {
-                        tracing::debug!(
-                            "Loaded favicon from: {} (encoded length: {})",
-                            favicon_path,
-                            favicon_data.len()
-                        );
-
-                        // Check if the favicon is too large for Minecraft protocol
-                        if favicon_data.len() > crate::protocol::types::McString::MAX_LENGTH {
-                            tracing::error!(
-                                "Favicon is too large ({} chars) for Minecraft protocol (max: {} chars). Please use a smaller image.",
-                                favicon_data.len(),
-                                crate::protocol::types::McString::MAX_LENGTH
-                            );
-                            None
-                        } else {
-                            Some(favicon_data)
-                        }
-                    }
-                    Err(e) => {
-                        tracing::warn!("Failed to load favicon from {}: {}", favicon_path, e);
-                        None
-                    }
-                }
-            }
-        } else {
-            None
-        };
+        // Load the favicon once, if configured
+        let favicon = config
+            .favicon
+            .as_deref()
+            .map(FaviconLoader::new)
+            .and_then(|loader| loader.favicon().map(str::to_string));
 
         // Create server status
         let status = ServerStatus {
@@ -86,7 +51,7 @@ impl MinecraftServer {
             },
             players: PlayersInfo {
                 max: config.max_players,
-                online: 0, // TODO: Update dynamically
+                online: 0, // Filled in per status request
                 sample: None,
             },
             description: Description::Text(config.motd.clone()),
@@ -95,23 +60,20 @@ impl MinecraftServer {
         };
 
         Ok(Self {
-            config,
-            players: Arc::new(PlayerManager::new()),
-            world: Arc::new(RwLock::new(World::new("world".to_string(), 12345))),
-            status,
+            context: Arc::new(ServerContext::new(config, status)?),
         })
     }
 
     /// Start the server
-    pub async fn run(mut self) -> Result<()> {
+    pub async fn run(self) -> Result<()> {
         tracing::info!("Obsidium Minecraft Server v{}", env!("CARGO_PKG_VERSION"));
-        tracing::debug!("Starting server on {}", self.config.bind_address);
+        tracing::debug!("Starting server on {}", self.context.config.bind_address);
 
         // Create connection sender for the listener
         let (connection_sender, mut connection_receiver) = mpsc::unbounded_channel();
 
         // Start the network listener
-        let listener = ServerListener::new(self.config.clone(), connection_sender).await?;
+        let listener = ServerListener::new(self.context.config.clone(), connection_sender).await?;
         let listener_addr = listener.local_addr()?;
         tracing::debug!("Server listening on {}", listener_addr);
 
@@ -122,11 +84,29 @@ impl MinecraftServer {
             }
         });
 
-        // Create update timer
-        let mut update_timer = interval(Duration::from_millis(50)); // 20 TPS
+        // Save the world's metadata in the background
+        let save_handle = tokio::spawn(save_level_periodically(Arc::clone(&self.context)));
+
+        let console = ConsoleInput::stdin(Arc::clone(&self.context));
+
+        // Run commands typed into the console
+        let console_handle = tokio::spawn(async move {
+            if let Err(e) = console.run().await {
+                tracing::error!("Console error: {}", e);
+            }
+        });
+
+        // Serve metrics, RCON and queries, as enabled
+        let service_handles = self.start_services().await?;
+
+        // Run the game, until a stop is requested
+        let mut tick_handle = tokio::spawn(TickLoop::new(Arc::clone(&self.context)).run());
 
         tracing::info!("Server started successfully!");
 
+        // Connections being handled, so they can be closed on shutdown
+        let mut connections = JoinSet::new();
+
         // Main server loop
         loop {
             tokio::select! {
@@ -145,258 +125,125 @@ impl MinecraftServer {
 
                 // Handle new connections
                 Some(connection) = connection_receiver.recv() => {
-                    let players = Arc::clone(&self.players);
-                    let world = Arc::clone(&self.world);
-                    let status = self.status.clone();
-                    let config = self.config.clone();
+                    let handler = ConnectionHandler::new(connection, Arc::clone(&self.context));
 
-                    tokio::spawn(async move {
-                        if let Err(e) = Self::handle_connection(connection, players, world, status, config).await {
+                    connections.spawn(async move {
+                        if let Err(e) = handler.run().await {
                             tracing::error!("Connection error: {}", e);
                         }
                     });
                 }
 
-                // Update world and game logic
-                _ = update_timer.tick() => {
-                    let mut world = self.world.write().await;
-                    world.update(0.05); // 50ms delta
+                // Forget connections that have closed
+                Some(_) = connections.join_next(), if !connections.is_empty() => {}
 
-                    // Update player count in status
-                    self.status.players.online = self.players.player_count().await as u32;
-                }
+                // The game loop only returns once a stop was requested
+                _ = &mut tick_handle => break,
             }
         }
 
-        // Abort the listener task
+        // Stop accepting clients and abort the background tasks
         listener_handle.abort();
+        tick_handle.abort();
+        console_handle.abort();
+        for handle in service_handles {
+            handle.abort();
+        }
+        save_handle.abort();
 
-        // Log current player count
-        let player_count = self.players.player_count().await;
-        if player_count > 0 {
-            tracing::info!("Disconnecting {} connected player(s)...", player_count);
+        // Disconnect the clients, so the players' data is saved, then save
+        // the world one last time
+        self.close_connections(connections).await;
+        if let Err(e) = self.context.save_level().await {
+            tracing::error!("Failed to save level data: {}", e);
         }
 
         tracing::info!("Server shutdown complete");
         Ok(())
     }
 
-    /// Handle an individual connection
-    async fn handle_connection(
-        mut connection: Connection,
-        players: Arc<PlayerManager>,
-        _world: Arc<RwLock<World>>,
-        status: ServerStatus,
-        config: ServerConfig,
-    ) -> Result<()> {
-        tracing::debug!("Handling connection from {}", connection.peer_addr());
-
-        loop {
-            // Read packet
-            let (packet_id, data) = match connection.read_packet().await {
-                Ok((pid, pdata)) => {
-                    tracing::debug!(
-                        "Received packet ID: 0x{:02X}, data length: {}, state: {:?}",
-                        pid.0,
-                        pdata.len(),
-                        connection.state()
-                    );
-                    (pid, pdata)
-                }
-                Err(e) => {
-                    tracing::debug!("Connection closed: {}", e);
-                    break;
-                }
-            };
-
-            let should_break = match connection.state() {
-                ConnectionState::Handshaking => {
-                    Self::handle_handshaking_packet(&mut connection, packet_id, &data)?;
-                    false
-                }
-                ConnectionState::Status => {
-                    Self::handle_status_packet(&mut connection, packet_id, &data, &status).await?
-                }
-                ConnectionState::Login => {
-                    Self::handle_login_packet(&mut connection, packet_id, &data, &config, &players)
-                        .await?;
-                    false
-                }
-                ConnectionState::Configuration => {
-                    Self::handle_configuration_packet(&mut connection, packet_id, &data, &config)
-                        .await?;
-                    false
-                }
-                ConnectionState::Play => {
-                    Self::handle_play_packet(packet_id);
-                    false
-                }
-            };
-
-            if should_break {
-                break;
-            }
+    /// Tell every connection to disconnect its client and wait for them to
+    /// finish, dropping the ones that take longer than 10 seconds
+    async fn close_connections(&self, mut connections: JoinSet<()>) {
+        let player_count = self.context.players.player_count().await;
+        if player_count > 0 {
+            tracing::info!("Disconnecting {} connected player(s)...", player_count);
         }
-
-        // Remove player when connection closes
-        players.remove_player(connection.peer_addr()).await;
-
-        Ok(())
-    }
-
-    /// Handle handshaking state packets
-    fn handle_handshaking_packet(
-        connection: &mut Connection,
-        packet_id: crate::protocol::VarInt,
-        data: &[u8],
-    ) -> Result<()> {
-        if packet_id.0 == HandshakePacket::ID {
-            let handshake = HandshakePacket::read(&mut std::io::Cursor::new(data))?;
-
-            tracing::debug!(
-                "Handshake: version={}, address={}, port={}, next_state={}",
-                handshake.protocol_version.0,
-                handshake.server_address.0,
-                handshake.server_port,
-                handshake.next_state.0
+        self.context.close_connections();
+        let closed = tokio::time::timeout(DISCONNECT_TIMEOUT, async {
+            while connections.join_next().await.is_some() {}
+        })
+        .await;
+        if closed.is_err() {
+            tracing::warn!(
+                "Dropping {} connection(s) that did not close in time",
+                connections.len()
             );
-
-            connection.set_protocol_version(handshake.protocol_version.0);
-
-            match handshake.next_state.0 {
-                1 => connection.set_state(ConnectionState::Status),
-                2 => connection.set_state(ConnectionState::Login),
-                3 => {
-                    // Transfer intent - for now, treat as login
-                    // TODO: Implement proper transfer handling
-                    connection.set_state(ConnectionState::Login);
-                    tracing::debug!("Transfer intent received, treating as login for now");
-                }
-                _ => {
-                    return Err(ServerError::Protocol("Invalid next state".to_string()));
-                }
-            }
-        }
-        Ok(())
-    }
-
-    /// Handle status state packets
-    async fn handle_status_packet(
-        connection: &mut Connection,
-        packet_id: crate::protocol::VarInt,
-        data: &[u8],
-        status: &ServerStatus,
-    ) -> Result<bool> {
-        if packet_id.0 == StatusRequestPacket::ID {
-            // Send status response
-            let json = status.to_json()?;
-            let response = StatusResponsePacket {
-                json_response: json.into(),
-            };
-            connection.write_packet(&response).await?;
-        } else if packet_id.0 == PingRequestPacket::ID {
-            let ping = PingRequestPacket::read(&mut std::io::Cursor::new(data))?;
-            let pong = PingResponsePacket {
-                payload: ping.payload,
-            };
-            connection.write_packet(&pong).await?;
-            return Ok(true); // Close connection after ping
+            connections.abort_all();
         }
-        Ok(false)
     }
 
-    /// Handle login state packets
-    async fn handle_login_packet(
-        connection: &mut Connection,
-        packet_id: crate::protocol::VarInt,
-        data: &[u8],
-        config: &ServerConfig,
-        players: &Arc<PlayerManager>,
-    ) -> Result<()> {
-        if packet_id.0 == LoginStartPacket::ID {
-            let login_start = LoginStartPacket::read(&mut std::io::Cursor::new(data))?;
+    /// Start the servers enabled next to the game: metrics for monitoring,
+    /// the remote console and the query protocol
+    async fn start_services(&self) -> Result<Vec<JoinHandle<()>>> {
+        let config = &self.context.config;
+        let mut handles = Vec::new();
 
+        if let Some(address) = config.metrics_address {
+            let metrics = MetricsServer::bind(address, Metrics::global()).await?;
             tracing::info!(
-                "Player {} ({}) logging in from {}",
-                login_start.name.0,
-                login_start.player_uuid,
-                connection.peer_addr()
+                "Serving metrics on http://{}/metrics",
+                metrics.local_addr()?
             );
-
-            // Enable compression if configured
-            if let Some(threshold) = config.compression_threshold {
-                let compression_packet = SetCompressionPacket {
-                    threshold: (threshold as i32).into(),
-                };
-                connection.write_packet(&compression_packet).await?;
-                connection.enable_compression(threshold)?;
-            } // Send login success
-            let login_success = LoginSuccessPacket {
-                uuid: login_start.player_uuid,
-                username: login_start.name.clone(),
-                properties: Vec::new(),
-            };
-            connection.write_packet(&login_success).await?;
-
-            // Create player
-            let player =
-                crate::game::player::Player::new(login_start.player_uuid, login_start.name.0);
-
-            players.add_player(player, connection.peer_addr()).await;
-
-            connection.set_state(ConnectionState::Configuration);
-
-            tracing::info!("Player logged in successfully, transitioning to configuration state");
+            handles.push(tokio::spawn(async move {
+                if let Err(e) = metrics.run().await {
+                    tracing::error!("Metrics server error: {}", e);
+                }
+            }));
         }
-        Ok(())
-    }
-
-    /// Handle configuration state packets
-    async fn handle_configuration_packet(
-        connection: &mut Connection,
-        packet_id: crate::protocol::VarInt,
-        data: &[u8],
-        config: &ServerConfig,
-    ) -> Result<()> {
-        if packet_id.0 == LoginAcknowledgedPacket::ID {
-            let _login_ack = LoginAcknowledgedPacket::read(&mut std::io::Cursor::new(data))?;
-
-            tracing::debug!("Login acknowledged received in configuration state");
-
-            // Send finish configuration packet
-            use crate::protocol::packets::configuration::FinishConfigurationPacket;
-            let finish_config = FinishConfigurationPacket;
-            connection.write_packet(&finish_config).await?;
-
-            tracing::debug!("Finish configuration packet sent");
-        } else if packet_id.0 == 0x02 {
-            // Acknowledge Finish Configuration packet
-            use crate::protocol::packets::configuration::AcknowledgeFinishConfigurationPacket;
-            let _ack_finish =
-                AcknowledgeFinishConfigurationPacket::read(&mut std::io::Cursor::new(data))?;
-
-            tracing::debug!(
-                "Acknowledge finish configuration received, transitioning to play state"
-            );
-
-            connection.set_state(ConnectionState::Play);
 
-            // Send login play packet after transitioning to play state
-            let login_play = LoginPlayPacket::from_server_config(config, 1);
-            connection.write_packet(&login_play).await?;
+        match config.rcon_port {
+            Some(_) if config.rcon_password.is_empty() => {
+                tracing::warn!("No RCON password is set, so RCON is disabled");
+            }
+            Some(port) => {
+                let address = SocketAddr::new(config.bind_address.ip(), port);
+                let password = config.rcon_password.as_str();
+                let rcon = RconServer::bind(address, password, Arc::clone(&self.context)).await?;
+                tracing::info!("RCON listening on {}", rcon.local_addr()?);
+                handles.push(tokio::spawn(async move {
+                    if let Err(e) = rcon.run().await {
+                        tracing::error!("RCON server error: {}", e);
+                    }
+                }));
+            }
+            None => {}
+        }
 
-            tracing::info!("Login play packet sent, player is now in play state");
+        if let Some(port) = config.query_port {
+            let address = SocketAddr::new(config.bind_address.ip(), port);
+            let query = QueryServer::bind(address, Arc::clone(&self.context)).await?;
+            tracing::info!("Answering queries on {}", query.local_addr()?);
+            handles.push(tokio::spawn(async move {
+                if let Err(e) = query.run().await {
+                    tracing::error!("Query server error: {}", e);
+                }
+            }));
         }
-        Ok(())
-    }
 
-    /// Handle play state packets
-    fn handle_play_packet(packet_id: crate::protocol::VarInt) {
-        // Handle play packets
-        tracing::debug!("Received play packet ID: 0x{:02X}", packet_id.0);
+        Ok(handles)
+    }
+}
 
-        // TODO: Implement play packet handlers
-        // For now, just log them
+/// Save the world's `level.dat` every [`LEVEL_SAVE_INTERVAL`]
+async fn save_level_periodically(context: Arc<ServerContext>) {
+    let mut timer = interval_at(Instant::now() + LEVEL_SAVE_INTERVAL, LEVEL_SAVE_INTERVAL);
+    loop {
+        timer.tick().await;
+        match context.save_level().await {
+            Ok(()) => tracing::debug!("Saved level data"),
+            Err(e) => tracing::error!("Failed to save level data: {}", e),
+        }
     }
 }
 
@@ -2,6 +2,64 @@
 //!
 //! This module contains the main server logic and orchestration.
 
+pub mod auth;
+pub mod bans;
+pub mod block_updates;
+pub mod boss_bars;
+pub mod chat;
+pub mod commands;
+pub mod console;
+pub mod context;
+pub mod cookies;
+pub mod entities;
+pub mod events;
+pub mod handler;
+pub mod health;
+pub mod inventory;
 pub mod minecraft;
+pub mod movement;
+pub mod ops;
+pub mod placement;
+pub mod player_list;
+pub mod plugin_channel;
+pub mod scoreboard;
+pub mod signs;
+pub mod sound;
+pub mod status;
+pub mod suggestions;
+pub mod tab_list;
+pub mod tick;
+pub mod title;
+pub mod tps;
+pub mod transfer;
+pub mod whitelist;
+pub mod world;
 
+pub use bans::{BanEntry, BanList};
+pub use boss_bars::{BossBar, BossBarManager};
+pub use chat::{BroadcastChatRouter, ChatRouter};
+pub use commands::{Command, CommandContext, CommandDispatcher};
+pub use console::ConsoleInput;
+pub use context::ServerContext;
+pub use cookies::{CookieManager, CookieStore};
+pub use entities::{EntityRegistry, EntityState};
+pub use events::{Event, EventBus, Listener};
+pub use handler::ConnectionHandler;
+pub use health::HealthManager;
+pub use inventory::Container;
 pub use minecraft::MinecraftServer;
+pub use ops::{OpEntry, OpList};
+pub use player_list::PlayerList;
+pub use plugin_channel::{PluginChannelHandler, PluginChannelRegistry};
+pub use scoreboard::{Scoreboard, Team, TeamManager};
+pub use signs::SignStore;
+pub use sound::SoundRef;
+pub use status::FaviconLoader;
+pub use suggestions::{CommandListSuggestionProvider, CommandSuggestionProvider, Suggestions};
+pub use tab_list::TabListManager;
+pub use tick::{Scheduler, TaskHandle, TickLoop};
+pub use title::TitleBuilder;
+pub use tps::{CircularBuffer, TickTimes};
+pub use transfer::TransferManager;
+pub use whitelist::{Whitelist, WhitelistEntry};
+pub use world::{WeatherState, WorldTime};
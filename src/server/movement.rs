@@ -0,0 +1,117 @@
+//! Movement validation
+//!
+//! Clients simulate their own player and report where it ended up. The
+//! [`MovementValidator`] refuses moves that no client could legitimately
+//! make, so the player can be teleported back to where it last was.
+
+/// Largest distance a player may move in one packet, in blocks
+pub const MAX_MOVEMENT_PER_PACKET: f64 = 100.0;
+
+/// Verdict on a reported move
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MovementCheck {
+    /// The move is plausible and the new position was recorded
+    Accepted,
+    /// The move was made before the client confirmed a teleport, so it
+    /// refers to a position the server no longer has the player at
+    Ignored,
+    /// The move is impossible; the player belongs at this position
+    Rejected(f64, f64, f64),
+}
+
+/// Checks the positions a client reports for its player
+#[derive(Debug, Clone, Default)]
+pub struct MovementValidator {
+    /// Last accepted position, if any
+    last_valid: Option<(f64, f64, f64)>,
+    /// Teleport the client has not confirmed yet
+    pending_teleport: Option<i32>,
+}
+
+impl MovementValidator {
+    /// Create a validator that accepts the first reported position
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Last accepted position
+    pub fn last_valid(&self) -> Option<(f64, f64, f64)> {
+        self.last_valid
+    }
+
+    /// Record that the client was teleported, ignoring moves until it
+    /// confirms the teleport
+    pub fn teleported(&mut self, teleport_id: i32, position: (f64, f64, f64)) {
+        self.last_valid = Some(position);
+        self.pending_teleport = Some(teleport_id);
+    }
+
+    /// Record a teleport confirmation, returning whether it was for the
+    /// pending teleport
+    pub fn confirm_teleport(&mut self, teleport_id: i32) -> bool {
+        if self.pending_teleport == Some(teleport_id) {
+            self.pending_teleport = None;
+            return true;
+        }
+        false
+    }
+
+    /// Check a reported position, recording it if it is accepted
+    pub fn check(&mut self, x: f64, y: f64, z: f64) -> MovementCheck {
+        if self.pending_teleport.is_some() {
+            return MovementCheck::Ignored;
+        }
+        let valid = x.is_finite()
+            && y.is_finite()
+            && z.is_finite()
+            && self.last_valid.is_none_or(|(last_x, last_y, last_z)| {
+                let distance_squared =
+                    (x - last_x).powi(2) + (y - last_y).powi(2) + (z - last_z).powi(2);
+                distance_squared <= MAX_MOVEMENT_PER_PACKET * MAX_MOVEMENT_PER_PACKET
+            });
+
+        match (valid, self.last_valid) {
+            (false, Some((last_x, last_y, last_z))) => {
+                MovementCheck::Rejected(last_x, last_y, last_z)
+            }
+            (false, None) => MovementCheck::Ignored,
+            (true, _) => {
+                self.last_valid = Some((x, y, z));
+                MovementCheck::Accepted
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_impossible_moves_are_rejected() {
+        let mut validator = MovementValidator::new();
+        assert_eq!(validator.check(0.5, 64.0, 0.5), MovementCheck::Accepted);
+        assert_eq!(validator.check(60.5, 64.0, 80.5), MovementCheck::Accepted);
+
+        assert_eq!(
+            validator.check(60.5, 64.0, 181.0),
+            MovementCheck::Rejected(60.5, 64.0, 80.5)
+        );
+        assert_eq!(
+            validator.check(f64::NAN, 64.0, 80.5),
+            MovementCheck::Rejected(60.5, 64.0, 80.5)
+        );
+        assert_eq!(validator.last_valid(), Some((60.5, 64.0, 80.5)));
+    }
+
+    #[test]
+    fn test_moves_wait_for_teleport_confirm() {
+        let mut validator = MovementValidator::new();
+        validator.teleported(3, (1000.0, 64.0, 0.0));
+        assert_eq!(validator.check(0.0, 64.0, 0.0), MovementCheck::Ignored);
+
+        assert!(!validator.confirm_teleport(2));
+        assert!(validator.confirm_teleport(3));
+        assert_eq!(validator.check(1001.0, 64.0, 0.0), MovementCheck::Accepted);
+    }
+}
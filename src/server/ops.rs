@@ -0,0 +1,155 @@
+//! Server operators
+//!
+//! Operators can run privileged commands, up to their permission level.
+//! They are saved to `ops.json` in the same format as vanilla.
+
+use crate::error::Result;
+use crate::protocol::types::McUuid;
+use crate::storage::json_list;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Name of the operator list file in the server directory
+pub const OP_LIST_FILE: &str = "ops.json";
+
+/// Highest permission level, which can run every command
+pub const MAX_PERMISSION_LEVEL: u8 = 4;
+
+/// An operator
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpEntry {
+    /// Player UUID
+    pub uuid: McUuid,
+    /// Name of the player when they were made an operator
+    pub name: String,
+    /// Permission level, from 1 to [`MAX_PERMISSION_LEVEL`]
+    pub level: u8,
+    /// Whether the player can join when the server is full
+    #[serde(rename = "bypassesPlayerLimit")]
+    pub bypass_player_limit: bool,
+}
+
+impl OpEntry {
+    /// Create an operator that does not bypass the player limit
+    pub fn new(uuid: McUuid, name: impl Into<String>, level: u8) -> Self {
+        Self {
+            uuid,
+            name: name.into(),
+            level,
+            bypass_player_limit: false,
+        }
+    }
+}
+
+/// The operators of the server
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OpList {
+    /// Operators in the order they were added
+    entries: Vec<OpEntry>,
+}
+
+impl OpList {
+    /// Create a list without operators
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read an operator list from the contents of `ops.json`
+    pub fn from_json(json: &str) -> Result<Self> {
+        let entries = json_list::from_json(json)?;
+        Ok(Self { entries })
+    }
+
+    /// Convert the operator list to the contents of `ops.json`
+    pub fn to_json(&self) -> Result<String> {
+        json_list::to_json(&self.entries)
+    }
+
+    /// Load an operator list file, or an empty list if there is none
+    pub fn load(path: &Path) -> Result<Self> {
+        let entries = json_list::load(path)?;
+        Ok(Self { entries })
+    }
+
+    /// Save the operator list to a file
+    pub fn save(&self, path: &Path) -> Result<()> {
+        json_list::save(path, &self.entries)
+    }
+
+    /// Every operator
+    pub fn entries(&self) -> &[OpEntry] {
+        &self.entries
+    }
+
+    /// Get the entry of an operator
+    pub fn get(&self, uuid: &McUuid) -> Option<&OpEntry> {
+        self.entries.iter().find(|entry| entry.uuid == *uuid)
+    }
+
+    /// Find an operator by name
+    pub fn find_by_name(&self, name: &str) -> Option<&OpEntry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Permission level of a player, 0 if they are not an operator
+    pub fn level(&self, uuid: &McUuid) -> u8 {
+        self.get(uuid).map_or(0, |entry| entry.level)
+    }
+
+    /// Make a player an operator, returning false if they already were one
+    pub fn add(&mut self, entry: OpEntry) -> bool {
+        if self.get(&entry.uuid).is_some() {
+            return false;
+        }
+        self.entries.push(entry);
+        true
+    }
+
+    /// Take away a player's operator status, returning their entry
+    pub fn remove(&mut self, uuid: &McUuid) -> Option<OpEntry> {
+        let index = self.entries.iter().position(|entry| entry.uuid == *uuid)?;
+        Some(self.entries.remove(index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vanilla_format_roundtrip() {
+        let uuid = McUuid::new_v4();
+        let json = format!(
+            r#"[
+  {{
+    "uuid": "{}",
+    "name": "Steve",
+    "level": 4,
+    "bypassesPlayerLimit": false
+  }}
+]"#,
+            uuid
+        );
+        let ops = OpList::from_json(&json).unwrap();
+        assert_eq!(ops.entries(), [OpEntry::new(uuid, "Steve", 4)]);
+        assert_eq!(ops.to_json().unwrap(), json);
+        assert!(OpList::from_json(r#"[{"name": "Steve"}]"#).is_err());
+    }
+
+    #[test]
+    fn test_levels() {
+        let mut ops = OpList::new();
+        let (steve, alex) = (McUuid::new_v4(), McUuid::new_v4());
+        assert!(ops.add(OpEntry::new(steve, "Steve", 2)));
+        assert!(!ops.add(OpEntry::new(steve, "Steve", 4)));
+        assert_eq!(ops.level(&steve), 2);
+        assert_eq!(ops.level(&alex), 0);
+        assert_eq!(ops.find_by_name("steve").unwrap().uuid, steve);
+
+        assert_eq!(ops.remove(&steve).unwrap().name, "Steve");
+        assert!(ops.remove(&steve).is_none());
+        assert_eq!(ops.level(&steve), 0);
+    }
+}
@@ -0,0 +1,155 @@
+//! Block placement
+//!
+//! A block is placed into the clicked block if that is air, or next to the
+//! clicked face otherwise. Both blocks and entities standing in the way stop
+//! the placement.
+
+use crate::error::Result;
+use crate::game::world::{BlockState, World};
+use crate::protocol::packets::play::BlockFace;
+use crate::protocol::types::Position;
+use crate::server::entities::EntityState;
+
+/// Result of trying to place a block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placement {
+    /// The block was placed at this position
+    Placed(Position),
+    /// The position is taken by this block state, or by an entity when the
+    /// block there is air
+    Blocked(Position, BlockState),
+}
+
+/// Place a block against a face of the clicked block
+///
+/// Chunks the block would go in are loaded first.
+pub fn place_block(
+    world: &mut World,
+    entities: &[EntityState],
+    clicked: Position,
+    face: BlockFace,
+    block_state: BlockState,
+) -> Result<Placement> {
+    let target = match block_at(world, clicked)?.is_air() {
+        true => clicked,
+        false => face.offset(clicked),
+    };
+    let current = block_at(world, target)?;
+    if !current.is_air()
+        || entities
+            .iter()
+            .any(|entity| entity.intersects_block(target))
+    {
+        return Ok(Placement::Blocked(target, current));
+    }
+    if !world.set_block(target, block_state)? {
+        return Ok(Placement::Blocked(target, current));
+    }
+    Ok(Placement::Placed(target))
+}
+
+/// Get a block, loading its chunk, with positions outside the world as air
+pub fn block_at(world: &World, position: Position) -> Result<BlockState> {
+    Ok(world.get_block(position)?.unwrap_or(BlockState::AIR))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::world::ChunkStore;
+    use crate::game::world::generators::FlatWorldGenerator;
+    use crate::game::world::generators::flat::GRASS_BLOCK;
+    use crate::protocol::types::McUuid;
+    use crate::server::entities::PLAYER_ENTITY_TYPE;
+    use std::sync::Arc;
+
+    /// A world of the default flat chunks, whose grass is at y -61
+    fn flat_world() -> World {
+        let chunks = ChunkStore::new(Box::new(FlatWorldGenerator::default()));
+        World::new("world".to_string(), 0, Arc::new(chunks))
+    }
+
+    #[test]
+    fn test_place_against_face() {
+        let mut world = flat_world();
+        let stone = BlockState(1);
+        let placed = place_block(
+            &mut world,
+            &[],
+            Position::new(2, -61, 2),
+            BlockFace::Top,
+            stone,
+        );
+        assert_eq!(placed.unwrap(), Placement::Placed(Position::new(2, -60, 2)));
+        assert_eq!(
+            world.get_block(Position::new(2, -60, 2)).unwrap(),
+            Some(stone)
+        );
+        assert_eq!(
+            world.get_block(Position::new(2, -61, 2)).unwrap(),
+            Some(BlockState(GRASS_BLOCK))
+        );
+
+        // Clicking air places into it
+        let placed = place_block(
+            &mut world,
+            &[],
+            Position::new(2, 70, 2),
+            BlockFace::Top,
+            stone,
+        );
+        assert_eq!(placed.unwrap(), Placement::Placed(Position::new(2, 70, 2)));
+        assert_eq!(world.take_mutations().len(), 2);
+    }
+
+    #[test]
+    fn test_blocked_placement() {
+        let mut world = flat_world();
+        let stone = BlockState(1);
+        world
+            .set_block(Position::new(2, -60, 3), BlockState(14))
+            .unwrap();
+        world.take_mutations();
+
+        // Another block is in the way
+        let placed = place_block(
+            &mut world,
+            &[],
+            Position::new(2, -61, 3),
+            BlockFace::Top,
+            stone,
+        );
+        assert_eq!(
+            placed.unwrap(),
+            Placement::Blocked(Position::new(2, -60, 3), BlockState(14))
+        );
+
+        // A player is standing there
+        let player = EntityState::new(McUuid::new_v4(), PLAYER_ENTITY_TYPE, 0.5, -60.0, 0.5);
+        let placed = place_block(
+            &mut world,
+            &[player],
+            Position::new(0, -61, 0),
+            BlockFace::Top,
+            stone,
+        );
+        assert_eq!(
+            placed.unwrap(),
+            Placement::Blocked(Position::new(0, -60, 0), BlockState::AIR)
+        );
+
+        // Nothing is placed above the world
+        let placed = place_block(
+            &mut world,
+            &[],
+            Position::new(0, 320, 0),
+            BlockFace::Top,
+            stone,
+        );
+        assert_eq!(
+            placed.unwrap(),
+            Placement::Blocked(Position::new(0, 320, 0), BlockState::AIR)
+        );
+        assert!(world.take_mutations().is_empty());
+    }
+}
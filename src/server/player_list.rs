@@ -0,0 +1,423 @@
+//! Player list
+//!
+//! This module tracks the players that are in the play state together with
+//! a channel to their connection, so packets can be sent to any of them.
+
+use crate::error::Result;
+use crate::game::player::GameMode;
+use crate::network::{PacketSender, RawPacket};
+use crate::protocol::packets::Packet;
+use crate::protocol::packets::login::Property;
+use crate::protocol::packets::play::{PlayerChatPacket, PlayerInfoUpdatePacket};
+use crate::protocol::types::{McUuid, VarInt};
+use crate::server::scoreboard::Scoreboard;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Tab list information about a player
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerInfo {
+    /// Player username
+    pub username: String,
+    /// Current game mode
+    pub game_mode: GameMode,
+    /// Latency in milliseconds
+    pub ping: i32,
+    /// Name shown in the tab list instead of the username
+    pub display_name: Option<String>,
+    /// Profile properties, such as the skin textures
+    pub properties: Vec<Property>,
+    /// Chat mode from the client's settings (0=Enabled, 1=Commands only,
+    /// 2=Hidden)
+    pub chat_mode: i32,
+}
+
+impl PlayerInfo {
+    /// Chat mode of players who see messages from other players
+    pub const CHAT_ENABLED: i32 = 0;
+
+    /// Create player info for a newly joined player
+    pub fn new(username: String, game_mode: GameMode) -> Self {
+        Self {
+            username,
+            game_mode,
+            ping: 0,
+            display_name: None,
+            properties: Vec::new(),
+            chat_mode: Self::CHAT_ENABLED,
+        }
+    }
+
+    /// Set the profile properties sent to other players
+    pub fn with_properties(mut self, properties: Vec<Property>) -> Self {
+        self.properties = properties;
+        self
+    }
+
+    /// Set the chat mode from the client's settings
+    pub fn with_chat_mode(mut self, chat_mode: i32) -> Self {
+        self.chat_mode = chat_mode;
+        self
+    }
+}
+
+/// A player in the list and the channel to their connection
+struct PlayerEntry {
+    /// Tab list information
+    info: PlayerInfo,
+    /// Packets sent here are written to the player's connection
+    sender: PacketSender,
+    /// Number of player chat packets sent to the player
+    chat_index: i32,
+}
+
+/// Thread-safe list of players in the play state
+///
+/// The list also keeps the [`Scoreboard::ping`] objective, holding the
+/// latency of every player in it.
+#[derive(Clone)]
+pub struct PlayerList {
+    /// Players keyed by UUID
+    players: Arc<RwLock<HashMap<McUuid, PlayerEntry>>>,
+    /// Latency of each player, by username
+    ping_scoreboard: Arc<RwLock<Scoreboard>>,
+}
+
+impl PlayerList {
+    /// Create an empty player list
+    pub fn new() -> Self {
+        Self {
+            players: Arc::default(),
+            ping_scoreboard: Arc::new(RwLock::new(Scoreboard::ping())),
+        }
+    }
+
+    /// Add a player, replacing any player with the same UUID, and register
+    /// their ping score
+    ///
+    /// Clients are not told about the new score; see
+    /// [`PlayerList::ping_scoreboard`].
+    pub async fn add_player(&self, uuid: McUuid, info: PlayerInfo, sender: PacketSender) {
+        self.ping_scoreboard
+            .write()
+            .await
+            .set_score(&info.username, info.ping);
+        let mut players = self.players.write().await;
+        players.insert(
+            uuid,
+            PlayerEntry {
+                info,
+                sender,
+                chat_index: 0,
+            },
+        );
+    }
+
+    /// Remove a player and their ping score
+    pub async fn remove_player(&self, uuid: &McUuid) -> Option<PlayerInfo> {
+        let mut players = self.players.write().await;
+        let info = players.remove(uuid).map(|entry| entry.info)?;
+        self.ping_scoreboard
+            .write()
+            .await
+            .remove_score(&info.username);
+        Some(info)
+    }
+
+    /// Get a copy of the ping objective and its scores
+    pub async fn ping_scoreboard(&self) -> Scoreboard {
+        self.ping_scoreboard.read().await.clone()
+    }
+
+    /// Record a player's latency and send their new ping score to every
+    /// player, returning whether the player is in the list
+    pub async fn update_ping(&self, uuid: &McUuid, ping: i32) -> Result<bool> {
+        let username = {
+            let mut players = self.players.write().await;
+            let Some(entry) = players.get_mut(uuid) else {
+                return Ok(false);
+            };
+            entry.info.ping = ping;
+            entry.info.username.clone()
+        };
+        let update = self
+            .ping_scoreboard
+            .write()
+            .await
+            .set_score(&username, ping);
+        self.broadcast(&update).await?;
+        Ok(true)
+    }
+
+    /// Record a player's game mode and show it to every player, returning
+    /// whether the player is in the list
+    pub async fn update_game_mode(&self, uuid: &McUuid, game_mode: GameMode) -> Result<bool> {
+        {
+            let mut players = self.players.write().await;
+            let Some(entry) = players.get_mut(uuid) else {
+                return Ok(false);
+            };
+            entry.info.game_mode = game_mode;
+        }
+        let update = PlayerInfoUpdatePacket::game_mode(*uuid, game_mode as i32);
+        self.broadcast(&update).await?;
+        Ok(true)
+    }
+
+    /// Record the chat mode from a player's settings, returning whether the
+    /// player is in the list
+    pub async fn update_chat_mode(&self, uuid: &McUuid, chat_mode: i32) -> bool {
+        let mut players = self.players.write().await;
+        let Some(entry) = players.get_mut(uuid) else {
+            return false;
+        };
+        entry.info.chat_mode = chat_mode;
+        true
+    }
+
+    /// Get a player's information
+    pub async fn get_player(&self, uuid: &McUuid) -> Option<PlayerInfo> {
+        let players = self.players.read().await;
+        players.get(uuid).map(|entry| entry.info.clone())
+    }
+
+    /// Find a player by username, ignoring case
+    pub async fn find_by_name(&self, username: &str) -> Option<(McUuid, PlayerInfo)> {
+        let players = self.players.read().await;
+        players
+            .iter()
+            .find(|(_, entry)| entry.info.username.eq_ignore_ascii_case(username))
+            .map(|(uuid, entry)| (*uuid, entry.info.clone()))
+    }
+
+    /// Get every player's UUID and information
+    pub async fn get_all_players(&self) -> Vec<(McUuid, PlayerInfo)> {
+        let players = self.players.read().await;
+        players
+            .iter()
+            .map(|(uuid, entry)| (*uuid, entry.info.clone()))
+            .collect()
+    }
+
+    /// Get the number of players
+    pub async fn len(&self) -> usize {
+        self.players.read().await.len()
+    }
+
+    /// Check whether the list is empty
+    pub async fn is_empty(&self) -> bool {
+        self.players.read().await.is_empty()
+    }
+
+    /// Queue a packet for a single player
+    ///
+    /// Returns `false` if the player is not in the list or has disconnected.
+    pub async fn send_to<P: Packet>(&self, uuid: &McUuid, packet: &P) -> Result<bool> {
+        let packet = RawPacket::from_packet(packet)?;
+        let players = self.players.read().await;
+        Ok(players
+            .get(uuid)
+            .is_some_and(|entry| entry.sender.send(packet).is_ok()))
+    }
+
+    /// Queue the same packet for every player
+    ///
+    /// Returns the number of players the packet was queued for.
+    pub async fn broadcast<P: Packet>(&self, packet: &P) -> Result<usize> {
+        let packet = RawPacket::from_packet(packet)?;
+        self.broadcast_to_all(|_, _| Ok(packet.clone())).await
+    }
+
+    /// Queue the same packet for every player except one
+    ///
+    /// Returns the number of players the packet was queued for.
+    pub async fn broadcast_except<P: Packet>(&self, except: &McUuid, packet: &P) -> Result<usize> {
+        let packet = RawPacket::from_packet(packet)?;
+        let players = self.players.read().await;
+        Ok(players
+            .iter()
+            .filter(|(uuid, entry)| *uuid != except && entry.sender.send(packet.clone()).is_ok())
+            .count())
+    }
+
+    /// Queue a player chat message for every player with chat enabled
+    ///
+    /// Clients check that the messages they receive are numbered in order,
+    /// so each copy gets the recipient's next index. Returns the number of
+    /// players the message was queued for.
+    pub async fn broadcast_player_chat(&self, packet: &PlayerChatPacket) -> Result<usize> {
+        let mut packet = packet.clone();
+        let mut players = self.players.write().await;
+        let mut sent = 0;
+        for entry in players.values_mut() {
+            if entry.info.chat_mode != PlayerInfo::CHAT_ENABLED {
+                continue;
+            }
+            packet.global_index = VarInt(entry.chat_index);
+            if entry.sender.send(RawPacket::from_packet(&packet)?).is_ok() {
+                entry.chat_index += 1;
+                sent += 1;
+            }
+        }
+        Ok(sent)
+    }
+
+    /// Queue a packet built for each player
+    ///
+    /// The closure receives the recipient's UUID and information. Players
+    /// whose connection has already closed are skipped. Returns the number of
+    /// players a packet was queued for.
+    pub async fn broadcast_to_all<F>(&self, mut make_packet: F) -> Result<usize>
+    where
+        F: FnMut(&McUuid, &PlayerInfo) -> Result<RawPacket>,
+    {
+        let players = self.players.read().await;
+        let mut sent = 0;
+        for (uuid, entry) in players.iter() {
+            if entry.sender.send(make_packet(uuid, &entry.info)?).is_ok() {
+                sent += 1;
+            }
+        }
+        Ok(sent)
+    }
+}
+
+impl Default for PlayerList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::PacketReceiver;
+    use crate::protocol::TextComponent;
+    use crate::protocol::packets::play::{KeepAlivePacket, UpdateScorePacket};
+    use tokio::sync::mpsc;
+
+    async fn add(list: &PlayerList, username: &str) -> (McUuid, PacketReceiver) {
+        let uuid = McUuid::new_v4();
+        let (sender, receiver) = mpsc::unbounded_channel();
+        list.add_player(
+            uuid,
+            PlayerInfo::new(username.to_string(), GameMode::Survival),
+            sender,
+        )
+        .await;
+        (uuid, receiver)
+    }
+
+    #[tokio::test]
+    async fn test_add_remove_lookup() {
+        let list = PlayerList::new();
+        let (uuid, _receiver) = add(&list, "Steve").await;
+        add(&list, "Alex").await;
+
+        assert_eq!(list.len().await, 2);
+        assert_eq!(list.get_player(&uuid).await.unwrap().username, "Steve");
+        assert_eq!(list.get_all_players().await.len(), 2);
+
+        assert_eq!(list.remove_player(&uuid).await.unwrap().username, "Steve");
+        assert!(list.get_player(&uuid).await.is_none());
+        assert_eq!(list.len().await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_reaches_all_players() {
+        let list = PlayerList::new();
+        let (_, mut steve) = add(&list, "Steve").await;
+        let (_, mut alex) = add(&list, "Alex").await;
+
+        let sent = list
+            .broadcast(&KeepAlivePacket { keep_alive_id: 7 })
+            .await
+            .unwrap();
+        assert_eq!(sent, 2);
+
+        for receiver in [&mut steve, &mut alex] {
+            let packet = receiver.try_recv().unwrap();
+            assert_eq!(packet.parse::<KeepAlivePacket>().unwrap().keep_alive_id, 7);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ping_scores() {
+        let list = PlayerList::new();
+        let (uuid, mut steve) = add(&list, "Steve").await;
+        assert_eq!(list.ping_scoreboard().await.score("Steve"), Some(0));
+
+        assert!(list.update_ping(&uuid, 42).await.unwrap());
+        let update = steve.try_recv().unwrap();
+        assert_eq!(update.parse::<UpdateScorePacket>().unwrap().value.0, 42);
+        assert_eq!(list.get_player(&uuid).await.unwrap().ping, 42);
+
+        list.remove_player(&uuid).await;
+        assert!(list.ping_scoreboard().await.is_empty());
+        assert!(!list.update_ping(&uuid, 1).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_except() {
+        let list = PlayerList::new();
+        let (steve_uuid, mut steve) = add(&list, "Steve").await;
+        let (_, mut alex) = add(&list, "Alex").await;
+
+        let sent = list
+            .broadcast_except(&steve_uuid, &KeepAlivePacket { keep_alive_id: 3 })
+            .await
+            .unwrap();
+        assert_eq!(sent, 1);
+        assert!(steve.try_recv().is_err());
+        assert!(alex.try_recv().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_player_chat_is_numbered_per_player() {
+        let list = PlayerList::new();
+        let (steve_uuid, mut steve) = add(&list, "Steve").await;
+        let (alex_uuid, mut alex) = add(&list, "Alex").await;
+        let chat = PlayerChatPacket::unsigned(steve_uuid, TextComponent::text("Steve"), "hi", 0);
+
+        assert_eq!(list.broadcast_player_chat(&chat).await.unwrap(), 2);
+        // Alex hides chat, so Steve's numbering goes on without them
+        assert!(list.update_chat_mode(&alex_uuid, 2).await);
+        assert_eq!(list.broadcast_player_chat(&chat).await.unwrap(), 1);
+
+        for expected in [0, 1] {
+            let packet = steve.try_recv().unwrap();
+            let packet = packet.parse::<PlayerChatPacket>().unwrap();
+            assert_eq!(packet.global_index.0, expected);
+        }
+        let packet = alex.try_recv().unwrap();
+        assert_eq!(
+            packet.parse::<PlayerChatPacket>().unwrap().global_index.0,
+            0
+        );
+        assert!(alex.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_to_all_per_player() {
+        let list = PlayerList::new();
+        let (_, mut steve) = add(&list, "Steve").await;
+        let (_, alex) = add(&list, "Alex").await;
+        drop(alex);
+
+        // Each packet is built for its recipient; closed connections are skipped
+        let sent = list
+            .broadcast_to_all(|_, info| {
+                RawPacket::from_packet(&KeepAlivePacket {
+                    keep_alive_id: info.username.len() as i64,
+                })
+            })
+            .await
+            .unwrap();
+        assert_eq!(sent, 1);
+
+        let packet = steve.try_recv().unwrap();
+        assert_eq!(packet.parse::<KeepAlivePacket>().unwrap().keep_alive_id, 5);
+        assert!(steve.try_recv().is_err());
+    }
+}
@@ -0,0 +1,98 @@
+//! Plugin channels
+//!
+//! Clients and mods exchange custom data over named plugin channels. Handlers
+//! for serverbound messages are registered with the server's
+//! [`PluginChannelRegistry`] before it starts.
+
+use crate::error::Result;
+use crate::protocol::types::{McIdentifier, McUuid};
+use std::collections::HashMap;
+
+/// Handles messages received on a plugin channel
+pub trait PluginChannelHandler {
+    /// Handle a message sent by a player
+    fn handle(&self, player: &McUuid, data: &[u8]) -> Result<()>;
+}
+
+/// Plugin channel handlers, keyed by channel
+#[derive(Default)]
+pub struct PluginChannelRegistry {
+    /// Registered handlers
+    handlers: HashMap<McIdentifier, Box<dyn PluginChannelHandler + Send + Sync>>,
+}
+
+impl PluginChannelRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the handler for a channel, replacing any previous one
+    pub fn register(
+        &mut self,
+        channel: McIdentifier,
+        handler: Box<dyn PluginChannelHandler + Send + Sync>,
+    ) {
+        self.handlers.insert(channel, handler);
+    }
+
+    /// Whether a channel has a handler
+    pub fn is_registered(&self, channel: &McIdentifier) -> bool {
+        self.handlers.contains_key(channel)
+    }
+
+    /// Pass a message to the channel's handler
+    ///
+    /// Returns whether the channel has a handler; messages on unknown
+    /// channels are ignored, as vanilla does.
+    pub fn dispatch(&self, channel: &McIdentifier, player: &McUuid, data: &[u8]) -> Result<bool> {
+        match self.handlers.get(channel) {
+            Some(handler) => handler.handle(player, data).map(|()| true),
+            None => Ok(false),
+        }
+    }
+}
+
+impl std::fmt::Debug for PluginChannelRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.handlers.keys()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// Handler recording every message it receives
+    struct RecordingHandler(Arc<Mutex<Vec<Vec<u8>>>>);
+
+    impl PluginChannelHandler for RecordingHandler {
+        fn handle(&self, _player: &McUuid, data: &[u8]) -> Result<()> {
+            self.0.lock().unwrap().push(data.to_vec());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_dispatch() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let channel: McIdentifier = "mymod:sync".parse().unwrap();
+
+        let mut registry = PluginChannelRegistry::new();
+        registry.register(
+            channel.clone(),
+            Box::new(RecordingHandler(Arc::clone(&received))),
+        );
+        assert!(registry.is_registered(&channel));
+
+        let player = McUuid::new_v4();
+        assert!(registry.dispatch(&channel, &player, &[1, 2]).unwrap());
+        assert!(
+            !registry
+                .dispatch(&McIdentifier::minecraft("other"), &player, &[3])
+                .unwrap()
+        );
+        assert_eq!(*received.lock().unwrap(), vec![vec![1, 2]]);
+    }
+}
@@ -0,0 +1,469 @@
+//! Scoreboards
+//!
+//! A [`Scoreboard`] is the server's copy of one objective and its scores, and
+//! a [`TeamManager`] holds the teams players are grouped in. Their methods
+//! return the packets that bring clients up to date, so callers decide who
+//! sees the change.
+
+use crate::protocol::packets::play::scoreboard::ObjectiveDisplay;
+use crate::protocol::packets::play::{
+    DisplayObjectivePacket, DisplaySlot, ObjectiveAction, ResetScorePacket,
+    ScoreboardObjectivePacket, TeamAction, TeamInfo, TeamPacket, UpdateScorePacket,
+};
+use crate::protocol::text::TextComponent;
+use crate::protocol::types::{McString, VarInt};
+use std::collections::{BTreeMap, HashSet};
+
+pub use crate::protocol::packets::play::{
+    CollisionRule, ScoreboardRenderType, TeamColor, Visibility,
+};
+
+/// Name of the objective holding every player's latency
+pub const PING_OBJECTIVE: &str = "ping";
+
+/// An objective and the scores in it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scoreboard {
+    /// Unique name of the objective
+    pub name: String,
+    /// Title shown to players
+    pub display_name: TextComponent,
+    /// How scores are drawn
+    pub render_type: ScoreboardRenderType,
+    /// Scores by player name
+    scores: BTreeMap<String, i32>,
+}
+
+impl Scoreboard {
+    /// Create an objective without scores
+    pub fn new(
+        name: impl Into<String>,
+        display_name: TextComponent,
+        render_type: ScoreboardRenderType,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            display_name,
+            render_type,
+            scores: BTreeMap::new(),
+        }
+    }
+
+    /// Create the objective of player latencies, in milliseconds
+    pub fn ping() -> Self {
+        Self::new(
+            PING_OBJECTIVE,
+            TextComponent::text("Ping"),
+            ScoreboardRenderType::Integer,
+        )
+    }
+
+    /// Get the score of a player
+    pub fn score(&self, player: &str) -> Option<i32> {
+        self.scores.get(player).copied()
+    }
+
+    /// Number of scores
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    /// Whether there are no scores
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    /// Set the score of a player, returning the packet announcing it
+    pub fn set_score(&mut self, player: &str, value: i32) -> UpdateScorePacket {
+        self.scores.insert(player.to_string(), value);
+        self.score_packet(player, value)
+    }
+
+    /// Remove the score of a player, returning the packet announcing it if
+    /// the player had one
+    pub fn remove_score(&mut self, player: &str) -> Option<ResetScorePacket> {
+        self.scores.remove(player)?;
+        Some(ResetScorePacket {
+            entity_name: McString(player.to_string()),
+            objective_name: Some(McString(self.name.clone())),
+        })
+    }
+
+    /// Build the packet creating the objective on a client
+    pub fn create_packet(&self) -> ScoreboardObjectivePacket {
+        self.objective_packet(ObjectiveAction::Create(self.display()))
+    }
+
+    /// Build the packet applying a new display name or render type
+    pub fn update_packet(&self) -> ScoreboardObjectivePacket {
+        self.objective_packet(ObjectiveAction::Update(self.display()))
+    }
+
+    /// Build the packet removing the objective from a client
+    pub fn remove_packet(&self) -> ScoreboardObjectivePacket {
+        self.objective_packet(ObjectiveAction::Remove)
+    }
+
+    /// Build the packet showing the objective in a display slot
+    pub fn display_packet(&self, slot: DisplaySlot) -> DisplayObjectivePacket {
+        DisplayObjectivePacket {
+            slot,
+            objective_name: McString(self.name.clone()),
+        }
+    }
+
+    /// Build the packet announcing the current score of a player, if they
+    /// have one
+    pub fn score_update(&self, player: &str) -> Option<UpdateScorePacket> {
+        let value = self.score(player)?;
+        Some(self.score_packet(player, value))
+    }
+
+    /// Build the packets of every score, for a client that just created
+    /// the objective
+    pub fn score_packets(&self) -> Vec<UpdateScorePacket> {
+        self.scores
+            .iter()
+            .map(|(player, &value)| self.score_packet(player, value))
+            .collect()
+    }
+
+    /// How the objective is displayed
+    fn display(&self) -> ObjectiveDisplay {
+        ObjectiveDisplay {
+            display_name: self.display_name.clone(),
+            render_type: self.render_type,
+            number_format: None,
+        }
+    }
+
+    /// Build an objective packet for this objective
+    fn objective_packet(&self, action: ObjectiveAction) -> ScoreboardObjectivePacket {
+        ScoreboardObjectivePacket {
+            objective_name: McString(self.name.clone()),
+            action,
+        }
+    }
+
+    /// Build the packet announcing a score
+    fn score_packet(&self, player: &str, value: i32) -> UpdateScorePacket {
+        UpdateScorePacket {
+            entity_name: McString(player.to_string()),
+            objective_name: McString(self.name.clone()),
+            value: VarInt(value),
+            display_name: None,
+            number_format: None,
+        }
+    }
+}
+
+/// A group of players sharing name colors and collision rules
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Team {
+    /// Unique name of the team
+    pub name: String,
+    /// Name of the team shown to players
+    pub display_name: TextComponent,
+    /// Text shown before the members' names
+    pub prefix: TextComponent,
+    /// Text shown after the members' names
+    pub suffix: TextComponent,
+    /// Whether members can hurt each other
+    pub friendly_fire: bool,
+    /// Whether members see invisible teammates
+    pub see_invisible: bool,
+    /// Who sees the members' name tags
+    pub name_tag_visibility: Visibility,
+    /// Which entities push the members
+    pub collision_rule: CollisionRule,
+    /// Color of the members' names
+    pub color: TeamColor,
+    /// Player names and entity UUIDs in the team
+    pub members: HashSet<String>,
+}
+
+impl Team {
+    /// Create an empty team with vanilla's defaults
+    pub fn new(name: impl Into<String>) -> Self {
+        let name = name.into();
+        Self {
+            display_name: TextComponent::text(name.clone()),
+            name,
+            prefix: TextComponent::default(),
+            suffix: TextComponent::default(),
+            friendly_fire: true,
+            see_invisible: true,
+            name_tag_visibility: Visibility::Always,
+            collision_rule: CollisionRule::Always,
+            color: TeamColor::Reset,
+            members: HashSet::new(),
+        }
+    }
+
+    /// Build the packet creating the team and its members on a client
+    pub fn create_packet(&self) -> TeamPacket {
+        let mut members: Vec<&String> = self.members.iter().collect();
+        members.sort();
+        let members = members.into_iter().cloned().map(McString).collect();
+        self.team_packet(TeamAction::Create(self.info(), members))
+    }
+
+    /// Build the packet applying a new display to a client
+    pub fn update_packet(&self) -> TeamPacket {
+        self.team_packet(TeamAction::UpdateInfo(self.info()))
+    }
+
+    /// Build the packet removing the team from a client
+    pub fn remove_packet(&self) -> TeamPacket {
+        self.team_packet(TeamAction::Remove)
+    }
+
+    /// How the team is displayed
+    fn info(&self) -> TeamInfo {
+        TeamInfo {
+            display_name: self.display_name.clone(),
+            friendly_fire: self.friendly_fire,
+            see_invisible: self.see_invisible,
+            name_tag_visibility: self.name_tag_visibility,
+            collision_rule: self.collision_rule,
+            color: self.color,
+            prefix: self.prefix.clone(),
+            suffix: self.suffix.clone(),
+        }
+    }
+
+    /// Build a team packet for this team
+    fn team_packet(&self, action: TeamAction) -> TeamPacket {
+        TeamPacket {
+            team_name: McString(self.name.clone()),
+            action,
+        }
+    }
+}
+
+/// The teams of the server, each member being in at most one
+#[derive(Debug, Clone, Default)]
+pub struct TeamManager {
+    /// Teams by name
+    teams: BTreeMap<String, Team>,
+}
+
+impl TeamManager {
+    /// Create a manager without teams
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get a team by name
+    pub fn team(&self, name: &str) -> Option<&Team> {
+        self.teams.get(name)
+    }
+
+    /// Get the team a player or entity is in
+    pub fn team_of(&self, member: &str) -> Option<&Team> {
+        self.teams
+            .values()
+            .find(|team| team.members.contains(member))
+    }
+
+    /// Iterate over the teams, by name
+    pub fn teams(&self) -> impl Iterator<Item = &Team> {
+        self.teams.values()
+    }
+
+    /// Add a team, returning the packet creating it, or `None` if a team
+    /// with its name exists
+    ///
+    /// Members already in another team are moved out of it.
+    pub fn add_team(&mut self, team: Team) -> Option<TeamPacket> {
+        if self.teams.contains_key(&team.name) {
+            return None;
+        }
+        for other in self.teams.values_mut() {
+            other
+                .members
+                .retain(|member| !team.members.contains(member));
+        }
+        let packet = team.create_packet();
+        self.teams.insert(team.name.clone(), team);
+        Some(packet)
+    }
+
+    /// Change how a team is displayed, returning the packet applying it
+    ///
+    /// Returns `None` if there is no such team.
+    pub fn update_team(
+        &mut self,
+        name: &str,
+        update: impl FnOnce(&mut Team),
+    ) -> Option<TeamPacket> {
+        let team = self.teams.get_mut(name)?;
+        let members = team.members.clone();
+        update(team);
+        // Members change through add_to_team and remove_from_team only
+        team.name = name.to_string();
+        team.members = members;
+        Some(team.update_packet())
+    }
+
+    /// Remove a team, returning the packet removing it if it existed
+    pub fn remove_team(&mut self, name: &str) -> Option<TeamPacket> {
+        self.teams.remove(name).map(|team| team.remove_packet())
+    }
+
+    /// Put a player or entity in a team, taking them out of their previous
+    /// one, and return the packet announcing it
+    ///
+    /// Returns `None` if there is no such team or the member already is in
+    /// it. Clients move the member out of its previous team themselves.
+    pub fn add_to_team(&mut self, name: &str, member: &str) -> Option<TeamPacket> {
+        if self.team(name)?.members.contains(member) {
+            return None;
+        }
+        for team in self.teams.values_mut() {
+            team.members.remove(member);
+        }
+        let team = self.teams.get_mut(name)?;
+        team.members.insert(member.to_string());
+        Some(team.team_packet(TeamAction::AddEntities(vec![McString(member.to_string())])))
+    }
+
+    /// Take a player or entity out of their team, returning the packet
+    /// announcing it if they were in one
+    pub fn remove_from_team(&mut self, member: &str) -> Option<TeamPacket> {
+        let team = self
+            .teams
+            .values_mut()
+            .find(|team| team.members.contains(member))?;
+        team.members.remove(member);
+        Some(team.team_packet(TeamAction::RemoveEntities(vec![McString(
+            member.to_string(),
+        )])))
+    }
+
+    /// Build the packets creating every team, for a client that just joined
+    pub fn create_packets(&self) -> Vec<TeamPacket> {
+        self.teams.values().map(Team::create_packet).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scores() {
+        let mut scoreboard = Scoreboard::new(
+            "kills",
+            TextComponent::text("Kills"),
+            ScoreboardRenderType::Integer,
+        );
+        let update = scoreboard.set_score("Steve", 3);
+        assert_eq!(update.entity_name.0, "Steve");
+        assert_eq!(update.objective_name.0, "kills");
+        assert_eq!(update.value.0, 3);
+        scoreboard.set_score("Alex", 1);
+        scoreboard.set_score("Steve", 4);
+        assert_eq!(scoreboard.score("Steve"), Some(4));
+        assert_eq!(scoreboard.score_packets().len(), 2);
+
+        let reset = scoreboard.remove_score("Steve").unwrap();
+        assert_eq!(reset.objective_name.unwrap().0, "kills");
+        assert!(scoreboard.remove_score("Steve").is_none());
+        assert_eq!(scoreboard.len(), 1);
+    }
+
+    #[test]
+    fn test_objective_packets() {
+        let mut scoreboard = Scoreboard::ping();
+        assert!(matches!(
+            scoreboard.create_packet().action,
+            ObjectiveAction::Create(ObjectiveDisplay {
+                render_type: ScoreboardRenderType::Integer,
+                ..
+            })
+        ));
+
+        scoreboard.render_type = ScoreboardRenderType::Hearts;
+        assert!(matches!(
+            scoreboard.update_packet().action,
+            ObjectiveAction::Update(ObjectiveDisplay {
+                render_type: ScoreboardRenderType::Hearts,
+                ..
+            })
+        ));
+        assert_eq!(scoreboard.remove_packet().action, ObjectiveAction::Remove);
+        assert_eq!(
+            scoreboard
+                .display_packet(DisplaySlot::List)
+                .objective_name
+                .0,
+            PING_OBJECTIVE
+        );
+    }
+
+    #[test]
+    fn test_team_packets() {
+        let mut team = Team::new("red");
+        team.color = TeamColor::Named(crate::protocol::text::NamedColor::Red);
+        team.members.insert("Steve".to_string());
+        team.members.insert("Alex".to_string());
+        let TeamAction::Create(info, members) = team.create_packet().action else {
+            unreachable!("a team is created with its info and members");
+        };
+        assert_eq!(info.display_name, TextComponent::text("red"));
+        assert_eq!(info.color, team.color);
+        assert_eq!(
+            members,
+            vec![McString("Alex".to_string()), McString("Steve".to_string())]
+        );
+        assert!(matches!(
+            team.update_packet().action,
+            TeamAction::UpdateInfo(_)
+        ));
+        assert_eq!(team.remove_packet().action, TeamAction::Remove);
+    }
+
+    #[test]
+    fn test_team_manager() {
+        let mut teams = TeamManager::new();
+        let mut red = Team::new("red");
+        red.members.insert("Steve".to_string());
+        assert!(teams.add_team(red).is_some());
+        assert!(teams.add_team(Team::new("red")).is_none());
+        teams.add_team(Team::new("blue"));
+
+        // Joining a team leaves the previous one
+        let packet = teams.add_to_team("blue", "Steve").unwrap();
+        assert_eq!(packet.team_name.0, "blue");
+        assert_eq!(
+            packet.action,
+            TeamAction::AddEntities(vec![McString("Steve".to_string())])
+        );
+        assert!(teams.team("red").unwrap().members.is_empty());
+        assert_eq!(teams.team_of("Steve").unwrap().name, "blue");
+        assert!(teams.add_to_team("blue", "Steve").is_none());
+        assert!(teams.add_to_team("green", "Alex").is_none());
+
+        let packet = teams
+            .update_team("blue", |team| team.friendly_fire = false)
+            .unwrap();
+        assert!(matches!(
+            packet.action,
+            TeamAction::UpdateInfo(TeamInfo {
+                friendly_fire: false,
+                ..
+            })
+        ));
+
+        let packet = teams.remove_from_team("Steve").unwrap();
+        assert_eq!(
+            packet.action,
+            TeamAction::RemoveEntities(vec![McString("Steve".to_string())])
+        );
+        assert!(teams.remove_from_team("Steve").is_none());
+        assert_eq!(teams.create_packets().len(), 2);
+        assert!(teams.remove_team("red").is_some());
+        assert!(teams.remove_team("red").is_none());
+    }
+}
@@ -0,0 +1,151 @@
+//! Signs
+//!
+//! The server keeps the text of every sign that was edited, and sends it to
+//! clients as block entity data.
+
+use crate::protocol::nbt::{NbtCompound, NbtTag};
+use crate::protocol::packets::play::BlockEntityDataPacket;
+use crate::protocol::packets::play::block_entity::SIGN_BLOCK_ENTITY;
+use crate::protocol::packets::play::sign::SIGN_LINES;
+use crate::protocol::types::{Position, VarInt};
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+
+/// Identifier of the sign block players place
+pub const SIGN_BLOCK: &str = "minecraft:oak_sign";
+
+/// The text on one side of a sign
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SignText {
+    /// Lines from top to bottom
+    pub lines: [String; SIGN_LINES],
+}
+
+impl SignText {
+    /// Convert to the NBT of one side of a sign block entity, in black
+    /// without glowing
+    fn to_nbt(&self) -> NbtTag {
+        let messages = self
+            .lines
+            .iter()
+            .map(|line| NbtTag::String(line.clone()))
+            .collect();
+        NbtTag::Compound(
+            NbtCompound::new()
+                .with("messages", NbtTag::List(messages))
+                .with("color", "black")
+                .with("has_glowing_text", false),
+        )
+    }
+}
+
+/// The text on both sides of a sign
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Sign {
+    /// Text facing the player who placed the sign
+    pub front: SignText,
+    /// Text on the other side
+    pub back: SignText,
+}
+
+impl Sign {
+    /// Convert to the NBT of a sign block entity
+    pub fn to_nbt(&self) -> NbtTag {
+        NbtTag::Compound(
+            NbtCompound::new()
+                .with("front_text", self.front.to_nbt())
+                .with("back_text", self.back.to_nbt())
+                .with("is_waxed", false),
+        )
+    }
+
+    /// Build the packet showing the sign at a position to clients
+    pub fn data_packet(&self, position: Position) -> BlockEntityDataPacket {
+        BlockEntityDataPacket {
+            position,
+            block_entity_type: VarInt(SIGN_BLOCK_ENTITY),
+            data: Some(self.to_nbt()),
+        }
+    }
+}
+
+/// Text of the signs in the world, by position
+#[derive(Debug, Default)]
+pub struct SignStore {
+    /// Text of each sign
+    signs: RwLock<HashMap<Position, Sign>>,
+}
+
+impl SignStore {
+    /// Create a store without any signs
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the text of a sign, which is blank until first edited
+    pub async fn get(&self, position: Position) -> Sign {
+        self.signs
+            .read()
+            .await
+            .get(&position)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Replace the text of one side of a sign, returning the sign
+    pub async fn set_text(&self, position: Position, front: bool, text: SignText) -> Sign {
+        let mut signs = self.signs.write().await;
+        let sign = signs.entry(position).or_default();
+        if front {
+            sign.front = text;
+        } else {
+            sign.back = text;
+        }
+        sign.clone()
+    }
+
+    /// Forget a sign, such as when it is broken, returning its text
+    pub async fn remove(&self, position: Position) -> Option<Sign> {
+        self.signs.write().await.remove(&position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sign_store() {
+        let signs = SignStore::new();
+        let position = Position::new(0, 64, 0);
+        assert_eq!(signs.get(position).await, Sign::default());
+
+        let text = SignText {
+            lines: ["Hello".into(), String::new(), String::new(), "World".into()],
+        };
+        let sign = signs.set_text(position, false, text.clone()).await;
+        assert_eq!(sign.back, text);
+        assert_eq!(signs.get(position).await.front, SignText::default());
+        assert_eq!(signs.remove(position).await, Some(sign));
+        assert_eq!(signs.remove(position).await, None);
+    }
+
+    #[test]
+    fn test_sign_nbt() {
+        let mut sign = Sign::default();
+        sign.front.lines[0] = "Spawn".to_string();
+        let packet = sign.data_packet(Position::new(1, 2, 3));
+        let data = packet.data.unwrap();
+        let front = data
+            .as_compound()
+            .and_then(|sign| sign.get("front_text"))
+            .and_then(NbtTag::as_compound)
+            .unwrap();
+        let Some(NbtTag::List(messages)) = front.get("messages") else {
+            unreachable!("front text without messages");
+        };
+        assert_eq!(messages.len(), SIGN_LINES);
+        assert_eq!(messages[0].as_str(), Some("Spawn"));
+        assert_eq!(front.get("color").and_then(NbtTag::as_str), Some("black"));
+    }
+}
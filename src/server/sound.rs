@@ -0,0 +1,137 @@
+//! Playing sounds to players
+//!
+//! Sounds are referred to by name. Names found in the sound event registry
+//! are sent as their ID, anything else inline by name.
+
+use crate::data::SoundRegistry;
+use crate::error::Result;
+use crate::network::RawPacket;
+use crate::protocol::packets::play::sound::{SoundPlacement, SoundSource, to_fixed_point};
+use crate::protocol::packets::play::{NamedSoundEffectPacket, SoundEffectPacket};
+use crate::protocol::types::{McIdentifier, Position};
+use crate::server::ServerContext;
+
+/// A sound event to play, by identifier
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoundRef {
+    /// Identifier of the sound event
+    pub name: McIdentifier,
+    /// Category of the sound
+    pub source: SoundSource,
+    /// Distance the sound is heard from regardless of volume, only sent
+    /// for sounds played by name
+    pub fixed_range: Option<f32>,
+}
+
+impl SoundRef {
+    /// Refer to a sound event in the master category
+    pub fn new(name: McIdentifier) -> Self {
+        Self {
+            name,
+            source: SoundSource::Master,
+            fixed_range: None,
+        }
+    }
+
+    /// Set the category of the sound
+    pub fn with_source(mut self, source: SoundSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Build the packet playing this sound, by ID if the registry knows it
+    /// and by name otherwise
+    pub fn packet(&self, sounds: &SoundRegistry, placement: SoundPlacement) -> Result<RawPacket> {
+        match sounds.id_of(&self.name.to_string()) {
+            Some(sound_id) => RawPacket::from_packet(&SoundEffectPacket {
+                sound_id,
+                placement,
+            }),
+            None => RawPacket::from_packet(&NamedSoundEffectPacket {
+                sound_name: self.name.clone(),
+                fixed_range: self.fixed_range,
+                placement,
+            }),
+        }
+    }
+}
+
+impl ServerContext {
+    /// Play a sound at the center of a block to every player, returning how
+    /// many players it was sent to
+    pub async fn play_sound_at(
+        &self,
+        location: Position,
+        sound: &SoundRef,
+        volume: f32,
+        pitch: f32,
+    ) -> Result<usize> {
+        let placement = SoundPlacement {
+            source: sound.source,
+            x: to_fixed_point(f64::from(location.x) + 0.5),
+            y: to_fixed_point(f64::from(location.y) + 0.5),
+            z: to_fixed_point(f64::from(location.z) + 0.5),
+            volume,
+            pitch,
+            seed: rand::random(),
+        };
+        let packet = sound.packet(self.data.sounds(), placement)?;
+        self.player_list
+            .broadcast_to_all(|_, _| Ok(packet.clone()))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ServerConfig;
+    use crate::game::player::GameMode;
+    use crate::protocol::packets::Packet;
+    use crate::protocol::types::McUuid;
+    use crate::server::player_list::PlayerInfo;
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn test_play_sound_at() {
+        let context = ServerContext::for_tests(ServerConfig::new());
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        context
+            .player_list
+            .add_player(
+                McUuid::new_v4(),
+                PlayerInfo::new("Steve".to_string(), GameMode::Survival),
+                sender,
+            )
+            .await;
+
+        let registered = SoundRef::new(McIdentifier::minecraft("entity.allay.hurt"));
+        let sent = context
+            .play_sound_at(Position::new(1, 64, -1), &registered, 1.0, 1.0)
+            .await
+            .unwrap();
+        assert_eq!(sent, 1);
+        let packet = receiver
+            .try_recv()
+            .unwrap()
+            .parse::<SoundEffectPacket>()
+            .unwrap();
+        assert_eq!(packet.sound_id, 3);
+        assert_eq!(
+            (packet.placement.x, packet.placement.y, packet.placement.z),
+            (12, 516, -4)
+        );
+
+        let custom = SoundRef::new(McIdentifier::new("custom", "horn").unwrap())
+            .with_source(SoundSource::Record);
+        context
+            .play_sound_at(Position::new(0, 0, 0), &custom, 2.0, 0.5)
+            .await
+            .unwrap();
+        let packet = receiver.try_recv().unwrap();
+        assert_eq!(packet.id.0, NamedSoundEffectPacket::ID);
+        let named = packet.parse::<NamedSoundEffectPacket>().unwrap();
+        assert_eq!(named.sound_name, custom.name);
+        assert_eq!(named.placement.source, SoundSource::Record);
+    }
+}
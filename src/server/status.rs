@@ -0,0 +1,124 @@
+//! Server list status
+//!
+//! The icon shown next to the server in the server list is read by a
+//! [`FaviconLoader`] the first time it is asked for, and the encoded result
+//! is kept from then on. [`MinecraftServer::new`] asks for it while the
+//! server starts, so a broken icon is reported before any client connects.
+//!
+//! [`MinecraftServer::new`]: crate::server::MinecraftServer::new
+
+use crate::error::Result;
+use crate::favicon;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// File vanilla servers read their icon from
+pub const FAVICON_FILE: &str = "server-icon.png";
+
+/// Loads the server icon once and keeps it for status responses
+#[derive(Debug)]
+pub struct FaviconLoader {
+    /// Path of a 64x64 PNG file, or an already encoded data URL
+    source: String,
+    /// Data URL of the icon, or `None` if there is none to show
+    favicon: OnceLock<Option<String>>,
+}
+
+impl FaviconLoader {
+    /// Create a loader for a PNG file or a `data:image/png;base64,` URL
+    pub fn new(source: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            favicon: OnceLock::new(),
+        }
+    }
+
+    /// Read a PNG file and encode it as a data URL
+    ///
+    /// Returns `None` if the file does not exist, since the icon is
+    /// optional. Files that are not 64x64 PNGs are rejected.
+    pub fn load(path: &Path) -> Result<Option<String>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        favicon::load_favicon_from_file(path).map(Some)
+    }
+
+    /// Data URL of the icon, loading it on the first call
+    ///
+    /// Icons that fail to load are logged and left out of the status.
+    pub fn favicon(&self) -> Option<&str> {
+        self.favicon
+            .get_or_init(|| {
+                if self.source.starts_with("data:image/png;base64,") {
+                    tracing::info!(
+                        "Using provided favicon data URL (length: {})",
+                        self.source.len()
+                    );
+                    return Some(self.source.clone());
+                }
+                match Self::load(Path::new(&self.source)) {
+                    Ok(Some(favicon)) => {
+                        tracing::debug!(
+                            "Loaded favicon from: {} (encoded length: {})",
+                            self.source,
+                            favicon.len()
+                        );
+                        Some(favicon)
+                    }
+                    Ok(None) => {
+                        tracing::debug!("No favicon at {}", self.source);
+                        None
+                    }
+                    Err(e) => {
+                        tracing::warn!("Failed to load favicon from {}: {}", self.source, e);
+                        None
+                    }
+                }
+            })
+            .as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_load() {
+        let favicon = FaviconLoader::load(Path::new(FAVICON_FILE))
+            .unwrap()
+            .unwrap();
+        assert!(favicon.starts_with("data:image/png;base64,iVBORw0KGgo"));
+        assert_eq!(
+            FaviconLoader::load(Path::new("missing-icon.png")).unwrap(),
+            None
+        );
+
+        // Icons of other sizes are refused
+        let mut data = fs::read(FAVICON_FILE).unwrap();
+        data[16..20].copy_from_slice(&32u32.to_be_bytes());
+        let path = std::env::temp_dir().join(format!("obsidium-{}-icon.png", std::process::id()));
+        fs::write(&path, data).unwrap();
+        assert!(FaviconLoader::load(&path).is_err());
+        assert_eq!(FaviconLoader::new(path.to_string_lossy()).favicon(), None);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_favicon_is_cached() {
+        let path =
+            std::env::temp_dir().join(format!("obsidium-{}-cached-icon.png", std::process::id()));
+        fs::copy(FAVICON_FILE, &path).unwrap();
+        let loader = FaviconLoader::new(path.to_string_lossy());
+        let favicon = loader.favicon().unwrap().to_string();
+
+        // The file is only read the first time
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loader.favicon(), Some(favicon.as_str()));
+
+        let data_url = "data:image/png;base64,AAAA";
+        assert_eq!(FaviconLoader::new(data_url).favicon(), Some(data_url));
+    }
+}
@@ -0,0 +1,94 @@
+//! Command tab-completion
+//!
+//! Suggestion requests from players are answered by the server's
+//! [`CommandSuggestionProvider`].
+
+use crate::protocol::packets::play::SuggestionMatch;
+use crate::protocol::types::McUuid;
+
+/// Completions for a piece of command text
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Suggestions {
+    /// Start of the text the matches replace, in characters
+    pub start: usize,
+    /// Length of the text the matches replace, in characters
+    pub length: usize,
+    /// Suggested replacements
+    pub matches: Vec<SuggestionMatch>,
+}
+
+/// Completes command text typed by players
+pub trait CommandSuggestionProvider: Send + Sync {
+    /// Suggest completions for the text a player has typed so far
+    fn suggest(&self, player: &McUuid, text: &str) -> Suggestions;
+}
+
+/// Completes command names against a fixed list of commands
+///
+/// Only the command name itself is completed; arguments get no suggestions.
+#[derive(Debug, Clone, Default)]
+pub struct CommandListSuggestionProvider {
+    /// Sorted command names, without the leading `/`
+    commands: Vec<String>,
+}
+
+impl CommandListSuggestionProvider {
+    /// Create a provider completing the given command names
+    pub fn new(commands: impl IntoIterator<Item = String>) -> Self {
+        let mut commands: Vec<String> = commands.into_iter().collect();
+        commands.sort();
+        commands.dedup();
+        Self { commands }
+    }
+}
+
+impl CommandSuggestionProvider for CommandListSuggestionProvider {
+    fn suggest(&self, _player: &McUuid, text: &str) -> Suggestions {
+        let start = usize::from(text.starts_with('/'));
+        let partial = &text[start..];
+        if partial.contains(' ') {
+            return Suggestions {
+                start: text.chars().count(),
+                ..Suggestions::default()
+            };
+        }
+
+        Suggestions {
+            start,
+            length: partial.chars().count(),
+            matches: self
+                .commands
+                .iter()
+                .filter(|command| command.starts_with(partial))
+                .map(|command| SuggestionMatch::new(command.as_str()))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_list_suggestions() {
+        let provider =
+            CommandListSuggestionProvider::new(["tp", "help", "time", "tp"].map(str::to_string));
+        let player = McUuid::new_v4();
+
+        let suggestions = provider.suggest(&player, "/t");
+        assert_eq!((suggestions.start, suggestions.length), (1, 1));
+        assert_eq!(
+            suggestions.matches,
+            vec![SuggestionMatch::new("time"), SuggestionMatch::new("tp")]
+        );
+
+        // Every command matches a lone slash
+        assert_eq!(provider.suggest(&player, "/").matches.len(), 3);
+
+        // Arguments are not completed
+        let suggestions = provider.suggest(&player, "/tp St");
+        assert_eq!(suggestions.start, 6);
+        assert!(suggestions.matches.is_empty());
+    }
+}
@@ -0,0 +1,207 @@
+//! Tab list
+//!
+//! The manager turns the player list into the player info packets clients
+//! fill their tab list from: the whole list for a player that just joined,
+//! and single entries for the players already online. Each player's latency,
+//! measured by their keep alives, is shown to everyone once a minute.
+
+use crate::error::Result;
+use crate::protocol::TextComponent;
+use crate::protocol::packets::play::{
+    PlayerInfoEntry, PlayerInfoRemovePacket, PlayerInfoUpdatePacket,
+};
+use crate::protocol::types::{McUuid, VarInt};
+use crate::server::PlayerList;
+use crate::server::player_list::PlayerInfo;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{RwLock, watch};
+
+/// Build the tab list entry of a player
+pub fn tab_list_entry(uuid: McUuid, info: &PlayerInfo) -> PlayerInfoEntry {
+    PlayerInfoEntry {
+        uuid,
+        name: info.username.clone().into(),
+        properties: info.properties.clone(),
+        game_mode: VarInt(info.game_mode as i32),
+        listed: true,
+        latency: VarInt(info.ping),
+        display_name: info.display_name.as_deref().map(TextComponent::text),
+    }
+}
+
+/// Keeps the tab list of every client in sync with the player list
+#[derive(Clone)]
+pub struct TabListManager {
+    /// Players shown in the tab list
+    player_list: PlayerList,
+    /// Latency of each player, published by their keep alive task
+    latencies: Arc<RwLock<HashMap<McUuid, watch::Receiver<Duration>>>>,
+}
+
+impl TabListManager {
+    /// Create a manager showing the given players
+    pub fn new(player_list: PlayerList) -> Self {
+        Self {
+            player_list,
+            latencies: Arc::default(),
+        }
+    }
+
+    /// Build the packet adding every player to the tab list, for a player
+    /// that just joined
+    pub async fn list_packet(&self) -> PlayerInfoUpdatePacket {
+        let entries = self
+            .player_list
+            .get_all_players()
+            .await
+            .iter()
+            .map(|(uuid, info)| tab_list_entry(*uuid, info))
+            .collect();
+        PlayerInfoUpdatePacket::add_players(entries)
+    }
+
+    /// Add a player that just joined to the tab list of everyone else,
+    /// returning the number of players told
+    pub async fn announce(&self, uuid: &McUuid) -> Result<usize> {
+        let Some(info) = self.player_list.get_player(uuid).await else {
+            return Ok(0);
+        };
+        let packet = PlayerInfoUpdatePacket::add_players(vec![tab_list_entry(*uuid, &info)]);
+        self.player_list.broadcast_except(uuid, &packet).await
+    }
+
+    /// Show a player's latency from now on
+    pub async fn track_latency(&self, uuid: McUuid, latency: watch::Receiver<Duration>) {
+        self.latencies.write().await.insert(uuid, latency);
+    }
+
+    /// Send the latencies that changed since the last call to every player,
+    /// updating their ping scores, and return the number of players told
+    pub async fn broadcast_latency(&self) -> Result<usize> {
+        let mut changed = Vec::new();
+        for (uuid, latency) in self.latencies.write().await.iter_mut() {
+            // A closed channel keeps its last value, so it counts as unchanged
+            if latency.has_changed().unwrap_or(false) {
+                let millis = latency.borrow_and_update().as_millis();
+                changed.push((*uuid, i32::try_from(millis).unwrap_or(i32::MAX)));
+            }
+        }
+        if changed.is_empty() {
+            return Ok(0);
+        }
+        for (uuid, ping) in &changed {
+            self.player_list.update_ping(uuid, *ping).await?;
+        }
+        self.player_list
+            .broadcast(&PlayerInfoUpdatePacket::latency(changed))
+            .await
+    }
+
+    /// Remove a player that left from the tab list of every player,
+    /// returning the number of players told
+    pub async fn remove(&self, uuid: &McUuid) -> Result<usize> {
+        self.latencies.write().await.remove(uuid);
+        let packet = PlayerInfoRemovePacket { uuids: vec![*uuid] };
+        self.player_list.broadcast(&packet).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::player::GameMode;
+    use crate::network::PacketReceiver;
+    use crate::protocol::packets::login::Property;
+    use tokio::sync::mpsc;
+
+    async fn add(list: &PlayerList, info: PlayerInfo) -> (McUuid, PacketReceiver) {
+        let uuid = McUuid::new_v4();
+        let (sender, receiver) = mpsc::unbounded_channel();
+        list.add_player(uuid, info, sender).await;
+        (uuid, receiver)
+    }
+
+    #[tokio::test]
+    async fn test_join_fills_tab_lists() {
+        let list = PlayerList::new();
+        let tab_list = TabListManager::new(list.clone());
+        let skin = Property {
+            name: "textures".to_string().into(),
+            value: "e30=".to_string().into(),
+            signature: None,
+        };
+        let (steve_uuid, mut steve) = add(
+            &list,
+            PlayerInfo::new("Steve".to_string(), GameMode::Creative)
+                .with_properties(vec![skin.clone()]),
+        )
+        .await;
+        let (alex_uuid, mut alex) = add(
+            &list,
+            PlayerInfo::new("Alex".to_string(), GameMode::Survival),
+        )
+        .await;
+
+        // The new player sees everyone, themselves included
+        let packet = tab_list.list_packet().await;
+        assert_eq!(packet.actions, PlayerInfoUpdatePacket::ADD_ACTIONS);
+        assert_eq!(packet.entries.len(), 2);
+        let entry = packet
+            .entries
+            .iter()
+            .find(|entry| entry.uuid == steve_uuid)
+            .unwrap();
+        assert_eq!(entry.name.0, "Steve");
+        assert_eq!(entry.properties, vec![skin]);
+        assert_eq!(entry.game_mode.0, GameMode::Creative as i32);
+        assert!(entry.listed);
+
+        // Everyone else is only told about the new player
+        assert_eq!(tab_list.announce(&alex_uuid).await.unwrap(), 1);
+        let update = steve.try_recv().unwrap();
+        let update = update.parse::<PlayerInfoUpdatePacket>().unwrap();
+        assert_eq!(update.entries.len(), 1);
+        assert_eq!(update.entries[0].uuid, alex_uuid);
+        assert_eq!(update.entries[0].name.0, "Alex");
+        assert!(alex.try_recv().is_err());
+
+        assert_eq!(tab_list.remove(&alex_uuid).await.unwrap(), 2);
+        let removal = steve.try_recv().unwrap();
+        assert_eq!(
+            removal.parse::<PlayerInfoRemovePacket>().unwrap().uuids,
+            vec![alex_uuid]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_latency_broadcast() {
+        let list = PlayerList::new();
+        let tab_list = TabListManager::new(list.clone());
+        let (uuid, mut steve) = add(
+            &list,
+            PlayerInfo::new("Steve".to_string(), GameMode::Survival),
+        )
+        .await;
+        let (latency, receiver) = watch::channel(Duration::ZERO);
+        tab_list.track_latency(uuid, receiver).await;
+
+        // Nothing is sent until a keep alive is measured
+        assert_eq!(tab_list.broadcast_latency().await.unwrap(), 0);
+
+        latency.send_replace(Duration::from_millis(42));
+        assert_eq!(tab_list.broadcast_latency().await.unwrap(), 1);
+        assert_eq!(list.get_player(&uuid).await.unwrap().ping, 42);
+        // The ping score comes first, then the tab list latency
+        steve.try_recv().unwrap();
+        let update = steve.try_recv().unwrap();
+        let update = update.parse::<PlayerInfoUpdatePacket>().unwrap();
+        assert_eq!(update.actions, PlayerInfoUpdatePacket::UPDATE_LATENCY);
+        assert_eq!(update.entries[0].latency.0, 42);
+
+        // An unchanged latency is not sent again
+        assert_eq!(tab_list.broadcast_latency().await.unwrap(), 0);
+        assert!(steve.try_recv().is_err());
+    }
+}
@@ -0,0 +1,346 @@
+//! Game loop
+//!
+//! A [`TickLoop`] runs [`ServerContext::tick`] 20 times a second. Each tick
+//! runs the tasks scheduled for it, updates the world, advances the time
+//! of day and the weather, and once a minute sends the players' latency.
+//! A step that panics is logged and skipped, so one bad tick doesn't stop
+//! the server.
+
+use crate::server::block_updates::block_updates;
+use crate::server::context::ServerContext;
+use crate::server::tps::TICKS_PER_MINUTE;
+use std::cmp;
+use std::collections::BinaryHeap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::time::{Duration, Interval, MissedTickBehavior, interval};
+
+/// Length of a game tick, at 20 ticks per second
+pub const TICK_DURATION: Duration = Duration::from_millis(50);
+
+/// Callback run once a number of ticks have passed
+pub type DelayedTask = Box<dyn FnOnce(&Arc<ServerContext>) + Send>;
+
+/// Handle to a scheduled task, which can cancel it
+#[derive(Debug, Clone, Default)]
+pub struct TaskHandle {
+    /// Set once the task should no longer run
+    cancelled: Arc<AtomicBool>,
+}
+
+impl TaskHandle {
+    /// Stop the task from running, if it hasn't yet
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Check whether the task was cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// A task waiting in the [`Scheduler`]
+struct ScheduledEntry {
+    /// Tick the task runs on
+    tick: u64,
+    /// Order the task was scheduled in, so tasks of a tick run in order
+    sequence: u64,
+    /// Cancellation flag shared with the task's handle
+    handle: TaskHandle,
+    /// Task to run
+    task: DelayedTask,
+}
+
+impl ScheduledEntry {
+    /// Key the heap is ordered by
+    fn key(&self) -> (u64, u64) {
+        (self.tick, self.sequence)
+    }
+}
+
+impl PartialEq for ScheduledEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for ScheduledEntry {}
+
+impl PartialOrd for ScheduledEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ScheduledEntry {
+    // Reversed, so the heap's greatest entry is the one due first
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        other.key().cmp(&self.key())
+    }
+}
+
+/// Tasks waiting for their tick
+#[derive(Default)]
+pub struct Scheduler {
+    /// Number of the tick that last ran
+    current_tick: u64,
+    /// Number of tasks scheduled so far
+    scheduled: u64,
+    /// Waiting tasks, the first one due on top
+    tasks: BinaryHeap<ScheduledEntry>,
+}
+
+impl Scheduler {
+    /// Create a scheduler with no tasks
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the number of the tick that last ran
+    pub fn current_tick(&self) -> u64 {
+        self.current_tick
+    }
+
+    /// Get the number of tasks waiting to run, including cancelled ones
+    /// that were not reached yet
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    /// Check whether no tasks are waiting
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    /// Run a task once `delay_ticks` ticks have passed
+    ///
+    /// A delay of zero runs the task on the next tick.
+    pub fn schedule(&mut self, delay_ticks: u32, task: DelayedTask) -> TaskHandle {
+        let handle = TaskHandle::default();
+        self.tasks.push(ScheduledEntry {
+            tick: self.current_tick + u64::from(delay_ticks.max(1)),
+            sequence: self.scheduled,
+            handle: handle.clone(),
+            task,
+        });
+        self.scheduled += 1;
+        handle
+    }
+
+    /// Start the next tick, taking the tasks due on it that weren't
+    /// cancelled
+    pub fn advance(&mut self) -> Vec<DelayedTask> {
+        self.current_tick += 1;
+        let mut due = Vec::new();
+        while self
+            .tasks
+            .peek()
+            .is_some_and(|entry| entry.tick <= self.current_tick)
+        {
+            if let Some(entry) = self.tasks.pop() {
+                if !entry.handle.is_cancelled() {
+                    due.push(entry.task);
+                }
+            }
+        }
+        due
+    }
+}
+
+/// Run part of a tick, logging and discarding a panic
+fn catch_panic<T>(step: &str, run: impl FnOnce() -> T) -> Option<T> {
+    match panic::catch_unwind(AssertUnwindSafe(run)) {
+        Ok(value) => Some(value),
+        Err(_) => {
+            tracing::error!("Tick step {} panicked, skipping it", step);
+            None
+        }
+    }
+}
+
+impl ServerContext {
+    /// Run a task on the main loop once `delay_ticks` ticks have passed
+    ///
+    /// The returned handle cancels the task if it hasn't run yet.
+    pub fn schedule(
+        &self,
+        delay_ticks: u32,
+        task: impl FnOnce(&Arc<ServerContext>) + Send + 'static,
+    ) -> TaskHandle {
+        self.scheduler
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .schedule(delay_ticks, Box::new(task))
+    }
+
+    /// Run one game tick
+    pub async fn tick(self: &Arc<Self>) {
+        let (tick, tasks) = {
+            let mut scheduler = self.scheduler.lock().unwrap_or_else(|e| e.into_inner());
+            let tasks = scheduler.advance();
+            (scheduler.current_tick(), tasks)
+        };
+        for task in tasks {
+            catch_panic("scheduled task", || task(self));
+        }
+
+        let mutations = {
+            let mut world = self.world.write().await;
+            catch_panic("world update", || {
+                world.update(TICK_DURATION.as_secs_f64());
+                world.take_mutations()
+            })
+        };
+        for update in block_updates(&mutations.unwrap_or_default()) {
+            let sent = match update.to_raw_packet() {
+                Ok(packet) => {
+                    self.player_list
+                        .broadcast_to_all(|_, _| Ok(packet.clone()))
+                        .await
+                }
+                Err(e) => Err(e),
+            };
+            if let Err(e) = sent {
+                tracing::error!("Failed to send block updates: {}", e);
+            }
+        }
+
+        // The time is sent to every player once a second
+        let time = {
+            let mut time = self.time.write().await;
+            catch_panic("time", || time.tick().then(|| time.packet()))
+        };
+        if let Some(packet) = time.flatten() {
+            if let Err(e) = self.player_list.broadcast(&packet).await {
+                tracing::error!("Failed to send the time: {}", e);
+            }
+        }
+
+        let events = {
+            let mut weather = self.weather.write().await;
+            catch_panic("weather", || weather.tick(&mut rand::thread_rng()))
+        };
+        for event in events.unwrap_or_default() {
+            if let Err(e) = self.player_list.broadcast(&event).await {
+                tracing::error!("Failed to send the weather: {}", e);
+            }
+        }
+
+        if tick % TICKS_PER_MINUTE as u64 == 0 {
+            if let Err(e) = self.tab_list.broadcast_latency().await {
+                tracing::error!("Failed to send the latency: {}", e);
+            }
+        }
+    }
+}
+
+/// Drives the game at a fixed 20 ticks per second
+pub struct TickLoop {
+    /// Timer firing every [`TICK_DURATION`]
+    interval: Interval,
+    /// Server being ticked
+    context: Arc<ServerContext>,
+}
+
+impl TickLoop {
+    /// Create a loop ticking the given server
+    pub fn new(context: Arc<ServerContext>) -> Self {
+        let mut interval = interval(TICK_DURATION);
+        // Ticks missed behind a slow one are run right away to catch up
+        interval.set_missed_tick_behavior(MissedTickBehavior::Burst);
+        Self { interval, context }
+    }
+
+    /// Tick until a stop is requested
+    pub async fn run(mut self) {
+        loop {
+            self.interval.tick().await;
+            if self.context.shutdown.load(Ordering::Relaxed) {
+                tracing::info!("Stop requested, shutting down server...");
+                return;
+            }
+            self.context.tick_times.write().await.tick();
+            self.context.tick().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ServerConfig;
+    use std::sync::atomic::AtomicUsize;
+
+    /// Task that records its number when run
+    fn record(runs: &Arc<std::sync::Mutex<Vec<u32>>>, number: u32) -> DelayedTask {
+        let runs = Arc::clone(runs);
+        Box::new(move |_| runs.lock().unwrap().push(number))
+    }
+
+    #[test]
+    fn test_scheduler() {
+        let mut scheduler = Scheduler::new();
+        scheduler.schedule(2, Box::new(|_| {}));
+        scheduler.schedule(0, Box::new(|_| {}));
+        assert_eq!(scheduler.len(), 2);
+
+        assert_eq!(scheduler.advance().len(), 1);
+        assert_eq!(scheduler.advance().len(), 1);
+        assert!(scheduler.is_empty());
+        assert_eq!(scheduler.current_tick(), 2);
+    }
+
+    #[test]
+    fn test_scheduler_order_and_cancel() {
+        let runs = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut scheduler = Scheduler::new();
+        scheduler.schedule(2, record(&runs, 1));
+        scheduler.schedule(1, record(&runs, 2));
+        let cancelled = scheduler.schedule(2, record(&runs, 3));
+        scheduler.schedule(2, record(&runs, 4));
+        cancelled.cancel();
+        assert!(cancelled.is_cancelled());
+
+        let context = ServerContext::for_tests(ServerConfig::new().with_online_mode(false));
+        for _ in 0..3 {
+            for task in scheduler.advance() {
+                task(&context);
+            }
+        }
+        // Earlier ticks first, then in scheduling order
+        assert_eq!(*runs.lock().unwrap(), vec![2, 1, 4]);
+        assert!(scheduler.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_tick_runs_scheduled_tasks() {
+        let context = ServerContext::for_tests(ServerConfig::new().with_online_mode(false));
+        let runs = Arc::new(AtomicUsize::new(0));
+        for delay in [1, 3] {
+            let runs = Arc::clone(&runs);
+            context.schedule(delay, move |context| {
+                runs.fetch_add(1, Ordering::Relaxed);
+                // Tasks can schedule further tasks
+                let runs = Arc::clone(&runs);
+                context.schedule(1, move |_| {
+                    runs.fetch_add(10, Ordering::Relaxed);
+                });
+            });
+        }
+        context.schedule(1, |_| unreachable!("a panicking task"));
+
+        let start = context.time.read().await.packet().world_age;
+        context.tick().await;
+        assert_eq!(runs.load(Ordering::Relaxed), 1);
+        context.tick().await;
+        assert_eq!(runs.load(Ordering::Relaxed), 11);
+        context.tick().await;
+        assert_eq!(runs.load(Ordering::Relaxed), 12);
+        context.tick().await;
+        assert_eq!(runs.load(Ordering::Relaxed), 22);
+        // The panic didn't stop the rest of the tick
+        assert_eq!(context.time.read().await.packet().world_age, start + 4);
+    }
+}
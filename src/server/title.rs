@@ -0,0 +1,132 @@
+//! Showing titles to players
+//!
+//! Times and subtitle must reach the client before the title that uses
+//! them, so [`TitleBuilder`] sends the packets in that order.
+
+use crate::error::Result;
+use crate::protocol::TextComponent;
+use crate::protocol::packets::play::{
+    SetSubtitleTextPacket, SetTitleAnimationTimesPacket, SetTitleTextPacket,
+};
+use crate::protocol::types::McUuid;
+use crate::server::PlayerList;
+
+/// A title to show to players
+#[derive(Debug, Clone, PartialEq)]
+pub struct TitleBuilder {
+    /// Text of the title
+    title: TextComponent,
+    /// Text below the title
+    subtitle: Option<TextComponent>,
+    /// Fade and stay times, in ticks
+    times: SetTitleAnimationTimesPacket,
+}
+
+impl TitleBuilder {
+    /// Create a title with the default times and no subtitle
+    pub fn new(title: TextComponent) -> Self {
+        Self {
+            title,
+            subtitle: None,
+            times: SetTitleAnimationTimesPacket::default(),
+        }
+    }
+
+    /// Set the text below the title
+    pub fn subtitle(mut self, subtitle: TextComponent) -> Self {
+        self.subtitle = Some(subtitle);
+        self
+    }
+
+    /// Set how many ticks the title takes to fade in
+    pub fn fade_in(mut self, ticks: i32) -> Self {
+        self.times.fade_in = ticks;
+        self
+    }
+
+    /// Set how many ticks the title stays fully shown
+    pub fn stay(mut self, ticks: i32) -> Self {
+        self.times.stay = ticks;
+        self
+    }
+
+    /// Set how many ticks the title takes to fade out
+    pub fn fade_out(mut self, ticks: i32) -> Self {
+        self.times.fade_out = ticks;
+        self
+    }
+
+    /// Send the title to a player, returning whether they are in the list
+    pub async fn send_to(&self, player_list: &PlayerList, uuid: &McUuid) -> Result<bool> {
+        if !player_list.send_to(uuid, &self.times).await? {
+            return Ok(false);
+        }
+        if let Some(subtitle) = &self.subtitle {
+            player_list
+                .send_to(
+                    uuid,
+                    &SetSubtitleTextPacket {
+                        text: subtitle.clone(),
+                    },
+                )
+                .await?;
+        }
+        player_list
+            .send_to(
+                uuid,
+                &SetTitleTextPacket {
+                    text: self.title.clone(),
+                },
+            )
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::player::GameMode;
+    use crate::protocol::packets::Packet;
+    use crate::server::player_list::PlayerInfo;
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn test_packet_order() {
+        let list = PlayerList::new();
+        let uuid = McUuid::new_v4();
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        list.add_player(
+            uuid,
+            PlayerInfo::new("Steve".to_string(), GameMode::Survival),
+            sender,
+        )
+        .await;
+
+        let title = TitleBuilder::new(TextComponent::text("Victory"))
+            .subtitle(TextComponent::text("The raid is over"))
+            .fade_in(20)
+            .stay(60)
+            .fade_out(20);
+        assert!(title.send_to(&list, &uuid).await.unwrap());
+
+        let times = receiver.try_recv().unwrap();
+        assert_eq!(
+            times.parse::<SetTitleAnimationTimesPacket>().unwrap(),
+            SetTitleAnimationTimesPacket {
+                fade_in: 20,
+                stay: 60,
+                fade_out: 20,
+            }
+        );
+        let subtitle = receiver.try_recv().unwrap();
+        assert_eq!(subtitle.id.0, SetSubtitleTextPacket::ID);
+        let text = receiver.try_recv().unwrap();
+        assert_eq!(
+            text.parse::<SetTitleTextPacket>().unwrap().text,
+            TextComponent::text("Victory")
+        );
+        assert!(receiver.try_recv().is_err());
+
+        assert!(!title.send_to(&list, &McUuid::new_v4()).await.unwrap());
+    }
+}
@@ -0,0 +1,195 @@
+//! Tick rate measurement
+//!
+//! The main loop records how long each tick actually took, so operators can
+//! see with `/tps` whether the server keeps up with the intended 20 ticks per
+//! second.
+
+use std::time::{Duration, Instant};
+
+/// Ticks per second the main loop aims for
+pub const TARGET_TPS: f64 = 20.0;
+
+/// Ticks in a minute at the target rate
+pub const TICKS_PER_MINUTE: usize = 1200;
+
+/// Whole minutes of averages kept for the longest window
+const MINUTES_KEPT: usize = 15;
+
+/// Fixed-capacity buffer that overwrites its oldest value when full
+#[derive(Debug, Clone)]
+pub struct CircularBuffer<T, const N: usize> {
+    /// Stored values, in insertion order starting at `next` once full
+    values: [T; N],
+    /// Slot the next value is written to
+    next: usize,
+    /// Number of values stored, at most `N`
+    len: usize,
+}
+
+impl<T: Copy + Default, const N: usize> CircularBuffer<T, N> {
+    /// Create an empty buffer
+    pub fn new() -> Self {
+        Self {
+            values: [T::default(); N],
+            next: 0,
+            len: 0,
+        }
+    }
+}
+
+impl<T: Copy + Default, const N: usize> Default for CircularBuffer<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Copy, const N: usize> CircularBuffer<T, N> {
+    /// Add a value, dropping the oldest one if the buffer is full
+    pub fn push(&mut self, value: T) {
+        if N == 0 {
+            return;
+        }
+        self.values[self.next] = value;
+        self.next = (self.next + 1) % N;
+        self.len = (self.len + 1).min(N);
+    }
+
+    /// Get the number of values stored
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check whether no values are stored
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the maximum number of values stored
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Iterate over up to `count` of the most recent values, newest first
+    pub fn latest(&self, count: usize) -> impl Iterator<Item = T> + '_ {
+        (1..=count.min(self.len)).map(move |back| self.values[(self.next + N - back) % N])
+    }
+}
+
+/// Recent tick durations, for working out the tick rate
+#[derive(Debug, Clone, Default)]
+pub struct TickTimes {
+    /// Milliseconds each tick of the last minute took
+    ticks: CircularBuffer<f64, TICKS_PER_MINUTE>,
+    /// Mean tick milliseconds of each whole minute
+    minutes: CircularBuffer<f64, MINUTES_KEPT>,
+    /// Ticks recorded since the last whole minute
+    ticks_this_minute: usize,
+    /// When the last tick started
+    last_tick: Option<Instant>,
+}
+
+impl TickTimes {
+    /// Create an empty record, which reports the target rate
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that a tick is starting now
+    ///
+    /// The time since the previous tick is the duration of that tick.
+    pub fn tick(&mut self) {
+        let now = Instant::now();
+        if let Some(last_tick) = self.last_tick.replace(now) {
+            self.record(now - last_tick);
+        }
+    }
+
+    /// Record the duration of a tick
+    pub fn record(&mut self, elapsed: Duration) {
+        self.ticks.push(elapsed.as_secs_f64() * 1000.0);
+        self.ticks_this_minute += 1;
+        if self.ticks_this_minute == TICKS_PER_MINUTE {
+            self.ticks_this_minute = 0;
+            let minute = mean(self.ticks.latest(TICKS_PER_MINUTE));
+            self.minutes.push(minute.unwrap_or_default());
+        }
+    }
+
+    /// Get the ticks per second over the last minute
+    pub fn last_minute(&self) -> f64 {
+        tps(mean(self.ticks.latest(TICKS_PER_MINUTE)))
+    }
+
+    /// Get the ticks per second over the last whole `minutes`
+    ///
+    /// Until a whole minute has passed this is the rate of the ticks so
+    /// far, and until `minutes` have passed it covers the minutes there are.
+    pub fn last_minutes(&self, minutes: usize) -> f64 {
+        if self.minutes.is_empty() {
+            return self.last_minute();
+        }
+        tps(mean(self.minutes.latest(minutes)))
+    }
+}
+
+/// Get the mean of some values, if there are any
+fn mean(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values.fold((0.0, 0usize), |(sum, count), value| {
+        (sum + value, count + 1)
+    });
+    (count > 0).then(|| sum / count as f64)
+}
+
+/// Convert a mean tick duration in milliseconds to ticks per second
+///
+/// Ticks run late behind a slow one are run early to catch up, so the rate
+/// never goes above the target.
+fn tps(mean_millis: Option<f64>) -> f64 {
+    match mean_millis {
+        Some(millis) if millis > 0.0 => (1000.0 / millis).min(TARGET_TPS),
+        _ => TARGET_TPS,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_circular_buffer() {
+        let mut buffer = CircularBuffer::<u32, 3>::new();
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.latest(2).count(), 0);
+
+        for value in 1..=5 {
+            buffer.push(value);
+        }
+        assert_eq!(buffer.len(), 3);
+        assert_eq!(buffer.capacity(), 3);
+        assert_eq!(buffer.latest(10).collect::<Vec<_>>(), vec![5, 4, 3]);
+        assert_eq!(buffer.latest(2).collect::<Vec<_>>(), vec![5, 4]);
+    }
+
+    #[test]
+    fn test_tick_times() {
+        let mut times = TickTimes::new();
+        assert_eq!(times.last_minute(), TARGET_TPS);
+
+        // Half speed for a minute, then two minutes on time
+        for _ in 0..TICKS_PER_MINUTE {
+            times.record(Duration::from_millis(100));
+        }
+        assert_eq!(times.last_minute(), 10.0);
+        assert_eq!(times.last_minutes(5), 10.0);
+        for _ in 0..TICKS_PER_MINUTE * 2 {
+            times.record(Duration::from_millis(50));
+        }
+        assert_eq!(times.last_minute(), 20.0);
+        // (100 + 50 + 50) / 3 milliseconds per tick
+        assert!((times.last_minutes(5) - 15.0).abs() < 1e-9);
+
+        // Ticks run early to catch up don't count above the target
+        times.record(Duration::ZERO);
+        assert_eq!(times.last_minute(), 20.0);
+    }
+}
@@ -0,0 +1,82 @@
+//! Transfers
+//!
+//! Clients that arrive with the Transfer intent were sent here by another
+//! server. When backends are configured, this server acts as a router: it
+//! logs those clients in, then transfers them on to the next backend in
+//! turn.
+
+use crate::config::TransferBackend;
+use crate::protocol::packets::configuration::TransferPacket;
+use crate::protocol::types::VarInt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Picks the backend each transferred client is sent to
+#[derive(Debug, Default)]
+pub struct TransferManager {
+    /// Servers clients are sent to
+    backends: Vec<TransferBackend>,
+    /// Number of clients sent so far, which selects the next backend
+    sent: AtomicUsize,
+}
+
+impl TransferManager {
+    /// Create a manager sending clients to the given backends
+    pub fn new(backends: Vec<TransferBackend>) -> Self {
+        Self {
+            backends,
+            sent: AtomicUsize::new(0),
+        }
+    }
+
+    /// Servers clients are sent to
+    pub fn backends(&self) -> &[TransferBackend] {
+        &self.backends
+    }
+
+    /// Pick the backend for the next client, going round the list, or
+    /// `None` when there are no backends
+    pub fn next_backend(&self) -> Option<&TransferBackend> {
+        if self.backends.is_empty() {
+            return None;
+        }
+        let sent = self.sent.fetch_add(1, Ordering::Relaxed);
+        self.backends.get(sent % self.backends.len())
+    }
+}
+
+/// Build the packet sending a client to a backend
+pub fn transfer_packet(backend: &TransferBackend) -> TransferPacket {
+    TransferPacket {
+        host: backend.host.clone().into(),
+        port: VarInt(i32::from(backend.port)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_robin() {
+        let transfers = TransferManager::new(vec![
+            TransferBackend::new("lobby-1", 25565),
+            TransferBackend::new("lobby-2", 25566),
+        ]);
+        let hosts: Vec<_> = (0..5)
+            .map(|_| transfers.next_backend().unwrap().host.clone())
+            .collect();
+        assert_eq!(
+            hosts,
+            ["lobby-1", "lobby-2", "lobby-1", "lobby-2", "lobby-1"]
+        );
+
+        assert!(TransferManager::default().next_backend().is_none());
+    }
+
+    #[test]
+    fn test_transfer_packet() {
+        let packet = transfer_packet(&TransferBackend::new("lobby", 25566));
+        assert_eq!(packet.host.0, "lobby");
+        assert_eq!(packet.port.0, 25566);
+    }
+}
@@ -0,0 +1,179 @@
+//! Whitelisted players
+//!
+//! While the whitelist is enabled, only the players in it can join. The
+//! players are saved to `whitelist.json` in the same format as vanilla, and
+//! whether the whitelist is enabled comes from the `white-list` property.
+
+use crate::error::Result;
+use crate::protocol::types::McUuid;
+use crate::storage::json_list;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Name of the whitelist file in the server directory
+pub const WHITELIST_FILE: &str = "whitelist.json";
+
+/// Reason given to players turned away by the whitelist
+pub const NOT_WHITELISTED: &str = "You are not whitelisted on this server.";
+
+/// A player allowed to join
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WhitelistEntry {
+    /// Player UUID, unknown for players added while offline
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<McUuid>,
+    /// Name of the player when they were added
+    pub name: String,
+}
+
+impl WhitelistEntry {
+    /// Create an entry for a player
+    pub fn new(name: impl Into<String>, uuid: Option<McUuid>) -> Self {
+        Self {
+            uuid,
+            name: name.into(),
+        }
+    }
+
+    /// Whether the entry is for a player
+    pub fn matches(&self, uuid: &McUuid, name: &str) -> bool {
+        match self.uuid {
+            Some(allowed) => allowed == *uuid,
+            None => self.name.eq_ignore_ascii_case(name),
+        }
+    }
+}
+
+/// The players allowed to join, and whether anyone else is turned away
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Whitelist {
+    /// Whether players not in the whitelist are turned away
+    enabled: bool,
+    /// Players in the order they were added
+    entries: Vec<WhitelistEntry>,
+}
+
+impl Whitelist {
+    /// Create an empty whitelist
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Read the players from the contents of `whitelist.json`
+    pub fn from_json(enabled: bool, json: &str) -> Result<Self> {
+        let entries = json_list::from_json(json)?;
+        Ok(Self { enabled, entries })
+    }
+
+    /// Convert the players to the contents of `whitelist.json`
+    pub fn to_json(&self) -> Result<String> {
+        json_list::to_json(&self.entries)
+    }
+
+    /// Load a whitelist file, or an empty whitelist if there is none
+    pub fn load(enabled: bool, path: &Path) -> Result<Self> {
+        let entries = json_list::load(path)?;
+        Ok(Self { enabled, entries })
+    }
+
+    /// Save the players to a file
+    pub fn save(&self, path: &Path) -> Result<()> {
+        json_list::save(path, &self.entries)
+    }
+
+    /// Whether players not in the whitelist are turned away
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Turn the whitelist on or off, returning whether that changed anything
+    pub fn set_enabled(&mut self, enabled: bool) -> bool {
+        std::mem::replace(&mut self.enabled, enabled) != enabled
+    }
+
+    /// Every whitelisted player
+    pub fn entries(&self) -> &[WhitelistEntry] {
+        &self.entries
+    }
+
+    /// Whether a player is in the whitelist
+    pub fn contains(&self, uuid: &McUuid, name: &str) -> bool {
+        self.entries.iter().any(|entry| entry.matches(uuid, name))
+    }
+
+    /// Whether a player may join
+    pub fn allows(&self, uuid: &McUuid, name: &str) -> bool {
+        !self.enabled || self.contains(uuid, name)
+    }
+
+    /// Add a player, returning false if they were already whitelisted
+    pub fn add(&mut self, entry: WhitelistEntry) -> bool {
+        let exists = self.entries.iter().any(|existing| {
+            (entry.uuid.is_some() && existing.uuid == entry.uuid)
+                || existing.name.eq_ignore_ascii_case(&entry.name)
+        });
+        if !exists {
+            self.entries.push(entry);
+        }
+        !exists
+    }
+
+    /// Remove a player by name, returning their entry
+    pub fn remove(&mut self, name: &str) -> Option<WhitelistEntry> {
+        let index = self
+            .entries
+            .iter()
+            .position(|entry| entry.name.eq_ignore_ascii_case(name))?;
+        Some(self.entries.remove(index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vanilla_format_roundtrip() {
+        let uuid = McUuid::new_v4();
+        let json = format!(
+            r#"[
+  {{
+    "uuid": "{}",
+    "name": "Steve"
+  }}
+]"#,
+            uuid
+        );
+        let whitelist = Whitelist::from_json(true, &json).unwrap();
+        assert_eq!(
+            whitelist.entries(),
+            [WhitelistEntry::new("Steve", Some(uuid))]
+        );
+        assert_eq!(whitelist.to_json().unwrap(), json);
+        assert!(Whitelist::from_json(true, "{}").is_err());
+    }
+
+    #[test]
+    fn test_allows() {
+        let mut whitelist = Whitelist::new(false);
+        let steve = McUuid::new_v4();
+        assert!(whitelist.allows(&steve, "Steve"));
+        assert!(whitelist.set_enabled(true));
+        assert!(!whitelist.set_enabled(true));
+        assert!(!whitelist.allows(&steve, "Steve"));
+
+        assert!(whitelist.add(WhitelistEntry::new("Steve", Some(steve))));
+        assert!(!whitelist.add(WhitelistEntry::new("steve", None)));
+        assert!(whitelist.add(WhitelistEntry::new("Alex", None)));
+        assert!(whitelist.allows(&steve, "Steve2"));
+        assert!(!whitelist.allows(&McUuid::new_v4(), "Steve"));
+        assert!(whitelist.allows(&McUuid::new_v4(), "alex"));
+
+        assert!(whitelist.remove("ALEX").is_some());
+        assert!(whitelist.remove("Alex").is_none());
+        assert!(!whitelist.allows(&McUuid::new_v4(), "Alex"));
+    }
+}
@@ -0,0 +1,10 @@
+//! World simulation
+//!
+//! State of the main world that changes every tick, such as the time of
+//! day and the weather, kept apart from its blocks in [`crate::game::world`].
+
+pub mod time;
+pub mod weather;
+
+pub use time::WorldTime;
+pub use weather::{Weather, WeatherState};
@@ -0,0 +1,97 @@
+//! Day and night cycle
+//!
+//! The world keeps two clocks: its age, which always advances, and the time
+//! of day, which stops while the `doDaylightCycle` game rule is off. Clients
+//! run their own clocks and are sent the server's once a second.
+
+use crate::protocol::packets::play::UpdateTimePacket;
+
+/// Ticks in a full day
+pub const DAY_LENGTH: i64 = 24_000;
+
+/// Time of day `/time set day` sets
+pub const DAY: i64 = 1_000;
+
+/// Time of day `/time set noon` sets
+pub const NOON: i64 = 6_000;
+
+/// Time of day `/time set night` sets
+pub const NIGHT: i64 = 13_000;
+
+/// Time of day `/time set midnight` sets
+pub const MIDNIGHT: i64 = 18_000;
+
+/// Ticks between the time updates sent to clients
+pub const TIME_UPDATE_TICKS: i64 = 20;
+
+/// Age and time of day of a world
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorldTime {
+    /// Ticks the world has run for
+    pub world_age: i64,
+    /// Time of day in ticks, which keeps counting past a full day
+    pub day_time: i64,
+    /// Whether the time of day advances
+    pub do_daylight_cycle: bool,
+}
+
+impl WorldTime {
+    /// Create the clocks of a world, with the daylight cycle running
+    pub fn new(world_age: i64, day_time: i64) -> Self {
+        Self {
+            world_age,
+            day_time,
+            do_daylight_cycle: true,
+        }
+    }
+
+    /// Advance one tick, returning whether clients are due a time update
+    pub fn tick(&mut self) -> bool {
+        self.world_age += 1;
+        if self.do_daylight_cycle {
+            self.day_time += 1;
+        }
+        self.world_age % TIME_UPDATE_TICKS == 0
+    }
+
+    /// Time of day within the current day, from 0 to 23999
+    pub fn time_of_day(&self) -> i64 {
+        self.day_time.rem_euclid(DAY_LENGTH)
+    }
+
+    /// Build the packet telling clients the time
+    pub fn packet(&self) -> UpdateTimePacket {
+        UpdateTimePacket {
+            world_age: self.world_age,
+            time_of_day: self.day_time,
+            time_of_day_increasing: self.do_daylight_cycle,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tick() {
+        let mut time = WorldTime::new(0, DAY_LENGTH - 1);
+        let updates = (0..40).filter(|_| time.tick()).count();
+        assert_eq!(updates, 2);
+        assert_eq!((time.world_age, time.day_time), (40, DAY_LENGTH + 39));
+        assert_eq!(time.time_of_day(), 39);
+    }
+
+    #[test]
+    fn test_daylight_cycle_paused() {
+        let mut time = WorldTime::new(100, NIGHT);
+        time.do_daylight_cycle = false;
+        for _ in 0..20 {
+            time.tick();
+        }
+        assert_eq!((time.world_age, time.day_time), (120, NIGHT));
+        let packet = time.packet();
+        assert_eq!(packet.time_of_day, NIGHT);
+        assert!(!packet.time_of_day_increasing);
+    }
+}
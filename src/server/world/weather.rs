@@ -0,0 +1,286 @@
+//! Weather cycle
+//!
+//! Rain and thunder each switch on and off when a countdown runs out, so
+//! thunderstorms only happen while it also rains. Clients are told the
+//! weather through game events and fade between rain levels themselves,
+//! following the levels the server sends every tick they change.
+
+use crate::protocol::packets::play::GameEventPacket;
+use rand::Rng;
+use std::ops::RangeInclusive;
+
+/// Ticks of clear weather set by `/weather clear` without a duration
+pub const CLEAR_DURATION: RangeInclusive<i32> = 12_000..=180_000;
+
+/// Ticks of rain, and of dry weather between rain
+pub const RAIN_DURATION: RangeInclusive<i32> = 12_000..=24_000;
+
+/// Ticks of a thunderstorm
+pub const THUNDER_DURATION: RangeInclusive<i32> = 3_600..=15_600;
+
+/// Ticks between thunderstorms
+pub const THUNDER_DELAY: RangeInclusive<i32> = 12_000..=180_000;
+
+/// Change of the rain and thunder levels per tick
+const LEVEL_STEP: f32 = 0.01;
+
+/// Rain level above which clients consider it to be raining
+const RAINING_LEVEL: f32 = 0.2;
+
+/// Weather that can be set with `/weather`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Weather {
+    /// No rain
+    Clear,
+    /// Rain without thunder
+    Rain,
+    /// Rain and thunder
+    Thunder,
+}
+
+impl Weather {
+    /// Pick a random duration for this weather
+    pub fn random_duration(self, rng: &mut impl Rng) -> i32 {
+        match self {
+            Weather::Clear => rng.gen_range(CLEAR_DURATION),
+            Weather::Rain => rng.gen_range(RAIN_DURATION),
+            Weather::Thunder => rng.gen_range(THUNDER_DURATION),
+        }
+    }
+}
+
+/// Weather of a world
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WeatherState {
+    /// Whether rain is starting or ongoing
+    pub raining: bool,
+    /// Whether thunder is starting or ongoing
+    pub thundering: bool,
+    /// Strength of the rain, from 0 to 1
+    pub rain_level: f32,
+    /// Strength of the thunder, from 0 to 1
+    pub thunder_level: f32,
+    /// Ticks until rain starts or stops, or 0 to pick a new countdown
+    pub rain_time: i32,
+    /// Ticks until thunder starts or stops, or 0 to pick a new countdown
+    pub thunder_time: i32,
+    /// Ticks of clear weather forced by `/weather clear`
+    pub clear_time: i32,
+    /// Whether the countdowns run, from the `doWeatherCycle` game rule
+    pub do_weather_cycle: bool,
+}
+
+impl Default for WeatherState {
+    fn default() -> Self {
+        Self {
+            raining: false,
+            thundering: false,
+            rain_level: 0.0,
+            thunder_level: 0.0,
+            rain_time: 0,
+            thunder_time: 0,
+            clear_time: 0,
+            do_weather_cycle: true,
+        }
+    }
+}
+
+impl WeatherState {
+    /// Create the weather of a loaded world, with rain and thunder at full
+    /// strength if they are ongoing
+    pub fn new(raining: bool, thundering: bool) -> Self {
+        Self {
+            raining,
+            thundering,
+            rain_level: if raining { 1.0 } else { 0.0 },
+            thunder_level: if thundering { 1.0 } else { 0.0 },
+            ..Self::default()
+        }
+    }
+
+    /// Whether clients see rain
+    pub fn is_raining(&self) -> bool {
+        self.rain_level > RAINING_LEVEL
+    }
+
+    /// Current weather, as `/weather` would set it
+    pub fn weather(&self) -> Weather {
+        match (self.raining, self.thundering) {
+            (false, _) => Weather::Clear,
+            (true, false) => Weather::Rain,
+            (true, true) => Weather::Thunder,
+        }
+    }
+
+    /// Force a weather for a number of ticks
+    pub fn set_weather(&mut self, weather: Weather, duration: i32) {
+        let (clear_time, rain_time) = match weather {
+            Weather::Clear => (duration, 0),
+            Weather::Rain | Weather::Thunder => (0, duration),
+        };
+        self.clear_time = clear_time;
+        self.rain_time = rain_time;
+        self.thunder_time = rain_time;
+        self.raining = weather != Weather::Clear;
+        self.thundering = weather == Weather::Thunder;
+    }
+
+    /// Advance one tick, returning the game events to send to every player
+    pub fn tick(&mut self, rng: &mut impl Rng) -> Vec<GameEventPacket> {
+        if self.do_weather_cycle {
+            self.advance_countdowns(rng);
+        }
+
+        let was_raining = self.is_raining();
+        let (old_rain, old_thunder) = (self.rain_level, self.thunder_level);
+        let step = |level: f32, rising: bool| {
+            let step = if rising { LEVEL_STEP } else { -LEVEL_STEP };
+            (level + step).clamp(0.0, 1.0)
+        };
+        self.rain_level = step(self.rain_level, self.raining);
+        self.thunder_level = step(self.thunder_level, self.thundering);
+
+        let mut events = Vec::new();
+        if was_raining != self.is_raining() {
+            let event = if was_raining {
+                GameEventPacket::END_RAINING
+            } else {
+                GameEventPacket::BEGIN_RAINING
+            };
+            events.push(GameEventPacket::new(event, 0.0));
+        }
+        if was_raining != self.is_raining() || old_rain != self.rain_level {
+            events.push(self.rain_level_event());
+        }
+        if was_raining != self.is_raining() || old_thunder != self.thunder_level {
+            events.push(self.thunder_level_event());
+        }
+        events
+    }
+
+    /// Count the rain and thunder countdowns down, switching the weather
+    /// when they run out
+    fn advance_countdowns(&mut self, rng: &mut impl Rng) {
+        if self.clear_time > 0 {
+            self.clear_time -= 1;
+            // Restart both cycles as soon as the forced weather ends
+            self.rain_time = i32::from(!self.raining);
+            self.thunder_time = i32::from(!self.thundering);
+            self.raining = false;
+            self.thundering = false;
+            return;
+        }
+
+        if self.thunder_time > 0 {
+            self.thunder_time -= 1;
+            if self.thunder_time == 0 {
+                self.thundering = !self.thundering;
+            }
+        } else if self.thundering {
+            self.thunder_time = rng.gen_range(THUNDER_DURATION);
+        } else {
+            self.thunder_time = rng.gen_range(THUNDER_DELAY);
+        }
+
+        if self.rain_time > 0 {
+            self.rain_time -= 1;
+            if self.rain_time == 0 {
+                self.raining = !self.raining;
+            }
+        } else if self.raining {
+            self.rain_time = rng.gen_range(RAIN_DURATION);
+        } else {
+            self.rain_time = rng.gen_range(CLEAR_DURATION);
+        }
+    }
+
+    /// Game events telling a joining player the weather, empty when clear
+    pub fn join_events(&self) -> Vec<GameEventPacket> {
+        if !self.is_raining() {
+            return Vec::new();
+        }
+        vec![
+            GameEventPacket::new(GameEventPacket::BEGIN_RAINING, 0.0),
+            self.rain_level_event(),
+            self.thunder_level_event(),
+        ]
+    }
+
+    /// Event setting the client's rain level
+    fn rain_level_event(&self) -> GameEventPacket {
+        GameEventPacket::new(GameEventPacket::RAIN_LEVEL_CHANGE, self.rain_level)
+    }
+
+    /// Event setting the client's thunder level
+    fn thunder_level_event(&self) -> GameEventPacket {
+        GameEventPacket::new(GameEventPacket::THUNDER_LEVEL_CHANGE, self.thunder_level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn test_rain_fades_in_and_out() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut weather = WeatherState::default();
+        weather.set_weather(Weather::Rain, 200);
+
+        // Rounding makes the levels take one tick more than 100 to fade
+        let events: Vec<_> = (0..101).flat_map(|_| weather.tick(&mut rng)).collect();
+        let begin = GameEventPacket::new(GameEventPacket::BEGIN_RAINING, 0.0);
+        assert_eq!(events.iter().filter(|event| **event == begin).count(), 1);
+        assert_eq!(weather.rain_level, 1.0);
+        assert_eq!(weather.thunder_level, 0.0);
+        assert!(weather.is_raining());
+        assert_eq!(weather.weather(), Weather::Rain);
+        // Nothing changes once the rain is at full strength
+        assert!(weather.tick(&mut rng).is_empty());
+
+        weather.set_weather(Weather::Clear, 1000);
+        let events: Vec<_> = (0..101).flat_map(|_| weather.tick(&mut rng)).collect();
+        let end = GameEventPacket::new(GameEventPacket::END_RAINING, 0.0);
+        assert_eq!(events.iter().filter(|event| **event == end).count(), 1);
+        assert_eq!(weather.rain_level, 0.0);
+        assert_eq!(weather.clear_time, 899);
+    }
+
+    #[test]
+    fn test_countdowns() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let mut weather = WeatherState::default();
+        weather.tick(&mut rng);
+        assert!(CLEAR_DURATION.contains(&weather.rain_time));
+        assert!(THUNDER_DELAY.contains(&weather.thunder_time));
+
+        weather.rain_time = 1;
+        weather.tick(&mut rng);
+        assert!(weather.raining);
+        weather.tick(&mut rng);
+        assert!(RAIN_DURATION.contains(&weather.rain_time));
+
+        // Paused cycles keep their countdowns
+        weather.do_weather_cycle = false;
+        let rain_time = weather.rain_time;
+        weather.tick(&mut rng);
+        assert_eq!(weather.rain_time, rain_time);
+    }
+
+    #[test]
+    fn test_join_events() {
+        assert!(WeatherState::default().join_events().is_empty());
+        let thunder = WeatherState::new(true, true);
+        assert_eq!(thunder.weather(), Weather::Thunder);
+        assert_eq!(
+            thunder.join_events(),
+            [
+                GameEventPacket::new(GameEventPacket::BEGIN_RAINING, 0.0),
+                GameEventPacket::new(GameEventPacket::RAIN_LEVEL_CHANGE, 1.0),
+                GameEventPacket::new(GameEventPacket::THUNDER_LEVEL_CHANGE, 1.0),
+            ]
+        );
+    }
+}
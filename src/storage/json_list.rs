@@ -0,0 +1,69 @@
+//! JSON list files
+//!
+//! The ban list, operator list and whitelist are saved as a JSON array of
+//! entries, pretty printed the way vanilla writes them. A list whose file
+//! does not exist yet is empty.
+
+use super::write_atomically;
+use crate::error::Result;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::io::ErrorKind;
+use std::path::Path;
+
+/// Read the entries of a list from the contents of its file
+pub fn from_json<T: DeserializeOwned>(json: &str) -> Result<Vec<T>> {
+    Ok(serde_json::from_str(json)?)
+}
+
+/// Convert the entries of a list to the contents of its file
+pub fn to_json<T: Serialize>(entries: &[T]) -> Result<String> {
+    Ok(serde_json::to_string_pretty(entries)?)
+}
+
+/// Load the entries of a list file, or none if there is no file
+pub fn load<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>> {
+    match std::fs::read_to_string(path) {
+        Ok(json) => from_json(&json),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Save the entries of a list to its file
+pub fn save<T: Serialize>(path: &Path, entries: &[T]) -> Result<()> {
+    write_atomically(path, to_json(entries)?.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ServerError;
+
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir().join(format!("obsidium-{}-list.json", std::process::id()));
+        assert!(load::<String>(&path).unwrap().is_empty());
+
+        let entries = ["Steve".to_string(), "Alex".to_string()];
+        save(&path, &entries).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "[\n  \"Steve\",\n  \"Alex\"\n]"
+        );
+        assert_eq!(load::<String>(&path).unwrap(), entries);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_json() {
+        assert!(matches!(
+            from_json::<String>("{}"),
+            Err(ServerError::Json(_))
+        ));
+        assert!(matches!(
+            from_json::<String>("[1]"),
+            Err(ServerError::Json(_))
+        ));
+    }
+}
@@ -0,0 +1,265 @@
+//! World data in `level.dat`
+//!
+//! A world's metadata is stored in a gzip-compressed NBT file in its
+//! directory, as a `Data` compound holding the spawn point, the game rules
+//! as strings, how long the world has run, its weather and the version that
+//! saved it. Other keys of vanilla's `level.dat` are not kept.
+
+use super::write_atomically;
+use crate::data::{encode_nbt, read_nbt_file};
+use crate::error::{Result, ServerError};
+use crate::game::game_rules::GameRuleRegistry;
+use crate::protocol::MINECRAFT_VERSION;
+use crate::protocol::nbt::{NbtCompound, NbtTag};
+use crate::protocol::types::Position;
+use std::path::Path;
+
+/// Name of the file in the world directory
+pub const LEVEL_DATA_FILE: &str = "level.dat";
+
+/// Data version of the worlds this server saves
+pub const DATA_VERSION: i32 = 4435;
+
+/// Version of the game that saved a world
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LevelVersion {
+    /// Data version
+    pub id: i32,
+    /// Name of the version, such as "1.21.6"
+    pub name: String,
+    /// Whether the version is a snapshot
+    pub snapshot: bool,
+}
+
+impl Default for LevelVersion {
+    fn default() -> Self {
+        Self {
+            id: DATA_VERSION,
+            name: MINECRAFT_VERSION.to_string(),
+            snapshot: false,
+        }
+    }
+}
+
+/// Metadata of a world
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelData {
+    /// X coordinate of the spawn point
+    pub spawn_x: i32,
+    /// Y coordinate of the spawn point
+    pub spawn_y: i32,
+    /// Z coordinate of the spawn point
+    pub spawn_z: i32,
+    /// Value of every game rule
+    pub game_rules: GameRuleRegistry,
+    /// Ticks the world has run for
+    pub time: i64,
+    /// Time of day in ticks, which keeps counting past 24000
+    pub day_time: i64,
+    /// Whether it is raining
+    pub raining: bool,
+    /// Whether there is a thunderstorm
+    pub thundering: bool,
+    /// Ticks until rain starts or stops
+    pub rain_time: i32,
+    /// Ticks until thunder starts or stops
+    pub thunder_time: i32,
+    /// Ticks of clear weather left, set by `/weather clear`
+    pub clear_weather_time: i32,
+    /// Version that saved the world
+    pub version: LevelVersion,
+}
+
+impl Default for LevelData {
+    fn default() -> Self {
+        Self::at_spawn(Position::new(0, 64, 0))
+    }
+}
+
+impl LevelData {
+    /// Create the data of a new world with default game rules
+    pub fn at_spawn(spawn: Position) -> Self {
+        Self {
+            spawn_x: spawn.x,
+            spawn_y: spawn.y,
+            spawn_z: spawn.z,
+            game_rules: GameRuleRegistry::new(),
+            time: 0,
+            day_time: 0,
+            raining: false,
+            thundering: false,
+            rain_time: 0,
+            thunder_time: 0,
+            clear_weather_time: 0,
+            version: LevelVersion::default(),
+        }
+    }
+
+    /// Spawn point of the world
+    pub fn spawn_position(&self) -> Position {
+        Position::new(self.spawn_x, self.spawn_y, self.spawn_z)
+    }
+
+    /// Move the spawn point of the world
+    pub fn set_spawn_position(&mut self, spawn: Position) {
+        (self.spawn_x, self.spawn_y, self.spawn_z) = (spawn.x, spawn.y, spawn.z);
+    }
+
+    /// Convert the data to the root compound of `level.dat`
+    pub fn to_nbt(&self) -> NbtTag {
+        let game_rules = self
+            .game_rules
+            .iter()
+            .map(|(name, value)| (name.to_string(), NbtTag::String(value.to_string())))
+            .collect::<NbtCompound>();
+        let version = NbtCompound::new()
+            .with("Id", self.version.id)
+            .with("Name", self.version.name.as_str())
+            .with("Snapshot", self.version.snapshot);
+        let data = NbtCompound::new()
+            .with("DataVersion", self.version.id)
+            .with("SpawnX", self.spawn_x)
+            .with("SpawnY", self.spawn_y)
+            .with("SpawnZ", self.spawn_z)
+            .with("GameRules", game_rules)
+            .with("Time", self.time)
+            .with("DayTime", self.day_time)
+            .with("raining", self.raining)
+            .with("thundering", self.thundering)
+            .with("rainTime", self.rain_time)
+            .with("thunderTime", self.thunder_time)
+            .with("clearWeatherTime", self.clear_weather_time)
+            .with("Version", version);
+        NbtCompound::new().with("Data", data).into()
+    }
+
+    /// Read the data from the root compound of `level.dat`
+    ///
+    /// Game rules missing from the file keep their default value.
+    pub fn from_nbt(tag: &NbtTag) -> Result<Self> {
+        let invalid = |field: &str| ServerError::Nbt(format!("Invalid level data {}", field));
+        let data = tag
+            .as_compound()
+            .and_then(|root| root.get("Data"))
+            .and_then(NbtTag::as_compound)
+            .ok_or_else(|| invalid("Data"))?;
+        let int = |name: &str| {
+            data.get(name)
+                .and_then(NbtTag::as_i64)
+                .and_then(|value| i32::try_from(value).ok())
+                .ok_or_else(|| invalid(name))
+        };
+        let long = |name: &str| data.get(name).and_then(NbtTag::as_i64).unwrap_or(0);
+        let optional_int = |name: &str| i32::try_from(long(name)).map_err(|_| invalid(name));
+        let flag = |name: &str| long(name) == 1;
+
+        let mut saved_rules = Vec::new();
+        if let Some(rules) = data.get("GameRules").and_then(NbtTag::as_compound) {
+            for (name, value) in rules.iter() {
+                let value = value.as_str().ok_or_else(|| invalid("GameRules"))?;
+                saved_rules.push((name, value));
+            }
+        }
+        let game_rules = GameRuleRegistry::from_strings(saved_rules);
+
+        let version = data.get("Version").and_then(NbtTag::as_compound);
+        let version_field = |name: &str| version.and_then(|version| version.get(name));
+        let version = LevelVersion {
+            id: version_field("Id")
+                .and_then(NbtTag::as_i64)
+                .and_then(|id| i32::try_from(id).ok())
+                .unwrap_or(DATA_VERSION),
+            name: version_field("Name")
+                .and_then(NbtTag::as_str)
+                .unwrap_or(MINECRAFT_VERSION)
+                .to_string(),
+            snapshot: version_field("Snapshot").and_then(NbtTag::as_i64) == Some(1),
+        };
+
+        Ok(Self {
+            spawn_x: int("SpawnX")?,
+            spawn_y: int("SpawnY")?,
+            spawn_z: int("SpawnZ")?,
+            game_rules,
+            time: long("Time"),
+            day_time: long("DayTime"),
+            raining: flag("raining"),
+            thundering: flag("thundering"),
+            rain_time: optional_int("rainTime")?,
+            thunder_time: optional_int("thunderTime")?,
+            clear_weather_time: optional_int("clearWeatherTime")?,
+            version,
+        })
+    }
+}
+
+/// Load the `level.dat` of a world
+pub fn load(world_dir: &Path) -> Result<LevelData> {
+    LevelData::from_nbt(&read_nbt_file(&world_dir.join(LEVEL_DATA_FILE))?)
+}
+
+/// Save the `level.dat` of a world, creating its directory if needed
+pub fn save(world_dir: &Path, data: &LevelData) -> Result<()> {
+    std::fs::create_dir_all(world_dir)?;
+    let bytes = encode_nbt(&data.to_nbt(), true)?;
+    write_atomically(&world_dir.join(LEVEL_DATA_FILE), &bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::game_rules::DO_DAYLIGHT_CYCLE;
+
+    #[test]
+    fn test_save_and_load() {
+        let world_dir =
+            std::env::temp_dir().join(format!("obsidium-{}-level-data", std::process::id()));
+        let mut data = LevelData::at_spawn(Position::new(8, 100, -8));
+        data.game_rules.set_bool("keepInventory", true);
+        data.time = 123_456;
+        data.day_time = 30_000;
+        data.raining = true;
+        data.rain_time = 4_000;
+
+        save(&world_dir, &data).unwrap();
+        assert_eq!(load(&world_dir).unwrap(), data);
+        std::fs::remove_dir_all(&world_dir).unwrap();
+        assert!(matches!(load(&world_dir), Err(ServerError::Io(_))));
+    }
+
+    #[test]
+    fn test_missing_game_rules_are_defaults() {
+        let game_rules = NbtCompound::new().with("doDaylightCycle", "false");
+        let tag = NbtCompound::new()
+            .with(
+                "Data",
+                NbtCompound::new()
+                    .with("SpawnX", 1)
+                    .with("SpawnY", 70)
+                    .with("SpawnZ", -1)
+                    .with("GameRules", game_rules),
+            )
+            .into();
+
+        let data = LevelData::from_nbt(&tag).unwrap();
+        assert_eq!(data.spawn_position(), Position::new(1, 70, -1));
+        assert_eq!(data.game_rules.len(), GameRuleRegistry::new().len());
+        assert_eq!(data.game_rules.get_bool(DO_DAYLIGHT_CYCLE), Some(false));
+        assert_eq!(data.game_rules.get_int("randomTickSpeed"), Some(3));
+        assert_eq!((data.time, data.day_time), (0, 0));
+        assert!(!data.raining && !data.thundering);
+        assert_eq!(data.version, LevelVersion::default());
+    }
+
+    #[test]
+    fn test_invalid_level_data() {
+        assert!(LevelData::from_nbt(&NbtCompound::new().into()).is_err());
+        let mut tag = LevelData::default().to_nbt();
+        if let NbtTag::Compound(root) = &mut tag {
+            if let Some(NbtTag::Compound(data)) = root.get_mut("Data") {
+                data.insert("GameRules", NbtCompound::new().with("keepInventory", 1));
+            }
+        }
+        assert!(LevelData::from_nbt(&tag).is_err());
+    }
+}
@@ -0,0 +1,45 @@
+//! Persistent storage
+//!
+//! This module saves server state that must outlive a restart, such as the
+//! world's chunks and metadata and the data of players that have left.
+
+pub mod json_list;
+pub mod level_data;
+pub mod player_data;
+pub mod region;
+
+pub use level_data::{LevelData, LevelVersion};
+pub use player_data::{PlayerData, PlayerDataStore};
+pub use region::{ChunkStorage, PaletteNames, RegionFile};
+
+use crate::error::Result;
+use std::path::Path;
+
+/// Replace the contents of a file
+///
+/// The contents are written to a temporary file next to it, which is then
+/// moved over the old one, so a failed save leaves the previous file
+/// intact. The temporary file has `_tmp` added to the name, so
+/// `level.dat` is written through `level.dat_tmp`.
+pub fn write_atomically(path: &Path, contents: &[u8]) -> Result<()> {
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push("_tmp");
+    std::fs::write(&temp_path, contents)?;
+    std::fs::rename(temp_path, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_atomically() {
+        let path = std::env::temp_dir().join(format!("obsidium-{}-atomic.dat", std::process::id()));
+        write_atomically(&path, b"old").unwrap();
+        write_atomically(&path, b"new").unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"new");
+        assert!(!path.with_extension("dat_tmp").exists());
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,233 @@
+//! Player data files
+//!
+//! Every player that has left the server has a gzip-compressed NBT file
+//! named after their UUID, holding where they were, their inventory, health
+//! and game mode. Items are stored by their protocol ID and slots by their
+//! index in the player inventory window.
+
+use super::write_atomically;
+use crate::data::{decode_nbt, encode_nbt};
+use crate::error::{Result, ServerError};
+use crate::protocol::nbt::{NbtCompound, NbtTag};
+use crate::protocol::types::{McUuid, Position, Slot};
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+/// Saved state of a player
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerData {
+    /// Position of the player's feet
+    pub position: (f64, f64, f64),
+    /// Slots of the player inventory window
+    pub inventory: Vec<Slot>,
+    /// Health
+    pub health: f32,
+    /// Game mode ID
+    pub gamemode: u8,
+}
+
+impl Default for PlayerData {
+    fn default() -> Self {
+        Self::at_spawn(Position::new(0, 64, 0))
+    }
+}
+
+impl PlayerData {
+    /// Create the data of a new player standing at the center of the spawn
+    /// block, with an empty inventory, full health and in survival mode
+    pub fn at_spawn(spawn: Position) -> Self {
+        Self {
+            position: (
+                f64::from(spawn.x) + 0.5,
+                f64::from(spawn.y),
+                f64::from(spawn.z) + 0.5,
+            ),
+            inventory: Vec::new(),
+            health: 20.0,
+            gamemode: 0,
+        }
+    }
+
+    /// Convert the data to the compound stored in its file
+    ///
+    /// Empty slots are left out.
+    pub fn to_nbt(&self) -> NbtTag {
+        let (x, y, z) = self.position;
+        let inventory = self
+            .inventory
+            .iter()
+            .enumerate()
+            .filter_map(|(index, slot)| {
+                let item_id = slot.item_id.filter(|_| !slot.is_empty())?;
+                let mut item = NbtCompound::new()
+                    .with("Slot", index as i8)
+                    .with("id", item_id)
+                    .with("count", slot.count as i8);
+                if let Some(nbt) = &slot.nbt {
+                    item.insert("tag", nbt.clone());
+                }
+                Some(item.into())
+            })
+            .collect();
+        NbtCompound::new()
+            .with("Pos", NbtTag::List(vec![x.into(), y.into(), z.into()]))
+            .with("Inventory", NbtTag::List(inventory))
+            .with("Health", self.health)
+            .with("playerGameType", i32::from(self.gamemode))
+            .into()
+    }
+
+    /// Read the data from the compound stored in its file
+    pub fn from_nbt(tag: &NbtTag) -> Result<Self> {
+        let invalid = |field: &str| ServerError::Nbt(format!("Invalid player data {}", field));
+        let data = tag.as_compound().ok_or_else(|| invalid("root"))?;
+
+        let position = match data.get("Pos") {
+            Some(NbtTag::List(coordinates)) => match coordinates.as_slice() {
+                [x, y, z] => x
+                    .as_f64()
+                    .zip(y.as_f64())
+                    .zip(z.as_f64())
+                    .map(|((x, y), z)| (x, y, z)),
+                _ => None,
+            },
+            _ => None,
+        }
+        .ok_or_else(|| invalid("Pos"))?;
+
+        let mut inventory = Vec::new();
+        if let Some(NbtTag::List(items)) = data.get("Inventory") {
+            for item in items {
+                let item = item.as_compound().ok_or_else(|| invalid("Inventory"))?;
+                let field = |name: &str| item.get(name).and_then(NbtTag::as_i64);
+                let (Some(index), Some(item_id), Some(count)) =
+                    (field("Slot"), field("id"), field("count"))
+                else {
+                    return Err(invalid("Inventory"));
+                };
+                let index = usize::try_from(index).map_err(|_| invalid("Inventory"))?;
+                if inventory.len() <= index {
+                    inventory.resize(index + 1, Slot::empty());
+                }
+                let mut slot = Slot::new(item_id as i32, count as u8);
+                slot.nbt = item.get("tag").cloned();
+                inventory[index] = slot;
+            }
+        }
+
+        Ok(Self {
+            position,
+            inventory,
+            health: data
+                .get("Health")
+                .and_then(NbtTag::as_f64)
+                .ok_or_else(|| invalid("Health"))? as f32,
+            gamemode: data
+                .get("playerGameType")
+                .and_then(NbtTag::as_i64)
+                .and_then(|id| u8::try_from(id).ok())
+                .ok_or_else(|| invalid("playerGameType"))?,
+        })
+    }
+}
+
+/// Saves player data to a directory, in one file per player
+#[derive(Debug, Clone)]
+pub struct PlayerDataStore {
+    /// Directory holding the files, created on the first save
+    directory: PathBuf,
+}
+
+impl PlayerDataStore {
+    /// Create a store of the files in a directory
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+
+    /// Path of a player's file
+    fn path(&self, uuid: &McUuid) -> PathBuf {
+        self.directory.join(format!("{}.dat", uuid))
+    }
+
+    /// Load a player's data, or the data of a new player if none was saved
+    pub async fn load(&self, uuid: &McUuid) -> Result<PlayerData> {
+        self.load_or(uuid, PlayerData::default()).await
+    }
+
+    /// Load a player's data, or return `new_player` if none was saved
+    pub async fn load_or(&self, uuid: &McUuid, new_player: PlayerData) -> Result<PlayerData> {
+        match tokio::fs::read(self.path(uuid)).await {
+            Ok(bytes) => PlayerData::from_nbt(&decode_nbt(&bytes)?),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(new_player),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Save a player's data
+    pub async fn save(&self, uuid: &McUuid, data: &PlayerData) -> Result<()> {
+        tokio::fs::create_dir_all(&self.directory).await?;
+        let path = self.path(uuid);
+        let bytes = encode_nbt(&data.to_nbt(), true)?;
+        tokio::task::spawn_blocking(move || write_atomically(&path, &bytes))
+            .await
+            .map_err(std::io::Error::other)?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store(name: &str) -> PlayerDataStore {
+        PlayerDataStore::new(std::env::temp_dir().join(format!(
+            "obsidium-{}-{}",
+            std::process::id(),
+            name
+        )))
+    }
+
+    #[tokio::test]
+    async fn test_save_and_load() {
+        let store = store("player-data");
+        let uuid = McUuid::new_v4();
+        let mut inventory = vec![Slot::empty(); 37];
+        inventory[36] = Slot::new(1, 64);
+        inventory[9] = Slot::new(885, 1).with_nbt(NbtCompound::new().with("id", 5).into());
+        let data = PlayerData {
+            position: (10.5, 70.0, -3.25),
+            inventory,
+            health: 13.5,
+            gamemode: 1,
+        };
+
+        store.save(&uuid, &data).await.unwrap();
+        assert_eq!(store.load(&uuid).await.unwrap(), data);
+        tokio::fs::remove_dir_all(&store.directory).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_new_player() {
+        let store = store("new-player");
+        let uuid = McUuid::new_v4();
+        let data = store.load(&uuid).await.unwrap();
+        assert_eq!(data, PlayerData::default());
+        assert_eq!(data.position, (0.5, 64.0, 0.5));
+        assert_eq!((data.health, data.gamemode), (20.0, 0));
+
+        let spawn = PlayerData::at_spawn(Position::new(-8, 100, 8));
+        let data = store.load_or(&uuid, spawn.clone()).await.unwrap();
+        assert_eq!(data, spawn);
+    }
+
+    #[test]
+    fn test_invalid_data() {
+        let data = PlayerData::default().to_nbt();
+        let mut missing = data.as_compound().unwrap().clone();
+        missing.remove("Health");
+        assert!(PlayerData::from_nbt(&missing.into()).is_err());
+        assert!(PlayerData::from_nbt(&NbtTag::Int(0)).is_err());
+        assert_eq!(PlayerData::from_nbt(&data).unwrap(), PlayerData::default());
+    }
+}
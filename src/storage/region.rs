@@ -0,0 +1,737 @@
+//! Anvil region files
+//!
+//! A region file holds the 32x32 chunks of a region in 4 KiB sectors. The
+//! first two sectors are its header: the location of each chunk, as a
+//! 3-byte sector offset and a 1-byte sector count, followed by the time
+//! each chunk was last saved. A chunk's sectors start with the length of
+//! its data and the compression used, followed by the compressed NBT.
+//!
+//! Palettes name blocks and biomes as vanilla does, see [`PaletteNames`].
+//! A [`ChunkStorage`] reads and writes the chunks of a world's `region`
+//! directory.
+
+use crate::data::{BlockRegistry, GameData, decode_nbt, encode_nbt};
+use crate::error::{Result, ServerError};
+use crate::game::world::chunk::SECTION_COUNT;
+use crate::game::world::{
+    BlockState, ChunkData, ChunkPosition, ChunkSection, LightArray, PalettedContainer,
+};
+use crate::protocol::nbt::{NbtCompound, NbtTag};
+use crate::protocol::packets::play::chunk_data::{SECTION_BIOME_COUNT, SECTION_BLOCK_COUNT};
+use crate::storage::level_data::DATA_VERSION;
+use flate2::Compression;
+use flate2::read::{GzDecoder, ZlibDecoder};
+use flate2::write::ZlibEncoder;
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Size of a sector in bytes
+pub const SECTOR_SIZE: usize = 4096;
+
+/// Number of chunks along each side of a region
+pub const REGION_SIZE: usize = 32;
+
+/// Number of chunks in a region
+const CHUNK_COUNT: usize = REGION_SIZE * REGION_SIZE;
+
+/// Sectors taken by the header
+const HEADER_SECTORS: u32 = 2;
+
+/// Most sectors a chunk may take, as its count is a single byte
+const MAX_CHUNK_SECTORS: usize = 255;
+
+/// Section Y of the lowest section, at the bottom of the world
+const MIN_SECTION_Y: i32 = -4;
+
+/// Registry biome palettes are named from
+const BIOME_REGISTRY: &str = "minecraft:worldgen/biome";
+
+/// Biome unknown biomes are loaded as
+const PLAINS: &str = "minecraft:plains";
+
+/// How the data of a chunk is compressed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ChunkCompression {
+    /// Gzip, which vanilla no longer writes
+    Gzip = 1,
+    /// Zlib, the default
+    Zlib = 2,
+    /// Not compressed
+    None = 3,
+}
+
+impl TryFrom<u8> for ChunkCompression {
+    type Error = ServerError;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            1 => Ok(ChunkCompression::Gzip),
+            2 => Ok(ChunkCompression::Zlib),
+            3 => Ok(ChunkCompression::None),
+            _ => Err(ServerError::Nbt(format!(
+                "Unsupported chunk compression: {}",
+                value
+            ))),
+        }
+    }
+}
+
+/// Path of the region file holding a chunk, in a world's `region`
+/// directory
+pub fn region_path(region_dir: &Path, chunk_x: i32, chunk_z: i32) -> PathBuf {
+    region_dir.join(format!("r.{}.{}.mca", chunk_x >> 5, chunk_z >> 5))
+}
+
+/// An open region file
+#[derive(Debug)]
+pub struct RegionFile {
+    /// The file, opened for reading and writing
+    file: File,
+    /// Sector offset and count of each chunk, 0 for chunks never saved
+    locations: [u32; CHUNK_COUNT],
+    /// When each chunk was last saved, in seconds since the Unix epoch
+    timestamps: [u32; CHUNK_COUNT],
+}
+
+impl RegionFile {
+    /// Open a region file, creating it with an empty header if it does not
+    /// exist
+    ///
+    /// The header is read into memory; chunks are read from the file as
+    /// they are requested.
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        let mut header = vec![0u8; SECTOR_SIZE * HEADER_SECTORS as usize];
+        if file.metadata()?.len() < header.len() as u64 {
+            file.set_len(header.len() as u64)?;
+        }
+        file.read_exact(&mut header)?;
+
+        let entry = |index: usize| {
+            let bytes = &header[index * 4..index * 4 + 4];
+            u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+        };
+        Ok(Self {
+            file,
+            locations: std::array::from_fn(entry),
+            timestamps: std::array::from_fn(|index| entry(CHUNK_COUNT + index)),
+        })
+    }
+
+    /// When a chunk was last saved, in seconds since the Unix epoch, or 0
+    /// if it never was
+    pub fn timestamp(&self, local_x: u8, local_z: u8) -> Result<u32> {
+        Ok(self.timestamps[chunk_index(local_x, local_z)?])
+    }
+
+    /// Read a chunk by its coordinates within the region, or `None` if it
+    /// was never saved
+    pub fn read_chunk(
+        &mut self,
+        local_x: u8,
+        local_z: u8,
+        names: &PaletteNames,
+    ) -> Result<Option<ChunkData>> {
+        let location = self.locations[chunk_index(local_x, local_z)?];
+        if location == 0 {
+            return Ok(None);
+        }
+        let (offset, sectors) = (location >> 8, (location & 0xFF) as usize);
+
+        self.file
+            .seek(SeekFrom::Start(u64::from(offset) * SECTOR_SIZE as u64))?;
+        let mut prefix = [0u8; 5];
+        self.file.read_exact(&mut prefix)?;
+        let length = u32::from_be_bytes([prefix[0], prefix[1], prefix[2], prefix[3]]) as usize;
+        if length == 0 || length + 4 > sectors * SECTOR_SIZE {
+            return Err(ServerError::Nbt(format!(
+                "Invalid length of chunk {}, {}: {}",
+                local_x, local_z, length
+            )));
+        }
+        let mut compressed = vec![0u8; length - 1];
+        self.file.read_exact(&mut compressed)?;
+
+        let mut bytes = Vec::new();
+        match ChunkCompression::try_from(prefix[4])? {
+            ChunkCompression::Gzip => {
+                GzDecoder::new(compressed.as_slice()).read_to_end(&mut bytes)?;
+            }
+            ChunkCompression::Zlib => {
+                ZlibDecoder::new(compressed.as_slice()).read_to_end(&mut bytes)?;
+            }
+            ChunkCompression::None => bytes = compressed,
+        }
+        chunk_from_nbt(&decode_nbt(&bytes)?, names).map(Some)
+    }
+
+    /// Save a chunk by its coordinates within the region
+    ///
+    /// The chunk is written over its old sectors if it still fits in them,
+    /// and otherwise moved to the first free sectors large enough.
+    pub fn write_chunk(
+        &mut self,
+        local_x: u8,
+        local_z: u8,
+        chunk: &ChunkData,
+        names: &PaletteNames,
+    ) -> Result<()> {
+        let index = chunk_index(local_x, local_z)?;
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&encode_nbt(&chunk_to_nbt(chunk, names)?, false)?)?;
+        let compressed = encoder.finish()?;
+
+        let mut data = Vec::with_capacity(compressed.len() + 5);
+        data.extend_from_slice(&(compressed.len() as u32 + 1).to_be_bytes());
+        data.push(ChunkCompression::Zlib as u8);
+        data.extend_from_slice(&compressed);
+        let sectors = data.len().div_ceil(SECTOR_SIZE);
+        if sectors > MAX_CHUNK_SECTORS {
+            return Err(ServerError::Nbt(format!(
+                "Chunk {}, {} is too large to save ({} sectors)",
+                local_x, local_z, sectors
+            )));
+        }
+        data.resize(sectors * SECTOR_SIZE, 0);
+
+        let current = self.locations[index];
+        let offset = if current != 0 && (current & 0xFF) as usize >= sectors {
+            current >> 8
+        } else {
+            self.locations[index] = 0;
+            self.free_sectors(sectors)
+        };
+        self.file
+            .seek(SeekFrom::Start(u64::from(offset) * SECTOR_SIZE as u64))?;
+        self.file.write_all(&data)?;
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs() as u32);
+        self.locations[index] = (offset << 8) | sectors as u32;
+        self.timestamps[index] = timestamp;
+        self.write_header_entry(index, self.locations[index])?;
+        self.write_header_entry(CHUNK_COUNT + index, timestamp)?;
+        self.file.flush()?;
+        Ok(())
+    }
+
+    /// Find the first run of unused sectors of the given length, which may
+    /// extend past the end of the file
+    fn free_sectors(&self, count: usize) -> u32 {
+        let mut used: Vec<(u32, u32)> = self
+            .locations
+            .iter()
+            .filter(|&&location| location != 0)
+            .map(|&location| (location >> 8, location & 0xFF))
+            .collect();
+        used.sort_unstable();
+
+        let mut start = HEADER_SECTORS;
+        for (offset, sectors) in used {
+            if offset >= start + count as u32 {
+                break;
+            }
+            start = start.max(offset + sectors);
+        }
+        start
+    }
+
+    /// Overwrite one 4-byte entry of the header
+    fn write_header_entry(&mut self, index: usize, value: u32) -> Result<()> {
+        self.file.seek(SeekFrom::Start(index as u64 * 4))?;
+        self.file.write_all(&value.to_be_bytes())?;
+        Ok(())
+    }
+}
+
+/// Chunks saved in the region files of a world
+#[derive(Debug, Clone)]
+pub struct ChunkStorage {
+    /// The world's `region` directory
+    directory: PathBuf,
+    /// Names of the blocks and biomes in palettes
+    names: PaletteNames,
+}
+
+impl ChunkStorage {
+    /// Store chunks in the `region` directory of a world
+    pub fn new(world_dir: &Path, names: PaletteNames) -> Self {
+        Self {
+            directory: world_dir.join("region"),
+            names,
+        }
+    }
+
+    /// Read a saved chunk, or `None` if it was never saved
+    pub fn load(&self, position: ChunkPosition) -> Result<Option<ChunkData>> {
+        let path = region_path(&self.directory, position.x, position.z);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let (x, z) = local_coordinates(position.x, position.z);
+        RegionFile::open(&path)?.read_chunk(x, z, &self.names)
+    }
+
+    /// Save chunks, opening each region file once
+    pub fn save<'a>(&self, chunks: impl IntoIterator<Item = &'a ChunkData>) -> Result<()> {
+        let mut regions: BTreeMap<PathBuf, Vec<&ChunkData>> = BTreeMap::new();
+        for chunk in chunks {
+            let path = region_path(&self.directory, chunk.x, chunk.z);
+            regions.entry(path).or_default().push(chunk);
+        }
+        if regions.is_empty() {
+            return Ok(());
+        }
+        std::fs::create_dir_all(&self.directory)?;
+        for (path, chunks) in regions {
+            let mut region = RegionFile::open(&path)?;
+            for chunk in chunks {
+                let (x, z) = local_coordinates(chunk.x, chunk.z);
+                region.write_chunk(x, z, chunk, &self.names)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Coordinates of a chunk within its region
+fn local_coordinates(chunk_x: i32, chunk_z: i32) -> (u8, u8) {
+    let mask = REGION_SIZE as i32 - 1;
+    ((chunk_x & mask) as u8, (chunk_z & mask) as u8)
+}
+
+/// Index of a chunk in the header
+fn chunk_index(local_x: u8, local_z: u8) -> Result<usize> {
+    let (x, z) = (usize::from(local_x), usize::from(local_z));
+    if x >= REGION_SIZE || z >= REGION_SIZE {
+        return Err(ServerError::Nbt(format!(
+            "Chunk {}, {} is outside the region",
+            local_x, local_z
+        )));
+    }
+    Ok(x + z * REGION_SIZE)
+}
+
+/// Names saved in chunk palettes in place of protocol IDs
+///
+/// Blocks are named through the bundled block data, which only knows the
+/// default state of each block. Saving any other state fails, and saved
+/// states with other properties are loaded in their block's default state.
+#[derive(Debug, Clone)]
+pub struct PaletteNames {
+    /// Blocks and the properties of their default states
+    blocks: BlockRegistry,
+    /// Biome identifiers, in protocol ID order
+    biomes: Vec<String>,
+}
+
+impl PaletteNames {
+    /// Take the names of blocks and biomes from the server's game data
+    pub fn new(data: &GameData) -> Self {
+        let biomes = data
+            .get_registry_entries(BIOME_REGISTRY)
+            .unwrap_or_default()
+            .iter()
+            .map(|entry| entry.identifier.to_string())
+            .collect();
+        Self {
+            blocks: data.blocks().clone(),
+            biomes,
+        }
+    }
+
+    /// Build the palette entry of a block state: its block's name and the
+    /// state's properties, if it has any
+    fn block_to_nbt(&self, state: u16) -> Result<NbtTag> {
+        let name = self
+            .blocks
+            .block_of_default_state(i32::from(state))
+            .ok_or_else(|| ServerError::Nbt(format!("Block state {} has no name", state)))?;
+        let entry = NbtCompound::new().with("Name", name);
+        let Some(properties) = self.blocks.default_properties_of(name) else {
+            return Ok(entry.into());
+        };
+        let properties = properties
+            .iter()
+            .fold(NbtCompound::new(), |compound, (key, value)| {
+                compound.with(key.as_str(), value.as_str())
+            });
+        Ok(entry.with("Properties", properties).into())
+    }
+
+    /// Find the block state of a palette entry
+    ///
+    /// Unknown blocks are loaded as air, as vanilla does with blocks that no
+    /// longer exist.
+    fn block_from_nbt(&self, tag: &NbtTag) -> Result<u16> {
+        let name = tag
+            .as_compound()
+            .and_then(|entry| entry.get("Name"))
+            .and_then(NbtTag::as_str)
+            .ok_or_else(|| ServerError::Nbt("Invalid block palette entry".to_string()))?;
+        let Some(state) = self.blocks.default_state_of(name) else {
+            tracing::debug!("Loading unknown block {} as air", name);
+            return Ok(BlockState::AIR.0);
+        };
+        u16::try_from(state).map_err(|_| ServerError::Nbt(format!("Invalid block state {}", state)))
+    }
+
+    /// Get the name of a biome
+    fn biome_to_nbt(&self, biome: u8) -> Result<NbtTag> {
+        self.biomes
+            .get(usize::from(biome))
+            .map(|name| NbtTag::String(name.clone()))
+            .ok_or_else(|| ServerError::Nbt(format!("Biome {} has no name", biome)))
+    }
+
+    /// Find the biome of a palette entry
+    ///
+    /// Unknown biomes are loaded as plains, as vanilla does.
+    fn biome_from_nbt(&self, tag: &NbtTag) -> Result<u8> {
+        let name = tag
+            .as_str()
+            .ok_or_else(|| ServerError::Nbt("Invalid biome palette entry".to_string()))?;
+        let index = self
+            .biomes
+            .iter()
+            .position(|biome| biome == name)
+            .or_else(|| {
+                tracing::debug!("Loading unknown biome {} as plains", name);
+                self.biomes.iter().position(|biome| biome == PLAINS)
+            })
+            .unwrap_or_default();
+        u8::try_from(index).map_err(|_| ServerError::Nbt(format!("Invalid biome {}", name)))
+    }
+}
+
+/// Convert a chunk to the compound saved in its region file
+pub fn chunk_to_nbt(chunk: &ChunkData, names: &PaletteNames) -> Result<NbtTag> {
+    let sections = chunk
+        .sections
+        .iter()
+        .enumerate()
+        .map(|(index, section)| {
+            let (blocks, data) = section.block_states.to_saved();
+            let blocks = blocks
+                .into_iter()
+                .map(|state| names.block_to_nbt(state))
+                .collect::<Result<_>>()?;
+            let (biomes, biome_data) = section.biomes.to_saved();
+            let biomes = biomes
+                .into_iter()
+                .map(|biome| names.biome_to_nbt(biome))
+                .collect::<Result<_>>()?;
+            Ok(NbtCompound::new()
+                .with("Y", (index as i32 + MIN_SECTION_Y) as i8)
+                .with("block_states", container_to_nbt(blocks, data))
+                .with("biomes", container_to_nbt(biomes, biome_data))
+                .with("BlockLight", light_to_nbt(&section.block_light))
+                .with("SkyLight", light_to_nbt(&section.sky_light))
+                .into())
+        })
+        .collect::<Result<_>>()?;
+    Ok(NbtCompound::new()
+        .with("DataVersion", DATA_VERSION)
+        .with("xPos", chunk.x)
+        .with("zPos", chunk.z)
+        .with("yPos", MIN_SECTION_Y)
+        .with("Status", "minecraft:full")
+        .with("sections", NbtTag::List(sections))
+        .into())
+}
+
+/// Read a chunk from the compound saved in its region file
+///
+/// Sections missing from the compound are left empty.
+pub fn chunk_from_nbt(tag: &NbtTag, names: &PaletteNames) -> Result<ChunkData> {
+    let invalid = |field: &str| ServerError::Nbt(format!("Invalid chunk {}", field));
+    let data = tag.as_compound().ok_or_else(|| invalid("root"))?;
+    let coordinate = |name: &str| {
+        data.get(name)
+            .and_then(NbtTag::as_i64)
+            .and_then(|value| i32::try_from(value).ok())
+            .ok_or_else(|| invalid(name))
+    };
+
+    let mut chunk = ChunkData::new(coordinate("xPos")?, coordinate("zPos")?, 0);
+    let Some(NbtTag::List(sections)) = data.get("sections") else {
+        return Err(invalid("sections"));
+    };
+    for section in sections {
+        let section = section.as_compound().ok_or_else(|| invalid("section"))?;
+        let index = section
+            .get("Y")
+            .and_then(NbtTag::as_i64)
+            .map(|y| y - i64::from(MIN_SECTION_Y))
+            .and_then(|index| usize::try_from(index).ok())
+            .filter(|&index| index < SECTION_COUNT)
+            .ok_or_else(|| invalid("section Y"))?;
+
+        let (blocks, data) = container_from_nbt(section.get("block_states"))
+            .ok_or_else(|| invalid("block_states"))?;
+        let blocks = blocks
+            .iter()
+            .map(|entry| names.block_from_nbt(entry))
+            .collect::<Result<Vec<_>>>()?;
+        let block_states = PalettedContainer::from_saved(SECTION_BLOCK_COUNT, &blocks, &data)?;
+        let (biomes, data) =
+            container_from_nbt(section.get("biomes")).ok_or_else(|| invalid("biomes"))?;
+        let biomes = biomes
+            .iter()
+            .map(|entry| names.biome_from_nbt(entry))
+            .collect::<Result<Vec<_>>>()?;
+        let biomes = PalettedContainer::from_saved(SECTION_BIOME_COUNT, &biomes, &data)?;
+
+        chunk.sections[index] = ChunkSection::from_parts(
+            block_states,
+            biomes,
+            light_from_nbt(section.get("BlockLight")).unwrap_or_else(LightArray::dark),
+            light_from_nbt(section.get("SkyLight")).unwrap_or_else(LightArray::full),
+        );
+    }
+    Ok(chunk)
+}
+
+/// Build the compound of a saved paletted container
+fn container_to_nbt(palette: Vec<NbtTag>, data: Vec<i64>) -> NbtCompound {
+    let container = NbtCompound::new().with("palette", NbtTag::List(palette));
+    if data.is_empty() {
+        container
+    } else {
+        container.with("data", NbtTag::LongArray(data))
+    }
+}
+
+/// Read the palette and data of a saved paletted container
+fn container_from_nbt(tag: Option<&NbtTag>) -> Option<(&[NbtTag], Vec<i64>)> {
+    let container = tag?.as_compound()?;
+    let Some(NbtTag::List(palette)) = container.get("palette") else {
+        return None;
+    };
+    let data = match container.get("data") {
+        Some(NbtTag::LongArray(data)) => data.clone(),
+        Some(_) => return None,
+        None => Vec::new(),
+    };
+    Some((palette, data))
+}
+
+/// Convert light levels to a byte array tag
+fn light_to_nbt(light: &LightArray) -> NbtTag {
+    NbtTag::ByteArray(light.as_bytes().iter().map(|&byte| byte as i8).collect())
+}
+
+/// Read light levels from a byte array tag
+fn light_from_nbt(tag: Option<&NbtTag>) -> Option<LightArray> {
+    match tag? {
+        NbtTag::ByteArray(bytes) => {
+            LightArray::from_bytes(bytes.iter().map(|&byte| byte as u8).collect())
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Default states of bundled blocks: stone, granite, grass, dirt,
+    /// cobblestone, bedrock and chest
+    const STATES: [u16; 7] = [1, 2, 9, 10, 14, 85, 3010];
+
+    fn temp_region(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("obsidium-{}-{}.mca", std::process::id(), name))
+    }
+
+    fn names() -> PaletteNames {
+        PaletteNames::new(&GameData::load().unwrap())
+    }
+
+    /// A chunk with blocks scattered over its lowest sections, so it
+    /// compresses badly
+    fn test_chunk(x: i32, z: i32, blocks: usize) -> ChunkData {
+        let mut chunk = ChunkData::new(x, z, 0);
+        let mut seed = 1u64;
+        for index in 0..blocks {
+            seed = seed
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            let state = STATES[(seed >> 33) as usize % STATES.len()];
+            chunk.set_block(
+                index % 16,
+                index / 256,
+                (index / 16) % 16,
+                BlockState(state),
+            );
+        }
+        chunk.sections[4].block_light.set(0, 15);
+        chunk
+    }
+
+    /// Assert two chunks hold the same blocks, biomes and light, however
+    /// their palettes are ordered
+    fn assert_same_chunk(chunk: Option<ChunkData>, expected: &ChunkData) {
+        let chunk = chunk.unwrap_or_else(|| unreachable!("Chunk was not saved"));
+        assert_eq!((chunk.x, chunk.z), (expected.x, expected.z));
+        for (section, expected) in chunk.sections.iter().zip(&expected.sections) {
+            assert_eq!(
+                section.block_states.values(),
+                expected.block_states.values()
+            );
+            assert_eq!(section.biomes.values(), expected.biomes.values());
+            assert_eq!(section.block_light, expected.block_light);
+            assert_eq!(section.sky_light, expected.sky_light);
+            assert_eq!(section.block_count(), expected.block_count());
+        }
+    }
+
+    #[test]
+    fn test_chunk_nbt_roundtrip() {
+        let names = names();
+        let chunk = test_chunk(-3, 7, 5000);
+        let saved = chunk_from_nbt(&chunk_to_nbt(&chunk, &names).unwrap(), &names).unwrap();
+        assert_eq!(saved.sections[0].block_count(), 4096);
+        assert_same_chunk(Some(saved), &chunk);
+        assert!(chunk_from_nbt(&NbtCompound::new().into(), &names).is_err());
+
+        // States without a name cannot be saved
+        let mut unnamed = ChunkData::new(0, 0, 0);
+        unnamed.set_block(0, 0, 0, BlockState(8));
+        assert!(chunk_to_nbt(&unnamed, &names).is_err());
+    }
+
+    #[test]
+    fn test_vanilla_palettes() {
+        let names = names();
+        let mut chunk = ChunkData::new(0, 0, 0);
+        chunk.set_block(0, 0, 0, BlockState(9));
+        let tag = chunk_to_nbt(&chunk, &names).unwrap();
+        let section = |tag: &NbtTag| match tag.as_compound().unwrap().get("sections") {
+            Some(NbtTag::List(sections)) => sections[0].as_compound().unwrap().clone(),
+            _ => unreachable!("Chunk has no sections"),
+        };
+        let palette = |section: &NbtCompound, container: &str| match section
+            .get(container)
+            .and_then(NbtTag::as_compound)
+        {
+            Some(container) => match container.get("palette") {
+                Some(NbtTag::List(palette)) => palette.clone(),
+                _ => unreachable!("Container has no palette"),
+            },
+            None => unreachable!("Section has no {}", container),
+        };
+
+        let blocks = palette(&section(&tag), "block_states");
+        let air = NbtTag::from(NbtCompound::new().with("Name", "minecraft:air"));
+        let grass = NbtTag::from(
+            NbtCompound::new()
+                .with("Name", "minecraft:grass_block")
+                .with("Properties", NbtCompound::new().with("snowy", "false")),
+        );
+        assert_eq!(blocks, [grass, air]);
+        let biomes = palette(&section(&tag), "biomes");
+        assert_eq!(biomes, [NbtTag::String(names.biomes[0].clone())]);
+
+        // Unknown blocks load as air, and unknown biomes as plains
+        let saved = NbtCompound::new()
+            .with("xPos", 0)
+            .with("zPos", 0)
+            .with(
+                "sections",
+                NbtTag::List(vec![
+                    NbtCompound::new()
+                        .with("Y", -4i8)
+                        .with(
+                            "block_states",
+                            NbtCompound::new().with(
+                                "palette",
+                                NbtTag::List(vec![
+                                    NbtCompound::new().with("Name", "minecraft:unknown").into(),
+                                ]),
+                            ),
+                        )
+                        .with(
+                            "biomes",
+                            NbtCompound::new().with(
+                                "palette",
+                                NbtTag::List(vec![NbtTag::String("minecraft:unknown".into())]),
+                            ),
+                        )
+                        .into(),
+                ]),
+            )
+            .into();
+        let loaded = chunk_from_nbt(&saved, &names).unwrap();
+        assert_eq!(loaded.get_block(0, 0, 0), Some(BlockState::AIR));
+        let plains = names.biomes.iter().position(|biome| biome == PLAINS);
+        assert_eq!(
+            loaded.sections[0].biomes.values()[0],
+            plains.unwrap_or_default() as u8
+        );
+    }
+
+    #[test]
+    fn test_region_file() {
+        let names = names();
+        let path = temp_region("region");
+        let small = test_chunk(0, 0, 10);
+        let other = test_chunk(1, 0, 10);
+        {
+            let mut region = RegionFile::open(&path).unwrap();
+            assert_eq!(region.read_chunk(0, 0, &names).unwrap(), None);
+            assert_eq!(region.timestamp(0, 0).unwrap(), 0);
+            region.write_chunk(0, 0, &small, &names).unwrap();
+            region.write_chunk(1, 0, &other, &names).unwrap();
+            assert!(region.write_chunk(32, 0, &small, &names).is_err());
+        }
+
+        // Growing the first chunk moves it past the second
+        let large = test_chunk(0, 0, 98_304);
+        let mut region = RegionFile::open(&path).unwrap();
+        assert_same_chunk(region.read_chunk(0, 0, &names).unwrap(), &small);
+        assert!(region.timestamp(0, 0).unwrap() > 0);
+        region.write_chunk(0, 0, &large, &names).unwrap();
+        assert!(region.locations[0] >> 8 > region.locations[1] >> 8);
+        assert_eq!(region.free_sectors(1), HEADER_SECTORS);
+
+        let mut region = RegionFile::open(&path).unwrap();
+        assert_same_chunk(region.read_chunk(0, 0, &names).unwrap(), &large);
+        assert_same_chunk(region.read_chunk(1, 0, &names).unwrap(), &other);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_chunk_storage() {
+        let directory =
+            std::env::temp_dir().join(format!("obsidium-{}-chunk-storage", std::process::id()));
+        let storage = ChunkStorage::new(&directory, names());
+        assert_eq!(storage.load(ChunkPosition::new(0, 0)).unwrap(), None);
+
+        let chunks = [test_chunk(0, 0, 10), test_chunk(-1, 40, 10)];
+        storage.save(&chunks).unwrap();
+        assert!(directory.join("region").join("r.-1.1.mca").exists());
+        assert_same_chunk(storage.load(ChunkPosition::new(0, 0)).unwrap(), &chunks[0]);
+        assert_same_chunk(
+            storage.load(ChunkPosition::new(-1, 40)).unwrap(),
+            &chunks[1],
+        );
+        assert_eq!(storage.load(ChunkPosition::new(1, 0)).unwrap(), None);
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_region_path() {
+        let path = region_path(Path::new("region"), -1, 33);
+        assert_eq!(path, Path::new("region").join("r.-1.1.mca"));
+    }
+}